@@ -0,0 +1,281 @@
+//! Pseudonymizing records for sharing (`--anonymize`).
+//!
+//! An anonymization profile names the fields of a record to replace and how.
+//! Each value maps to the same pseudonym everywhere in an export, so records
+//! can still be joined and grouped, while emails and IP addresses keep their
+//! shape for tools that parse them.
+//!
+//! ```toml
+//! # Optional: fix the salt to keep pseudonyms stable across runs
+//! salt = "vendor-2024"
+//!
+//! [fields]
+//! "attributes.host" = "hash"
+//! "attributes.attributes.usr.email" = "email"
+//! "attributes.attributes.network.client.ip" = "ip"
+//! ```
+//!
+//! Field paths are dotted; a segment may also match a key that itself
+//! contains dots (such as a flattened `usr.email` attribute).
+
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::hash::{BuildHasher, DefaultHasher, Hash, Hasher, RandomState};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::Path;
+
+use crate::error::AppError;
+
+/// How a field's values are replaced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldKind {
+    /// A hex digest of the value
+    Hash,
+
+    /// An address at a reserved `.example` domain; the same domain maps to
+    /// the same pseudonymous domain
+    Email,
+
+    /// An address in a private range of the same family: 10.0.0.0/8 or fd00::/8
+    Ip,
+}
+
+/// A parsed anonymization profile.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AnonymizeProfile {
+    /// Salt mixed into every pseudonym; a random salt is used per run when unset
+    pub salt: Option<String>,
+
+    /// Fields to replace, keyed by dotted path
+    #[serde(default)]
+    pub fields: BTreeMap<String, FieldKind>,
+}
+
+impl AnonymizeProfile {
+    /// Loads a profile from a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Config` if the file cannot be read or parsed, or
+    /// names no fields.
+    pub fn load(path: &Path) -> Result<Self, AppError> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| AppError::Config(format!("Failed to read {}: {}", path.display(), e)))?;
+        let profile: Self = toml::from_str(&contents)
+            .map_err(|e| AppError::Config(format!("Invalid {}: {}", path.display(), e)))?;
+        if profile.fields.is_empty() {
+            return Err(AppError::Config(format!(
+                "{} has no [fields] to anonymize",
+                path.display()
+            )));
+        }
+        Ok(profile)
+    }
+}
+
+/// Source of the salt pseudonyms are hashed with.
+#[derive(Debug)]
+enum Salt {
+    Random(RandomState),
+    Fixed(String),
+}
+
+/// Replaces a profile's fields in records with consistent pseudonyms.
+#[derive(Debug)]
+pub struct Anonymizer {
+    fields: Vec<(Vec<String>, FieldKind)>,
+    salt: Salt,
+}
+
+impl Anonymizer {
+    /// Creates an anonymizer for a profile, drawing a random salt unless the
+    /// profile fixes one.
+    pub fn new(profile: AnonymizeProfile) -> Self {
+        let salt = match profile.salt {
+            Some(salt) => Salt::Fixed(salt),
+            None => Salt::Random(RandomState::new()),
+        };
+        let fields = profile
+            .fields
+            .into_iter()
+            .map(|(path, kind)| (path.split('.').map(String::from).collect(), kind))
+            .collect();
+        Self { fields, salt }
+    }
+
+    /// Replaces every configured field of a record in place.
+    ///
+    /// Fields missing from the record are skipped. Arrays and objects at a
+    /// field's path have each of their scalar values replaced.
+    pub fn apply(&self, record: &mut Value) {
+        for (path, kind) in &self.fields {
+            let segments: Vec<&str> = path.iter().map(String::as_str).collect();
+            for value in lookup(record, &segments) {
+                self.replace(value, *kind);
+            }
+        }
+    }
+
+    fn replace(&self, value: &mut Value, kind: FieldKind) {
+        match value {
+            Value::Null => {}
+            Value::Array(items) => items.iter_mut().for_each(|v| self.replace(v, kind)),
+            Value::Object(map) => map.values_mut().for_each(|v| self.replace(v, kind)),
+            Value::String(text) => *text = self.pseudonym(text, kind),
+            scalar => *scalar = Value::String(self.pseudonym(&scalar.to_string(), kind)),
+        }
+    }
+
+    fn pseudonym(&self, text: &str, kind: FieldKind) -> String {
+        match kind {
+            FieldKind::Hash => format!("{:016x}", self.hash(text)),
+            FieldKind::Email => match text.rsplit_once('@') {
+                Some((_, domain)) => format!(
+                    "u{:08x}@d{:06x}.example",
+                    self.hash(text) as u32,
+                    self.hash(&domain.to_ascii_lowercase()) & 0xff_ffff
+                ),
+                None => self.pseudonym(text, FieldKind::Hash),
+            },
+            FieldKind::Ip => match text.parse::<IpAddr>() {
+                Ok(IpAddr::V4(_)) => {
+                    let [_, a, b, c, ..] = self.hash(text).to_be_bytes();
+                    Ipv4Addr::new(10, a, b, c).to_string()
+                }
+                Ok(IpAddr::V6(_)) => {
+                    let high = u128::from(self.hash(text)) << 64;
+                    let low = u128::from(self.hash(&format!("{}/low", text)));
+                    let bits = (high | low) & !(0xff << 120) | (0xfd << 120);
+                    Ipv6Addr::from(bits).to_string()
+                }
+                Err(_) => self.pseudonym(text, FieldKind::Hash),
+            },
+        }
+    }
+
+    fn hash(&self, text: &str) -> u64 {
+        match &self.salt {
+            Salt::Random(state) => state.hash_one(text),
+            Salt::Fixed(salt) => {
+                let mut hasher = DefaultHasher::new();
+                salt.hash(&mut hasher);
+                text.hash(&mut hasher);
+                hasher.finish()
+            }
+        }
+    }
+}
+
+/// Finds the values at a dotted path, letting one segment match a key that
+/// contains dots.
+fn lookup<'a>(value: &'a mut Value, path: &[&str]) -> Vec<&'a mut Value> {
+    if path.is_empty() {
+        return vec![value];
+    }
+    let Value::Object(map) = value else {
+        return Vec::new();
+    };
+    let mut found = Vec::new();
+    for (key, child) in map.iter_mut() {
+        let matched = (1..=path.len()).find(|&n| key.as_str() == path[..n].join("."));
+        if let Some(n) = matched {
+            found.extend(lookup(child, &path[n..]));
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn anonymizer(fields: &[(&str, FieldKind)]) -> Anonymizer {
+        Anonymizer::new(AnonymizeProfile {
+            salt: None,
+            fields: fields
+                .iter()
+                .map(|(path, kind)| (path.to_string(), *kind))
+                .collect(),
+        })
+    }
+
+    #[test]
+    fn test_apply_is_consistent() {
+        let anonymizer = anonymizer(&[("host", FieldKind::Hash), ("tags", FieldKind::Hash)]);
+        let mut first = json!({"host": "web-1", "tags": ["web-1", "web-2"], "service": "api"});
+        let mut second = json!({"host": "web-1"});
+        anonymizer.apply(&mut first);
+        anonymizer.apply(&mut second);
+
+        assert_ne!(first["host"], "web-1");
+        assert_eq!(first["host"], second["host"]);
+        assert_eq!(first["tags"][0], first["host"]);
+        assert_ne!(first["tags"][1], first["host"]);
+        assert_eq!(first["service"], "api");
+    }
+
+    #[test]
+    fn test_apply_preserves_formats() {
+        let anonymizer = anonymizer(&[
+            ("usr.email", FieldKind::Email),
+            ("client.ip", FieldKind::Ip),
+            ("client.ip6", FieldKind::Ip),
+        ]);
+        let mut a = json!({
+            "usr": {"email": "jane@acme.com"},
+            "client": {"ip": "203.0.113.7", "ip6": "2001:db8::1"}
+        });
+        let mut b = json!({"usr.email": "john@ACME.com"});
+        anonymizer.apply(&mut a);
+        anonymizer.apply(&mut b);
+
+        let email_a = a["usr"]["email"].as_str().unwrap();
+        let email_b = b["usr.email"].as_str().unwrap();
+        assert!(email_a.ends_with(".example"));
+        assert_ne!(email_a, email_b);
+        assert_eq!(
+            email_a.split_once('@').unwrap().1,
+            email_b.split_once('@').unwrap().1
+        );
+
+        let ip: Ipv4Addr = a["client"]["ip"].as_str().unwrap().parse().unwrap();
+        assert_eq!(ip.octets()[0], 10);
+        let ip6: Ipv6Addr = a["client"]["ip6"].as_str().unwrap().parse().unwrap();
+        assert_eq!(ip6.octets()[0], 0xfd);
+    }
+
+    #[test]
+    fn test_fixed_salt_is_stable() {
+        let profile = |salt: &str| AnonymizeProfile {
+            salt: Some(salt.to_string()),
+            fields: BTreeMap::from([("id".to_string(), FieldKind::Hash)]),
+        };
+        let run = |anonymizer: Anonymizer| {
+            let mut record = json!({"id": 42});
+            anonymizer.apply(&mut record);
+            record
+        };
+
+        assert_eq!(
+            run(Anonymizer::new(profile("a"))),
+            run(Anonymizer::new(profile("a")))
+        );
+        assert_ne!(
+            run(Anonymizer::new(profile("a"))),
+            run(Anonymizer::new(profile("b")))
+        );
+    }
+
+    #[test]
+    fn test_profile_from_toml() {
+        let profile: AnonymizeProfile =
+            toml::from_str("[fields]\n\"attributes.host\" = \"hash\"\nip = \"ip\"").unwrap();
+        assert_eq!(profile.salt, None);
+        assert_eq!(profile.fields.get("ip"), Some(&FieldKind::Ip));
+        assert!(toml::from_str::<AnonymizeProfile>("[fields]\nhost = \"mask\"").is_err());
+    }
+}
@@ -0,0 +1,95 @@
+//! Audit log of write requests (`audit.ndjson` in the state dir).
+//!
+//! Every request `ddog api post` sends is appended as one line, with the
+//! endpoint, outcome, and the command line that sent it, so changes made
+//! through the tool can be traced afterwards. Dry runs are not recorded.
+
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::config::state_dir;
+use crate::error::AppError;
+
+/// Name of the audit log in the state directory.
+pub const AUDIT_FILE: &str = "audit.ndjson";
+
+/// One sent request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// When the request was sent (RFC3339, UTC)
+    pub timestamp: String,
+
+    /// HTTP method
+    pub method: String,
+
+    /// Full request URL, without query parameters
+    pub url: String,
+
+    /// Whether the request succeeded
+    pub ok: bool,
+
+    /// Error message for a failed request
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+
+    /// Command-line arguments, without the program name
+    pub args: Vec<String>,
+}
+
+/// Returns the path of the audit log (`audit.ndjson` in [`state_dir`]).
+pub fn audit_path() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join(AUDIT_FILE))
+}
+
+/// Appends an entry, creating the file and its directory if needed.
+///
+/// Unlike the query history, the audit log is never trimmed.
+///
+/// # Errors
+///
+/// Returns `AppError::Config` if the file cannot be written.
+pub fn append(path: &Path, entry: &AuditEntry) -> Result<(), AppError> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| AppError::Config(format!("Failed to create {}: {}", dir.display(), e)))?;
+    }
+    let mut line = serde_json::to_string(entry)?;
+    line.push('\n');
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(line.as_bytes()))
+        .map_err(|e| AppError::Config(format!("Failed to write {}: {}", path.display(), e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append() {
+        let dir = std::env::temp_dir().join(format!("ddog-audit-{}", std::process::id()));
+        let path = dir.join(AUDIT_FILE);
+        let entry = AuditEntry {
+            timestamp: "2024-01-15T10:00:00Z".to_string(),
+            method: "POST".to_string(),
+            url: "https://api.datadoghq.com/api/v1/monitor".to_string(),
+            ok: true,
+            error: None,
+            args: vec!["api".to_string(), "post".to_string()],
+        };
+
+        append(&path, &entry).unwrap();
+        append(&path, &entry).unwrap();
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<AuditEntry> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines, vec![entry.clone(), entry]);
+        assert!(!contents.contains("error"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
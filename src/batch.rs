@@ -0,0 +1,158 @@
+//! Batch query input (`--batch`).
+//!
+//! A batch file lists one search per line, either as a bare query or as an
+//! NDJSON job spec that can also set its own time range and id:
+//!
+//! ```text
+//! service:api status:error
+//! {"id": "tenant-a", "query": "@tenant:a status:error", "from": "now-1d"}
+//! ```
+//!
+//! Blank lines and lines starting with `#` are skipped. Jobs without an id
+//! are identified by their line number.
+
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::io::Read;
+use std::path::Path;
+
+use crate::error::AppError;
+
+/// One search from a batch file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchJob {
+    /// Id tagged onto every record the search returns
+    pub id: String,
+
+    /// Search query
+    pub query: String,
+
+    /// Start time, overriding `--from`/`--last`
+    pub from: Option<String>,
+
+    /// End time, overriding `--to`
+    pub to: Option<String>,
+}
+
+/// An NDJSON job spec line.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JobSpec {
+    id: Option<String>,
+    query: String,
+    from: Option<String>,
+    to: Option<String>,
+}
+
+/// Parses a batch file's contents into jobs, in order.
+///
+/// # Errors
+///
+/// Returns `AppError::InvalidQuery` if a job spec is malformed or an id is
+/// used twice.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::batch::parse_jobs;
+///
+/// let jobs = parse_jobs("status:error\n{\"id\": \"a\", \"query\": \"@tenant:a\"}\n").unwrap();
+/// assert_eq!(jobs[0].id, "1");
+/// assert_eq!(jobs[1].id, "a");
+/// assert_eq!(jobs[1].query, "@tenant:a");
+/// ```
+pub fn parse_jobs(input: &str) -> Result<Vec<BatchJob>, AppError> {
+    let mut jobs = Vec::new();
+    let mut ids = HashSet::new();
+
+    for (index, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line_number = (index + 1).to_string();
+        let job = if line.starts_with('{') {
+            let spec: JobSpec = serde_json::from_str(line).map_err(|e| {
+                AppError::InvalidQuery(format!("Invalid batch job on line {}: {}", line_number, e))
+            })?;
+            BatchJob {
+                id: spec.id.unwrap_or(line_number),
+                query: spec.query,
+                from: spec.from,
+                to: spec.to,
+            }
+        } else {
+            BatchJob {
+                id: line_number,
+                query: line.to_string(),
+                from: None,
+                to: None,
+            }
+        };
+
+        if !ids.insert(job.id.clone()) {
+            return Err(AppError::InvalidQuery(format!(
+                "Duplicate batch job id '{}'",
+                job.id
+            )));
+        }
+        jobs.push(job);
+    }
+    Ok(jobs)
+}
+
+/// Reads and parses batch jobs from a file, or from stdin when `path` is `-`.
+///
+/// # Errors
+///
+/// Returns `AppError::Io` if the input cannot be read, and the errors of
+/// [`parse_jobs`] otherwise.
+pub fn read_jobs(path: &Path) -> Result<Vec<BatchJob>, AppError> {
+    let input = if path == Path::new("-") {
+        let mut input = String::new();
+        std::io::stdin().read_to_string(&mut input)?;
+        input
+    } else {
+        std::fs::read_to_string(path)?
+    };
+    parse_jobs(&input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_jobs() {
+        let input = "# nightly report\nservice:api status:error\n\n{\"id\": \"tenant-a\", \"query\": \"@tenant:a\", \"from\": \"now-1d\", \"to\": \"now-1h\"}\n";
+        let jobs = parse_jobs(input).unwrap();
+        assert_eq!(
+            jobs,
+            vec![
+                BatchJob {
+                    id: "2".to_string(),
+                    query: "service:api status:error".to_string(),
+                    from: None,
+                    to: None,
+                },
+                BatchJob {
+                    id: "tenant-a".to_string(),
+                    query: "@tenant:a".to_string(),
+                    from: Some("now-1d".to_string()),
+                    to: Some("now-1h".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_jobs_errors() {
+        let err = parse_jobs("status:error\n{\"querry\": \"x\"}").unwrap_err();
+        assert!(err.to_string().contains("line 2"));
+
+        let err =
+            parse_jobs("{\"id\": \"a\", \"query\": \"x\"}\n{\"id\": \"a\", \"query\": \"y\"}")
+                .unwrap_err();
+        assert!(matches!(err, AppError::InvalidQuery(_)));
+    }
+}
@@ -0,0 +1,226 @@
+//! Search benchmarks (`ddog bench`).
+//!
+//! A benchmark runs the same search several times end to end, timing each
+//! run with [`measure`]: how long until its first record and its last, how
+//! long each page took to fetch and parse, and how many records it read per
+//! second. [`summarize`] reduces the runs to distributions, for comparing
+//! sites, indexes, and storage tiers, or one build of ddog with another.
+
+use futures_util::{Stream, StreamExt};
+use serde::Serialize;
+use std::time::Instant;
+
+use crate::error::AppError;
+use crate::stats::percentile;
+
+/// Timings of one run of a search.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Iteration {
+    /// Records read
+    pub records: u64,
+
+    /// Time until the first record, in milliseconds (`None` if none matched)
+    pub first_record_ms: Option<f64>,
+
+    /// Time to fetch and parse each page, in milliseconds
+    pub page_ms: Vec<f64>,
+
+    /// Time to read every record, in milliseconds
+    pub total_ms: f64,
+}
+
+impl Iteration {
+    /// Records read per second.
+    pub fn records_per_sec(&self) -> f64 {
+        if self.total_ms > 0.0 {
+            self.records as f64 * 1000.0 / self.total_ms
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Reads a search's results to the end, timing it.
+///
+/// Records are counted and dropped. A page ends after every `page_size`
+/// records, and at the end of the results; its time runs from the end of
+/// the previous page.
+///
+/// # Errors
+///
+/// Returns the first error of the search.
+pub async fn measure<T, S>(results: S, page_size: usize) -> Result<Iteration, AppError>
+where
+    S: Stream<Item = Result<T, AppError>>,
+{
+    let mut results = std::pin::pin!(results);
+    let started = Instant::now();
+    let elapsed_ms = || started.elapsed().as_secs_f64() * 1000.0;
+    let mut iteration = Iteration {
+        records: 0,
+        first_record_ms: None,
+        page_ms: Vec::new(),
+        total_ms: 0.0,
+    };
+    let mut page_start = 0.0;
+    while let Some(result) = results.next().await {
+        result?;
+        iteration.records += 1;
+        if iteration.first_record_ms.is_none() {
+            iteration.first_record_ms = Some(elapsed_ms());
+        }
+        if page_size > 0 && iteration.records.is_multiple_of(page_size as u64) {
+            let now = elapsed_ms();
+            iteration.page_ms.push(now - page_start);
+            page_start = now;
+        }
+    }
+    iteration.total_ms = elapsed_ms();
+    // A short last page, or the one request of a search matching nothing
+    if page_size == 0
+        || iteration.records == 0
+        || !iteration.records.is_multiple_of(page_size as u64)
+    {
+        iteration.page_ms.push(iteration.total_ms - page_start);
+    }
+    Ok(iteration)
+}
+
+/// Minimum, percentiles, mean, and maximum of a set of values.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Distribution {
+    /// Smallest value
+    pub min: f64,
+
+    /// Median
+    pub p50: f64,
+
+    /// 95th percentile
+    pub p95: f64,
+
+    /// Average
+    pub mean: f64,
+
+    /// Largest value
+    pub max: f64,
+}
+
+impl Distribution {
+    /// Summarizes values, or returns `None` if there are none.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ddog::bench::Distribution;
+    ///
+    /// let d = Distribution::of(&[30.0, 10.0, 20.0]).unwrap();
+    /// assert_eq!((d.min, d.p50, d.max, d.mean), (10.0, 20.0, 30.0, 20.0));
+    /// assert!(Distribution::of(&[]).is_none());
+    /// ```
+    pub fn of(values: &[f64]) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+        let mut sorted = values.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        Some(Self {
+            min: sorted[0],
+            p50: percentile(&sorted, 50.0),
+            p95: percentile(&sorted, 95.0),
+            mean: sorted.iter().sum::<f64>() / sorted.len() as f64,
+            max: sorted[sorted.len() - 1],
+        })
+    }
+}
+
+/// Summary of a benchmark's runs.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BenchReport {
+    /// Runs of the search
+    pub iterations: usize,
+
+    /// Records read per run, as of the last run
+    pub records: u64,
+
+    /// Pages fetched over all runs
+    pub pages: usize,
+
+    /// Time to read every record of a run, in milliseconds
+    pub latency_ms: Option<Distribution>,
+
+    /// Time until a run's first record, in milliseconds
+    pub first_record_ms: Option<Distribution>,
+
+    /// Time to fetch and parse a page, over the pages of every run
+    pub page_ms: Option<Distribution>,
+
+    /// Records read per second, per run
+    pub records_per_sec: Option<Distribution>,
+}
+
+/// Summarizes the runs of a benchmark.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::bench::{Iteration, summarize};
+///
+/// let run = |total_ms: f64| Iteration {
+///     records: 2000,
+///     first_record_ms: Some(200.0),
+///     page_ms: vec![total_ms / 2.0, total_ms / 2.0],
+///     total_ms,
+/// };
+/// let report = summarize(&[run(1000.0), run(2000.0), run(4000.0)]);
+/// assert_eq!((report.iterations, report.pages), (3, 6));
+/// assert_eq!(report.latency_ms.unwrap().p50, 2000.0);
+/// assert_eq!(report.records_per_sec.unwrap().max, 2000.0);
+/// ```
+pub fn summarize(iterations: &[Iteration]) -> BenchReport {
+    let collect = |f: fn(&Iteration) -> f64| iterations.iter().map(f).collect::<Vec<_>>();
+    let first_records: Vec<f64> = iterations
+        .iter()
+        .filter_map(|i| i.first_record_ms)
+        .collect();
+    let pages: Vec<f64> = iterations
+        .iter()
+        .flat_map(|i| i.page_ms.iter().copied())
+        .collect();
+    BenchReport {
+        iterations: iterations.len(),
+        records: iterations.last().map_or(0, |i| i.records),
+        pages: pages.len(),
+        latency_ms: Distribution::of(&collect(|i| i.total_ms)),
+        first_record_ms: Distribution::of(&first_records),
+        page_ms: Distribution::of(&pages),
+        records_per_sec: Distribution::of(&collect(Iteration::records_per_sec)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_measure_counts_pages() {
+        let results = futures_util::stream::iter((0..5).map(Ok::<_, AppError>));
+        let iteration = measure(results, 2).await.unwrap();
+        assert_eq!(iteration.records, 5);
+        assert_eq!(iteration.page_ms.len(), 3);
+        assert!(iteration.first_record_ms.is_some());
+
+        let results = futures_util::stream::iter((0..4).map(Ok::<_, AppError>));
+        assert_eq!(measure(results, 2).await.unwrap().page_ms.len(), 2);
+
+        let empty = futures_util::stream::iter(Vec::<Result<u8, AppError>>::new());
+        let iteration = measure(empty, 2).await.unwrap();
+        assert_eq!((iteration.records, iteration.page_ms.len()), (0, 1));
+        assert_eq!(iteration.first_record_ms, None);
+    }
+
+    #[tokio::test]
+    async fn test_measure_fails_with_search() {
+        let results = futures_util::stream::iter([Ok(1), Err(AppError::Api("boom".into()))]);
+        assert!(matches!(measure(results, 10).await, Err(AppError::Api(_))));
+    }
+}
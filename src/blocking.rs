@@ -0,0 +1,310 @@
+//! Blocking clients, for synchronous programs.
+//!
+//! [`LogsClient`] and [`SpansClient`] wrap the async clients of the same names
+//! in [`client`] and run them on a runtime of their own, so synchronous tools
+//! and build scripts can search without setting up tokio.
+//! Searches return iterators that fetch the next page when the current one
+//! runs out.
+//!
+//! Each blocking client has a connection pool of its own, as connections
+//! can't outlive the runtime that opened them. Calling a blocking client from
+//! inside an async runtime panics; use the async clients there.
+//!
+//! ```no_run
+//! use ddog::LogsSearchRequest;
+//! use ddog::blocking::LogsClient;
+//!
+//! let client = LogsClient::builder().build()?;
+//! let request = LogsSearchRequest::builder()
+//!     .query("service:checkout status:error")
+//!     .from("now-15m")
+//!     .build();
+//! for log in client.search(&request).take(100) {
+//!     println!("{:?}", log?.message);
+//! }
+//! # Ok::<(), ddog::AppError>(())
+//! ```
+
+use datadog_api_client::datadog::Configuration;
+use futures_util::StreamExt;
+use reqwest_middleware::ClientWithMiddleware;
+use tokio::runtime::Runtime;
+
+use crate::client::http::{build_isolated_client, default_transport};
+use crate::client::{
+    self, BuildableClient, ClientBuilder, LogsSearchRequest, SearchStream, SpansSearchRequest,
+};
+use crate::error::AppError;
+use crate::records::{LogEvent, SpanEvent};
+
+/// Starts the runtime a blocking client runs its requests on.
+fn runtime() -> Runtime {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("Failed to start async runtime")
+}
+
+/// Results of a blocking search, fetched a page at a time.
+pub struct SearchIter<'a, T> {
+    runtime: &'a Runtime,
+    stream: SearchStream<'a, T>,
+}
+
+impl<T> Iterator for SearchIter<'_, T> {
+    type Item = Result<T, AppError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.runtime.block_on(self.stream.next())
+    }
+}
+
+/// Blocking client for querying Datadog logs.
+pub struct LogsClient {
+    inner: client::LogsClient,
+    runtime: Runtime,
+}
+
+impl LogsClient {
+    /// Creates a new LogsClient with the given configuration.
+    pub fn new(config: Configuration) -> Self {
+        let http = build_isolated_client(&config, default_transport());
+        Self::from_parts(config, http)
+    }
+
+    /// Starts building a client from explicit credentials and site.
+    pub fn builder() -> ClientBuilder<Self> {
+        ClientBuilder::new()
+    }
+
+    /// Collects up to `limit` logs matching a request (0 for all of them).
+    ///
+    /// # Errors
+    ///
+    /// Same as the async [`client::LogsClient::fetch`].
+    pub fn fetch(
+        &self,
+        request: &LogsSearchRequest,
+        limit: usize,
+    ) -> Result<Vec<LogEvent>, AppError> {
+        self.runtime.block_on(self.inner.fetch(request, limit))
+    }
+
+    /// Searches logs matching a request, as an iterator of typed [`LogEvent`]s.
+    ///
+    /// The iterator handles pagination, fetching the request's page limit of
+    /// records per API request.
+    pub fn search(&self, request: &LogsSearchRequest) -> SearchIter<'_, LogEvent> {
+        SearchIter {
+            runtime: &self.runtime,
+            stream: self.inner.search(request).map_records(LogEvent::from),
+        }
+    }
+}
+
+impl BuildableClient for LogsClient {
+    const OWN_RUNTIME: bool = true;
+
+    fn from_parts(config: Configuration, http: ClientWithMiddleware) -> Self {
+        Self {
+            inner: client::LogsClient::from_parts(config, http),
+            runtime: runtime(),
+        }
+    }
+}
+
+/// Blocking client for querying Datadog APM spans.
+pub struct SpansClient {
+    inner: client::SpansClient,
+    runtime: Runtime,
+}
+
+impl SpansClient {
+    /// Creates a new SpansClient with the given configuration.
+    pub fn new(config: Configuration) -> Self {
+        let http = build_isolated_client(&config, default_transport());
+        Self::from_parts(config, http)
+    }
+
+    /// Starts building a client from explicit credentials and site.
+    pub fn builder() -> ClientBuilder<Self> {
+        ClientBuilder::new()
+    }
+
+    /// Collects up to `limit` spans matching a request (0 for all of them).
+    ///
+    /// # Errors
+    ///
+    /// Same as the async [`client::SpansClient::fetch`].
+    pub fn fetch(
+        &self,
+        request: &SpansSearchRequest,
+        limit: usize,
+    ) -> Result<Vec<SpanEvent>, AppError> {
+        self.runtime.block_on(self.inner.fetch(request, limit))
+    }
+
+    /// Searches APM spans matching a request, as an iterator of typed
+    /// [`SpanEvent`]s.
+    ///
+    /// The iterator handles pagination, fetching the request's page limit of
+    /// records per API request.
+    pub fn search(&self, request: &SpansSearchRequest) -> SearchIter<'_, SpanEvent> {
+        SearchIter {
+            runtime: &self.runtime,
+            stream: self.inner.search(request).map_records(SpanEvent::from),
+        }
+    }
+}
+
+impl BuildableClient for SpansClient {
+    const OWN_RUNTIME: bool = true;
+
+    fn from_parts(config: Configuration, http: ClientWithMiddleware) -> Self {
+        Self {
+            inner: client::SpansClient::from_parts(config, http),
+            runtime: runtime(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{CacheTransport, MockTransport};
+    use serde_json::json;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[test]
+    fn test_search_iterates_across_pages() {
+        let path = "/api/v2/logs/events/search";
+        let transport = MockTransport::new()
+            .respond(
+                "POST",
+                path,
+                200,
+                json!({"data": [{"id": "a", "type": "log"}], "meta": {"page": {"after": "next"}}}),
+            )
+            .respond(
+                "POST",
+                path,
+                200,
+                json!({"data": [{"id": "b", "type": "log"}]}),
+            );
+        let client = LogsClient::builder()
+            .api_key("test")
+            .app_key("test")
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+
+        let request = LogsSearchRequest::builder().page_limit(1).build();
+        let ids: Vec<_> = client
+            .search(&request)
+            .map(|log| log.unwrap().id.unwrap())
+            .collect();
+        assert_eq!(ids, ["a", "b"]);
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    #[test]
+    fn test_spans_fetch_maps_errors() {
+        let transport = MockTransport::new().respond(
+            "POST",
+            "/api/v2/spans/events/search",
+            401,
+            json!({"errors": ["Unauthorized"]}),
+        );
+        let client = SpansClient::builder()
+            .api_key("test")
+            .app_key("test")
+            .transport(transport)
+            .build()
+            .unwrap();
+
+        let result = client.fetch(&SpansSearchRequest::builder().build(), 10);
+        assert!(matches!(result, Err(AppError::Auth(_))));
+    }
+
+    #[test]
+    fn test_cache_misses_use_own_connections() {
+        // Answers every request on a keep-alive connection, counting requests
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let requests = Arc::new(AtomicUsize::new(0));
+        let answered = requests.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let answered = answered.clone();
+                std::thread::spawn(move || {
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    let mut stream = stream;
+                    let mut line = String::new();
+                    let mut length = 0;
+                    while reader.read_line(&mut line).unwrap_or(0) > 0 {
+                        let lower = line.to_ascii_lowercase();
+                        if let Some(value) = lower.strip_prefix("content-length:") {
+                            length = value.trim().parse().unwrap();
+                        }
+                        if line == "\r\n" {
+                            let mut body = vec![0; std::mem::take(&mut length)];
+                            reader.read_exact(&mut body).unwrap();
+                            answered.fetch_add(1, Ordering::SeqCst);
+                            let body = r#"{"data":[{"id":"a","type":"log"}]}"#;
+                            let response = format!(
+                                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                                body.len(),
+                                body
+                            );
+                            stream.write_all(response.as_bytes()).unwrap();
+                        }
+                        line.clear();
+                    }
+                });
+            }
+        });
+
+        let dir = std::env::temp_dir().join(format!("ddog-blocking-cache-{}", std::process::id()));
+        let mut config = Configuration::new();
+        config.server_index = 1;
+        config.server_variables.insert("name".into(), address);
+        config
+            .server_variables
+            .insert("protocol".into(), "http".into());
+        let client = |dir: &std::path::Path| {
+            let cache = CacheTransport::new(dir, Duration::from_secs(60));
+            let http = build_isolated_client(&config, Some(Arc::new(cache)));
+            LogsClient::from_parts(config.clone(), http)
+        };
+        let (first, second) = (client(&dir), client(&dir));
+        let request = |query: &str| {
+            LogsSearchRequest::builder()
+                .query(query)
+                .from("2024-01-15T10:00:00Z")
+                .to("2024-01-15T11:00:00Z")
+                .build()
+        };
+
+        // A connection opened on one client's runtime is only driven while
+        // that client runs, so a client sending on another's would hang
+        let (done, finished) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            for (client, query) in [(&first, "a"), (&second, "b"), (&first, "c")] {
+                let logs = client.fetch(&request(query), 0).unwrap();
+                assert_eq!(logs[0].id.as_deref(), Some("a"));
+            }
+            // Answered from the cache
+            second.fetch(&request("a"), 0).unwrap();
+            done.send(()).unwrap();
+        });
+        finished
+            .recv_timeout(Duration::from_secs(30))
+            .expect("cache misses failed or hung");
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,501 @@
+//! State and rendering for the interactive result browser (`--interactive`).
+//!
+//! A [`Browser`] holds the loaded records with a one-line summary each,
+//! narrows them with an fzf-style fuzzy filter as the user types, and draws
+//! frames as plain lines for the terminal layer to paint. Key presses come
+//! in as [`Key`]s and may produce an [`Action`] for the terminal layer, such
+//! as copying a trace ID or opening a record in the Datadog UI.
+
+use serde_json::Value;
+
+use crate::links::DatadogLinks;
+
+/// A decoded key press.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Enter,
+    Backspace,
+    Esc,
+    /// A printable character
+    Char(char),
+    /// A letter pressed with Ctrl (e.g., `Ctrl('t')`)
+    Ctrl(char),
+}
+
+/// What the terminal layer should do after a key press.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    /// Redraw and wait for the next key
+    Continue,
+    /// Leave the browser
+    Quit,
+    /// Copy text to the clipboard
+    Copy(String),
+    /// Open a URL in the browser
+    Open(String),
+}
+
+/// Footer listing the keys.
+const KEYS: &str = "Enter expand  ^T copy trace_id  ^O open  Esc quit";
+
+/// Browser state over a set of loaded records.
+#[derive(Debug, Clone)]
+pub struct Browser {
+    records: Vec<Value>,
+    lines: Vec<String>,
+    links: DatadogLinks,
+    filter: String,
+    /// Indices of records matching the filter, best match first
+    matches: Vec<usize>,
+    /// Position of the selection in `matches`
+    cursor: usize,
+    /// First visible row of the list, or of the expanded record
+    offset: usize,
+    /// Whether the selected record is shown as pretty JSON
+    expanded: bool,
+    /// One-off status message shown in the footer
+    message: Option<String>,
+}
+
+impl Browser {
+    /// Creates a browser over `records`, summarized by `lines` (one per record).
+    pub fn new(records: Vec<Value>, lines: Vec<String>, links: DatadogLinks) -> Self {
+        let matches = (0..records.len()).collect();
+        Self {
+            records,
+            lines,
+            links,
+            filter: String::new(),
+            matches,
+            cursor: 0,
+            offset: 0,
+            expanded: false,
+            message: None,
+        }
+    }
+
+    /// The selected record, if any record matches the filter.
+    pub fn selected(&self) -> Option<&Value> {
+        self.matches.get(self.cursor).map(|&i| &self.records[i])
+    }
+
+    /// Applies a key press, with `rows` lines available for the list.
+    pub fn handle(&mut self, key: Key, rows: usize) -> Action {
+        self.message = None;
+        let rows = rows.max(1);
+        match key {
+            Key::Esc | Key::Ctrl('c') if self.expanded => self.collapse(),
+            Key::Esc | Key::Ctrl('c') => return Action::Quit,
+            Key::Enter if self.selected().is_some() => {
+                self.expanded = !self.expanded;
+                self.offset = 0;
+                if !self.expanded {
+                    self.collapse();
+                }
+            }
+            Key::Up if self.expanded => self.offset = self.offset.saturating_sub(1),
+            Key::Down if self.expanded => self.offset += 1,
+            Key::PageUp if self.expanded => self.offset = self.offset.saturating_sub(rows),
+            Key::PageDown if self.expanded => self.offset += rows,
+            Key::Up => self.cursor = self.cursor.saturating_sub(1),
+            Key::Down => self.cursor = (self.cursor + 1).min(self.matches.len().saturating_sub(1)),
+            Key::PageUp => self.cursor = self.cursor.saturating_sub(rows),
+            Key::PageDown => {
+                self.cursor = (self.cursor + rows).min(self.matches.len().saturating_sub(1))
+            }
+            Key::Ctrl('t') => match self.selected().and_then(trace_id) {
+                Some(id) => {
+                    let id = id.to_string();
+                    self.message = Some(format!("Copied trace_id {}", id));
+                    return Action::Copy(id);
+                }
+                None => self.message = Some("No trace_id on this record".to_string()),
+            },
+            Key::Ctrl('o') => match self.selected().and_then(|r| record_url(r, &self.links)) {
+                Some(url) => {
+                    self.message = Some(format!("Opening {}", url));
+                    return Action::Open(url);
+                }
+                None => self.message = Some("No link for this record".to_string()),
+            },
+            Key::Backspace if !self.expanded => {
+                self.filter.pop();
+                self.refilter();
+            }
+            Key::Char(c) if !self.expanded => {
+                self.filter.push(c);
+                self.refilter();
+            }
+            _ => {}
+        }
+        self.scroll_into_view(rows);
+        Action::Continue
+    }
+
+    /// Sets the message shown in the footer until the next key press.
+    pub fn set_message(&mut self, message: impl Into<String>) {
+        self.message = Some(message.into());
+    }
+
+    /// Draws a frame of `height` lines, each at most `width` characters.
+    ///
+    /// The list (or the expanded record) fills all but the last two lines,
+    /// which hold the filter prompt and a status line. The selected row is
+    /// marked with `>`; the terminal layer may highlight it.
+    pub fn render(&self, width: usize, height: usize) -> Vec<String> {
+        let rows = height.saturating_sub(2);
+        let mut frame: Vec<String> = match (self.expanded, self.selected()) {
+            (true, Some(record)) => serde_json::to_string_pretty(record)
+                .unwrap_or_default()
+                .lines()
+                .skip(self.offset)
+                .take(rows)
+                .map(String::from)
+                .collect(),
+            _ => self
+                .matches
+                .iter()
+                .enumerate()
+                .skip(self.offset)
+                .take(rows)
+                .map(|(position, &index)| {
+                    let marker = if position == self.cursor { '>' } else { ' ' };
+                    format!("{} {}", marker, self.lines[index])
+                })
+                .collect(),
+        };
+        frame.resize(rows, String::new());
+
+        frame.push(format!("filter: {}", self.filter));
+        let status = match &self.message {
+            Some(message) => message.clone(),
+            None => format!("{}/{}  {}", self.matches.len(), self.records.len(), KEYS),
+        };
+        frame.push(status);
+        frame.iter().map(|line| truncate(line, width)).collect()
+    }
+
+    /// Position of the selected row in a frame from [`Browser::render`], if visible.
+    pub fn cursor_row(&self) -> Option<usize> {
+        (!self.expanded && !self.matches.is_empty()).then(|| self.cursor - self.offset)
+    }
+
+    fn collapse(&mut self) {
+        self.expanded = false;
+        self.offset = self.cursor;
+    }
+
+    fn refilter(&mut self) {
+        let terms: Vec<&str> = self.filter.split_whitespace().collect();
+        let mut scored: Vec<(i64, usize)> = self
+            .lines
+            .iter()
+            .enumerate()
+            .filter_map(|(index, line)| {
+                terms
+                    .iter()
+                    .try_fold(0, |total, term| Some(total + fuzzy_score(term, line)?))
+                    .map(|score| (score, index))
+            })
+            .collect();
+        // Best score first; ties keep the records' order
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        self.matches = scored.into_iter().map(|(_, index)| index).collect();
+        self.cursor = 0;
+        self.offset = 0;
+    }
+
+    fn scroll_into_view(&mut self, rows: usize) {
+        if self.expanded {
+            return;
+        }
+        if self.cursor < self.offset {
+            self.offset = self.cursor;
+        } else if self.cursor >= self.offset + rows {
+            self.offset = self.cursor + 1 - rows;
+        }
+    }
+}
+
+/// Scores how well `pattern` fuzzy-matches `text`, or `None` if it doesn't.
+///
+/// Every character of the pattern must appear in order. Consecutive matches
+/// and matches at the start of a word score higher, gaps score lower; each
+/// occurrence of the first character is tried as a starting point and the
+/// best alignment wins. The match ignores case unless the pattern contains an
+/// uppercase letter.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::browser::fuzzy_score;
+///
+/// let line = "ERROR checkout payment declined";
+/// assert!(fuzzy_score("chk", line).is_some());
+/// assert!(fuzzy_score("check", line) > fuzzy_score("chk", line));
+/// assert_eq!(fuzzy_score("xyz", line), None);
+/// ```
+pub fn fuzzy_score(pattern: &str, text: &str) -> Option<i64> {
+    let case_sensitive = pattern.chars().any(char::is_uppercase);
+    let normalize = |c: char| {
+        if case_sensitive {
+            c
+        } else {
+            c.to_ascii_lowercase()
+        }
+    };
+
+    let text: Vec<char> = text.chars().map(normalize).collect();
+    let pattern: Vec<char> = pattern.chars().map(normalize).collect();
+    let Some(&first) = pattern.first() else {
+        return Some(0);
+    };
+    (0..text.len())
+        .filter(|&start| text[start] == first)
+        .filter_map(|start| score_from(&pattern, &text, start))
+        .max()
+}
+
+/// Scores a greedy match of `pattern` in `text` starting at `start`.
+fn score_from(pattern: &[char], text: &[char], start: usize) -> Option<i64> {
+    let mut score = 0;
+    let mut position = start;
+    let mut previous: Option<usize> = None;
+    for &wanted in pattern {
+        let found = (position..text.len()).find(|&i| text[i] == wanted)?;
+        score += 16;
+        if previous.is_some_and(|p| p + 1 == found) {
+            score += 8;
+        } else if let Some(p) = previous {
+            score -= (found - p - 1).min(8) as i64;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 8;
+        }
+        previous = Some(found);
+        position = found + 1;
+    }
+    Some(score)
+}
+
+/// The trace ID of a log or span record.
+pub fn trace_id(record: &Value) -> Option<&str> {
+    [
+        "/attributes/trace_id",
+        "/attributes/attributes/dd/trace_id",
+        "/attributes/attributes/trace_id",
+    ]
+    .iter()
+    .find_map(|pointer| record.pointer(pointer).and_then(Value::as_str))
+    .filter(|id| !id.is_empty())
+}
+
+/// The Datadog UI page for a record: its trace if it has one, else the log itself.
+pub fn record_url(record: &Value, links: &DatadogLinks) -> Option<String> {
+    if let Some(id) = trace_id(record) {
+        return Some(links.trace(id));
+    }
+    match (
+        record.get("type").and_then(Value::as_str),
+        record.get("id").and_then(Value::as_str),
+    ) {
+        (Some("log"), Some(id)) => Some(links.log(id)),
+        _ => None,
+    }
+}
+
+/// Decodes raw terminal input into key presses.
+///
+/// Understands arrow and page keys as sent by xterm-compatible terminals;
+/// unknown escape sequences are dropped.
+pub fn parse_keys(input: &[u8]) -> Vec<Key> {
+    let text = String::from_utf8_lossy(input);
+    let mut keys = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let key = match c {
+            '\x1b' if chars.peek() == Some(&'[') || chars.peek() == Some(&'O') => {
+                chars.next();
+                let mut sequence = String::new();
+                while let Some(&c) = chars.peek() {
+                    chars.next();
+                    sequence.push(c);
+                    if c.is_ascii_alphabetic() || c == '~' {
+                        break;
+                    }
+                }
+                match sequence.as_str() {
+                    "A" => Key::Up,
+                    "B" => Key::Down,
+                    "5~" => Key::PageUp,
+                    "6~" => Key::PageDown,
+                    _ => continue,
+                }
+            }
+            '\x1b' => Key::Esc,
+            '\r' | '\n' => Key::Enter,
+            '\x7f' | '\x08' => Key::Backspace,
+            '\x01'..='\x1a' => Key::Ctrl((b'a' + c as u8 - 1) as char),
+            c if c.is_control() => continue,
+            c => Key::Char(c),
+        };
+        keys.push(key);
+    }
+    keys
+}
+
+/// Encodes bytes as standard base64, for the OSC 52 clipboard sequence.
+pub fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::new();
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+fn truncate(line: &str, width: usize) -> String {
+    line.chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .take(width)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn browser() -> Browser {
+        let records = vec![
+            json!({"id": "a", "type": "log", "attributes": {"message": "payment declined"}}),
+            json!({"id": "b", "type": "log", "attributes": {"attributes": {"dd": {"trace_id": "123"}}}}),
+            json!({"id": "c", "type": "log", "attributes": {"message": "timeout"}}),
+        ];
+        let lines = vec![
+            "ERROR checkout payment declined".to_string(),
+            "WARN api slow request".to_string(),
+            "ERROR api upstream timeout".to_string(),
+        ];
+        Browser::new(records, lines, DatadogLinks::new("datadoghq.com"))
+    }
+
+    fn type_text(browser: &mut Browser, text: &str) {
+        for c in text.chars() {
+            browser.handle(Key::Char(c), 10);
+        }
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert!(fuzzy_score("api", "ERROR api upstream").is_some());
+        assert!(fuzzy_score("API", "ERROR api upstream").is_none());
+        assert!(fuzzy_score("ups", "x upstream") > fuzzy_score("ups", "x u p s"));
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+    }
+
+    #[test]
+    fn test_filter_and_navigation() {
+        let mut browser = browser();
+        type_text(&mut browser, "api");
+        assert_eq!(browser.render(80, 5)[3], "filter: api");
+        assert_eq!(browser.selected().unwrap()["id"], "b");
+
+        browser.handle(Key::Down, 10);
+        assert_eq!(browser.selected().unwrap()["id"], "c");
+        browser.handle(Key::Down, 10);
+        assert_eq!(browser.selected().unwrap()["id"], "c");
+
+        type_text(&mut browser, " tmo");
+        assert_eq!(browser.render(80, 5)[4], "1/3  ".to_string() + KEYS);
+        browser.handle(Key::Backspace, 10);
+        browser.handle(Key::Backspace, 10);
+        browser.handle(Key::Backspace, 10);
+        browser.handle(Key::Backspace, 10);
+        assert_eq!(browser.render(80, 5)[4], "2/3  ".to_string() + KEYS);
+
+        type_text(&mut browser, "zzz");
+        assert!(browser.selected().is_none());
+        assert_eq!(browser.handle(Key::Ctrl('o'), 10), Action::Continue);
+    }
+
+    #[test]
+    fn test_render_scrolls_to_cursor() {
+        let mut browser = browser();
+        browser.handle(Key::PageDown, 2);
+        let frame = browser.render(12, 4);
+        assert_eq!(frame.len(), 4);
+        assert_eq!(frame[0], "  WARN api s");
+        assert_eq!(frame[1], "> ERROR api ");
+        assert_eq!(browser.cursor_row(), Some(1));
+    }
+
+    #[test]
+    fn test_expand_and_actions() {
+        let mut browser = browser();
+        browser.handle(Key::Down, 10);
+        assert_eq!(
+            browser.handle(Key::Ctrl('t'), 10),
+            Action::Copy("123".to_string())
+        );
+        assert_eq!(
+            browser.handle(Key::Ctrl('o'), 10),
+            Action::Open("https://app.datadoghq.com/apm/trace/123".to_string())
+        );
+
+        browser.handle(Key::Enter, 10);
+        assert_eq!(browser.render(80, 4)[0], "{");
+        assert_eq!(browser.cursor_row(), None);
+        assert_eq!(browser.handle(Key::Esc, 10), Action::Continue);
+        assert_eq!(browser.selected().unwrap()["id"], "b");
+
+        browser.handle(Key::Up, 10);
+        assert_eq!(browser.handle(Key::Ctrl('t'), 10), Action::Continue);
+        assert_eq!(browser.render(80, 4)[3], "No trace_id on this record");
+        assert_eq!(
+            browser.handle(Key::Ctrl('o'), 10),
+            Action::Open("https://app.datadoghq.com/logs?event=a".to_string())
+        );
+        assert_eq!(browser.handle(Key::Esc, 10), Action::Quit);
+    }
+
+    #[test]
+    fn test_parse_keys() {
+        assert_eq!(
+            parse_keys(b"a\x1b[A\x1b[6~\r\x7f\x14\x1b"),
+            vec![
+                Key::Char('a'),
+                Key::Up,
+                Key::PageDown,
+                Key::Enter,
+                Key::Backspace,
+                Key::Ctrl('t'),
+                Key::Esc,
+            ]
+        );
+        assert!(parse_keys(b"\x1b[1;5C").is_empty());
+    }
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"trace-123"), "dHJhY2UtMTIz");
+    }
+}
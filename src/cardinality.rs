@@ -0,0 +1,106 @@
+//! Tag cardinality of a metric.
+//!
+//! Every distinct combination of tag values is a separate custom metric
+//! series, billed on its own, so one tag key with many values (a user ID, a
+//! request path) multiplies a metric's cost. [`tag_cardinality`] counts the
+//! values seen for each tag key of a metric, highest first, so the key behind
+//! an explosion stands out.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Values kept as examples for each tag key.
+const EXAMPLES: usize = 3;
+
+/// The values seen for one tag key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TagCardinality {
+    /// Tag key (e.g., "host"), or the whole tag for a tag without a value
+    pub tag_key: String,
+
+    /// Number of distinct values
+    pub values: usize,
+
+    /// A few of the values, in sorted order
+    pub examples: Vec<String>,
+}
+
+/// Counts the distinct values of each tag key in `tags` (`key:value`
+/// strings), most values first and then by key.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::cardinality::tag_cardinality;
+///
+/// let tags = ["host:a", "host:b", "env:prod", "host:c"].map(String::from);
+/// let keys = tag_cardinality(&tags);
+/// assert_eq!(keys[0].tag_key, "host");
+/// assert_eq!(keys[0].values, 3);
+/// assert_eq!(keys[1].tag_key, "env");
+/// ```
+pub fn tag_cardinality(tags: &[String]) -> Vec<TagCardinality> {
+    let mut keys: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for tag in tags {
+        let (key, value) = tag.split_once(':').unwrap_or((tag, ""));
+        keys.entry(key).or_default().insert(value);
+    }
+
+    let mut cardinality: Vec<TagCardinality> = keys
+        .into_iter()
+        .map(|(key, values)| TagCardinality {
+            tag_key: key.to_string(),
+            values: values.len(),
+            examples: values
+                .into_iter()
+                .filter(|v| !v.is_empty())
+                .take(EXAMPLES)
+                .map(String::from)
+                .collect(),
+        })
+        .collect();
+    // Stable, so keys with as many values stay in key order
+    cardinality.sort_by_key(|key| std::cmp::Reverse(key.values));
+    cardinality
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tag_cardinality() {
+        let tags: Vec<String> = [
+            "env:prod",
+            "user_id:1",
+            "user_id:2",
+            "user_id:3",
+            "user_id:4",
+            "region:us",
+            "region:eu",
+            "path:/a:b",
+            "canary",
+            "env:prod",
+        ]
+        .map(String::from)
+        .into();
+        let keys = tag_cardinality(&tags);
+        let summary: Vec<_> = keys
+            .iter()
+            .map(|k| (k.tag_key.as_str(), k.values))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("user_id", 4),
+                ("region", 2),
+                ("canary", 1),
+                ("env", 1),
+                ("path", 1)
+            ]
+        );
+        assert_eq!(keys[0].examples, ["1", "2", "3"]);
+        assert_eq!(keys[2].examples, Vec::<String>::new());
+        assert_eq!(keys[4].examples, ["/a:b"]);
+    }
+}
@@ -0,0 +1,110 @@
+//! Raw API domain command actions.
+
+use clap::Subcommand;
+
+/// Available actions for the raw API domain.
+#[derive(Subcommand, Debug)]
+pub enum ApiAction {
+    /// Send a GET request to any Datadog API endpoint
+    #[command(long_about = "Send a GET request to any Datadog API endpoint.
+
+An escape hatch for endpoints without a dedicated command. The request uses
+the same credentials, site, proxy, and retries as every other command, so
+PATH is just the part after the host (e.g., /api/v2/logs/config/indexes).
+
+Output Format:
+  Each line contains one item of the response:
+  - a top-level array yields its elements
+  - an object yields the elements of its \"data\" array (v2 endpoints) or of
+    its only array field (e.g., \"indexes\" or \"host_list\" in v1 endpoints)
+  - anything else is a single record
+
+  Use --raw to write the whole response as a single record instead.
+
+Examples:
+  # Log index configuration
+  ddog api get /api/v1/logs/config/indexes
+
+  # Query parameters, as one string or one flag per parameter
+  ddog api get /api/v2/services/definitions --query 'page[size]=10&schema_version=v2.2'
+  ddog api get /api/v1/hosts --query filter=env:prod --query count=100
+
+  # Full response, including pagination metadata
+  ddog api get /api/v2/users --raw | jq .meta")]
+    Get {
+        /// API path, starting with / (e.g., /api/v1/validate)
+        #[arg(value_name = "PATH")]
+        path: String,
+
+        /// Query parameters as KEY=VALUE pairs joined by & (repeatable)
+        #[arg(short, long, value_name = "PARAMS")]
+        query: Vec<String>,
+
+        /// Write the whole response as a single record
+        #[arg(long)]
+        raw: bool,
+    },
+
+    /// Send a POST request with a JSON body to any Datadog API endpoint
+    #[command(
+        long_about = "Send a POST request with a JSON body to any Datadog API endpoint.
+
+The body is JSON given inline, read from a file with @FILE, or from stdin
+with @-. It may contain {{NAME}} placeholders filled in with --var NAME=VALUE:
+a string that is exactly one placeholder becomes the value as JSON when it
+parses as JSON (numbers, booleans, arrays), and placeholders inside longer
+strings are replaced as text. Every placeholder needs a value and every
+variable must be used.
+
+Before sending, the method, URL, and body are shown on stderr and you are
+asked to confirm. Without a terminal to ask on, pass --yes. Use --dry-run to
+print the request that would be sent instead of sending it.
+
+Every request sent is appended to audit.ndjson in the state directory
+(~/.local/state/ddog), with its outcome and the command line that sent it.
+
+Output Format:
+  Same as `ddog api get`: one record per item of the response, or the whole
+  response with --raw. With --dry-run, a single record with the method, url,
+  query, and body of the request.
+
+Examples:
+  # Create a monitor from a template
+  ddog api post /api/v1/monitor --body @monitor.json --var service=checkout --var threshold=90
+
+  # Preview the rendered request without sending it
+  ddog api post /api/v1/monitor --body @monitor.json --var service=checkout --dry-run
+
+  # Inline body, no prompt (for scripts)
+  ddog api post /api/v2/logs/events/search --body '{\"filter\": {\"query\": \"status:error\"}}' --yes"
+    )]
+    Post {
+        /// API path, starting with / (e.g., /api/v1/monitor)
+        #[arg(value_name = "PATH")]
+        path: String,
+
+        /// Request body: inline JSON, @FILE, or @- for stdin
+        #[arg(long, value_name = "JSON|@FILE")]
+        body: String,
+
+        /// Value for a {{NAME}} placeholder in the body (repeatable)
+        #[arg(long = "var", value_name = "NAME=VALUE", value_parser = ddog::template::parse_var)]
+        vars: Vec<(String, String)>,
+
+        /// Query parameters as KEY=VALUE pairs joined by & (repeatable)
+        #[arg(short, long, value_name = "PARAMS")]
+        query: Vec<String>,
+
+        /// Print the request instead of sending it
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Send without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+
+        /// Write the whole response as a single record
+        #[arg(long)]
+        raw: bool,
+    },
+}
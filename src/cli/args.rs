@@ -1,26 +1,73 @@
 //! Main CLI argument definitions.
 
-use clap::{Parser, Subcommand};
+use clap::{ArgAction, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
+use ddog::client::DEFAULT_CACHE_TTL;
+use ddog::loki::DEFAULT_LABELS;
+use ddog::sink::DEFAULT_BATCH_SIZE;
+use ddog::time::{Timezone, duration_seconds, parse_last};
+use ddog::transform::{TimestampFormat, parse_field, parse_rename};
+use ddog::upload::ObjectUrl;
+use std::path::PathBuf;
 
+use crate::output::OutputFormat;
+
+use super::api::ApiAction;
+use super::bench::BenchAction;
+use super::docs::DocsAction;
+use super::drift::DriftAction;
+use super::history::HistoryAction;
+use super::hosts::HostsAction;
 use super::logs::LogsAction;
 use super::metrics::MetricsAction;
+use super::monitors::MonitorsAction;
+use super::query::QueryAction;
+use super::shared::parse_http_url;
 use super::spans::SpansAction;
+use super::telemetry::TelemetryAction;
 
 /// Main CLI application structure.
 #[derive(Parser, Debug)]
 #[command(name = "ddog")]
-#[command(about = "Query Datadog logs, APM spans, and metrics from the command line")]
+#[command(about = "Query Datadog logs, APM spans, metrics, and monitors from the command line")]
 #[command(
-    long_about = "Query Datadog logs, APM spans, and metrics from the command line.
+    long_about = "Query Datadog logs, APM spans, metrics, and monitors from the command line.
 
 Environment Variables (Required):
   DD_API_KEY     Your Datadog API key
   DD_APP_KEY     Your Datadog application key
   DD_SITE        Datadog site (optional, default: datadoghq.com)
 
+Workspace:
+  A .ddog.toml file in the current directory or any parent sets the profile,
+  default service/env filters, and saved queries (@name) for that checkout.
+
+Saved Queries:
+  `ddog query save <NAME> <QUERY>` stores a query in the config directory;
+  reference it from any logs or spans search as @NAME.
+
+History:
+  Executed queries are recorded in the state directory; `ddog history list`
+  shows them and `ddog history rerun <N>` runs one again. Set
+  DDOG_NO_HISTORY=1 to stop recording.
+
+Cassettes:
+  --record FILE saves the API responses of a command and --replay FILE
+  answers from them offline; DDOG_CASSETTE=FILE picks one automatically.
+
+Response Cache:
+  Queries over an absolute, already ended time range are answered from disk
+  for --cache-ttl (default 5m) when repeated; --no-cache always asks Datadog.
+
+Verbose Output:
+  -v     Debug messages plus one trace line per API request (URL, status,
+         latency, page cursor, request ID, rate-limit headers), written to stderr
+  -vv    Also dump each API response body
+
 Output Format:
   All commands output newline-delimited JSON (NDJSON), one record per line.
   Perfect for piping to jq, grep, or other line-oriented tools.
+  Use --format text for human-readable lines with clickable Datadog links.
 
 Examples:
   # Search logs for errors
@@ -32,19 +79,633 @@ Examples:
   # Search spans with time range
   ddog spans search \"service:web\" --from now-1h --to now
 
+  # Alert episodes for a monitor over the last week
+  ddog monitors history 123456 --last 7d
+
+  # On-call handoff summary for the last 24 hours
+  ddog handoff --team payments
+
 Documentation:
   https://github.com/tmcinerney/ddog"
 )]
 #[command(version)]
 pub struct Cli {
-    /// Enable verbose/debug output
-    #[arg(short, long, global = true)]
-    pub verbose: bool,
+    /// Enable verbose/debug output with request tracing (-vv also dumps response bodies)
+    #[arg(short, long, global = true, action = ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Connection profile from ~/.config/ddog/config.toml (overrides the workspace profile)
+    #[arg(long, global = true, value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// Ignore any .ddog.toml workspace file in the current directory or its parents
+    #[arg(long, global = true)]
+    pub no_workspace: bool,
+
+    /// Timezone for local times like "yesterday 9am" or 2024-01-15T10:00 (IANA name or "local")
+    #[arg(
+        long,
+        global = true,
+        value_name = "TZ",
+        default_value = "local",
+        long_help = "Timezone used to resolve local times in --from/--to: natural-language
+times such as \"today\", \"yesterday 9am\", or \"last monday 14:30\", and ISO8601
+times without a UTC offset such as 2024-01-15T10:00:00.
+
+Also used by --local-timestamps to display record timestamps.
+
+Accepts an IANA timezone name or \"local\" for the system timezone.
+
+Examples:
+  --timezone Europe/London
+  --timezone America/New_York
+  --timezone UTC"
+    )]
+    pub timezone: Timezone,
+
+    /// Output format: ndjson (default) or human-readable text
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        default_value_t = OutputFormat::Ndjson,
+        long_help = "Output format for records written to stdout.
+
+  ndjson   One JSON object per line (default), for jq and other tools
+  text     One human-readable line per record. When stdout is a terminal,
+           trace IDs and hosts are clickable links into the Datadog UI
+           (OSC 8 hyperlinks); piped output never contains escape codes.
+
+`ddog spans trace` also renders the calls between services as a sequence
+diagram, every span as a waterfall, or folded stacks, instead of records:
+
+  mermaid-sequence    Mermaid sequenceDiagram, for Markdown docs
+  plantuml-sequence   PlantUML @startuml sequence diagram
+  waterfall           Gantt-style span bars for the terminal, errors in red
+  folded              Folded stacks for flamegraph.pl and inferno
+
+`ddog spans deps` can draw its dependency edges as a graph instead:
+
+  dot      Graphviz digraph, for `dot -Tsvg`
+
+`ddog metrics query` writes one record per datapoint by default, and with
+wide a record per series instead:
+
+  wide     {\"metric\",\"scope\",\"tags\",\"points\":[[ts,value],...]} per line
+
+`ddog logs search` can push logs to Grafana Loki instead (see --push):
+
+  loki     Loki push API payloads, sent to --push
+
+For loading records into Splunk:
+
+  splunk-hec  {\"time\",\"host\",\"source\",\"sourcetype\",\"index\",\"event\"} per line, the
+              HTTP Event Collector envelope (see --splunk-sourcetype)"
+    )]
+    pub format: OutputFormat,
+
+    /// Show record timestamps in --timezone instead of UTC (text output)
+    #[arg(
+        long,
+        global = true,
+        long_help = "Rewrite record timestamps into the --timezone timezone in human-readable
+output (--format text), e.g. 2024-01-15 11:00:00.000 +01:00 instead of
+2024-01-15T10:00:00Z. NDJSON output keeps timestamps as returned by Datadog.
+
+Examples:
+  ddog logs search \"status:error\" --format text --local-timestamps
+  ddog logs search \"status:error\" --format text --local-timestamps --timezone Asia/Tokyo"
+    )]
+    pub local_timestamps: bool,
+
+    /// Write a leading {"type":"meta"} record with the resolved query and time window
+    #[arg(
+        long,
+        global = true,
+        long_help = "Write a leading record describing exactly what was queried, before any results:
+
+  {\"type\":\"meta\",\"domain\":\"logs\",\"query\":\"status:error\",
+   \"from\":\"2024-01-15T09:00:00.000Z\",\"to\":\"2024-01-15T10:00:00.000Z\",
+   \"indexes\":[\"*\"]}
+
+Relative and natural-language bounds are resolved to absolute UTC times, so
+archived exports record the exact window they cover.
+
+Examples:
+  ddog logs search \"status:error\" --from now-1d/d --to now/d --emit-meta > export.ndjson
+  ddog logs search \"status:error\" --emit-meta | head -1 | jq .from"
+    )]
+    pub emit_meta: bool,
+
+    /// Print how a logs or spans search was resolved to stderr before running it
+    #[arg(
+        long,
+        global = true,
+        long_help = "Before a logs or spans search runs, print to stderr exactly what it asks
+Datadog for: the final query string (with saved queries, query builder flags,
+and workspace defaults applied), the absolute time bounds, the indexes and
+storage tier, the sort, and the page size. A count of the matching records
+(one extra request) gives the number of pages the search should fetch.
+
+Zero results usually come down to one of these: a filter added by a flag or
+workspace default, a window resolved to a different time than expected, or
+the wrong index. With --emit-meta, the meta record carries the same fields.
+Not available with --batch, --open, or --count.
+
+Examples:
+  ddog logs search \"service:api status:error\" --last 15m --explain
+  ddog spans search \"service:web\" --from yesterday --explain --emit-meta | head -1"
+    )]
+    pub explain: bool,
+
+    /// Replace the values at these field paths with [REDACTED] (comma-separated, * wildcards)
+    #[arg(
+        long,
+        global = true,
+        value_name = "PATHS",
+        value_delimiter = ',',
+        long_help = "Replace the values at these field paths with [REDACTED] in every record
+written, whatever the output format or destination (stdout, --output,
+--sink, --push, --route-by-owner files, and export files).
+
+Paths are dotted, as records are written (logs keep custom attributes under
+attributes.attributes). A * in a segment matches any characters, and arrays
+along a path are searched element by element.
+
+Examples:
+  --redact attributes.attributes.usr.email
+  --redact 'attributes.attributes.*.password,attributes.attributes.*token*'"
+    )]
+    pub redact: Vec<String>,
+
+    /// Mask emails, IPv4 addresses, card numbers, and SSNs in every string value
+    #[arg(
+        long,
+        global = true,
+        long_help = "Mask personal data found in any string value of every record written, so
+exports shared with vendors don't leak it. Each match is replaced with a
+typed marker:
+
+  Email addresses                   [REDACTED:email]
+  IPv4 addresses                    [REDACTED:ip]
+  Payment card numbers (Luhn-valid) [REDACTED:card]
+  US social security numbers        [REDACTED:ssn]
+
+Detection is pattern-based: it can't catch every form of personal data, so
+pair it with --redact for fields known to hold it. Combine with --anonymize
+to keep identities joinable instead.
+
+Examples:
+  ddog logs search \"service:checkout\" --last 1d --redact-pii > vendor.ndjson"
+    )]
+    pub redact_pii: bool,
+
+    /// Remove the fields at these paths from every record (comma-separated, * wildcards)
+    #[arg(
+        long,
+        global = true,
+        value_name = "PATHS",
+        value_delimiter = ',',
+        long_help = "Remove the fields at these paths from every record written, whatever the
+output format or destination, so ETL jobs don't need jq to trim records.
+
+Paths are dotted, as records are written. A * in a segment matches any
+characters, and arrays along a path are searched element by element.
+
+Output transforms run in a fixed order: --redact and --redact-pii first,
+then --timestamp-format, --drop-field, --rename-field, --add-field, and
+--flatten.
+
+Examples:
+  --drop-field attributes.tags
+  --drop-field 'attributes.attributes.debug_*,attributes.attributes.http.headers'"
+    )]
+    pub drop_field: Vec<String>,
+
+    /// Move a field to another path in every record (OLD=NEW, repeatable)
+    #[arg(
+        long,
+        global = true,
+        value_name = "OLD=NEW",
+        value_delimiter = ',',
+        value_parser = parse_rename,
+        long_help = "Move the value at dotted path OLD to path NEW in every record written,
+creating objects along NEW as needed. Records without OLD are left as they
+are. Renames run in the order given, after --drop-field.
+
+Examples:
+  --rename-field attributes.message=msg
+  --rename-field attributes.service=labels.service,attributes.host=labels.host"
+    )]
+    pub rename_field: Vec<(String, String)>,
+
+    /// Set a field to a constant in every record (PATH=VALUE, repeatable)
+    #[arg(
+        long,
+        global = true,
+        value_name = "PATH=VALUE",
+        value_delimiter = ',',
+        value_parser = parse_field,
+        long_help = "Set the field at dotted path PATH to VALUE in every record written,
+creating objects along PATH as needed and replacing any value already there.
+A VALUE that is a number, true, false, or null is written as such; anything
+else is written as a string. Fields are added last, after --rename-field.
+
+Examples:
+  --add-field env=prod
+  --add-field source=ddog,batch.id=42"
+    )]
+    pub add_field: Vec<(String, serde_json::Value)>,
+
+    /// Write nested objects as dotted keys (attributes.http.status_code)
+    #[arg(
+        long,
+        global = true,
+        long_help = "Flatten every record written so nested objects become dotted keys, as
+loaders such as BigQuery schema autodetection and CSV converters expect:
+
+  {\"attributes\": {\"http\": {\"status_code\": 502}}}
+  becomes {\"attributes.http.status_code\": 502}
+
+Arrays are kept as values, and empty objects as {}. Flattening runs after
+every other output transform, so their paths are the nested ones.
+
+Examples:
+  ddog logs search \"service:api\" --last 1h --flatten > logs.ndjson
+  bq load --autodetect --source_format=NEWLINE_DELIMITED_JSON ds.logs logs.ndjson"
+    )]
+    pub flatten: bool,
+
+    /// Rewrite record timestamps as unix-ms, rfc3339, or epoch-s
+    #[arg(
+        long,
+        global = true,
+        value_name = "FORMAT",
+        long_help = "Rewrite the timestamp of every record written in one format, so logs,
+spans, metrics, and events load alike (each API returns times differently):
+
+  unix-ms  Unix milliseconds, as a number
+  rfc3339  RFC 3339 in UTC with milliseconds (2024-01-15T10:00:00.000Z)
+  epoch-s  Unix seconds, as a number
+
+The timestamp is the first of attributes.timestamp (logs),
+attributes.start_timestamp (spans), timestamp (metric points, events), and
+ts that holds an RFC 3339 string or a number; numbers below 10^11 are read
+as seconds, others as milliseconds. Records without one are left as they
+are. The timestamp is rewritten before the other field transforms run.
+
+Examples:
+  ddog logs search \"service:api\" --last 1h --timestamp-format unix-ms
+  ddog metrics query \"avg:system.cpu.user{*}\" --timestamp-format rfc3339 --timestamp-field ts"
+    )]
+    pub timestamp_format: Option<TimestampFormat>,
+
+    /// Move the rewritten timestamp to this field path (requires --timestamp-format)
+    #[arg(
+        long,
+        global = true,
+        value_name = "PATH",
+        requires = "timestamp_format",
+        long_help = "Move the timestamp rewritten by --timestamp-format to this dotted path,
+so every domain's records have it in the same field.
+
+Examples:
+  ddog spans search \"service:api\" --last 1h --timestamp-format unix-ms --timestamp-field ts"
+    )]
+    pub timestamp_field: Option<String>,
+
+    /// Upload records to object storage as gzipped NDJSON (s3://bucket/prefix/ or gs://...)
+    #[arg(
+        long,
+        global = true,
+        value_name = "URL",
+        long_help = "Upload the records a command writes to object storage as gzipped NDJSON,
+instead of writing them to stdout. The upload is streamed in 8 MiB compressed
+parts with a multipart upload, so exports of any size never touch local disk.
+
+A URL ending in / is a prefix, and the object is named under it as
+ddog-<UTC time>.ndjson.gz; any other URL names the object itself.
+
+  s3://BUCKET/PREFIX/   Amazon S3, with AWS_ACCESS_KEY_ID, AWS_SECRET_ACCESS_KEY,
+                        and optionally AWS_SESSION_TOKEN, AWS_REGION (default
+                        us-east-1), and AWS_ENDPOINT_URL for S3-compatible stores
+  gs://BUCKET/PREFIX/   Google Cloud Storage, with an HMAC key in
+                        GCS_HMAC_ACCESS_ID and GCS_HMAC_SECRET
+
+The uploaded object's URL is printed to stderr once the upload completes. If
+the upload fails, it is aborted and the command fails with the storage
+service's error.
+
+Examples:
+  ddog logs search \"service:api\" --from now-7d --output s3://exports/api/
+  ddog spans search \"env:prod\" --output gs://exports/spans/2024-01-15.ndjson.gz"
+    )]
+    pub output: Option<ObjectUrl>,
+
+    /// POST records in batches as JSON arrays to an HTTP endpoint instead of stdout
+    #[arg(
+        long,
+        global = true,
+        value_name = "URL",
+        group = "delivery",
+        conflicts_with = "output",
+        value_parser = parse_http_url,
+        long_help = "POST the records a command writes to an HTTP endpoint instead of writing
+them to stdout, in batches of --batch-size records sent as a JSON array
+(Content-Type: application/json).
+
+Batches are sent in order while the search runs. A request failing with a
+connection error, 429, or 5xx response is retried up to 3 times with
+exponential backoff; if it still fails, or the endpoint rejects a batch, no
+further batches are sent and the command fails with the endpoint's error
+(exit code 2 for 401, 11 for 403, 12 for 429, 3 otherwise). The records and
+batches posted are printed to stderr once every batch is delivered.
+
+Examples:
+  ddog logs search \"status:error\" --sink http://internal-svc/ingest
+  ddog spans search \"env:prod\" --sink https://collector/spans --batch-size 500"
+    )]
+    pub sink: Option<String>,
+
+    /// Push logs to Grafana Loki (with --format loki), e.g. http://loki:3100
+    #[arg(
+        long,
+        global = true,
+        value_name = "URL",
+        group = "delivery",
+        conflicts_with = "output",
+        value_parser = parse_http_url,
+        long_help = "Push the logs `ddog logs search` finds to Grafana Loki, with --format loki,
+for mirroring Datadog logs into Loki or migrating to it.
+
+Each log becomes a Loki entry: its message as the line, its timestamp, and
+labels from the --labels fields it has. Logs are pushed in batches of
+--batch-size, grouped into one stream per label set, to the push API
+(/loki/api/v1/push is appended unless the URL already ends with it).
+Failed pushes are retried like --sink requests.
+
+Examples:
+  ddog logs search \"service:api\" --from now-1h --format loki --push http://loki:3100
+  ddog logs search \"env:prod\" --format loki --push http://loki:3100 --labels service,env,kube_namespace"
+    )]
+    pub push: Option<String>,
+
+    /// Log fields to label Loki streams with (comma-separated)
+    #[arg(
+        long,
+        global = true,
+        value_name = "FIELDS",
+        value_delimiter = ',',
+        default_values_t = DEFAULT_LABELS.map(String::from),
+        requires = "push",
+        long_help = "Log fields to label Loki streams with, comma-separated (default:
+service,host,status).
+
+Each field is looked up as a log attribute such as service or host, then a
+key:value tag such as env:prod, then a custom attribute, where a dotted name
+such as http.status_code follows nested objects. Logs without the field get
+no such label. Names are sanitized into Loki label names (kube.namespace
+becomes kube_namespace).
+
+Keep labels to low-cardinality fields: every distinct label set is a Loki
+stream."
+    )]
+    pub labels: Vec<String>,
+
+    /// Splunk sourcetype of --format splunk-hec events (default: datadog:log)
+    #[arg(
+        long,
+        global = true,
+        value_name = "SOURCETYPE",
+        long_help = "Splunk sourcetype set on --format splunk-hec events (default: datadog:log).
+
+With --format splunk-hec, each record is written as the envelope Splunk's
+HTTP Event Collector accepts: the record as the event, its timestamp as the
+event time in epoch seconds, its host, source ddog, and the sourcetype and
+--splunk-index. HEC accepts the lines concatenated, so the output can be
+posted as is.
+
+Examples:
+  ddog logs search \"service:auth\" --from now-1d --format splunk-hec \\
+    --splunk-sourcetype datadog:auth --splunk-index security > events.ndjson
+  curl -H \"Authorization: Splunk $HEC_TOKEN\" \\
+    https://splunk:8088/services/collector/event --data-binary @events.ndjson"
+    )]
+    pub splunk_sourcetype: Option<String>,
+
+    /// Splunk index of --format splunk-hec events (default: the HEC token's)
+    #[arg(long, global = true, value_name = "INDEX")]
+    pub splunk_index: Option<String>,
+
+    /// Records per --sink or --push request
+    #[arg(
+        long,
+        global = true,
+        value_name = "N",
+        default_value_t = DEFAULT_BATCH_SIZE,
+        requires = "delivery",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub batch_size: u64,
+
+    /// Skip the client-side syntax check run before logs and spans searches
+    #[arg(
+        long,
+        global = true,
+        long_help = "Skip the client-side syntax check run before logs and spans searches.
+
+By default, search queries are checked with the same rules as `ddog query lint`
+before any API call: errors such as unbalanced parentheses abort the search
+(exit code 4), and warnings such as lowercase `or` are printed to stderr.
+Use this flag if the check rejects a query Datadog accepts."
+    )]
+    pub no_validate: bool,
+
+    /// Write nothing to stderr but errors: no progress lines, notices, or warnings
+    #[arg(
+        long,
+        global = true,
+        long_help = "Write nothing to stderr but errors (and --verbose output), for cron jobs
+where any stderr output sends mail. Silences:
+
+  - the progress line of long searches
+  - notices, such as the --sink and --output delivery summary
+  - warnings, as with --no-warnings
+
+While `logs search` or `spans search` writes to a file or pipe and stderr is a
+terminal, the progress line shows the pages fetched, records written, elapsed
+time, and an estimate of the time left, based on how much of the time range
+the results have covered so far (or how close they are to --limit):
+
+  12 pages, 11734 records, 0:42 elapsed, 38% done, ~1:08 left
+
+The line is erased when the search ends. It is never shown with --verbose.
+
+Examples:
+  ddog logs search \"status:error\" --last 1h --quiet > errors.ndjson"
+    )]
+    pub quiet: bool,
+
+    /// Exit with code 14 when the command writes no records
+    #[arg(
+        long,
+        global = true,
+        long_help = "Fail with exit code 14 when the command succeeds but writes no records,
+for scripts that expect at least one match, such as verifying that a deploy
+was logged. Leading --emit-meta records don't count as results. Outputs that
+aren't records, such as logs schema, --dot graphs, and trace diagrams,
+count as results when they describe at least one record.
+
+Examples:
+  ddog logs search \"service:api deploy finished\" --last 15m --limit 1 --fail-if-empty
+  ddog spans search \"service:api env:staging\" --last 1h --fail-if-empty > /dev/null"
+    )]
+    pub fail_if_empty: bool,
+
+    /// Don't write warnings to stderr (partial results, query lint warnings)
+    #[arg(
+        long,
+        global = true,
+        long_help = "Don't write warnings to stderr: results cut short by --limit, query lint
+warnings, and records that couldn't be added to the history or audit log.
+Errors, notices, and the progress line are still shown; --quiet silences
+those too.
+
+Examples:
+  ddog logs patterns \"status:error\" --last 1d --no-warnings"
+    )]
+    pub no_warnings: bool,
+
+    /// Add the Datadog request ID of each record's page to it as _request_id
+    #[arg(
+        long,
+        global = true,
+        long_help = "Add the ID Datadog assigned the API request that fetched each record to the
+record, as a _request_id field, so a support ticket can quote the exact request:
+
+  {\"id\":\"AQAAAY...\",\"type\":\"log\",\"attributes\":{...},\"_request_id\":\"3f9c...\"}
+
+Request IDs are always included in API error messages and, with -v, in the
+trace line of each request.
+
+Examples:
+  ddog logs search \"status:error\" --stamp-request-id | jq -r ._request_id | sort -u"
+    )]
+    pub stamp_request_id: bool,
+
+    /// Record every API request and response to a cassette file
+    #[arg(
+        long,
+        global = true,
+        value_name = "FILE",
+        conflicts_with = "replay",
+        long_help = "Record every API request and response to a JSON cassette file, replacing it.
+
+Request headers, which carry the API and application keys, are never saved,
+and the keys are replaced with REDACTED wherever else they appear. Replay the
+file later with --replay.
+
+Examples:
+  ddog logs search \"status:error\" --from now-15m --record errors.json
+  ddog logs search \"status:error\" --replay errors.json"
+    )]
+    pub record: Option<PathBuf>,
+
+    /// Answer API requests from a cassette file instead of Datadog
+    #[arg(
+        long,
+        global = true,
+        value_name = "FILE",
+        long_help = "Answer API requests from a cassette file written by --record, without
+credentials or network access. Requests are matched by method and path, in
+the order they were recorded; query strings and bodies are not compared.
+
+DDOG_CASSETTE=FILE does the same as --replay when FILE exists, and as
+--record when it doesn't."
+    )]
+    pub replay: Option<PathBuf>,
+
+    /// Reuse cached responses to identical queries for this long
+    #[arg(
+        long,
+        global = true,
+        value_name = "DURATION",
+        default_value = DEFAULT_CACHE_TTL,
+        value_parser = duration_seconds,
+        long_help = "Answer a query from the response saved for the identical request within
+this long, instead of asking Datadog again (e.g., 30s, 5m, 1h). Responses are
+saved in ~/.cache/ddog/responses (or $XDG_CACHE_HOME/ddog/responses).
+
+A request is identical when its endpoint, query, time range, and page cursor
+all match, for the same site and API key. Only searches, aggregations, and
+metric queries over an absolute time range that has already ended are cached:
+relative ranges such as --last 1h or --from now-15m cover a different window
+on every run, so they always reach Datadog.
+
+Examples:
+  ddog logs search \"service:api\" --from 2024-01-15T10:00:00Z --to 2024-01-15T11:00:00Z | jq .
+  ddog logs search \"service:api\" --from 2024-01-15T10:00:00Z --to 2024-01-15T11:00:00Z --cache-ttl 1h"
+    )]
+    pub cache_ttl: i64,
+
+    /// Always ask Datadog, neither reading nor saving cached responses
+    #[arg(long, global = true, conflicts_with = "cache_ttl")]
+    pub no_cache: bool,
+
+    /// Ask for uncompressed API responses
+    #[arg(
+        long,
+        global = true,
+        long_help = "Ask Datadog for uncompressed responses.
+
+Responses are requested gzip or zstd compressed (Accept-Encoding: gzip, zstd)
+and decompressed as they arrive, which makes large log pages several times
+faster to download. Turn it off to read raw responses in a debugging proxy.
+
+Examples:
+  ddog logs search \"service:api\" --no-compression
+  ddog spans search \"service:web\" --limit 1 --no-compression"
+    )]
+    pub no_compression: bool,
+
+    /// How failures are reported on stderr: text (default) or json
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        default_value_t = ErrorFormat::Text,
+        long_help = "How failures are reported on stderr.
+
+  text   Error: <message> (default)
+  json   One JSON object, for orchestration tools:
+
+         {\"code\":12,\"kind\":\"rate_limited\",\"message\":\"Rate limited: ...\",
+          \"retryable\":true,\"rate_limit_reset\":12}
+
+code is the exit code and kind names the error (auth, forbidden,
+rate_limited, api, invalid_query, config, io, serialization, drift,
+truncated, check_failed, interrupted).
+retryable is true for rate limits, 5xx responses, and network failures;
+rate_limit_reset is the seconds until a rate limit resets, or null.
+
+Examples:
+  ddog logs search \"status:error\" --error-format json 2> error.json"
+    )]
+    pub error_format: ErrorFormat,
 
     #[command(subcommand)]
     pub domain: Domain,
 }
 
+/// How failures are reported on stderr.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ErrorFormat {
+    /// Error: <message>
+    Text,
+    /// One JSON object with the exit code, kind, and message
+    Json,
+}
+
 /// Available domains for querying Datadog.
 #[derive(Subcommand, Debug)]
 pub enum Domain {
@@ -65,4 +726,254 @@ pub enum Domain {
         #[command(subcommand)]
         action: MetricsAction,
     },
+
+    /// Monitors domain - alert history and episodes
+    Monitors {
+        #[command(subcommand)]
+        action: MonitorsAction,
+    },
+
+    /// Hosts domain - agent restarts and reporting gaps
+    Hosts {
+        #[command(subcommand)]
+        action: HostsAction,
+    },
+
+    /// Drift domain - live configuration against a desired-state bundle
+    Drift {
+        #[command(subcommand)]
+        action: DriftAction,
+    },
+
+    /// On-call handoff - Markdown summary of a team's shift
+    #[command(
+        long_about = "Summarize a team's shift as Markdown for the on-call handoff doc.
+
+Gathers, for the --since window up to now:
+
+  Triggered monitors   Monitors tagged team:<TEAM> that alerted, with their
+                       episode counts (see `ddog monitors history`)
+  Incidents            Incidents for the team declared in the window
+  New error patterns   Top 5 error log patterns for the team's services that
+                       did not occur in the preceding window of equal length
+  SLO burn             SLOs tagged team:<TEAM> below target or burning error
+                       budget faster than the target allows
+
+The team's services come from the Service Catalog; without any, error logs
+are matched on the team:<TEAM> tag instead. A section whose API call fails
+says so rather than failing the whole summary.
+
+Output Format:
+  Markdown, written to stdout regardless of --format.
+
+Examples:
+  # Summary of the last 24 hours
+  ddog handoff --team payments
+
+  # Weekend handoff, copied to the clipboard
+  ddog handoff --team payments --since 3d | pbcopy"
+    )]
+    Handoff {
+        /// How far back the shift goes (e.g. 12h, 24h, 3d)
+        #[arg(long, value_name = "DURATION", default_value = "24h", value_parser = parse_last)]
+        since: String,
+
+        /// Team to summarize, as used in team:<TEAM> tags
+        #[arg(long, value_name = "TEAM")]
+        team: String,
+    },
+
+    /// Bulk export - run the searches of a spec file into files
+    #[command(long_about = "Run the searches listed in an export spec into files.
+
+The spec is a TOML file with a [[job]] table per search:
+
+  concurrency = 4                   # jobs run at once (default: 4)
+  manifest = \"out/manifest.json\"   # default: manifest.json
+
+  [[job]]
+  id = \"api-errors\"                 # unique name
+  domain = \"logs\"                   # logs, spans, or metrics (default: logs)
+  query = \"service:api status:error\"
+  from = \"now-1d\"                   # default: now-1h
+  to = \"now\"                        # default: now
+  output = \"out/api-errors.ndjson\"
+  format = \"ndjson\"                 # ndjson or text (default: ndjson)
+  limit = 0                         # records (or metric points); 0 for all
+
+Relative paths are relative to the spec's directory. Logs and spans are
+written as the API returns them, metrics as one {metric, scope, tags, ts,
+value} row per point.
+
+Each finished job is recorded in a checkpoint file next to the manifest
+(manifest.json.checkpoint). Running the same spec again skips jobs that
+finished with the same definition, so a failed or interrupted export
+resumes where it stopped; --restart runs every job again. Once every job
+has run, the manifest lists them all with their record counts, and the
+checkpoint is removed if none failed.
+
+Output Format:
+  A {id, domain, query, from, to, output, status, records, finished_at, error}
+  record per job as it ends; status is done, resumed, or failed.
+
+Examples:
+  # Run an export
+  ddog export --spec exports/incident-1234.toml
+
+  # Run every job again, ignoring the checkpoint
+  ddog export --spec exports/incident-1234.toml --restart")]
+    Export {
+        /// Export spec (TOML)
+        #[arg(long, value_name = "FILE")]
+        spec: PathBuf,
+
+        /// Ignore the checkpoint and run every job again
+        #[arg(long)]
+        restart: bool,
+    },
+
+    /// Raw API - call endpoints without a dedicated command
+    Api {
+        #[command(subcommand)]
+        action: ApiAction,
+    },
+
+    /// Interactive session - run searches with sticky settings
+    #[command(long_about = "Start an interactive session for exploratory searches.
+
+Each line is a logs or spans query, run with the session's sticky settings
+(domain, time range, limit, and log indexes), or a backslash command:
+
+  \\domain logs|spans     Switch the search domain (default: logs)
+  \\time FROM [TO]        Set the time range (default: now-1h to now)
+  \\last DURATION         Set the time range to the last DURATION
+  \\limit N               Fetch at most N results per query (default: 100)
+  \\indexes A,B           Log indexes to search (default: *)
+  \\more                  Show the next page of the last result (or press Enter)
+  \\ndjson [FILE]         Export the last result as NDJSON (stdout by default)
+  \\settings              Show the current settings
+  \\help                  List commands
+  \\quit                  Leave the session (or Ctrl-D)
+
+Results are shown as text, 20 at a time. Relative times are re-evaluated for
+every query, so `now-1h` always means the last hour. Saved queries (@NAME),
+workspace defaults, and the query lint apply as in `logs search`. Ctrl-C
+cancels a running query.
+
+Examples:
+  ddog repl
+  logs now-1h..now> \\last 15m
+  logs now-15m..now> service:api status:error
+  logs now-15m..now> \\ndjson errors.ndjson")]
+    Repl,
+
+    /// Query tools - check search syntax before running it
+    Query {
+        #[command(subcommand)]
+        action: QueryAction,
+    },
+
+    /// Query history - list and rerun earlier queries
+    History {
+        #[command(subcommand)]
+        action: HistoryAction,
+    },
+
+    /// Docs - generate reference documentation such as man pages
+    Docs {
+        #[command(subcommand)]
+        action: DocsAction,
+    },
+
+    /// Print the help of every command, for embedding in documentation
+    #[command(
+        long_about = "Print the full help of ddog and every subcommand in one document.
+
+Commands are listed parents first, each under a heading naming it
+(ddog logs search), followed by the same text as `--help`, so internal
+documentation can embed a generated reference that stays current.
+
+Examples:
+  ddog help-all > docs/ddog-reference.txt
+  ddog help-all | less"
+    )]
+    HelpAll,
+
+    /// Bench - time repeated searches end to end
+    Bench {
+        #[command(subcommand)]
+        action: BenchAction,
+    },
+
+    /// Telemetry - report opt-in local usage metrics
+    Telemetry {
+        #[command(subcommand)]
+        action: TelemetryAction,
+    },
+
+    /// Self-update - replace ddog with the latest release
+    #[command(
+        long_about = "Replace the running ddog binary with the latest GitHub release.
+
+Looks up the latest release of tmcinerney/ddog, downloads the archive for
+this platform (Linux x86_64, macOS x86_64 or Apple Silicon), checks it
+against the release's SHA-256 checksum file, and swaps the binary in place.
+Nothing is replaced if the checksum is missing or doesn't match, or if this
+version is already the latest. Updating a binary in a system directory such
+as /usr/local/bin needs permission to write it, e.g. sudo.
+
+Binaries installed with cargo or a package manager are better updated with
+those tools.
+
+Output Format:
+  One line with the outcome:
+  {\"current\":\"1.1.0\",\"latest\":\"1.2.0\",\"updated\":true,\"path\":\"/usr/local/bin/ddog\"}
+
+Examples:
+  # Is a newer version out?
+  ddog self-update --check
+
+  sudo ddog self-update"
+    )]
+    SelfUpdate {
+        /// Only report whether a newer release exists
+        #[arg(long)]
+        check: bool,
+    },
+
+    /// Shell completions - print the setup script for a shell
+    #[command(long_about = "Print the shell completion setup script for SHELL.
+
+By default the script asks ddog for candidates as you type, so besides
+subcommands and flags it completes values specific to your org and
+checkout:
+
+  --indexes     Log index names, fetched from Datadog with the current
+                credentials (skipped if they are missing or Datadog is slow)
+  @NAME         Saved queries from `ddog query save` and .ddog.toml
+
+With --static the script is generated once and only completes subcommands,
+flags, and fixed values, without calling ddog.
+
+Examples:
+  # Bash (~/.bashrc)
+  source <(ddog completions bash)
+
+  # Zsh (~/.zshrc)
+  source <(ddog completions zsh)
+
+  # Fish
+  ddog completions fish > ~/.config/fish/completions/ddog.fish
+
+  # PowerShell ($PROFILE)
+  ddog completions powershell | Out-String | Invoke-Expression")]
+    Completions {
+        /// Shell to print the script for
+        #[arg(value_name = "SHELL")]
+        shell: Shell,
+
+        /// Generate a static script without dynamic values
+        #[arg(long = "static")]
+        static_script: bool,
+    },
 }
@@ -0,0 +1,72 @@
+//! Bench domain command actions.
+
+use clap::Subcommand;
+use ddog::client::{MAX_LOGS_PAGE_SIZE, SEARCH_PAGE_SIZE};
+
+use super::shared::{LogsSource, QueryFilters, TimeRange};
+
+/// Available actions for the bench domain.
+#[derive(Subcommand, Debug)]
+pub enum BenchAction {
+    /// Time a logs search end to end over several runs
+    #[command(
+        long_about = "Run the same logs search several times and summarize how long it took.
+
+Each run reads the search's results (up to --limit) as `logs search` would,
+and drops them. The report covers the time until every run's first and last
+record, the time to fetch and parse each page, and the records read per
+second, as distributions over the runs. Compare sites, indexes, and storage
+tiers, or one version of ddog with another.
+
+Runs always reach Datadog: the response cache is skipped. Runs count against
+the API rate limit like any search.
+
+Output Format:
+  One line summarizing the runs, times in milliseconds:
+  {\"query\":\"service:api\",\"from\":\"now-1h\",\"to\":\"now\",\"iterations\":5,\"records\":1000,\"pages\":5,
+   \"latency_ms\":{\"min\":612.4,\"p50\":701.9,\"p95\":980.2,\"mean\":744.1,\"max\":980.2},
+   \"first_record_ms\":{...},\"page_ms\":{...},\"records_per_sec\":{...}}
+  Use -v to also log each run's timings.
+
+Examples:
+  ddog bench logs \"service:api\" --from now-1h --iterations 5
+
+  # Compare storage tiers
+  ddog bench logs \"service:api\" --last 1d --storage-tier flex
+  ddog bench logs \"service:api\" --last 1d --storage-tier indexes
+
+  # Throughput of large pages over a full export
+  ddog bench logs \"*\" --last 15m --limit 0 --page-size 5000"
+    )]
+    Logs {
+        /// Datadog query string (e.g., "service:api AND status:error")
+        query: String,
+
+        #[command(flatten)]
+        time_range: TimeRange,
+
+        #[command(flatten)]
+        source: LogsSource,
+
+        #[command(flatten)]
+        filters: QueryFilters,
+
+        /// Times to run the search
+        #[arg(long, value_name = "N", default_value_t = 5,
+            value_parser = clap::value_parser!(u32).range(1..=100))]
+        iterations: u32,
+
+        /// Records each run reads (0 for every match)
+        #[arg(short, long, value_name = "N", default_value_t = 1000)]
+        limit: u64,
+
+        /// Records to fetch per API request (1 to 5000)
+        #[arg(
+            long,
+            value_name = "N",
+            default_value_t = SEARCH_PAGE_SIZE,
+            value_parser = clap::value_parser!(i32).range(1..=i64::from(MAX_LOGS_PAGE_SIZE))
+        )]
+        page_size: i32,
+    },
+}
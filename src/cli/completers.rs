@@ -0,0 +1,70 @@
+//! Dynamic shell completion values.
+//!
+//! These run when the shell asks `ddog` for candidates (see `ddog completions`),
+//! so they must be quick and never fail loudly: any error simply yields no
+//! candidates and the shell falls back to plain completion.
+
+use clap_complete::CompletionCandidate;
+use ddog::client::ResourcesClient;
+use ddog::config;
+use ddog::saved_queries::{SavedQueries, saved_queries_path};
+use ddog::workspace::Workspace;
+use std::time::Duration;
+
+/// How long to wait for Datadog before giving up on index names.
+const INDEX_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Log index names in the org, for `--indexes`.
+///
+/// Uses the workspace's profile credentials when a `.ddog.toml` is found.
+pub fn log_indexes() -> Vec<CompletionCandidate> {
+    let mut candidates = vec![CompletionCandidate::new("*").help(Some("All indexes".into()))];
+    candidates.extend(
+        fetch_log_indexes()
+            .unwrap_or_default()
+            .into_iter()
+            .map(CompletionCandidate::new),
+    );
+    candidates
+}
+
+fn fetch_log_indexes() -> Option<Vec<String>> {
+    let workspace = Workspace::discover(&std::env::current_dir().ok()?).ok()?;
+    let user_config = config::load_user_config().ok()?;
+    let profile = match workspace.and_then(|ws| ws.profile) {
+        Some(name) => Some(user_config.profile(&name).ok()?),
+        None => None,
+    };
+    let client = ResourcesClient::new(config::load_config_for_profile(profile).ok()?);
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .ok()?;
+    runtime
+        .block_on(tokio::time::timeout(
+            INDEX_TIMEOUT,
+            client.log_index_names(),
+        ))
+        .ok()?
+        .ok()
+}
+
+/// Saved query references (`@name`) from the workspace and the saved queries file.
+pub fn saved_queries() -> Vec<CompletionCandidate> {
+    let mut candidates = Vec::new();
+    if let Some(workspace) = std::env::current_dir()
+        .ok()
+        .and_then(|dir| Workspace::discover(&dir).ok().flatten())
+    {
+        candidates.extend(workspace.queries.into_iter().map(|(name, query)| {
+            CompletionCandidate::new(format!("@{}", name)).help(Some(query.into()))
+        }));
+    }
+    if let Some(saved) = saved_queries_path().and_then(|path| SavedQueries::load(&path).ok()) {
+        candidates.extend(saved.queries.into_iter().map(|(name, saved)| {
+            CompletionCandidate::new(format!("@{}", name)).help(Some(saved.query.into()))
+        }));
+    }
+    candidates
+}
@@ -0,0 +1,32 @@
+//! Docs domain command actions.
+
+use clap::Subcommand;
+use std::path::PathBuf;
+
+/// Available actions for the docs domain.
+#[derive(Subcommand, Debug)]
+pub enum DocsAction {
+    /// Generate man pages for every command
+    #[command(
+        long_about = "Generate a man page for ddog and one for every subcommand.
+
+Pages are named after the command path (ddog.1, ddog-logs.1,
+ddog-logs-search.1, ...) and written to DIR, which is created if needed.
+Existing pages are overwritten, so the command can run on every release.
+
+Output Format:
+  The path of each page written, one per line on stderr.
+
+Examples:
+  ddog docs man --dir man
+  man ./man/ddog-logs-search.1
+
+  # Install for the current user
+  ddog docs man --dir ~/.local/share/man/man1"
+    )]
+    Man {
+        /// Directory to write the pages to
+        #[arg(long, value_name = "DIR", default_value = "man")]
+        dir: PathBuf,
+    },
+}
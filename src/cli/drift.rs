@@ -0,0 +1,51 @@
+//! Drift domain command actions.
+
+use clap::Subcommand;
+use std::path::PathBuf;
+
+/// Available actions for the drift domain.
+#[derive(Subcommand, Debug)]
+pub enum DriftAction {
+    /// Compare live configuration against a desired-state bundle
+    #[command(
+        long_about = "Compare live configuration against a desired-state bundle.
+
+The bundle is a directory of JSON definitions, one resource per file:
+
+  monitors/*.json     Monitors, identified by \"id\"
+  slos/*.json         SLOs, identified by \"id\"
+  dashboards/*.json   Dashboards, identified by \"id\"
+  indexes/*.json      Log indexes, identified by \"name\"
+
+Each resource is fetched from Datadog and compared field by field with its
+definition. Only the fields a definition contains are checked, so it can be
+a full API export or just the settings that must not change. Fields Datadog
+manages itself (created, modified, overall_state, ...) are ignored.
+
+Output Format:
+  Each line contains one difference:
+  {\"resource\":\"monitor\",\"id\":\"123\",\"file\":\"bundle/monitors/high-cpu.json\",
+   \"status\":\"changed\",\"path\":\"/options/thresholds/critical\",
+   \"expected\":90,\"actual\":95.0}
+
+  status is \"changed\" for a field whose live value differs (actual is null
+  if the field is unset) or \"missing\" for a resource that doesn't exist,
+  with the whole definition as expected.
+
+Exit Codes:
+  0   Live configuration matches the bundle
+  8   Drift detected (see the records on stdout)
+
+Examples:
+  # Fail a CI job on drift
+  ddog drift check bundle/
+
+  # Drift per resource
+  ddog drift check bundle/ | jq -s 'group_by(.file) | map({file: .[0].file, fields: map(.path)})'"
+    )]
+    Check {
+        /// Bundle directory with monitors/, slos/, dashboards/, and indexes/ subdirectories
+        #[arg(value_name = "BUNDLE")]
+        bundle: PathBuf,
+    },
+}
@@ -0,0 +1,53 @@
+//! History domain command actions.
+
+use clap::Subcommand;
+
+/// Available actions for the history domain.
+#[derive(Subcommand, Debug)]
+pub enum HistoryAction {
+    /// List recently executed queries
+    #[command(long_about = "List recently executed logs, spans, and metrics queries.
+
+Every query ddog runs is recorded in history.ndjson in the state directory
+($XDG_STATE_HOME/ddog or ~/.local/state/ddog), keeping the last 1000.
+Set DDOG_NO_HISTORY=1 to stop recording.
+
+Output Format:
+  Each line contains one query, oldest first:
+  {\"n\":41,\"timestamp\":\"2024-01-15T10:00:00Z\",\"domain\":\"logs\",
+   \"query\":\"service:api status:error\",\"from\":\"2024-01-15T09:00:00+00:00\",
+   \"to\":\"now\",\"results\":120,\"args\":[\"logs\",\"search\",\"service:api status:error\",\"--last\",\"1h\"]}
+
+  n is the number to pass to `ddog history rerun`.
+
+Examples:
+  ddog history list
+  ddog history list --limit 50 --format text
+
+  # Yesterday's queries
+  ddog history list --limit 1000 | jq 'select(.timestamp | startswith(\"2024-01-14\"))'")]
+    List {
+        /// Number of most recent queries to list
+        #[arg(long, default_value = "20")]
+        limit: usize,
+    },
+
+    /// Run a query from the history again
+    #[command(long_about = "Run a query from the history again.
+
+Re-executes the recorded command line with the current binary, so relative
+times such as --last 1h are evaluated from now. Use `ddog history list` to
+find the query's number.
+
+Output Format:
+  Whatever the rerun command writes.
+
+Examples:
+  ddog history rerun 41
+  ddog history rerun 41 | jq .message")]
+    Rerun {
+        /// Number of the query, as shown by `ddog history list`
+        #[arg(value_name = "N")]
+        n: usize,
+    },
+}
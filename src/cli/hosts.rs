@@ -0,0 +1,62 @@
+//! Hosts domain command actions.
+
+use clap::Subcommand;
+
+use super::shared::TimeRangeRelativeOnly;
+
+/// Available actions for the hosts domain.
+#[derive(Subcommand, Debug)]
+pub enum HostsAction {
+    /// List hosts with repeated agent restarts or reporting gaps
+    #[command(
+        long_about = "List hosts with repeated agent restarts or reporting gaps.
+
+Combines three sources per host:
+
+  restarts      Agent start events (\"Datadog agent (v. ...) started on <host>\")
+  gaps          Stretches where datadog.agent.running stopped reporting,
+                at 5-minute resolution (see `ddog metrics gaps`)
+  metadata      Whether the host is up and its agent version, from the
+                host list
+
+Hosts are listed when they have at least --min-flaps restarts or
+--min-flaps gaps, most unstable first: by restarts plus gaps, then by time
+spent not reporting. Aliases such as cloud instance IDs are resolved to the
+host name.
+
+⚠️  Time Format Limitation:
+  Hosts commands do NOT support ISO8601 timestamps.
+  Use relative times (now-7d) or Unix timestamps only.
+
+Output Format:
+  Each line contains one host:
+  {\"rank\":1,\"host\":\"web-3\",\"restarts\":6,\"gaps\":4,\"gap_seconds\":2700,
+   \"ongoing_gap\":false,\"last_restart\":\"2024-01-15T10:02:11.000Z\",
+   \"up\":true,\"agent_version\":\"7.50.0\"}
+
+  up and agent_version are absent for hosts missing from the host list,
+  such as hosts that no longer exist.
+
+Examples:
+  # Unstable hosts over the last week
+  ddog hosts flaps --from now-7d
+
+  # Production hosts only
+  ddog hosts flaps --from now-7d --scope env:prod
+
+  # Hosts that are down right now
+  ddog hosts flaps --from now-1d --min-flaps 1 | jq 'select(.ongoing_gap)'"
+    )]
+    Flaps {
+        #[command(flatten)]
+        time_range: TimeRangeRelativeOnly,
+
+        /// Only consider hosts with these tags (comma-separated, e.g. env:prod,team:infra)
+        #[arg(long, value_name = "TAGS")]
+        scope: Option<String>,
+
+        /// Restarts or gaps needed for a host to be listed
+        #[arg(long, value_name = "N", default_value = "2")]
+        min_flaps: usize,
+    },
+}
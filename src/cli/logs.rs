@@ -1,8 +1,16 @@
 //! Logs domain command actions.
 
 use clap::Subcommand;
+use clap_complete::ArgValueCandidates;
+use ddog::client::Measure;
+use ddog::diff::{Window, parse_window};
+use std::path::PathBuf;
 
-use super::shared::{Pagination, TimeRange};
+use super::completers;
+use super::shared::{
+    Aggregation, Anonymization, FacetDiscovery, LogsSource, OwnerRouting, Pagination, QueryFilters,
+    TimeRange, Watch, parse_http_url,
+};
 
 /// Available actions for the logs domain.
 #[derive(Subcommand, Debug)]
@@ -33,14 +41,30 @@ Examples:
   # Search specific indexes
   ddog logs search \"env:production\" --indexes main,web
 
+  # Build the query from flags
+  ddog logs search --service api --env prod --status error --attr http.status_code=500
+
   # Complex query with filtering
   ddog logs search \"service:api\" | jq 'select(.attributes.duration > 1000)'
 
+  # Browse results interactively
+  ddog logs search \"status:error\" --last 15m --interactive
+
+  # Count matching logs without fetching them
+  ddog logs search \"service:api status:error\" --last 15m --count --raw
+
+  # Open the same search in the Log Explorer
+  ddog logs search \"service:api status:error\" --last 4h --open
+
 Documentation:
   https://docs.datadoghq.com/logs/explorer/search_syntax/")]
     Search {
         /// Datadog query string (e.g., "service:api AND @http.status_code:500")
-        #[arg(long_help = "Datadog query string using Datadog's log search syntax.
+        #[arg(
+            default_value = "*",
+            add = ArgValueCandidates::new(completers::saved_queries),
+            long_help = "Datadog query string using Datadog's log search syntax.
+Defaults to \"*\", so --service/--env/--status/--attr can build the query alone.
 
 Supports:
   • Attributes: @http.status_code:500
@@ -54,7 +78,8 @@ Examples:
   \"service:api AND status:error\"
   \"@http.status_code:500\"
   \"(service:api OR service:web) AND env:prod\"
-  \"service:* -env:development\"")]
+  \"service:* -env:development\""
+        )]
         query: String,
 
         #[command(flatten)]
@@ -63,19 +88,753 @@ Examples:
         #[command(flatten)]
         pagination: Pagination,
 
+        #[command(flatten)]
+        filters: QueryFilters,
+
+        #[command(flatten)]
+        routing: OwnerRouting,
+
+        #[command(flatten)]
+        anonymization: Anonymization,
+
+        #[command(flatten)]
+        watch: Watch,
+
+        #[command(flatten)]
+        source: LogsSource,
+
+        /// Run one search per line of FILE ("-" for stdin) instead of QUERY
+        #[arg(
+            long,
+            value_name = "FILE",
+            conflicts_with_all = ["query", "max_pages", "max_bytes", "sample", "watch"],
+            long_help = "Run one search per line of FILE (\"-\" for stdin) instead of QUERY.
+
+Each line is either a bare query or an NDJSON job spec with its own id and
+time range (defaulting to --from/--to/--last):
+
+  service:api status:error
+  {\"id\": \"tenant-a\", \"query\": \"@tenant:a status:error\", \"from\": \"now-1d\"}
+
+Blank lines and lines starting with # are skipped. Every record gets a
+query_id field naming the job that returned it: the job's id, or its line
+number. --limit applies to each job, and the query builder flags, workspace
+defaults, and saved queries (@name) apply to every job.
+
+Examples:
+  cat tenants.ndjson | ddog logs search --batch - --concurrency 4
+  ddog logs search --batch nightly.txt --last 1d --limit 0 > report.ndjson"
+        )]
+        batch: Option<PathBuf>,
+
+        /// Browse results in a terminal UI instead of writing them
+        #[arg(
+            long,
+            conflicts_with_all = ["batch", "route_by_owner", "notify_owners", "max_pages", "max_bytes", "sample", "watch"],
+            long_help = "Browse results in a terminal UI instead of writing them to stdout.
+
+Loads up to --limit logs, then shows one line per log:
+
+  typing          Fuzzy-filter the loaded logs (space-separated terms must all match)
+  Up/Down, PgUp/PgDn
+                  Move the selection (or scroll an expanded log)
+  Enter           Expand the selected log's full JSON, or collapse it
+  Ctrl-T          Copy the log's trace_id to the clipboard (OSC 52)
+  Ctrl-O          Open the log's trace, or the log itself, in the Datadog UI
+  Esc, Ctrl-C     Collapse the expanded log, or quit
+
+Requires a Unix terminal."
+        )]
+        interactive: bool,
+
+        /// Open the search in the Datadog Log Explorer instead of running it
+        #[arg(
+            long,
+            conflicts_with_all = ["batch", "interactive", "route_by_owner", "notify_owners", "sample", "watch", "storage_tier"],
+            long_help = "Open the search in the Datadog Log Explorer instead of running it.
+
+The URL carries the query (after --service/--env/... flags, saved queries, and
+workspace defaults are applied), the time range with relative times fixed to
+now, and the --indexes other than *. It is also printed to stderr, so it can
+be copied when no browser can be started (e.g., over SSH)."
+        )]
+        open: bool,
+
+        /// Print only the number of matching logs instead of the logs
+        #[arg(
+            long,
+            conflicts_with_all = ["batch", "interactive", "open", "route_by_owner", "notify_owners", "max_pages", "max_bytes", "sample", "watch", "storage_tier"],
+            long_help = "Print only the number of matching logs instead of the logs.
+
+Counts with the aggregate endpoint in one request, however many logs match,
+and writes a single record:
+
+  {\"query\": \"service:api status:error\", \"from\": \"now-15m\", \"to\": \"now\", \"count\": 42}
+
+With --raw, writes just the number, for alert scripts:
+
+  [ \"$(ddog logs search 'service:api status:error' --last 15m --count --raw)\" -gt 100 ] && page-oncall"
+        )]
+        count: bool,
+
+        /// With --count, write the bare number instead of a record
+        #[arg(long, requires = "count")]
+        raw: bool,
+
+        /// Number of --batch searches to run at once (1-16)
+        #[arg(
+            long,
+            default_value = "1",
+            requires = "batch",
+            value_parser = clap::value_parser!(u8).range(1..=16)
+        )]
+        concurrency: u8,
+    },
+
+    /// Count logs over time grouped by a facet, optionally as a heatmap
+    #[command(
+        long_about = "Count logs matching a query per time bucket and facet value.
+
+Uses the logs aggregation API, so counts cover every matching log rather than
+a page of results. Only the --top facet values by volume are kept.
+
+Output Format:
+  Each line contains one time bucket for one facet value:
+  {\"timestamp\":\"2024-01-15T10:00:00.000Z\",\"group\":\"api\",\"count\":42.0}
+
+  With --chart heatmap, a shaded grid is printed instead, one row per facet
+  value ordered by when it first saw logs:
+
+  service  01-15 10:00        01-15 11:50
+  checkout ··░▒▓█████▓▓        1873
+  api      ····░▒▓███▓▒        1210
+
+Examples:
+  # Which service started erroring first?
+  ddog logs aggregate \"status:error\" --group-by service --interval 10m --chart heatmap
+
+  # Status codes per minute as NDJSON
+  ddog logs aggregate \"service:api\" --group-by @http.status_code --interval 1m --last 1h
+
+  # Heatmap as an HTML page
+  ddog logs aggregate \"status:error\" --group-by service --chart heatmap --html errors.html
+
+  # Records to a file, sparklines to the terminal
+  ddog logs aggregate \"status:error\" --group-by service --interval 1m --viz sparkline > errors.ndjson"
+    )]
+    Aggregate {
+        /// Datadog query string (e.g., "status:error")
+        #[arg(default_value = "*", add = ArgValueCandidates::new(completers::saved_queries))]
+        query: String,
+
+        #[command(flatten)]
+        time_range: TimeRange,
+
+        #[command(flatten)]
+        filters: QueryFilters,
+
+        #[command(flatten)]
+        aggregation: Aggregation,
+
+        #[command(flatten)]
+        watch: Watch,
+
+        /// Log indexes to search (comma-separated, default: all)
+        #[arg(
+            short,
+            long,
+            value_delimiter = ',',
+            default_value = "*",
+            add = ArgValueCandidates::new(completers::log_indexes)
+        )]
+        indexes: Vec<String>,
+    },
+
+    /// Discover the facets of matching logs with their top values
+    #[command(
+        long_about = "Discover which facets matching logs carry, with top values and cardinality.
+
+Samples matching logs (--sample) to find the reserved attributes (service,
+host, status, source), custom @attributes, and tag keys they carry, then
+summarizes the most common ones (--max-facets) over every matching log with
+the aggregation API: the number of distinct values and the most frequent
+values (--top).
+
+Output Format:
+  Each line contains one facet, most common in the sample first:
+  {\"facet\":\"@http.status_code\",\"coverage\":0.93,\"cardinality\":6,
+   \"top\":[{\"value\":\"200\",\"count\":9120.0},{\"value\":\"500\",\"count\":310.0}]}
+
+  coverage is the share of sampled logs carrying the facet. Custom attributes
+  that aren't declared as facets in Datadog have no cardinality or values.
+
+Examples:
+  # What can I filter checkout errors on?
+  ddog logs facets \"service:checkout status:error\" --from now-1h
+
+  # Facet names only
+  ddog logs facets \"service:checkout\" | jq -r .facet
+
+  # Larger sample, more values per facet
+  ddog logs facets \"env:prod\" --sample 2000 --top 10"
+    )]
+    Facets {
+        /// Datadog query string (e.g., "service:api")
+        #[arg(default_value = "*", add = ArgValueCandidates::new(completers::saved_queries))]
+        query: String,
+
+        #[command(flatten)]
+        time_range: TimeRange,
+
+        #[command(flatten)]
+        filters: QueryFilters,
+
+        #[command(flatten)]
+        discovery: FacetDiscovery,
+
+        /// Log indexes to search (comma-separated, default: all)
+        #[arg(
+            short,
+            long,
+            value_delimiter = ',',
+            default_value = "*",
+            add = ArgValueCandidates::new(completers::log_indexes)
+        )]
+        indexes: Vec<String>,
+    },
+
+    /// Group log messages into patterns, with a count and an example each
+    #[command(long_about = "Group the messages of matching logs into patterns.
+
+Streams the logs matching QUERY, up to --limit, and reduces each message to
+a pattern: only its first line is kept, and tokens containing a digit (IDs,
+counts, durations, addresses) are replaced by <*>, keeping a key= prefix.
+Messages sharing a pattern are counted together, which summarizes a flood of
+unfamiliar errors in a few lines.
+
+Output Format:
+  Each line contains one pattern, most frequent first, with the first
+  message seen for it:
+  {\"pattern\":\"Timeout after <*> calling <*>\",\"count\":412,
+   \"example\":\"Timeout after 3000ms calling 10.0.0.7\"}
+
+Examples:
+  # What are the api's errors about?
+  ddog logs patterns \"service:api status:error\" --from now-1h
+
+  # Every pattern over a day, from up to 50000 logs
+  ddog logs patterns --status error --last 1d --limit 50000 --top 0")]
+    Patterns {
+        /// Datadog query string (e.g., "service:api status:error")
+        #[arg(
+            default_value = "*",
+            add = ArgValueCandidates::new(completers::saved_queries)
+        )]
+        query: String,
+
+        #[command(flatten)]
+        time_range: TimeRange,
+
+        #[command(flatten)]
+        filters: QueryFilters,
+
         /// Log indexes to search (comma-separated, default: all)
         #[arg(
             short,
             long,
             value_delimiter = ',',
             default_value = "*",
-            long_help = "Log indexes to search. Specify multiple indexes separated by commas.
+            add = ArgValueCandidates::new(completers::log_indexes)
+        )]
+        indexes: Vec<String>,
+
+        /// Maximum number of logs to fetch (0 for all of them)
+        #[arg(short, long, value_name = "N", default_value = "10000")]
+        limit: u64,
+
+        /// Number of patterns to write, most frequent first (0 for all of them)
+        #[arg(long, value_name = "N", default_value = "20")]
+        top: usize,
+    },
+
+    /// Compute statistics of one field of matching logs, client-side
+    #[command(
+        long_about = "Compute statistics of one field of the logs matching a query.
+
+Streams the logs matching QUERY, up to --limit, and summarizes the values of
+--field: how many logs carry it, its number of distinct values, and for
+numeric values their min, max, average, and p50/p90/p95/p99. Computed
+client-side, so any attribute or tag works, including ones that aren't
+declared as facets or measures in Datadog.
+
+Name the field as in queries: @ and a dotted path for custom attributes
+(@duration, @http.status_code), otherwise a reserved attribute (service,
+host, status) or a tag key (env, version).
+
+Output Format:
+  One line with the summary:
+  {\"field\":\"@duration\",\"count\":980,\"missing\":20,\"cardinality\":412,\"numeric\":980,
+   \"min\":2.0,\"max\":5210.0,\"avg\":148.3,\"p50\":95.0,\"p90\":310.0,\"p95\":480.0,\"p99\":1900.0}
+
+  Statistics of numeric values are null when the field holds none.
 
 Examples:
-  --indexes main           # Search only the 'main' index
-  --indexes main,web       # Search both 'main' and 'web' indexes
-  --indexes \"*\"            # Search all indexes (default)"
+  # How slow is the api?
+  ddog logs stats \"service:api\" --field @duration --from now-1h
+
+  # How many users hit errors?
+  ddog logs stats \"status:error\" --field @usr.id --last 1d | jq .cardinality"
+    )]
+    Stats {
+        /// Datadog query string (e.g., "service:api")
+        #[arg(
+            default_value = "*",
+            add = ArgValueCandidates::new(completers::saved_queries)
+        )]
+        query: String,
+
+        /// Field to summarize (e.g., @duration, @http.status_code, service, env)
+        #[arg(long, value_name = "FIELD")]
+        field: String,
+
+        #[command(flatten)]
+        time_range: TimeRange,
+
+        #[command(flatten)]
+        filters: QueryFilters,
+
+        /// Log indexes to search (comma-separated, default: all)
+        #[arg(
+            short,
+            long,
+            value_delimiter = ',',
+            default_value = "*",
+            add = ArgValueCandidates::new(completers::log_indexes)
         )]
         indexes: Vec<String>,
+
+        /// Maximum number of logs to fetch (0 for all of them)
+        #[arg(short, long, value_name = "N", default_value = "10000")]
+        limit: u64,
+    },
+
+    /// Infer a JSON Schema of matching logs from a sample
+    #[command(
+        long_about = "Infer a JSON Schema of the logs matching a query from a sample of them.
+
+Fetches up to --sample logs matching QUERY, newest first, and writes a JSON
+Schema (draft 2020-12) describing them as `ddog logs search` or `ddog export`
+would write them, for building warehouse tables ahead of an export. Output
+transforms such as --drop-field and --flatten are applied to the sample, so
+the schema matches an export run with the same flags.
+
+For every field the schema lists the types it held (several when they
+varied, with integers folded into number when both appeared) and, for each
+object, the fields every sampled record carried as required. Strings that
+were always RFC 3339 timestamps get \"format\": \"date-time\". Fields absent
+from the sample are missing from the schema, so sample a representative
+time range.
+
+Output Format:
+  One pretty-printed JSON Schema document on stdout:
+  {
+    \"$schema\": \"https://json-schema.org/draft/2020-12/schema\",
+    \"type\": \"object\",
+    \"properties\": {\"attributes\": {\"type\": \"object\", ...}, \"id\": {\"type\": \"string\"}, ...},
+    \"required\": [\"attributes\", \"id\", \"type\"],
+    \"description\": \"Inferred from 1000 log(s)\"
+  }
+
+Examples:
+  # Schema of the api's logs
+  ddog logs schema \"service:api\" --from now-1h --sample 1000 > api-logs.schema.json
+
+  # Schema of a flattened export, for BigQuery or CSV loaders
+  ddog logs schema \"service:api\" --last 1d --flatten"
+    )]
+    Schema {
+        /// Datadog query string (e.g., "service:api")
+        #[arg(
+            default_value = "*",
+            add = ArgValueCandidates::new(completers::saved_queries)
+        )]
+        query: String,
+
+        #[command(flatten)]
+        time_range: TimeRange,
+
+        #[command(flatten)]
+        filters: QueryFilters,
+
+        /// Log indexes to search (comma-separated, default: all)
+        #[arg(
+            short,
+            long,
+            value_delimiter = ',',
+            default_value = "*",
+            add = ArgValueCandidates::new(completers::log_indexes)
+        )]
+        indexes: Vec<String>,
+
+        /// Number of logs to infer the schema from
+        #[arg(long, value_name = "N", default_value = "1000", value_parser = clap::value_parser!(u64).range(1..))]
+        sample: u64,
+    },
+
+    /// Rank the most frequent values of a facet
+    #[command(
+        long_about = "Rank the most frequent values of a facet among matching logs.
+
+Counts matching logs per value of FACET over the whole time range with the
+aggregation API and writes the values largest first. With --by unique:<facet>
+values are ranked by the number of distinct values of another facet instead,
+e.g. which services affected the most users.
+
+Output Format:
+  Each line contains one value:
+  {\"rank\":1,\"facet\":\"service\",\"value\":\"checkout\",\"count\":1834.0,\"share\":0.61}
+
+  share is the value's share of all matching logs, and is only present for
+  --by count. Logs without the facet are counted as \"N/A\".
+
+  With --format text, each value is one row of a table:
+  #1  checkout  1834  61.0%
+
+Examples:
+  # Which services are erroring?
+  ddog logs top service --query \"status:error\" --from now-1h
+
+  # Top 20 endpoints returning 5xx, as a table
+  ddog logs top @http.url_details.path --query \"@http.status_code:>=500\" --limit 20 --format text
+
+  # Services affecting the most users
+  ddog logs top service --query \"status:error\" --by unique:@usr.id"
+    )]
+    Top {
+        /// Facet to rank values of (e.g., service, host, @http.status_code)
+        facet: String,
+
+        /// Datadog query string (e.g., "status:error")
+        #[arg(
+            short,
+            long,
+            default_value = "*",
+            add = ArgValueCandidates::new(completers::saved_queries)
+        )]
+        query: String,
+
+        /// What to rank values by: count, or unique:<facet> (e.g. unique:@usr.id)
+        #[arg(long, value_name = "MEASURE", default_value = "count")]
+        by: Measure,
+
+        /// Number of values to return
+        #[arg(long, value_name = "N", default_value = "10")]
+        limit: i64,
+
+        #[command(flatten)]
+        time_range: TimeRange,
+
+        #[command(flatten)]
+        filters: QueryFilters,
+
+        /// Log indexes to search (comma-separated, default: all)
+        #[arg(
+            short,
+            long,
+            value_delimiter = ',',
+            default_value = "*",
+            add = ArgValueCandidates::new(completers::log_indexes)
+        )]
+        indexes: Vec<String>,
+    },
+
+    /// Fail if the number of matching logs is outside a threshold
+    #[command(
+        long_about = "Fail if the number of matching logs is outside a threshold.
+
+Counts the logs matching QUERY with the aggregation API and exits 0 if the
+count is within --min/--max, or with code 10 if it isn't, so pipelines can
+gate on logs: fail a deploy if new 5xx logs appear, or an alert script if a
+heartbeat stops logging. Without --min or --max, the check is --max 0.
+
+Output Format:
+  A single line with the count and the threshold, whether or not it passed:
+  {\"query\":\"service:api @http.status_code:>=500\",\"from\":\"now-10m\",\"to\":\"now\",\"count\":3,\"max\":0,\"passed\":false}
+
+Examples:
+  # Fail the deploy if any 5xx logs appeared since it started
+  ddog logs check \"service:api @http.status_code:>=500\" --from now-10m --max 0
+
+  # Tolerate a few errors
+  ddog logs check --service api --status error --last 15m --max 25
+
+  # Fail if the nightly job didn't log its completion
+  ddog logs check \"service:etl @msg:\\\"job complete\\\"\" --last 1d --min 1
+
+  # Alert a Slack channel from a cron job
+  ddog logs check --service api --status error --last 5m --max 25 \\
+    --notify-slack \"$SLACK_WEBHOOK_URL\""
+    )]
+    Check {
+        /// Datadog query string (e.g., "service:api @http.status_code:>=500")
+        #[arg(
+            default_value = "*",
+            add = ArgValueCandidates::new(completers::saved_queries)
+        )]
+        query: String,
+
+        /// Fail if more than N logs match
+        #[arg(long, value_name = "N")]
+        max: Option<u64>,
+
+        /// Fail if fewer than N logs match
+        #[arg(long, value_name = "N")]
+        min: Option<u64>,
+
+        #[command(flatten)]
+        time_range: TimeRange,
+
+        #[command(flatten)]
+        filters: QueryFilters,
+
+        /// Log indexes to search (comma-separated, default: all)
+        #[arg(
+            short,
+            long,
+            value_delimiter = ',',
+            default_value = "*",
+            add = ArgValueCandidates::new(completers::log_indexes)
+        )]
+        indexes: Vec<String>,
+
+        /// Post a summary to a Slack incoming webhook when the check fails
+        #[arg(
+            long,
+            value_name = "URL",
+            value_parser = parse_http_url,
+            long_help = "Post a summary to a Slack incoming webhook when the check fails: the query,
+the count and the violated bound, the time range, and a link to the logs in
+the Log Explorer. Nothing is posted when the check passes.
+
+The request is retried like --sink batches. If it still fails, the command
+fails with the webhook's error instead of exit code 10."
+        )]
+        notify_slack: Option<String>,
+    },
+
+    /// Compare per-group log counts between two time windows
+    #[command(long_about = "Compare per-group log counts between two time windows.
+
+Counts the logs matching QUERY per value of the --group-by facet in the
+--baseline and --compare windows with the aggregation API, and writes one
+record per value with both counts and the change, largest change first. This
+answers \"what changed since the last deploy?\" without a dashboard.
+
+Output Format:
+  Each line contains one facet value:
+  {\"facet\":\"service\",\"group\":\"checkout\",\"baseline\":120.0,\"compare\":480.0,\"delta\":360.0,\"change\":3.0,\"movement\":\"up\"}
+
+  change is relative to the baseline (3.0 is +300%) and null when the baseline
+  is 0. movement is new, gone, up, down, or same. Only the --limit largest values
+  of each window are fetched; a value missing from a window that returned
+  --limit values may have ranked below the cut, so its count there is null and
+  its movement is unknown.
+
+  Compare windows of the same length; a warning is printed when they differ.
+
+Examples:
+  # Which services changed in the hour since the deploy?
+  ddog logs diff \"status:error\" --baseline now-2h..now-1h --compare now-1h..now --group-by service
+
+  # Same hour yesterday vs today, by endpoint
+  ddog logs diff --service api --baseline now-25h..now-24h --compare now-1h.. --group-by @http.url_details.path")]
+    Diff {
+        /// Datadog query string (e.g., "status:error")
+        #[arg(
+            default_value = "*",
+            add = ArgValueCandidates::new(completers::saved_queries)
+        )]
+        query: String,
+
+        /// Window to compare against, as FROM..TO (e.g. now-2h..now-1h)
+        #[arg(long, value_name = "FROM..TO", value_parser = parse_window)]
+        baseline: Window,
+
+        /// Window to compare, as FROM..TO; an empty TO means now
+        #[arg(
+            long,
+            value_name = "FROM..TO",
+            default_value = "now-1h..now",
+            value_parser = parse_window
+        )]
+        compare: Window,
+
+        /// Facet to group by (e.g., service, host, @http.status_code)
+        #[arg(long, value_name = "FACET")]
+        group_by: String,
+
+        /// Number of facet values to fetch per window, by volume
+        #[arg(long, value_name = "N", default_value = "25")]
+        limit: i64,
+
+        #[command(flatten)]
+        filters: QueryFilters,
+
+        /// Log indexes to search (comma-separated, default: all)
+        #[arg(
+            short,
+            long,
+            value_delimiter = ',',
+            default_value = "*",
+            add = ArgValueCandidates::new(completers::log_indexes)
+        )]
+        indexes: Vec<String>,
+    },
+
+    /// Manage log-based metrics: list, create, or delete
+    Metrics {
+        #[command(subcommand)]
+        action: LogsMetricsAction,
+    },
+
+    /// Inspect index exclusion filters, and which would drop a query's logs
+    Exclusions {
+        #[command(subcommand)]
+        action: LogsExclusionsAction,
+    },
+}
+
+/// Actions on log index exclusion filters.
+#[derive(Subcommand, Debug)]
+pub enum LogsExclusionsAction {
+    /// List the exclusion filters of every log index
+    #[command(long_about = "List the exclusion filters of every log index.
+
+Exclusion filters drop matching logs from an index before they're stored (all
+of them, or a sample_rate share), so they can't be searched for.
+
+Output Format:
+  One line per filter, index by index:
+  {\"index\":\"main\",\"index_query\":\"*\",\"name\":\"Health checks\",
+   \"enabled\":true,\"query\":\"@http.url_details.path:/health\",\"sample_rate\":1.0}
+
+Examples:
+  ddog logs exclusions list
+  ddog logs exclusions list | jq -c 'select(.enabled) | {index, name, query}'")]
+    List,
+
+    /// Show which exclusion filters would drop logs matching a query
+    #[command(
+        long_about = "Show which enabled exclusion filters would drop logs matching a query.
+
+Explains gaps in search results: excluded logs are never stored, so a search
+can't find them. The query is compared with each filter's query and its
+index's, without fetching any logs. A filter \"drops\" the logs when every
+log matching the query that reaches its index matches the filter, and \"may_drop\"
+them when they can't be ruled out, as with queries using OR or groups.
+Filters whose queries contradict the query (e.g., status:debug and
+status:error) aren't listed.
+
+Output Format:
+  One line per filter, those that drop the logs first:
+  {\"index\":\"main\",\"name\":\"Debug logs\",\"query\":\"status:debug\",
+   \"sample_rate\":0.9,\"verdict\":\"drops\"}
+
+Examples:
+  ddog logs exclusions test \"service:checkout status:debug\"
+
+  # Fail when a filter drops every matching log
+  ddog logs exclusions test \"service:checkout\" | jq -e 'select(.verdict == \"drops\")' && exit 1"
+    )]
+    Test {
+        /// Datadog query string of the logs to look for
+        #[arg(add = ArgValueCandidates::new(completers::saved_queries))]
+        query: String,
+
+        #[command(flatten)]
+        filters: QueryFilters,
+    },
+}
+
+/// Actions on log-based metrics.
+#[derive(Subcommand, Debug)]
+pub enum LogsMetricsAction {
+    /// List the org's log-based metrics with their definitions
+    #[command(long_about = "List the metrics Datadog generates from ingested logs.
+
+Output Format:
+  One line per metric, in the API's shape, which `logs metrics create` accepts
+  back as a definition:
+  {\"id\":\"checkout.errors\",\"type\":\"logs_metrics\",\"attributes\":{
+   \"compute\":{\"aggregation_type\":\"count\"},
+   \"filter\":{\"query\":\"service:checkout status:error\"},
+   \"group_by\":[{\"path\":\"@http.status_code\",\"tag_name\":\"status_code\"}]}}
+
+Examples:
+  ddog logs metrics list | jq -r .id
+
+  # Keep one file per metric in version control
+  ddog logs metrics list | while read -r m; do echo \"$m\" | jq . > \"metrics/$(echo \"$m\" | jq -r .id).json\"; done")]
+    List,
+
+    /// Create a log-based metric from a JSON definition
+    #[command(long_about = "Create a log-based metric from a JSON definition.
+
+The definition is the API's create request body ({\"data\": {...}}), or the
+metric itself as `logs metrics list` writes it, given inline, read from a
+file with @FILE, or from stdin with @-. Its \"type\" may be left out.
+
+Before sending, the request is shown on stderr and you are asked to confirm.
+Without a terminal to ask on, pass --yes. Every request sent is appended to
+the audit log (audit.ndjson in the state directory).
+
+Output Format:
+  The metric as Datadog stored it. With --dry-run, a single record with the
+  method, url, and body of the request.
+
+Examples:
+  ddog logs metrics create --definition @metrics/checkout.errors.json
+  ddog logs metrics create --definition @metrics/checkout.errors.json --dry-run
+  cat metric.json | ddog logs metrics create --definition @- --yes")]
+    Create {
+        /// Metric definition: inline JSON, @FILE, or @- for stdin
+        #[arg(long, value_name = "JSON|@FILE")]
+        definition: String,
+
+        /// Print the request instead of sending it
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Send without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Delete a log-based metric
+    #[command(long_about = "Delete a log-based metric by its name (its id).
+
+Before sending, the request is shown on stderr and you are asked to confirm.
+Without a terminal to ask on, pass --yes. Every request sent is appended to
+the audit log (audit.ndjson in the state directory).
+
+Output Format:
+  One line naming the deleted metric: {\"id\":\"checkout.errors\",\"deleted\":true}
+  With --dry-run, a single record with the method and url of the request.
+
+Examples:
+  ddog logs metrics delete checkout.errors
+  ddog logs metrics delete checkout.errors --yes")]
+    Delete {
+        /// Name of the metric to delete
+        #[arg(value_name = "ID")]
+        id: String,
+
+        /// Print the request instead of sending it
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Send without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
     },
 }
@@ -1,8 +1,11 @@
 //! Metrics domain command actions.
 
 use clap::Subcommand;
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use ddog::client::Reducer;
+use ddog::time::duration_seconds;
 
-use super::shared::{TimeFrom, TimeRangeRelativeOnly};
+use super::shared::{TimeFrom, TimeRangeRelativeOnly, Viz};
 
 /// Available actions for the metrics domain.
 #[derive(Subcommand, Debug)]
@@ -18,6 +21,7 @@ pub enum MetricsAction {
 Query Syntax:
   • Basic: avg:system.cpu.user{*}
   • Aggregation: sum, avg, min, max, count
+  • Percentiles (distribution metrics): p50, p75, p90, p95, p99
   • Tag filtering: avg:system.cpu.user{env:prod}
   • Multiple tags: avg:system.cpu.user{env:prod,service:web}
   • Wildcards: avg:system.cpu.user{host:web-*}
@@ -25,8 +29,10 @@ Query Syntax:
   • Functions: avg:system.cpu.user{*}.rollup(avg, 60)
 
 Output Format:
-  Each line contains a JSON object with timestamp and metric value.
+  Each line contains one datapoint, ready for time-series databases and pandas:
+  {\"metric\":\"system.cpu.user\",\"scope\":\"host:a\",\"tags\":[\"host:a\"],\"ts\":1705315200,\"value\":12.5}
   Pipe to jq for processing: ddog metrics query \"...\" | jq '.value'
+  With --format wide, each line contains one series and its [ts, value] points.
 
 Examples:
   # Query CPU usage
@@ -35,12 +41,25 @@ Examples:
   # Query with host filter
   ddog metrics query \"max:system.mem.used{host:prod-*}\"
 
+  # 99th percentile latency of a distribution metric
+  ddog metrics query \"p99:trace.http.request.duration{service:api}\"
+
   # Multiple metrics
   ddog metrics query \"avg:system.cpu.user{*},avg:system.cpu.system{*}\"
 
+  # One line per series
+  ddog metrics query \"avg:system.cpu.user{host:*}\" --format wide
+
+  # Multiple metrics joined into one row per timestamp
+  ddog metrics query \"avg:system.cpu.user{*},avg:system.cpu.system{*}\" --join
+
   # With arithmetic
   ddog metrics query \"avg:system.cpu.user{*} + avg:system.cpu.system{*}\"
 
+  # Error rate computed by Datadog from two queries (named a and b)
+  ddog metrics query --query \"sum:trace.http.request.errors{*}\" \\
+    --query \"sum:trace.http.request.hits{*}\" --formula \"a / b * 100\"
+
   # Get average value with jq
   ddog metrics query \"avg:system.cpu.idle{*}\" | jq -s 'add / length | .value'
 
@@ -49,12 +68,16 @@ Documentation:
     )]
     Query {
         /// Datadog metric query (e.g., "avg:system.cpu.user{*}")
-        #[arg(long_help = "Datadog metric query using Datadog's metric query syntax.
+        #[arg(
+            required_unless_present = "queries",
+            conflicts_with = "queries",
+            long_help = "Datadog metric query using Datadog's metric query syntax.
 
 Format: <aggregation>:<metric_name>{<tag_filters>}[.<function>]
 
 Supports:
   • Aggregations: avg, sum, min, max, count
+  • Percentiles: p50, p75, p90, p95, p99 (distribution metrics only)
   • Tag filtering: {env:prod}, {env:prod,service:web}
   • Wildcards: {host:web-*}, {service:*}
   • Arithmetic: metric1 + metric2, metric1 - metric2
@@ -64,9 +87,42 @@ Examples:
   \"avg:system.cpu.user{*}\"
   \"max:system.mem.used{host:prod-*}\"
   \"sum:redis.net.connections{env:prod,cluster:main}\"
+  \"p95:trace.http.request.duration{env:prod}\"
   \"avg:system.cpu.user{*} + avg:system.cpu.system{*}\"
-  \"avg:system.load.1{*}.rollup(avg, 60)\"")]
-        query: String,
+  \"avg:system.load.1{*}.rollup(avg, 60)\""
+        )]
+        query: Option<String>,
+
+        /// Named metric query for --formula (repeatable; named a, b, c, ...)
+        #[arg(
+            long = "query",
+            value_name = "QUERY",
+            long_help = "Metric query to combine with --formula (repeatable).
+
+Queries are named a, b, c, ... in the order given, and sent together to the
+v2 timeseries API. Without --formula, each query's series is written as is.
+
+Examples:
+  --query \"sum:trace.http.request.errors{*}\" --query \"sum:trace.http.request.hits{*}\""
+        )]
+        queries: Vec<String>,
+
+        /// Formula over the queries, computed by Datadog (repeatable)
+        #[arg(
+            long = "formula",
+            value_name = "FORMULA",
+            long_help = "Formula over the queries, computed by Datadog (repeatable).
+
+Refers to queries by name (a, b, c, ... in order; a single positional query
+is a) and supports arithmetic and functions like abs() or log10(). Points are
+written per formula, with the formula as the metric name and each group's
+tags as the scope.
+
+Examples:
+  --formula \"a / b * 100\"                   # error rate in percent
+  --formula \"a / b\" --formula \"a - b\""
+        )]
+        formulas: Vec<String>,
 
         #[command(flatten)]
         time_range: TimeRangeRelativeOnly,
@@ -87,6 +143,45 @@ Examples:
   --limit 0          # Return all available data points"
         )]
         limit: u64,
+
+        /// Join all series into one row per timestamp, with a column per series
+        #[arg(
+            long,
+            long_help = "Join all series into one row per timestamp, with a column per series.
+
+Useful with several comma-separated queries: series are aligned onto a
+common time grid (the coarsest interval among them, averaging finer points
+within each bucket) and emitted as rows like:
+
+  {\"timestamp\":1705315200,\"avg:system.cpu.user{*}\":12.5,\"avg:system.cpu.system{*}\":3.1}
+
+Columns are named <aggr>:<metric>{<scope>}. A series with no point in a
+bucket gets null. --limit applies to rows.
+
+Examples:
+  ddog metrics query \"avg:system.cpu.user{*},avg:system.cpu.system{*}\" --join
+  ddog metrics query \"sum:trace.http.request.errors{*},sum:trace.http.request.hits{*}\" --join"
+        )]
+        join: bool,
+
+        /// Also draw the series on stderr (histogram or sparkline)
+        #[arg(
+            long,
+            value_enum,
+            value_name = "KIND",
+            long_help = "Also draw the series on stderr, to eyeball spikes without the Datadog UI.
+
+  histogram   Block bar chart per series, eight rows tall
+  sparkline   One sparkline row per series, with its range
+
+Records are still written to stdout. Long series are squeezed into 60
+columns, keeping each column's largest value so short spikes stay visible.
+
+Examples:
+  ddog metrics query \"sum:trace.http.request.errors{*}\" --viz histogram > errors.ndjson
+  ddog metrics query \"avg:system.load.1{*} by {host}\" --viz sparkline > /dev/null"
+        )]
+        viz: Option<Viz>,
     },
 
     /// List active metrics within a time window
@@ -124,4 +219,146 @@ the specified start time.")]
         #[command(flatten)]
         time_from: TimeFrom,
     },
+
+    /// Find stretches where a metric's series stopped reporting
+    #[command(
+        long_about = "Find stretches where a metric's series stopped reporting.
+
+Queries the metric over the range and, for each series (one per tag
+combination; add `by {host}` to split by host), reports every stretch with
+no points longer than 1.5 times the expected reporting interval. A series
+that started late or is still missing at the end of the range is reported
+too, allowing one extra interval at the edges for ingestion delay.
+
+The expected interval is --interval when given, otherwise each series'
+median spacing between points. Datadog rolls points up over long ranges
+(e.g. to 5 minutes over a day), so gaps shorter than the rollup can't be
+seen; narrow the range, or set the rollup with .rollup(count, 60).
+
+Only series with at least one point in the range appear: a host that never
+reported is not listed.
+
+⚠️  Time Format Limitation:
+  Metrics commands do NOT support ISO8601 timestamps.
+  Use relative times (now-1h) or Unix timestamps only.
+
+Output Format:
+  Each line contains one gap, ordered by series then start (Unix seconds):
+  {\"series\":\"env:prod,host:web-1\",\"start\":1705312800,\"end\":1705314600,
+   \"duration_seconds\":1800,\"missing_points\":29,\"ongoing\":false}
+
+  ongoing is true when the series had not resumed by the end of the range.
+
+Examples:
+  # Which hosts stopped reporting today, and when?
+  ddog metrics gaps \"avg:system.cpu.user{env:prod} by {host}\" --from now-6h
+
+  # Hosts still down
+  ddog metrics gaps \"avg:system.cpu.user{*} by {host}\" | jq 'select(.ongoing)'
+
+  # A check expected every 15 seconds
+  ddog metrics gaps \"avg:redis.info.latency_ms{*} by {redis_host}\" --from now-30m --interval 15s"
+    )]
+    Gaps {
+        /// Datadog metric query, usually split by tags (e.g., "avg:system.cpu.user{env:prod} by {host}")
+        query: String,
+
+        #[command(flatten)]
+        time_range: TimeRangeRelativeOnly,
+
+        /// Expected reporting interval (e.g. 15s, 1m); defaults to each series' median spacing
+        #[arg(long, value_name = "DURATION", value_parser = duration_seconds)]
+        interval: Option<i64>,
+    },
+
+    /// Report the tag keys of a metric and how many values each has
+    #[command(
+        long_about = "Report the tag keys of a metric and how many values each has.
+
+Every distinct combination of tag values is a separate custom metric series,
+so a tag key with many values (user IDs, request paths, container IDs)
+multiplies what the metric costs. Lists the metric's indexed tags over the
+previous hour and writes one record per tag key, most values first, with a
+few example values. Datadog's estimate of the metric's distinct series is
+printed on stderr.
+
+Output Format:
+  Each line contains one tag key:
+  {\"metric\":\"app.requests\",\"tag_key\":\"user_id\",\"values\":48211,\"examples\":[\"1001\",\"1002\",\"1003\"]}
+
+Examples:
+  # Which tag is exploding?
+  ddog metrics cardinality app.requests | head -3
+
+  # Keys with more than 100 values
+  ddog metrics cardinality app.requests | jq 'select(.values > 100) | .tag_key'"
+    )]
+    Cardinality {
+        /// Metric name (e.g., "app.requests")
+        metric: String,
+    },
+
+    /// Reduce a metric query to a single value, optionally checked against levels
+    #[command(
+        long_about = "Reduce a metric query to a single value, optionally checked against levels.
+
+Asks Datadog's scalar API for the query reduced over the range with --reduce,
+for alert scripts and Nagios-style checks built on Datadog metrics. A grouped
+query (`by {host}`) gives one value per group.
+
+With --warn and/or --crit, each value is graded ok, warning, or critical, and
+the command exits with 13 if any value is past --warn and 10 if any is past
+--crit. Values above a level are past it; when --crit is below --warn, lower
+is worse and values below a level are past it instead. A query with no data
+is unknown and, with levels set, fails like critical.
+
+⚠️  Time Format Limitation:
+  Metrics commands do NOT support ISO8601 timestamps.
+  Use relative times (now-1h) or Unix timestamps only.
+
+Output Format:
+  Each line contains one value:
+  {\"query\":\"avg:system.cpu.user{env:prod}\",\"reduce\":\"avg\",\"scope\":\"*\",
+   \"value\":42.5,\"warn\":80.0,\"crit\":95.0,\"status\":\"ok\"}
+
+  With --format text: OK  avg  avg:system.cpu.user{env:prod}  *  42.5
+
+Examples:
+  # Average CPU over the last 5 minutes
+  ddog metrics scalar \"avg:system.cpu.user{env:prod}\" --last 5m --reduce avg
+
+  # Just the number
+  ddog metrics scalar \"sum:trace.http.request.errors{service:api}\" --last 15m --reduce sum | jq '.value'
+
+  # Nagios-style check on queue depth
+  ddog metrics scalar \"max:rabbitmq.queue.messages{queue:orders}\" --last 10m --reduce max --warn 1000 --crit 5000
+
+  # Alert when free disk drops below 10% (lower is worse)
+  ddog metrics scalar \"min:system.disk.free_pct{host:db-1}\" --last 5m --reduce min --warn 20 --crit 10"
+    )]
+    Scalar {
+        /// Datadog metric query (e.g., "avg:system.cpu.user{env:prod}")
+        query: String,
+
+        #[command(flatten)]
+        time_range: TimeRangeRelativeOnly,
+
+        /// How to reduce the series to one value: avg, min, max, sum, or last
+        #[arg(
+            long,
+            value_name = "REDUCER",
+            default_value = "avg",
+            value_parser = PossibleValuesParser::new(["avg", "min", "max", "sum", "last"])
+                .map(|reducer| reducer.parse::<Reducer>().expect("reducer is a possible value"))
+        )]
+        reduce: Reducer,
+
+        /// Exit with 13 if a value is past this level
+        #[arg(long, value_name = "LEVEL", allow_negative_numbers = true)]
+        warn: Option<f64>,
+
+        /// Exit with 10 if a value is past this level
+        #[arg(long, value_name = "LEVEL", allow_negative_numbers = true)]
+        crit: Option<f64>,
+    },
 }
@@ -2,14 +2,36 @@
 //!
 //! Defines the CLI structure with domain-based subcommands for querying Datadog.
 
+mod api;
 mod args;
+mod bench;
+mod completers;
+mod docs;
+mod drift;
+mod history;
+mod hosts;
 mod logs;
 mod metrics;
+mod monitors;
+mod query;
 mod shared;
 mod spans;
+mod telemetry;
 
-pub use args::{Cli, Domain};
-pub use logs::LogsAction;
+pub use api::ApiAction;
+pub use args::{Cli, Domain, ErrorFormat};
+pub use bench::BenchAction;
+pub use docs::DocsAction;
+pub use drift::DriftAction;
+pub use history::HistoryAction;
+pub use hosts::HostsAction;
+pub use logs::{LogsAction, LogsExclusionsAction, LogsMetricsAction};
 pub use metrics::MetricsAction;
-pub use shared::{Pagination, TimeFrom, TimeRange, TimeRangeRelativeOnly};
-pub use spans::SpansAction;
+pub use monitors::MonitorsAction;
+pub use query::QueryAction;
+pub use shared::{
+    Aggregation, Chart, FacetDiscovery, LogsSource, OwnerRouting, Pagination, QueryFilters,
+    TimeFrom, TimeRange, TimeRangeRelativeOnly, Viz, Watch,
+};
+pub use spans::{SpansAction, SpansMetricsAction, SpansRetentionFiltersAction};
+pub use telemetry::TelemetryAction;
@@ -0,0 +1,137 @@
+//! Monitors domain command actions.
+
+use clap::Subcommand;
+
+use super::shared::{TimeRange, Watch, parse_http_url};
+
+/// Available actions for the monitors domain.
+#[derive(Subcommand, Debug)]
+pub enum MonitorsAction {
+    /// Pair a monitor's alert and recovery events into episodes
+    #[command(
+        long_about = "Pair a monitor's alert and recovery events into episodes.
+
+Fetches the monitor's state-change events (triggered, warn, no data,
+recovered) over the time range and folds them into episodes, each running
+from the first non-OK transition to the recovery. Multi-alert monitors get
+one episode per group.
+
+Output Format:
+  Each line contains one episode:
+  {\"monitor_id\":123,\"group\":\"host:web-1\",\"start\":\"2024-01-15T10:00:00.000Z\",
+   \"end\":\"2024-01-15T10:20:00.000Z\",\"duration_seconds\":1200,
+   \"severity\":\"alert\",\"transitions\":2,\"title\":\"[Triggered on {host:web-1}] High CPU\"}
+
+  Episodes still open at the end of the range have null end and
+  duration_seconds. A recovery whose trigger fell before the range is ignored.
+
+Examples:
+  # Episodes over the last week
+  ddog monitors history 123456 --last 7d
+
+  # Mean time to recovery in seconds
+  ddog monitors history 123456 --last 30d | jq -s 'map(select(.duration_seconds)) | add / length | .duration_seconds'
+
+  # Longest episodes first
+  ddog monitors history 123456 --last 30d | jq -s 'sort_by(-.duration_seconds)'"
+    )]
+    History {
+        /// Numeric monitor ID
+        #[arg(value_name = "ID")]
+        monitor_id: i64,
+
+        #[command(flatten)]
+        time_range: TimeRange,
+    },
+
+    /// Show a monitor's state, and with --watch each transition
+    #[command(
+        long_about = "Show a monitor's overall state, and with --watch each transition.
+
+Writes the monitor's current state. With --watch, keeps polling on the
+interval until Ctrl-C and writes a record each time the state changes (OK to
+Alert, Alert to OK, and so on), as a lightweight substitute for a webhook in
+shell automations.
+
+Output Format:
+  Each line contains one state, with the state it changed from (null for
+  the first poll):
+  {\"timestamp\":\"2024-01-15T10:00:00.000Z\",\"monitor_id\":123,\"name\":\"High CPU\",
+   \"from\":\"OK\",\"to\":\"Alert\"}
+
+  States are OK, Warn, Alert, No Data, Ignored, Skipped, and Unknown. With
+  --watch --bell, the bell rings three times on a change to Alert.
+
+Examples:
+  # Current state
+  ddog monitors status 123456 | jq -r '.to'
+
+  # Roll back when the monitor fires
+  ddog monitors status 123456 --watch 60s | jq --unbuffered -r 'select(.to == \"Alert\") | .name' |
+    while read -r name; do ./rollback.sh \"$name\"; done
+
+  # Page a Slack channel while the monitor is firing
+  ddog monitors status 123456 --watch 60s --notify-slack \"$SLACK_WEBHOOK_URL\""
+    )]
+    Status {
+        /// Numeric monitor ID
+        #[arg(value_name = "ID")]
+        monitor_id: i64,
+
+        #[command(flatten)]
+        watch: Watch,
+
+        /// Post to a Slack incoming webhook when the monitor is in Alert, Warn, or No Data
+        #[arg(
+            long,
+            value_name = "URL",
+            value_parser = parse_http_url,
+            long_help = "Post to a Slack incoming webhook when the monitor is in Alert, Warn, or No
+Data: its name, the state it changed from, and a link to the monitor. A
+message is posted for the state at the first poll and, with --watch, for
+each change to one of these states; recoveries are not posted.
+
+The request is retried like --sink batches. If it still fails, the command
+fails with the webhook's error."
+        )]
+        notify_slack: Option<String>,
+    },
+
+    /// Rank monitors by alert noise: alert counts, time to recover, and flappiness
+    #[command(long_about = "Rank monitors by alert noise for alert-hygiene reviews.
+
+For every monitor matching the --tag filters, pairs its alert and recovery
+events into episodes (see `ddog monitors history`) and summarizes them:
+
+  alerts               Alert episodes in the range
+  open                 Episodes not yet recovered at the end of the range
+  mean_ttr_seconds     Mean time to recover
+  median_ttr_seconds   Median time to recover
+  flaps                Episodes that recovered within 5 minutes
+  flappiness           Share of episodes that were flaps (0.0 to 1.0)
+
+Monitors are ranked noisiest first, by alert count and then flappiness.
+
+Output Format:
+  Each line contains one monitor:
+  {\"rank\":1,\"monitor_id\":123,\"name\":\"High CPU\",\"alerts\":14,\"open\":0,
+   \"mean_ttr_seconds\":412.5,\"median_ttr_seconds\":180.0,\"flaps\":9,\"flappiness\":0.64}
+
+Examples:
+  # Noisiest payments monitors over the last 30 days
+  ddog monitors noise-report --tag team:payments --from now-30d
+
+  # Top 5 as a table
+  ddog monitors noise-report --tag team:payments --last 30d --format text | head -5
+
+  # Monitors that flap more often than not
+  ddog monitors noise-report --tag team:payments --last 30d | jq 'select(.flappiness > 0.5)'")]
+    NoiseReport {
+        /// Only include monitors with this tag (repeatable, e.g. --tag team:payments)
+        #[arg(long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+
+        #[command(flatten)]
+        time_range: TimeRange,
+    },
+}
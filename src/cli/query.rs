@@ -0,0 +1,85 @@
+//! Query domain command actions.
+
+use clap::Subcommand;
+use ddog::lint::SearchDomain;
+
+/// Available actions for the query domain.
+#[derive(Subcommand, Debug)]
+pub enum QueryAction {
+    /// Check a logs or spans search query for syntax mistakes
+    #[command(
+        long_about = "Check a logs or spans search query for syntax mistakes without calling Datadog.
+
+Errors (exit code 4):
+  • Unbalanced parentheses or quotes
+  • Malformed ranges: [200 to 299], [TO 100], [100 TO
+  • Reversed comparisons: @duration:=>100
+  • Operators without operands: AND service:api, (status:error OR)
+  • Missing values: service:
+
+Warnings:
+  • Lowercase and/or/not, which Datadog searches as text
+  • Likely misspellings of reserved attributes: stauts:error, sevice:api
+
+The same checks run before every logs and spans search; pass --no-validate
+to skip them. Saved queries (@name) are expanded first.
+
+Output Format:
+  Each line contains one issue:
+  {\"severity\":\"error\",\"message\":\"unclosed '('\",\"start\":0,\"end\":1,
+   \"suggestion\":\"(service:api OR service:web)\"}
+
+Examples:
+  ddog query lint \"(service:api OR service:web\"
+  ddog query lint \"resource_nam:/api/users\" --domain spans
+  ddog query lint @errors"
+    )]
+    Lint {
+        /// Search query to check
+        query: String,
+
+        /// Search syntax to check against: logs or spans
+        #[arg(long, default_value = "logs")]
+        domain: SearchDomain,
+    },
+
+    /// Save a logs or spans query under a name for reuse as @name
+    #[command(
+        long_about = "Save a logs or spans query under a name for reuse as @name.
+
+Saved queries are stored in queries.toml in the config directory
+($XDG_CONFIG_HOME/ddog or ~/.config/ddog) and can be referenced by any
+logs or spans search, or by `ddog query lint`, as @name. A query with the
+same name in the workspace's .ddog.toml [queries] table takes precedence.
+
+The query is checked with `ddog query lint` before it is saved; pass
+--no-validate to save it anyway.
+
+Output Format:
+  One line describing the saved query:
+  {\"name\":\"errors-prod\",\"query\":\"service:api status:error\",\"domain\":\"logs\",
+   \"path\":\"/home/me/.config/ddog/queries.toml\"}
+
+Examples:
+  ddog query save errors-prod \"service:api status:error\" --domain logs
+  ddog logs search @errors-prod --from now-1h
+
+  # Replace an existing query
+  ddog query save slow \"@duration:>2s\" --domain spans --force"
+    )]
+    Save {
+        /// Name to reference the query by (letters, digits, '-' and '_')
+        name: String,
+
+        /// Search query to save
+        query: String,
+
+        /// Search syntax the query is written for: logs or spans
+        #[arg(long, default_value = "logs")]
+        domain: SearchDomain,
+
+        /// Replace an existing saved query with the same name
+        #[arg(long)]
+        force: bool,
+    },
+}
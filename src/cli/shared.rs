@@ -1,6 +1,25 @@
 //! Shared CLI argument structures used across multiple commands.
 
-use clap::Args;
+use chrono::{DateTime, FixedOffset};
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use clap::{Args, ValueEnum};
+use clap_complete::ArgValueCandidates;
+use ddog::anonymize::{AnonymizeProfile, Anonymizer};
+use ddog::client::{
+    Grouping, LogsSearchRequest, MAX_LOGS_PAGE_SIZE, SEARCH_PAGE_SIZE, SpansSearchRequest,
+    StorageTier,
+};
+use ddog::diff::Window;
+use ddog::error::AppError;
+use ddog::pagination::Budget;
+use ddog::query;
+use ddog::sample::Sampler;
+use ddog::time::{
+    SnapUnit, TimeSpec, Timezone, parse_last, parse_time, resolve_absolute, resolve_local,
+};
+use std::path::PathBuf;
+
+use super::completers;
 
 /// Time range arguments for logs and spans (supports ISO8601, relative, and Unix timestamps).
 #[derive(Args, Debug, Clone)]
@@ -15,14 +34,19 @@ pub struct TimeRange {
 Supported formats:
   • Relative: now, now-15m, now-1h, now-2d, now-1w, now-3mo, now-1y
     Units: s (seconds), m (minutes), h (hours), d (days), w (weeks), mo (months), y (years)
+    Snap to a unit with /: now/d (start of today), now-1d/d, now/w (Monday)
   • ISO8601: 2024-01-15T10:00:00Z, 2024-01-15T10:00:00+00:00
+    Without an offset (2024-01-15T10:00:00), the time is in --timezone
   • Unix timestamp: 1705315200000 (milliseconds since epoch)
+  • Natural language: today, yesterday 9am, last monday 14:30, 2 hours ago
+    (resolved in the --timezone timezone)
 
 Examples:
   --from now-30m     # 30 minutes ago
   --from now-1d      # 1 day ago
   --from 2024-01-15T10:00:00Z
-  --from 1705315200000"
+  --from 1705315200000
+  --from yesterday   # Midnight at the start of yesterday"
     )]
     pub from: String,
 
@@ -36,16 +60,118 @@ Examples:
 Supported formats:
   • Relative: now, now-15m, now-1h, now-2d, now-1w, now-3mo, now-1y
     Units: s (seconds), m (minutes), h (hours), d (days), w (weeks), mo (months), y (years)
+    Snap to a unit with /: now/d (start of today), now-1d/d, now/w (Monday)
   • ISO8601: 2024-01-15T10:00:00Z, 2024-01-15T10:00:00+00:00
+    Without an offset (2024-01-15T10:00:00), the time is in --timezone
   • Unix timestamp: 1705315200000 (milliseconds since epoch)
+  • Natural language: today, yesterday 9am, last monday 14:30, 2 hours ago
+    (resolved in the --timezone timezone)
 
 Examples:
   --to now           # Current time (default)
   --to now-5m        # 5 minutes ago
   --to 2024-01-15T11:00:00Z
-  --to 1705318800000"
+  --to 1705318800000
+  --to today         # Midnight at the start of today"
     )]
     pub to: String,
+
+    /// Shorthand for --from now-<DURATION> --to now (e.g. 15m, 1h, 2d)
+    #[arg(
+        long,
+        value_name = "DURATION",
+        value_parser = parse_last,
+        conflicts_with_all = ["from", "to"],
+        long_help = "Query the last <DURATION> up to now; shorthand for --from now-<DURATION> --to now.
+
+Cannot be combined with --from or --to.
+
+Units: s (seconds), m (minutes), h (hours), d (days), w (weeks), mo (months), y (years)
+
+Examples:
+  --last 15m         # Same as --from now-15m --to now
+  --last 2d"
+    )]
+    pub last: Option<String>,
+
+    /// Round the time bounds outward to whole units (hour, day)
+    #[arg(
+        long,
+        value_name = "UNIT",
+        long_help = "Round the time bounds outward to whole units so results fall into clean buckets.
+
+The start is rounded down and the end rounded up, in the --timezone timezone.
+Accepts hour or day (also minute, week, month, year).
+
+Examples:
+  --from now-6h --align hour   # 10:37 -> 04:00 to 11:00
+  --last 7d --align day        # Whole days, midnight to midnight"
+    )]
+    pub align: Option<SnapUnit>,
+}
+
+impl From<Window> for TimeRange {
+    fn from(window: Window) -> Self {
+        Self {
+            from: window.from,
+            to: window.to,
+            last: None,
+            align: None,
+        }
+    }
+}
+
+impl TimeRange {
+    /// Returns a copy with the bounds a batch job sets replaced.
+    ///
+    /// Replacing `from` drops `--last`, which would otherwise override it.
+    pub fn with_bounds(&self, from: Option<&str>, to: Option<&str>) -> Self {
+        let mut range = self.clone();
+        if let Some(from) = from {
+            range.from = from.to_string();
+            range.last = None;
+        }
+        if let Some(to) = to {
+            range.to = to.to_string();
+        }
+        range
+    }
+
+    /// A logs search of `query` over this range.
+    pub fn logs_request(&self, query: &str, indexes: &[String]) -> LogsSearchRequest {
+        LogsSearchRequest::builder()
+            .query(query)
+            .from(&self.from)
+            .to(&self.to)
+            .indexes(indexes)
+            .build()
+    }
+
+    /// A spans search of `query` over this range.
+    pub fn spans_request(&self, query: &str) -> SpansSearchRequest {
+        SpansSearchRequest::builder()
+            .query(query)
+            .from(&self.from)
+            .to(&self.to)
+            .build()
+    }
+
+    /// Expands `--last` and `--align`, and replaces times that depend on
+    /// `timezone` with RFC3339 timestamps.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::InvalidQuery` if a bound is not a valid time or
+    /// cannot be aligned.
+    pub fn resolve(&mut self, timezone: Timezone) -> Result<(), AppError> {
+        resolve_bounds(
+            [&mut self.from, &mut self.to],
+            self.last.take(),
+            self.align,
+            timezone,
+            |time| time.to_rfc3339(),
+        )
+    }
 }
 
 /// Time range arguments for metrics (supports only relative and Unix timestamps, no ISO8601).
@@ -63,7 +189,11 @@ pub struct TimeRangeRelativeOnly {
 Supported formats:
   • Relative: now, now-15m, now-1h, now-2d, now-1w, now-3mo, now-1y
     Units: s (seconds), m (minutes), h (hours), d (days), w (weeks), mo (months), y (years)
+    Snap to a unit with /: now/d (start of today), now-1d/d, now/w (Monday)
   • Unix timestamp: 1705315200000 (milliseconds since epoch)
+  • Natural language: today, yesterday 9am, last monday 14:30, 2 hours ago
+  • Local time without an offset: 2024-01-15T10:00:00
+    (both resolved client-side in the --timezone timezone)
 
 Examples:
   --from now-30m     # 30 minutes ago
@@ -87,7 +217,11 @@ Not supported:
 Supported formats:
   • Relative: now, now-15m, now-1h, now-2d, now-1w, now-3mo, now-1y
     Units: s (seconds), m (minutes), h (hours), d (days), w (weeks), mo (months), y (years)
+    Snap to a unit with /: now/d (start of today), now-1d/d, now/w (Monday)
   • Unix timestamp: 1705315200000 (milliseconds since epoch)
+  • Natural language: today, yesterday 9am, last monday 14:30, 2 hours ago
+  • Local time without an offset: 2024-01-15T11:00:00
+    (both resolved client-side in the --timezone timezone)
 
 Examples:
   --to now           # Current time (default)
@@ -98,6 +232,116 @@ Not supported:
   --to 2024-01-15T11:00:00Z  ❌ ISO8601 format not available for metrics"
     )]
     pub to: String,
+
+    /// Shorthand for --from now-<DURATION> --to now (e.g. 15m, 1h, 2d)
+    #[arg(
+        long,
+        value_name = "DURATION",
+        value_parser = parse_last,
+        conflicts_with_all = ["from", "to"],
+        long_help = "Query the last <DURATION> up to now; shorthand for --from now-<DURATION> --to now.
+
+Cannot be combined with --from or --to.
+
+Units: s (seconds), m (minutes), h (hours), d (days), w (weeks), mo (months), y (years)
+
+Examples:
+  --last 15m         # Same as --from now-15m --to now
+  --last 2d"
+    )]
+    pub last: Option<String>,
+
+    /// Round the time bounds outward to whole units (hour, day)
+    #[arg(
+        long,
+        value_name = "UNIT",
+        long_help = "Round the time bounds outward to whole units so results fall into clean buckets.
+
+The start is rounded down and the end rounded up, in the --timezone timezone.
+Accepts hour or day (also minute, week, month, year).
+
+Examples:
+  --from now-6h --align hour   # 10:37 -> 04:00 to 11:00
+  --last 7d --align day        # Whole days, midnight to midnight"
+    )]
+    pub align: Option<SnapUnit>,
+}
+
+impl TimeRangeRelativeOnly {
+    /// Expands `--last` and `--align`, and replaces times that depend on
+    /// `timezone` with Unix milliseconds, since metrics reject ISO8601.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::InvalidQuery` if a bound is not a valid time or
+    /// cannot be aligned.
+    pub fn resolve(&mut self, timezone: Timezone) -> Result<(), AppError> {
+        resolve_bounds(
+            [&mut self.from, &mut self.to],
+            self.last.take(),
+            self.align,
+            timezone,
+            |time| time.timestamp_millis().to_string(),
+        )
+    }
+}
+
+/// Resolves `[from, to]` in place for the time range argument structs.
+///
+/// `--last` replaces both bounds. With `--align`, both bounds are resolved
+/// to absolute times and rounded outward; otherwise only timezone-dependent
+/// times are rewritten and the rest are checked with [`parse_time`] and left
+/// for Datadog to evaluate.
+fn resolve_bounds(
+    [from, to]: [&mut String; 2],
+    last: Option<String>,
+    align: Option<SnapUnit>,
+    timezone: Timezone,
+    render: fn(DateTime<FixedOffset>) -> String,
+) -> Result<(), AppError> {
+    if let Some(start) = last {
+        *from = start;
+        *to = "now".to_string();
+    }
+
+    if let Some(unit) = align {
+        let start = timezone.convert(&resolve_absolute(from, timezone)?);
+        let end = timezone.convert(&resolve_absolute(to, timezone)?);
+        let (Some(start), Some(end)) = (unit.floor(&start), unit.ceil(&end)) else {
+            return Err(AppError::InvalidQuery(format!(
+                "Cannot align {} to {} to {} boundaries in {}",
+                from, to, unit, timezone
+            )));
+        };
+        *from = render(start);
+        *to = render(end);
+        return Ok(());
+    }
+
+    for value in [from, to] {
+        if let Some(time) = resolve_local(value, timezone) {
+            *value = render(time);
+        }
+        check_resolved(value)?;
+    }
+    Ok(())
+}
+
+/// Validates a time after [`resolve_local`], which resolves the formats
+/// Datadog doesn't accept.
+///
+/// # Errors
+///
+/// Returns `AppError::InvalidQuery` if the time is not valid, or is a
+/// well-formed natural-language or snapped time that didn't resolve, being
+/// out of range.
+fn check_resolved(value: &str) -> Result<(), AppError> {
+    match parse_time(value)? {
+        TimeSpec::Natural(_) | TimeSpec::Relative { snap: Some(_), .. } => Err(
+            AppError::InvalidQuery(format!("Time out of range: {}", value)),
+        ),
+        _ => Ok(()),
+    }
 }
 
 /// Single time argument for commands that only need a start time (e.g., metrics list).
@@ -115,7 +359,11 @@ pub struct TimeFrom {
 Supported formats:
   • Relative: now, now-15m, now-1h, now-2d, now-1w, now-3mo, now-1y
     Units: s (seconds), m (minutes), h (hours), d (days), w (weeks), mo (months), y (years)
+    Snap to a unit with /: now/d (start of today), now-1d/d, now/w (Monday)
   • Unix timestamp: 1705315200000 (milliseconds since epoch)
+  • Natural language: today, yesterday 9am, last monday 14:30, 2 hours ago
+  • Local time without an offset: 2024-01-15T10:00:00
+    (both resolved client-side in the --timezone timezone)
 
 Examples:
   --from now-30m     # 30 minutes ago
@@ -128,6 +376,21 @@ Not supported:
     pub from: String,
 }
 
+impl TimeFrom {
+    /// Replaces a natural-language or offset-less ISO8601 time with Unix
+    /// milliseconds, since metrics reject ISO8601.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::InvalidQuery` if the time is not valid.
+    pub fn resolve(&mut self, timezone: Timezone) -> Result<(), AppError> {
+        if let Some(time) = resolve_local(&self.from, timezone) {
+            self.from = time.timestamp_millis().to_string();
+        }
+        check_resolved(&self.from)
+    }
+}
+
 /// Pagination arguments for limiting query results.
 #[derive(Args, Debug, Clone)]
 pub struct Pagination {
@@ -143,7 +406,486 @@ Set to 0 for unlimited results (use with caution on large datasets).
 Examples:
   --limit 50         # Return up to 50 results
   --limit 1000       # Return up to 1000 results
-  --limit 0          # Return all matching results (unlimited)"
+  --limit 0          # Return all matching results (unlimited)
+
+The limit is exact: a page is cut off at the limit'th record."
     )]
     pub limit: u64,
+
+    /// Stop after fetching N pages of results
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = clap::value_parser!(u64).range(1..),
+        conflicts_with = "watch",
+        long_help = "Stop after fetching N pages of results (--page-size records per page).
+
+If more results match, the records fetched so far are written and the command
+exits with code 9, so scripts can tell an incomplete export from a complete
+one. Combine with --limit 0 to cap an export by API requests instead of records."
+    )]
+    pub max_pages: Option<u64>,
+
+    /// Stop before writing more than SIZE bytes of records
+    #[arg(
+        long,
+        value_name = "SIZE",
+        value_parser = ddog::pagination::parse_size,
+        conflicts_with = "watch",
+        long_help = "Stop before writing more than SIZE bytes of records, measured as NDJSON.
+
+SIZE is a number of bytes with an optional K, M, or G suffix (powers of 1024).
+If more results match, the records that fit are written and the command exits
+with code 9, so scripts can tell an incomplete export from a complete one.
+
+Examples:
+  --max-bytes 500K
+  --max-bytes 2G"
+    )]
+    pub max_bytes: Option<u64>,
+
+    /// Keep each result with probability RATE (e.g. 0.01 for 1%)
+    #[arg(
+        long,
+        value_name = "RATE",
+        value_parser = ddog::sample::parse_rate,
+        conflicts_with = "watch",
+        long_help = "Keep each result with probability RATE, a fraction in (0, 1].
+
+Results are sampled client-side as they are fetched, each independently, so
+an exploratory query over a huge volume returns a representative subset.
+--limit counts the results kept; --max-pages counts every page fetched, and
+bounds how much is read to find them.
+
+Examples:
+  --sample 0.01                  # About 1% of matching results
+  --sample 0.1 --seed 42         # The same 10% on every run"
+    )]
+    pub sample: Option<f64>,
+
+    /// Seed for --sample, to keep the same results on every run
+    #[arg(long, value_name = "N", requires = "sample")]
+    pub seed: Option<u64>,
+
+    /// Records to fetch per API request (1 to 5000)
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = SEARCH_PAGE_SIZE,
+        value_parser = clap::value_parser!(i32).range(1..=i64::from(MAX_LOGS_PAGE_SIZE)),
+        long_help = "Records to fetch per API request, from 1 to 5000. Spans search pages hold
+at most 1000, so larger sizes fetch 1000 spans per request.
+
+Larger pages read a big result set in fewer requests, using less of the rate
+limit; smaller ones return the first results sooner and hold less in memory.
+When a search reads past its first page (--limit 0, a --limit above the page
+size, or --sample), the next page is fetched while the current one is written.
+
+Examples:
+  --page-size 5000 --limit 0     # Export in a fifth of the requests
+  --page-size 100 --limit 20     # Fetch no more than needed"
+    )]
+    pub page_size: i32,
+
+    /// Most records fetched ahead of output (default: one page)
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = clap::value_parser!(u64).range(1..),
+        long_help = "Most records fetched ahead of output, when the next page is fetched while
+the current one is written (see --page-size). Defaults to one page.
+
+When output can't keep up, such as a slow consumer downstream of a pipe,
+fetching pauses once N records wait unwritten, instead of holding the rest of
+the result set in memory. A smaller buffer bounds memory more tightly; a
+larger one rides out bursts of slow output without stalling the API.
+
+Examples:
+  ddog logs search \"*\" --limit 0 | slow-consumer
+  ddog logs search \"*\" --limit 0 --buffer-records 100 | slow-consumer"
+    )]
+    pub buffer_records: Option<u64>,
+}
+
+impl Pagination {
+    /// The sampler for `--sample`, or `None` when every result is kept.
+    pub fn sampler(&self) -> Option<Sampler> {
+        self.sample.map(|rate| Sampler::new(rate, self.seed))
+    }
+
+    /// Records per page for a search whose pages hold at most `max`.
+    pub fn page_limit(&self, max: i32) -> i32 {
+        self.page_size.min(max)
+    }
+
+    /// Whether a search is expected to read past its first page, so
+    /// fetching the next page ahead pays off.
+    pub fn prefetches(&self) -> bool {
+        self.limit == 0 || self.limit > self.page_size as u64 || self.sample.is_some()
+    }
+
+    /// Most records to fetch ahead of output for a search fetching
+    /// `page_limit` records per page.
+    pub fn buffer(&self, page_limit: i32) -> usize {
+        self.buffer_records.map_or(page_limit as usize, |n| {
+            usize::try_from(n).unwrap_or(usize::MAX)
+        })
+    }
+
+    /// Budget tracking a search against these caps.
+    pub fn budget(&self) -> Budget {
+        Budget::new(self.limit, self.max_pages, self.max_bytes)
+    }
+}
+
+/// Arguments for re-running a command on an interval.
+#[derive(Args, Debug, Clone)]
+pub struct Watch {
+    /// Re-run every DURATION and write only new or changed results (e.g. 30s)
+    #[arg(
+        long,
+        value_name = "DURATION",
+        value_parser = ddog::watch::parse_watch_interval,
+        long_help = "Re-run every DURATION and write only new or changed results, until Ctrl-C.
+
+Searches write each event once, keyed by its event ID. After the first run,
+each run starts 5 minutes before the newest event seen (or at --from, if
+later), so late-indexed events are still caught and --limit applies to each
+run. Aggregations write the buckets whose count is new or has changed since
+the previous run.
+
+After the first run, each run that writes something prints a one-line summary
+to stderr with the number of new results and, for searches, the most common
+message pattern (resource for spans):
+
+  [10:00:30] 5 new logs, top pattern (3): Timeout after <*> calling <*>
+
+DURATION is at least 5s. Examples: --watch 30s, --watch 5m"
+    )]
+    pub watch: Option<std::time::Duration>,
+
+    /// Ring the terminal bell when a --watch run finds something
+    #[arg(
+        long,
+        requires = "watch",
+        long_help = "Ring the terminal bell on stderr when a --watch run writes new results.
+
+The bell rings three times instead of once when a new log or span has an
+error or more severe status, or a monitor changes to Alert, so a background
+terminal can grab attention before a monitor would fire."
+    )]
+    pub bell: bool,
+}
+
+/// Filters that scope a logs or spans query without editing the query string.
+#[derive(Args, Debug, Clone)]
+pub struct QueryFilters {
+    /// Scope the query to the service and version of the current repository
+    #[arg(
+        long,
+        long_help = "Scope the query to the service and version of the current repository.
+
+The service name and version are inferred from the repository root:
+  • service.datadog.yaml (dd-service) for the service name
+  • Cargo.toml or package.json for name and version
+  • The latest git tag for the version
+
+Adds `service:<name> version:<version>` to the query. Facets already present
+in the query are left untouched.
+
+Examples:
+  ddog logs search \"status:error\" --this-service
+  ddog spans search \"@duration:>1s\" --this-service"
+    )]
+    pub this_service: bool,
+
+    /// Only match these services (repeatable; values are ORed)
+    #[arg(long, value_name = "NAME")]
+    pub service: Vec<String>,
+
+    /// Only match this environment (e.g. prod)
+    #[arg(long, value_name = "ENV")]
+    pub env: Option<String>,
+
+    /// Only match this status (e.g. error, warn, info)
+    #[arg(long, value_name = "STATUS")]
+    pub status: Option<String>,
+
+    /// Only match records whose attribute has this value (repeatable)
+    #[arg(
+        long,
+        value_name = "KEY=VALUE",
+        value_parser = parse_attr,
+        long_help = "Only match records whose attribute has the given value, written as
+KEY=VALUE without the leading @. Repeat to require several attributes.
+Values with spaces or search syntax characters are quoted for you.
+
+Together with --service, --env, and --status, this builds the query for you;
+the terms are ANDed with the positional query, which defaults to \"*\".
+
+Examples:
+  ddog logs search --service api --env prod --status error
+  ddog logs search --attr http.status_code=500 --attr http.method=POST
+  ddog logs search \"timeout\" --service api --attr error.kind=\"connection reset\""
+    )]
+    pub attr: Vec<(String, String)>,
+}
+
+impl QueryFilters {
+    /// Search terms for the query builder flags, in flag order.
+    pub fn terms(&self) -> Vec<String> {
+        let mut terms = Vec::new();
+        if !self.service.is_empty() {
+            terms.push(query::term("service", &self.service));
+        }
+        if let Some(env) = &self.env {
+            terms.push(query::term("env", &[env]));
+        }
+        if let Some(status) = &self.status {
+            terms.push(query::term("status", &[status]));
+        }
+        for (key, value) in &self.attr {
+            terms.push(query::term(&format!("@{}", key), &[value]));
+        }
+        terms
+    }
+}
+
+/// Parses a `KEY=VALUE` attribute filter; a leading `@` on the key is optional.
+fn parse_attr(attr: &str) -> Result<(String, String), String> {
+    match attr.split_once('=') {
+        Some((key, value)) if !key.trim_start_matches('@').is_empty() => {
+            Ok((key.trim_start_matches('@').to_string(), value.to_string()))
+        }
+        _ => Err(format!(
+            "invalid attribute filter '{}' (expected KEY=VALUE, e.g. http.status_code=500)",
+            attr
+        )),
+    }
+}
+
+/// Routing of results to the teams that own each record's service.
+#[derive(Args, Debug, Clone)]
+pub struct OwnerRouting {
+    /// Write each owning team's results to <DIR>/<team>.ndjson instead of stdout
+    #[arg(
+        long,
+        value_name = "DIR",
+        long_help = "Split results by owning team and write them to <DIR>/<team>.ndjson.
+
+Each record's service is looked up in the ownership mapping (--owners, or the
+Datadog Service Catalog by default). Records with no known owner go to
+unowned.ndjson. A summary line per team is printed to stdout.
+
+Examples:
+  ddog logs search \"status:error\" --route-by-owner out/
+  ddog logs search \"status:error\" --route-by-owner out/ --owners owners.toml"
+    )]
+    pub route_by_owner: Option<PathBuf>,
+
+    /// Post a per-team summary of the results to each team's webhook
+    #[arg(
+        long,
+        long_help = "Post a per-team summary of the results to each team's webhook.
+
+Webhooks are read from the ownership file given with --owners:
+
+  [teams.payments]
+  services = [\"checkout\", \"billing-*\"]
+  webhook = \"https://hooks.slack.com/services/...\"
+
+The payload includes a Slack-compatible \"text\" field."
+    )]
+    pub notify_owners: bool,
+
+    /// Service ownership file (TOML); defaults to the Datadog Service Catalog
+    #[arg(long, value_name = "FILE")]
+    pub owners: Option<PathBuf>,
+}
+
+/// Pseudonymization of exported records.
+#[derive(Args, Debug, Clone)]
+pub struct Anonymization {
+    /// Pseudonymize the fields listed in a profile (TOML) in every record
+    #[arg(
+        long,
+        value_name = "PROFILE",
+        long_help = "Pseudonymize the fields listed in an anonymization profile (TOML).
+
+Each value is replaced by the same pseudonym everywhere in the export,
+including per-team files, so records can still be joined and grouped.
+Emails stay emails (at a reserved .example domain) and IPs stay IPs (in
+10.0.0.0/8 or fd00::/8). The salt is random per run unless the profile
+sets one.
+
+  salt = \"vendor-2024\"            # optional
+
+  [fields]
+  \"attributes.host\" = \"hash\"
+  \"attributes.attributes.usr.email\" = \"email\"
+  \"attributes.attributes.network.client.ip\" = \"ip\"
+
+Examples:
+  ddog logs search \"service:checkout\" --anonymize profile.toml > dataset.ndjson"
+    )]
+    pub anonymize: Option<PathBuf>,
+}
+
+impl Anonymization {
+    /// Loads the anonymization profile, or `None` when `--anonymize` is not given.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Config` if the profile cannot be read or parsed.
+    pub fn anonymizer(&self) -> Result<Option<Anonymizer>, AppError> {
+        self.anonymize
+            .as_deref()
+            .map(|path| AnonymizeProfile::load(path).map(Anonymizer::new))
+            .transpose()
+    }
+}
+
+/// Where a logs search reads from: the indexes, and the storage tier.
+#[derive(Args, Debug, Clone)]
+pub struct LogsSource {
+    /// Log indexes to search (comma-separated, default: all)
+    #[arg(
+        short,
+        long,
+        value_delimiter = ',',
+        default_value = "*",
+        add = ArgValueCandidates::new(completers::log_indexes),
+        long_help = "Log indexes to search. Specify multiple indexes separated by commas.
+
+Examples:
+  --indexes main           # Search only the 'main' index
+  --indexes main,web       # Search both 'main' and 'web' indexes
+  --indexes \"*\"            # Search all indexes (default)"
+    )]
+    pub indexes: Vec<String>,
+
+    /// Storage tier to search: indexes (default), online-archives, or flex
+    #[arg(
+        long,
+        value_name = "TIER",
+        value_parser = PossibleValuesParser::new(["indexes", "online-archives", "flex"])
+            .map(|tier| tier.parse::<StorageTier>().expect("tier is a possible value")),
+        long_help = "Storage tier to search. Without this flag, Datadog searches the indexes.
+
+  indexes           Indexed logs
+  online-archives   Logs in Online Archives
+  flex              Flex Logs
+
+Examples:
+  ddog logs search \"service:api\" --storage-tier flex --last 30d
+  ddog logs search \"@order_id:9f8e7d\" --storage-tier online-archives --from now-90d"
+    )]
+    pub storage_tier: Option<StorageTier>,
+}
+
+impl LogsSource {
+    /// A logs search of `query` over `range`.
+    pub fn request(&self, query: &str, range: &TimeRange) -> LogsSearchRequest {
+        let mut request = range.logs_request(query, &self.indexes);
+        request.storage_tier = self.storage_tier;
+        request
+    }
+}
+
+/// Chart rendered from an aggregation instead of writing records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Chart {
+    /// Shaded grid of volume per facet value over time
+    Heatmap,
+}
+
+/// Terminal chart drawn on stderr alongside the records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Viz {
+    /// Block bar chart per series, eight rows tall
+    Histogram,
+    /// One sparkline row per series
+    Sparkline,
+}
+
+/// Grouping and charting of a logs or spans volume aggregation.
+#[derive(Args, Debug, Clone)]
+pub struct Aggregation {
+    /// Facet to group by (e.g., service, status, @http.status_code)
+    #[arg(long, value_name = "FACET")]
+    pub group_by: String,
+
+    /// Time bucket size (e.g. 1m, 10m, 1h)
+    #[arg(long, value_name = "DURATION", default_value = "10m", value_parser = parse_interval)]
+    pub interval: String,
+
+    /// Number of facet values to keep, by volume
+    #[arg(long, value_name = "N", default_value = "10")]
+    pub top: i64,
+
+    /// Render a chart instead of writing one record per bucket
+    #[arg(
+        long,
+        value_enum,
+        conflicts_with = "watch",
+        long_help = "Render a chart instead of writing one record per bucket.
+
+  heatmap   One row per facet value and one column per time bucket, shaded by
+            volume (· ░ ▒ ▓ █). Rows are ordered by when they first saw events,
+            so the top row is the value that started first. Each row ends with
+            its total.
+
+Use --html to write the chart to a standalone HTML file instead."
+    )]
+    pub chart: Option<Chart>,
+
+    /// Write the chart to an HTML file instead of the terminal
+    #[arg(long, value_name = "FILE", requires = "chart")]
+    pub html: Option<PathBuf>,
+
+    /// Also draw each facet value's volume on stderr (histogram or sparkline)
+    #[arg(long, value_enum, value_name = "KIND", conflicts_with_all = ["chart", "watch"])]
+    pub viz: Option<Viz>,
+}
+
+impl Aggregation {
+    /// Facet, bucket size, and group limit for the aggregate API.
+    pub fn grouping(&self) -> Grouping {
+        Grouping {
+            facet: self.group_by.clone(),
+            interval: self.interval.clone(),
+            top: self.top,
+        }
+    }
+}
+
+/// Sampling and summary sizes for facet discovery.
+#[derive(Args, Debug, Clone)]
+pub struct FacetDiscovery {
+    /// Number of matching logs to sample for attribute names
+    #[arg(long, value_name = "N", default_value = "500")]
+    pub sample: usize,
+
+    /// Number of facets to summarize, most common in the sample first
+    #[arg(long, value_name = "N", default_value = "20")]
+    pub max_facets: usize,
+
+    /// Number of top values to list per facet
+    #[arg(long, value_name = "N", default_value = "5")]
+    pub top: i64,
+}
+
+/// Validates an HTTP endpoint (`--sink`, `--push`, `--notify-slack`),
+/// returning it unchanged.
+pub(super) fn parse_http_url(url: &str) -> Result<String, String> {
+    if url.starts_with("http://") || url.starts_with("https://") {
+        Ok(url.to_string())
+    } else {
+        Err(format!("'{}' is not an http:// or https:// URL", url))
+    }
+}
+
+/// Validates a time bucket size such as "10m", returning it unchanged.
+pub(super) fn parse_interval(interval: &str) -> Result<String, String> {
+    parse_last(interval).map(|_| interval.to_string())
 }
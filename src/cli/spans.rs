@@ -1,8 +1,17 @@
 //! Spans domain command actions.
 
 use clap::Subcommand;
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use clap_complete::ArgValueCandidates;
+use ddog::client::{Percentile, SpansSearchType};
+use ddog::watch::parse_watch_interval;
+use std::time::Duration;
 
-use super::shared::{Pagination, TimeRange};
+use super::completers;
+use super::shared::{
+    Aggregation, Anonymization, OwnerRouting, Pagination, QueryFilters, TimeRange, Watch,
+    parse_interval,
+};
 
 /// Available actions for the spans domain.
 #[derive(Subcommand, Debug)]
@@ -36,14 +45,27 @@ Examples:
   # Find errors in a specific service
   ddog spans search \"service:api error:true\" --limit 50
 
+  # Build the query from flags
+  ddog spans search --service web --env prod --attr http.status_code=500
+
   # Complex query with resource filtering
   ddog spans search \"service:web resource_name:/api/users/*\" | jq 'select(.duration > 1000000000)'
 
+  # Count matching spans without fetching them
+  ddog spans search \"service:web error:true\" --last 15m --count
+
+  # Open the same search in the Trace Explorer
+  ddog spans search \"service:web error:true\" --last 1h --open
+
 Documentation:
   https://docs.datadoghq.com/tracing/trace_explorer/query_syntax/")]
     Search {
         /// Datadog query string (e.g., "service:web env:prod @duration:>1s")
-        #[arg(long_help = "Datadog query string using Datadog's APM search syntax.
+        #[arg(
+            default_value = "*",
+            add = ArgValueCandidates::new(completers::saved_queries),
+            long_help = "Datadog query string using Datadog's APM search syntax.
+Defaults to \"*\", so --service/--env/--status/--attr can build the query alone.
 
 Supports:
   • Service filtering: service:api, service:web
@@ -59,7 +81,8 @@ Examples:
   \"service:api env:prod\"
   \"@duration:>1s\"
   \"service:web AND error:true\"
-  \"resource_name:/api/users/* @duration:[100ms TO 1s]\"")]
+  \"resource_name:/api/users/* @duration:[100ms TO 1s]\""
+        )]
         query: String,
 
         #[command(flatten)]
@@ -67,5 +90,498 @@ Examples:
 
         #[command(flatten)]
         pagination: Pagination,
+
+        #[command(flatten)]
+        filters: QueryFilters,
+
+        #[command(flatten)]
+        routing: OwnerRouting,
+
+        #[command(flatten)]
+        anonymization: Anonymization,
+
+        #[command(flatten)]
+        watch: Watch,
+
+        /// What to return: spans (default) or traces (one root span per trace)
+        #[arg(
+            long = "type",
+            value_name = "TYPE",
+            default_value = "spans",
+            value_parser = PossibleValuesParser::new(["spans", "traces"])
+                .map(|t| t.parse::<SpansSearchType>().expect("type is a possible value")),
+            long_help = "What to return.
+
+  spans    Every span matching the query (default)
+  traces   Only matching spans that are the root of their trace, so each
+           trace is written once
+
+With traces, the query is still matched against spans: a trace appears when
+its root span matches, and spans further down are dropped as pages arrive.
+
+Examples:
+  ddog spans search \"service:web env:prod\" --type traces --last 15m"
+        )]
+        search_type: SpansSearchType,
+
+        /// Open the search in the Datadog Trace Explorer instead of running it
+        #[arg(
+            long,
+            conflicts_with_all = ["route_by_owner", "notify_owners", "sample", "watch", "search_type"],
+            long_help = "Open the search in the Datadog Trace Explorer instead of running it.
+
+The URL carries the query (after --service/--env/... flags, saved queries, and
+workspace defaults are applied) and the time range with relative times fixed
+to now. It is also printed to stderr, so it can be copied when no browser can
+be started (e.g., over SSH)."
+        )]
+        open: bool,
+
+        /// Print only the number of matching spans instead of the spans
+        #[arg(
+            long,
+            conflicts_with_all = ["open", "route_by_owner", "notify_owners", "max_pages", "max_bytes", "sample", "watch", "search_type"],
+            long_help = "Print only the number of matching spans instead of the spans.
+
+Counts with the aggregate endpoint in one request, however many spans match,
+and writes a single record:
+
+  {\"query\": \"service:api status:error\", \"from\": \"now-15m\", \"to\": \"now\", \"count\": 42}
+
+With --raw, writes just the number, for alert scripts:
+
+  [ \"$(ddog spans search 'service:api status:error' --last 15m --count --raw)\" -gt 100 ] && page-oncall"
+        )]
+        count: bool,
+
+        /// With --count, write the bare number instead of a record
+        #[arg(long, requires = "count")]
+        raw: bool,
+    },
+
+    /// Follow spans matching a query as they arrive, like tail -f
+    #[command(
+        long_about = "Follow spans matching a query as they arrive, like tail -f.
+
+Writes the spans since --from (5 minutes ago by default), oldest first, then
+polls the spans search every --interval until Ctrl-C and writes each new span
+as it is indexed, to watch failing requests roll in during a deploy.
+
+Each poll pages through its results with the API's cursor and starts 5
+minutes before the newest span seen, but not before --from, so spans indexed
+late are still caught; spans already written are recognized by their ID and
+skipped. A relative --from such as now-5m moves forward with each poll.
+
+After the first poll, each poll that finds spans prints a one-line summary to
+stderr with the most common resource:
+
+  [10:00:30] 5 new spans, top pattern (3): GET /api/checkout
+
+This is `ddog spans search QUERY --from FROM --watch INTERVAL` with defaults
+suited to following a live service.
+
+Output Format:
+  Each line contains one span, as with `ddog spans search`.
+
+Examples:
+  # Watch failing requests during a deploy
+  ddog spans tail \"service:api error:true\"
+
+  # Slow checkout spans, as text, with a bell for errors
+  ddog spans tail \"service:web resource_name:*checkout* @duration:>2s\" --format text --bell
+
+  # Start from the last 15 minutes and poll every 30 seconds
+  ddog spans tail --service api --env prod --from now-15m --interval 30s"
+    )]
+    Tail {
+        /// Datadog query string (e.g., "service:api error:true")
+        #[arg(default_value = "*", add = ArgValueCandidates::new(completers::saved_queries))]
+        query: String,
+
+        /// Earliest spans to write (relative, ISO8601, or Unix ms)
+        #[arg(short, long, default_value = "now-5m")]
+        from: String,
+
+        /// Time between polls (e.g. 10s), at least 5s
+        #[arg(
+            long,
+            value_name = "DURATION",
+            default_value = "5s",
+            value_parser = parse_watch_interval
+        )]
+        interval: Duration,
+
+        /// Ring the terminal bell when a poll finds spans, three times for errors
+        #[arg(
+            long,
+            long_help = "Ring the terminal bell on stderr when a poll writes new spans.
+
+The bell rings three times instead of once when a new span has an error or
+more severe status, so a background terminal can grab attention before a
+monitor would fire."
+        )]
+        bell: bool,
+
+        #[command(flatten)]
+        filters: QueryFilters,
+
+        #[command(flatten)]
+        anonymization: Anonymization,
+    },
+
+    /// Count spans over time grouped by a facet, optionally as a heatmap
+    #[command(
+        long_about = "Count APM spans matching a query per time bucket and facet value.
+
+Uses the spans aggregation API, so counts cover every matching span rather
+than a page of results. Only the --top facet values by volume are kept.
+
+Output Format:
+  Each line contains one time bucket for one facet value:
+  {\"timestamp\":\"2024-01-15T10:00:00.000Z\",\"group\":\"web\",\"count\":42.0}
+
+  With --chart heatmap, a shaded grid is printed instead, one row per facet
+  value ordered by when it first saw spans.
+
+Examples:
+  # Which service started erroring first?
+  ddog spans aggregate \"status:error\" --group-by service --interval 10m --chart heatmap
+
+  # Slow requests per endpoint as an HTML heatmap
+  ddog spans aggregate \"@duration:>1s\" --group-by resource_name --chart heatmap --html slow.html
+
+  # Records to a file, histograms to the terminal
+  ddog spans aggregate \"status:error\" --group-by service --interval 1m --viz histogram > errors.ndjson"
+    )]
+    Aggregate {
+        /// Datadog query string (e.g., "status:error")
+        #[arg(default_value = "*", add = ArgValueCandidates::new(completers::saved_queries))]
+        query: String,
+
+        #[command(flatten)]
+        time_range: TimeRange,
+
+        #[command(flatten)]
+        filters: QueryFilters,
+
+        #[command(flatten)]
+        aggregation: Aggregation,
+
+        #[command(flatten)]
+        watch: Watch,
+    },
+
+    /// Compute latency percentiles over time, for SLO spot-checks
+    #[command(
+        long_about = "Compute latency percentiles of the spans matching a query over time.
+
+Uses the spans aggregation API on @duration, so percentiles cover every
+matching span rather than a page of results. Handy for spot-checking an SLO
+without building a dashboard.
+
+Supported percentiles are 50 (the median), 75, 90, 95, 98, and 99.
+
+Output Format:
+  Each line contains one time bucket, with durations in milliseconds:
+  {\"timestamp\":\"2024-01-15T10:00:00.000Z\",\"p50_ms\":12.5,\"p95_ms\":48.2,\"p99_ms\":120.0}
+
+  Percentiles of buckets without spans are left out.
+
+Examples:
+  # Checkout latency over the last 4 hours
+  ddog spans latency \"service:api resource_name:/checkout\" --percentiles 50,95,99 --interval 5m --last 4h
+
+  # Buckets breaching a 500 ms p99 objective
+  ddog spans latency --service api --last 1d | jq 'select(.p99_ms > 500)'"
+    )]
+    Latency {
+        /// Datadog query string (e.g., "service:api resource_name:/checkout")
+        #[arg(default_value = "*", add = ArgValueCandidates::new(completers::saved_queries))]
+        query: String,
+
+        #[command(flatten)]
+        time_range: TimeRange,
+
+        #[command(flatten)]
+        filters: QueryFilters,
+
+        /// Comma-separated percentiles to compute (50, 75, 90, 95, 98, 99)
+        #[arg(
+            long,
+            value_name = "LIST",
+            value_delimiter = ',',
+            default_value = "50,95,99"
+        )]
+        percentiles: Vec<Percentile>,
+
+        /// Time bucket size (e.g. 1m, 5m, 1h)
+        #[arg(long, value_name = "DURATION", default_value = "5m", value_parser = parse_interval)]
+        interval: String,
+    },
+
+    /// Report total spans, errors, and error percentage per facet value
+    #[command(
+        long_about = "Report the error rate of the spans matching a query per facet value.
+
+Counts spans per --group-by value and status in one aggregation, so counts
+cover every matching span rather than a page of results. Only the --top
+values by span count are kept, and groups are written most errors first.
+
+Output Format:
+  Each line contains one facet value:
+  {\"group\":\"POST /checkout\",\"total\":1200,\"errors\":36,\"error_pct\":3.0}
+
+Examples:
+  # Which endpoints are failing?
+  ddog spans error-rate \"service:api\" --group-by resource_name --from now-1h
+
+  # Services above 1% errors in production
+  ddog spans error-rate --env prod --last 15m | jq 'select(.error_pct > 1)'"
+    )]
+    ErrorRate {
+        /// Datadog query string (e.g., "service:api")
+        #[arg(default_value = "*", add = ArgValueCandidates::new(completers::saved_queries))]
+        query: String,
+
+        #[command(flatten)]
+        time_range: TimeRange,
+
+        #[command(flatten)]
+        filters: QueryFilters,
+
+        /// Facet to group by (e.g., service, resource_name, @http.status_code)
+        #[arg(long, value_name = "FACET", default_value = "service")]
+        group_by: String,
+
+        /// Number of facet values to report, by span count
+        #[arg(long, value_name = "N", default_value = "20")]
+        top: i64,
+    },
+
+    /// Show the spans of one trace as a call tree, a diagram, or a flamegraph's folded stacks
+    #[command(
+        long_about = "Show the spans of one trace, depth-first, as a call tree.
+
+Fetches every span of the trace within the time range (widen it with --last
+for older traces) and writes them in call order: each span follows its
+parent, and siblings are ordered by start time.
+
+Output Format:
+  Each line contains one span:
+  {\"span_id\":\"2\",\"parent_id\":\"1\",\"service\":\"api\",\"resource\":\"GET /users\",
+   \"operation\":\"http.request\",\"start\":\"2024-01-15T10:00:00.010000Z\",\"duration_ms\":30.0,
+   \"error\":false,\"depth\":1,\"offset_ms\":10.0}
+
+  With --format mermaid-sequence or --format plantuml-sequence, the calls
+  between services are drawn as a sequence diagram instead, labelled with each
+  call's start offset and duration:
+
+  sequenceDiagram
+      participant p0 as web
+      participant p1 as api
+      p0->>p1: GET /users (+10.0 ms)
+      p1-->>p0: 30.0 ms
+
+  With --format waterfall, every span is drawn as a bar on the trace's
+  timeline, indented by depth, with its duration. Error spans are marked with
+  ✗, and drawn in red when stdout is a terminal:
+
+                       0 ms                                                   50.0 ms
+  web GET /checkout    │████████████████████████████████████████████████████████████│ 50.0 ms
+    api GET /users     │            ████████████████████████████████████            │ 30.0 ms
+      db SELECT users  │                  ██████                                    │  5.0 ms ✗
+
+  With --format folded, each span is written as a folded stack, the input of
+  flamegraph.pl and inferno: the path of frames (service operation resource)
+  from the root, and the span's self time in microseconds (its duration less
+  its children's):
+
+  web http.request GET /checkout 20000
+  web http.request GET /checkout;api http.request GET /users 25000
+  web http.request GET /checkout;api http.request GET /users;db postgres.query SELECT users 5000
+
+Examples:
+  # Call tree with indentation by depth
+  ddog spans trace 1234567890 --last 1d | jq -r '(\"  \" * .depth) + .service + \" \" + .resource'
+
+  # Sequence diagram for a postmortem
+  ddog spans trace 1234567890 --last 1d --format mermaid-sequence > trace.mmd
+
+  # Where did the time go?
+  ddog spans trace 1234567890 --last 1d --format waterfall
+
+  # Flamegraph (https://github.com/brendangregg/FlameGraph or inferno)
+  ddog spans trace 1234567890 --last 1d --format folded | flamegraph.pl > trace.svg"
+    )]
+    Trace {
+        /// Trace ID as shown in the Datadog UI
+        #[arg(value_name = "TRACE_ID")]
+        trace_id: String,
+
+        #[command(flatten)]
+        time_range: TimeRange,
+    },
+
+    /// Summarize calls between services as dependency edges
+    #[command(
+        long_about = "Summarize the calls between services in the spans matching a query.
+
+Fetches the spans matching QUERY in the time range, up to --limit, and
+treats each span whose parent is among them and belongs to another service as
+a call from the parent's service to the span's. Calls are folded into one
+edge per caller and callee, busiest first, with the callee spans' p95
+latency. Spans whose parent wasn't fetched are left out, so raise --limit
+for a busy environment.
+
+Output Format:
+  Each line contains one edge:
+  {\"caller\":\"web\",\"callee\":\"api\",\"requests\":1200,\"errors\":3,\"p95_ms\":45.2}
+
+  With --format dot, a Graphviz digraph is written instead, each edge
+  labelled with its requests and p95 latency and drawn red when some calls
+  failed.
+
+Examples:
+  # Who calls whom in production
+  ddog spans deps \"env:prod\" --from now-1h
+
+  # Render the dependency graph
+  ddog spans deps \"env:prod\" --last 15m --format dot | dot -Tsvg > deps.svg
+
+  # Slowest downstream dependencies of the api
+  ddog spans deps --env prod --last 1h | jq -s 'map(select(.caller == \"api\")) | sort_by(-.p95_ms)'"
+    )]
+    Deps {
+        /// Datadog query string (e.g., "env:prod")
+        #[arg(default_value = "*", add = ArgValueCandidates::new(completers::saved_queries))]
+        query: String,
+
+        #[command(flatten)]
+        time_range: TimeRange,
+
+        #[command(flatten)]
+        filters: QueryFilters,
+
+        /// Maximum number of spans to fetch (0 for all of them)
+        #[arg(short, long, value_name = "N", default_value = "10000")]
+        limit: u64,
+    },
+
+    /// Break spans down by ingestion reason, sampling priority, and retention per service
+    #[command(
+        long_about = "Break spans down per service by how they were ingested and retained.
+
+Packages the \"why can't I find my trace?\" investigation: for each service,
+spans matching the query are counted by
+
+  ingestion_reasons      Mechanism that ingested them: auto and rule (head-based
+                         sampling), manual, error, rare, single_span, ...
+  sampling_priorities    Sampling decision (@_sampling_priority_v1): -1 and 0
+                         reject the trace, 1 and 2 keep it
+  retained_by            What keeps indexed spans searchable past 15 minutes:
+                         retention_filter or diversity_sampling
+
+and findings flag where data is likely dropped:
+
+  • Head-based sampling keeps under 10% of spans, leaving the error and rare
+    samplers to ingest fragments of traces
+  • Over half the spans carry a rejecting sampling priority
+  • Spans are kept only by diversity sampling, with no retention filter
+
+Over a range within the last 15 minutes all ingested spans are live, so
+retained_by is mostly N/A; use a longer range to check retention.
+
+Output Format:
+  Each line contains one service, most spans first:
+  {\"service\":\"checkout\",\"spans\":1000.0,
+   \"ingestion_reasons\":{\"error\":95.0,\"rule\":5.0},
+   \"sampling_priorities\":{\"0\":95.0,\"2\":5.0},
+   \"retained_by\":{\"diversity_sampling\":10.0},
+   \"findings\":[\"Only 5% of spans were kept by head-based sampling ...\"]}
+
+Examples:
+  ddog spans sampling-report --last 1d
+  ddog spans sampling-report \"env:prod\" --service checkout --last 4h
+
+  # Services with findings only
+  ddog spans sampling-report --last 1d | jq 'select(.findings != [])'"
+    )]
+    SamplingReport {
+        /// Datadog query string (e.g., "env:prod")
+        #[arg(default_value = "*", add = ArgValueCandidates::new(completers::saved_queries))]
+        query: String,
+
+        #[command(flatten)]
+        time_range: TimeRange,
+
+        #[command(flatten)]
+        filters: QueryFilters,
+
+        /// Number of services to report, by span count
+        #[arg(long, value_name = "N", default_value = "20")]
+        top: i64,
+    },
+
+    /// Audit APM retention filters: which spans are indexed
+    RetentionFilters {
+        #[command(subcommand)]
+        action: SpansRetentionFiltersAction,
     },
+
+    /// Audit span-based metrics
+    Metrics {
+        #[command(subcommand)]
+        action: SpansMetricsAction,
+    },
+}
+
+/// Actions on APM retention filters.
+#[derive(Subcommand, Debug)]
+pub enum SpansRetentionFiltersAction {
+    /// List the org's retention filters with their queries and rates
+    #[command(
+        long_about = "List the retention filters that decide which ingested spans are indexed.
+
+Spans older than the live window (15 minutes) are searchable only if a
+retention filter indexed them, so a `spans search` over a longer range can
+return fewer spans than were ingested: spans matching no enabled filter are
+not indexed, and a filter with a rate below 1.0 indexes only that share of
+its spans. Filters are listed in the order Datadog evaluates them.
+
+Output Format:
+  One line per filter, in the API's shape:
+  {\"id\":\"7RBOb7dLSYWI01yc3pIH8w\",\"type\":\"apm_retention_filter\",\"attributes\":{
+   \"name\":\"Errors\",\"enabled\":true,\"filter\":{\"query\":\"status:error\"},
+   \"filter_type\":\"spans-errors-sampling-processor\",\"rate\":1.0}}
+
+Examples:
+  ddog spans retention-filters list
+
+  # Enabled filters and the share of spans each keeps
+  ddog spans retention-filters list | jq -r 'select(.attributes.enabled) | \"\\(.attributes.rate)\\t\\(.attributes.filter.query)\"'"
+    )]
+    List,
+}
+
+/// Actions on span-based metrics.
+#[derive(Subcommand, Debug)]
+pub enum SpansMetricsAction {
+    /// List the org's span-based metrics with their definitions
+    #[command(long_about = "List the metrics Datadog generates from ingested spans.
+
+Span-based metrics are computed from every ingested span, before retention
+filters apply, so they can count spans that a `spans search` past the live
+window no longer finds.
+
+Output Format:
+  One line per metric, in the API's shape:
+  {\"id\":\"checkout.latency\",\"type\":\"spans_metrics\",\"attributes\":{
+   \"compute\":{\"aggregation_type\":\"distribution\",\"path\":\"@duration\"},
+   \"filter\":{\"query\":\"service:checkout\"},
+   \"group_by\":[{\"path\":\"resource_name\",\"tag_name\":\"resource_name\"}]}}
+
+Examples:
+  ddog spans metrics list | jq -r .id
+  ddog spans metrics list | jq 'select(.attributes.filter.query | contains(\"service:checkout\"))'")]
+    List,
 }
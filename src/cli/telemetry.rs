@@ -0,0 +1,37 @@
+//! Telemetry domain command actions.
+
+use clap::Subcommand;
+
+/// Available actions for the telemetry domain.
+#[derive(Subcommand, Debug)]
+pub enum TelemetryAction {
+    /// Summarize locally recorded usage per command
+    #[command(
+        long_about = "Summarize the usage ddog has recorded locally, per command.
+
+Usage is recorded only when turned on in the user configuration file
+(~/.config/ddog/config.toml):
+
+  [telemetry]
+  enabled = true
+
+Each command run is then appended to telemetry.ndjson in the state directory
+($XDG_STATE_HOME/ddog or ~/.local/state/ddog), keeping the last 10000, with
+its name, duration, and the kind of error it failed with. Queries,
+arguments, and credentials are never recorded, and nothing is sent
+anywhere: collect the file or this report however suits your platform.
+
+Output Format:
+  Each line summarizes one command, most run first:
+  {\"command\":\"logs search\",\"runs\":412,\"errors\":9,\"error_kinds\":{\"rate_limited\":7,\"invalid_query\":2},
+   \"p50_ms\":840.0,\"p95_ms\":4210.0,\"last_run\":\"2024-01-15T10:00:00Z\"}
+
+Examples:
+  ddog telemetry report
+  ddog telemetry report --format text
+
+  # Share with the platform team
+  ddog telemetry report > \"usage-$(whoami).ndjson\""
+    )]
+    Report,
+}
@@ -0,0 +1,407 @@
+//! Flattening of logs and spans aggregation responses.
+//!
+//! Both aggregate APIs answer a timeseries count grouped by a facet with one
+//! bucket per facet value, each holding a list of `{time, value}` points.
+//! The bucket shapes differ slightly between logs and spans, so buckets are
+//! read as JSON and flattened into [`VolumePoint`]s here, into
+//! [`FacetValue`]s for a total count per facet value, or into
+//! [`GroupCount`]s for a total count per combination of facet values.
+//! Latency aggregations compute one timeseries per [`Percentile`], merged
+//! into a [`LatencyPoint`] per time bucket.
+
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// How to bucket events for a volume aggregation.
+#[derive(Debug, Clone)]
+pub struct Grouping {
+    /// Facet to group by (e.g., "service", "@http.status_code")
+    pub facet: String,
+
+    /// Time bucket size (e.g., "10m")
+    pub interval: String,
+
+    /// Number of facet values to keep, by volume
+    pub top: i64,
+}
+
+/// How to rank the values of a facet over a whole time range.
+#[derive(Debug, Clone)]
+pub struct Ranking {
+    /// Facet whose values are ranked (e.g., "service")
+    pub facet: String,
+
+    /// Number of values to keep
+    pub limit: i64,
+
+    /// What to rank values by
+    pub measure: Measure,
+}
+
+/// What to rank facet values by.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Measure {
+    /// Number of events
+    Count,
+
+    /// Number of distinct values of another facet (e.g., "@usr.id")
+    Unique(String),
+}
+
+impl FromStr for Measure {
+    type Err = String;
+
+    /// Parses `count` or `unique:<facet>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ddog::client::Measure;
+    ///
+    /// assert_eq!("count".parse(), Ok(Measure::Count));
+    /// assert_eq!("unique:@usr.id".parse(), Ok(Measure::Unique("@usr.id".to_string())));
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "count" => Ok(Measure::Count),
+            Some(("unique", facet)) if !facet.is_empty() => Ok(Measure::Unique(facet.to_string())),
+            _ => Err(format!(
+                "invalid measure '{}': expected count or unique:<facet> (e.g. unique:@usr.id)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Measure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Measure::Count => write!(f, "count"),
+            Measure::Unique(facet) => write!(f, "unique:{}", facet),
+        }
+    }
+}
+
+/// A latency percentile the spans aggregation API computes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Percentile(u8);
+
+impl Percentile {
+    /// Percentiles the API supports.
+    pub const SUPPORTED: [u8; 6] = [50, 75, 90, 95, 98, 99];
+
+    /// The percentile, from 50 to 99.
+    pub fn value(self) -> u8 {
+        self.0
+    }
+
+    /// Name of the percentile's field in a [`LatencyPoint`] (e.g., "p95_ms").
+    pub fn field(self) -> String {
+        format!("p{}_ms", self.0)
+    }
+}
+
+impl FromStr for Percentile {
+    type Err = String;
+
+    /// Parses a supported percentile, with or without a leading `p`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ddog::client::Percentile;
+    ///
+    /// let p95: Percentile = "95".parse().unwrap();
+    /// assert_eq!(p95.value(), 95);
+    /// assert_eq!("p99".parse::<Percentile>().unwrap().value(), 99);
+    /// assert!("80".parse::<Percentile>().is_err());
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits = s.trim().trim_start_matches('p');
+        match digits.parse::<u8>() {
+            Ok(p) if Self::SUPPORTED.contains(&p) => Ok(Percentile(p)),
+            _ => Err(format!(
+                "invalid percentile '{}': expected one of 50, 75, 90, 95, 98, 99",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Percentile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "p{}", self.0)
+    }
+}
+
+/// Number of events for one facet value in one time bucket.
+///
+/// This struct represents a flattened view of an aggregation bucket from the Datadog API.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct VolumePoint {
+    /// Start of the time bucket (RFC3339, as returned by Datadog)
+    pub timestamp: String,
+
+    /// Facet value the events share (e.g., "api" for --group-by service)
+    pub group: String,
+
+    /// Number of events in the bucket
+    pub count: f64,
+}
+
+/// Number of events (or another [`Measure`]) sharing one facet value over a
+/// whole time range.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct FacetValue {
+    /// Facet value (e.g., "api" for the service facet)
+    pub value: String,
+
+    /// Number of events with the value, or distinct values for [`Measure::Unique`]
+    pub count: f64,
+}
+
+/// Number of events sharing one combination of facet values.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct GroupCount {
+    /// Value of each grouped facet, keyed by facet
+    pub by: BTreeMap<String, String>,
+
+    /// Number of events with the values
+    pub count: f64,
+}
+
+/// Latency percentiles of the spans in one time bucket.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct LatencyPoint {
+    /// Start of the time bucket (RFC3339, as returned by Datadog)
+    pub timestamp: String,
+
+    /// Duration at each percentile in milliseconds, keyed by
+    /// [`Percentile::field`]; percentiles without spans in the bucket are left out
+    #[serde(flatten)]
+    pub percentiles: BTreeMap<String, f64>,
+}
+
+/// Flattens aggregation buckets into volume points for `facet`.
+///
+/// Accepts logs buckets (`{"by": ..., "computes": ...}`) and spans buckets
+/// (`{"attributes": {"by": ..., "compute": ...}}`). Buckets without a value
+/// for `facet` are grouped as "N/A", as in the Datadog UI.
+pub(crate) fn volume_points(buckets: &[Value], facet: &str) -> Vec<VolumePoint> {
+    let mut points = Vec::new();
+
+    for bucket in buckets {
+        let bucket = bucket.get("attributes").unwrap_or(bucket);
+        let group = group_value(bucket, facet);
+        let series = first_compute(bucket).and_then(Value::as_array);
+
+        for point in series.into_iter().flatten() {
+            let (Some(time), Some(count)) = (
+                point.get("time").and_then(Value::as_str),
+                point.get("value").and_then(Value::as_f64),
+            ) else {
+                continue;
+            };
+            points.push(VolumePoint {
+                timestamp: time.to_string(),
+                group: group.clone(),
+                count,
+            });
+        }
+    }
+
+    points
+}
+
+/// Merges the timeseries of an ungrouped spans latency aggregation, one
+/// compute per percentile in order, into points ordered by time.
+///
+/// Durations are converted from the nanoseconds of `@duration` to milliseconds.
+pub(crate) fn latency_points(buckets: &[Value], percentiles: &[Percentile]) -> Vec<LatencyPoint> {
+    let mut points: BTreeMap<&str, BTreeMap<String, f64>> = BTreeMap::new();
+
+    for bucket in buckets {
+        let bucket = bucket.get("attributes").unwrap_or(bucket);
+        let Some(computes) = bucket.get("computes").or_else(|| bucket.get("compute")) else {
+            continue;
+        };
+        for (i, percentile) in percentiles.iter().enumerate() {
+            let series = computes.get(format!("c{}", i)).and_then(Value::as_array);
+            for point in series.into_iter().flatten() {
+                let (Some(time), Some(ns)) = (
+                    point.get("time").and_then(Value::as_str),
+                    point.get("value").and_then(Value::as_f64),
+                ) else {
+                    continue;
+                };
+                points
+                    .entry(time)
+                    .or_default()
+                    .insert(percentile.field(), ns / 1_000_000.0);
+            }
+        }
+    }
+
+    points
+        .into_iter()
+        .map(|(timestamp, percentiles)| LatencyPoint {
+            timestamp: timestamp.to_string(),
+            percentiles,
+        })
+        .collect()
+}
+
+/// Reads total counts per facet value from aggregation buckets, in bucket order.
+pub(crate) fn facet_values(buckets: &[Value], facet: &str) -> Vec<FacetValue> {
+    buckets
+        .iter()
+        .filter_map(|bucket| {
+            let bucket = bucket.get("attributes").unwrap_or(bucket);
+            let count = first_compute(bucket).and_then(Value::as_f64)?;
+            Some(FacetValue {
+                value: group_value(bucket, facet),
+                count,
+            })
+        })
+        .collect()
+}
+
+/// Reads total counts per combination of `facets` values from aggregation buckets.
+pub(crate) fn group_counts(buckets: &[Value], facets: &[&str]) -> Vec<GroupCount> {
+    buckets
+        .iter()
+        .filter_map(|bucket| {
+            let bucket = bucket.get("attributes").unwrap_or(bucket);
+            let count = first_compute(bucket).and_then(Value::as_f64)?;
+            let by = facets
+                .iter()
+                .map(|facet| (facet.to_string(), group_value(bucket, facet)))
+                .collect();
+            Some(GroupCount { by, count })
+        })
+        .collect()
+}
+
+/// Reads the total of an ungrouped aggregation, such as a cardinality.
+pub(crate) fn total(buckets: &[Value]) -> Option<f64> {
+    let bucket = buckets.first()?;
+    first_compute(bucket.get("attributes").unwrap_or(bucket)).and_then(Value::as_f64)
+}
+
+/// A bucket's value for `facet`; "N/A" when missing, as in the Datadog UI.
+fn group_value(bucket: &Value, facet: &str) -> String {
+    match bucket.pointer("/by").and_then(|by| by.get(facet)) {
+        Some(Value::String(value)) => value.clone(),
+        Some(Value::Null) | None => "N/A".to_string(),
+        Some(value) => value.to_string(),
+    }
+}
+
+/// A bucket's first compute (`c0`), under `computes` (logs) or `compute` (spans).
+fn first_compute(bucket: &Value) -> Option<&Value> {
+    bucket
+        .get("computes")
+        .or_else(|| bucket.get("compute"))
+        .and_then(|computes| computes.get("c0"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_volume_points_logs() {
+        let buckets = vec![json!({
+            "by": {"service": "api"},
+            "computes": {"c0": [
+                {"time": "2024-01-15T10:00:00.000Z", "value": 3},
+                {"time": "2024-01-15T10:10:00.000Z", "value": 5}
+            ]}
+        })];
+        let points = volume_points(&buckets, "service");
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[1].group, "api");
+        assert_eq!(points[1].timestamp, "2024-01-15T10:10:00.000Z");
+        assert_eq!(points[1].count, 5.0);
+    }
+
+    #[test]
+    fn test_volume_points_spans() {
+        let buckets = vec![json!({
+            "attributes": {
+                "by": {"@http.status_code": 500},
+                "compute": {"c0": [{"time": "2024-01-15T10:00:00.000Z", "value": 7}]}
+            }
+        })];
+        let points = volume_points(&buckets, "@http.status_code");
+        assert_eq!(points[0].group, "500");
+        assert_eq!(points[0].count, 7.0);
+
+        let missing = volume_points(&buckets, "service");
+        assert_eq!(missing[0].group, "N/A");
+    }
+
+    #[test]
+    fn test_facet_values_and_total() {
+        let buckets = vec![
+            json!({"by": {"service": "api"}, "computes": {"c0": 42}}),
+            json!({"by": {"service": "web"}, "computes": {"c0": 7}}),
+        ];
+        let values = facet_values(&buckets, "service");
+        assert_eq!(values.len(), 2);
+        assert_eq!(values[0].value, "api");
+        assert_eq!(values[0].count, 42.0);
+
+        assert_eq!(total(&[json!({"computes": {"c0": 12}})]), Some(12.0));
+        assert_eq!(total(&[]), None);
+    }
+
+    #[test]
+    fn test_latency_points_merge_percentiles() {
+        let buckets = vec![json!({
+            "attributes": {
+                "compute": {
+                    "c0": [
+                        {"time": "2024-01-15T10:05:00.000Z", "value": 14_000_000},
+                        {"time": "2024-01-15T10:00:00.000Z", "value": 12_500_000}
+                    ],
+                    "c1": [
+                        {"time": "2024-01-15T10:00:00.000Z", "value": 80_000_000},
+                        {"time": "2024-01-15T10:05:00.000Z", "value": null}
+                    ]
+                }
+            }
+        })];
+        let percentiles: Vec<Percentile> =
+            ["50", "99"].iter().map(|p| p.parse().unwrap()).collect();
+        let points = latency_points(&buckets, &percentiles);
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0].timestamp, "2024-01-15T10:00:00.000Z");
+        assert_eq!(points[0].percentiles["p50_ms"], 12.5);
+        assert_eq!(points[0].percentiles["p99_ms"], 80.0);
+        // No spans at p99 in the second bucket
+        assert_eq!(points[1].percentiles.len(), 1);
+        assert_eq!(
+            serde_json::to_value(&points[0]).unwrap(),
+            json!({"timestamp": "2024-01-15T10:00:00.000Z", "p50_ms": 12.5, "p99_ms": 80.0})
+        );
+    }
+
+    #[test]
+    fn test_group_counts() {
+        let buckets = vec![json!({
+            "attributes": {
+                "by": {"service": "web", "ingestion_reason": "rule"},
+                "compute": {"c0": 12}
+            }
+        })];
+        let counts = group_counts(&buckets, &["service", "ingestion_reason", "env"]);
+        assert_eq!(counts[0].by["service"], "web");
+        assert_eq!(counts[0].by["ingestion_reason"], "rule");
+        assert_eq!(counts[0].by["env"], "N/A");
+        assert_eq!(counts[0].count, 12.0);
+    }
+}
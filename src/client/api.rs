@@ -0,0 +1,227 @@
+//! Raw requests to Datadog API endpoints.
+//!
+//! An escape hatch for endpoints without a dedicated wrapper: requests go
+//! through the shared HTTP client (proxy, retries, tracing) with the same
+//! credentials and site as every other command.
+
+use datadog_api_client::datadog::{self, Configuration};
+use reqwest::Method;
+use reqwest_middleware::ClientWithMiddleware;
+use serde_json::Value;
+
+use super::http::build_client;
+use super::{convert_datadog_error, status_error};
+use crate::config::Credentials;
+use crate::error::AppError;
+
+/// Client for arbitrary Datadog API endpoints.
+pub struct ApiClient {
+    client: ClientWithMiddleware,
+    base_url: String,
+    credentials: Credentials,
+}
+
+impl ApiClient {
+    /// Creates a new ApiClient for the configuration's site.
+    pub fn new(config: &Configuration, credentials: Credentials) -> Self {
+        Self {
+            client: build_client(config),
+            // Operations without their own server use the site's API host
+            base_url: config.get_operation_host(""),
+            credentials,
+        }
+    }
+
+    /// The full URL for an API path (e.g., "/api/v1/validate").
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url.trim_end_matches('/'), path)
+    }
+
+    /// Sends a GET request and returns the response body as JSON.
+    ///
+    /// An empty body is returned as `null`, and a body that isn't JSON as a string.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Config` if `path` doesn't start with `/` (full URLs
+    /// are rejected so credentials only go to the configured site), and the
+    /// converted API error for failed requests and non-success statuses.
+    pub async fn get(&self, path: &str, query: &[(String, String)]) -> Result<Value, AppError> {
+        self.send(Method::GET, path, query, None).await
+    }
+
+    /// Sends a POST request with a JSON body and returns the response body as JSON.
+    ///
+    /// Responses are handled as in [`ApiClient::get`].
+    ///
+    /// # Errors
+    ///
+    /// Same as [`ApiClient::get`].
+    pub async fn post(
+        &self,
+        path: &str,
+        query: &[(String, String)],
+        body: &Value,
+    ) -> Result<Value, AppError> {
+        self.send(Method::POST, path, query, Some(body)).await
+    }
+
+    async fn send(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(String, String)],
+        body: Option<&Value>,
+    ) -> Result<Value, AppError> {
+        if !path.starts_with('/') {
+            return Err(AppError::Config(format!(
+                "API path '{}' must start with '/' (e.g., /api/v2/logs/config/indexes)",
+                path
+            )));
+        }
+
+        let mut request = self
+            .client
+            .request(method, self.url(path))
+            .query(query)
+            .header("Accept", "application/json")
+            .header("DD-API-KEY", &self.credentials.api_key)
+            .header("DD-APPLICATION-KEY", &self.credentials.app_key);
+        if let Some(body) = body {
+            request = request
+                .header("Content-Type", "application/json")
+                .body(body.to_string());
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| convert_datadog_error(datadog::Error::<()>::ReqwestMiddleware(e), path))?;
+
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .map_err(|e| convert_datadog_error(datadog::Error::<()>::Reqwest(e), path))?;
+        if !status.is_success() {
+            return Err(status_error(status, &body, path));
+        }
+
+        if body.trim().is_empty() {
+            return Ok(Value::Null);
+        }
+        Ok(serde_json::from_str(&body).unwrap_or(Value::String(body)))
+    }
+}
+
+/// Parses `key=value` pairs separated by `&`, percent-decoding both sides.
+///
+/// A pair without `=` has an empty value.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::client::parse_query;
+///
+/// let pairs = parse_query("filter[query]=service%3Aapi&page[limit]=10").unwrap();
+/// assert_eq!(pairs[0], ("filter[query]".to_string(), "service:api".to_string()));
+/// assert_eq!(pairs[1], ("page[limit]".to_string(), "10".to_string()));
+/// ```
+///
+/// # Errors
+///
+/// Returns `AppError::Config` for a pair with an empty key or invalid percent-encoding.
+pub fn parse_query(query: &str) -> Result<Vec<(String, String)>, AppError> {
+    let decode = |s: &str| {
+        urlencoding::decode(&s.replace('+', " "))
+            .map(|s| s.into_owned())
+            .map_err(|e| AppError::Config(format!("Invalid query parameter '{}': {}", s, e)))
+    };
+
+    query
+        .trim_start_matches('?')
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            if key.is_empty() {
+                return Err(AppError::Config(format!(
+                    "Invalid query parameter '{}': expected KEY=VALUE",
+                    pair
+                )));
+            }
+            Ok((decode(key)?, decode(value)?))
+        })
+        .collect()
+}
+
+/// Splits a response body into records, one per listed item.
+///
+/// A top-level array yields its elements. An object yields the elements of
+/// its `data` array (the JSON:API shape of v2 endpoints) or, failing that, of
+/// its only array field (e.g., `indexes` or `host_list` in v1 endpoints).
+/// Anything else is a single record; `null` yields none.
+pub fn into_records(body: Value) -> Vec<Value> {
+    match body {
+        Value::Null => Vec::new(),
+        Value::Array(items) => items,
+        Value::Object(mut fields) => {
+            let arrays: Vec<&String> = fields
+                .iter()
+                .filter(|(_, value)| value.is_array())
+                .map(|(key, _)| key)
+                .collect();
+            let list = match arrays[..] {
+                _ if fields.get("data").is_some_and(Value::is_array) => Some("data".to_string()),
+                [key] => Some(key.clone()),
+                _ => None,
+            };
+            match list.and_then(|key| fields.remove(&key)) {
+                Some(Value::Array(items)) => items,
+                _ => vec![Value::Object(fields)],
+            }
+        }
+        other => vec![other],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_into_records() {
+        assert_eq!(into_records(json!([1, 2])), vec![json!(1), json!(2)]);
+        assert_eq!(
+            into_records(json!({"data": [{"id": "a"}], "meta": {"page": {}}, "included": []})),
+            vec![json!({"id": "a"})]
+        );
+        assert_eq!(
+            into_records(json!({"indexes": [{"name": "main"}, {"name": "web"}]})),
+            vec![json!({"name": "main"}), json!({"name": "web"})]
+        );
+        assert_eq!(
+            into_records(json!({"valid": true})),
+            vec![json!({"valid": true})]
+        );
+        assert_eq!(
+            into_records(json!({"a": [], "b": []})),
+            vec![json!({"a": [], "b": []})]
+        );
+        assert!(into_records(Value::Null).is_empty());
+    }
+
+    #[test]
+    fn test_parse_query() {
+        assert_eq!(
+            parse_query("?from=now-1h&q=a+b&flag").unwrap(),
+            vec![
+                ("from".to_string(), "now-1h".to_string()),
+                ("q".to_string(), "a b".to_string()),
+                ("flag".to_string(), String::new()),
+            ]
+        );
+        assert!(parse_query("").unwrap().is_empty());
+        assert!(matches!(parse_query("=x"), Err(AppError::Config(_))));
+    }
+}
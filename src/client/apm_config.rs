@@ -0,0 +1,143 @@
+//! Datadog APM configuration API client wrapper.
+//!
+//! Lists the retention filters that decide which ingested spans are indexed
+//! (and so searchable past the live window), and the metrics Datadog
+//! generates from spans, with definitions as JSON in the API's own shape.
+
+use datadog_api_client::datadog::Configuration;
+use datadog_api_client::datadogV2::api_apm_retention_filters::APMRetentionFiltersAPI;
+use datadog_api_client::datadogV2::api_spans_metrics::SpansMetricsAPI;
+use reqwest_middleware::ClientWithMiddleware;
+use serde_json::Value;
+
+use super::builder::{BuildableClient, ClientBuilder};
+use super::convert_datadog_error;
+use super::http::build_client;
+use crate::error::AppError;
+
+/// Path of the APM retention filters endpoint.
+pub const RETENTION_FILTERS_PATH: &str = "/api/v2/apm/config/retention-filters";
+
+/// Path of the span-based metrics endpoint.
+pub const SPAN_METRICS_PATH: &str = "/api/v2/apm/config/metrics";
+
+/// Client for reading APM retention filters and span-based metrics.
+///
+/// Wraps the Datadog SDK's APMRetentionFiltersAPI and SpansMetricsAPI.
+pub struct ApmConfigClient {
+    retention_filters: APMRetentionFiltersAPI,
+    metrics: SpansMetricsAPI,
+}
+
+impl ApmConfigClient {
+    /// Creates a new ApmConfigClient with the given configuration.
+    pub fn new(config: Configuration) -> Self {
+        let http = build_client(&config);
+        Self::from_parts(config, http)
+    }
+
+    /// Starts building a client from explicit credentials and site.
+    pub fn builder() -> ClientBuilder<Self> {
+        ClientBuilder::new()
+    }
+
+    /// Lists the org's retention filters in the order Datadog applies them,
+    /// each as `{"id", "type", "attributes"}`.
+    pub async fn retention_filters(&self) -> Result<Vec<Value>, AppError> {
+        let response = self
+            .retention_filters
+            .list_apm_retention_filters()
+            .await
+            .map_err(|e| convert_datadog_error(e, "APM retention filters"))?;
+        response
+            .data
+            .into_iter()
+            .map(|filter| Ok(serde_json::to_value(filter)?))
+            .collect()
+    }
+
+    /// Lists the org's span-based metrics, each as `{"id", "type",
+    /// "attributes"}`.
+    pub async fn metrics(&self) -> Result<Vec<Value>, AppError> {
+        let response = self
+            .metrics
+            .list_spans_metrics()
+            .await
+            .map_err(|e| convert_datadog_error(e, "span-based metrics"))?;
+        response
+            .data
+            .unwrap_or_default()
+            .into_iter()
+            .map(|metric| Ok(serde_json::to_value(metric)?))
+            .collect()
+    }
+}
+
+impl BuildableClient for ApmConfigClient {
+    fn from_parts(config: Configuration, http: ClientWithMiddleware) -> Self {
+        Self {
+            retention_filters: APMRetentionFiltersAPI::with_client_and_config(
+                config.clone(),
+                http.clone(),
+            ),
+            metrics: SpansMetricsAPI::with_client_and_config(config, http),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::MockTransport;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_list() {
+        let transport = MockTransport::new()
+            .respond(
+                "GET",
+                RETENTION_FILTERS_PATH,
+                200,
+                json!({"data": [{
+                    "id": "7RBOb7dLSYWI01yc3pIH8w",
+                    "type": "apm_retention_filter",
+                    "attributes": {
+                        "name": "Errors",
+                        "enabled": true,
+                        "filter": {"query": "status:error"},
+                        "filter_type": "spans-errors-sampling-processor",
+                        "rate": 1.0,
+                    },
+                }]}),
+            )
+            .respond(
+                "GET",
+                SPAN_METRICS_PATH,
+                200,
+                json!({"data": [{
+                    "id": "checkout.latency",
+                    "type": "spans_metrics",
+                    "attributes": {
+                        "compute": {"aggregation_type": "distribution", "path": "@duration"},
+                        "filter": {"query": "service:checkout"},
+                    },
+                }]}),
+            );
+        let client = ApmConfigClient::builder()
+            .api_key("test")
+            .app_key("test")
+            .transport(transport)
+            .build()
+            .unwrap();
+
+        let filters = client.retention_filters().await.unwrap();
+        assert_eq!(filters.len(), 1);
+        assert_eq!(filters[0]["attributes"]["filter"]["query"], "status:error");
+        assert_eq!(filters[0]["attributes"]["rate"], 1.0);
+
+        let metrics = client.metrics().await.unwrap();
+        assert_eq!(metrics.len(), 1);
+        assert_eq!(metrics[0]["id"], "checkout.latency");
+        assert_eq!(metrics[0]["attributes"]["compute"]["path"], "@duration");
+    }
+}
@@ -0,0 +1,177 @@
+//! Builder for the Datadog clients.
+//!
+//! Lets code embedding the library construct a client from explicit
+//! credentials and a site, instead of the `DD_*` environment variables the
+//! CLI reads.
+
+use datadog_api_client::datadog::{APIKey, Configuration};
+use reqwest_middleware::ClientWithMiddleware;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use super::http::{build_client_with, build_isolated_client};
+use super::transport::DatadogTransport;
+
+use crate::config::{Credentials, read_key};
+use crate::error::AppError;
+
+/// Builds a client of type `C`, such as [`LogsClient`](super::LogsClient).
+///
+/// Credentials not set on the builder are read from `DD_API_KEY` and
+/// `DD_APP_KEY`, and the site from `DD_SITE`, defaulting to `datadoghq.com`.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::client::LogsClient;
+///
+/// let client = LogsClient::builder()
+///     .api_key("my-api-key")
+///     .app_key("my-app-key")
+///     .site("datadoghq.eu")
+///     .build()
+///     .unwrap();
+/// ```
+pub struct ClientBuilder<C> {
+    api_key: Option<String>,
+    app_key: Option<String>,
+    site: Option<String>,
+    transport: Option<Arc<dyn DatadogTransport>>,
+    client: PhantomData<fn() -> C>,
+}
+
+/// A client that [`ClientBuilder`] can build.
+pub trait BuildableClient: Sized {
+    /// Creates the client from its configuration and HTTP client.
+    #[doc(hidden)]
+    fn from_parts(config: Configuration, http: ClientWithMiddleware) -> Self;
+
+    /// Whether the client runs its requests on a runtime of its own, and so
+    /// can't share connections with clients on other runtimes.
+    #[doc(hidden)]
+    const OWN_RUNTIME: bool = false;
+}
+
+impl<C: BuildableClient> ClientBuilder<C> {
+    pub(crate) fn new() -> Self {
+        Self {
+            api_key: None,
+            app_key: None,
+            site: None,
+            transport: None,
+            client: PhantomData,
+        }
+    }
+
+    /// Sets the API key, sent as `DD-API-KEY`.
+    pub fn api_key(mut self, key: impl Into<String>) -> Self {
+        self.api_key = Some(key.into());
+        self
+    }
+
+    /// Sets the application key, sent as `DD-APPLICATION-KEY`.
+    pub fn app_key(mut self, key: impl Into<String>) -> Self {
+        self.app_key = Some(key.into());
+        self
+    }
+
+    /// Sets both keys.
+    pub fn credentials(self, credentials: Credentials) -> Self {
+        self.api_key(credentials.api_key)
+            .app_key(credentials.app_key)
+    }
+
+    /// Sets the Datadog site (e.g., "datadoghq.eu", "us5.datadoghq.com").
+    pub fn site(mut self, site: impl Into<String>) -> Self {
+        self.site = Some(site.into());
+        self
+    }
+
+    /// Sends requests through `transport` instead of the network, such as a
+    /// [`MockTransport`](super::MockTransport) in tests.
+    pub fn transport(mut self, transport: impl DatadogTransport + 'static) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Builds the client.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Config` if a key is empty, or is neither set nor in
+    /// its environment variable.
+    pub fn build(mut self) -> Result<C, AppError> {
+        let transport = self.transport.take();
+        let config = self.configuration()?;
+        let http = if C::OWN_RUNTIME {
+            build_isolated_client(&config, transport)
+        } else {
+            build_client_with(&config, transport)
+        };
+        Ok(C::from_parts(config, http))
+    }
+
+    fn configuration(self) -> Result<Configuration, AppError> {
+        let api_key = match self.api_key {
+            Some(key) => key,
+            None => read_key("DD_API_KEY")?,
+        };
+        let app_key = match self.app_key {
+            Some(key) => key,
+            None => read_key("DD_APP_KEY")?,
+        };
+        for (name, key) in [("API key", &api_key), ("application key", &app_key)] {
+            if key.is_empty() {
+                return Err(AppError::Config(format!("The {} is empty", name)));
+            }
+        }
+
+        let mut config = Configuration::new();
+        if let Some(site) = self.site {
+            config.server_variables.insert("site".to_string(), site);
+        }
+        for (name, key) in [("apiKeyAuth", api_key), ("appKeyAuth", app_key)] {
+            config.set_auth_key(
+                name,
+                APIKey {
+                    key,
+                    prefix: String::new(),
+                },
+            );
+        }
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Captured(Configuration);
+
+    impl BuildableClient for Captured {
+        fn from_parts(config: Configuration, _http: ClientWithMiddleware) -> Self {
+            Self(config)
+        }
+    }
+
+    #[test]
+    fn test_build_sets_site() {
+        let Captured(config) = ClientBuilder::<Captured>::new()
+            .api_key("api")
+            .app_key("app")
+            .site("datadoghq.eu")
+            .build()
+            .unwrap();
+        assert_eq!(config.server_variables["site"], "datadoghq.eu");
+    }
+
+    #[test]
+    fn test_build_rejects_empty_key() {
+        let result = ClientBuilder::<Captured>::new()
+            .api_key("")
+            .app_key("app")
+            .build();
+        assert!(matches!(result, Err(AppError::Config(_))));
+    }
+}
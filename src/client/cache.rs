@@ -0,0 +1,426 @@
+//! On-disk cache of query responses (`--cache-ttl`, `--no-cache`).
+//!
+//! A [`CacheTransport`] answers a request from the response saved for the
+//! identical request, if it was saved within the TTL, and otherwise sends it
+//! on and saves a successful response. Requests are keyed by site, API and
+//! application keys, method, path, query string, and body, which together pin
+//! the endpoint, the query, its time range, and the page cursor; the key is
+//! hashed, so neither the keys nor the query are written in the clear as a
+//! file name.
+//!
+//! Only reads over a time range that has already ended are cached: `GET`
+//! requests and searches, aggregations, and metric queries, whose every time
+//! bound is absolute and in the past. A window ending `now` or in the future
+//! gets different results on every run, so those requests always reach
+//! Datadog, as do requests with no time range at all.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::{Method, Request, Response};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::compression::decode;
+use super::http::shared_client;
+use super::transport::{DatadogTransport, pass_on};
+use crate::error::AppError;
+
+/// TTL of cached responses when not set.
+pub const DEFAULT_CACHE_TTL: &str = "5m";
+
+/// Header carrying the API key, which tells organizations apart.
+const API_KEY_HEADER: &str = "dd-api-key";
+
+/// Header carrying the application key, whose permissions and restriction
+/// queries decide what a search returns.
+const APP_KEY_HEADER: &str = "dd-application-key";
+
+/// Query parameters and body fields holding a time bound.
+const TIME_FIELDS: [&str; 4] = ["from", "to", "start", "end"];
+
+/// Unix timestamps above this are in milliseconds rather than seconds.
+const MILLIS_THRESHOLD: i64 = 100_000_000_000;
+
+/// A saved response.
+#[derive(Debug, Serialize, Deserialize)]
+struct Entry {
+    status: u16,
+    body: String,
+}
+
+/// A transport answering repeated query requests from disk.
+///
+/// Misses are sent to Datadog with the HTTP client of the client the cache
+/// is installed in, so they go through its proxy and connection pool.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ddog::client::{CacheTransport, LogsClient};
+/// use std::time::Duration;
+///
+/// let cache = CacheTransport::new("/tmp/ddog-cache", Duration::from_secs(300));
+/// let client = LogsClient::builder().transport(cache).build().unwrap();
+/// ```
+pub struct CacheTransport {
+    dir: PathBuf,
+    ttl: Duration,
+    inner: Option<Arc<dyn DatadogTransport>>,
+}
+
+impl CacheTransport {
+    /// Caches responses in `dir` for `ttl`, sending misses to Datadog.
+    pub fn new(dir: impl Into<PathBuf>, ttl: Duration) -> Self {
+        Self {
+            dir: dir.into(),
+            ttl,
+            inner: None,
+        }
+    }
+
+    /// Sends misses through `transport` instead of the network, such as a
+    /// [`MockTransport`](super::MockTransport) in tests.
+    pub fn with_transport(mut self, transport: impl DatadogTransport + 'static) -> Self {
+        self.inner = Some(Arc::new(transport));
+        self
+    }
+
+    /// Removes the responses saved more than the TTL ago, returning how many
+    /// were removed. A missing cache directory has nothing to remove.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Io` if the cache directory can't be read.
+    pub fn prune(&self) -> Result<usize, AppError> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e.into()),
+        };
+        let mut removed = 0;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !self.is_fresh(&path) && std::fs::remove_file(&path).is_ok() {
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Whether the file at `path` was written within the TTL.
+    fn is_fresh(&self, path: &Path) -> bool {
+        std::fs::metadata(path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age <= self.ttl)
+    }
+
+    fn load(&self, path: &Path) -> Option<Entry> {
+        if !self.is_fresh(path) {
+            return None;
+        }
+        let contents = std::fs::read_to_string(path).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    fn save(&self, path: &Path, entry: &Entry) -> Result<(), AppError> {
+        std::fs::create_dir_all(&self.dir)?;
+        // Written aside and renamed, so a concurrent run never reads half an entry
+        let staged = path.with_extension("tmp");
+        std::fs::write(&staged, serde_json::to_vec(entry)?)?;
+        std::fs::rename(&staged, path)?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl DatadogTransport for CacheTransport {
+    /// Answers `request`, sending a miss to Datadog through the shared
+    /// connection pool, as there is no client's own to use.
+    async fn send(&self, request: Request) -> Result<Response, AppError> {
+        self.send_with(request, &shared_client(None)).await
+    }
+
+    async fn send_with(
+        &self,
+        request: Request,
+        network: &reqwest::Client,
+    ) -> Result<Response, AppError> {
+        let inner = self.inner.as_ref();
+        if !is_cacheable(&request, Utc::now()) {
+            return pass_on(inner, request, network).await;
+        }
+        let path = self.dir.join(format!("{}.json", cache_key(&request)));
+        if let Some(entry) = self.load(&path) {
+            tracing::debug!(target: "ddog::http", path = %request.url().path(), "answered from cache");
+            return text_response(entry.status, entry.body);
+        }
+
+        let response = pass_on(inner, request, network).await?;
+        let status = response.status().as_u16();
+        if !response.status().is_success() {
+            return Ok(response);
+        }
+        let mut headers = response.headers().clone();
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|e| AppError::Api(e.to_string()))?;
+        let bytes = decode(&mut headers, &bytes).map_err(|e| AppError::Api(e.to_string()))?;
+        if let Ok(body) = std::str::from_utf8(&bytes) {
+            let entry = Entry {
+                status,
+                body: body.to_string(),
+            };
+            // A cache that can't be written only costs the next run a request
+            if let Err(e) = self.save(&path, &entry) {
+                tracing::debug!(target: "ddog::http", error = %e, "failed to cache response");
+            }
+        }
+
+        let mut rebuilt = http::Response::builder().status(status);
+        if let Some(h) = rebuilt.headers_mut() {
+            *h = headers;
+        }
+        let rebuilt = rebuilt
+            .body(bytes)
+            .map_err(|e| AppError::Api(e.to_string()))?;
+        Ok(Response::from(rebuilt))
+    }
+}
+
+/// Hex SHA-256 of everything that makes a request distinct.
+fn cache_key(request: &Request) -> String {
+    let url = request.url();
+    let mut hasher = Sha256::new();
+    for part in [
+        url.host_str().unwrap_or_default().as_bytes(),
+        request
+            .headers()
+            .get(API_KEY_HEADER)
+            .map(|v| v.as_bytes())
+            .unwrap_or_default(),
+        request
+            .headers()
+            .get(APP_KEY_HEADER)
+            .map(|v| v.as_bytes())
+            .unwrap_or_default(),
+        request.method().as_str().as_bytes(),
+        url.path().as_bytes(),
+        url.query().unwrap_or_default().as_bytes(),
+        request
+            .body()
+            .and_then(|b| b.as_bytes())
+            .unwrap_or_default(),
+    ] {
+        hasher.update((part.len() as u64).to_le_bytes());
+        hasher.update(part);
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Whether a request is a read over a time range that ended before `now`.
+fn is_cacheable(request: &Request, now: DateTime<Utc>) -> bool {
+    let path = request.url().path();
+    let read = match *request.method() {
+        Method::GET => true,
+        Method::POST => {
+            path.ends_with("/search")
+                || path.ends_with("/aggregate")
+                || path.starts_with("/api/v2/query/")
+        }
+        _ => false,
+    };
+    if !read {
+        return false;
+    }
+
+    let mut bounds: Vec<Value> = request
+        .url()
+        .query_pairs()
+        .filter(|(key, _)| {
+            let key = key
+                .strip_prefix("filter[")
+                .unwrap_or(key)
+                .trim_end_matches(']');
+            TIME_FIELDS.contains(&key)
+        })
+        .map(|(_, value)| Value::String(value.into_owned()))
+        .collect();
+    if let Some(body) = request
+        .body()
+        .and_then(|b| b.as_bytes())
+        .and_then(|b| serde_json::from_slice::<Value>(b).ok())
+    {
+        collect_bounds(&body, &mut bounds);
+    }
+
+    !bounds.is_empty()
+        && bounds
+            .iter()
+            .all(|bound| parse_bound(bound).is_some_and(|time| time <= now))
+}
+
+/// Collects the `from` and `to` fields found anywhere in a request body.
+fn collect_bounds(value: &Value, bounds: &mut Vec<Value>) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                if key == "from" || key == "to" {
+                    bounds.push(child.clone());
+                } else {
+                    collect_bounds(child, bounds);
+                }
+            }
+        }
+        Value::Array(items) => items.iter().for_each(|item| collect_bounds(item, bounds)),
+        _ => {}
+    }
+}
+
+/// Parses an absolute time bound: Unix seconds or milliseconds, as a number
+/// or a string, or an RFC 3339 timestamp. Relative times give `None`.
+fn parse_bound(bound: &Value) -> Option<DateTime<Utc>> {
+    let timestamp = match bound {
+        Value::Number(n) => n.as_i64()?,
+        Value::String(s) => match s.parse::<i64>() {
+            Ok(timestamp) => timestamp,
+            Err(_) => return DateTime::parse_from_rfc3339(s).ok().map(|t| t.to_utc()),
+        },
+        _ => return None,
+    };
+    if timestamp > MILLIS_THRESHOLD {
+        DateTime::from_timestamp_millis(timestamp)
+    } else {
+        DateTime::from_timestamp(timestamp, 0)
+    }
+}
+
+fn text_response(status: u16, body: String) -> Result<Response, AppError> {
+    let response = http::Response::builder()
+        .status(status)
+        .header("content-type", "application/json")
+        .body(body.into_bytes())
+        .map_err(|e| AppError::Api(e.to_string()))?;
+    Ok(Response::from(response))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{LogsClient, LogsSearchRequest, MockTransport};
+    use serde_json::json;
+
+    const SEARCH: &str = "/api/v2/logs/events/search";
+
+    fn post(path: &str, body: Value) -> Request {
+        let mut request = Request::new(
+            Method::POST,
+            format!("https://api.datadoghq.com{}", path)
+                .parse()
+                .unwrap(),
+        );
+        *request.body_mut() = Some(serde_json::to_vec(&body).unwrap().into());
+        request
+    }
+
+    #[test]
+    fn test_only_ended_absolute_windows_are_cacheable() {
+        let now = DateTime::parse_from_rfc3339("2024-01-15T12:00:00Z")
+            .unwrap()
+            .to_utc();
+        let search = |from: &str, to: &str| {
+            post(
+                SEARCH,
+                json!({"filter": {"query": "*", "from": from, "to": to}}),
+            )
+        };
+        assert!(is_cacheable(
+            &search("2024-01-15T10:00:00Z", "2024-01-15T11:00:00Z"),
+            now
+        ));
+        assert!(is_cacheable(&search("1705312800000", "1705316400000"), now));
+        assert!(!is_cacheable(&search("now-15m", "now"), now));
+        assert!(!is_cacheable(
+            &search("2024-01-15T10:00:00Z", "2024-01-15T13:00:00Z"),
+            now
+        ));
+        // Timeseries queries carry their window as numbers
+        assert!(is_cacheable(
+            &post(
+                "/api/v2/query/timeseries",
+                json!({"data": {"attributes": {"from": 1705312800000i64, "to": 1705316400000i64}}})
+            ),
+            now
+        ));
+        // Writes and requests without a window always go through
+        assert!(!is_cacheable(
+            &post("/api/v1/monitor", json!({"from": "1705312800"})),
+            now
+        ));
+        let monitors = Request::new(
+            Method::GET,
+            "https://api.datadoghq.com/api/v1/monitor".parse().unwrap(),
+        );
+        assert!(!is_cacheable(&monitors, now));
+    }
+
+    #[test]
+    fn test_key_covers_body_and_credentials() {
+        let body =
+            |cursor: &str| json!({"filter": {"from": "1", "to": "2"}, "page": {"cursor": cursor}});
+        let key = |body: Value, api_key: &str, app_key: &str| {
+            let mut request = post(SEARCH, body);
+            let headers = request.headers_mut();
+            headers.insert(API_KEY_HEADER, api_key.parse().unwrap());
+            headers.insert(APP_KEY_HEADER, app_key.parse().unwrap());
+            cache_key(&request)
+        };
+        let base = key(body("a"), "k1", "a1");
+        assert_eq!(base, key(body("a"), "k1", "a1"));
+        assert_ne!(base, key(body("b"), "k1", "a1"));
+        assert_ne!(base, key(body("a"), "k2", "a1"));
+        assert_ne!(base, key(body("a"), "k1", "a2"));
+    }
+
+    #[tokio::test]
+    async fn test_repeated_search_answered_from_cache() {
+        let dir = std::env::temp_dir().join(format!("ddog-cache-{}", std::process::id()));
+        let upstream = MockTransport::new().respond(
+            "POST",
+            SEARCH,
+            200,
+            json!({"data": [{"id": "a", "type": "log", "attributes": {}}]}),
+        );
+        let client = || {
+            LogsClient::builder()
+                .api_key("test")
+                .app_key("test")
+                .transport(
+                    CacheTransport::new(&dir, Duration::from_secs(60))
+                        .with_transport(upstream.clone()),
+                )
+                .build()
+                .unwrap()
+        };
+        let request = LogsSearchRequest::builder()
+            .from("2024-01-15T10:00:00Z")
+            .to("2024-01-15T11:00:00Z")
+            .build();
+
+        let first = client().fetch(&request, 0).await.unwrap();
+        let second = client().fetch(&request, 0).await.unwrap();
+        assert_eq!(first[0].id, second[0].id);
+        assert_eq!(upstream.requests().len(), 1);
+
+        let expired = CacheTransport::new(&dir, Duration::ZERO);
+        std::thread::sleep(Duration::from_millis(10));
+        assert_eq!(expired.prune().unwrap(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,397 @@
+//! Record/replay cassettes for offline testing.
+//!
+//! A [`CassetteTransport`] in record mode sends requests to Datadog as usual
+//! and saves each request and response to a JSON file; in replay mode it
+//! answers from that file without touching the network, so tests and bug
+//! reports run deterministically without credentials or API quota.
+//!
+//! Cassettes are sanitized as they're written: request headers, which carry
+//! the API and application keys, are never saved, and any key that appears
+//! in a URL or body is replaced with `REDACTED`.
+//!
+//! Replay matches requests by method and path, answering each with the next
+//! response recorded for it. Query strings and bodies are not compared, as
+//! they embed times resolved from "now" that change between runs.
+
+use async_trait::async_trait;
+use reqwest::{Request, Response};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use super::compression::decode;
+use super::http::shared_client;
+use super::transport::{DatadogTransport, pass_on};
+use crate::error::AppError;
+
+/// Environment variable naming a cassette to replay, or to record when the
+/// file doesn't exist.
+pub const CASSETTE_ENV: &str = "DDOG_CASSETTE";
+
+/// Replaces secrets in saved cassettes.
+const REDACTED: &str = "REDACTED";
+
+/// Headers whose values are scrubbed from anything saved.
+const SECRET_HEADERS: [&str; 2] = ["dd-api-key", "dd-application-key"];
+
+/// One request and the response Datadog gave it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    /// HTTP method
+    pub method: String,
+
+    /// URL path (e.g., "/api/v2/logs/events/search")
+    pub path: String,
+
+    /// URL query string, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+
+    /// Request body, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request: Option<Value>,
+
+    /// Response status
+    pub status: u16,
+
+    /// Response body (a string when it isn't JSON)
+    pub response: Value,
+}
+
+/// Contents of a cassette file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Cassette {
+    /// Interactions, in the order they were recorded
+    pub interactions: Vec<Interaction>,
+}
+
+impl Cassette {
+    /// Reads a cassette file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Config` if the file can't be read or isn't a cassette.
+    pub fn load(path: &Path) -> Result<Self, AppError> {
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            AppError::Config(format!("Failed to read cassette {}: {}", path.display(), e))
+        })?;
+        serde_json::from_str(&contents)
+            .map_err(|e| AppError::Config(format!("Invalid cassette {}: {}", path.display(), e)))
+    }
+
+    fn save(&self, path: &Path) -> Result<(), AppError> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)? + "\n")?;
+        Ok(())
+    }
+}
+
+/// A transport that records interactions to a cassette or replays them.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ddog::client::{CassetteTransport, LogsClient};
+///
+/// // Replays the file if it exists, otherwise records it from Datadog
+/// let transport = CassetteTransport::open("tests/cassettes/errors.json").unwrap();
+/// let client = LogsClient::builder().transport(transport).build().unwrap();
+/// ```
+pub struct CassetteTransport {
+    mode: Mode,
+}
+
+/// Recorded responses queued by method and path.
+type Responses = HashMap<(String, String), VecDeque<(u16, Value)>>;
+
+enum Mode {
+    Record {
+        path: PathBuf,
+        inner: Option<Arc<dyn DatadogTransport>>,
+        cassette: Mutex<Cassette>,
+    },
+    Replay {
+        path: PathBuf,
+        responses: Mutex<Responses>,
+    },
+}
+
+impl CassetteTransport {
+    /// Records every interaction to `path`, replacing anything already there.
+    ///
+    /// Requests are sent to Datadog with the HTTP client of the client the
+    /// cassette is installed in.
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        Self::recording(path.into(), None)
+    }
+
+    /// Replays the interactions saved in `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Config` if the cassette can't be read.
+    pub fn replay(path: impl Into<PathBuf>) -> Result<Self, AppError> {
+        let path = path.into();
+        let mut responses = Responses::new();
+        for interaction in Cassette::load(&path)?.interactions {
+            responses
+                .entry((interaction.method, interaction.path))
+                .or_default()
+                .push_back((interaction.status, interaction.response));
+        }
+        Ok(Self {
+            mode: Mode::Replay {
+                path,
+                responses: Mutex::new(responses),
+            },
+        })
+    }
+
+    /// Replays `path` if it exists, and records it otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Config` if an existing cassette can't be read.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, AppError> {
+        let path = path.into();
+        if path.exists() {
+            Self::replay(path)
+        } else {
+            Ok(Self::record(path))
+        }
+    }
+
+    /// Opens the cassette named by `DDOG_CASSETTE`, if it's set.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Config` if an existing cassette can't be read.
+    pub fn from_env() -> Result<Option<Self>, AppError> {
+        match std::env::var_os(CASSETTE_ENV).filter(|v| !v.is_empty()) {
+            Some(path) => Self::open(PathBuf::from(path)).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Whether requests are answered from the cassette rather than Datadog.
+    pub fn is_replay(&self) -> bool {
+        matches!(self.mode, Mode::Replay { .. })
+    }
+
+    fn recording(path: PathBuf, inner: Option<Arc<dyn DatadogTransport>>) -> Self {
+        Self {
+            mode: Mode::Record {
+                path,
+                inner,
+                cassette: Mutex::new(Cassette::default()),
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl DatadogTransport for CassetteTransport {
+    /// Answers `request`, recording through the shared connection pool, as
+    /// there is no client's own to use.
+    async fn send(&self, request: Request) -> Result<Response, AppError> {
+        self.send_with(request, &shared_client(None)).await
+    }
+
+    async fn send_with(
+        &self,
+        request: Request,
+        network: &reqwest::Client,
+    ) -> Result<Response, AppError> {
+        let method = request.method().to_string();
+        let path = request.url().path().to_string();
+        match &self.mode {
+            Mode::Replay {
+                path: file,
+                responses,
+            } => {
+                let (status, body) = responses
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .get_mut(&(method.clone(), path.clone()))
+                    .and_then(VecDeque::pop_front)
+                    .ok_or_else(|| {
+                        AppError::Api(format!(
+                            "No recorded response for {} {} in cassette {}; record it again",
+                            method,
+                            path,
+                            file.display()
+                        ))
+                    })?;
+                json_response(status, &body)
+            }
+            Mode::Record {
+                path: file,
+                inner,
+                cassette,
+            } => {
+                let secrets: Vec<String> = SECRET_HEADERS
+                    .iter()
+                    .filter_map(|name| request.headers().get(*name)?.to_str().ok())
+                    .filter(|value| !value.is_empty())
+                    .map(String::from)
+                    .collect();
+                let query = request.url().query().map(|q| scrub_str(q, &secrets));
+                let body = request
+                    .body()
+                    .and_then(|b| b.as_bytes())
+                    .map(|b| scrub(parse_body(b), &secrets));
+
+                let response = pass_on(inner.as_ref(), request, network).await?;
+                let status = response.status().as_u16();
+                let mut headers = response.headers().clone();
+                let bytes = response
+                    .bytes()
+                    .await
+                    .map_err(|e| AppError::Api(e.to_string()))?;
+                // Cassettes keep readable JSON, whatever encoding it came in
+                let bytes =
+                    decode(&mut headers, &bytes).map_err(|e| AppError::Api(e.to_string()))?;
+
+                let interaction = Interaction {
+                    method,
+                    path,
+                    query,
+                    request: body,
+                    status,
+                    response: scrub(parse_body(&bytes), &secrets),
+                };
+                {
+                    let mut cassette = cassette.lock().unwrap_or_else(|e| e.into_inner());
+                    cassette.interactions.push(interaction);
+                    // Saved after every interaction, so an interrupted run keeps what it got
+                    cassette.save(file)?;
+                }
+
+                let mut response = http::Response::builder().status(status);
+                if let Some(h) = response.headers_mut() {
+                    *h = headers;
+                }
+                let response = response
+                    .body(bytes)
+                    .map_err(|e| AppError::Api(e.to_string()))?;
+                Ok(Response::from(response))
+            }
+        }
+    }
+}
+
+fn json_response(status: u16, body: &Value) -> Result<Response, AppError> {
+    let bytes = match body {
+        Value::String(text) => text.clone().into_bytes(),
+        body => serde_json::to_vec(body)?,
+    };
+    let response = http::Response::builder()
+        .status(status)
+        .header("content-type", "application/json")
+        .body(bytes)
+        .map_err(|e| AppError::Api(e.to_string()))?;
+    Ok(Response::from(response))
+}
+
+/// Parses a body as JSON, keeping it as a string when it isn't.
+fn parse_body(bytes: &[u8]) -> Value {
+    serde_json::from_slice(bytes)
+        .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(bytes).into_owned()))
+}
+
+/// Replaces every secret in the strings of a JSON value.
+fn scrub(value: Value, secrets: &[String]) -> Value {
+    match value {
+        Value::String(s) => Value::String(scrub_str(&s, secrets)),
+        Value::Array(items) => Value::Array(items.into_iter().map(|v| scrub(v, secrets)).collect()),
+        Value::Object(fields) => Value::Object(
+            fields
+                .into_iter()
+                .map(|(k, v)| (scrub_str(&k, secrets), scrub(v, secrets)))
+                .collect(),
+        ),
+        value => value,
+    }
+}
+
+fn scrub_str(s: &str, secrets: &[String]) -> String {
+    secrets.iter().fold(s.to_string(), |s, secret| {
+        s.replace(secret.as_str(), REDACTED)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{LogsClient, LogsSearchRequest, MockTransport};
+    use serde_json::json;
+
+    const SEARCH: &str = "/api/v2/logs/events/search";
+
+    fn client(transport: CassetteTransport) -> LogsClient {
+        LogsClient::builder()
+            .api_key("secret-api-key")
+            .app_key("secret-app-key")
+            .transport(transport)
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_record_then_replay() {
+        let path = std::env::temp_dir().join(format!("ddog-cassette-{}.json", std::process::id()));
+        let upstream = MockTransport::new()
+            .respond(
+                "POST",
+                SEARCH,
+                200,
+                json!({"data": [{"id": "a", "type": "log", "attributes": {"message": "key secret-api-key"}}], "meta": {"page": {"after": "next"}}}),
+            )
+            .respond("POST", SEARCH, 200, json!({"data": [{"id": "b", "type": "log"}]}));
+        let recorder = CassetteTransport::recording(path.clone(), Some(Arc::new(upstream)));
+        let request = LogsSearchRequest::builder().page_limit(1).build();
+        let recorded = client(recorder).fetch(&request, 0).await.unwrap();
+
+        let cassette = Cassette::load(&path).unwrap();
+        assert_eq!(cassette.interactions.len(), 2);
+        let saved = std::fs::read_to_string(&path).unwrap();
+        assert!(!saved.contains("secret-api-key"));
+        assert!(saved.contains(REDACTED));
+
+        let replayer = CassetteTransport::replay(&path).unwrap();
+        assert!(replayer.is_replay());
+        let replayed = client(replayer).fetch(&request, 0).await.unwrap();
+        let ids = |logs: &[crate::records::LogEvent]| -> Vec<Option<String>> {
+            logs.iter().map(|l| l.id.clone()).collect()
+        };
+        assert_eq!(ids(&replayed), ids(&recorded));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_replay_without_recording_fails() {
+        let path =
+            std::env::temp_dir().join(format!("ddog-cassette-empty-{}.json", std::process::id()));
+        Cassette::default().save(&path).unwrap();
+        let result = client(CassetteTransport::replay(&path).unwrap())
+            .fetch(&LogsSearchRequest::builder().build(), 0)
+            .await;
+        assert!(matches!(result, Err(AppError::Api(msg)) if msg.contains("No recorded response")));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_scrub_nested_values() {
+        let secrets = ["k3y".to_string()];
+        assert_eq!(
+            scrub(json!({"a": ["x k3y", 1], "k3y": null}), &secrets),
+            json!({"a": ["x REDACTED", 1], "REDACTED": null})
+        );
+        assert_eq!(parse_body(b"not json"), json!("not json"));
+    }
+}
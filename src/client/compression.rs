@@ -0,0 +1,252 @@
+//! Compressed API responses.
+//!
+//! Log pages are large and compress well, so every request asks for a gzip
+//! or zstd response body (`Accept-Encoding: gzip, zstd`), unless turned off
+//! with [`set_compression`](super::http::set_compression). The middleware
+//! here decompresses responses before anything else reads them, except the
+//! bodies of search pages, which [`pages`](super::pages) decompresses chunk
+//! by chunk as it parses them.
+
+use async_trait::async_trait;
+use reqwest::header::{ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_LENGTH, HeaderMap, HeaderValue};
+use reqwest::{Request, Response};
+use reqwest_middleware::{Middleware, Next};
+use std::io::Write;
+use task_local_extensions::Extensions;
+
+use super::http;
+
+/// Encodings asked for, most preferred first.
+const ENCODINGS: &str = "gzip, zstd";
+
+/// Marks a request whose caller decompresses the response body as it reads
+/// it, see [`Decoder`].
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct StreamedBody;
+
+/// Decompresses a response body fed in chunks.
+pub(crate) enum Decoder {
+    Gzip(flate2::write::GzDecoder<Vec<u8>>),
+    Zstd(zstd::stream::write::Decoder<'static, Vec<u8>>),
+}
+
+impl Decoder {
+    /// The decoder for a response's `Content-Encoding`, or `None` if its
+    /// body isn't compressed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for an encoding that wasn't asked for.
+    pub(crate) fn for_headers(headers: &HeaderMap) -> std::io::Result<Option<Self>> {
+        let Some(encoding) = headers.get(CONTENT_ENCODING) else {
+            return Ok(None);
+        };
+        match encoding.to_str().unwrap_or_default().trim() {
+            "" | "identity" => Ok(None),
+            "gzip" | "x-gzip" => Ok(Some(Self::Gzip(flate2::write::GzDecoder::new(Vec::new())))),
+            "zstd" => Ok(Some(Self::Zstd(zstd::stream::write::Decoder::new(
+                Vec::new(),
+            )?))),
+            other => Err(std::io::Error::other(format!(
+                "unsupported response encoding '{}'",
+                other
+            ))),
+        }
+    }
+
+    /// Decompresses a chunk, returning the bytes it completes.
+    pub(crate) fn write(&mut self, chunk: &[u8]) -> std::io::Result<Vec<u8>> {
+        match self {
+            Self::Gzip(decoder) => {
+                decoder.write_all(chunk)?;
+                decoder.flush()?;
+                Ok(std::mem::take(decoder.get_mut()))
+            }
+            Self::Zstd(decoder) => {
+                decoder.write_all(chunk)?;
+                decoder.flush()?;
+                Ok(std::mem::take(decoder.get_mut()))
+            }
+        }
+    }
+
+    /// Returns the rest of the body once every chunk is written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the body was cut short.
+    pub(crate) fn finish(self) -> std::io::Result<Vec<u8>> {
+        match self {
+            Self::Gzip(decoder) => decoder.finish(),
+            Self::Zstd(mut decoder) => {
+                decoder.flush()?;
+                Ok(decoder.into_inner())
+            }
+        }
+    }
+}
+
+/// Decompresses a whole body encoded as `headers` say, removing the headers
+/// that describe the encoded body.
+pub(crate) fn decode(headers: &mut HeaderMap, body: &[u8]) -> std::io::Result<Vec<u8>> {
+    let Some(mut decoder) = Decoder::for_headers(headers)? else {
+        return Ok(body.to_vec());
+    };
+    let mut decoded = decoder.write(body)?;
+    decoded.extend(decoder.finish()?);
+    headers.remove(CONTENT_ENCODING);
+    headers.remove(CONTENT_LENGTH);
+    Ok(decoded)
+}
+
+/// Middleware asking for compressed responses and decompressing them.
+pub(crate) struct CompressionMiddleware;
+
+#[async_trait]
+impl Middleware for CompressionMiddleware {
+    async fn handle(
+        &self,
+        mut req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        if !http::compression_enabled() {
+            return next.run(req, extensions).await;
+        }
+        req.headers_mut()
+            .entry(ACCEPT_ENCODING)
+            .or_insert(HeaderValue::from_static(ENCODINGS));
+        let response = next.run(req, extensions).await?;
+
+        // Bodies rewritten above are read whole anyway
+        let streamed = extensions.get::<StreamedBody>().is_some()
+            && response.status().is_success()
+            && !http::rewrites_bodies();
+        if streamed || !response.headers().contains_key(CONTENT_ENCODING) {
+            return Ok(response);
+        }
+        decompress(response).await
+    }
+}
+
+/// Reads and decompresses a response's body, rebuilding the response.
+async fn decompress(response: Response) -> reqwest_middleware::Result<Response> {
+    let status = response.status();
+    let version = response.version();
+    let mut headers = response.headers().clone();
+    let body = response.bytes().await?;
+    let body =
+        decode(&mut headers, &body).map_err(|e| reqwest_middleware::Error::Middleware(e.into()))?;
+
+    let mut rebuilt = ::http::Response::builder().status(status).version(version);
+    if let Some(h) = rebuilt.headers_mut() {
+        *h = headers;
+    }
+    let rebuilt = rebuilt
+        .body(body)
+        .map_err(|e| reqwest_middleware::Error::Middleware(e.into()))?;
+    Ok(Response::from(rebuilt))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{DatadogTransport, LogsClient, LogsSearchRequest};
+    use crate::error::AppError;
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+
+    /// Answers with zstd compressed JSON, in order, keeping the
+    /// `Accept-Encoding` of each request.
+    struct Zstd {
+        responses: Mutex<Vec<(u16, serde_json::Value)>>,
+        accepted: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl DatadogTransport for Zstd {
+        async fn send(&self, request: Request) -> Result<Response, AppError> {
+            let accepted = request.headers().get(ACCEPT_ENCODING);
+            self.accepted.lock().unwrap().push(
+                accepted
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or_default()
+                    .to_string(),
+            );
+            let (status, body) = self.responses.lock().unwrap().remove(0);
+            let body = zstd::encode_all(body.to_string().as_bytes(), 0).unwrap();
+            let response = ::http::Response::builder()
+                .status(status)
+                .header(CONTENT_ENCODING, "zstd")
+                .body(body)
+                .unwrap();
+            Ok(Response::from(response))
+        }
+    }
+
+    fn headers(encoding: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_ENCODING, encoding.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_decodes_gzip_and_zstd_in_chunks() {
+        let body = br#"{"data":[{"id":"a"}]}"#.repeat(100);
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gzip.write_all(&body).unwrap();
+        let gzip = gzip.finish().unwrap();
+        let zstd = zstd::encode_all(&body[..], 0).unwrap();
+
+        for (encoding, compressed) in [("gzip", gzip), ("zstd", zstd)] {
+            let mut decoder = Decoder::for_headers(&headers(encoding)).unwrap().unwrap();
+            let mut decoded = Vec::new();
+            for chunk in compressed.chunks(7) {
+                decoded.extend(decoder.write(chunk).unwrap());
+            }
+            decoded.extend(decoder.finish().unwrap());
+            assert_eq!(decoded, body, "{}", encoding);
+        }
+
+        let mut gzipped = headers("gzip");
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gzip.write_all(&body).unwrap();
+        assert_eq!(decode(&mut gzipped, &gzip.finish().unwrap()).unwrap(), body);
+        assert!(gzipped.is_empty());
+        assert_eq!(decode(&mut HeaderMap::new(), b"plain").unwrap(), b"plain");
+        assert!(Decoder::for_headers(&headers("br")).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_search_decompresses_pages_and_errors() {
+        let log = json!({"id": "a", "type": "log", "attributes": {"message": "hello"}});
+        let accepted = Arc::new(Mutex::new(Vec::new()));
+        let transport = Zstd {
+            responses: Mutex::new(vec![
+                (200, json!({"data": [log], "meta": {}})),
+                (403, json!({"errors": ["bad key"]})),
+            ]),
+            accepted: accepted.clone(),
+        };
+        let client = LogsClient::builder()
+            .api_key("test")
+            .app_key("test")
+            .transport(transport)
+            .build()
+            .unwrap();
+        let request = LogsSearchRequest::builder().build();
+
+        let logs = client.fetch(&request, 0).await.unwrap();
+        assert_eq!(logs[0].message.as_deref(), Some("hello"));
+        match client.fetch(&request, 0).await {
+            Err(AppError::Forbidden(message)) => {
+                assert!(message.contains("bad key"), "{}", message)
+            }
+            other => panic!(
+                "expected a forbidden error, got {:?}",
+                other.map(|l| l.len())
+            ),
+        }
+        assert_eq!(*accepted.lock().unwrap(), [ENCODINGS, ENCODINGS]);
+    }
+}
@@ -0,0 +1,179 @@
+//! Datadog Events API client wrapper.
+//!
+//! Provides a simplified interface for fetching monitor alert events and
+//! agent start events with automatic pagination.
+
+use datadog_api_client::datadog::Configuration;
+use datadog_api_client::datadogV2::api_events::{EventsAPI, ListEventsOptionalParams};
+use datadog_api_client::datadogV2::model::{EventResponse, EventsSort};
+use futures_util::{Stream, StreamExt};
+
+use super::convert_datadog_error;
+use super::http::build_client;
+use crate::error::AppError;
+
+/// Event search matching the event an agent sends when it starts, titled
+/// "Datadog agent (v. 7.50.0) started on web-1".
+const AGENT_START_QUERY: &str = "\"Datadog agent\" \"started on\"";
+
+/// Client for querying Datadog events.
+///
+/// Wraps the Datadog SDK's EventsAPI with automatic pagination support.
+pub struct EventsClient {
+    api: EventsAPI,
+}
+
+impl EventsClient {
+    /// Creates a new EventsClient with the given configuration.
+    pub fn new(config: Configuration) -> Self {
+        Self {
+            api: EventsAPI::with_client_and_config(config.clone(), build_client(&config)),
+        }
+    }
+
+    /// Fetches a monitor's alert events, oldest first.
+    ///
+    /// Returns an async stream of alert, warning, no-data, and recovery events
+    /// for the monitor. The stream handles pagination automatically.
+    ///
+    /// # Arguments
+    ///
+    /// * `monitor_id` - Numeric monitor ID
+    /// * `from` - Start time: relative ("now-1h"), ISO8601 ("2024-01-15T10:00:00Z"), or Unix ms ("1705315200000")
+    /// * `to` - End time: relative ("now"), ISO8601 ("2024-01-15T10:00:00Z"), or Unix ms ("1705315200000")
+    pub fn monitor_events(
+        &self,
+        monitor_id: i64,
+        from: &str,
+        to: &str,
+    ) -> impl Stream<Item = Result<MonitorEvent, AppError>> + '_ {
+        let params = ListEventsOptionalParams::default()
+            .filter_query(format!("source:alert @monitor_id:{}", monitor_id))
+            .filter_from(from.to_string())
+            .filter_to(to.to_string())
+            .sort(EventsSort::TIMESTAMP_ASCENDING)
+            .page_limit(1000);
+
+        self.api
+            .list_events_with_pagination(params)
+            .filter_map(move |result| async move {
+                match result {
+                    Ok(event) => MonitorEvent::from_response(event)
+                        .filter(|e| e.monitor_id.is_none_or(|id| id == monitor_id))
+                        .map(Ok),
+                    Err(e) => Some(Err(convert_datadog_error(e, "events"))),
+                }
+            })
+    }
+
+    /// Fetches agent start events, oldest first.
+    ///
+    /// Each agent (re)start sends one event, so repeated starts on a host
+    /// point to restarts or reboots. Events without a host are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - Start time: relative ("now-7d"), ISO8601 ("2024-01-15T10:00:00Z"), or Unix ms ("1705315200000")
+    /// * `to` - End time: relative ("now"), ISO8601 ("2024-01-15T10:00:00Z"), or Unix ms ("1705315200000")
+    pub fn agent_starts(
+        &self,
+        from: &str,
+        to: &str,
+    ) -> impl Stream<Item = Result<AgentStart, AppError>> + '_ {
+        let params = ListEventsOptionalParams::default()
+            .filter_query(AGENT_START_QUERY.to_string())
+            .filter_from(from.to_string())
+            .filter_to(to.to_string())
+            .sort(EventsSort::TIMESTAMP_ASCENDING)
+            .page_limit(1000);
+
+        self.api
+            .list_events_with_pagination(params)
+            .filter_map(|result| async move {
+                match result {
+                    Ok(event) => AgentStart::from_response(event).map(Ok),
+                    Err(e) => Some(Err(convert_datadog_error(e, "events"))),
+                }
+            })
+    }
+}
+
+/// An agent start on a host.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct AgentStart {
+    /// Host the agent started on
+    pub host: String,
+
+    /// Timestamp in Unix milliseconds
+    pub timestamp: i64,
+}
+
+impl AgentStart {
+    /// Flattens an API event, returning `None` unless it has a timestamp, a
+    /// host, and an agent start title.
+    fn from_response(event: EventResponse) -> Option<Self> {
+        let outer = event.attributes?;
+        let inner = outer.attributes.unwrap_or_default();
+        let timestamp = outer
+            .timestamp
+            .map(|t| t.timestamp_millis())
+            .or(inner.timestamp)?;
+        let title = inner.title.or(outer.message).unwrap_or_default();
+        let (_, titled_host) = title.split_once(" started on ")?;
+
+        let host = inner
+            .hostname
+            .filter(|h| !h.is_empty())
+            .unwrap_or_else(|| titled_host.trim().to_string());
+        (!host.is_empty()).then_some(Self { host, timestamp })
+    }
+}
+
+/// A monitor state-change event.
+///
+/// This struct represents a flattened view of an alert event from the Datadog API.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct MonitorEvent {
+    /// ID of the monitor that fired the event
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub monitor_id: Option<i64>,
+
+    /// Timestamp in Unix milliseconds
+    pub timestamp: i64,
+
+    /// Event title (e.g., "[Triggered on {host:web-1}] High CPU")
+    pub title: String,
+
+    /// Event status (e.g., "error", "warning", "success")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+
+    /// Monitor group the event applies to, for multi-alert monitors
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+}
+
+impl MonitorEvent {
+    /// Flattens an API event, returning `None` if it has no timestamp.
+    fn from_response(event: EventResponse) -> Option<Self> {
+        let outer = event.attributes?;
+        let inner = outer.attributes.unwrap_or_default();
+        let timestamp = outer
+            .timestamp
+            .map(|t| t.timestamp_millis())
+            .or(inner.timestamp)?;
+        let group = inner
+            .monitor_groups
+            .flatten()
+            .filter(|groups| !groups.is_empty())
+            .map(|groups| groups.join(","));
+
+        Some(Self {
+            monitor_id: inner.monitor_id.flatten(),
+            timestamp,
+            title: inner.title.or(outer.message).unwrap_or_default(),
+            status: inner.status.map(|s| s.to_string()),
+            group,
+        })
+    }
+}
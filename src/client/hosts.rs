@@ -0,0 +1,104 @@
+//! Datadog Hosts API client wrapper.
+//!
+//! Provides a simplified interface for listing hosts with their agent
+//! metadata, with automatic pagination.
+
+use datadog_api_client::datadog::Configuration;
+use datadog_api_client::datadogV1::api_hosts::{HostsAPI, ListHostsOptionalParams};
+use datadog_api_client::datadogV1::model::Host;
+
+use super::convert_datadog_error;
+use super::http::build_client;
+use crate::error::AppError;
+
+/// Hosts returned per page; the API maximum.
+const PAGE_SIZE: i64 = 1000;
+
+/// Client for querying Datadog hosts.
+///
+/// Wraps the Datadog SDK's HostsAPI, fetching every page of results.
+pub struct HostsClient {
+    api: HostsAPI,
+}
+
+impl HostsClient {
+    /// Creates a new HostsClient with the given configuration.
+    pub fn new(config: Configuration) -> Self {
+        Self {
+            api: HostsAPI::with_client_and_config(config.clone(), build_client(&config)),
+        }
+    }
+
+    /// Lists hosts that reported since `from`, optionally filtered.
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - Host search as in the infrastructure list (e.g., "env:prod"); `None` for all hosts
+    /// * `from` - Only hosts that reported after this time (Unix seconds)
+    pub async fn list(
+        &self,
+        filter: Option<&str>,
+        from: i64,
+    ) -> Result<Vec<HostSummary>, AppError> {
+        let mut hosts = Vec::new();
+        let mut start = 0;
+        loop {
+            let mut params = ListHostsOptionalParams::default()
+                .from(from)
+                .start(start)
+                .count(PAGE_SIZE)
+                .include_hosts_metadata(true);
+            if let Some(filter) = filter {
+                params = params.filter(filter.to_string());
+            }
+
+            let page = self
+                .api
+                .list_hosts(params)
+                .await
+                .map_err(|e| convert_datadog_error(e, "hosts"))?
+                .host_list
+                .unwrap_or_default();
+            let last_page = (page.len() as i64) < PAGE_SIZE;
+            start += page.len() as i64;
+            hosts.extend(page.into_iter().filter_map(HostSummary::from_host));
+            if last_page {
+                return Ok(hosts);
+            }
+        }
+    }
+}
+
+/// A host's identity and agent state.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct HostSummary {
+    /// Host name as used in the host tag
+    pub name: String,
+
+    /// Other names the host is known by (e.g., cloud instance IDs)
+    pub aliases: Vec<String>,
+
+    /// Whether the host is currently reporting
+    pub up: bool,
+
+    /// Last time the host reported, in Unix seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_reported: Option<i64>,
+
+    /// Version of the Datadog agent on the host
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent_version: Option<String>,
+}
+
+impl HostSummary {
+    /// Flattens an API host, returning `None` if it has no name.
+    fn from_host(host: Host) -> Option<Self> {
+        Some(Self {
+            name: host.host_name.or(host.name)?,
+            aliases: host.aliases.unwrap_or_default(),
+            up: host.up.unwrap_or_default(),
+            last_reported: host.last_reported_time,
+            agent_version: host.meta.and_then(|m| m.agent_version),
+        })
+    }
+}
@@ -0,0 +1,481 @@
+//! Shared HTTP client construction.
+//!
+//! Builds the `reqwest` client used by every API wrapper. It mirrors the
+//! Datadog SDK's own setup (proxy and retry handling) and adds a tracing
+//! middleware that records each request as a `tracing` span.
+//!
+//! Events are emitted under the `ddog::http` target:
+//! - `DEBUG`: method, URL, page cursor, status, latency, request ID, and
+//!   rate-limit headers
+//! - `TRACE`: full response bodies
+//!
+//! The middleware also remembers when the latest rate limit resets, see
+//! [`last_rate_limit_reset`], and adds the `x-request-id` Datadog assigns a
+//! request to its error body as [`REQUEST_ID_FIELD`], so error messages can
+//! quote it. With [`stamp_request_ids`], search results get it too.
+//!
+//! Responses are asked for compressed and decompressed below the tracing
+//! middleware, see [`set_compression`].
+
+use async_trait::async_trait;
+use datadog_api_client::datadog::Configuration;
+use reqwest::{Request, Response};
+use reqwest_middleware::{ClientBuilder, ClientWithMiddleware, Middleware, Next};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Instant;
+use task_local_extensions::Extensions;
+use tracing::Instrument;
+
+use super::compression::CompressionMiddleware;
+use super::transport::{DatadogTransport, TransportMiddleware};
+
+/// Rate-limit headers returned by the Datadog API.
+const RATE_LIMIT_HEADERS: [&str; 4] = [
+    "x-ratelimit-limit",
+    "x-ratelimit-remaining",
+    "x-ratelimit-period",
+    "x-ratelimit-reset",
+];
+
+/// Header carrying the ID Datadog assigns each request.
+const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Field the request ID is added to response bodies as.
+pub const REQUEST_ID_FIELD: &str = "_request_id";
+
+/// Whether search results are stamped with request IDs, see [`stamp_request_ids`].
+static STAMP_REQUEST_IDS: AtomicBool = AtomicBool::new(false);
+
+/// Whether compressed responses are asked for, see [`set_compression`].
+static COMPRESSION: AtomicBool = AtomicBool::new(true);
+
+/// Transport used by every client built afterwards, see [`set_default_transport`].
+static DEFAULT_TRANSPORT: OnceLock<Arc<dyn DatadogTransport>> = OnceLock::new();
+
+/// `reqwest` clients shared by every client built, by proxy URL, see
+/// [`shared_client`].
+static SHARED_CLIENTS: Mutex<Vec<(Option<String>, reqwest::Client)>> = Mutex::new(Vec::new());
+
+/// `x-ratelimit-reset` of the latest 429 response, see [`last_rate_limit_reset`].
+static RATE_LIMIT_RESET: Mutex<Option<u64>> = Mutex::new(None);
+
+/// Builds the HTTP client shared by the API wrappers.
+///
+/// Honors the configuration's proxy URL and retry settings, like the SDK's
+/// `with_config` constructors. Clients with the same proxy share one
+/// connection pool.
+pub fn build_client(config: &Configuration) -> ClientWithMiddleware {
+    build_client_with(config, default_transport())
+}
+
+/// The transport set with [`set_default_transport`], if any.
+pub(crate) fn default_transport() -> Option<Arc<dyn DatadogTransport>> {
+    DEFAULT_TRANSPORT.get().cloned()
+}
+
+/// Sends the requests of every client built afterwards with `new` through
+/// `transport`, such as a cassette for `--record`/`--replay`.
+///
+/// Can be set once per process; returns false if a transport was already set.
+pub fn set_default_transport(transport: Arc<dyn DatadogTransport>) -> bool {
+    DEFAULT_TRANSPORT.set(transport).is_ok()
+}
+
+/// Adds the ID of the request that fetched them to every search result (each
+/// object of a response's `data` array) as [`REQUEST_ID_FIELD`], from then on.
+///
+/// Results keep unknown fields, so the ID is written out with records as
+/// returned by Datadog, for quoting in support tickets.
+pub fn stamp_request_ids(enabled: bool) {
+    STAMP_REQUEST_IDS.store(enabled, Ordering::Relaxed);
+}
+
+/// Asks for gzip or zstd compressed responses, and decompresses them, from
+/// then on. On by default; turning it off makes raw responses easier to
+/// inspect with a proxy.
+pub fn set_compression(enabled: bool) {
+    COMPRESSION.store(enabled, Ordering::Relaxed);
+}
+
+pub(crate) fn compression_enabled() -> bool {
+    COMPRESSION.load(Ordering::Relaxed)
+}
+
+/// Whether [`TracingMiddleware`] may read whole success bodies, to stamp
+/// them or log them.
+pub(crate) fn rewrites_bodies() -> bool {
+    STAMP_REQUEST_IDS.load(Ordering::Relaxed)
+        || tracing::enabled!(target: "ddog::http", tracing::Level::TRACE)
+}
+
+/// Seconds until the rate limit resets, as of the latest request rejected
+/// with 429 Too Many Requests, if any was.
+pub fn last_rate_limit_reset() -> Option<u64> {
+    *RATE_LIMIT_RESET.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Returns the `reqwest` client for a proxy URL, building it on first use.
+///
+/// A `reqwest` client owns a connection pool, which every clone shares, so
+/// the API wrappers of a command (and the searches of a batch) reuse open
+/// connections to each host instead of paying a TLS handshake per client.
+///
+/// # Panics
+///
+/// Panics if the proxy URL cannot be parsed.
+pub(crate) fn shared_client(proxy_url: Option<&str>) -> reqwest::Client {
+    let mut clients = SHARED_CLIENTS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((_, client)) = clients.iter().find(|(url, _)| url.as_deref() == proxy_url) {
+        return client.clone();
+    }
+    let client = new_client(proxy_url);
+    clients.push((proxy_url.map(String::from), client.clone()));
+    client
+}
+
+/// Builds a `reqwest` client with a connection pool of its own.
+///
+/// # Panics
+///
+/// Panics if the proxy URL cannot be parsed.
+fn new_client(proxy_url: Option<&str>) -> reqwest::Client {
+    let mut builder = reqwest::Client::builder();
+    if let Some(proxy_url) = proxy_url {
+        let proxy = reqwest::Proxy::all(proxy_url).expect("Failed to parse proxy URL");
+        builder = builder.proxy(proxy);
+    }
+    builder.build().expect("Failed to build HTTP client")
+}
+
+/// Builds the shared HTTP client, sending requests through `transport`
+/// instead of the network when one is given.
+///
+/// The transport sits below the retry, tracing, and compression
+/// middleware, so they all still apply to its responses.
+pub fn build_client_with(
+    config: &Configuration,
+    transport: Option<Arc<dyn DatadogTransport>>,
+) -> ClientWithMiddleware {
+    with_middleware(
+        shared_client(config.proxy_url.as_deref()),
+        config,
+        transport,
+    )
+}
+
+/// Builds an HTTP client like [`build_client_with`], with a connection pool
+/// of its own instead of the shared one.
+///
+/// For clients that run their requests on a runtime of their own, such as
+/// the [`blocking`](crate::blocking) ones: a pooled connection is driven by a
+/// task on the runtime that opened it, and fails with "dispatch task is
+/// gone" once that runtime is dropped, so it can't be handed to another.
+pub(crate) fn build_isolated_client(
+    config: &Configuration,
+    transport: Option<Arc<dyn DatadogTransport>>,
+) -> ClientWithMiddleware {
+    with_middleware(new_client(config.proxy_url.as_deref()), config, transport)
+}
+
+/// Wraps `client` in the retry, tracing, compression, and transport
+/// middleware.
+fn with_middleware(
+    client: reqwest::Client,
+    config: &Configuration,
+    transport: Option<Arc<dyn DatadogTransport>>,
+) -> ClientWithMiddleware {
+    let mut builder = ClientBuilder::new(client.clone());
+
+    if config.enable_retry {
+        let backoff_policy = reqwest_retry::policies::ExponentialBackoff::builder()
+            .build_with_max_retries(config.max_retries);
+        builder = builder.with(reqwest_retry::RetryTransientMiddleware::new_with_policy(
+            backoff_policy,
+        ));
+    }
+
+    builder = builder.with(TracingMiddleware).with(CompressionMiddleware);
+    if let Some(transport) = transport {
+        builder = builder.with(TransportMiddleware(transport, client));
+    }
+    builder.build()
+}
+
+/// Middleware that wraps each request in a `tracing` span.
+struct TracingMiddleware;
+
+#[async_trait]
+impl Middleware for TracingMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        let span = tracing::debug_span!(
+            target: "ddog::http",
+            "request",
+            method = %req.method(),
+            url = %req.url(),
+        );
+
+        if let Some(cursor) = page_cursor(&req) {
+            span.in_scope(|| tracing::debug!(target: "ddog::http", cursor, "page cursor"));
+        }
+
+        let start = Instant::now();
+        let result = next.run(req, extensions).instrument(span.clone()).await;
+        let latency_ms = start.elapsed().as_millis() as u64;
+
+        match result {
+            Ok(response) => {
+                let request_id = response
+                    .headers()
+                    .get(REQUEST_ID_HEADER)
+                    .and_then(|id| id.to_str().ok())
+                    .map(String::from);
+                span.in_scope(|| {
+                    tracing::debug!(
+                        target: "ddog::http",
+                        status = response.status().as_u16(),
+                        latency_ms,
+                        request_id = request_id.as_deref().unwrap_or("-"),
+                        rate_limit = %rate_limit_summary(&response),
+                        "response"
+                    )
+                });
+                if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                    *RATE_LIMIT_RESET.lock().unwrap_or_else(|e| e.into_inner()) =
+                        rate_limit_reset(&response);
+                }
+
+                let stamp = request_id.filter(|_| {
+                    !response.status().is_success() || STAMP_REQUEST_IDS.load(Ordering::Relaxed)
+                });
+                if stamp.is_some() || tracing::enabled!(target: "ddog::http", tracing::Level::TRACE)
+                {
+                    return rewrite_body(response, stamp.as_deref())
+                        .instrument(span)
+                        .await;
+                }
+                Ok(response)
+            }
+            Err(e) => {
+                span.in_scope(|| {
+                    tracing::debug!(target: "ddog::http", latency_ms, error = %e, "request failed")
+                });
+                Err(e)
+            }
+        }
+    }
+}
+
+/// Extracts the pagination cursor from a request, if any.
+///
+/// GET endpoints carry it as a `page[cursor]` query parameter, while the
+/// logs and spans search endpoints put it in the JSON body.
+fn page_cursor(req: &Request) -> Option<String> {
+    if let Some((_, cursor)) = req
+        .url()
+        .query_pairs()
+        .find(|(key, _)| key == "page[cursor]")
+    {
+        return Some(cursor.into_owned());
+    }
+
+    let body = req.body()?.as_bytes()?;
+    cursor_from_body(body)
+}
+
+/// Finds `page.cursor` in a logs (`page.cursor`) or spans (`data.attributes.page.cursor`) request body.
+fn cursor_from_body(body: &[u8]) -> Option<String> {
+    let json: serde_json::Value = serde_json::from_slice(body).ok()?;
+    json.pointer("/page/cursor")
+        .or_else(|| json.pointer("/data/attributes/page/cursor"))
+        .and_then(|c| c.as_str())
+        .map(String::from)
+}
+
+/// Formats the rate-limit headers of a response as `name=value` pairs.
+fn rate_limit_summary(response: &Response) -> String {
+    let parts: Vec<String> = RATE_LIMIT_HEADERS
+        .iter()
+        .filter_map(|name| {
+            let value = response.headers().get(*name)?.to_str().ok()?;
+            Some(format!(
+                "{}={}",
+                name.trim_start_matches("x-ratelimit-"),
+                value
+            ))
+        })
+        .collect();
+    if parts.is_empty() {
+        "-".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
+
+/// Parses a response's `x-ratelimit-reset` header.
+fn rate_limit_reset(response: &Response) -> Option<u64> {
+    response
+        .headers()
+        .get("x-ratelimit-reset")?
+        .to_str()
+        .ok()?
+        .parse()
+        .ok()
+}
+
+/// Logs a response body at TRACE level, stamps it with `request_id` if
+/// given (see [`stamp_body`]), and rebuilds the response for the caller.
+async fn rewrite_body(
+    response: Response,
+    request_id: Option<&str>,
+) -> reqwest_middleware::Result<Response> {
+    let status = response.status();
+    let version = response.version();
+    let mut headers = response.headers().clone();
+    let mut body = response.bytes().await?.to_vec();
+
+    tracing::trace!(
+        target: "ddog::http",
+        body = %String::from_utf8_lossy(&body),
+        "response body"
+    );
+    if let Some(stamped) = request_id.and_then(|id| stamp_body(&body, id, status.is_success())) {
+        body = stamped;
+        headers.remove(http::header::CONTENT_LENGTH);
+    }
+
+    let mut rebuilt = http::Response::builder().status(status).version(version);
+    if let Some(h) = rebuilt.headers_mut() {
+        *h = headers;
+    }
+    let rebuilt = rebuilt
+        .body(body)
+        .map_err(|e| reqwest_middleware::Error::Middleware(e.into()))?;
+    Ok(Response::from(rebuilt))
+}
+
+/// Adds `request_id` as [`REQUEST_ID_FIELD`] to a JSON error body, or to each
+/// object of a successful body's `data` array. Returns `None` if the body
+/// isn't JSON or has nowhere to put it.
+fn stamp_body(body: &[u8], request_id: &str, success: bool) -> Option<Vec<u8>> {
+    let mut json: serde_json::Value = serde_json::from_slice(body).ok()?;
+    let id = serde_json::Value::String(request_id.to_string());
+    if success {
+        let records = json.get_mut("data")?.as_array_mut()?;
+        for record in records.iter_mut().filter_map(|r| r.as_object_mut()) {
+            record.insert(REQUEST_ID_FIELD.to_string(), id.clone());
+        }
+    } else {
+        json.as_object_mut()?
+            .insert(REQUEST_ID_FIELD.to_string(), id);
+    }
+    serde_json::to_vec(&json).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::atomic::AtomicUsize;
+
+    #[tokio::test]
+    async fn test_clients_share_connections() {
+        // Answers every request on a connection, counting connections
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/api/v1/validate", listener.local_addr().unwrap());
+        let connections = Arc::new(AtomicUsize::new(0));
+        let accepted = connections.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                accepted.fetch_add(1, Ordering::SeqCst);
+                std::thread::spawn(move || {
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    let mut stream = stream;
+                    let mut line = String::new();
+                    while reader.read_line(&mut line).unwrap_or(0) > 0 {
+                        if line == "\r\n" {
+                            let response = "HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n{}";
+                            stream.write_all(response.as_bytes()).unwrap();
+                        }
+                        line.clear();
+                    }
+                });
+            }
+        });
+
+        let config = Configuration::new();
+        for _ in 0..3 {
+            let client = build_client_with(&config, None);
+            let response = client.get(&url).send().await.unwrap();
+            assert_eq!(response.text().await.unwrap(), "{}");
+        }
+        assert_eq!(connections.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_cursor_from_logs_body() {
+        let body = br#"{"filter":{"query":"*"},"page":{"cursor":"abc","limit":1000}}"#;
+        assert_eq!(cursor_from_body(body), Some("abc".to_string()));
+    }
+
+    #[test]
+    fn test_cursor_from_spans_body() {
+        let body = br#"{"data":{"attributes":{"page":{"cursor":"xyz"}}}}"#;
+        assert_eq!(cursor_from_body(body), Some("xyz".to_string()));
+    }
+
+    #[test]
+    fn test_cursor_absent() {
+        assert_eq!(cursor_from_body(br#"{"page":{"limit":1000}}"#), None);
+        assert_eq!(cursor_from_body(b"not json"), None);
+    }
+
+    #[test]
+    fn test_page_cursor_from_query_string() {
+        let req = Request::new(
+            reqwest::Method::GET,
+            "https://api.datadoghq.com/api/v2/logs/events?page%5Bcursor%5D=next"
+                .parse()
+                .unwrap(),
+        );
+        assert_eq!(page_cursor(&req), Some("next".to_string()));
+    }
+
+    #[test]
+    fn test_stamp_body() {
+        let stamped = stamp_body(br#"{"errors":["Forbidden"]}"#, "abc", false).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&stamped).unwrap();
+        assert_eq!(json["_request_id"], "abc");
+
+        let stamped = stamp_body(br#"{"data":[{"id":"1"},{"id":"2"}]}"#, "abc", true).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&stamped).unwrap();
+        assert_eq!(json["data"][1]["_request_id"], "abc");
+        assert_eq!(json.get("_request_id"), None);
+
+        assert_eq!(stamp_body(b"<html>", "abc", false), None);
+        assert_eq!(stamp_body(br#"{"data":{"id":"1"}}"#, "abc", true), None);
+    }
+
+    #[test]
+    fn test_rate_limit_summary() {
+        let response = Response::from(
+            http::Response::builder()
+                .header("x-ratelimit-remaining", "299")
+                .header("x-ratelimit-reset", "12")
+                .body(Vec::new())
+                .unwrap(),
+        );
+        assert_eq!(rate_limit_summary(&response), "remaining=299 reset=12");
+        assert_eq!(rate_limit_reset(&response), Some(12));
+
+        let response = Response::from(http::Response::new(Vec::new()));
+        assert_eq!(rate_limit_summary(&response), "-");
+        assert_eq!(rate_limit_reset(&response), None);
+    }
+}
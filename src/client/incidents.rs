@@ -0,0 +1,100 @@
+//! Datadog Incidents API client wrapper.
+//!
+//! Provides a simplified interface for searching incidents with automatic
+//! pagination.
+
+use chrono::SecondsFormat;
+use datadog_api_client::datadog::Configuration;
+use datadog_api_client::datadogV2::api_incidents::{IncidentsAPI, SearchIncidentsOptionalParams};
+use datadog_api_client::datadogV2::model::{IncidentResponseData, IncidentSearchSortOrder};
+use futures_util::{Stream, StreamExt};
+
+use super::convert_datadog_error;
+use super::http::build_client;
+use crate::error::AppError;
+
+/// Client for querying Datadog incidents.
+///
+/// Wraps the Datadog SDK's IncidentsAPI with automatic pagination support.
+pub struct IncidentsClient {
+    api: IncidentsAPI,
+}
+
+impl IncidentsClient {
+    /// Creates a new IncidentsClient with the given configuration.
+    ///
+    /// Incident search is an unstable operation in the SDK, so it is enabled here.
+    pub fn new(mut config: Configuration) -> Self {
+        config.set_unstable_operation_enabled("v2.search_incidents", true);
+        Self {
+            api: IncidentsAPI::with_client_and_config(config.clone(), build_client(&config)),
+        }
+    }
+
+    /// Searches incidents, newest first.
+    ///
+    /// Returns an async stream of incidents. The stream handles pagination
+    /// automatically.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - Incident search syntax (e.g., "state:active AND teams:payments")
+    pub fn search(
+        &self,
+        query: &str,
+    ) -> impl Stream<Item = Result<IncidentSummary, AppError>> + '_ {
+        let params = SearchIncidentsOptionalParams::default()
+            .sort(IncidentSearchSortOrder::CREATED_DESCENDING)
+            .page_size(100);
+
+        self.api
+            .search_incidents_with_pagination(query.to_string(), params)
+            .filter_map(|result| async move {
+                match result {
+                    Ok(incident) => IncidentSummary::from_response(incident.data).map(Ok),
+                    Err(e) => Some(Err(convert_datadog_error(e, "incidents"))),
+                }
+            })
+    }
+}
+
+/// An incident, as needed to report on it.
+///
+/// This struct represents a flattened view of an incident from the Datadog API.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct IncidentSummary {
+    /// Public incident number shown in the UI (e.g., 1234 for "#1234")
+    pub public_id: i64,
+
+    /// Incident title
+    pub title: String,
+
+    /// Severity (e.g., "SEV-2"), or "UNKNOWN"
+    pub severity: String,
+
+    /// Current state (e.g., "active", "stable", "resolved")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+
+    /// When the incident was declared (RFC3339, UTC)
+    pub created: String,
+}
+
+impl IncidentSummary {
+    /// Flattens an API incident; `None` if it has no attributes.
+    fn from_response(data: IncidentResponseData) -> Option<Self> {
+        let attributes = data.attributes?;
+        Some(Self {
+            public_id: attributes.public_id.unwrap_or_default(),
+            title: attributes.title,
+            severity: attributes
+                .severity
+                .map_or_else(|| "UNKNOWN".to_string(), |s| s.to_string()),
+            state: attributes.state.flatten(),
+            created: attributes
+                .created
+                .map(|t| t.to_rfc3339_opts(SecondsFormat::Millis, true))
+                .unwrap_or_default(),
+        })
+    }
+}
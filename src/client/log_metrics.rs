@@ -0,0 +1,201 @@
+//! Datadog log-based metrics API client wrapper.
+//!
+//! Lists, creates, and deletes the metrics Datadog generates from ingested
+//! logs, with definitions as JSON in the API's own shape, so they can be kept
+//! in version control and reconciled from a pipeline.
+
+use datadog_api_client::datadog::Configuration;
+use datadog_api_client::datadogV2::api_logs_metrics::LogsMetricsAPI;
+use datadog_api_client::datadogV2::model::{LogsMetricCreateData, LogsMetricCreateRequest};
+use reqwest_middleware::ClientWithMiddleware;
+use serde_json::Value;
+
+use super::builder::{BuildableClient, ClientBuilder};
+use super::convert_datadog_error;
+use super::http::build_client;
+use crate::error::AppError;
+
+/// Path of the log-based metrics endpoint.
+pub const LOG_METRICS_PATH: &str = "/api/v2/logs/config/metrics";
+
+/// Client for managing log-based metrics.
+///
+/// Wraps the Datadog SDK's LogsMetricsAPI.
+pub struct LogMetricsClient {
+    api: LogsMetricsAPI,
+    base_url: String,
+}
+
+impl LogMetricsClient {
+    /// Creates a new LogMetricsClient with the given configuration.
+    pub fn new(config: Configuration) -> Self {
+        let http = build_client(&config);
+        Self::from_parts(config, http)
+    }
+
+    /// Starts building a client from explicit credentials and site.
+    pub fn builder() -> ClientBuilder<Self> {
+        ClientBuilder::new()
+    }
+
+    /// The URL of the endpoint, or of the metric `id` on it.
+    pub fn url(&self, id: Option<&str>) -> String {
+        let base = self.base_url.trim_end_matches('/');
+        match id {
+            Some(id) => format!("{}{}/{}", base, LOG_METRICS_PATH, id),
+            None => format!("{}{}", base, LOG_METRICS_PATH),
+        }
+    }
+
+    /// Lists the org's log-based metrics, each as `{"id", "type",
+    /// "attributes"}`.
+    pub async fn list(&self) -> Result<Vec<Value>, AppError> {
+        let response = self
+            .api
+            .list_logs_metrics()
+            .await
+            .map_err(|e| convert_datadog_error(e, "log-based metrics"))?;
+        response
+            .data
+            .unwrap_or_default()
+            .into_iter()
+            .map(|metric| Ok(serde_json::to_value(metric)?))
+            .collect()
+    }
+
+    /// Creates a log-based metric, returning it as Datadog stored it.
+    pub async fn create(&self, definition: LogsMetricCreateRequest) -> Result<Value, AppError> {
+        let response = self
+            .api
+            .create_logs_metric(definition)
+            .await
+            .map_err(|e| convert_datadog_error(e, "log-based metrics"))?;
+        Ok(serde_json::to_value(response.data)?)
+    }
+
+    /// Deletes the log-based metric `id`.
+    pub async fn delete(&self, id: &str) -> Result<(), AppError> {
+        self.api
+            .delete_logs_metric(id.to_string())
+            .await
+            .map_err(|e| convert_datadog_error(e, "log-based metrics"))
+    }
+}
+
+impl BuildableClient for LogMetricsClient {
+    fn from_parts(config: Configuration, http: ClientWithMiddleware) -> Self {
+        Self {
+            // Operations without their own server use the site's API host
+            base_url: config.get_operation_host(""),
+            api: LogsMetricsAPI::with_client_and_config(config, http),
+        }
+    }
+}
+
+/// Parses a log-based metric definition: the API's create request body
+/// (`{"data": {...}}`), or the metric itself, as `list` writes it. The
+/// `type` may be left out.
+///
+/// # Errors
+///
+/// Returns `AppError::Config` if the definition lacks an `id` or `compute`,
+/// or doesn't match the API's schema.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::client::parse_log_metric;
+/// use serde_json::json;
+///
+/// let request = parse_log_metric(json!({
+///     "id": "checkout.errors",
+///     "attributes": {
+///         "compute": {"aggregation_type": "count"},
+///         "filter": {"query": "service:checkout status:error"},
+///         "group_by": [{"path": "@http.status_code", "tag_name": "status_code"}],
+///     },
+/// }))
+/// .unwrap();
+/// assert_eq!(request.data.id, "checkout.errors");
+/// ```
+pub fn parse_log_metric(definition: Value) -> Result<LogsMetricCreateRequest, AppError> {
+    let mut data = match definition {
+        Value::Object(mut envelope) if envelope.contains_key("data") => {
+            envelope.remove("data").unwrap_or_default()
+        }
+        other => other,
+    };
+    let Some(metric) = data.as_object_mut() else {
+        return Err(AppError::Config(
+            "A log-based metric definition must be a JSON object".to_string(),
+        ));
+    };
+    metric
+        .entry("type")
+        .or_insert_with(|| Value::from("logs_metrics"));
+    let data: LogsMetricCreateData = serde_json::from_value(data)
+        .map_err(|e| AppError::Config(format!("Invalid log-based metric definition: {}", e)))?;
+    Ok(LogsMetricCreateRequest::new(data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::MockTransport;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_log_metric() {
+        let metric = json!({
+            "id": "api.latency",
+            "type": "logs_metrics",
+            "attributes": {"compute": {"aggregation_type": "distribution", "path": "@duration"}},
+        });
+        let bare = parse_log_metric(metric.clone()).unwrap();
+        let wrapped = parse_log_metric(json!({"data": metric})).unwrap();
+        assert_eq!(bare, wrapped);
+        assert_eq!(
+            serde_json::to_value(&bare).unwrap()["data"]["attributes"]["compute"]["path"],
+            "@duration"
+        );
+
+        assert!(parse_log_metric(json!({"id": "no.compute", "attributes": {}})).is_err());
+        assert!(parse_log_metric(json!(["api.latency"])).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_list() {
+        let transport = MockTransport::new().respond(
+            "GET",
+            LOG_METRICS_PATH,
+            200,
+            json!({"data": [{
+                "id": "checkout.errors",
+                "type": "logs_metrics",
+                "attributes": {
+                    "compute": {"aggregation_type": "count"},
+                    "filter": {"query": "service:checkout status:error"},
+                },
+            }]}),
+        );
+        let client = LogMetricsClient::builder()
+            .api_key("test")
+            .app_key("test")
+            .transport(transport)
+            .build()
+            .unwrap();
+
+        let metrics = client.list().await.unwrap();
+        assert_eq!(metrics.len(), 1);
+        assert_eq!(metrics[0]["id"], "checkout.errors");
+        assert_eq!(
+            metrics[0]["attributes"]["filter"]["query"],
+            "service:checkout status:error"
+        );
+        assert!(
+            client
+                .url(Some("checkout.errors"))
+                .ends_with("/api/v2/logs/config/metrics/checkout.errors")
+        );
+    }
+}
@@ -1,61 +1,341 @@
 //! Datadog Logs API client wrapper.
 //!
 //! Provides a simplified interface for searching logs with automatic pagination.
+//! Search pages are parsed a record at a time as they arrive (see [`pages`](super::pages)).
 
-use datadog_api_client::datadog::{self, Configuration};
+use datadog_api_client::datadog::Configuration;
 use datadog_api_client::datadogV2::api_logs::{ListLogsOptionalParams, LogsAPI};
 use datadog_api_client::datadogV2::model::{
-    Log, LogsListRequest, LogsListRequestPage, LogsQueryFilter, LogsSort,
+    Log, LogsAggregateRequest, LogsAggregateSort, LogsAggregateSortType, LogsAggregationFunction,
+    LogsCompute, LogsComputeType, LogsGroupBy, LogsListRequest, LogsListRequestPage,
+    LogsQueryFilter, LogsSort, LogsSortOrder, LogsStorageTier,
 };
-use futures_util::Stream;
+use futures_util::{StreamExt, TryStreamExt};
+use reqwest_middleware::ClientWithMiddleware;
+
+use super::aggregate::{
+    FacetValue, Grouping, Measure, Ranking, VolumePoint, facet_values, total, volume_points,
+};
+use super::builder::{BuildableClient, ClientBuilder};
+use super::convert_datadog_error;
+use super::http::build_client;
+use super::pages::{capture_request, search_pages};
+use super::request::{LogsSearchRequest, SortOrder, StorageTier};
+use super::stream::SearchStream;
+use crate::error::AppError;
+use crate::records::LogEvent;
 
 /// Client for querying Datadog logs.
 ///
 /// Wraps the Datadog SDK's LogsAPI with automatic pagination support.
 pub struct LogsClient {
     api: LogsAPI,
+    config: Configuration,
+    http: ClientWithMiddleware,
 }
 
 impl LogsClient {
     /// Creates a new LogsClient with the given configuration.
     pub fn new(config: Configuration) -> Self {
-        Self {
-            api: LogsAPI::with_config(config),
+        let http = build_client(&config);
+        Self::from_parts(config, http)
+    }
+
+    /// Starts building a client from explicit credentials and site.
+    pub fn builder() -> ClientBuilder<Self> {
+        ClientBuilder::new()
+    }
+
+    /// Collects up to `limit` logs matching a request (0 for all of them), as
+    /// typed [`LogEvent`]s.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use ddog::client::{LogsClient, LogsSearchRequest};
+    ///
+    /// # async fn example() -> Result<(), ddog::error::AppError> {
+    /// let client = LogsClient::builder().build()?;
+    /// let request = LogsSearchRequest::builder()
+    ///     .query("service:api status:error")
+    ///     .from("now-15m")
+    ///     .build();
+    /// for log in client.fetch(&request, 100).await? {
+    ///     println!("{:?} {:?}", log.status, log.message);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Auth` for rejected credentials, `AppError::InvalidQuery`
+    /// for a query or time range Datadog rejects, and `AppError::Api` otherwise.
+    pub async fn fetch(
+        &self,
+        request: &LogsSearchRequest,
+        limit: usize,
+    ) -> Result<Vec<LogEvent>, AppError> {
+        self.search(request)
+            .take_records(limit)
+            .map_records(LogEvent::from)
+            .try_collect()
+            .await
+    }
+
+    /// Searches logs matching a request.
+    ///
+    /// Returns a [`SearchStream`] of log records. The stream handles
+    /// pagination automatically, fetching the request's page limit of records
+    /// per API request.
+    pub fn search(&self, request: &LogsSearchRequest) -> SearchStream<'static, Log> {
+        let sort = match request.sort {
+            SortOrder::Ascending => LogsSort::TIMESTAMP_ASCENDING,
+            SortOrder::Descending => LogsSort::TIMESTAMP_DESCENDING,
+        };
+        let mut filter = filter(
+            &request.query,
+            &request.from,
+            &request.to,
+            request.indexes.clone(),
+        );
+        if let Some(tier) = request.storage_tier {
+            filter = filter.storage_tier(match tier {
+                StorageTier::Indexes => LogsStorageTier::INDEXES,
+                StorageTier::OnlineArchives => LogsStorageTier::ONLINE_ARCHIVES,
+                StorageTier::Flex => LogsStorageTier::FLEX,
+            });
         }
+        let body = LogsListRequest::new()
+            .filter(filter)
+            .page(LogsListRequestPage::new().limit(request.page_limit))
+            .sort(sort);
+
+        let page_size = usize::try_from(request.page_limit).unwrap_or(0);
+        let config = self.config.clone();
+        let logs = search_pages(self.http.clone(), page_size, move |cursor| {
+            let mut body = body.clone();
+            if let Some(page) = body.page.as_mut() {
+                page.cursor = cursor;
+            }
+            let config = config.clone();
+            capture_request(move |http| async move {
+                LogsAPI::with_client_and_config(config, http)
+                    .list_logs(ListLogsOptionalParams::default().body(body))
+                    .await
+            })
+        })
+        .map(|result| result.map_err(|e| convert_datadog_error(e, "logs")));
+        SearchStream::numbered(logs, page_size)
     }
 
-    /// Searches logs matching the given query.
+    /// Counts logs matching the query per time bucket and facet value.
     ///
-    /// Returns an async stream of log records. The stream handles pagination
-    /// automatically, fetching up to 1000 records per API request.
+    /// # Arguments
+    ///
+    /// * `query` - Datadog query syntax (e.g., "status:error")
+    /// * `from` - Start time: relative ("now-1h"), ISO8601 ("2024-01-15T10:00:00Z"), or Unix ms ("1705315200000")
+    /// * `to` - End time: relative ("now"), ISO8601 ("2024-01-15T10:00:00Z"), or Unix ms ("1705315200000")
+    /// * `indexes` - Log indexes to search (use ["*"] for all)
+    /// * `grouping` - Facet, time bucket size, and number of facet values to keep
+    pub async fn volume(
+        &self,
+        query: &str,
+        from: &str,
+        to: &str,
+        indexes: Vec<String>,
+        grouping: &Grouping,
+    ) -> Result<Vec<VolumePoint>, AppError> {
+        let body = LogsAggregateRequest::new()
+            .filter(filter(query, from, to, indexes))
+            .compute(vec![
+                LogsCompute::new(LogsAggregationFunction::COUNT)
+                    .type_(LogsComputeType::TIMESERIES)
+                    .interval(grouping.interval.clone()),
+            ])
+            .group_by(vec![
+                LogsGroupBy::new(grouping.facet.clone()).limit(grouping.top),
+            ]);
+
+        let response = self
+            .api
+            .aggregate_logs(body)
+            .await
+            .map_err(|e| convert_datadog_error(e, "logs"))?;
+        let buckets = response.data.and_then(|d| d.buckets).unwrap_or_default();
+        let buckets = serde_json::to_value(buckets)?;
+
+        Ok(volume_points(
+            buckets.as_array().map(Vec::as_slice).unwrap_or_default(),
+            &grouping.facet,
+        ))
+    }
+
+    /// Measures logs matching the query per value of a facet, largest first.
     ///
     /// # Arguments
     ///
-    /// * `query` - Datadog query syntax (e.g., "service:api AND status:error")
+    /// * `query` - Datadog query syntax (e.g., "status:error")
     /// * `from` - Start time: relative ("now-1h"), ISO8601 ("2024-01-15T10:00:00Z"), or Unix ms ("1705315200000")
     /// * `to` - End time: relative ("now"), ISO8601 ("2024-01-15T10:00:00Z"), or Unix ms ("1705315200000")
     /// * `indexes` - Log indexes to search (use ["*"] for all)
-    pub fn search(
+    /// * `ranking` - Facet to rank values of (e.g., "service", "@http.status_code"),
+    ///   number of values to return, and what to rank them by
+    pub async fn top_values(
         &self,
         query: &str,
         from: &str,
         to: &str,
         indexes: Vec<String>,
-    ) -> impl Stream<
-        Item = Result<Log, datadog::Error<datadog_api_client::datadogV2::api_logs::ListLogsError>>,
-    > + '_ {
-        let body = LogsListRequest::new()
-            .filter(
-                LogsQueryFilter::new()
-                    .query(query.to_string())
-                    .from(from.to_string())
-                    .to(to.to_string())
-                    .indexes(indexes),
-            )
-            .page(LogsListRequestPage::new().limit(1000))
-            .sort(LogsSort::TIMESTAMP_ASCENDING);
-
-        self.api
-            .list_logs_with_pagination(ListLogsOptionalParams::default().body(body))
+        ranking: &Ranking,
+    ) -> Result<Vec<FacetValue>, AppError> {
+        let (aggregation, metric) = match &ranking.measure {
+            Measure::Count => (LogsAggregationFunction::COUNT, None),
+            Measure::Unique(of) => (LogsAggregationFunction::CARDINALITY, Some(of.clone())),
+        };
+        let mut compute = LogsCompute::new(aggregation.clone());
+        let mut sort = LogsAggregateSort::new()
+            .aggregation(aggregation)
+            .order(LogsSortOrder::DESCENDING)
+            .type_(LogsAggregateSortType::MEASURE);
+        if let Some(metric) = metric {
+            compute = compute.metric(metric.clone());
+            sort = sort.metric(metric);
+        }
+
+        let body = LogsAggregateRequest::new()
+            .filter(filter(query, from, to, indexes))
+            .compute(vec![compute])
+            .group_by(vec![
+                LogsGroupBy::new(ranking.facet.clone())
+                    .limit(ranking.limit)
+                    .sort(sort),
+            ]);
+
+        let response = self
+            .api
+            .aggregate_logs(body)
+            .await
+            .map_err(|e| convert_datadog_error(e, "logs"))?;
+        let buckets = response.data.and_then(|d| d.buckets).unwrap_or_default();
+        let buckets = serde_json::to_value(buckets)?;
+
+        Ok(facet_values(
+            buckets.as_array().map(Vec::as_slice).unwrap_or_default(),
+            &ranking.facet,
+        ))
+    }
+
+    /// Counts logs matching the query.
+    pub async fn count(
+        &self,
+        query: &str,
+        from: &str,
+        to: &str,
+        indexes: Vec<String>,
+    ) -> Result<Option<f64>, AppError> {
+        let body = LogsAggregateRequest::new()
+            .filter(filter(query, from, to, indexes))
+            .compute(vec![LogsCompute::new(LogsAggregationFunction::COUNT)]);
+
+        let response = self
+            .api
+            .aggregate_logs(body)
+            .await
+            .map_err(|e| convert_datadog_error(e, "logs"))?;
+        let buckets = response.data.and_then(|d| d.buckets).unwrap_or_default();
+        let buckets = serde_json::to_value(buckets)?;
+
+        Ok(total(
+            buckets.as_array().map(Vec::as_slice).unwrap_or_default(),
+        ))
+    }
+
+    /// Counts the distinct values of a facet among logs matching the query.
+    ///
+    /// Returns `None` when Datadog returns no count, such as for an unknown facet.
+    pub async fn cardinality(
+        &self,
+        query: &str,
+        from: &str,
+        to: &str,
+        indexes: Vec<String>,
+        facet: &str,
+    ) -> Result<Option<f64>, AppError> {
+        let body = LogsAggregateRequest::new()
+            .filter(filter(query, from, to, indexes))
+            .compute(vec![
+                LogsCompute::new(LogsAggregationFunction::CARDINALITY).metric(facet.to_string()),
+            ]);
+
+        let response = self
+            .api
+            .aggregate_logs(body)
+            .await
+            .map_err(|e| convert_datadog_error(e, "logs"))?;
+        let buckets = response.data.and_then(|d| d.buckets).unwrap_or_default();
+        let buckets = serde_json::to_value(buckets)?;
+
+        Ok(total(
+            buckets.as_array().map(Vec::as_slice).unwrap_or_default(),
+        ))
+    }
+}
+
+impl BuildableClient for LogsClient {
+    fn from_parts(config: Configuration, http: ClientWithMiddleware) -> Self {
+        Self {
+            api: LogsAPI::with_client_and_config(config.clone(), http.clone()),
+            config,
+            http,
+        }
+    }
+}
+
+/// Builds the filter shared by logs requests.
+fn filter(query: &str, from: &str, to: &str, indexes: Vec<String>) -> LogsQueryFilter {
+    LogsQueryFilter::new()
+        .query(query.to_string())
+        .from(from.to_string())
+        .to(to.to_string())
+        .indexes(indexes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::MockTransport;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_search_sends_storage_tier() {
+        let path = "/api/v2/logs/events/search";
+        let transport = MockTransport::new()
+            .respond("POST", path, 200, json!({"data": []}))
+            .respond("POST", path, 200, json!({"data": []}));
+        let client = LogsClient::builder()
+            .api_key("test")
+            .app_key("test")
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+
+        let request = LogsSearchRequest::builder()
+            .storage_tier(StorageTier::OnlineArchives)
+            .build();
+        client.fetch(&request, 0).await.unwrap();
+        client
+            .fetch(&LogsSearchRequest::builder().build(), 0)
+            .await
+            .unwrap();
+
+        let requests = transport.requests();
+        let tier = |i: usize| {
+            requests[i].body.as_ref().unwrap()["filter"]
+                .get("storage_tier")
+                .cloned()
+        };
+        assert_eq!(tier(0), Some(json!("online-archives")));
+        assert_eq!(tier(1), None);
     }
 }
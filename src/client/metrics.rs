@@ -1,12 +1,29 @@
 //! Datadog Metrics API client wrapper.
 //!
 //! Provides a simplified interface for querying metrics timeseries data and listing available metrics.
+//! Single queries use the v1 query API; several queries combined by formulas
+//! use the v2 timeseries formula API.
 
 use datadog_api_client::datadog::Configuration;
 use datadog_api_client::datadogV1::api_metrics::{ListActiveMetricsOptionalParams, MetricsAPI};
+use datadog_api_client::datadogV2::api_metrics::MetricsAPI as MetricsV2API;
+use datadog_api_client::datadogV2::model::{
+    MetricVolumes, MetricsAggregator, MetricsDataSource, MetricsScalarQuery,
+    MetricsTimeseriesQuery, QueryFormula, ScalarColumn, ScalarFormulaQueryRequest,
+    ScalarFormulaQueryResponse, ScalarFormulaRequest, ScalarFormulaRequestAttributes,
+    ScalarFormulaRequestType, ScalarQuery, TimeseriesFormulaQueryRequest,
+    TimeseriesFormulaQueryResponse, TimeseriesFormulaRequest, TimeseriesFormulaRequestAttributes,
+    TimeseriesFormulaRequestType, TimeseriesQuery,
+};
 use futures_util::stream::{self, Stream, StreamExt};
+use reqwest_middleware::ClientWithMiddleware;
+use std::fmt;
 use std::pin::Pin;
+use std::str::FromStr;
 
+use super::builder::{BuildableClient, ClientBuilder};
+use super::convert_datadog_error;
+use super::http::build_client;
 use crate::error::AppError;
 
 /// Client for querying Datadog metrics.
@@ -14,16 +31,27 @@ use crate::error::AppError;
 /// Wraps the Datadog SDK's MetricsAPI for querying timeseries data and listing metrics.
 pub struct MetricsClient {
     api: MetricsAPI,
+    api_v2: MetricsV2API,
 }
 
+/// Names formula queries refer to their queries by, in order.
+const QUERY_NAMES: &str = "abcdefghijklmnopqrstuvwxyz";
+
 impl MetricsClient {
     /// Creates a new MetricsClient with the given configuration.
     pub fn new(config: Configuration) -> Self {
+        let http = build_client(&config);
         Self {
-            api: MetricsAPI::with_config(config),
+            api: MetricsAPI::with_client_and_config(config.clone(), http.clone()),
+            api_v2: MetricsV2API::with_client_and_config(config, http),
         }
     }
 
+    /// Starts building a client from explicit credentials and site.
+    pub fn builder() -> ClientBuilder<Self> {
+        ClientBuilder::new()
+    }
+
     /// Queries metrics timeseries data.
     ///
     /// Returns an async stream of individual timeseries points. Each point is flattened
@@ -91,7 +119,7 @@ impl MetricsClient {
                     }
                     Err(e) => {
                         // Convert the error and return it as a single-item stream
-                        let app_error = convert_datadog_error(e);
+                        let app_error = convert_datadog_error(e, "metrics");
                         stream::once(async move { Err(app_error) }).boxed()
                     }
                 }
@@ -100,6 +128,134 @@ impl MetricsClient {
         )
     }
 
+    /// Queries several metrics at once, combined by formulas.
+    ///
+    /// Queries are named `a`, `b`, `c`, ... in order, and each formula refers
+    /// to them by name (e.g., `a / b * 100`). Points are returned per formula,
+    /// with the formula as the metric name and `query_index` its position; with
+    /// no formulas, points are returned per query instead. A grouped query's
+    /// group tags become the series scope.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`AppError::InvalidQuery`] if there are no queries or more
+    /// than 26, or if Datadog returns no series and an error instead.
+    pub fn query_formulas(
+        &self,
+        queries: &[String],
+        formulas: &[String],
+        from: i64,
+        to: i64,
+    ) -> Pin<Box<dyn Stream<Item = Result<MetricPoint, AppError>> + Send + '_>> {
+        let request = formula_request(queries, formulas, from, to);
+        let expressions = if formulas.is_empty() {
+            queries.to_vec()
+        } else {
+            formulas.to_vec()
+        };
+        let api = &self.api_v2;
+
+        Box::pin(
+            stream::once(async move {
+                let result = match request {
+                    Ok(request) => api
+                        .query_timeseries_data(request)
+                        .await
+                        .map_err(|e| convert_datadog_error(e, "metrics"))
+                        .and_then(|response| formula_points(response, &expressions)),
+                    Err(e) => Err(e),
+                };
+                match result {
+                    Ok(points) => stream::iter(points.into_iter().map(Ok)).boxed(),
+                    Err(e) => stream::once(async move { Err(e) }).boxed(),
+                }
+            })
+            .flatten(),
+        )
+    }
+
+    /// Reduces a metric query to a single value per group over `[from, to]`
+    /// (Unix seconds), using the v2 scalar API.
+    ///
+    /// An ungrouped query returns one value; a value is `None` when the
+    /// query had no data.
+    pub async fn scalar(
+        &self,
+        query: &str,
+        reducer: Reducer,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<ScalarValue>, AppError> {
+        let query = ScalarQuery::MetricsScalarQuery(Box::new(
+            MetricsScalarQuery::new(
+                reducer.aggregator(),
+                MetricsDataSource::METRICS,
+                query.to_string(),
+            )
+            .name("a".to_string()),
+        ));
+        let request = ScalarFormulaQueryRequest::new(ScalarFormulaRequest::new(
+            ScalarFormulaRequestAttributes::new(from * 1000, vec![query], to * 1000),
+            ScalarFormulaRequestType::SCALAR_REQUEST,
+        ));
+        let response = self
+            .api_v2
+            .query_scalar_data(request)
+            .await
+            .map_err(|e| convert_datadog_error(e, "metrics"))?;
+        scalar_values(response)
+    }
+
+    /// Lists the indexed `key:value` tags of a metric over the previous hour.
+    pub async fn tags(&self, metric: &str) -> Result<Vec<String>, AppError> {
+        self.api_v2
+            .list_tags_by_metric_name(metric.to_string())
+            .await
+            .map(|response| {
+                response
+                    .data
+                    .and_then(|data| data.attributes)
+                    .and_then(|attributes| attributes.tags)
+                    .unwrap_or_default()
+            })
+            .map_err(|e| convert_datadog_error(e, "metric tags"))
+    }
+
+    /// Fetches the estimated number of distinct series of a metric.
+    pub async fn volumes(&self, metric: &str) -> Result<SeriesVolumes, AppError> {
+        let response = self
+            .api_v2
+            .list_volumes_by_metric_name(metric.to_string())
+            .await
+            .map_err(|e| convert_datadog_error(e, "metric volumes"))?;
+        Ok(match response.data {
+            Some(MetricVolumes::MetricDistinctVolume(volume)) => SeriesVolumes {
+                distinct: volume.attributes.and_then(|a| a.distinct_volume),
+                ..SeriesVolumes::default()
+            },
+            Some(MetricVolumes::MetricIngestedIndexedVolume(volume)) => {
+                let attributes = volume.attributes;
+                SeriesVolumes {
+                    ingested: attributes.as_ref().and_then(|a| a.ingested_volume),
+                    indexed: attributes.and_then(|a| a.indexed_volume),
+                    ..SeriesVolumes::default()
+                }
+            }
+            _ => SeriesVolumes::default(),
+        })
+    }
+
+    /// Fetches a metric's type (e.g., "gauge", "count", "distribution") from its metadata.
+    ///
+    /// Returns `None` if the metric has no type recorded.
+    pub async fn metric_type(&self, metric: &str) -> Result<Option<String>, AppError> {
+        self.api
+            .get_metric_metadata(metric.to_string())
+            .await
+            .map(|metadata| metadata.type_)
+            .map_err(|e| convert_datadog_error(e, "metric metadata"))
+    }
+
     /// Lists active metrics within a time window.
     ///
     /// Returns an async stream of metric names that were actively reporting
@@ -130,7 +286,7 @@ impl MetricsClient {
                     }
                     Err(e) => {
                         // Convert the error and return it as a single-item stream
-                        let app_error = convert_datadog_error(e);
+                        let app_error = convert_datadog_error(e, "metrics");
                         stream::once(async move { Err(app_error) }).boxed()
                     }
                 }
@@ -140,25 +296,254 @@ impl MetricsClient {
     }
 }
 
-/// Converts a Datadog API error to an AppError.
-fn convert_datadog_error<T: std::fmt::Display>(e: T) -> AppError {
-    let msg = format!("{}", e);
-
-    if msg.contains("401") {
-        AppError::Auth(format!(
-            "Authentication failed (401): Invalid API or App key. {}",
-            msg
-        ))
-    } else if msg.contains("403") || msg.contains("Forbidden") {
-        AppError::Auth(format!(
-            "Access denied (403): Your API key may not have permission to access metrics. {}",
-            msg
-        ))
-    } else if msg.contains("400") || msg.contains("Bad Request") {
-        AppError::InvalidQuery(msg)
-    } else {
-        AppError::Api(msg)
+impl BuildableClient for MetricsClient {
+    fn from_parts(config: Configuration, http: ClientWithMiddleware) -> Self {
+        Self {
+            api: MetricsAPI::with_client_and_config(config.clone(), http.clone()),
+            api_v2: MetricsV2API::with_client_and_config(config, http),
+        }
+    }
+}
+
+/// Flattens a v2 scalar response into one value per group.
+fn scalar_values(response: ScalarFormulaQueryResponse) -> Result<Vec<ScalarValue>, AppError> {
+    let columns = response
+        .data
+        .and_then(|data| data.attributes)
+        .and_then(|attributes| attributes.columns)
+        .unwrap_or_default();
+    let mut groups = Vec::new();
+    let mut values = Vec::new();
+    for column in columns {
+        match column {
+            ScalarColumn::GroupScalarColumn(column) => {
+                groups = column.values.unwrap_or_default();
+            }
+            ScalarColumn::DataScalarColumn(column) => {
+                values = column.values.unwrap_or_default();
+            }
+            _ => {}
+        }
+    }
+    if values.is_empty() {
+        if let Some(errors) = response.errors.filter(|e| !e.is_empty()) {
+            return Err(AppError::InvalidQuery(errors));
+        }
+        // No data at all still answers the query, with an unknown value
+        values.push(None);
+    }
+
+    Ok(values
+        .into_iter()
+        .enumerate()
+        .map(|(row, value)| {
+            let tag_set = groups.get(row).cloned().unwrap_or_default();
+            let scope = match tag_set.is_empty() {
+                true => "*".to_string(),
+                false => tag_set.join(","),
+            };
+            ScalarValue {
+                scope,
+                tag_set,
+                value,
+            }
+        })
+        .collect())
+}
+
+/// Builds a v2 timeseries request for `queries` named `a`, `b`, ..., with
+/// `from` and `to` in Unix seconds.
+fn formula_request(
+    queries: &[String],
+    formulas: &[String],
+    from: i64,
+    to: i64,
+) -> Result<TimeseriesFormulaQueryRequest, AppError> {
+    if queries.is_empty() || queries.len() > QUERY_NAMES.len() {
+        return Err(AppError::InvalidQuery(format!(
+            "Expected 1 to {} metric queries, got {}",
+            QUERY_NAMES.len(),
+            queries.len()
+        )));
+    }
+    let queries = queries
+        .iter()
+        .zip(QUERY_NAMES.chars())
+        .map(|(query, name)| {
+            TimeseriesQuery::MetricsTimeseriesQuery(Box::new(
+                MetricsTimeseriesQuery::new(MetricsDataSource::METRICS, query.clone())
+                    .name(name.to_string()),
+            ))
+        })
+        .collect();
+    let mut attributes = TimeseriesFormulaRequestAttributes::new(from * 1000, queries, to * 1000);
+    if !formulas.is_empty() {
+        attributes = attributes.formulas(
+            formulas
+                .iter()
+                .map(|f| QueryFormula::new(f.clone()))
+                .collect(),
+        );
+    }
+    Ok(TimeseriesFormulaQueryRequest::new(
+        TimeseriesFormulaRequest::new(attributes, TimeseriesFormulaRequestType::TIMESERIES_REQUEST),
+    ))
+}
+
+/// Flattens a v2 timeseries response into points, naming each series after
+/// the expression (formula or query) at its query index.
+fn formula_points(
+    response: TimeseriesFormulaQueryResponse,
+    expressions: &[String],
+) -> Result<Vec<MetricPoint>, AppError> {
+    let attributes = response.data.and_then(|data| data.attributes);
+    let series = attributes
+        .as_ref()
+        .and_then(|a| a.series.clone())
+        .unwrap_or_default();
+    if series.is_empty()
+        && let Some(errors) = response.errors.filter(|e| !e.is_empty())
+    {
+        return Err(AppError::InvalidQuery(errors));
+    }
+    let Some(attributes) = attributes else {
+        return Ok(Vec::new());
+    };
+    let times = attributes.times.unwrap_or_default();
+    let values = attributes.values.unwrap_or_default();
+
+    let mut points = Vec::new();
+    for (series, values) in series.into_iter().zip(values) {
+        let query_index = series.query_index.map(i64::from);
+        let metric = query_index
+            .and_then(|i| expressions.get(i as usize))
+            .cloned()
+            .unwrap_or_default();
+        let tag_set = series.group_tags.unwrap_or_default();
+        let scope = match tag_set.is_empty() {
+            true => "*".to_string(),
+            false => tag_set.join(","),
+        };
+        for (time, value) in times.iter().zip(values) {
+            let Some(value) = value else { continue };
+            points.push(MetricPoint {
+                metric: metric.clone(),
+                display_name: None,
+                query_index,
+                aggr: None,
+                scope: scope.clone(),
+                tag_set: tag_set.clone(),
+                timestamp: time / 1000,
+                value,
+            });
+        }
     }
+    Ok(points)
+}
+
+/// How [`MetricsClient::scalar`] reduces a series to one value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Reducer {
+    /// Average of the points
+    #[default]
+    Avg,
+
+    /// Smallest point
+    Min,
+
+    /// Largest point
+    Max,
+
+    /// Sum of the points
+    Sum,
+
+    /// Most recent point
+    Last,
+}
+
+impl Reducer {
+    fn aggregator(self) -> MetricsAggregator {
+        match self {
+            Reducer::Avg => MetricsAggregator::AVG,
+            Reducer::Min => MetricsAggregator::MIN,
+            Reducer::Max => MetricsAggregator::MAX,
+            Reducer::Sum => MetricsAggregator::SUM,
+            Reducer::Last => MetricsAggregator::LAST,
+        }
+    }
+}
+
+impl FromStr for Reducer {
+    type Err = String;
+
+    /// Parses `avg`, `min`, `max`, `sum`, or `last`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ddog::client::Reducer;
+    ///
+    /// assert_eq!("last".parse(), Ok(Reducer::Last));
+    /// assert!("median".parse::<Reducer>().is_err());
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "avg" => Ok(Reducer::Avg),
+            "min" => Ok(Reducer::Min),
+            "max" => Ok(Reducer::Max),
+            "sum" => Ok(Reducer::Sum),
+            "last" => Ok(Reducer::Last),
+            _ => Err(format!(
+                "invalid reducer '{}': expected avg, min, max, sum, or last",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Reducer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Reducer::Avg => "avg",
+            Reducer::Min => "min",
+            Reducer::Max => "max",
+            Reducer::Sum => "sum",
+            Reducer::Last => "last",
+        })
+    }
+}
+
+/// A metric query reduced to one value, for one group.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct ScalarValue {
+    /// Group tags joined by commas, or "*" for an ungrouped query
+    pub scope: String,
+
+    /// Group tags
+    pub tag_set: Vec<String>,
+
+    /// Reduced value; `None` when the query had no data
+    pub value: Option<f64>,
+}
+
+/// Estimated distinct series of a metric, as reported by Datadog.
+///
+/// Distribution metrics report a distinct volume; other metrics report the
+/// series ingested and the series indexed (queryable) after tag
+/// configuration.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+pub struct SeriesVolumes {
+    /// Distinct series of a distribution metric
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub distinct: Option<i64>,
+
+    /// Series ingested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ingested: Option<i64>,
+
+    /// Series indexed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub indexed: Option<i64>,
 }
 
 /// A single metric timeseries point.
@@ -193,3 +578,157 @@ pub struct MetricPoint {
     /// Metric value at this timestamp
     pub value: f64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::MockTransport;
+    use futures_util::TryStreamExt;
+    use serde_json::json;
+
+    const TIMESERIES: &str = "/api/v2/query/timeseries";
+
+    fn client(transport: &MockTransport) -> MetricsClient {
+        MetricsClient::builder()
+            .api_key("test")
+            .app_key("test")
+            .transport(transport.clone())
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_query_formulas() {
+        let transport = MockTransport::new().respond(
+            "POST",
+            TIMESERIES,
+            200,
+            json!({"data": {"type": "timeseries_response", "attributes": {
+                "series": [
+                    {"query_index": 0, "group_tags": ["service:api"]},
+                    {"query_index": 0, "group_tags": ["service:web"]}
+                ],
+                "times": [1705315200000i64, 1705315260000i64],
+                "values": [[2.5, null], [1.0, 0.5]]
+            }}}),
+        );
+        let queries = [
+            "sum:trace.http.request.errors{*} by {service}".to_string(),
+            "sum:trace.http.request.hits{*} by {service}".to_string(),
+        ];
+        let formulas = ["a / b * 100".to_string()];
+        let points: Vec<_> = client(&transport)
+            .query_formulas(&queries, &formulas, 1705315200, 1705318800)
+            .try_collect()
+            .await
+            .unwrap();
+
+        assert_eq!(points.len(), 3);
+        assert_eq!(points[0].metric, "a / b * 100");
+        assert_eq!(points[0].scope, "service:api");
+        assert_eq!(points[0].timestamp, 1705315200);
+        assert_eq!(points[2].value, 0.5);
+
+        let body = transport.requests()[0].body.clone().unwrap();
+        let attributes = &body["data"]["attributes"];
+        assert_eq!(attributes["from"], 1705315200000i64);
+        assert_eq!(attributes["queries"][1]["name"], "b");
+        assert_eq!(attributes["queries"][1]["data_source"], "metrics");
+        assert_eq!(attributes["formulas"][0]["formula"], "a / b * 100");
+    }
+
+    #[tokio::test]
+    async fn test_scalar() {
+        let transport = MockTransport::new()
+            .respond(
+                "POST",
+                "/api/v2/query/scalar",
+                200,
+                json!({"data": {"type": "scalar_response", "attributes": {"columns": [
+                    {"type": "group", "name": "host", "values": [["host:a"], ["host:b"]]},
+                    {"type": "number", "name": "a", "values": [42.5, null]}
+                ]}}}),
+            )
+            .respond(
+                "POST",
+                "/api/v2/query/scalar",
+                200,
+                json!({"data": {"type": "scalar_response", "attributes": {"columns": []}}}),
+            );
+        let client = client(&transport);
+
+        let values = client
+            .scalar("avg:system.cpu.user{*} by {host}", Reducer::Max, 0, 300)
+            .await
+            .unwrap();
+        assert_eq!(values.len(), 2);
+        assert_eq!(values[0].scope, "host:a");
+        assert_eq!(values[0].value, Some(42.5));
+        assert_eq!(values[1].value, None);
+        let body = transport.requests()[0].body.clone().unwrap();
+        assert_eq!(
+            body["data"]["attributes"]["queries"][0]["aggregator"],
+            "max"
+        );
+        assert_eq!(body["data"]["attributes"]["to"], 300000);
+
+        let empty = client
+            .scalar("avg:system.cpu.user{*}", Reducer::Avg, 0, 300)
+            .await
+            .unwrap();
+        assert_eq!(empty[0].scope, "*");
+        assert_eq!(empty[0].value, None);
+    }
+
+    #[tokio::test]
+    async fn test_tags_and_volumes() {
+        let transport = MockTransport::new()
+            .respond(
+                "GET",
+                "/api/v2/metrics/app.requests/all-tags",
+                200,
+                json!({"data": {"type": "metrics", "id": "app.requests",
+                    "attributes": {"tags": ["env:prod", "user_id:1"]}}}),
+            )
+            .respond(
+                "GET",
+                "/api/v2/metrics/app.requests/volumes",
+                200,
+                json!({"data": {"type": "metric_volumes", "id": "app.requests",
+                    "attributes": {"ingested_volume": 5200, "indexed_volume": 4800}}}),
+            );
+        let client = client(&transport);
+
+        let tags = client.tags("app.requests").await.unwrap();
+        assert_eq!(tags, ["env:prod", "user_id:1"]);
+        let volumes = client.volumes("app.requests").await.unwrap();
+        assert_eq!(volumes.indexed, Some(4800));
+        assert_eq!(volumes.distinct, None);
+    }
+
+    #[tokio::test]
+    async fn test_query_formulas_errors() {
+        let transport = MockTransport::new().respond(
+            "POST",
+            TIMESERIES,
+            200,
+            json!({"errors": "Error parsing formula: a /"}),
+        );
+        let result: Result<Vec<_>, _> = client(&transport)
+            .query_formulas(
+                &["avg:system.load.1{*}".to_string()],
+                &["a /".to_string()],
+                0,
+                60,
+            )
+            .try_collect()
+            .await;
+        assert!(matches!(result, Err(AppError::InvalidQuery(e)) if e.contains("parsing formula")));
+
+        let result: Result<Vec<_>, _> = client(&transport)
+            .query_formulas(&[], &[], 0, 60)
+            .try_collect()
+            .await;
+        assert!(matches!(result, Err(AppError::InvalidQuery(_))));
+    }
+}
@@ -1,11 +1,228 @@
 //! Datadog API client wrappers.
 //!
 //! Provides simplified interfaces to the Datadog SDK with automatic pagination.
+//! [`LogsClient`], [`SpansClient`], and [`MetricsClient`] are the library's
+//! public API: build one with `builder()`, then stream or collect the results
+//! of a [`LogsSearchRequest`] or [`SpansSearchRequest`]. Failures are
+//! [`AppError`]s.
 
+mod aggregate;
+mod api;
+mod apm_config;
+mod builder;
+mod cache;
+mod cassette;
+mod compression;
+mod events;
+mod hosts;
+pub mod http;
+mod incidents;
+mod log_metrics;
 mod logs;
 mod metrics;
+mod monitors;
+mod pages;
+mod request;
+mod resources;
+mod restrictions;
+mod services;
+mod slos;
 mod spans;
+mod stream;
+mod transport;
 
+pub use aggregate::{
+    FacetValue, GroupCount, Grouping, LatencyPoint, Measure, Percentile, Ranking, VolumePoint,
+};
+pub use api::{ApiClient, into_records, parse_query};
+pub use apm_config::{ApmConfigClient, RETENTION_FILTERS_PATH, SPAN_METRICS_PATH};
+pub use builder::{BuildableClient, ClientBuilder};
+pub use cache::{CacheTransport, DEFAULT_CACHE_TTL};
+pub use cassette::{CASSETTE_ENV, Cassette, CassetteTransport, Interaction};
+pub use events::{AgentStart, EventsClient, MonitorEvent};
+pub use hosts::{HostSummary, HostsClient};
+pub use incidents::{IncidentSummary, IncidentsClient};
+pub use log_metrics::{LOG_METRICS_PATH, LogMetricsClient, parse_log_metric};
 pub use logs::LogsClient;
-pub use metrics::MetricsClient;
-pub use spans::SpansClient;
+pub use metrics::{MetricPoint, MetricsClient, Reducer, ScalarValue, SeriesVolumes};
+pub use monitors::{MonitorStatus, MonitorSummary, MonitorsClient};
+pub use request::{
+    LogsSearchRequest, LogsSearchRequestBuilder, SortOrder, SpansSearchRequest,
+    SpansSearchRequestBuilder, SpansSearchType, StorageTier,
+};
+pub use resources::{ResourceKind, ResourcesClient};
+pub use restrictions::RestrictionsClient;
+pub use services::ServiceCatalogClient;
+pub use slos::{SloSummary, SlosClient};
+pub use spans::{SpansClient, TraceSpan};
+pub use stream::{SearchStream, Timestamped};
+pub(crate) use transport::TransportMiddleware;
+pub use transport::{DatadogTransport, MockTransport, RecordedRequest};
+
+/// Token for cancelling searches, see [`SearchStream::cancel_with`].
+pub use tokio_util::sync::CancellationToken;
+
+use datadog_api_client::datadog;
+use reqwest::StatusCode;
+
+use crate::error::AppError;
+
+/// Records requested per page by the paginated logs and spans searches.
+pub const SEARCH_PAGE_SIZE: i32 = 1000;
+
+/// Most records a logs search page can hold.
+pub const MAX_LOGS_PAGE_SIZE: i32 = 5000;
+
+/// Most records a spans search page can hold.
+pub const MAX_SPANS_PAGE_SIZE: i32 = 1000;
+
+/// Characters of a non-JSON error body kept in error messages.
+const MAX_ERROR_BODY: usize = 200;
+
+/// Converts a Datadog API error to an AppError.
+///
+/// `resource` names what was being accessed (e.g. "metrics") for the 403 hint.
+/// Error responses are classified by [`status_error`]; failures to send the
+/// request or read its response become `AppError::Unavailable`, unless a
+/// transport failed with an error that won't recur (such as a cassette
+/// missing the request), and other failures `AppError::Api`.
+pub(crate) fn convert_datadog_error<T>(e: datadog::Error<T>, resource: &str) -> AppError {
+    match e {
+        datadog::Error::ResponseError(response) => {
+            status_error(response.status, &response.content, resource)
+        }
+        e @ (datadog::Error::Reqwest(_) | datadog::Error::ReqwestMiddleware(_)) => {
+            let lasting = matches!(
+                &e,
+                datadog::Error::ReqwestMiddleware(reqwest_middleware::Error::Middleware(inner))
+                    if inner.downcast_ref::<AppError>().is_some_and(|e| !e.is_retryable())
+            );
+            if lasting {
+                AppError::Api(e.to_string())
+            } else {
+                AppError::Unavailable(e.to_string())
+            }
+        }
+        e => AppError::Api(e.to_string()),
+    }
+}
+
+/// Converts an error response to the AppError for its status code:
+///
+/// - 401: `Auth`
+/// - 403: `Forbidden`
+/// - 400: `InvalidQuery`
+/// - 429: `RateLimited`
+/// - 5xx: `Unavailable`
+/// - anything else: `Api`
+///
+/// The message includes the API's error strings from `body`, and the ID of
+/// the request if the body was stamped with it (see [`http`]).
+pub(crate) fn status_error(status: StatusCode, body: &str, resource: &str) -> AppError {
+    let details = match request_id(body) {
+        Some(id) => format!("{} (request ID: {})", error_details(body), id),
+        None => error_details(body),
+    };
+    match status {
+        StatusCode::UNAUTHORIZED => AppError::Auth(format!(
+            "Authentication failed (401): Invalid API or App key. {}",
+            details
+        )),
+        StatusCode::FORBIDDEN => AppError::Forbidden(format!(
+            "Your API key may not have permission to access {} (403). {}",
+            resource, details
+        )),
+        StatusCode::BAD_REQUEST => AppError::InvalidQuery(format!("{} (400)", details)),
+        StatusCode::TOO_MANY_REQUESTS => AppError::RateLimited(format!(
+            "Too many requests for {} (429). {}",
+            resource, details
+        )),
+        status if status.is_server_error() => {
+            AppError::Unavailable(format!("HTTP {}: {}", status, details))
+        }
+        status => AppError::Api(format!("HTTP {}: {}", status, details)),
+    }
+}
+
+/// The request ID stamped into an error body.
+fn request_id(body: &str) -> Option<String> {
+    let json = serde_json::from_str::<serde_json::Value>(body).ok()?;
+    json.get(http::REQUEST_ID_FIELD)?.as_str().map(String::from)
+}
+
+/// The error strings of a Datadog error body (`{"errors": [...]}`), or the
+/// start of the body if it has none.
+fn error_details(body: &str) -> String {
+    let errors = serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|json| json.get("errors")?.as_array().cloned())
+        .unwrap_or_default();
+    let errors: Vec<String> = errors
+        .iter()
+        .map(|error| match error {
+            serde_json::Value::String(s) => s.clone(),
+            // Some endpoints return JSON:API error objects
+            error => error
+                .get("detail")
+                .or_else(|| error.get("title"))
+                .and_then(|s| s.as_str())
+                .map_or_else(|| error.to_string(), String::from),
+        })
+        .collect();
+    if !errors.is_empty() {
+        return errors.join("; ");
+    }
+    let body = body.trim();
+    match body.char_indices().nth(MAX_ERROR_BODY) {
+        Some((end, _)) => format!("{}...", &body[..end]),
+        None => body.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_error_classifies_by_status() {
+        let body = r#"{"errors": ["Bad credentials"]}"#;
+        let error = status_error(StatusCode::UNAUTHORIZED, body, "logs");
+        assert!(matches!(error, AppError::Auth(msg) if msg.contains("Bad credentials")));
+
+        let error = status_error(StatusCode::FORBIDDEN, body, "logs");
+        assert!(matches!(error, AppError::Forbidden(msg) if msg.contains("access logs")));
+
+        let error = status_error(StatusCode::TOO_MANY_REQUESTS, "", "logs");
+        assert!(matches!(error, AppError::RateLimited(_)));
+
+        let error = status_error(StatusCode::SERVICE_UNAVAILABLE, "<html>down</html>", "logs");
+        assert_eq!(
+            error.to_string(),
+            "API error: HTTP 503 Service Unavailable: <html>down</html>"
+        );
+    }
+
+    #[test]
+    fn test_bad_request_keeps_api_errors() {
+        let body = r#"{"errors": ["Invalid query: unexpected ')'", "Try again"]}"#;
+        let error = status_error(StatusCode::BAD_REQUEST, body, "logs");
+        assert_eq!(
+            error.to_string(),
+            "Invalid query: Invalid query: unexpected ')'; Try again (400)"
+        );
+
+        let body = r#"{"errors": [{"title": "Bad Request", "detail": "invalid facet"}]}"#;
+        assert_eq!(error_details(body), "invalid facet");
+    }
+
+    #[test]
+    fn test_status_error_quotes_request_id() {
+        let body = r#"{"errors": ["Forbidden"], "_request_id": "abc123"}"#;
+        let error = status_error(StatusCode::FORBIDDEN, body, "logs");
+        assert!(
+            error
+                .to_string()
+                .ends_with("Forbidden (request ID: abc123)")
+        );
+    }
+}
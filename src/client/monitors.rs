@@ -0,0 +1,149 @@
+//! Datadog Monitors API client wrapper.
+//!
+//! Provides a simplified interface for listing monitors with automatic pagination
+//! and fetching a monitor's current state.
+
+use datadog_api_client::datadog::Configuration;
+use datadog_api_client::datadogV1::api_monitors::{
+    GetMonitorOptionalParams, ListMonitorsOptionalParams, MonitorsAPI,
+};
+use futures_util::{Stream, StreamExt};
+use reqwest_middleware::ClientWithMiddleware;
+
+use super::builder::{BuildableClient, ClientBuilder};
+use super::convert_datadog_error;
+use super::http::build_client;
+use crate::error::AppError;
+
+/// Client for querying Datadog monitors.
+///
+/// Wraps the Datadog SDK's MonitorsAPI with automatic pagination support.
+pub struct MonitorsClient {
+    api: MonitorsAPI,
+}
+
+impl MonitorsClient {
+    /// Creates a new MonitorsClient with the given configuration.
+    pub fn new(config: Configuration) -> Self {
+        Self {
+            api: MonitorsAPI::with_client_and_config(config.clone(), build_client(&config)),
+        }
+    }
+
+    /// Starts building a client from explicit credentials and site.
+    pub fn builder() -> ClientBuilder<Self> {
+        ClientBuilder::new()
+    }
+
+    /// Fetches a monitor's current overall state.
+    pub async fn status(&self, monitor_id: i64) -> Result<MonitorStatus, AppError> {
+        let monitor = self
+            .api
+            .get_monitor(monitor_id, GetMonitorOptionalParams::default())
+            .await
+            .map_err(|e| convert_datadog_error(e, "monitor"))?;
+        Ok(MonitorStatus {
+            monitor_id,
+            name: monitor.name.unwrap_or_default(),
+            state: monitor
+                .overall_state
+                .map_or_else(|| "Unknown".to_string(), |state| state.to_string()),
+        })
+    }
+
+    /// Lists monitors carrying all of the given monitor tags.
+    ///
+    /// Returns an async stream of monitors. The stream handles pagination
+    /// automatically.
+    ///
+    /// # Arguments
+    ///
+    /// * `tags` - Monitor tags such as "team:payments"; empty for all monitors
+    pub fn list(
+        &self,
+        tags: &[String],
+    ) -> impl Stream<Item = Result<MonitorSummary, AppError>> + '_ {
+        let mut params = ListMonitorsOptionalParams::default().page_size(100);
+        if !tags.is_empty() {
+            params = params.monitor_tags(tags.join(","));
+        }
+
+        self.api
+            .list_monitors_with_pagination(params)
+            .filter_map(|result| async move {
+                match result {
+                    Ok(monitor) => monitor.id.map(|id| {
+                        Ok(MonitorSummary {
+                            id,
+                            name: monitor.name.unwrap_or_default(),
+                            tags: monitor.tags.unwrap_or_default(),
+                        })
+                    }),
+                    Err(e) => Some(Err(convert_datadog_error(e, "monitors"))),
+                }
+            })
+    }
+}
+
+impl BuildableClient for MonitorsClient {
+    fn from_parts(config: Configuration, http: ClientWithMiddleware) -> Self {
+        Self {
+            api: MonitorsAPI::with_client_and_config(config, http),
+        }
+    }
+}
+
+/// A monitor's current state.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct MonitorStatus {
+    /// Numeric monitor ID
+    pub monitor_id: i64,
+
+    /// Monitor name
+    pub name: String,
+
+    /// Overall state as Datadog names it: "OK", "Alert", "Warn", "No Data", ...
+    pub state: String,
+}
+
+/// A monitor's identity, as needed to report on it.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct MonitorSummary {
+    /// Numeric monitor ID
+    pub id: i64,
+
+    /// Monitor name
+    pub name: String,
+
+    /// Monitor tags (e.g., "team:payments")
+    pub tags: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::MockTransport;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_status() {
+        let transport = MockTransport::new().respond(
+            "GET",
+            "/api/v1/monitor/123",
+            200,
+            json!({"id": 123, "name": "High CPU", "type": "metric alert",
+                "query": "avg(last_5m):avg:system.cpu.user{*} > 90",
+                "overall_state": "No Data"}),
+        );
+        let client = MonitorsClient::builder()
+            .api_key("test")
+            .app_key("test")
+            .transport(transport)
+            .build()
+            .unwrap();
+
+        let status = client.status(123).await.unwrap();
+        assert_eq!(status.name, "High CPU");
+        assert_eq!(status.state, "No Data");
+    }
+}
@@ -0,0 +1,386 @@
+//! Incremental parsing of search result pages.
+//!
+//! The SDK's paginated searches read each page's whole response body, then
+//! parse all of it before yielding the first record, so a page of 1000 large
+//! logs is held in memory twice over. [`search_pages`] still lets the SDK
+//! build every page request, with its credentials, body, and page cursor, but
+//! sends it through the shared HTTP client itself and parses the `data` array
+//! of the response one element at a time as the body arrives. Only the record
+//! being parsed, the chunk being read, and the response's envelope (`meta`
+//! and `links`, without the records) are held at once. Compressed bodies are
+//! decompressed chunk by chunk too.
+
+use async_trait::async_trait;
+use datadog_api_client::datadog::{self, ResponseContent};
+use futures_util::Stream;
+use reqwest::{Request, Response};
+use reqwest_middleware::{ClientBuilder, ClientWithMiddleware, Middleware, Next};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use task_local_extensions::Extensions;
+
+use super::compression::{Decoder, StreamedBody};
+use super::http;
+use crate::error::AppError;
+
+/// Field of the records in a search response.
+const DATA_FIELD: &[u8] = b"data";
+
+/// Where a search response keeps the cursor of the next page.
+const NEXT_CURSOR: &str = "/meta/page/after";
+
+/// Runs an SDK call against a client that keeps the request instead of
+/// sending it, and returns the request.
+///
+/// # Errors
+///
+/// Returns the call's error if it failed before sending anything, such as
+/// when its body couldn't be serialized.
+pub(crate) async fn capture_request<F, Fut, R, E>(call: F) -> Result<Request, datadog::Error<E>>
+where
+    F: FnOnce(ClientWithMiddleware) -> Fut,
+    Fut: Future<Output = Result<R, datadog::Error<E>>>,
+{
+    let captured = Arc::new(Mutex::new(None));
+    let client = ClientBuilder::new(http::shared_client(None))
+        .with(Capture(captured.clone()))
+        .build();
+    let result = call(client).await;
+    let request = captured.lock().unwrap_or_else(|e| e.into_inner()).take();
+    match (request, result) {
+        (Some(request), _) => Ok(request),
+        (None, Err(e)) => Err(e),
+        (None, Ok(_)) => Err(datadog::Error::Io(std::io::Error::other(
+            "the request was answered without being sent",
+        ))),
+    }
+}
+
+/// Middleware keeping the request it's handed and failing it.
+struct Capture(Arc<Mutex<Option<Request>>>);
+
+#[async_trait]
+impl Middleware for Capture {
+    async fn handle(
+        &self,
+        req: Request,
+        _extensions: &mut Extensions,
+        _next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        *self.0.lock().unwrap_or_else(|e| e.into_inner()) = Some(req);
+        Err(reqwest_middleware::Error::Middleware(
+            AppError::Api("request captured".to_string()).into(),
+        ))
+    }
+}
+
+/// Streams the records of a paginated search, parsing each page as it
+/// arrives.
+///
+/// `page` builds the request for a page from the cursor returned with the
+/// previous one (`None` for the first), usually with [`capture_request`].
+/// Pages are requested until one returns fewer than `page_size` records or
+/// no cursor, like the SDK's own pagination. The stream ends after the first
+/// error.
+///
+/// Each record comes with the number of the page it was on, counting from 1.
+pub(crate) fn search_pages<T, E, P, Fut>(
+    http: ClientWithMiddleware,
+    page_size: usize,
+    page: P,
+) -> impl Stream<Item = Result<(u64, T), datadog::Error<E>>>
+where
+    T: DeserializeOwned,
+    P: Fn(Option<String>) -> Fut,
+    Fut: Future<Output = Result<Request, datadog::Error<E>>>,
+{
+    let state = PageState {
+        http,
+        page,
+        page_size,
+        cursor: None,
+        reading: None,
+        pages: 0,
+        done: false,
+    };
+    futures_util::stream::try_unfold(state, |mut state| async move {
+        loop {
+            if state.done {
+                return Ok(None);
+            }
+            let Some(reading) = state.reading.as_mut() else {
+                let request = (state.page)(state.cursor.take()).await?;
+                let mut extensions = Extensions::new();
+                extensions.insert(StreamedBody);
+                let response = state
+                    .http
+                    .execute_with_extensions(request, &mut extensions)
+                    .await
+                    .map_err(datadog::Error::ReqwestMiddleware)?;
+                let status = response.status();
+                if !status.is_success() {
+                    let content = response.text().await.map_err(datadog::Error::Reqwest)?;
+                    return Err(datadog::Error::ResponseError(ResponseContent {
+                        status,
+                        content,
+                        entity: None,
+                    }));
+                }
+                let decoder =
+                    Decoder::for_headers(response.headers()).map_err(datadog::Error::Io)?;
+                state.reading = Some(Reading::new(response, decoder));
+                state.pages += 1;
+                continue;
+            };
+
+            if let Some(element) = reading.records.pop_front() {
+                reading.count += 1;
+                let record = serde_json::from_slice(&element).map_err(datadog::Error::Serde)?;
+                return Ok(Some(((state.pages, record), state)));
+            }
+            match reading
+                .response
+                .chunk()
+                .await
+                .map_err(datadog::Error::Reqwest)?
+            {
+                Some(chunk) => match reading.decoder.as_mut() {
+                    Some(decoder) => {
+                        let chunk = decoder.write(&chunk).map_err(datadog::Error::Io)?;
+                        reading.splitter.feed(&chunk, &mut reading.records);
+                    }
+                    None => reading.splitter.feed(&chunk, &mut reading.records),
+                },
+                None if reading.decoder.is_some() => {
+                    let decoder = reading.decoder.take().expect("checked above");
+                    let rest = decoder.finish().map_err(datadog::Error::Io)?;
+                    reading.splitter.feed(&rest, &mut reading.records);
+                }
+                None => {
+                    let count = reading.count;
+                    let envelope = reading.splitter.envelope()?;
+                    state.reading = None;
+                    state.cursor = envelope
+                        .pointer(NEXT_CURSOR)
+                        .and_then(Value::as_str)
+                        .filter(|_| count >= state.page_size)
+                        .map(String::from);
+                    state.done = state.cursor.is_none();
+                }
+            }
+        }
+    })
+}
+
+struct PageState<P> {
+    http: ClientWithMiddleware,
+    page: P,
+    page_size: usize,
+    cursor: Option<String>,
+    reading: Option<Reading>,
+    /// Pages requested so far
+    pages: u64,
+    done: bool,
+}
+
+/// A page response being read.
+struct Reading {
+    response: Response,
+    splitter: DataSplitter,
+    /// Set while the body is compressed
+    decoder: Option<Decoder>,
+    records: VecDeque<Vec<u8>>,
+    count: usize,
+}
+
+impl Reading {
+    fn new(response: Response, decoder: Option<Decoder>) -> Self {
+        Self {
+            response,
+            splitter: DataSplitter::default(),
+            decoder,
+            records: VecDeque::new(),
+            count: 0,
+        }
+    }
+}
+
+/// Splits the elements of a JSON object's top-level `data` array out of a
+/// body fed in chunks, keeping the rest of the object as its envelope.
+#[derive(Debug, Default)]
+struct DataSplitter {
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    /// The string being read at the top level, and the last one read
+    key: Vec<u8>,
+    last_key: Vec<u8>,
+    in_data: bool,
+    element: Vec<u8>,
+    envelope: Vec<u8>,
+}
+
+impl DataSplitter {
+    /// Reads a chunk, adding the elements it completes to `elements`.
+    fn feed(&mut self, chunk: &[u8], elements: &mut VecDeque<Vec<u8>>) {
+        for &byte in chunk {
+            // Between elements, where a bare number or literal also ends
+            if self.in_data
+                && self.depth == 2
+                && !self.in_string
+                && (byte.is_ascii_whitespace() || byte == b',' || byte == b']')
+            {
+                if !self.element.is_empty() {
+                    elements.push_back(std::mem::take(&mut self.element));
+                }
+                if byte == b']' {
+                    self.in_data = false;
+                    self.depth = 1;
+                    self.envelope.push(byte);
+                }
+                continue;
+            }
+
+            let in_data = self.in_data;
+            let mut closed = false;
+            if self.in_string {
+                if self.escaped {
+                    self.escaped = false;
+                } else if byte == b'\\' {
+                    self.escaped = true;
+                } else if byte == b'"' {
+                    self.in_string = false;
+                    closed = true;
+                    if self.depth == 1 {
+                        self.last_key = std::mem::take(&mut self.key);
+                    }
+                } else if self.depth == 1 {
+                    self.key.push(byte);
+                }
+            } else {
+                match byte {
+                    b'"' => {
+                        self.in_string = true;
+                        self.key.clear();
+                    }
+                    b'[' if self.depth == 1 && self.last_key == DATA_FIELD => {
+                        self.in_data = true;
+                        self.depth += 1;
+                    }
+                    b'{' | b'[' => self.depth += 1,
+                    b'}' | b']' => {
+                        self.depth = self.depth.saturating_sub(1);
+                        closed = true;
+                    }
+                    _ => {}
+                }
+            }
+
+            if !in_data {
+                self.envelope.push(byte);
+                continue;
+            }
+            self.element.push(byte);
+            // A string ends as it closes; bare values end at the next delimiter
+            if closed && self.depth == 2 && !self.in_string {
+                elements.push_back(std::mem::take(&mut self.element));
+            }
+        }
+    }
+
+    /// Parses what's left of the body once the records are taken out.
+    fn envelope<E>(&self) -> Result<Value, datadog::Error<E>> {
+        if self.envelope.iter().all(u8::is_ascii_whitespace) {
+            return Ok(Value::Null);
+        }
+        serde_json::from_slice(&self.envelope).map_err(datadog::Error::Serde)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{LogsClient, LogsSearchRequest, MockTransport};
+    use serde_json::json;
+
+    fn split(body: &str, chunk_size: usize) -> (Vec<Value>, Value) {
+        let mut splitter = DataSplitter::default();
+        let mut elements = VecDeque::new();
+        for chunk in body.as_bytes().chunks(chunk_size) {
+            splitter.feed(chunk, &mut elements);
+        }
+        let elements = elements
+            .iter()
+            .map(|e| serde_json::from_slice(e).unwrap())
+            .collect();
+        (elements, splitter.envelope::<()>().unwrap())
+    }
+
+    #[test]
+    fn test_splits_data_across_chunks() {
+        let body = json!({
+            "meta": {"page": {"after": "next"}},
+            "data": [
+                {"id": "a", "attributes": {"message": "quote \" and ] and } inside", "tags": ["x", "y"]}},
+                {"id": "b", "attributes": {"nested": {"data": [1, 2]}}},
+            ],
+            "links": {"next": "https://api.datadoghq.com/api/v2/logs/events?page[cursor]=next"},
+        });
+        let text = serde_json::to_string_pretty(&body).unwrap();
+        for chunk_size in [1, 7, text.len()] {
+            let (elements, envelope) = split(&text, chunk_size);
+            assert_eq!(elements, body["data"].as_array().unwrap().clone());
+            assert_eq!(envelope["meta"]["page"]["after"], "next");
+            assert_eq!(envelope["data"], json!([]));
+            assert_eq!(envelope["links"], body["links"]);
+        }
+    }
+
+    #[test]
+    fn test_splits_scalar_elements() {
+        let (elements, envelope) = split(r#"{"data": [1, "two", null, [3], true]}"#, 3);
+        assert_eq!(
+            elements,
+            [json!(1), json!("two"), json!(null), json!([3]), json!(true)]
+        );
+        assert_eq!(envelope, json!({"data": []}));
+        let (elements, _) = split(r#"{"data": null}"#, 3);
+        assert!(elements.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_search_stops_on_short_page() {
+        let log = |id: &str| json!({"id": id, "type": "log", "attributes": {"message": id}});
+        let transport = MockTransport::new()
+            .respond(
+                "POST",
+                "/api/v2/logs/events/search",
+                200,
+                json!({"data": [log("a"), log("b")], "meta": {"page": {"after": "p2"}}}),
+            )
+            .respond(
+                "POST",
+                "/api/v2/logs/events/search",
+                200,
+                json!({"data": [log("c")], "meta": {"page": {"after": "p3"}}}),
+            );
+        let client = LogsClient::builder()
+            .api_key("test")
+            .app_key("test")
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+        let request = LogsSearchRequest::builder().page_limit(2).build();
+
+        let logs = client.fetch(&request, 0).await.unwrap();
+        let messages: Vec<_> = logs.iter().filter_map(|l| l.message.as_deref()).collect();
+        assert_eq!(messages, ["a", "b", "c"]);
+
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].body.as_ref().unwrap()["page"]["cursor"], "p2");
+    }
+}
@@ -0,0 +1,343 @@
+//! Typed search requests.
+//!
+//! Searches are described by request structs built with a builder, so new
+//! options can be added without breaking callers. Times accept relative
+//! ("now-1h"), ISO8601 ("2024-01-15T10:00:00Z"), or Unix millisecond
+//! ("1705315200000") values.
+
+use std::fmt;
+use std::str::FromStr;
+
+use super::SEARCH_PAGE_SIZE;
+use crate::query;
+
+/// Order search results are returned in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortOrder {
+    /// Oldest first
+    #[default]
+    Ascending,
+    /// Newest first
+    Descending,
+}
+
+/// Storage tier a logs search reads from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageTier {
+    /// Log indexes
+    Indexes,
+    /// Online Archives
+    OnlineArchives,
+    /// Flex Logs
+    Flex,
+}
+
+impl FromStr for StorageTier {
+    type Err = String;
+
+    /// Parses `indexes`, `online-archives`, or `flex`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ddog::client::StorageTier;
+    ///
+    /// assert_eq!("flex".parse(), Ok(StorageTier::Flex));
+    /// assert_eq!("online-archives".parse(), Ok(StorageTier::OnlineArchives));
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "indexes" => Ok(StorageTier::Indexes),
+            "online-archives" => Ok(StorageTier::OnlineArchives),
+            "flex" => Ok(StorageTier::Flex),
+            _ => Err(format!(
+                "invalid storage tier '{}': expected indexes, online-archives, or flex",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for StorageTier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            StorageTier::Indexes => "indexes",
+            StorageTier::OnlineArchives => "online-archives",
+            StorageTier::Flex => "flex",
+        })
+    }
+}
+
+/// A logs search.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::client::{LogsSearchRequest, SortOrder};
+///
+/// let request = LogsSearchRequest::builder()
+///     .query("service:api status:error")
+///     .from("now-15m")
+///     .indexes(["main"])
+///     .sort(SortOrder::Descending)
+///     .build();
+/// assert_eq!(request.to, "now");
+/// assert_eq!(request.page_limit, 1000);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct LogsSearchRequest {
+    /// Datadog query syntax (default: "*")
+    pub query: String,
+
+    /// Start time (default: "now-1h")
+    pub from: String,
+
+    /// End time (default: "now")
+    pub to: String,
+
+    /// Log indexes to search (default: all)
+    pub indexes: Vec<String>,
+
+    /// Storage tier to search (default: Datadog's, the indexes)
+    pub storage_tier: Option<StorageTier>,
+
+    /// Result order (default: oldest first)
+    pub sort: SortOrder,
+
+    /// Records requested per page (default: 1000)
+    pub page_limit: i32,
+}
+
+impl LogsSearchRequest {
+    /// Starts building a request, with every option at its default.
+    pub fn builder() -> LogsSearchRequestBuilder {
+        LogsSearchRequestBuilder {
+            request: Self {
+                query: "*".to_string(),
+                from: "now-1h".to_string(),
+                to: "now".to_string(),
+                indexes: vec!["*".to_string()],
+                storage_tier: None,
+                sort: SortOrder::default(),
+                page_limit: SEARCH_PAGE_SIZE,
+            },
+        }
+    }
+}
+
+/// Builder for [`LogsSearchRequest`].
+#[derive(Debug, Clone)]
+pub struct LogsSearchRequestBuilder {
+    request: LogsSearchRequest,
+}
+
+impl LogsSearchRequestBuilder {
+    /// Sets the query.
+    pub fn query(mut self, query: impl Into<String>) -> Self {
+        self.request.query = query.into();
+        self
+    }
+
+    /// Sets the start time.
+    pub fn from(mut self, from: impl Into<String>) -> Self {
+        self.request.from = from.into();
+        self
+    }
+
+    /// Sets the end time.
+    pub fn to(mut self, to: impl Into<String>) -> Self {
+        self.request.to = to.into();
+        self
+    }
+
+    /// Sets the log indexes to search.
+    pub fn indexes<I: IntoIterator<Item = S>, S: Into<String>>(mut self, indexes: I) -> Self {
+        self.request.indexes = indexes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets the storage tier to search, such as Flex Logs.
+    pub fn storage_tier(mut self, storage_tier: StorageTier) -> Self {
+        self.request.storage_tier = Some(storage_tier);
+        self
+    }
+
+    /// Sets the result order.
+    pub fn sort(mut self, sort: SortOrder) -> Self {
+        self.request.sort = sort;
+        self
+    }
+
+    /// Sets the records requested per page.
+    pub fn page_limit(mut self, page_limit: i32) -> Self {
+        self.request.page_limit = page_limit;
+        self
+    }
+
+    /// Finishes the request.
+    pub fn build(self) -> LogsSearchRequest {
+        self.request
+    }
+}
+
+/// What a spans search returns.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SpansSearchType {
+    /// Every matching span
+    #[default]
+    Spans,
+    /// Matching root spans only, one per trace
+    Traces,
+}
+
+impl FromStr for SpansSearchType {
+    type Err = String;
+
+    /// Parses `spans` or `traces`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "spans" => Ok(SpansSearchType::Spans),
+            "traces" => Ok(SpansSearchType::Traces),
+            _ => Err(format!(
+                "invalid search type '{}': expected spans or traces",
+                s
+            )),
+        }
+    }
+}
+
+/// An APM spans search.
+///
+/// `service` and `env` scope the query like the CLI's `--service` and
+/// `--env`, unless it already names that facet.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::client::{SpansSearchRequest, SpansSearchType};
+///
+/// let request = SpansSearchRequest::builder()
+///     .query("service:web @duration:>1s")
+///     .from("now-15m")
+///     .env("prod")
+///     .search_type(SpansSearchType::Traces)
+///     .build();
+/// assert_eq!(request.query, "service:web @duration:>1s");
+/// assert_eq!(request.scoped_query(), "env:prod (service:web @duration:>1s)");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SpansSearchRequest {
+    /// Datadog query syntax (default: "*")
+    pub query: String,
+
+    /// Start time (default: "now-1h")
+    pub from: String,
+
+    /// End time (default: "now")
+    pub to: String,
+
+    /// Service to scope the query to (default: none)
+    pub service: Option<String>,
+
+    /// Environment to scope the query to (default: none)
+    pub env: Option<String>,
+
+    /// Whether to return every span or one root span per trace (default: spans)
+    pub search_type: SpansSearchType,
+
+    /// Result order (default: oldest first)
+    pub sort: SortOrder,
+
+    /// Records requested per page (default: 1000)
+    pub page_limit: i32,
+}
+
+impl SpansSearchRequest {
+    /// Starts building a request, with every option at its default.
+    pub fn builder() -> SpansSearchRequestBuilder {
+        SpansSearchRequestBuilder {
+            request: Self {
+                query: "*".to_string(),
+                from: "now-1h".to_string(),
+                to: "now".to_string(),
+                service: None,
+                env: None,
+                search_type: SpansSearchType::default(),
+                sort: SortOrder::default(),
+                page_limit: SEARCH_PAGE_SIZE,
+            },
+        }
+    }
+
+    /// The query as sent to Datadog, scoped to `service` and `env`.
+    pub fn scoped_query(&self) -> String {
+        let filters: Vec<(&str, &str)> = [("service", &self.service), ("env", &self.env)]
+            .into_iter()
+            .filter_map(|(facet, value)| Some((facet, value.as_deref()?)))
+            .collect();
+        query::scope(&self.query, &filters)
+    }
+}
+
+/// Builder for [`SpansSearchRequest`].
+#[derive(Debug, Clone)]
+pub struct SpansSearchRequestBuilder {
+    request: SpansSearchRequest,
+}
+
+impl SpansSearchRequestBuilder {
+    /// Sets the query.
+    pub fn query(mut self, query: impl Into<String>) -> Self {
+        self.request.query = query.into();
+        self
+    }
+
+    /// Sets the start time.
+    pub fn from(mut self, from: impl Into<String>) -> Self {
+        self.request.from = from.into();
+        self
+    }
+
+    /// Sets the end time.
+    pub fn to(mut self, to: impl Into<String>) -> Self {
+        self.request.to = to.into();
+        self
+    }
+
+    /// Scopes the query to a service.
+    pub fn service(mut self, service: impl Into<String>) -> Self {
+        self.request.service = Some(service.into());
+        self
+    }
+
+    /// Scopes the query to an environment.
+    pub fn env(mut self, env: impl Into<String>) -> Self {
+        self.request.env = Some(env.into());
+        self
+    }
+
+    /// Sets whether to return every span or one root span per trace.
+    pub fn search_type(mut self, search_type: SpansSearchType) -> Self {
+        self.request.search_type = search_type;
+        self
+    }
+
+    /// Sets the result order.
+    pub fn sort(mut self, sort: SortOrder) -> Self {
+        self.request.sort = sort;
+        self
+    }
+
+    /// Sets the records requested per page.
+    pub fn page_limit(mut self, page_limit: i32) -> Self {
+        self.request.page_limit = page_limit;
+        self
+    }
+
+    /// Finishes the request.
+    pub fn build(self) -> SpansSearchRequest {
+        self.request
+    }
+}
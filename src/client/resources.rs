@@ -0,0 +1,195 @@
+//! Fetching configuration resources by ID.
+//!
+//! Reads the live definition of monitors, SLOs, dashboards, and log indexes
+//! as JSON, in the shape the Datadog API returns them, for comparison with
+//! definitions kept in version control.
+
+use datadog_api_client::datadog::{self, Configuration};
+use datadog_api_client::datadogV1::api_dashboards::DashboardsAPI;
+use datadog_api_client::datadogV1::api_logs_indexes::LogsIndexesAPI;
+use datadog_api_client::datadogV1::api_monitors::{GetMonitorOptionalParams, MonitorsAPI};
+use datadog_api_client::datadogV1::api_service_level_objectives::{
+    GetSLOOptionalParams, ServiceLevelObjectivesAPI,
+};
+use reqwest::StatusCode;
+use serde_json::Value;
+use std::fmt;
+
+use super::convert_datadog_error;
+use super::http::build_client;
+use crate::error::AppError;
+use crate::exclusions::Exclusion;
+
+/// Kind of configuration resource.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ResourceKind {
+    /// Monitor, identified by its numeric ID
+    Monitor,
+    /// Service level objective, identified by its ID
+    Slo,
+    /// Dashboard, identified by its ID (e.g., "abc-def-ghi")
+    Dashboard,
+    /// Log index, identified by its name
+    LogIndex,
+}
+
+impl ResourceKind {
+    /// All kinds, in the order they are reported.
+    pub const ALL: [ResourceKind; 4] = [
+        ResourceKind::Monitor,
+        ResourceKind::Slo,
+        ResourceKind::Dashboard,
+        ResourceKind::LogIndex,
+    ];
+
+    /// Name used in records (e.g., "monitor").
+    pub fn name(self) -> &'static str {
+        match self {
+            ResourceKind::Monitor => "monitor",
+            ResourceKind::Slo => "slo",
+            ResourceKind::Dashboard => "dashboard",
+            ResourceKind::LogIndex => "log_index",
+        }
+    }
+
+    /// Directory holding this kind's definitions in a bundle (e.g., "monitors").
+    pub fn directory(self) -> &'static str {
+        match self {
+            ResourceKind::Monitor => "monitors",
+            ResourceKind::Slo => "slos",
+            ResourceKind::Dashboard => "dashboards",
+            ResourceKind::LogIndex => "indexes",
+        }
+    }
+}
+
+impl fmt::Display for ResourceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Client for reading configuration resources.
+///
+/// Wraps the Datadog SDK's Monitors, SLO, Dashboards, and Logs Indexes APIs.
+pub struct ResourcesClient {
+    monitors: MonitorsAPI,
+    slos: ServiceLevelObjectivesAPI,
+    dashboards: DashboardsAPI,
+    indexes: LogsIndexesAPI,
+}
+
+impl ResourcesClient {
+    /// Creates a new ResourcesClient with the given configuration.
+    pub fn new(config: Configuration) -> Self {
+        let client = build_client(&config);
+        Self {
+            monitors: MonitorsAPI::with_client_and_config(config.clone(), client.clone()),
+            slos: ServiceLevelObjectivesAPI::with_client_and_config(config.clone(), client.clone()),
+            dashboards: DashboardsAPI::with_client_and_config(config.clone(), client.clone()),
+            indexes: LogsIndexesAPI::with_client_and_config(config, client),
+        }
+    }
+
+    /// Fetches a resource's live definition as JSON.
+    ///
+    /// Returns `None` if the resource doesn't exist (404).
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Config` for a monitor ID that isn't numeric, and
+    /// the converted API error for other failures.
+    pub async fn get(&self, kind: ResourceKind, id: &str) -> Result<Option<Value>, AppError> {
+        let result = match kind {
+            ResourceKind::Monitor => {
+                let monitor_id = id.parse::<i64>().map_err(|_| {
+                    AppError::Config(format!("Monitor ID '{}' is not a number", id))
+                })?;
+                self.monitors
+                    .get_monitor(monitor_id, GetMonitorOptionalParams::default())
+                    .await
+                    .map(serde_json::to_value)
+                    .map_err(|e| resource_error(e, kind))
+            }
+            ResourceKind::Slo => self
+                .slos
+                .get_slo(id.to_string(), GetSLOOptionalParams::default())
+                .await
+                .map(|r| serde_json::to_value(r.data))
+                .map_err(|e| resource_error(e, kind)),
+            ResourceKind::Dashboard => self
+                .dashboards
+                .get_dashboard(id.to_string())
+                .await
+                .map(serde_json::to_value)
+                .map_err(|e| resource_error(e, kind)),
+            ResourceKind::LogIndex => self
+                .indexes
+                .get_logs_index(id.to_string())
+                .await
+                .map(serde_json::to_value)
+                .map_err(|e| resource_error(e, kind)),
+        };
+
+        match result {
+            Ok(value) => Ok(Some(value?).filter(|v| !v.is_null())),
+            Err(None) => Ok(None),
+            Err(Some(e)) => Err(e),
+        }
+    }
+
+    /// Lists the names of the org's log indexes, in the order Datadog returns them.
+    ///
+    /// # Errors
+    ///
+    /// Returns the converted API error if the request fails.
+    pub async fn log_index_names(&self) -> Result<Vec<String>, AppError> {
+        let response = self
+            .indexes
+            .list_log_indexes()
+            .await
+            .map_err(|e| convert_datadog_error(e, "indexes"))?;
+        Ok(response
+            .indexes
+            .unwrap_or_default()
+            .into_iter()
+            .map(|index| index.name)
+            .collect())
+    }
+
+    /// Lists the exclusion filters of the org's log indexes, index by index
+    /// in the order Datadog returns them.
+    ///
+    /// # Errors
+    ///
+    /// Returns the converted API error if the request fails.
+    pub async fn log_exclusions(&self) -> Result<Vec<Exclusion>, AppError> {
+        let response = self
+            .indexes
+            .list_log_indexes()
+            .await
+            .map_err(|e| convert_datadog_error(e, "indexes"))?;
+        let mut exclusions = Vec::new();
+        for index in response.indexes.unwrap_or_default() {
+            for filter in index.exclusion_filters.unwrap_or_default() {
+                exclusions.push(Exclusion {
+                    index: index.name.clone(),
+                    index_query: index.filter.query.clone(),
+                    name: filter.name,
+                    enabled: filter.is_enabled.unwrap_or(false),
+                    query: filter.filter.as_ref().and_then(|f| f.query.clone()),
+                    sample_rate: filter.filter.map_or(0.0, |f| f.sample_rate),
+                });
+            }
+        }
+        Ok(exclusions)
+    }
+}
+
+/// Converts an error fetching a resource, or `None` if it doesn't exist (404).
+fn resource_error<T>(e: datadog::Error<T>, kind: ResourceKind) -> Option<AppError> {
+    match &e {
+        datadog::Error::ResponseError(response) if response.status == StatusCode::NOT_FOUND => None,
+        _ => Some(convert_datadog_error(e, kind.directory())),
+    }
+}
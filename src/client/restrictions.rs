@@ -0,0 +1,141 @@
+//! Datadog logs restriction queries API client wrapper.
+//!
+//! Restriction queries are RBAC filters on log data: a user whose roles have
+//! one only sees the logs matching it, whatever they search for. This client
+//! looks up those of the user owning the application key, to tell a search
+//! that found nothing from data that is hidden.
+
+use datadog_api_client::datadog::Configuration;
+use datadog_api_client::datadogV2::api_key_management::{
+    KeyManagementAPI, ListCurrentUserApplicationKeysOptionalParams,
+};
+use datadog_api_client::datadogV2::api_logs_restriction_queries::LogsRestrictionQueriesAPI;
+use reqwest_middleware::ClientWithMiddleware;
+
+use super::builder::{BuildableClient, ClientBuilder};
+use super::convert_datadog_error;
+use super::http::build_client;
+use crate::error::AppError;
+
+/// Client for looking up the logs restriction queries of the current user.
+///
+/// Wraps the Datadog SDK's KeyManagementAPI and LogsRestrictionQueriesAPI.
+pub struct RestrictionsClient {
+    keys: KeyManagementAPI,
+    queries: LogsRestrictionQueriesAPI,
+}
+
+impl RestrictionsClient {
+    /// Creates a new RestrictionsClient with the given configuration.
+    pub fn new(config: Configuration) -> Self {
+        let http = build_client(&config);
+        Self::from_parts(config, http)
+    }
+
+    /// Starts building a client from explicit credentials and site.
+    pub fn builder() -> ClientBuilder<Self> {
+        ClientBuilder::new()
+    }
+
+    /// The ID of the user owning the application key, or `None` if the
+    /// key's owner can't be told.
+    pub async fn current_user_id(&self) -> Result<Option<String>, AppError> {
+        let params = ListCurrentUserApplicationKeysOptionalParams::default().page_size(1);
+        let response = self
+            .keys
+            .list_current_user_application_keys(params)
+            .await
+            .map_err(|e| convert_datadog_error(e, "application keys"))?;
+        Ok(response
+            .data
+            .unwrap_or_default()
+            .into_iter()
+            .find_map(|key| key.relationships?.owned_by.map(|owner| owner.data.id)))
+    }
+
+    /// The restriction queries limiting the logs the application key can
+    /// read, empty if it reads them all.
+    pub async fn restriction_queries(&self) -> Result<Vec<String>, AppError> {
+        let Some(user_id) = self.current_user_id().await? else {
+            return Ok(Vec::new());
+        };
+        let response = self
+            .queries
+            .list_user_restriction_queries(user_id)
+            .await
+            .map_err(|e| convert_datadog_error(e, "restriction queries"))?;
+        Ok(response
+            .data
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|query| query.attributes?.restriction_query)
+            .collect())
+    }
+}
+
+impl BuildableClient for RestrictionsClient {
+    // Listing a user's restriction queries is an unstable operation in the
+    // SDK, so it is enabled here
+    fn from_parts(mut config: Configuration, http: ClientWithMiddleware) -> Self {
+        config.set_unstable_operation_enabled("v2.list_user_restriction_queries", true);
+        Self {
+            keys: KeyManagementAPI::with_client_and_config(config.clone(), http.clone()),
+            queries: LogsRestrictionQueriesAPI::with_client_and_config(config, http),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::MockTransport;
+    use serde_json::json;
+
+    fn client(transport: MockTransport) -> RestrictionsClient {
+        RestrictionsClient::builder()
+            .api_key("test")
+            .app_key("test")
+            .transport(transport)
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_restriction_queries() {
+        let transport = MockTransport::new()
+            .respond(
+                "GET",
+                "/api/v2/current_user/application_keys",
+                200,
+                json!({"data": [{
+                    "id": "key-1",
+                    "type": "application_keys",
+                    "relationships": {"owned_by": {"data": {"id": "user-1", "type": "users"}}},
+                }]}),
+            )
+            .respond(
+                "GET",
+                "/api/v2/logs/config/restriction_queries/user/user-1",
+                200,
+                json!({"data": [{
+                    "id": "79a0e60a",
+                    "type": "logs_restriction_queries",
+                    "attributes": {"restriction_query": "env:sandbox"},
+                }]}),
+            );
+        let queries = client(transport).restriction_queries().await.unwrap();
+        assert_eq!(queries, vec!["env:sandbox"]);
+    }
+
+    #[tokio::test]
+    async fn test_restriction_queries_without_owner() {
+        let transport = MockTransport::new().respond(
+            "GET",
+            "/api/v2/current_user/application_keys",
+            200,
+            json!({"data": []}),
+        );
+        let queries = client(transport).restriction_queries().await.unwrap();
+        assert!(queries.is_empty());
+    }
+}
@@ -0,0 +1,104 @@
+//! Datadog Service Catalog API client wrapper.
+//!
+//! Provides service ownership lookups used to route results to teams.
+
+use datadog_api_client::datadog::Configuration;
+use datadog_api_client::datadogV2::api_service_definition::{
+    ListServiceDefinitionsOptionalParams, ServiceDefinitionAPI,
+};
+use futures_util::StreamExt;
+
+use super::convert_datadog_error;
+use super::http::build_client;
+use crate::error::AppError;
+use crate::ownership::OwnershipMap;
+
+/// Client for reading the Datadog Service Catalog.
+pub struct ServiceCatalogClient {
+    api: ServiceDefinitionAPI,
+}
+
+impl ServiceCatalogClient {
+    /// Creates a new ServiceCatalogClient with the given configuration.
+    pub fn new(config: Configuration) -> Self {
+        Self {
+            api: ServiceDefinitionAPI::with_client_and_config(
+                config.clone(),
+                build_client(&config),
+            ),
+        }
+    }
+
+    /// Builds a service-to-team mapping from all service definitions.
+    ///
+    /// Services without a `team` are skipped.
+    pub async fn ownership(&self) -> Result<OwnershipMap, AppError> {
+        let mut stream = std::pin::pin!(self.api.list_service_definitions_with_pagination(
+            ListServiceDefinitionsOptionalParams::default().page_size(100)
+        ));
+        let mut pairs = Vec::new();
+
+        while let Some(result) = stream.next().await {
+            let definition = result.map_err(|e| convert_datadog_error(e, "the Service Catalog"))?;
+            let Some(schema) = definition.attributes.and_then(|a| a.schema) else {
+                continue;
+            };
+            let schema = serde_json::to_value(schema)?;
+            if let Some(pair) = service_team(&schema) {
+                pairs.push(pair);
+            }
+        }
+
+        Ok(OwnershipMap::from_pairs(pairs))
+    }
+}
+
+/// Extracts `(service, team)` from a service definition of any schema version.
+///
+/// Schema v1 nests these under `info`/`org`; v2 and later keep them at the top level.
+fn service_team(schema: &serde_json::Value) -> Option<(String, String)> {
+    let service = schema
+        .pointer("/dd-service")
+        .or_else(|| schema.pointer("/info/dd-service"))?
+        .as_str()?;
+    let team = schema
+        .pointer("/team")
+        .or_else(|| schema.pointer("/org/team"))?
+        .as_str()?;
+    Some((service.to_string(), team.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_service_team_v2() {
+        let schema =
+            json!({"schema-version": "v2.2", "dd-service": "checkout", "team": "payments"});
+        assert_eq!(
+            service_team(&schema),
+            Some(("checkout".to_string(), "payments".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_service_team_v1() {
+        let schema = json!({
+            "schema-version": "v1",
+            "info": {"dd-service": "gateway"},
+            "org": {"team": "platform"}
+        });
+        assert_eq!(
+            service_team(&schema),
+            Some(("gateway".to_string(), "platform".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_service_team_missing_team() {
+        let schema = json!({"schema-version": "v2", "dd-service": "orphan"});
+        assert_eq!(service_team(&schema), None);
+    }
+}
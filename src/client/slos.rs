@@ -0,0 +1,113 @@
+//! Datadog Service Level Objectives API client wrapper.
+//!
+//! Provides a simplified interface for listing SLOs with automatic pagination
+//! and reading their SLI over a time window.
+
+use datadog_api_client::datadog::Configuration;
+use datadog_api_client::datadogV1::api_service_level_objectives::{
+    GetSLOHistoryOptionalParams, ListSLOsOptionalParams, ServiceLevelObjectivesAPI,
+};
+use futures_util::{Stream, StreamExt};
+
+use super::convert_datadog_error;
+use super::http::build_client;
+use crate::error::AppError;
+
+/// Client for querying Datadog service level objectives.
+///
+/// Wraps the Datadog SDK's ServiceLevelObjectivesAPI with automatic pagination support.
+pub struct SlosClient {
+    api: ServiceLevelObjectivesAPI,
+}
+
+impl SlosClient {
+    /// Creates a new SlosClient with the given configuration.
+    pub fn new(config: Configuration) -> Self {
+        Self {
+            api: ServiceLevelObjectivesAPI::with_client_and_config(
+                config.clone(),
+                build_client(&config),
+            ),
+        }
+    }
+
+    /// Lists SLOs carrying the given tag.
+    ///
+    /// Returns an async stream of SLOs. The stream handles pagination
+    /// automatically. SLOs without an ID or target are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `tag` - SLO tag such as "team:payments"
+    pub fn list(&self, tag: &str) -> impl Stream<Item = Result<SloSummary, AppError>> + '_ {
+        let params = ListSLOsOptionalParams::default()
+            .tags_query(tag.to_string())
+            .limit(100);
+
+        self.api
+            .list_slos_with_pagination(params)
+            .filter_map(|result| async move {
+                match result {
+                    Ok(slo) => {
+                        let target = slo
+                            .target_threshold
+                            .or_else(|| slo.thresholds.first().map(|t| t.target));
+                        slo.id.zip(target).map(|(id, target)| {
+                            Ok(SloSummary {
+                                id,
+                                name: slo.name,
+                                target,
+                            })
+                        })
+                    }
+                    Err(e) => Some(Err(convert_datadog_error(e, "SLOs"))),
+                }
+            })
+    }
+
+    /// Returns an SLO's overall SLI (percent good) between two Unix timestamps.
+    ///
+    /// Returns `None` when Datadog has no data for the window.
+    ///
+    /// # Arguments
+    ///
+    /// * `slo_id` - SLO ID
+    /// * `from_ts` - Start time in Unix seconds
+    /// * `to_ts` - End time in Unix seconds
+    pub async fn sli(
+        &self,
+        slo_id: &str,
+        from_ts: i64,
+        to_ts: i64,
+    ) -> Result<Option<f64>, AppError> {
+        let response = self
+            .api
+            .get_slo_history(
+                slo_id.to_string(),
+                from_ts,
+                to_ts,
+                GetSLOHistoryOptionalParams::default(),
+            )
+            .await
+            .map_err(|e| convert_datadog_error(e, "SLOs"))?;
+
+        Ok(response
+            .data
+            .and_then(|d| d.overall)
+            .and_then(|o| o.sli_value)
+            .flatten())
+    }
+}
+
+/// An SLO's identity and target, as needed to report on it.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SloSummary {
+    /// SLO ID
+    pub id: String,
+
+    /// SLO name
+    pub name: String,
+
+    /// Target, in percent good (e.g., 99.9)
+    pub target: f64,
+}
@@ -1,67 +1,570 @@
 //! Datadog Spans (APM) API client wrapper.
 //!
 //! Provides a simplified interface for searching APM spans with automatic pagination.
+//! Search pages are parsed a record at a time as they arrive (see [`pages`](super::pages)).
 
+use chrono::SecondsFormat;
 use datadog_api_client::datadog::{self, Configuration};
 use datadog_api_client::datadogV2::api_spans::SpansAPI;
 use datadog_api_client::datadogV2::model::{
-    Span, SpansListRequest, SpansListRequestAttributes, SpansListRequestData, SpansListRequestPage,
-    SpansListRequestType, SpansQueryFilter, SpansSort,
+    Span, SpansAggregateData, SpansAggregateRequest, SpansAggregateRequestAttributes,
+    SpansAggregateRequestType, SpansAggregationFunction, SpansCompute, SpansComputeType,
+    SpansGroupBy, SpansListRequest, SpansListRequestAttributes, SpansListRequestData,
+    SpansListRequestPage, SpansListRequestType, SpansQueryFilter, SpansSort,
 };
-use futures_util::Stream;
+use futures_util::{StreamExt, TryStreamExt};
+use reqwest_middleware::ClientWithMiddleware;
+
+use super::aggregate::{
+    GroupCount, Grouping, LatencyPoint, Percentile, VolumePoint, group_counts, latency_points,
+    total, volume_points,
+};
+use super::builder::{BuildableClient, ClientBuilder};
+use super::convert_datadog_error;
+use super::http::build_client;
+use super::pages::{capture_request, search_pages};
+use super::request::{SortOrder, SpansSearchRequest, SpansSearchType};
+use super::stream::SearchStream;
+use crate::error::AppError;
+use crate::records::SpanEvent;
 
 /// Client for querying Datadog APM spans.
 ///
 /// Wraps the Datadog SDK's SpansAPI with automatic pagination support.
 pub struct SpansClient {
     api: SpansAPI,
+    config: Configuration,
+    http: ClientWithMiddleware,
 }
 
 impl SpansClient {
     /// Creates a new SpansClient with the given configuration.
     pub fn new(config: Configuration) -> Self {
-        Self {
-            api: SpansAPI::with_config(config),
+        let http = build_client(&config);
+        Self::from_parts(config, http)
+    }
+
+    /// Starts building a client from explicit credentials and site.
+    pub fn builder() -> ClientBuilder<Self> {
+        ClientBuilder::new()
+    }
+
+    /// Collects up to `limit` spans matching a request (0 for all of them), as
+    /// typed [`SpanEvent`]s.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Auth` for rejected credentials, `AppError::InvalidQuery`
+    /// for a query or time range Datadog rejects, and `AppError::Api` otherwise.
+    pub async fn fetch(
+        &self,
+        request: &SpansSearchRequest,
+        limit: usize,
+    ) -> Result<Vec<SpanEvent>, AppError> {
+        self.search(request)
+            .take_records(limit)
+            .map_records(SpanEvent::from)
+            .try_collect()
+            .await
+    }
+
+    /// Searches APM spans matching a request.
+    ///
+    /// Returns a [`SearchStream`] of span records. The stream handles
+    /// pagination automatically, fetching the request's page limit of records
+    /// per API request.
+    ///
+    /// With [`SpansSearchType::Traces`], spans that aren't the root of their
+    /// trace are dropped as they arrive, so pages are still fetched in full.
+    pub fn search(&self, request: &SpansSearchRequest) -> SearchStream<'static, Span> {
+        let sort = match request.sort {
+            SortOrder::Ascending => SpansSort::TIMESTAMP_ASCENDING,
+            SortOrder::Descending => SpansSort::TIMESTAMP_DESCENDING,
+        };
+        let body = SpansListRequest::new().data(
+            SpansListRequestData::new()
+                .attributes(
+                    SpansListRequestAttributes::new()
+                        .filter(
+                            SpansQueryFilter::new()
+                                .query(request.scoped_query())
+                                .from(request.from.clone())
+                                .to(request.to.clone()),
+                        )
+                        .page(SpansListRequestPage::new().limit(request.page_limit))
+                        .sort(sort),
+                )
+                .type_(SpansListRequestType::SEARCH_REQUEST),
+        );
+
+        let page_size = usize::try_from(request.page_limit).unwrap_or(0);
+        let config = self.config.clone();
+        let spans = search_pages(self.http.clone(), page_size, move |cursor| {
+            let mut body = body.clone();
+            if let Some(page) = body
+                .data
+                .as_mut()
+                .and_then(|data| data.attributes.as_mut())
+                .and_then(|attributes| attributes.page.as_mut())
+            {
+                page.cursor = cursor;
+            }
+            let config = config.clone();
+            capture_request(move |http| async move {
+                SpansAPI::with_client_and_config(config, http)
+                    .list_spans(body)
+                    .await
+            })
+        })
+        .map(|result| result.map_err(spans_error));
+        let spans = SearchStream::numbered(spans, page_size);
+        match request.search_type {
+            SpansSearchType::Spans => spans,
+            SpansSearchType::Traces => spans.filter_records(|span| is_root(&span).then_some(span)),
         }
     }
 
-    /// Searches APM spans matching the given query.
+    /// Fetches the spans of one trace, flattened for tree building.
     ///
-    /// Returns an async stream of span records. The stream handles pagination
-    /// automatically, fetching up to 1000 records per API request.
+    /// Returns an async stream of spans in start order. Spans missing an ID or
+    /// timestamps are skipped.
     ///
     /// # Arguments
     ///
-    /// * `query` - Datadog query syntax (e.g., "service:web env:prod @duration:>1s")
+    /// * `trace_id` - Trace ID as shown in the Datadog UI
     /// * `from` - Start time: relative ("now-1h"), ISO8601 ("2024-01-15T10:00:00Z"), or Unix ms ("1705315200000")
     /// * `to` - End time: relative ("now"), ISO8601 ("2024-01-15T10:00:00Z"), or Unix ms ("1705315200000")
-    pub fn search(
+    pub fn trace(&self, trace_id: &str, from: &str, to: &str) -> SearchStream<'_, TraceSpan> {
+        let request = SpansSearchRequest::builder()
+            .query(format!("trace_id:{}", trace_id))
+            .from(from)
+            .to(to)
+            .build();
+        self.trace_spans(&request)
+    }
+
+    /// Searches spans like [`search`](Self::search), flattened into
+    /// [`TraceSpan`]s. Spans missing an ID or timestamps are skipped.
+    pub fn trace_spans(&self, request: &SpansSearchRequest) -> SearchStream<'_, TraceSpan> {
+        let spans = self.search(request).filter_map(|result| async move {
+            match result {
+                Ok(span) => TraceSpan::from_span(span).map(Ok),
+                Err(e) => Some(Err(e)),
+            }
+        });
+        SearchStream::new(spans)
+    }
+
+    /// Counts spans matching the query per time bucket and facet value.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - Datadog query syntax (e.g., "service:web status:error")
+    /// * `from` - Start time: relative ("now-1h"), ISO8601 ("2024-01-15T10:00:00Z"), or Unix ms ("1705315200000")
+    /// * `to` - End time: relative ("now"), ISO8601 ("2024-01-15T10:00:00Z"), or Unix ms ("1705315200000")
+    /// * `grouping` - Facet, time bucket size, and number of facet values to keep
+    pub async fn volume(
         &self,
         query: &str,
         from: &str,
         to: &str,
-    ) -> impl Stream<
-        Item = Result<
-            Span,
-            datadog::Error<datadog_api_client::datadogV2::api_spans::ListSpansError>,
-        >,
-    > + '_ {
-        let body = SpansListRequest::new().data(
-            SpansListRequestData::new()
+        grouping: &Grouping,
+    ) -> Result<Vec<VolumePoint>, AppError> {
+        let body = SpansAggregateRequest::new().data(
+            SpansAggregateData::new()
                 .attributes(
-                    SpansListRequestAttributes::new()
+                    SpansAggregateRequestAttributes::new()
                         .filter(
                             SpansQueryFilter::new()
                                 .query(query.to_string())
                                 .from(from.to_string())
                                 .to(to.to_string()),
                         )
-                        .page(SpansListRequestPage::new().limit(1000))
-                        .sort(SpansSort::TIMESTAMP_ASCENDING),
+                        .compute(vec![
+                            SpansCompute::new(SpansAggregationFunction::COUNT)
+                                .type_(SpansComputeType::TIMESERIES)
+                                .interval(grouping.interval.clone()),
+                        ])
+                        .group_by(vec![
+                            SpansGroupBy::new(grouping.facet.clone()).limit(grouping.top),
+                        ]),
                 )
-                .type_(SpansListRequestType::SEARCH_REQUEST),
+                .type_(SpansAggregateRequestType::AGGREGATE_REQUEST),
         );
 
-        self.api.list_spans_with_pagination(body)
+        let response = self
+            .api
+            .aggregate_spans(body)
+            .await
+            .map_err(|e| convert_datadog_error(e, "spans"))?;
+        let buckets = serde_json::to_value(response.data.unwrap_or_default())?;
+
+        Ok(volume_points(
+            buckets.as_array().map(Vec::as_slice).unwrap_or_default(),
+            &grouping.facet,
+        ))
+    }
+
+    /// Computes latency percentiles of spans matching the query per time bucket.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - Datadog query syntax (e.g., "service:api resource_name:/checkout")
+    /// * `from` - Start time: relative ("now-1h"), ISO8601 ("2024-01-15T10:00:00Z"), or Unix ms ("1705315200000")
+    /// * `to` - End time: relative ("now"), ISO8601 ("2024-01-15T10:00:00Z"), or Unix ms ("1705315200000")
+    /// * `percentiles` - Percentiles to compute, each a field of the returned points
+    /// * `interval` - Time bucket size (e.g., "5m")
+    pub async fn latency(
+        &self,
+        query: &str,
+        from: &str,
+        to: &str,
+        percentiles: &[Percentile],
+        interval: &str,
+    ) -> Result<Vec<LatencyPoint>, AppError> {
+        let computes = percentiles
+            .iter()
+            .map(|p| {
+                SpansCompute::new(percentile_function(*p))
+                    .metric("@duration".to_string())
+                    .type_(SpansComputeType::TIMESERIES)
+                    .interval(interval.to_string())
+            })
+            .collect();
+        let body = SpansAggregateRequest::new().data(
+            SpansAggregateData::new()
+                .attributes(
+                    SpansAggregateRequestAttributes::new()
+                        .filter(
+                            SpansQueryFilter::new()
+                                .query(query.to_string())
+                                .from(from.to_string())
+                                .to(to.to_string()),
+                        )
+                        .compute(computes),
+                )
+                .type_(SpansAggregateRequestType::AGGREGATE_REQUEST),
+        );
+
+        let response = self
+            .api
+            .aggregate_spans(body)
+            .await
+            .map_err(|e| convert_datadog_error(e, "spans"))?;
+        let buckets = serde_json::to_value(response.data.unwrap_or_default())?;
+
+        Ok(latency_points(
+            buckets.as_array().map(Vec::as_slice).unwrap_or_default(),
+            percentiles,
+        ))
+    }
+
+    /// Counts spans matching the query.
+    pub async fn count(&self, query: &str, from: &str, to: &str) -> Result<Option<f64>, AppError> {
+        let body = SpansAggregateRequest::new().data(
+            SpansAggregateData::new()
+                .attributes(
+                    SpansAggregateRequestAttributes::new()
+                        .filter(
+                            SpansQueryFilter::new()
+                                .query(query.to_string())
+                                .from(from.to_string())
+                                .to(to.to_string()),
+                        )
+                        .compute(vec![SpansCompute::new(SpansAggregationFunction::COUNT)]),
+                )
+                .type_(SpansAggregateRequestType::AGGREGATE_REQUEST),
+        );
+
+        let response = self
+            .api
+            .aggregate_spans(body)
+            .await
+            .map_err(|e| convert_datadog_error(e, "spans"))?;
+        let buckets = serde_json::to_value(response.data.unwrap_or_default())?;
+
+        Ok(total(
+            buckets.as_array().map(Vec::as_slice).unwrap_or_default(),
+        ))
+    }
+
+    /// Counts spans matching the query per combination of facet values.
+    ///
+    /// Groups are nested in the order of `facets`, each given with the number
+    /// of its values to keep within its parent group.
+    pub async fn group_counts(
+        &self,
+        query: &str,
+        from: &str,
+        to: &str,
+        facets: &[(&str, i64)],
+    ) -> Result<Vec<GroupCount>, AppError> {
+        let body = SpansAggregateRequest::new().data(
+            SpansAggregateData::new()
+                .attributes(
+                    SpansAggregateRequestAttributes::new()
+                        .filter(
+                            SpansQueryFilter::new()
+                                .query(query.to_string())
+                                .from(from.to_string())
+                                .to(to.to_string()),
+                        )
+                        .compute(vec![SpansCompute::new(SpansAggregationFunction::COUNT)])
+                        .group_by(
+                            facets
+                                .iter()
+                                .map(|(facet, limit)| {
+                                    SpansGroupBy::new(facet.to_string()).limit(*limit)
+                                })
+                                .collect(),
+                        ),
+                )
+                .type_(SpansAggregateRequestType::AGGREGATE_REQUEST),
+        );
+
+        let response = self
+            .api
+            .aggregate_spans(body)
+            .await
+            .map_err(|e| convert_datadog_error(e, "spans"))?;
+        let buckets = serde_json::to_value(response.data.unwrap_or_default())?;
+
+        let names: Vec<&str> = facets.iter().map(|(facet, _)| *facet).collect();
+        Ok(group_counts(
+            buckets.as_array().map(Vec::as_slice).unwrap_or_default(),
+            &names,
+        ))
+    }
+}
+
+impl BuildableClient for SpansClient {
+    fn from_parts(config: Configuration, http: ClientWithMiddleware) -> Self {
+        Self {
+            api: SpansAPI::with_client_and_config(config.clone(), http.clone()),
+            config,
+            http,
+        }
+    }
+}
+
+/// Converts a spans search error, noting that spans need their own permissions.
+fn spans_error<T>(e: datadog::Error<T>) -> AppError {
+    match convert_datadog_error(e, "APM spans") {
+        AppError::Forbidden(msg) => AppError::Forbidden(format!(
+            "{} Note: APM spans require different permissions than logs. \
+            Ensure your API key has 'APM and Infrastructure' read permissions.",
+            msg
+        )),
+        e => e,
+    }
+}
+
+/// The aggregation computing `percentile`; the API has no p50 but the median.
+fn percentile_function(percentile: Percentile) -> SpansAggregationFunction {
+    match percentile.value() {
+        75 => SpansAggregationFunction::PERCENTILE_75,
+        90 => SpansAggregationFunction::PERCENTILE_90,
+        95 => SpansAggregationFunction::PERCENTILE_95,
+        98 => SpansAggregationFunction::PERCENTILE_98,
+        99 => SpansAggregationFunction::PERCENTILE_99,
+        _ => SpansAggregationFunction::MEDIAN,
+    }
+}
+
+/// Whether a span starts its trace (has no parent).
+fn is_root(span: &Span) -> bool {
+    let parent_id = span
+        .attributes
+        .as_ref()
+        .and_then(|a| a.parent_id.as_deref());
+    parent_id.is_none_or(|id| id.is_empty() || id == "0")
+}
+
+/// One span of a trace, as needed to rebuild the call tree.
+///
+/// This struct represents a flattened view of a span from the Datadog API.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct TraceSpan {
+    /// Span ID
+    pub span_id: String,
+
+    /// ID of the calling span; `None` or "0" for the root
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<String>,
+
+    /// Service that emitted the span
+    pub service: String,
+
+    /// Resource (e.g., "GET /api/users")
+    pub resource: String,
+
+    /// Operation (e.g., "http.request")
+    pub operation: String,
+
+    /// Start time (RFC3339, UTC, microsecond precision)
+    pub start: String,
+
+    /// Start time in Unix nanoseconds
+    #[serde(skip)]
+    pub start_ns: i64,
+
+    /// Duration in milliseconds
+    pub duration_ms: f64,
+
+    /// Whether the span has an error status
+    pub error: bool,
+}
+
+impl TraceSpan {
+    /// Flattens an API span; `None` if it lacks an ID or timestamps.
+    fn from_span(span: Span) -> Option<Self> {
+        let attributes = span.attributes?;
+        let start = attributes.start_timestamp?;
+        let end = attributes.end_timestamp?;
+        let start_ns = start.timestamp_nanos_opt()?;
+        let end_ns = end.timestamp_nanos_opt()?;
+        let error = attributes
+            .additional_properties
+            .get("status")
+            .and_then(|status| status.as_str())
+            == Some("error");
+        let operation = attributes
+            .additional_properties
+            .get("operation_name")
+            .or_else(|| attributes.custom.as_ref()?.get("operation_name"))
+            .and_then(|name| name.as_str())
+            .unwrap_or_default()
+            .to_string();
+
+        Some(Self {
+            span_id: attributes.span_id?,
+            parent_id: attributes.parent_id,
+            service: attributes.service.unwrap_or_default(),
+            resource: attributes.resource_name.unwrap_or_default(),
+            operation,
+            start: start.to_rfc3339_opts(SecondsFormat::Micros, true),
+            start_ns,
+            duration_ms: (end_ns - start_ns) as f64 / 1_000_000.0,
+            error,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::MockTransport;
+    use serde_json::json;
+
+    fn span(id: &str, parent_id: &str) -> serde_json::Value {
+        json!({"id": id, "type": "spans", "attributes": {"parent_id": parent_id}})
+    }
+
+    #[tokio::test]
+    async fn test_traces_search_keeps_root_spans() {
+        let path = "/api/v2/spans/events/search";
+        let transport = MockTransport::new().respond(
+            "POST",
+            path,
+            200,
+            json!({"data": [span("a", "0"), span("b", "a"), span("c", "0")]}),
+        );
+        let client = SpansClient::builder()
+            .api_key("test")
+            .app_key("test")
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+
+        let request = SpansSearchRequest::builder()
+            .query("status:error")
+            .env("prod")
+            .search_type(SpansSearchType::Traces)
+            .build();
+        let spans: Vec<_> = client.search(&request).try_collect().await.unwrap();
+        let ids: Vec<_> = spans.iter().filter_map(|s| s.id.as_deref()).collect();
+        assert_eq!(ids, ["a", "c"]);
+
+        let body = transport.requests()[0].body.clone().unwrap();
+        assert_eq!(
+            body["data"]["attributes"]["filter"]["query"],
+            "env:prod (status:error)"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_traces_search_counts_pages_fetched() {
+        let path = "/api/v2/spans/events/search";
+        let transport = MockTransport::new()
+            .respond(
+                "POST",
+                path,
+                200,
+                json!({
+                    "data": [span("a", "0"), span("b", "a")],
+                    "meta": {"page": {"after": "2"}},
+                }),
+            )
+            .respond(
+                "POST",
+                path,
+                200,
+                json!({"data": [span("c", "a"), span("d", "0")]}),
+            );
+        let client = SpansClient::builder()
+            .api_key("test")
+            .app_key("test")
+            .transport(transport)
+            .build()
+            .unwrap();
+
+        // One root span per page of two: pages can't be told from positions
+        let request = SpansSearchRequest::builder()
+            .page_limit(2)
+            .search_type(SpansSearchType::Traces)
+            .build();
+        let mut stream = client.search(&request);
+        assert_eq!(stream.page(), 0);
+        stream.next().await.unwrap().unwrap();
+        assert_eq!(stream.page(), 1);
+        let span = stream.next().await.unwrap().unwrap();
+        assert_eq!(span.id.as_deref(), Some("d"));
+        assert_eq!(stream.page(), 2);
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_latency_computes_one_series_per_percentile() {
+        let transport = MockTransport::new().respond(
+            "POST",
+            "/api/v2/spans/analytics/aggregate",
+            200,
+            json!({"data": [{"type": "bucket", "attributes": {"compute": {
+                "c0": [{"time": "2024-01-15T10:00:00.000Z", "value": 2_000_000}],
+                "c1": [{"time": "2024-01-15T10:00:00.000Z", "value": 9_000_000}]
+            }}}]}),
+        );
+        let client = SpansClient::builder()
+            .api_key("test")
+            .app_key("test")
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+
+        let percentiles = ["50".parse().unwrap(), "95".parse().unwrap()];
+        let points = client
+            .latency("service:api", "now-1h", "now", &percentiles, "5m")
+            .await
+            .unwrap();
+        assert_eq!(points[0].percentiles["p50_ms"], 2.0);
+        assert_eq!(points[0].percentiles["p95_ms"], 9.0);
+
+        let body = transport.requests()[0].body.clone().unwrap();
+        let compute = &body["data"]["attributes"]["compute"];
+        assert_eq!(
+            compute[0],
+            json!({"aggregation": "median", "metric": "@duration", "type": "timeseries", "interval": "5m"})
+        );
+        assert_eq!(compute[1]["aggregation"], "pc95");
     }
 }
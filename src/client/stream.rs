@@ -0,0 +1,462 @@
+//! Streams of search results.
+//!
+//! Every paginated search returns a [`SearchStream`], which fetches pages as
+//! it's polled and yields records or [`AppError`]s. Its combinators cover the
+//! controls searches share: stopping after a number of records or past a
+//! timestamp, observing records as they arrive, and ending early on a
+//! cancellation signal such as Ctrl-C. Each stops fetching as soon as it
+//! ends, so a capped search never requests a page it doesn't use, and
+//! [`SearchStream::cancel_with`] finishes the page being read before ending.
+//! [`SearchStream::page`] tells which page the latest record came from, for
+//! capping a search by pages fetched.
+//!
+//! [`SearchStream::prefetch`] trades that for speed: it fetches the next
+//! page while the current one is being consumed, so network and output
+//! overlap. Its buffer is bounded, so a consumer that falls behind, such as
+//! a slow pipe on stdout, pauses fetching instead of piling records up in
+//! memory.
+
+use chrono::{DateTime, Utc};
+use datadog_api_client::datadogV2::model::{Log, Span};
+use futures_util::{Stream, StreamExt};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+use crate::error::AppError;
+use crate::records::{LogEvent, SpanEvent};
+
+/// A record with a timestamp, for [`SearchStream::until_timestamp`].
+pub trait Timestamped {
+    /// When the record was emitted (a span's start), if known.
+    fn timestamp(&self) -> Option<DateTime<Utc>>;
+}
+
+impl Timestamped for Log {
+    fn timestamp(&self) -> Option<DateTime<Utc>> {
+        self.attributes.as_ref().and_then(|a| a.timestamp)
+    }
+}
+
+impl Timestamped for Span {
+    fn timestamp(&self) -> Option<DateTime<Utc>> {
+        self.attributes.as_ref().and_then(|a| a.start_timestamp)
+    }
+}
+
+impl Timestamped for LogEvent {
+    fn timestamp(&self) -> Option<DateTime<Utc>> {
+        self.timestamp
+    }
+}
+
+impl Timestamped for SpanEvent {
+    fn timestamp(&self) -> Option<DateTime<Utc>> {
+        self.start
+    }
+}
+
+/// The results of a paginated search.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ddog::{LogsClient, LogsSearchRequest};
+/// use futures_util::TryStreamExt;
+///
+/// # async fn example() -> Result<(), ddog::AppError> {
+/// let client = LogsClient::builder().build()?;
+/// let request = LogsSearchRequest::builder().query("status:error").build();
+/// let mut seen = 0;
+/// let logs: Vec<_> = client
+///     .search(&request)
+///     .take_records(500)
+///     .with_progress(|_| seen += 1)
+///     .cancel_on(async {
+///         let _ = tokio::signal::ctrl_c().await;
+///     })
+///     .try_collect()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct SearchStream<'a, T> {
+    inner: Pin<Box<dyn Stream<Item = Numbered<T>> + Send + 'a>>,
+    page_size: usize,
+    page: u64,
+}
+
+/// A result with the number of the page it came from.
+type Numbered<T> = (u64, Result<T, AppError>);
+
+impl<'a, T: Send + 'a> SearchStream<'a, T> {
+    /// Wraps a stream of results.
+    pub fn new(stream: impl Stream<Item = Result<T, AppError>> + Send + 'a) -> Self {
+        Self::paged(stream, 0)
+    }
+
+    /// Wraps a stream of results fetched `page_size` at a time, so
+    /// [`cancel_with`](Self::cancel_with) can finish the current page.
+    ///
+    /// Every page but the last is taken to be full, so each result's page is
+    /// told by its position.
+    pub fn paged(
+        stream: impl Stream<Item = Result<T, AppError>> + Send + 'a,
+        page_size: usize,
+    ) -> Self {
+        let mut yielded = 0;
+        let numbered = stream.map(move |result| {
+            let page = match page_size {
+                0 => 1,
+                page_size => yielded / page_size as u64 + 1,
+            };
+            yielded += 1;
+            (page, result)
+        });
+        Self::wrap(numbered, page_size)
+    }
+
+    /// Wraps a stream of results, each with the number of the page it came
+    /// from, counting from 1.
+    pub(crate) fn numbered(
+        stream: impl Stream<Item = Result<(u64, T), AppError>> + Send + 'a,
+        page_size: usize,
+    ) -> Self {
+        // An error belongs to the page after the last record's
+        let mut last = 0;
+        let numbered = stream.map(move |result| match result {
+            Ok((page, record)) => {
+                last = page;
+                (page, Ok(record))
+            }
+            Err(e) => (last + 1, Err(e)),
+        });
+        Self::wrap(numbered, page_size)
+    }
+
+    fn wrap(stream: impl Stream<Item = Numbered<T>> + Send + 'a, page_size: usize) -> Self {
+        Self {
+            inner: Box::pin(stream),
+            page_size,
+            page: 0,
+        }
+    }
+
+    /// Number of the page the latest result yielded came from, counting
+    /// from 1 (0 before any).
+    ///
+    /// Pages are counted as the API returned them, so a page cut short, or
+    /// with records filtered out, still counts as one.
+    pub fn page(&self) -> u64 {
+        self.page
+    }
+
+    /// Ends after `n` results (0 for no limit).
+    pub fn take_records(self, n: usize) -> Self {
+        match n {
+            0 => self,
+            n => Self::wrap(self.inner.take(n), self.page_size),
+        }
+    }
+
+    /// Ends at the first record after `timestamp`, for searches sorted
+    /// oldest first. Records without a timestamp are kept.
+    pub fn until_timestamp(self, timestamp: DateTime<Utc>) -> Self
+    where
+        T: Timestamped,
+    {
+        let records = self.inner.take_while(move |(_, result)| {
+            let before = match result {
+                Ok(record) => record.timestamp().is_none_or(|t| t <= timestamp),
+                Err(_) => true,
+            };
+            std::future::ready(before)
+        });
+        Self::wrap(records, self.page_size)
+    }
+
+    /// Calls `progress` with each record as it's yielded.
+    pub fn with_progress(self, mut progress: impl FnMut(&T) + Send + 'a) -> Self {
+        let records = self.inner.inspect(move |(_, result)| {
+            if let Ok(record) = result {
+                progress(record);
+            }
+        });
+        Self::wrap(records, self.page_size)
+    }
+
+    /// Ends, without an error, once `signal` completes.
+    pub fn cancel_on(self, signal: impl Future<Output = ()> + Send + 'a) -> Self {
+        Self::wrap(self.inner.take_until(signal), self.page_size)
+    }
+
+    /// Ends, without an error, once `token` is cancelled, after yielding the
+    /// rest of the page already fetched. A page request in flight is dropped,
+    /// and no further page is requested. Without a known page size, ends as
+    /// soon as the token is cancelled.
+    pub fn cancel_with(self, token: CancellationToken) -> Self {
+        let page_size = self.page_size;
+        let records = futures_util::stream::unfold(
+            (self.inner, token, 0),
+            move |(mut inner, token, yielded)| async move {
+                // Every page but the last is full, so the page ends at a
+                // multiple of the page size
+                let next = if page_size > 0 && yielded % page_size != 0 {
+                    inner.next().await
+                } else {
+                    tokio::select! {
+                        biased;
+                        _ = token.cancelled() => None,
+                        next = inner.next() => next,
+                    }
+                };
+                next.map(|result| (result, (inner, token, yielded + 1)))
+            },
+        );
+        Self::wrap(records, page_size)
+    }
+
+    /// Converts each record.
+    pub fn map_records<U: Send + 'a>(
+        self,
+        mut f: impl FnMut(T) -> U + Send + 'a,
+    ) -> SearchStream<'a, U> {
+        let records = self
+            .inner
+            .map(move |(page, result)| (page, result.map(&mut f)));
+        SearchStream::wrap(records, self.page_size)
+    }
+
+    /// Keeps the results `f` returns `Some` for, converted, each still
+    /// counted on the page it came from.
+    pub fn filter_records<U: Send + 'a>(
+        self,
+        mut f: impl FnMut(T) -> Option<U> + Send + 'a,
+    ) -> SearchStream<'a, U> {
+        let records = self.inner.filter_map(move |(page, result)| {
+            let kept = match result {
+                Ok(record) => f(record).map(Ok),
+                Err(e) => Some(Err(e)),
+            };
+            std::future::ready(kept.map(|result| (page, result)))
+        });
+        // Pages no longer line up with the records yielded
+        SearchStream::wrap(records, 0)
+    }
+}
+
+impl<T: Send + 'static> SearchStream<'static, T> {
+    /// Fetches results ahead of the consumer on a task of their own, up to
+    /// `buffer` of them (at least one), so the next page is requested and
+    /// parsed while the current one is written out.
+    ///
+    /// Once `buffer` results wait unread, the task stops reading the response
+    /// until the consumer catches up, and the API's connection backs up with
+    /// it, so memory stays bounded however slow the consumer is. A buffer of
+    /// one page keeps a page in flight.
+    ///
+    /// Dropping the stream stops the task, but a page it has already
+    /// requested is not put to use: prefetch only searches expected to read
+    /// past their first page.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside a Tokio runtime.
+    pub fn prefetch(self, buffer: usize) -> Self {
+        let page_size = self.page_size;
+        let (records, received) = tokio::sync::mpsc::channel(buffer.max(1));
+        let mut inner = self.inner;
+        let fetcher = tokio::spawn(async move {
+            while let Some(numbered) = inner.next().await {
+                let failed = numbered.1.is_err();
+                if records.send(numbered).await.is_err() || failed {
+                    break;
+                }
+            }
+        });
+        let records = futures_util::stream::unfold(
+            (received, AbortOnDrop(fetcher)),
+            |(mut received, fetcher)| async move {
+                let numbered = received.recv().await?;
+                Some((numbered, (received, fetcher)))
+            },
+        );
+        Self::wrap(records, page_size)
+    }
+}
+
+/// Stops a task once the stream reading its results is dropped.
+struct AbortOnDrop(JoinHandle<()>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+impl<T> Stream for SearchStream<'_, T> {
+    type Item = Result<T, AppError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        match self.inner.as_mut().poll_next(cx) {
+            Poll::Ready(Some((page, result))) => {
+                self.page = page;
+                Poll::Ready(Some(result))
+            }
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::TryStreamExt;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn events(timestamps: &[&str]) -> SearchStream<'static, LogEvent> {
+        let logs: Vec<Log> = timestamps
+            .iter()
+            .map(|t| {
+                serde_json::from_value(serde_json::json!({
+                    "type": "log",
+                    "attributes": {"timestamp": t}
+                }))
+                .unwrap()
+            })
+            .collect();
+        let polled = futures_util::stream::iter(logs).map(Ok);
+        SearchStream::new(polled).map_records(LogEvent::from)
+    }
+
+    /// The results `0..n`, with a count of those pulled from the source so
+    /// far, for telling how far a prefetch has run ahead.
+    fn counted(
+        n: usize,
+    ) -> (
+        impl Stream<Item = Result<usize, AppError>> + Send + 'static,
+        tokio::sync::watch::Receiver<usize>,
+    ) {
+        let (pulled, count) = tokio::sync::watch::channel(0);
+        let records = futures_util::stream::iter(0..n).map(move |i| {
+            pulled.send_modify(|count| *count += 1);
+            Ok(i)
+        });
+        (records, count)
+    }
+
+    #[tokio::test]
+    async fn test_take_records_and_progress() {
+        let seen = Arc::new(AtomicUsize::new(0));
+        let counter = seen.clone();
+        let logs: Vec<_> = events(&["2024-01-15T10:00:00Z"; 5])
+            .take_records(3)
+            .with_progress(move |_| {
+                counter.fetch_add(1, Ordering::Relaxed);
+            })
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(logs.len(), 3);
+        assert_eq!(seen.load(Ordering::Relaxed), 3);
+
+        let all: Vec<_> = events(&["2024-01-15T10:00:00Z"; 5])
+            .take_records(0)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_until_timestamp() {
+        let cutoff = "2024-01-15T10:01:00Z".parse().unwrap();
+        let logs: Vec<_> = events(&[
+            "2024-01-15T10:00:00Z",
+            "2024-01-15T10:01:00Z",
+            "2024-01-15T10:02:00Z",
+            "2024-01-15T10:00:30Z",
+        ])
+        .until_timestamp(cutoff)
+        .try_collect()
+        .await
+        .unwrap();
+        assert_eq!(logs.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_with_finishes_page() {
+        let token = CancellationToken::new();
+        let records = futures_util::stream::iter(0..10).map(Ok);
+        let mut stream = SearchStream::paged(records, 4).cancel_with(token.clone());
+
+        assert_eq!(stream.next().await.unwrap().unwrap(), 0);
+        token.cancel();
+        let rest: Vec<_> = stream.try_collect().await.unwrap();
+        assert_eq!(rest, [1, 2, 3]);
+
+        // Without a page size, nothing more is yielded
+        let records = futures_util::stream::iter(0..10).map(Ok);
+        let mut stream = SearchStream::new(records).cancel_with(token);
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_runs_ahead_in_order() {
+        let (records, mut pulled) = counted(10);
+        let mut stream = SearchStream::paged(records, 4).prefetch(4);
+
+        assert_eq!(stream.next().await.unwrap().unwrap(), 0);
+        // The rest of the page and the first of the next are fetched ahead,
+        // and nothing more until one is read
+        pulled.wait_for(|&n| n >= 6).await.unwrap();
+        assert_eq!(*pulled.borrow(), 6);
+
+        let rest: Vec<_> = stream.try_collect().await.unwrap();
+        assert_eq!(rest, (1..10).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_bounded_by_buffer() {
+        let (records, mut pulled) = counted(100);
+        let mut stream = SearchStream::paged(records, 50).prefetch(2);
+
+        assert_eq!(stream.next().await.unwrap().unwrap(), 0);
+        // Two results wait unread and a third waits for room
+        pulled.wait_for(|&n| n >= 4).await.unwrap();
+        assert_eq!(*pulled.borrow(), 4);
+
+        // Each result read makes room for one more, never more than that
+        for read in 2..=100 {
+            assert_eq!(stream.next().await.unwrap().unwrap(), read - 1);
+            assert!(*pulled.borrow() <= read + 3);
+        }
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_ends_at_error() {
+        let records = futures_util::stream::iter([Ok(1), Err(AppError::Api("boom".into())), Ok(2)]);
+        let results: Vec<_> = SearchStream::paged(records, 2).prefetch(2).collect().await;
+        assert_eq!(results.len(), 2);
+        assert!(matches!(results[1], Err(AppError::Api(_))));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_on_ends_stream() {
+        let logs: Vec<_> = SearchStream::<LogEvent>::new(futures_util::stream::pending())
+            .cancel_on(std::future::ready(()))
+            .try_collect()
+            .await
+            .unwrap();
+        assert!(logs.is_empty());
+    }
+}
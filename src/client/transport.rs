@@ -0,0 +1,271 @@
+//! Pluggable HTTP transport.
+//!
+//! Every client sends its requests through the shared middleware stack built
+//! in [`http`](super::http). A [`DatadogTransport`] replaces the network at
+//! the bottom of that stack, so the SDK's request building, pagination, and
+//! the retry middleware all run as usual against whatever the transport
+//! answers. [`MockTransport`] answers with canned fixtures, for testing
+//! without credentials or a network.
+
+use async_trait::async_trait;
+use reqwest::{Method, Request, Response};
+use reqwest_middleware::{Middleware, Next};
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use task_local_extensions::Extensions;
+
+use crate::error::AppError;
+
+/// Sends an HTTP request to Datadog and returns its response.
+///
+/// Implement this to route requests through another HTTP stack, or to answer
+/// them in tests; install it with a client builder's `transport`.
+#[async_trait]
+pub trait DatadogTransport: Send + Sync {
+    /// Sends one request.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when no response was received; error statuses are
+    /// returned as responses.
+    async fn send(&self, request: Request) -> Result<Response, AppError>;
+
+    /// Sends one request for a client whose own HTTP client is `network`.
+    ///
+    /// Clients call this rather than [`send`](Self::send). A transport that
+    /// passes requests on to Datadog, such as a cache on a miss, sends them
+    /// with `network`, so they keep the client's proxy and connection pool.
+    /// By default, `network` is ignored.
+    async fn send_with(
+        &self,
+        request: Request,
+        network: &reqwest::Client,
+    ) -> Result<Response, AppError> {
+        let _ = network;
+        self.send(request).await
+    }
+}
+
+/// Middleware handing requests to a transport instead of the network, along
+/// with the client's own HTTP client.
+pub(crate) struct TransportMiddleware(
+    pub(crate) Arc<dyn DatadogTransport>,
+    pub(crate) reqwest::Client,
+);
+
+#[async_trait]
+impl Middleware for TransportMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        _extensions: &mut Extensions,
+        _next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        self.0
+            .send_with(req, &self.1)
+            .await
+            .map_err(|e| reqwest_middleware::Error::Middleware(e.into()))
+    }
+}
+
+/// Sends a request on through `inner` if given, and to Datadog with
+/// `network` otherwise, for transports that wrap the network.
+pub(crate) async fn pass_on(
+    inner: Option<&Arc<dyn DatadogTransport>>,
+    request: Request,
+    network: &reqwest::Client,
+) -> Result<Response, AppError> {
+    match inner {
+        Some(inner) => inner.send_with(request, network).await,
+        None => network
+            .execute(request)
+            .await
+            .map_err(|e| AppError::Unavailable(e.to_string())),
+    }
+}
+
+/// A request received by a [`MockTransport`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedRequest {
+    /// HTTP method
+    pub method: Method,
+
+    /// URL path (e.g., "/api/v2/logs/events/search")
+    pub path: String,
+
+    /// JSON body, if the request had one
+    pub body: Option<Value>,
+}
+
+/// A transport answering from canned responses, queued per method and path.
+///
+/// Each request takes the next response queued for its method and path, so
+/// queuing several responses scripts pagination or a failure followed by a
+/// successful retry. A request with nothing queued fails.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::client::{LogsClient, LogsSearchRequest, MockTransport};
+/// use serde_json::json;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let transport = MockTransport::new().respond(
+///     "POST",
+///     "/api/v2/logs/events/search",
+///     200,
+///     json!({"data": [{"id": "AAA", "type": "log", "attributes": {"message": "hi"}}]}),
+/// );
+/// let client = LogsClient::builder()
+///     .api_key("test")
+///     .app_key("test")
+///     .transport(transport.clone())
+///     .build()
+///     .unwrap();
+///
+/// let logs = client.fetch(&LogsSearchRequest::builder().build(), 0).await.unwrap();
+/// assert_eq!(logs[0].message.as_deref(), Some("hi"));
+/// assert_eq!(transport.requests().len(), 1);
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct MockTransport {
+    state: Arc<Mutex<MockState>>,
+}
+
+#[derive(Default)]
+struct MockState {
+    responses: HashMap<(Method, String), VecDeque<(u16, Value)>>,
+    requests: Vec<RecordedRequest>,
+}
+
+impl MockTransport {
+    /// Creates a transport with no responses queued.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues a JSON response with `status` for requests to `method` `path`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `method` is not a valid HTTP method.
+    pub fn respond(self, method: &str, path: &str, status: u16, body: Value) -> Self {
+        let method = Method::from_bytes(method.as_bytes()).expect("invalid HTTP method");
+        self.lock()
+            .responses
+            .entry((method, path.to_string()))
+            .or_default()
+            .push_back((status, body));
+        self
+    }
+
+    /// The requests received so far, in order.
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.lock().requests.clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, MockState> {
+        // A panic while holding the lock can't leave the state inconsistent
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[async_trait]
+impl DatadogTransport for MockTransport {
+    async fn send(&self, request: Request) -> Result<Response, AppError> {
+        let method = request.method().clone();
+        let path = request.url().path().to_string();
+        let body = request
+            .body()
+            .and_then(|b| b.as_bytes())
+            .and_then(|b| serde_json::from_slice(b).ok());
+
+        let mut state = self.lock();
+        state.requests.push(RecordedRequest {
+            method: method.clone(),
+            path: path.clone(),
+            body,
+        });
+        let (status, body) = state
+            .responses
+            .get_mut(&(method.clone(), path.clone()))
+            .and_then(VecDeque::pop_front)
+            .ok_or_else(|| AppError::Api(format!("No mock response for {} {}", method, path)))?;
+
+        let response = http::Response::builder()
+            .status(status)
+            .header("content-type", "application/json")
+            .body(serde_json::to_vec(&body)?)
+            .map_err(|e| AppError::Api(e.to_string()))?;
+        Ok(Response::from(response))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{LogsClient, LogsSearchRequest};
+    use serde_json::json;
+
+    const SEARCH: &str = "/api/v2/logs/events/search";
+
+    fn log(id: &str) -> Value {
+        json!({"id": id, "type": "log", "attributes": {}})
+    }
+
+    fn client(transport: &MockTransport) -> LogsClient {
+        LogsClient::builder()
+            .api_key("test")
+            .app_key("test")
+            .transport(transport.clone())
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_search_follows_cursor() {
+        let transport = MockTransport::new()
+            .respond(
+                "POST",
+                SEARCH,
+                200,
+                json!({"data": [log("a"), log("b")], "meta": {"page": {"after": "next"}}}),
+            )
+            .respond("POST", SEARCH, 200, json!({"data": [log("c")]}));
+        let request = LogsSearchRequest::builder().page_limit(2).build();
+
+        let logs = client(&transport).fetch(&request, 0).await.unwrap();
+        let ids: Vec<_> = logs.iter().filter_map(|l| l.id.as_deref()).collect();
+        assert_eq!(ids, ["a", "b", "c"]);
+
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(
+            requests[0].body.as_ref().unwrap()["page"].get("cursor"),
+            None
+        );
+        assert_eq!(requests[1].body.as_ref().unwrap()["page"]["cursor"], "next");
+    }
+
+    #[tokio::test]
+    async fn test_error_status_maps_to_app_error() {
+        let transport =
+            MockTransport::new().respond("POST", SEARCH, 403, json!({"errors": ["Forbidden"]}));
+        let result = client(&transport)
+            .fetch(&LogsSearchRequest::builder().build(), 0)
+            .await;
+        assert!(matches!(result, Err(AppError::Forbidden(_))));
+    }
+
+    #[tokio::test]
+    async fn test_unqueued_request_fails() {
+        let transport = MockTransport::new();
+        let result = client(&transport)
+            .fetch(&LogsSearchRequest::builder().build(), 0)
+            .await;
+        assert!(matches!(result, Err(AppError::Api(_))));
+    }
+}
@@ -0,0 +1,64 @@
+//! Shared output of logs and spans volume aggregations.
+//!
+//! Writes [`VolumePoint`] records, or renders them as a chart when `--chart`
+//! is given. [`draw`] also serves `--viz` for metrics queries.
+
+use std::io::Write;
+
+use crate::cli::{Aggregation, Chart, Viz};
+use crate::logging::{self, VerboseLogger};
+use crate::output::{self, RecordWriter};
+use ddog::client::VolumePoint;
+use ddog::error::AppError;
+use ddog::heatmap::Heatmap;
+use ddog::viz::Plot;
+
+/// Writes aggregation results as records or as the requested chart.
+///
+/// `title` names the chart in HTML output (e.g., "logs matching `status:error`").
+pub fn write(
+    points: &[VolumePoint],
+    aggregation: &Aggregation,
+    title: &str,
+    writer: &mut RecordWriter,
+    logger: &VerboseLogger,
+) -> Result<(), AppError> {
+    logger.log(&format!("Returned {} bucket(s)", points.len()));
+
+    match aggregation.chart {
+        None => {
+            for point in points {
+                writer.write(point)?;
+            }
+            if let Some(viz) = aggregation.viz {
+                draw(&Plot::from_volume(points), viz)?;
+            }
+        }
+        Some(Chart::Heatmap) => {
+            if !points.is_empty() {
+                output::count_record();
+            }
+            let heatmap = Heatmap::from_points(&aggregation.group_by, points);
+            match &aggregation.html {
+                Some(path) => {
+                    std::fs::write(path, heatmap.render_html(title))?;
+                    logging::notice(&format!("Wrote heatmap to {}", path.display()));
+                }
+                None => std::io::stdout()
+                    .lock()
+                    .write_all(heatmap.render_text().as_bytes())?,
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Draws a `--viz` chart on stderr, leaving stdout to the records.
+pub fn draw(plot: &Plot, viz: Viz) -> Result<(), AppError> {
+    let chart = match viz {
+        Viz::Histogram => plot.render_histogram(),
+        Viz::Sparkline => plot.render_sparklines(),
+    };
+    std::io::stderr().lock().write_all(chart.as_bytes())?;
+    Ok(())
+}
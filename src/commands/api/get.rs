@@ -0,0 +1,41 @@
+//! Raw GET command implementation.
+//!
+//! Handles the `ddog api get` command, passing a request through to any
+//! Datadog API endpoint.
+
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::{ApiClient, into_records, parse_query};
+use ddog::error::AppError;
+
+/// Executes the raw GET command.
+///
+/// Writes one record per item of the response, or the whole response with `raw`.
+pub async fn run(
+    client: ApiClient,
+    path: &str,
+    query: &[String],
+    raw: bool,
+    mut writer: RecordWriter,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let params = query
+        .iter()
+        .map(|q| parse_query(q))
+        .collect::<Result<Vec<_>, _>>()?
+        .concat();
+    logger.log(&format!("GET {}", client.url(path)));
+
+    let body = client.get(path, &params).await?;
+    if raw {
+        writer.write(&body)?;
+        return Ok(());
+    }
+
+    let records = into_records(body);
+    logger.log(&format!("Response contained {} record(s)", records.len()));
+    for record in &records {
+        writer.write(record)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,4 @@
+//! Raw API domain commands.
+
+pub mod get;
+pub mod post;
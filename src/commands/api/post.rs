@@ -0,0 +1,92 @@
+//! Raw POST command implementation.
+//!
+//! Handles the `ddog api post` command: renders the body template, confirms
+//! the request, sends it, and records it in the audit log.
+
+use crate::commands::write::{confirm, read_body, record};
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::{ApiClient, into_records, parse_query};
+use ddog::error::AppError;
+use ddog::template;
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+
+/// Arguments of `ddog api post`.
+pub struct Post {
+    /// API path, starting with /
+    pub path: String,
+
+    /// Body template: inline JSON, @FILE, or @- for stdin
+    pub body: String,
+
+    /// Values for the template's placeholders
+    pub vars: Vec<(String, String)>,
+
+    /// Query parameter strings
+    pub query: Vec<String>,
+
+    /// Print the request instead of sending it
+    pub dry_run: bool,
+
+    /// Send without asking for confirmation
+    pub yes: bool,
+
+    /// Write the whole response as a single record
+    pub raw: bool,
+}
+
+/// Executes the raw POST command.
+///
+/// Writes the response like `ddog api get`, or the request itself with `dry_run`.
+pub async fn run(
+    client: ApiClient,
+    post: Post,
+    mut writer: RecordWriter,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let params = post
+        .query
+        .iter()
+        .map(|q| parse_query(q))
+        .collect::<Result<Vec<_>, _>>()?
+        .concat();
+    let vars: BTreeMap<String, String> = post.vars.into_iter().collect();
+    let body = template::render(&read_body(&post.body)?, &vars)?;
+    let url = client.url(&post.path);
+
+    if post.dry_run {
+        let query: serde_json::Map<String, Value> = params
+            .iter()
+            .map(|(k, v)| (k.clone(), Value::String(v.clone())))
+            .collect();
+        writer.write(&json!({
+            "method": "POST",
+            "url": url,
+            "query": query,
+            "body": body,
+        }))?;
+        return Ok(());
+    }
+
+    if !post.yes && !confirm("POST", &url, Some(&body))? {
+        eprintln!("Not sent");
+        return Ok(());
+    }
+
+    logger.log(&format!("POST {}", url));
+    let result = client.post(&post.path, &params, &body).await;
+    record("POST", &url, &result, &logger);
+    let response = result?;
+
+    if post.raw {
+        writer.write(&response)?;
+        return Ok(());
+    }
+    let records = into_records(response);
+    logger.log(&format!("Response contained {} record(s)", records.len()));
+    for record in &records {
+        writer.write(record)?;
+    }
+    Ok(())
+}
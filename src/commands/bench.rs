@@ -0,0 +1,83 @@
+//! Bench command implementation.
+//!
+//! Handles the `ddog bench logs` command, timing repeated runs of a logs
+//! search and writing one summary of them.
+
+use serde::Serialize;
+
+use crate::cli::{LogsSource, TimeRange};
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::bench::{self, BenchReport};
+use ddog::client::{LogsClient, MAX_LOGS_PAGE_SIZE};
+use ddog::error::AppError;
+
+/// A benchmark's summary, with the search it ran.
+#[derive(Serialize)]
+struct BenchRecord<'a> {
+    /// Query as sent to Datadog
+    query: &'a str,
+
+    /// Start of the time range
+    from: &'a str,
+
+    /// End of the time range
+    to: &'a str,
+
+    #[serde(flatten)]
+    report: BenchReport,
+}
+
+/// Options of a benchmark.
+pub struct BenchOptions {
+    /// Times to run the search
+    pub iterations: u32,
+
+    /// Records each run reads, 0 for all
+    pub limit: u64,
+
+    /// Records per page
+    pub page_size: i32,
+}
+
+/// Executes the bench logs command.
+///
+/// Runs the search `iterations` times one after another, logging each run's
+/// timings, and writes their summary.
+pub async fn logs(
+    client: LogsClient,
+    query: String,
+    time_range: TimeRange,
+    source: LogsSource,
+    options: BenchOptions,
+    mut writer: RecordWriter,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let mut request = source.request(&query, &time_range);
+    request.page_limit = options.page_size.min(MAX_LOGS_PAGE_SIZE);
+    let limit = usize::try_from(options.limit).unwrap_or(usize::MAX);
+
+    let mut iterations = Vec::new();
+    for run in 1..=options.iterations {
+        let results = client.search(&request).take_records(limit);
+        let iteration = bench::measure(results, request.page_limit as usize).await?;
+        logger.log(&format!(
+            "Run {}/{}: {} log(s) in {:.0}ms over {} page(s), first after {:.0}ms",
+            run,
+            options.iterations,
+            iteration.records,
+            iteration.total_ms,
+            iteration.page_ms.len(),
+            iteration.first_record_ms.unwrap_or(iteration.total_ms),
+        ));
+        iterations.push(iteration);
+    }
+
+    writer.write(&BenchRecord {
+        query: &query,
+        from: &time_range.from,
+        to: &time_range.to,
+        report: bench::summarize(&iterations),
+    })?;
+    Ok(())
+}
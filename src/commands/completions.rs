@@ -0,0 +1,34 @@
+//! Shell completion script command.
+
+use clap::CommandFactory;
+use clap_complete::Shell;
+use clap_complete::env::Shells;
+use ddog::error::AppError;
+use std::io::{self, Write};
+
+use crate::cli::Cli;
+
+/// Environment variable the dynamic completion script sets when asking for candidates.
+pub const COMPLETE_VAR: &str = "COMPLETE";
+
+/// Prints the completion script for a shell.
+///
+/// The dynamic script calls back into `ddog` (via [`COMPLETE_VAR`]) for
+/// candidates; the static one is generated once from the CLI definition.
+pub fn run(shell: Shell, static_script: bool) -> Result<(), AppError> {
+    let mut stdout = io::stdout().lock();
+    if static_script {
+        clap_complete::generate(shell, &mut Cli::command(), "ddog", &mut stdout);
+    } else {
+        let shells = Shells::builtins();
+        let completer = shells.completer(&shell.to_string()).ok_or_else(|| {
+            AppError::Config(format!(
+                "Dynamic completions are not supported for {}",
+                shell
+            ))
+        })?;
+        completer.write_registration(COMPLETE_VAR, "ddog", "ddog", "ddog", &mut stdout)?;
+    }
+    stdout.flush()?;
+    Ok(())
+}
@@ -0,0 +1,66 @@
+//! Count-only search implementation.
+//!
+//! Handles `--count` on `ddog logs search` and `ddog spans search`: the
+//! matching events are counted with the aggregate endpoint instead of being
+//! streamed.
+
+use serde::Serialize;
+
+use crate::logging::VerboseLogger;
+use crate::output::{self, RecordWriter};
+use ddog::error::AppError;
+
+/// Number of events matching a search.
+#[derive(Debug, Serialize)]
+pub struct CountRecord<'a> {
+    /// Query as sent to Datadog
+    pub query: &'a str,
+
+    /// Start of the time range
+    pub from: &'a str,
+
+    /// End of the time range
+    pub to: &'a str,
+
+    /// Number of matching events
+    pub count: u64,
+}
+
+impl<'a> CountRecord<'a> {
+    /// Creates the record for a count returned by the aggregate endpoint.
+    ///
+    /// A search Datadog returns no count for matched nothing, so its count is 0.
+    pub fn new(query: &'a str, from: &'a str, to: &'a str, count: Option<f64>) -> Self {
+        Self {
+            query,
+            from,
+            to,
+            count: count.map_or(0, |count| count.round() as u64),
+        }
+    }
+}
+
+/// Writes the count as a record, or as a bare number with `raw`.
+///
+/// A count of 0 is written without counting it as a result, so that
+/// `--fail-if-empty` fails when the search matched nothing.
+pub fn write(
+    record: &CountRecord<'_>,
+    raw: bool,
+    writer: &mut RecordWriter,
+    logger: &VerboseLogger,
+) -> Result<(), AppError> {
+    logger.log(&format!("Counted {} event(s)", record.count));
+
+    if raw {
+        if record.count > 0 {
+            output::count_record();
+        }
+        println!("{}", record.count);
+    } else if record.count > 0 {
+        writer.write(record)?;
+    } else {
+        writer.write_meta(record)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,68 @@
+//! Reference documentation commands.
+//!
+//! Handles `ddog docs man`, rendering man pages with clap_mangen, and
+//! `ddog help-all`, printing the help of every command in one document.
+
+use clap::{Command, CommandFactory};
+use clap_mangen::Man;
+use ddog::error::AppError;
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::cli::Cli;
+use crate::logging;
+
+/// The CLI definition with every subcommand's full name resolved, such as
+/// `ddog-logs-search` for man pages and `ddog logs search` in usage lines.
+fn command_tree() -> Command {
+    let mut command = Cli::command().disable_help_subcommand(true);
+    command.build();
+    command
+}
+
+/// Visible commands of the tree, parents before their subcommands.
+fn visible(command: &Command) -> Vec<&Command> {
+    let mut commands = vec![command];
+    for subcommand in command.get_subcommands().filter(|s| !s.is_hide_set()) {
+        commands.extend(visible(subcommand));
+    }
+    commands
+}
+
+/// Writes a man page for every command to `dir`.
+pub fn man(dir: &Path) -> Result<(), AppError> {
+    std::fs::create_dir_all(dir)?;
+    let tree = command_tree();
+    for command in visible(&tree) {
+        let path = Man::new(command.clone())
+            .source(format!("ddog {}", env!("CARGO_PKG_VERSION")))
+            .generate_to(dir)?;
+        logging::notice(&path.display().to_string());
+    }
+    Ok(())
+}
+
+/// Prints the long help of every command, each under a heading naming it.
+pub fn help_all() -> Result<(), AppError> {
+    let mut stdout = io::stdout().lock();
+    let mut tree = command_tree();
+    let names: Vec<String> = visible(&tree)
+        .into_iter()
+        .map(|command| command.get_bin_name().unwrap_or("ddog").to_string())
+        .collect();
+    for (n, name) in names.iter().enumerate() {
+        let path: Vec<&str> = name.split(' ').skip(1).collect();
+        let command = path.iter().fold(&mut tree, |command, name| {
+            command
+                .find_subcommand_mut(name)
+                .expect("command paths come from the tree")
+        });
+        if n > 0 {
+            writeln!(stdout)?;
+        }
+        writeln!(stdout, "{}\n{}\n", name, "=".repeat(name.len()))?;
+        write!(stdout, "{}", command.render_long_help())?;
+    }
+    stdout.flush()?;
+    Ok(())
+}
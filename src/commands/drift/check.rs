@@ -0,0 +1,65 @@
+//! Drift check command implementation.
+//!
+//! Handles the `ddog drift check` command, comparing live configuration with
+//! a desired-state bundle.
+
+use futures_util::{StreamExt, TryStreamExt};
+use std::path::Path;
+
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::ResourcesClient;
+use ddog::drift::{check, load_bundle};
+use ddog::error::AppError;
+
+/// Number of resources fetched concurrently.
+const CONCURRENCY: usize = 4;
+
+/// Executes the drift check command.
+///
+/// Writes one record per difference, in bundle order, and fails with
+/// `AppError::Drift` if there is any.
+pub async fn run(
+    client: ResourcesClient,
+    bundle: &Path,
+    mut writer: RecordWriter,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let desired = load_bundle(bundle)?;
+    logger.log(&format!(
+        "Loaded {} definition(s) from {}",
+        desired.len(),
+        bundle.display()
+    ));
+
+    let client = &client;
+    let mut results = futures_util::stream::iter(&desired)
+        .map(|resource| async move {
+            let live = client.get(resource.kind, &resource.id).await?;
+            Ok::<_, AppError>(check(resource, live.as_ref()))
+        })
+        .buffered(CONCURRENCY);
+
+    let mut drifted = 0;
+    let mut differences = 0;
+    while let Some(drift) = results.try_next().await? {
+        if !drift.is_empty() {
+            drifted += 1;
+        }
+        for difference in &drift {
+            writer.write(difference)?;
+            differences += 1;
+        }
+    }
+
+    if drifted > 0 {
+        return Err(AppError::Drift(format!(
+            "{} difference(s) in {} of {} resource(s)",
+            differences,
+            drifted,
+            desired.len()
+        )));
+    }
+    logger.log(&format!("All {} resource(s) match", desired.len()));
+    Ok(())
+}
@@ -0,0 +1,3 @@
+//! Drift domain commands.
+
+pub mod check;
@@ -0,0 +1,251 @@
+//! Search explanation implementation.
+//!
+//! Handles `--explain` on `ddog logs search` and `ddog spans search`: before
+//! the search runs, describes exactly what it will ask Datadog for (the final
+//! query, absolute time bounds, indexes, sort, and page size) and how many
+//! pages that should take, going by a count of the matches. For logs, it also
+//! shows the restriction queries that limit what the application key can read.
+
+use datadog_api_client::datadog::Configuration;
+use serde::Serialize;
+
+use crate::cli::{LogsSource, Pagination, TimeRange};
+use crate::logging::VerboseLogger;
+use crate::output::MetaRecord;
+use ddog::client::{
+    LogsClient, MAX_LOGS_PAGE_SIZE, MAX_SPANS_PAGE_SIZE, RestrictionsClient, SortOrder,
+    SpansClient, SpansSearchType,
+};
+use ddog::error::AppError;
+use ddog::time::Timezone;
+
+/// How a search was resolved, printed by `--explain`, and written as the
+/// meta record with `--emit-meta`.
+#[derive(Debug, Serialize)]
+pub struct Explanation {
+    #[serde(flatten)]
+    pub meta: MetaRecord,
+
+    /// Storage tier searched, when not the indexes by default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub storage_tier: Option<String>,
+
+    /// Spans search type: spans or traces
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search_type: Option<&'static str>,
+
+    /// Result order, as the API's sort: `timestamp` (oldest first) or
+    /// `-timestamp` (newest first)
+    pub sort: &'static str,
+
+    /// Records requested per page
+    pub page_size: i32,
+
+    /// Records to write, `None` for all
+    pub limit: Option<u64>,
+
+    /// Records matching the query, if they could be counted
+    pub matches: Option<u64>,
+
+    /// Pages the search should fetch, if the matches could be counted
+    pub estimated_pages: Option<u64>,
+
+    /// Logs restriction queries of the application key's user, which hide
+    /// every log not matching them, if they could be looked up
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restriction_queries: Option<Vec<String>>,
+}
+
+impl Explanation {
+    fn new(meta: MetaRecord, sort: SortOrder, page_size: i32, limit: u64) -> Self {
+        Self {
+            meta,
+            storage_tier: None,
+            search_type: None,
+            sort: match sort {
+                SortOrder::Ascending => "timestamp",
+                SortOrder::Descending => "-timestamp",
+            },
+            page_size,
+            limit: (limit > 0).then_some(limit),
+            matches: None,
+            estimated_pages: None,
+            restriction_queries: None,
+        }
+    }
+
+    /// Records the count of matches, or logs why there is none.
+    fn with_matches(
+        mut self,
+        count: Result<Option<f64>, AppError>,
+        logger: &VerboseLogger,
+    ) -> Self {
+        match count {
+            Ok(count) => {
+                let matches = count.map_or(0, |count| count.round() as u64);
+                self.matches = Some(matches);
+                self.estimated_pages = Some(estimated_pages(
+                    matches,
+                    self.limit.unwrap_or(0),
+                    self.page_size,
+                ));
+            }
+            Err(e) => logger.log(&format!("Failed to count matches: {}", e)),
+        }
+        self
+    }
+
+    /// Prints the explanation to stderr, one field per line.
+    pub fn print(&self) {
+        let meta = &self.meta;
+        let mut lines = vec![format!("Domain:     {}", meta.domain)];
+        if let Some(query) = &meta.query {
+            lines.push(format!("Query:      {}", query));
+        }
+        lines.push(format!("From:       {}", meta.from));
+        lines.push(format!("To:         {}", meta.to));
+        if let Some(indexes) = &meta.indexes {
+            lines.push(format!("Indexes:    {}", indexes.join(",")));
+        }
+        if let Some(tier) = &self.storage_tier {
+            lines.push(format!("Tier:       {}", tier));
+        }
+        if let Some(search_type) = self.search_type {
+            lines.push(format!("Type:       {}", search_type));
+        }
+        lines.push(format!("Sort:       {}", self.sort));
+        lines.push(format!("Page size:  {}", self.page_size));
+        lines.push(format!(
+            "Limit:      {}",
+            self.limit.map_or("none".to_string(), |l| l.to_string())
+        ));
+        match (self.matches, self.estimated_pages) {
+            (Some(matches), Some(pages)) => {
+                lines.push(format!("Matches:    {}", matches));
+                lines.push(format!("Pages:      {} (estimated)", pages));
+            }
+            _ => lines.push("Matches:    unknown (count failed)".to_string()),
+        }
+        if let Some(queries) = &self.restriction_queries {
+            if queries.is_empty() {
+                lines.push("Restricted: no".to_string());
+            } else {
+                lines.push(format!(
+                    "Restricted: only logs matching {}",
+                    queries.join(" or ")
+                ));
+            }
+        }
+        eprintln!("Explain:\n  {}", lines.join("\n  "));
+    }
+}
+
+/// Pages a search of `matches` records, capped at `limit` (0 for none),
+/// fetches `page_size` at a time. A search matching nothing still fetches
+/// one page.
+pub fn estimated_pages(matches: u64, limit: u64, page_size: i32) -> u64 {
+    let records = if limit > 0 {
+        matches.min(limit)
+    } else {
+        matches
+    };
+    records.div_ceil(page_size.max(1) as u64).max(1)
+}
+
+/// Explains a logs search, counting its matches.
+///
+/// # Errors
+///
+/// Returns `AppError::InvalidQuery` if a bound of the time range isn't a valid time.
+pub async fn logs(
+    config: &Configuration,
+    query: &str,
+    time_range: &TimeRange,
+    source: &LogsSource,
+    pagination: &Pagination,
+    timezone: Timezone,
+    logger: &VerboseLogger,
+) -> Result<Explanation, AppError> {
+    let request = source.request(query, time_range);
+    let meta = MetaRecord::new(
+        "logs",
+        Some(query),
+        &time_range.from,
+        &time_range.to,
+        timezone,
+    )?
+    .with_indexes(&request.indexes);
+    let logs = LogsClient::new(config.clone());
+    let restrictions = RestrictionsClient::new(config.clone());
+    let (count, restriction_queries) = tokio::join!(
+        logs.count(
+            query,
+            &time_range.from,
+            &time_range.to,
+            request.indexes.clone(),
+        ),
+        restrictions.restriction_queries()
+    );
+    let mut explanation = Explanation::new(
+        meta,
+        request.sort,
+        pagination.page_limit(MAX_LOGS_PAGE_SIZE),
+        pagination.limit,
+    );
+    explanation.storage_tier = request.storage_tier.map(|tier| tier.to_string());
+    match restriction_queries {
+        Ok(queries) => explanation.restriction_queries = Some(queries),
+        Err(e) => logger.log(&format!("Failed to look up restriction queries: {}", e)),
+    }
+    Ok(explanation.with_matches(count, logger))
+}
+
+/// Explains a spans search, counting its matches.
+///
+/// # Errors
+///
+/// Returns `AppError::InvalidQuery` if a bound of the time range isn't a valid time.
+pub async fn spans(
+    config: &Configuration,
+    query: &str,
+    time_range: &TimeRange,
+    search_type: SpansSearchType,
+    pagination: &Pagination,
+    timezone: Timezone,
+    logger: &VerboseLogger,
+) -> Result<Explanation, AppError> {
+    let meta = MetaRecord::new(
+        "spans",
+        Some(query),
+        &time_range.from,
+        &time_range.to,
+        timezone,
+    )?;
+    let count = SpansClient::new(config.clone())
+        .count(query, &time_range.from, &time_range.to)
+        .await;
+    let mut explanation = Explanation::new(
+        meta,
+        time_range.spans_request(query).sort,
+        pagination.page_limit(MAX_SPANS_PAGE_SIZE),
+        pagination.limit,
+    );
+    explanation.search_type = Some(match search_type {
+        SpansSearchType::Spans => "spans",
+        SpansSearchType::Traces => "traces",
+    });
+    Ok(explanation.with_matches(count, logger))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimated_pages() {
+        assert_eq!(estimated_pages(0, 100, 1000), 1);
+        assert_eq!(estimated_pages(2500, 0, 1000), 3);
+        assert_eq!(estimated_pages(2500, 1000, 1000), 1);
+        assert_eq!(estimated_pages(2500, 1001, 1000), 2);
+    }
+}
@@ -0,0 +1,219 @@
+//! Export command implementation.
+//!
+//! Handles the `ddog export` command, running the searches of an export spec
+//! into files a few at a time, checkpointing each finished job, and writing a
+//! manifest once they have all run.
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+use chrono::{SecondsFormat, Utc};
+use datadog_api_client::datadog::Configuration;
+use futures_util::StreamExt;
+use serde::Serialize;
+
+use crate::logging::VerboseLogger;
+use crate::output::{self, NdjsonWriter, RecordWriter, TextOptions, TextWriter};
+use ddog::client::{LogsClient, LogsSearchRequest, MetricsClient, SpansClient, SpansSearchRequest};
+use ddog::error::AppError;
+use ddog::export::{
+    Checkpoint, ExportDomain, ExportFormat, ExportJob, ExportSpec, FinishedJob, JobReport,
+    JobStatus, Manifest,
+};
+use ddog::time::{Timezone, resolve_absolute};
+use ddog::timeseries::PointRow;
+
+/// Clients shared by every job of an export.
+struct Clients {
+    logs: LogsClient,
+    spans: SpansClient,
+    metrics: MetricsClient,
+}
+
+/// Writes a job's records to its output file.
+enum FileWriter {
+    Ndjson(NdjsonWriter<File>),
+    Text(TextWriter<File>),
+}
+
+impl FileWriter {
+    /// Creates the output file, and its directory if needed.
+    fn create(path: &Path, format: ExportFormat, timezone: Timezone) -> Result<Self, AppError> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        let file = File::create(path)
+            .map_err(|e| std::io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))?;
+        Ok(match format {
+            ExportFormat::Ndjson => Self::Ndjson(NdjsonWriter::with_writer(file)),
+            ExportFormat::Text => Self::Text(TextWriter::new(
+                file,
+                TextOptions {
+                    links: None,
+                    timezone: Some(timezone),
+                },
+            )),
+        })
+    }
+
+    fn write<T: Serialize>(&mut self, record: &T) -> std::io::Result<()> {
+        match output::transform(record)? {
+            Some(transformed) => self.emit(&transformed),
+            None => self.emit(record),
+        }
+    }
+
+    fn emit<T: Serialize>(&mut self, record: &T) -> std::io::Result<()> {
+        match self {
+            Self::Ndjson(writer) => writer.write(record),
+            Self::Text(writer) => writer.write(record),
+        }
+    }
+}
+
+/// Executes the export command.
+///
+/// Runs the spec's jobs with its concurrency, skipping those a checkpoint
+/// shows as finished unless `restart` is set, and writes each job's report
+/// to stdout as it ends. A failed job doesn't stop the others; the first
+/// failure is returned once the manifest is written.
+pub async fn run(
+    config: Configuration,
+    spec_path: PathBuf,
+    restart: bool,
+    timezone: Timezone,
+    mut writer: RecordWriter,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let spec = ExportSpec::load(&spec_path)?;
+    let checkpoint_path = spec.checkpoint_path();
+    if restart && checkpoint_path.exists() {
+        fs::remove_file(&checkpoint_path)?;
+    }
+    let checkpoint = Checkpoint::load(&checkpoint_path)?;
+
+    let clients = Clients {
+        logs: LogsClient::new(config.clone()),
+        spans: SpansClient::new(config.clone()),
+        metrics: MetricsClient::new(config),
+    };
+
+    let mut reports: HashMap<String, JobReport> = HashMap::new();
+    let mut pending = Vec::new();
+    for job in &spec.jobs {
+        match checkpoint.finished(job) {
+            Some(finished) => {
+                logger.log(&format!("Job {} already finished, skipping", job.id));
+                let report = JobReport::finished(finished, JobStatus::Resumed);
+                writer.write(&report)?;
+                reports.insert(job.id.clone(), report);
+            }
+            None => pending.push(job),
+        }
+    }
+
+    let mut first_error = None;
+    let mut finished_jobs = futures_util::stream::iter(pending)
+        .map(|job| {
+            logger.log(&format!("Starting job {}: {}", job.id, job.query));
+            let clients = &clients;
+            async move { (job, export(clients, job, timezone).await) }
+        })
+        .buffer_unordered(spec.concurrency);
+    while let Some((job, result)) = finished_jobs.next().await {
+        let report = match result {
+            Ok(records) => {
+                let finished = FinishedJob {
+                    job: job.clone(),
+                    records,
+                    finished_at: now(),
+                };
+                Checkpoint::append(&checkpoint_path, &finished)?;
+                JobReport::finished(&finished, JobStatus::Done)
+            }
+            Err(e) => {
+                let report = JobReport::failed(job, &e);
+                first_error.get_or_insert(e.context(&format!("job {}", job.id)));
+                report
+            }
+        };
+        writer.write(&report)?;
+        reports.insert(job.id.clone(), report);
+    }
+
+    let jobs: Vec<JobReport> = spec
+        .jobs
+        .iter()
+        .filter_map(|job| reports.remove(&job.id))
+        .collect();
+    let manifest = Manifest {
+        spec: spec_path,
+        finished_at: now(),
+        records: jobs.iter().map(|job| job.records).sum(),
+        jobs,
+    };
+    manifest.write(&spec.manifest)?;
+    logger.log(&format!("Wrote manifest {}", spec.manifest.display()));
+
+    match first_error {
+        Some(e) => Err(e),
+        None => {
+            if checkpoint_path.exists() {
+                fs::remove_file(&checkpoint_path)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Runs one job into its output file, returning the records written.
+async fn export(clients: &Clients, job: &ExportJob, timezone: Timezone) -> Result<u64, AppError> {
+    let from = resolve_absolute(&job.from, timezone)?;
+    let to = resolve_absolute(&job.to, timezone)?;
+    let limit = job.limit as usize;
+    let mut file = FileWriter::create(&job.output, job.format, timezone)?;
+    let mut count: u64 = 0;
+
+    match job.domain {
+        ExportDomain::Logs => {
+            let request = LogsSearchRequest::builder()
+                .query(&job.query)
+                .from(from.timestamp_millis().to_string())
+                .to(to.timestamp_millis().to_string())
+                .build();
+            let mut stream = clients.logs.search(&request).take_records(limit);
+            while let Some(log) = stream.next().await {
+                file.write(&log?)?;
+                count += 1;
+            }
+        }
+        ExportDomain::Spans => {
+            let request = SpansSearchRequest::builder()
+                .query(&job.query)
+                .from(from.timestamp_millis().to_string())
+                .to(to.timestamp_millis().to_string())
+                .build();
+            let mut stream = clients.spans.search(&request).take_records(limit);
+            while let Some(span) = stream.next().await {
+                file.write(&span?)?;
+                count += 1;
+            }
+        }
+        ExportDomain::Metrics => {
+            let points = clients
+                .metrics
+                .query(&job.query, from.timestamp(), to.timestamp());
+            let mut points = points.take(if limit == 0 { usize::MAX } else { limit });
+            while let Some(point) = points.next().await {
+                file.write(&PointRow::from(&point?))?;
+                count += 1;
+            }
+        }
+    }
+    Ok(count)
+}
+
+fn now() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)
+}
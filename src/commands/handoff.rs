@@ -0,0 +1,236 @@
+//! Handoff command implementation.
+//!
+//! Handles the `ddog handoff` command, composing monitors, incidents, error
+//! logs, and SLOs into a Markdown summary of a team's shift.
+
+use std::io::Write;
+
+use chrono::{SecondsFormat, TimeZone, Utc};
+use datadog_api_client::datadog::Configuration;
+use futures_util::{StreamExt, TryStreamExt};
+
+use crate::logging::VerboseLogger;
+use ddog::client::{
+    EventsClient, IncidentSummary, IncidentsClient, LogsClient, LogsSearchRequest, MonitorsClient,
+    ServiceCatalogClient, SlosClient,
+};
+use ddog::episodes::pair_episodes;
+use ddog::error::AppError;
+use ddog::handoff::{Handoff, Section, SloBurn, TOP_PATTERNS, TriggeredMonitor, render_markdown};
+use ddog::links::DatadogLinks;
+use ddog::patterns::{PatternCount, count_patterns, new_patterns};
+use ddog::time::parse_to_unix_seconds;
+
+/// Number of monitors or SLOs fetched concurrently.
+const CONCURRENT_REQUESTS: usize = 4;
+
+/// Error logs sampled per window for pattern detection.
+const MAX_PATTERN_LOGS: usize = 5000;
+
+/// A time window in Unix seconds.
+#[derive(Clone, Copy)]
+struct Window {
+    from: i64,
+    to: i64,
+}
+
+impl Window {
+    /// Bounds as Unix milliseconds, as the search APIs accept them.
+    fn millis(self) -> (String, String) {
+        ((self.from * 1000).to_string(), (self.to * 1000).to_string())
+    }
+
+    /// The window of equal length ending where this one starts.
+    fn previous(self) -> Self {
+        Self {
+            from: self.from - (self.to - self.from),
+            to: self.from,
+        }
+    }
+}
+
+/// Executes the handoff command.
+///
+/// Gathers each section concurrently and writes the Markdown summary to stdout.
+pub async fn run(
+    config: Configuration,
+    team: String,
+    since: String,
+    site: &str,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let window = Window {
+        from: parse_to_unix_seconds(&since)?,
+        to: parse_to_unix_seconds("now")?,
+    };
+    let tag = format!("team:{}", team);
+
+    let (monitors, incidents, patterns, slos) = tokio::join!(
+        triggered_monitors(&config, &tag, window),
+        incidents(&config, &team, window),
+        error_patterns(&config, &team, window, &logger),
+        slo_burn(&config, &tag, window),
+    );
+
+    let handoff = Handoff {
+        team,
+        from: format_seconds(window.from),
+        to: format_seconds(window.to),
+        monitors: section(monitors, "triggered monitors", &logger),
+        incidents: section(incidents, "incidents", &logger),
+        patterns: section(patterns, "error patterns", &logger),
+        slos: section(slos, "SLO burn", &logger),
+    };
+
+    let mut stdout = std::io::stdout().lock();
+    stdout.write_all(render_markdown(&handoff, &DatadogLinks::new(site)).as_bytes())?;
+    Ok(())
+}
+
+/// Turns a failed section into a note, logging the full error.
+fn section<T>(result: Result<Vec<T>, AppError>, name: &str, logger: &VerboseLogger) -> Section<T> {
+    result.map_err(|e| {
+        logger.log_error(&e.to_string(), name);
+        e.to_string()
+    })
+}
+
+/// Monitors tagged with the team that alerted in the window, most episodes first.
+async fn triggered_monitors(
+    config: &Configuration,
+    tag: &str,
+    window: Window,
+) -> Result<Vec<TriggeredMonitor>, AppError> {
+    let monitors = MonitorsClient::new(config.clone());
+    let events = &EventsClient::new(config.clone());
+    let matched: Vec<_> = monitors.list(&[tag.to_string()]).try_collect().await?;
+    let (from, to) = &window.millis();
+
+    let triggered: Vec<Option<TriggeredMonitor>> = futures_util::stream::iter(&matched)
+        .map(|monitor| async move {
+            let history: Vec<_> = events
+                .monitor_events(monitor.id, from, to)
+                .try_collect()
+                .await?;
+            let episodes = pair_episodes(monitor.id, &history);
+            Ok::<_, AppError>(TriggeredMonitor::from_episodes(
+                monitor.id,
+                &monitor.name,
+                &episodes,
+            ))
+        })
+        .buffer_unordered(CONCURRENT_REQUESTS)
+        .try_collect()
+        .await?;
+
+    let mut triggered: Vec<TriggeredMonitor> = triggered.into_iter().flatten().collect();
+    triggered.sort_by(|a, b| b.episodes.cmp(&a.episodes).then(a.name.cmp(&b.name)));
+    Ok(triggered)
+}
+
+/// The team's incidents declared in the window, newest first.
+async fn incidents(
+    config: &Configuration,
+    team: &str,
+    window: Window,
+) -> Result<Vec<IncidentSummary>, AppError> {
+    let client = IncidentsClient::new(config.clone());
+    let since = format_seconds(window.from);
+
+    // Results are newest first, so stop at the first incident before the window
+    client
+        .search(&format!("teams:\"{}\"", team))
+        .try_take_while(|incident| std::future::ready(Ok(incident.created >= since)))
+        .try_collect()
+        .await
+}
+
+/// Top error patterns for the team's services that are absent from the previous window.
+async fn error_patterns(
+    config: &Configuration,
+    team: &str,
+    window: Window,
+    logger: &VerboseLogger,
+) -> Result<Vec<PatternCount>, AppError> {
+    let ownership = ServiceCatalogClient::new(config.clone())
+        .ownership()
+        .await?;
+    let services = ownership
+        .teams
+        .get(team)
+        .map(|t| t.services.as_slice())
+        .unwrap_or_default();
+    let query = if services.is_empty() {
+        format!("status:error team:{}", team)
+    } else {
+        format!("status:error service:({})", services.join(" OR "))
+    };
+    logger.log(&format!("Error pattern query: {}", query));
+
+    let client = LogsClient::new(config.clone());
+    let current = error_messages(&client, &query, window).await?;
+    let previous = error_messages(&client, &query, window.previous()).await?;
+
+    let mut patterns = new_patterns(
+        count_patterns(current.iter().map(String::as_str)),
+        &count_patterns(previous.iter().map(String::as_str)),
+    );
+    patterns.truncate(TOP_PATTERNS);
+    Ok(patterns)
+}
+
+async fn error_messages(
+    client: &LogsClient,
+    query: &str,
+    window: Window,
+) -> Result<Vec<String>, AppError> {
+    let (from, to) = window.millis();
+    let request = LogsSearchRequest::builder()
+        .query(query)
+        .from(from)
+        .to(to)
+        .build();
+    client
+        .search(&request)
+        .take_records(MAX_PATTERN_LOGS)
+        .try_filter_map(|log| std::future::ready(Ok(log.attributes.and_then(|a| a.message))))
+        .try_collect()
+        .await
+}
+
+/// SLOs tagged with the team that are notable over the window, fastest burning first.
+async fn slo_burn(
+    config: &Configuration,
+    tag: &str,
+    window: Window,
+) -> Result<Vec<SloBurn>, AppError> {
+    let client = &SlosClient::new(config.clone());
+    let slos: Vec<_> = client.list(tag).try_collect().await?;
+
+    let burns: Vec<Option<SloBurn>> = futures_util::stream::iter(&slos)
+        .map(|slo| async move {
+            let sli = client.sli(&slo.id, window.from, window.to).await?;
+            Ok::<_, AppError>(sli.map(|sli| SloBurn::new(&slo.id, &slo.name, slo.target, sli)))
+        })
+        .buffer_unordered(CONCURRENT_REQUESTS)
+        .try_collect()
+        .await?;
+
+    let mut notable: Vec<SloBurn> = burns
+        .into_iter()
+        .flatten()
+        .filter(SloBurn::is_notable)
+        .collect();
+    notable.sort_by(|a, b| {
+        let rate = |s: &SloBurn| s.burn_rate.unwrap_or(f64::INFINITY);
+        rate(b).total_cmp(&rate(a))
+    });
+    Ok(notable)
+}
+
+fn format_seconds(seconds: i64) -> String {
+    Utc.timestamp_opt(seconds, 0)
+        .single()
+        .map(|t| t.to_rfc3339_opts(SecondsFormat::Secs, true))
+        .unwrap_or_else(|| seconds.to_string())
+}
@@ -0,0 +1,43 @@
+//! History list command implementation.
+//!
+//! Handles the `ddog history list` command, writing the most recent entries
+//! of the query history.
+
+use serde::Serialize;
+
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::error::AppError;
+use ddog::history::{HistoryEntry, history_path, load};
+
+/// History entry with the number `ddog history rerun` takes.
+#[derive(Serialize)]
+struct NumberedEntry<'a> {
+    n: usize,
+    #[serde(flatten)]
+    entry: &'a HistoryEntry,
+}
+
+/// Executes the history list command.
+///
+/// Writes the last `limit` entries, oldest first.
+pub fn run(limit: usize, mut writer: RecordWriter, logger: VerboseLogger) -> Result<(), AppError> {
+    let Some(path) = history_path() else {
+        return Ok(());
+    };
+    let entries = load(&path)?;
+    logger.log(&format!(
+        "Loaded {} history entries from {}",
+        entries.len(),
+        path.display()
+    ));
+
+    let skip = entries.len().saturating_sub(limit);
+    for (index, entry) in entries.iter().enumerate().skip(skip) {
+        writer.write(&NumberedEntry {
+            n: index + 1,
+            entry,
+        })?;
+    }
+    Ok(())
+}
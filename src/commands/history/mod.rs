@@ -0,0 +1,4 @@
+//! History domain commands.
+
+pub mod list;
+pub mod rerun;
@@ -0,0 +1,39 @@
+//! History rerun command implementation.
+//!
+//! Handles the `ddog history rerun` command, replaying a recorded command
+//! line with the current binary.
+
+use std::process::Command;
+
+use crate::logging::VerboseLogger;
+use ddog::error::AppError;
+use ddog::history::{history_path, load};
+
+/// Executes the history rerun command.
+///
+/// The rerun inherits stdin, stdout, and stderr; when it fails, this
+/// process exits with its exit code.
+pub fn run(n: usize, logger: VerboseLogger) -> Result<(), AppError> {
+    let entries = match history_path() {
+        Some(path) => load(&path)?,
+        None => Vec::new(),
+    };
+    let entry = n
+        .checked_sub(1)
+        .and_then(|index| entries.get(index))
+        .ok_or_else(|| {
+            AppError::InvalidQuery(format!(
+                "No query {} in the history (run `ddog history list` to see them)",
+                n
+            ))
+        })?;
+
+    logger.log(&format!("Rerunning: ddog {}", entry.args.join(" ")));
+    let status = Command::new(std::env::current_exe()?)
+        .args(&entry.args)
+        .status()?;
+    if !status.success() {
+        std::process::exit(status.code().unwrap_or(1));
+    }
+    Ok(())
+}
@@ -0,0 +1,77 @@
+//! Hosts flaps command implementation.
+//!
+//! Handles the `ddog hosts flaps` command, listing hosts with repeated agent
+//! restarts or reporting gaps.
+
+use datadog_api_client::datadog::Configuration;
+use futures_util::TryStreamExt;
+
+use crate::cli::TimeRangeRelativeOnly;
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::{EventsClient, HostsClient, MetricsClient};
+use ddog::error::AppError;
+use ddog::flaps::{AGENT_RUNNING, report};
+use ddog::gaps::find_gaps;
+use ddog::time::parse_to_unix_seconds;
+
+/// Rollup for the agent metric, in seconds.
+///
+/// Fixing it keeps gaps comparable across ranges; Datadog would otherwise
+/// roll a week up to points too coarse to show short gaps.
+const ROLLUP_SECONDS: i64 = 300;
+
+/// Executes the hosts flaps command.
+///
+/// Fetches the host list, agent start events, and the agent metric
+/// concurrently, then writes one record per unstable host.
+pub async fn run(
+    config: Configuration,
+    scope: Option<String>,
+    min_flaps: usize,
+    time_range: TimeRangeRelativeOnly,
+    mut writer: RecordWriter,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let from = parse_to_unix_seconds(&time_range.from)?;
+    let to = parse_to_unix_seconds(&time_range.to)?;
+
+    let hosts_client = HostsClient::new(config.clone());
+    let events_client = EventsClient::new(config.clone());
+    let metrics_client = MetricsClient::new(config);
+
+    let host_filter = scope.as_deref().map(|s| s.replace(',', " "));
+    let query = format!(
+        "max:{}{{{}}} by {{host}}.rollup(max, {})",
+        AGENT_RUNNING,
+        scope.as_deref().unwrap_or("*"),
+        ROLLUP_SECONDS
+    );
+    logger.log(&format!("Agent metric query: {}", query));
+
+    let (hosts, starts, points) = tokio::join!(
+        hosts_client.list(host_filter.as_deref(), from),
+        events_client
+            .agent_starts(&time_range.from, &time_range.to)
+            .try_collect::<Vec<_>>(),
+        metrics_client
+            .query(&query, from, to)
+            .try_collect::<Vec<_>>(),
+    );
+    let (hosts, starts, points) = (hosts?, starts?, points?);
+    logger.log(&format!(
+        "Found {} host(s), {} agent start(s), {} metric point(s)",
+        hosts.len(),
+        starts.len(),
+        points.len()
+    ));
+
+    let gaps = find_gaps(&points, from, to, Some(ROLLUP_SECONDS));
+    let flaps = report(&hosts, &starts, &gaps, min_flaps, scope.is_some());
+    for host in &flaps {
+        writer.write(host)?;
+    }
+
+    logger.log(&format!("Reported {} unstable host(s)", flaps.len()));
+    Ok(())
+}
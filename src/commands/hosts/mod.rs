@@ -0,0 +1,3 @@
+//! Hosts domain commands.
+
+pub mod flaps;
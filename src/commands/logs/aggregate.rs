@@ -0,0 +1,34 @@
+//! Logs aggregate command implementation.
+//!
+//! Handles the `ddog logs aggregate` command, counting logs per time bucket
+//! and facet value.
+
+use crate::cli::{Aggregation, TimeRange};
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::LogsClient;
+use ddog::error::AppError;
+
+/// Executes the logs aggregate command.
+pub async fn run(
+    client: LogsClient,
+    query: String,
+    time_range: TimeRange,
+    aggregation: Aggregation,
+    indexes: Vec<String>,
+    mut writer: RecordWriter,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let points = client
+        .volume(
+            &query,
+            &time_range.from,
+            &time_range.to,
+            indexes,
+            &aggregation.grouping(),
+        )
+        .await?;
+
+    let title = format!("Logs matching `{}` by {}", query, aggregation.group_by);
+    crate::commands::aggregate::write(&points, &aggregation, &title, &mut writer, &logger)
+}
@@ -0,0 +1,208 @@
+//! Interactive result browser for `ddog logs search --interactive`.
+//!
+//! Loads the matching logs, then hands the terminal to a [`Browser`]. The
+//! terminal (`/dev/tty`, so stdout may be redirected) is switched to raw mode
+//! on the alternate screen and restored when the browser exits, even on error.
+
+use futures_util::StreamExt;
+
+use super::search::search_error;
+use crate::cli::{LogsSource, Pagination, TimeRange};
+use crate::logging::VerboseLogger;
+use crate::output::{TextOptions, render_line};
+use ddog::anonymize::Anonymizer;
+use ddog::browser::Browser;
+use ddog::client::LogsClient;
+use ddog::error::AppError;
+use ddog::links::DatadogLinks;
+use ddog::time::Timezone;
+
+/// Presentation settings for the browser.
+pub struct BrowseOptions {
+    /// Datadog UI links for opening records
+    pub links: DatadogLinks,
+
+    /// Timezone to show timestamps in; `None` keeps them as returned
+    pub timezone: Option<Timezone>,
+
+    /// Pseudonymizes records before they are shown
+    pub anonymizer: Option<Anonymizer>,
+}
+
+/// Executes an interactive logs search.
+///
+/// Fetches up to the limit of matching logs, then browses them until the
+/// user quits. Nothing is written to stdout.
+pub async fn run(
+    client: LogsClient,
+    query: String,
+    time_range: TimeRange,
+    pagination: Pagination,
+    source: LogsSource,
+    options: BrowseOptions,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let mut stream = client
+        .search(&source.request(&query, &time_range))
+        .take_records(usize::try_from(pagination.limit).unwrap_or(usize::MAX));
+
+    let mut records = Vec::new();
+    while let Some(result) = stream.next().await {
+        let log = result.map_err(|e| search_error(e, &logger))?;
+        let mut record = serde_json::to_value(log)?;
+        if let Some(anonymizer) = &options.anonymizer {
+            anonymizer.apply(&mut record);
+        }
+        records.push(record);
+    }
+    logger.log(&format!("Loaded {} log(s)", records.len()));
+
+    if records.is_empty() {
+        eprintln!("No logs matched `{}`", query);
+        return Ok(());
+    }
+
+    let text_options = TextOptions {
+        links: None,
+        timezone: options.timezone,
+    };
+    let lines = records
+        .iter()
+        .map(|record| render_line(record, &text_options))
+        .collect();
+    terminal::browse(Browser::new(records, lines, options.links))
+}
+
+#[cfg(unix)]
+mod terminal {
+    use std::fs::{File, OpenOptions};
+    use std::io::{self, Read, Write};
+    use std::os::fd::AsRawFd;
+
+    use ddog::browser::{Action, Browser, base64, parse_keys};
+    use ddog::error::AppError;
+    use ddog::links::open_in_browser;
+
+    /// Runs the browser until the user quits.
+    pub fn browse(mut browser: Browser) -> Result<(), AppError> {
+        let mut tty = Tty::open()?;
+        let mut input = [0u8; 64];
+        loop {
+            let (width, height) = tty.size();
+            tty.draw(&browser, width, height)?;
+
+            let read = tty.file.read(&mut input)?;
+            if read == 0 {
+                return Ok(());
+            }
+            for key in parse_keys(&input[..read]) {
+                match browser.handle(key, height.saturating_sub(2)) {
+                    Action::Continue => {}
+                    Action::Quit => return Ok(()),
+                    Action::Copy(text) => tty.copy(&text)?,
+                    Action::Open(url) => {
+                        if let Err(e) = open_in_browser(&url) {
+                            browser.set_message(format!("Could not open {}: {}", url, e));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// The controlling terminal in raw mode on the alternate screen.
+    struct Tty {
+        file: File,
+        original: libc::termios,
+    }
+
+    impl Tty {
+        fn open() -> Result<Self, AppError> {
+            let file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open("/dev/tty")
+                .map_err(|e| AppError::Config(format!("--interactive needs a terminal: {}", e)))?;
+
+            let fd = file.as_raw_fd();
+            // SAFETY: termios is plain data, filled in by tcgetattr before use
+            let mut original: libc::termios = unsafe { std::mem::zeroed() };
+            // SAFETY: fd is an open terminal and the termios pointers are valid
+            unsafe {
+                if libc::tcgetattr(fd, &mut original) != 0 {
+                    return Err(io::Error::last_os_error().into());
+                }
+                let mut raw = original;
+                libc::cfmakeraw(&mut raw);
+                if libc::tcsetattr(fd, libc::TCSANOW, &raw) != 0 {
+                    return Err(io::Error::last_os_error().into());
+                }
+            }
+
+            let mut tty = Self { file, original };
+            // Alternate screen, hidden cursor
+            tty.file.write_all(b"\x1b[?1049h\x1b[?25l")?;
+            Ok(tty)
+        }
+
+        /// Terminal width and height, falling back to 80x24.
+        fn size(&self) -> (usize, usize) {
+            // SAFETY: winsize is plain data, filled in by the ioctl
+            let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+            // SAFETY: fd is an open terminal and size is a valid winsize
+            let ok = unsafe { libc::ioctl(self.file.as_raw_fd(), libc::TIOCGWINSZ, &mut size) };
+            if ok != 0 || size.ws_col == 0 || size.ws_row == 0 {
+                return (80, 24);
+            }
+            (size.ws_col as usize, size.ws_row as usize)
+        }
+
+        /// Paints a frame, highlighting the selected row.
+        fn draw(&mut self, browser: &Browser, width: usize, height: usize) -> io::Result<()> {
+            let frame = browser.render(width, height);
+            let mut out = String::from("\x1b[H");
+            for (row, line) in frame.iter().enumerate() {
+                if browser.cursor_row() == Some(row) {
+                    out.push_str(&format!("\x1b[7m{}\x1b[0m", line));
+                } else {
+                    out.push_str(line);
+                }
+                out.push_str("\x1b[K");
+                if row + 1 < frame.len() {
+                    out.push_str("\r\n");
+                }
+            }
+            self.file.write_all(out.as_bytes())?;
+            self.file.flush()
+        }
+
+        /// Copies text to the clipboard with an OSC 52 sequence.
+        fn copy(&mut self, text: &str) -> io::Result<()> {
+            write!(self.file, "\x1b]52;c;{}\x07", base64(text.as_bytes()))?;
+            self.file.flush()
+        }
+    }
+
+    impl Drop for Tty {
+        fn drop(&mut self) {
+            let _ = self.file.write_all(b"\x1b[?25h\x1b[?1049l");
+            let _ = self.file.flush();
+            // SAFETY: restores the settings read in `open` on the same fd
+            unsafe {
+                libc::tcsetattr(self.file.as_raw_fd(), libc::TCSANOW, &self.original);
+            }
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod terminal {
+    use ddog::browser::Browser;
+    use ddog::error::AppError;
+
+    pub fn browse(_browser: Browser) -> Result<(), AppError> {
+        Err(AppError::Config(
+            "--interactive is only supported on Unix terminals".to_string(),
+        ))
+    }
+}
@@ -0,0 +1,97 @@
+//! Logs check command implementation.
+//!
+//! Handles the `ddog logs check` command, gating a pipeline on the number of
+//! logs matching a query.
+
+use serde::Serialize;
+
+use crate::cli::TimeRange;
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::LogsClient;
+use ddog::error::AppError;
+use ddog::slack::{SlackMessage, SlackWebhook};
+use ddog::threshold::Threshold;
+
+/// What a check counts, and who hears about a failure.
+pub struct Check {
+    /// Datadog query string
+    pub query: String,
+
+    /// Bounds the count must be within
+    pub threshold: Threshold,
+
+    /// Log indexes to search
+    pub indexes: Vec<String>,
+
+    /// Webhook to notify when the check fails, with the Log Explorer URL
+    /// of the search
+    pub notify: Option<(SlackWebhook, String)>,
+}
+
+/// Result of a check.
+#[derive(Debug, Serialize)]
+struct CheckRecord<'a> {
+    query: &'a str,
+    from: &'a str,
+    to: &'a str,
+    count: u64,
+    #[serde(flatten)]
+    threshold: Threshold,
+    passed: bool,
+}
+
+/// Executes the logs check command.
+///
+/// Writes the result as a record either way, then fails with
+/// `AppError::CheckFailed` if the count is outside the threshold, after
+/// posting to the Slack webhook if there is one.
+pub async fn run(
+    client: LogsClient,
+    check: Check,
+    time_range: TimeRange,
+    mut writer: RecordWriter,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let Check {
+        query,
+        threshold,
+        indexes,
+        notify,
+    } = check;
+    let count = client
+        .count(&query, &time_range.from, &time_range.to, indexes)
+        .await?
+        .map_or(0, |count| count.round() as u64);
+    let outcome = threshold.check(count);
+    logger.log(&format!("Counted {} log(s)", count));
+
+    writer.write(&CheckRecord {
+        query: &query,
+        from: &time_range.from,
+        to: &time_range.to,
+        count,
+        threshold,
+        passed: outcome.is_ok(),
+    })?;
+    let Err(reason) = outcome else {
+        return Ok(());
+    };
+
+    if let Some((webhook, explorer_url)) = notify {
+        let message = SlackMessage::new("Log check failed")
+            .field("Query", format!("`{}`", query))
+            .field("Result", reason.as_str())
+            .field(
+                "Time range",
+                format!("{} to {}", time_range.from, time_range.to),
+            )
+            .link(explorer_url);
+        webhook.send(&message).await?;
+        logger.log("Posted the failure to Slack");
+    }
+    Err(AppError::CheckFailed(format!(
+        "{} (logs matching `{}`)",
+        reason, query
+    )))
+}
@@ -0,0 +1,72 @@
+//! Logs diff command implementation.
+//!
+//! Handles the `ddog logs diff` command, comparing per-group log counts
+//! between a baseline and a compare window.
+
+use crate::cli::TimeRange;
+use crate::logging::{self, VerboseLogger};
+use crate::output::RecordWriter;
+use ddog::client::{LogsClient, Ranking};
+use ddog::diff::diff;
+use ddog::error::AppError;
+use ddog::time::{Timezone, resolve_absolute};
+
+/// Executes the logs diff command.
+///
+/// `windows` are the baseline and compare windows, already resolved. Both are
+/// aggregated at once.
+pub async fn run(
+    client: LogsClient,
+    query: String,
+    windows: [TimeRange; 2],
+    ranking: Ranking,
+    indexes: Vec<String>,
+    mut writer: RecordWriter,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let [baseline, compare] = &windows;
+    let (baseline_values, compare_values) = tokio::join!(
+        client.top_values(
+            &query,
+            &baseline.from,
+            &baseline.to,
+            indexes.clone(),
+            &ranking
+        ),
+        client.top_values(&query, &compare.from, &compare.to, indexes, &ranking),
+    );
+
+    let deltas = diff(
+        &ranking.facet,
+        baseline_values?,
+        compare_values?,
+        ranking.limit as usize,
+    );
+    logger.log(&format!(
+        "Compared {} value(s) of {}",
+        deltas.len(),
+        ranking.facet
+    ));
+    for delta in &deltas {
+        writer.write(delta)?;
+    }
+    Ok(())
+}
+
+/// Warns when the windows differ in length, since their counts then aren't
+/// comparable as they are.
+pub fn warn_uneven(windows: &[TimeRange; 2], timezone: Timezone) -> Result<(), AppError> {
+    let mut lengths = [0; 2];
+    for (length, window) in lengths.iter_mut().zip(windows) {
+        let from = resolve_absolute(&window.from, timezone)?;
+        let to = resolve_absolute(&window.to, timezone)?;
+        *length = (to - from).num_seconds();
+    }
+    if lengths[0] != lengths[1] {
+        logging::warn(&format!(
+            "the baseline window is {}s long but the compare window is {}s; counts are not normalized",
+            lengths[0], lengths[1]
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,43 @@
+//! Log exclusion filters command implementations.
+//!
+//! Handles `ddog logs exclusions list` and `test`.
+
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::ResourcesClient;
+use ddog::error::AppError;
+use ddog::exclusions::preview;
+
+/// Executes the logs exclusions list command.
+pub async fn list(
+    client: ResourcesClient,
+    mut writer: RecordWriter,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let exclusions = client.log_exclusions().await?;
+    logger.log(&format!("Found {} exclusion filter(s)", exclusions.len()));
+    for exclusion in &exclusions {
+        writer.write(exclusion)?;
+    }
+    Ok(())
+}
+
+/// Executes the logs exclusions test command.
+pub async fn test(
+    client: ResourcesClient,
+    query: &str,
+    mut writer: RecordWriter,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let exclusions = client.log_exclusions().await?;
+    let matches = preview(query, &exclusions);
+    logger.log(&format!(
+        "{} of {} exclusion filter(s) may drop logs matching the query",
+        matches.len(),
+        exclusions.len()
+    ));
+    for exclusion in &matches {
+        writer.write(exclusion)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,75 @@
+//! Logs facets command implementation.
+//!
+//! Handles the `ddog logs facets` command, discovering the facets of matching
+//! logs from a sample and summarizing each with the aggregation API.
+
+use futures_util::{StreamExt, TryStreamExt};
+
+use super::search::search_error;
+use crate::cli::{FacetDiscovery, TimeRange};
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::{LogsClient, Measure, Ranking};
+use ddog::error::AppError;
+use ddog::facets::{FacetSummary, discover};
+
+/// Facets summarized at once.
+const CONCURRENCY: usize = 4;
+
+/// Executes the logs facets command.
+///
+/// Writes one summary per discovered facet, most common in the sample first.
+pub async fn run(
+    client: LogsClient,
+    query: String,
+    time_range: TimeRange,
+    discovery: FacetDiscovery,
+    indexes: Vec<String>,
+    mut writer: RecordWriter,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let (from, to) = (&time_range.from, &time_range.to);
+    let sample: Vec<serde_json::Value> = client
+        .search(&time_range.logs_request(&query, &indexes))
+        .take(discovery.sample)
+        .map_err(|e| search_error(e, &logger))
+        .and_then(|log| async move { Ok(serde_json::to_value(log)?) })
+        .try_collect()
+        .await?;
+    logger.log(&format!("Sampled {} log(s)", sample.len()));
+
+    let mut candidates = discover(&sample);
+    candidates.truncate(discovery.max_facets);
+
+    let summaries = futures_util::stream::iter(candidates)
+        .map(|candidate| {
+            let indexes = indexes.clone();
+            let client = &client;
+            let query = &query;
+            let sampled = sample.len();
+            let ranking = Ranking {
+                facet: candidate.facet.clone(),
+                limit: discovery.top,
+                measure: Measure::Count,
+            };
+            async move {
+                let (top, cardinality) = tokio::join!(
+                    client.top_values(query, from, to, indexes.clone(), &ranking),
+                    client.cardinality(query, from, to, indexes, &candidate.facet),
+                );
+                Ok::<_, AppError>(FacetSummary {
+                    coverage: candidate.seen as f64 / sampled as f64,
+                    cardinality: cardinality?.map(|c| c as u64),
+                    top: top?,
+                    facet: candidate.facet,
+                })
+            }
+        })
+        .buffered(CONCURRENCY);
+    let mut summaries = std::pin::pin!(summaries);
+
+    while let Some(summary) = summaries.try_next().await? {
+        writer.write(&summary)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,88 @@
+//! Log-based metrics command implementations.
+//!
+//! Handles `ddog logs metrics list`, `create`, and `delete`. Creating and
+//! deleting ask for confirmation and are recorded in the audit log, like
+//! `ddog api post`.
+
+use serde_json::json;
+
+use crate::commands::write::{confirm, read_body, record};
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::{LogMetricsClient, parse_log_metric};
+use ddog::error::AppError;
+
+/// Executes the logs metrics list command.
+pub async fn list(
+    client: LogMetricsClient,
+    mut writer: RecordWriter,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let metrics = client.list().await?;
+    logger.log(&format!("Found {} log-based metric(s)", metrics.len()));
+    for metric in &metrics {
+        writer.write(metric)?;
+    }
+    Ok(())
+}
+
+/// Executes the logs metrics create command.
+///
+/// Writes the created metric, or the request itself with `dry_run`.
+pub async fn create(
+    client: LogMetricsClient,
+    definition: &str,
+    dry_run: bool,
+    yes: bool,
+    mut writer: RecordWriter,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let definition: serde_json::Value = serde_json::from_str(&read_body(definition)?)
+        .map_err(|e| AppError::Config(format!("Metric definition is not valid JSON: {}", e)))?;
+    let request = parse_log_metric(definition)?;
+    let body = serde_json::to_value(&request)?;
+    let url = client.url(None);
+
+    if dry_run {
+        writer.write(&json!({"method": "POST", "url": url, "body": body}))?;
+        return Ok(());
+    }
+    if !yes && !confirm("POST", &url, Some(&body))? {
+        eprintln!("Not sent");
+        return Ok(());
+    }
+
+    logger.log(&format!("Creating log-based metric {}", request.data.id));
+    let result = client.create(request).await;
+    record("POST", &url, &result, &logger);
+    writer.write(&result?)?;
+    Ok(())
+}
+
+/// Executes the logs metrics delete command.
+pub async fn delete(
+    client: LogMetricsClient,
+    id: &str,
+    dry_run: bool,
+    yes: bool,
+    mut writer: RecordWriter,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let url = client.url(Some(id));
+
+    if dry_run {
+        writer.write(&json!({"method": "DELETE", "url": url}))?;
+        return Ok(());
+    }
+    if !yes && !confirm("DELETE", &url, None)? {
+        eprintln!("Not sent");
+        return Ok(());
+    }
+
+    logger.log(&format!("Deleting log-based metric {}", id));
+    let result = client.delete(id).await;
+    record("DELETE", &url, &result, &logger);
+    result?;
+    writer.write(&json!({"id": id, "deleted": true}))?;
+    Ok(())
+}
@@ -1,3 +1,14 @@
 //! Logs domain commands.
 
+pub mod aggregate;
+pub mod browse;
+pub mod check;
+pub mod diff;
+pub mod exclusions;
+pub mod facets;
+pub mod metrics;
+pub mod patterns;
+pub mod schema;
 pub mod search;
+pub mod stats;
+pub mod top;
@@ -0,0 +1,126 @@
+//! Logs patterns command implementation.
+//!
+//! Handles the `ddog logs patterns` command, grouping the messages of
+//! matching logs into patterns with their counts.
+
+use futures_util::TryStreamExt;
+
+use crate::logging::{self, VerboseLogger};
+use crate::output::RecordWriter;
+use ddog::client::{LogsClient, LogsSearchRequest};
+use ddog::error::AppError;
+use ddog::patterns::{PatternCount, PatternCounter};
+
+/// Executes the logs patterns command.
+///
+/// Streams up to `limit` logs (0 for all of them) and writes the `top` most
+/// frequent patterns of their messages (0 for all of them), most frequent
+/// first. Logs without a message are skipped.
+pub async fn run(
+    client: LogsClient,
+    request: LogsSearchRequest,
+    limit: u64,
+    top: usize,
+    mut writer: RecordWriter,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let (patterns, fetched) = count(&client, &request, limit).await?;
+    logger.log(&format!("Fetched {} log(s)", fetched));
+    if limit > 0 && fetched == limit {
+        logging::warn(&format!(
+            "stopped at --limit {} logs; counts cover only the logs fetched",
+            limit
+        ));
+    }
+
+    logger.log(&format!("Found {} pattern(s)", patterns.len()));
+    for pattern in top_patterns(&patterns, top) {
+        writer.write(pattern)?;
+    }
+    Ok(())
+}
+
+/// Counts the patterns of the messages of up to `limit` logs (0 for all of
+/// them) as the search yields them, so only the patterns are held in memory.
+///
+/// Returns the patterns, most frequent first, and the number of logs fetched.
+async fn count(
+    client: &LogsClient,
+    request: &LogsSearchRequest,
+    limit: u64,
+) -> Result<(Vec<PatternCount>, u64), AppError> {
+    let mut logs = client.search(request).take_records(limit as usize);
+    let mut counter = PatternCounter::default();
+    let mut fetched = 0;
+    while let Some(log) = logs.try_next().await? {
+        fetched += 1;
+        if let Some(message) = log.attributes.as_ref().and_then(|a| a.message.as_deref()) {
+            counter.add(message);
+        }
+    }
+    Ok((counter.into_patterns(), fetched))
+}
+
+/// The `top` most frequent of `patterns` (0 for all of them).
+fn top_patterns(patterns: &[PatternCount], top: usize) -> &[PatternCount] {
+    match top {
+        0 => patterns,
+        top => &patterns[..top.min(patterns.len())],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ddog::client::MockTransport;
+    use serde_json::json;
+
+    fn log(message: &str) -> serde_json::Value {
+        json!({"id": message, "type": "log", "attributes": {"message": message}})
+    }
+
+    #[tokio::test]
+    async fn test_count_limit_and_top() {
+        let path = "/api/v2/logs/events/search";
+        let transport = MockTransport::new()
+            .respond(
+                "POST",
+                path,
+                200,
+                json!({
+                    "data": [log("timeout after 30s"), log("payment declined")],
+                    "meta": {"page": {"after": "2"}},
+                }),
+            )
+            .respond(
+                "POST",
+                path,
+                200,
+                json!({
+                    "data": [log("timeout after 45s"), log("payment declined")],
+                    "meta": {"page": {"after": "3"}},
+                }),
+            );
+        let client = LogsClient::builder()
+            .api_key("test")
+            .app_key("test")
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+        let request = LogsSearchRequest::builder().page_limit(2).build();
+
+        // --limit 3 stops within the second page, before a third is fetched
+        let (patterns, fetched) = count(&client, &request, 3).await.unwrap();
+        assert_eq!(fetched, 3);
+        assert_eq!(transport.requests().len(), 2);
+        assert_eq!(patterns.len(), 2);
+        assert_eq!(patterns[0].pattern, "timeout after <*>");
+        assert_eq!(patterns[0].count, 2);
+        assert_eq!(patterns[1].count, 1);
+
+        // --top 1 keeps the most frequent, --top 0 all of them
+        assert_eq!(top_patterns(&patterns, 1), &patterns[..1]);
+        assert_eq!(top_patterns(&patterns, 0).len(), 2);
+        assert_eq!(top_patterns(&patterns, 5).len(), 2);
+    }
+}
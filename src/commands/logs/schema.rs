@@ -0,0 +1,51 @@
+//! Logs schema command implementation.
+//!
+//! Handles the `ddog logs schema` command, inferring a JSON Schema from a
+//! sample of the matching logs.
+
+use std::io::Write;
+
+use futures_util::TryStreamExt;
+
+use super::search::search_error;
+use crate::logging::{self, VerboseLogger};
+use crate::output;
+use ddog::client::{LogsClient, LogsSearchRequest};
+use ddog::error::AppError;
+use ddog::schema::SchemaInference;
+
+/// Executes the logs schema command.
+///
+/// Fetches up to `sample` logs, shaped as an export with the same output
+/// transforms would write them, and writes their schema to stdout.
+pub async fn run(
+    client: LogsClient,
+    request: LogsSearchRequest,
+    sample: u64,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let mut inference = SchemaInference::new();
+    let mut logs = client
+        .search(&request)
+        .take_records(sample as usize)
+        .map_err(|e| search_error(e, &logger));
+    while let Some(log) = logs.try_next().await? {
+        match output::transform(&log)? {
+            Some(transformed) => inference.add(&transformed),
+            None => inference.add(&serde_json::to_value(log)?),
+        }
+    }
+    logger.log(&format!("Sampled {} log(s)", inference.samples()));
+    if inference.samples() == 0 {
+        logging::warn("no logs matched; the schema is empty");
+    } else {
+        output::count_record();
+    }
+
+    let mut schema = inference.finish();
+    schema["description"] = format!("Inferred from {} log(s)", inference.samples()).into();
+    let mut stdout = std::io::stdout().lock();
+    serde_json::to_writer_pretty(&mut stdout, &schema)?;
+    writeln!(stdout)?;
+    Ok(())
+}
@@ -1,127 +1,305 @@
 //! Logs search command implementation.
 //!
-//! Handles the `ddog logs search` command, streaming log results to stdout.
+//! Handles the `ddog logs search` command, streaming log results to stdout,
+//! and its `--batch` mode running many searches into one output.
 
 use futures_util::StreamExt;
+use serde::Serialize;
+use std::collections::BTreeMap;
 
-use crate::cli::{Pagination, TimeRange};
-use crate::logging::VerboseLogger;
-use crate::output::NdjsonWriter;
-use ddog::client::LogsClient;
+use crate::cli::{LogsSource, Pagination, TimeRange};
+use crate::commands::watch::{self, EventInfo, Watcher};
+use crate::interrupt;
+use crate::logging::{self, VerboseLogger};
+use crate::routing::SearchSink;
+use datadog_api_client::datadogV2::model::Log;
+use ddog::client::{LogsClient, MAX_LOGS_PAGE_SIZE, RestrictionsClient};
 use ddog::error::AppError;
+use ddog::watch::is_severe;
+
+/// One resolved search from a `--batch` file.
+pub struct BatchSearch {
+    /// Job id tagged onto the search's records
+    pub id: String,
+
+    /// Query as sent to Datadog
+    pub query: String,
+
+    /// Resolved time range
+    pub time_range: TimeRange,
+}
+
+/// A record tagged with the batch job that produced it.
+#[derive(Serialize)]
+pub struct BatchRecord<'a, T> {
+    /// Id of the batch job
+    pub query_id: &'a str,
+
+    #[serde(flatten)]
+    pub record: T,
+}
 
 /// Executes the logs search command.
 ///
-/// Streams matching log records to stdout as NDJSON until the limit is reached
-/// or all results are exhausted.
+/// Streams matching log records to stdout until the limit is reached
+/// or all results are exhausted, and returns how many were written.
 pub async fn run(
     client: LogsClient,
     query: String,
     time_range: TimeRange,
     pagination: Pagination,
-    indexes: Vec<String>,
+    source: LogsSource,
+    mut sink: SearchSink,
     logger: VerboseLogger,
-) -> Result<(), AppError> {
-    let mut writer = NdjsonWriter::new();
-    let mut stream =
-        std::pin::pin!(client.search(&query, &time_range.from, &time_range.to, indexes));
+) -> Result<u64, AppError> {
+    let mut request = source.request(&query, &time_range);
+    request.page_limit = pagination.page_limit(MAX_LOGS_PAGE_SIZE);
+    let interrupt = interrupt::ctrl_c_token();
+    let mut stream = client.search(&request);
+    if pagination.prefetches() {
+        stream = stream.prefetch(pagination.buffer(request.page_limit));
+    }
+    let mut stream = stream.cancel_with(interrupt.clone());
+    let mut budget = pagination.budget();
+    let mut sampler = pagination.sampler();
     let mut count: u64 = 0;
+    let mut latest = None;
+    let mut truncated = None;
 
     while let Some(result) = stream.next().await {
-        let log = result.map_err(|e| {
-            let msg = format!("{}", e);
-            logger.log_error(&msg, "logs API request");
-
-            if msg.contains("401") {
-                AppError::Auth(format!(
-                    "Authentication failed (401): Invalid API or App key. {}",
-                    msg
-                ))
-            } else if msg.contains("403") || msg.contains("Forbidden") {
-                AppError::Auth(format!(
-                    "Access denied (403): Your API key may not have permission to access logs. {}",
-                    msg
-                ))
-            } else if msg.contains("400") || msg.contains("Bad Request") {
-                AppError::InvalidQuery(msg)
+        // Pages are checked first, so a failed page past the cap truncates
+        if let Err(cap) = budget.admit_page(stream.page()) {
+            logger.log(&format!("Reached {}", cap));
+            truncated = Some(cap);
+            break;
+        }
+        let log = result.map_err(|e| search_error(e, &logger))?;
+
+        // Records left out of a sample still count towards their page
+        if sampler.as_mut().is_none_or(|s| s.keep()) {
+            let bytes = if budget.counts_bytes() {
+                serde_json::to_vec(&log)?.len() as u64 + 1
             } else {
-                AppError::Api(msg)
+                0
+            };
+            if let Err(cap) = budget.admit(bytes) {
+                logger.log(&format!("Reached {}", cap));
+                truncated = Some(cap);
+                break;
             }
-        })?;
 
-        writer.write(&log)?;
-        count += 1;
+            let timestamp = log.attributes.as_ref().and_then(|a| a.timestamp);
+            sink.write(&log)?;
+            sink.advance(timestamp);
+            latest = timestamp.or(latest);
+            count += 1;
+        }
 
-        if pagination.limit > 0 && count >= pagination.limit {
-            logger.log(&format!("Reached limit of {} results", pagination.limit));
+        if let Some(cap) = budget.exhausted() {
+            logger.log(&format!("Reached {}", cap));
             break;
         }
     }
 
     logger.log(&format!("Returned {} log(s)", count));
-    Ok(())
+    // Stopped by Ctrl-C rather than by a cap or the end of the results
+    let interrupted =
+        truncated.is_none() && budget.exhausted().is_none() && interrupt.is_cancelled();
+
+    let description = format!(
+        "logs matching `{}` ({} to {})",
+        query, time_range.from, time_range.to
+    );
+    sink.finish(&description, &logger).await?;
+
+    match truncated {
+        Some(cap) => Err(cap.truncated("logs")),
+        None if interrupted => Err(interrupt::interrupted("logs", count, latest)),
+        None => Ok(count),
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use ddog::error::AppError;
+/// Executes the logs search command with `--watch`.
+///
+/// Re-runs the search until Ctrl-C, writing each log once.
+pub async fn watch(
+    client: LogsClient,
+    query: String,
+    time_range: TimeRange,
+    source: LogsSource,
+    mut sink: SearchSink,
+    watcher: Watcher,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let search = |from: String| {
+        let range = time_range.with_bounds(Some(&from), None);
+        client
+            .search(&source.request(&query, &range))
+            .map(|result| result.map_err(|e| search_error(e, &logger)))
+    };
+    watch::search(
+        search,
+        describe,
+        &time_range,
+        watcher,
+        &mut sink,
+        "logs",
+        &logger,
+    )
+    .await?;
 
-    fn parse_error_message(msg: &str) -> AppError {
-        if msg.contains("401") || msg.contains("403") || msg.contains("Forbidden") {
-            AppError::Auth(format!("Authentication failed: {}", msg))
-        } else if msg.contains("400") || msg.contains("Bad Request") {
-            AppError::InvalidQuery(msg.to_string())
-        } else {
-            AppError::Api(msg.to_string())
-        }
+    let description = format!("logs matching `{}` while watching", query);
+    sink.finish(&description, &logger).await
+}
+
+/// Change detection details of a log.
+fn describe(log: &Log) -> EventInfo<'_> {
+    let attributes = log.attributes.as_ref();
+    let status = attributes.and_then(|a| a.status.as_deref());
+    EventInfo {
+        id: log.id.as_deref(),
+        timestamp: attributes.and_then(|a| a.timestamp),
+        message: attributes.and_then(|a| a.message.as_deref()),
+        severe: status.is_some_and(is_severe),
     }
+}
 
-    #[test]
-    fn test_error_parsing_401() {
-        let error = parse_error_message("401 Unauthorized");
-        assert!(matches!(error, AppError::Auth(_)));
-        assert_eq!(error.exit_code(), 2);
+/// Executes a `--batch` logs search.
+///
+/// Runs up to `concurrency` searches at once, applying the limit to each, and
+/// writes every record tagged with its job's id. Records from concurrent
+/// searches are interleaved as they arrive.
+pub async fn run_batch(
+    client: LogsClient,
+    searches: Vec<BatchSearch>,
+    pagination: Pagination,
+    source: LogsSource,
+    concurrency: usize,
+    mut sink: SearchSink,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let limit = usize::try_from(pagination.limit).unwrap_or(usize::MAX);
+    let interrupt = interrupt::ctrl_c_token();
+    let jobs = futures_util::stream::iter(&searches).map(|search| {
+        logger.log_request(
+            "logs",
+            &search.query,
+            &search.time_range.from,
+            &search.time_range.to,
+        );
+        let mut request = source.request(&search.query, &search.time_range);
+        request.page_limit = pagination.page_limit(MAX_LOGS_PAGE_SIZE);
+        let logs = client
+            .search(&request)
+            .take_records(limit)
+            .cancel_with(interrupt.clone())
+            .map(move |result| (search.id.as_str(), result));
+        Box::pin(logs)
+    });
+    let mut stream = std::pin::pin!(jobs.flatten_unordered(concurrency));
+    let mut counts: BTreeMap<&str, u64> = BTreeMap::new();
+
+    while let Some((query_id, result)) = stream.next().await {
+        let log =
+            result.map_err(|e| search_error(e.context(&format!("query {}", query_id)), &logger))?;
+        sink.write(&BatchRecord {
+            query_id,
+            record: log,
+        })?;
+        *counts.entry(query_id).or_default() += 1;
     }
 
-    #[test]
-    fn test_error_parsing_403() {
-        let error = parse_error_message("403 Forbidden");
-        assert!(matches!(error, AppError::Auth(_)));
-        assert_eq!(error.exit_code(), 2);
+    for search in &searches {
+        let count = counts.get(search.id.as_str()).copied().unwrap_or(0);
+        logger.log(&format!("Query {} returned {} log(s)", search.id, count));
+    }
+
+    let description = format!("logs for {} batch queries", searches.len());
+    sink.finish(&description, &logger).await?;
+
+    if interrupt.is_cancelled() {
+        return Err(interrupt::interrupted("logs", counts.values().sum(), None));
+    }
+    Ok(())
+}
+
+/// Warns that a search may have found nothing because restriction queries
+/// hide every log not matching them from the application key.
+///
+/// Best effort: a failed lookup is only logged.
+pub async fn warn_if_restricted(restrictions: &RestrictionsClient, logger: &VerboseLogger) {
+    match restrictions.restriction_queries().await {
+        Ok(queries) if !queries.is_empty() => logging::warn(&format!(
+            "no logs matched, but your roles only let you read logs matching {}, \
+             so others are hidden rather than missing",
+            queries.join(" or ")
+        )),
+        Ok(_) => {}
+        Err(e) => logger.log(&format!("Failed to look up restriction queries: {}", e)),
     }
+}
+
+/// Logs a failed logs search, already classified by its HTTP status.
+pub fn search_error(error: AppError, logger: &VerboseLogger) -> AppError {
+    logger.log_error(&error.to_string(), "logs API request");
+    error
+}
 
-    #[test]
-    fn test_error_parsing_forbidden() {
-        let error = parse_error_message("Forbidden access");
+#[cfg(test)]
+mod tests {
+    use ddog::client::{LogsClient, LogsSearchRequest, MockTransport};
+    use ddog::error::AppError;
+    use serde_json::{Value, json};
+
+    /// The error a search fails with when the API answers `status` with `body`.
+    async fn search_error(status: u16, body: Value) -> AppError {
+        let transport =
+            MockTransport::new().respond("POST", "/api/v2/logs/events/search", status, body);
+        let client = LogsClient::builder()
+            .api_key("test")
+            .app_key("test")
+            .transport(transport)
+            .build()
+            .unwrap();
+        client
+            .fetch(&LogsSearchRequest::builder().build(), 1)
+            .await
+            .unwrap_err()
+    }
+
+    #[tokio::test]
+    async fn test_error_parsing_401() {
+        let error = search_error(401, json!({"errors": ["Unauthorized"]})).await;
         assert!(matches!(error, AppError::Auth(_)));
         assert_eq!(error.exit_code(), 2);
     }
 
-    #[test]
-    fn test_error_parsing_400() {
-        let error = parse_error_message("400 Bad Request");
-        assert!(matches!(error, AppError::InvalidQuery(_)));
-        assert_eq!(error.exit_code(), 4);
+    #[tokio::test]
+    async fn test_error_parsing_403() {
+        let error = search_error(403, json!({"errors": ["Forbidden"]})).await;
+        assert!(matches!(error, AppError::Forbidden(_)));
+        assert_eq!(error.exit_code(), 11);
     }
 
-    #[test]
-    fn test_error_parsing_bad_request() {
-        let error = parse_error_message("Bad Request: invalid syntax");
-        assert!(matches!(error, AppError::InvalidQuery(_)));
+    #[tokio::test]
+    async fn test_error_parsing_400() {
+        let error = search_error(400, json!({"errors": ["unexpected ')'"]})).await;
+        assert!(matches!(&error, AppError::InvalidQuery(msg) if msg.contains("unexpected ')'")));
         assert_eq!(error.exit_code(), 4);
     }
 
-    #[test]
-    fn test_error_parsing_generic_api_error() {
-        let error = parse_error_message("500 Internal Server Error");
-        assert!(matches!(error, AppError::Api(_)));
-        assert_eq!(error.exit_code(), 3);
+    #[tokio::test]
+    async fn test_error_parsing_429() {
+        let error = search_error(429, json!({"errors": ["Too many requests"]})).await;
+        assert!(matches!(error, AppError::RateLimited(_)));
+        assert_eq!(error.exit_code(), 12);
     }
 
-    #[test]
-    fn test_error_parsing_network_error() {
-        let error = parse_error_message("Connection timeout");
-        assert!(matches!(error, AppError::Api(_)));
+    #[tokio::test]
+    async fn test_error_parsing_generic_api_error() {
+        let error = search_error(500, json!({"errors": ["Internal Server Error"]})).await;
+        assert!(matches!(error, AppError::Unavailable(_)));
         assert_eq!(error.exit_code(), 3);
+        assert!(error.context("query q1").is_retryable());
     }
 }
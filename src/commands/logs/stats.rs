@@ -0,0 +1,47 @@
+//! Logs stats command implementation.
+//!
+//! Handles the `ddog logs stats` command, summarizing one field of the
+//! matching logs client-side.
+
+use futures_util::TryStreamExt;
+
+use super::search::search_error;
+use crate::logging::{self, VerboseLogger};
+use crate::output::RecordWriter;
+use ddog::client::{LogsClient, LogsSearchRequest};
+use ddog::error::AppError;
+use ddog::stats::FieldCollector;
+
+/// Executes the logs stats command.
+///
+/// Streams up to `limit` logs (0 for all of them) and writes one summary of
+/// `field` over them.
+pub async fn run(
+    client: LogsClient,
+    request: LogsSearchRequest,
+    field: String,
+    limit: u64,
+    mut writer: RecordWriter,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let mut collector = FieldCollector::new(&field);
+    let mut logs = client
+        .search(&request)
+        .take_records(limit as usize)
+        .map_err(|e| search_error(e, &logger));
+    let mut fetched = 0u64;
+    while let Some(log) = logs.try_next().await? {
+        collector.add(&serde_json::to_value(log)?);
+        fetched += 1;
+    }
+    logger.log(&format!("Fetched {} log(s)", fetched));
+    if limit > 0 && fetched == limit {
+        logging::warn(&format!(
+            "stopped at --limit {} logs; statistics cover only the logs fetched",
+            limit
+        ));
+    }
+
+    writer.write(&collector.finish())?;
+    Ok(())
+}
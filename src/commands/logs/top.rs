@@ -0,0 +1,48 @@
+//! Logs top command implementation.
+//!
+//! Handles the `ddog logs top` command, ranking the most frequent values of a
+//! facet among matching logs.
+
+use crate::cli::TimeRange;
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::{LogsClient, Measure, Ranking};
+use ddog::error::AppError;
+use ddog::facets::rank;
+
+/// Executes the logs top command.
+///
+/// Counting by logs also fetches the total number of matching logs, so each
+/// value carries its share of the total.
+pub async fn run(
+    client: LogsClient,
+    query: String,
+    time_range: TimeRange,
+    ranking: Ranking,
+    indexes: Vec<String>,
+    mut writer: RecordWriter,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let (from, to) = (&time_range.from, &time_range.to);
+    let (values, total) = tokio::join!(
+        client.top_values(&query, from, to, indexes.clone(), &ranking),
+        async {
+            match ranking.measure {
+                Measure::Count => client.count(&query, from, to, indexes.clone()).await,
+                Measure::Unique(_) => Ok(None),
+            }
+        },
+    );
+
+    let ranked = rank(&ranking.facet, values?, total?);
+    logger.log(&format!(
+        "Ranked {} value(s) of {} by {}",
+        ranked.len(),
+        ranking.facet,
+        ranking.measure
+    ));
+    for value in &ranked {
+        writer.write(value)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,63 @@
+//! Metrics cardinality command implementation.
+//!
+//! Handles the `ddog metrics cardinality` command, reporting how many values
+//! each tag key of a metric has.
+
+use serde::Serialize;
+
+use crate::logging::{self, VerboseLogger};
+use crate::output::RecordWriter;
+use ddog::cardinality::{TagCardinality, tag_cardinality};
+use ddog::client::MetricsClient;
+use ddog::error::AppError;
+
+/// One tag key of the metric.
+#[derive(Debug, Serialize)]
+struct CardinalityRecord<'a> {
+    metric: &'a str,
+    #[serde(flatten)]
+    key: TagCardinality,
+}
+
+/// Executes the metrics cardinality command.
+///
+/// Writes one record per tag key, most values first, then summarizes the
+/// metric's estimated series on stderr. The summary is skipped if Datadog
+/// doesn't report volumes for the metric.
+pub async fn run(
+    client: MetricsClient,
+    metric: String,
+    mut writer: RecordWriter,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let tags = client.tags(&metric).await?;
+    logger.log(&format!("Fetched {} tag(s)", tags.len()));
+
+    let keys = tag_cardinality(&tags);
+    let key_count = keys.len();
+    for key in keys {
+        writer.write(&CardinalityRecord {
+            metric: &metric,
+            key,
+        })?;
+    }
+
+    match client.volumes(&metric).await {
+        Ok(volumes) => {
+            let series = match (volumes.distinct, volumes.ingested, volumes.indexed) {
+                (Some(distinct), _, _) => format!("{} distinct series", distinct),
+                (None, Some(ingested), Some(indexed)) => {
+                    format!("{} series ingested, {} indexed", ingested, indexed)
+                }
+                (None, None, Some(indexed)) => format!("{} series indexed", indexed),
+                _ => "no series volume reported".to_string(),
+            };
+            logging::notice(&format!(
+                "{}: {} tag key(s) in the last hour; {}",
+                metric, key_count, series
+            ));
+        }
+        Err(e) => logger.log(&format!("Skipping series volumes: {}", e)),
+    }
+    Ok(())
+}
@@ -0,0 +1,42 @@
+//! Metrics gaps command implementation.
+//!
+//! Handles the `ddog metrics gaps` command, reporting stretches where a
+//! metric's series had no points.
+
+use futures_util::TryStreamExt;
+
+use crate::cli::TimeRangeRelativeOnly;
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::MetricsClient;
+use ddog::error::AppError;
+use ddog::gaps::find_gaps;
+use ddog::time::parse_to_unix_seconds;
+
+/// Executes the metrics gaps command.
+///
+/// Writes one record per gap, ordered by series then start.
+pub async fn run(
+    client: MetricsClient,
+    query: String,
+    time_range: TimeRangeRelativeOnly,
+    interval: Option<i64>,
+    mut writer: RecordWriter,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let from_secs = parse_to_unix_seconds(&time_range.from)?;
+    let to_secs = parse_to_unix_seconds(&time_range.to)?;
+
+    let points: Vec<_> = client
+        .query(&query, from_secs, to_secs)
+        .try_collect()
+        .await?;
+    logger.log(&format!("Fetched {} metric point(s)", points.len()));
+
+    let gaps = find_gaps(&points, from_secs, to_secs, interval);
+    logger.log(&format!("Found {} gap(s)", gaps.len()));
+    for gap in &gaps {
+        writer.write(gap)?;
+    }
+    Ok(())
+}
@@ -7,7 +7,7 @@ use serde::Serialize;
 
 use crate::cli::TimeFrom;
 use crate::logging::VerboseLogger;
-use crate::output::NdjsonWriter;
+use crate::output::RecordWriter;
 use ddog::client::MetricsClient;
 use ddog::error::AppError;
 use ddog::time::parse_to_unix_seconds;
@@ -20,10 +20,11 @@ struct MetricName {
 
 /// Executes the metrics list command.
 ///
-/// Lists active metrics after the specified start time and streams them to stdout as NDJSON.
+/// Lists active metrics after the specified start time and streams them to stdout.
 pub async fn run(
     client: MetricsClient,
     time_from: TimeFrom,
+    mut writer: RecordWriter,
     logger: VerboseLogger,
 ) -> Result<(), AppError> {
     // Convert time string to Unix seconds
@@ -31,7 +32,6 @@ pub async fn run(
 
     logger.log(&format!("Listing active metrics from {}", from_secs));
 
-    let mut writer = NdjsonWriter::new();
     let mut stream = std::pin::pin!(client.list_active(from_secs));
     let mut count: u64 = 0;
 
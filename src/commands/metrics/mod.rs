@@ -1,4 +1,7 @@
 //! Metrics domain commands.
 
+pub mod cardinality;
+pub mod gaps;
 pub mod list;
 pub mod query;
+pub mod scalar;
@@ -1,27 +1,68 @@
 //! Metrics query command implementation.
 //!
-//! Handles the `ddog metrics query` command, streaming metric timeseries points to stdout.
+//! Handles the `ddog metrics query` command, streaming metric timeseries points to
+//! stdout as one `{metric, scope, tags, ts, value}` row per datapoint.
 
 use futures_util::StreamExt;
 
-use crate::cli::TimeRangeRelativeOnly;
+use crate::cli::{TimeRangeRelativeOnly, Viz};
+use crate::commands::aggregate::draw;
 use crate::logging::VerboseLogger;
-use crate::output::NdjsonWriter;
+use crate::output::RecordWriter;
 use ddog::client::MetricsClient;
 use ddog::error::AppError;
+use ddog::metric_query::{check_aggregator, is_percentile, parse_terms};
 use ddog::time::parse_to_unix_seconds;
+use ddog::timeseries::{PointRow, join_points, series_rows};
+use ddog::viz::Plot;
+
+/// How the metrics query command writes its results.
+#[derive(Debug, Clone, Copy)]
+pub struct QueryOutput {
+    /// Maximum number of points (or joined rows) to write; 0 for unlimited
+    pub limit: u64,
+
+    /// Write one row per timestamp with a column per series
+    pub join: bool,
+
+    /// Write one row per series with all of its points (`--format wide`)
+    pub wide: bool,
+
+    /// Chart to draw on stderr after the records
+    pub viz: Option<Viz>,
+}
 
 /// Executes the metrics query command.
 ///
-/// Queries metrics timeseries data and streams individual points to stdout as NDJSON
-/// until the limit is reached or all results are exhausted.
+/// Queries metrics timeseries data and streams individual points to stdout
+/// until the limit is reached or all results are exhausted. With `join`, all
+/// series are collected first and written as one row per timestamp instead;
+/// with `wide`, up to the limit of points are collected and written as one
+/// row per series.
+/// With `viz`, the written points are also charted on stderr.
+///
+/// A single query without formulas goes to the v1 query API; several queries,
+/// or any formulas, go to the v2 timeseries API, which computes the formulas.
 pub async fn run(
     client: MetricsClient,
-    query: String,
+    queries: Vec<String>,
+    formulas: Vec<String>,
     time_range: TimeRangeRelativeOnly,
-    limit: u64,
+    output: QueryOutput,
+    mut writer: RecordWriter,
     logger: VerboseLogger,
 ) -> Result<(), AppError> {
+    let QueryOutput {
+        limit,
+        join,
+        wide,
+        viz,
+    } = output;
+
+    for query in &queries {
+        check_aggregators(&client, query, &logger).await?;
+    }
+
     // Convert time strings to Unix seconds
     let from_secs = parse_to_unix_seconds(&time_range.from)?;
     let to_secs = parse_to_unix_seconds(&time_range.to)?;
@@ -31,14 +72,43 @@ pub async fn run(
         from_secs, to_secs
     ));
 
-    let mut writer = NdjsonWriter::new();
-    let mut stream = std::pin::pin!(client.query(&query, from_secs, to_secs));
+    let mut stream = match (queries.as_slice(), formulas.is_empty()) {
+        ([query], true) => client.query(query, from_secs, to_secs),
+        _ => client.query_formulas(&queries, &formulas, from_secs, to_secs),
+    };
     let mut count: u64 = 0;
 
+    if join {
+        let mut points = Vec::new();
+        while let Some(result) = stream.next().await {
+            points.push(result?);
+        }
+        for row in join_points(&points) {
+            writer.write(&row)?;
+            count += 1;
+
+            if limit > 0 && count >= limit {
+                logger.log(&format!("Reached limit of {} rows", limit));
+                break;
+            }
+        }
+        logger.log(&format!("Returned {} joined row(s)", count));
+        if let Some(viz) = viz {
+            draw(&Plot::from_metrics(&points), viz)?;
+        }
+        return Ok(());
+    }
+
+    let mut plotted = Vec::new();
     while let Some(result) = stream.next().await {
         let point = result?;
-        writer.write(&point)?;
+        if !wide {
+            writer.write(&PointRow::from(&point))?;
+        }
         count += 1;
+        if viz.is_some() || wide {
+            plotted.push(point);
+        }
 
         if limit > 0 && count >= limit {
             logger.log(&format!("Reached limit of {} results", limit));
@@ -46,6 +116,63 @@ pub async fn run(
         }
     }
 
-    logger.log(&format!("Returned {} metric point(s)", count));
+    if wide {
+        let series = series_rows(&plotted);
+        for row in &series {
+            writer.write(row)?;
+        }
+        logger.log(&format!(
+            "Returned {} metric point(s) in {} series",
+            count,
+            series.len()
+        ));
+    } else {
+        logger.log(&format!("Returned {} metric point(s)", count));
+    }
+    if let Some(viz) = viz {
+        draw(&Plot::from_metrics(&plotted), viz)?;
+    }
+    Ok(())
+}
+
+/// Rejects aggregators that are unknown or illegal for their metric's type.
+///
+/// Metadata is only fetched for percentile terms, since those are the only
+/// aggregators restricted by type. If metadata can't be fetched the term is
+/// let through and Datadog has the final say.
+async fn check_aggregators(
+    client: &MetricsClient,
+    query: &str,
+    logger: &VerboseLogger,
+) -> Result<(), AppError> {
+    let terms = parse_terms(query);
+    for term in &terms {
+        check_aggregator(term, None)?;
+    }
+
+    let mut checked: Vec<&str> = Vec::new();
+    for term in terms.iter().filter(|t| is_percentile(&t.aggregator)) {
+        if checked.contains(&term.metric.as_str()) {
+            continue;
+        }
+        checked.push(&term.metric);
+
+        match client.metric_type(&term.metric).await {
+            Ok(metric_type) => {
+                logger.log(&format!(
+                    "Metric {} has type {}",
+                    term.metric,
+                    metric_type.as_deref().unwrap_or("unknown")
+                ));
+                for same_metric in terms.iter().filter(|t| t.metric == term.metric) {
+                    check_aggregator(same_metric, metric_type.as_deref())?;
+                }
+            }
+            Err(e) => logger.log(&format!(
+                "Skipping aggregator check for {}: {}",
+                term.metric, e
+            )),
+        }
+    }
     Ok(())
 }
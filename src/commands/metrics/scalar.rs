@@ -0,0 +1,86 @@
+//! Metrics scalar command implementation.
+//!
+//! Handles the `ddog metrics scalar` command, reducing a metric query to a
+//! single value per group and optionally grading it against warning and
+//! critical levels.
+
+use serde::Serialize;
+
+use crate::cli::TimeRangeRelativeOnly;
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::{MetricsClient, Reducer};
+use ddog::error::AppError;
+use ddog::threshold::{CheckStatus, Levels};
+use ddog::time::parse_to_unix_seconds;
+
+/// One reduced value.
+#[derive(Debug, Serialize)]
+struct ScalarRecord<'a> {
+    query: &'a str,
+    reduce: String,
+    scope: String,
+    value: Option<f64>,
+    #[serde(flatten)]
+    levels: Levels,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<CheckStatus>,
+}
+
+/// Executes the metrics scalar command.
+///
+/// Writes one record per group, then, with levels set, fails with
+/// `AppError::CheckFailed` if any value is critical or unknown, or
+/// `AppError::CheckWarning` if any is past the warning level.
+pub async fn run(
+    client: MetricsClient,
+    query: String,
+    time_range: TimeRangeRelativeOnly,
+    reduce: Reducer,
+    levels: Levels,
+    mut writer: RecordWriter,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let from_secs = parse_to_unix_seconds(&time_range.from)?;
+    let to_secs = parse_to_unix_seconds(&time_range.to)?;
+
+    let values = client.scalar(&query, reduce, from_secs, to_secs).await?;
+    logger.log(&format!("Reduced to {} value(s)", values.len()));
+
+    let mut worst: Option<(CheckStatus, ScalarRecord)> = None;
+    for value in values {
+        let status = levels.status(value.value);
+        let record = ScalarRecord {
+            query: &query,
+            reduce: reduce.to_string(),
+            scope: value.scope,
+            value: value.value,
+            levels,
+            status: levels.is_set().then_some(status),
+        };
+        writer.write(&record)?;
+        if worst.as_ref().is_none_or(|(worst, _)| status > *worst) {
+            worst = Some((status, record));
+        }
+    }
+
+    let Some((status, record)) = worst.filter(|_| levels.is_set()) else {
+        return Ok(());
+    };
+    let context = format!("`{}` over {}", query, record.scope);
+    let value = record.value.unwrap_or_default();
+    match (status, levels.warn, levels.crit) {
+        (CheckStatus::Critical, _, Some(crit)) => Err(AppError::CheckFailed(format!(
+            "{} is past --crit {} ({})",
+            value, crit, context
+        ))),
+        (CheckStatus::Warning, Some(warn), _) => Err(AppError::CheckWarning(format!(
+            "{} is past --warn {} ({})",
+            value, warn, context
+        ))),
+        (CheckStatus::Unknown, _, _) => {
+            Err(AppError::CheckFailed(format!("no data ({})", context)))
+        }
+        _ => Ok(()),
+    }
+}
@@ -1,5 +1,25 @@
 //! Command implementations organized by domain.
 
+pub mod aggregate;
+pub mod api;
+pub mod bench;
+pub mod completions;
+pub mod count;
+pub mod docs;
+pub mod drift;
+pub mod explain;
+pub mod export;
+pub mod handoff;
+pub mod history;
+pub mod hosts;
 pub mod logs;
 pub mod metrics;
+pub mod monitors;
+pub mod open;
+pub mod query;
+pub mod repl;
+pub mod self_update;
 pub mod spans;
+pub mod telemetry;
+pub mod watch;
+pub mod write;
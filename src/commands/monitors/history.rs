@@ -0,0 +1,42 @@
+//! Monitor history command implementation.
+//!
+//! Handles the `ddog monitors history` command, pairing a monitor's alert and
+//! recovery events into episodes.
+
+use futures_util::StreamExt;
+
+use crate::cli::TimeRange;
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::EventsClient;
+use ddog::episodes::pair_episodes;
+use ddog::error::AppError;
+
+/// Executes the monitors history command.
+///
+/// Collects all of the monitor's events in the range, then writes one record
+/// per episode, ordered by start time.
+pub async fn run(
+    client: EventsClient,
+    monitor_id: i64,
+    time_range: TimeRange,
+    mut writer: RecordWriter,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let mut stream =
+        std::pin::pin!(client.monitor_events(monitor_id, &time_range.from, &time_range.to));
+    let mut events = Vec::new();
+
+    while let Some(result) = stream.next().await {
+        events.push(result?);
+    }
+    logger.log(&format!("Fetched {} monitor event(s)", events.len()));
+
+    let episodes = pair_episodes(monitor_id, &events);
+    for episode in &episodes {
+        writer.write(episode)?;
+    }
+
+    logger.log(&format!("Returned {} episode(s)", episodes.len()));
+    Ok(())
+}
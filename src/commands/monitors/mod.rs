@@ -0,0 +1,5 @@
+//! Monitors domain commands.
+
+pub mod history;
+pub mod noise_report;
+pub mod status;
@@ -0,0 +1,55 @@
+//! Monitor noise report command implementation.
+//!
+//! Handles the `ddog monitors noise-report` command, ranking monitors by how
+//! often they alert and how quickly they recover.
+
+use futures_util::{StreamExt, TryStreamExt};
+
+use crate::cli::TimeRange;
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::{EventsClient, MonitorsClient};
+use ddog::episodes::pair_episodes;
+use ddog::error::AppError;
+use ddog::noise::{MonitorNoise, rank};
+
+/// Number of monitors whose events are fetched concurrently.
+const CONCURRENT_MONITORS: usize = 4;
+
+/// Executes the monitors noise-report command.
+///
+/// Lists the monitors matching `tags`, pairs each one's events into episodes,
+/// and writes one summary per monitor, noisiest first.
+pub async fn run(
+    monitors: MonitorsClient,
+    events: EventsClient,
+    tags: Vec<String>,
+    time_range: TimeRange,
+    mut writer: RecordWriter,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let matched: Vec<_> = monitors.list(&tags).try_collect().await?;
+    logger.log(&format!("Found {} monitor(s)", matched.len()));
+
+    let events = &events;
+    let time_range = &time_range;
+    let summaries: Vec<MonitorNoise> = futures_util::stream::iter(&matched)
+        .map(|monitor| async move {
+            let history: Vec<_> = events
+                .monitor_events(monitor.id, &time_range.from, &time_range.to)
+                .try_collect()
+                .await?;
+            let episodes = pair_episodes(monitor.id, &history);
+            Ok::<_, AppError>(MonitorNoise::new(monitor.id, &monitor.name, &episodes))
+        })
+        .buffer_unordered(CONCURRENT_MONITORS)
+        .try_collect()
+        .await?;
+
+    for summary in rank(summaries) {
+        writer.write(&summary)?;
+    }
+
+    logger.log(&format!("Reported on {} monitor(s)", matched.len()));
+    Ok(())
+}
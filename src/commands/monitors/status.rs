@@ -0,0 +1,90 @@
+//! Monitor status command implementation.
+//!
+//! Handles the `ddog monitors status` command, writing a monitor's state and,
+//! with `--watch`, each transition to another state until Ctrl-C.
+
+use chrono::{SecondsFormat, Utc};
+use serde::Serialize;
+
+use crate::commands::watch::Watcher;
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::MonitorsClient;
+use ddog::error::AppError;
+use ddog::slack::{SlackMessage, SlackWebhook};
+use ddog::watch::{StateChange, StateTracker, Trigger};
+
+/// A monitor's state at a poll, and the state it changed from.
+#[derive(Debug, Serialize)]
+struct TransitionRecord<'a> {
+    timestamp: String,
+    monitor_id: i64,
+    name: &'a str,
+    #[serde(flatten)]
+    change: StateChange,
+}
+
+/// States a Slack notification is posted for.
+const BREACHED_STATES: [&str; 3] = ["Alert", "Warn", "No Data"];
+
+/// Executes the monitors status command.
+///
+/// Writes the current state (with a null `from`), then, with a watcher,
+/// polls on its interval and writes a record for every state change. With
+/// a Slack webhook (and the monitor's URL), posts each state that is in
+/// [`BREACHED_STATES`].
+pub async fn run(
+    client: MonitorsClient,
+    monitor_id: i64,
+    watcher: Option<Watcher>,
+    notify: Option<(SlackWebhook, String)>,
+    mut writer: RecordWriter,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let mut tracker = StateTracker::default();
+    let mut first = true;
+
+    loop {
+        let status = tokio::select! {
+            status = client.status(monitor_id) => status?,
+            _ = tokio::signal::ctrl_c() => break,
+        };
+        logger.log(&format!("Monitor {} is {}", monitor_id, status.state));
+
+        if let Some(change) = tracker.update(&status.state) {
+            let mut trigger = Trigger::default();
+            trigger.add(None, change.to == "Alert");
+            if let Some((webhook, monitor_url)) = &notify
+                && BREACHED_STATES.contains(&change.to.as_str())
+            {
+                let message =
+                    SlackMessage::new(format!("Monitor {} is {}", status.name, change.to))
+                        .field("Monitor", format!("{} ({})", status.name, monitor_id))
+                        .field("State", change.to.as_str())
+                        .field(
+                            "Previous state",
+                            change.from.as_deref().unwrap_or("unknown"),
+                        )
+                        .link(monitor_url.as_str());
+                webhook.send(&message).await?;
+                logger.log("Posted the state to Slack");
+            }
+            writer.write(&TransitionRecord {
+                timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+                monitor_id,
+                name: &status.name,
+                change,
+            })?;
+            if let Some(watcher) = watcher.as_ref().filter(|_| !first) {
+                watcher.report(&trigger, "state change");
+            }
+        }
+        first = false;
+
+        match &watcher {
+            Some(watcher) if watcher.wait().await => {}
+            _ => break,
+        }
+    }
+    Ok(())
+}
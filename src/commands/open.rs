@@ -0,0 +1,49 @@
+//! Open-in-browser implementation.
+//!
+//! Handles `--open` on `ddog logs search` and `ddog spans search`: instead of
+//! running the search, opens the same query and time range in the Log or
+//! Trace Explorer.
+
+use crate::cli::TimeRange;
+use crate::logging::VerboseLogger;
+use ddog::error::AppError;
+use ddog::links::{DatadogLinks, open_in_browser};
+use ddog::lint::SearchDomain;
+use ddog::time::{Timezone, resolve_absolute};
+
+/// Builds the Explorer URL for a search. Relative times are fixed to now.
+///
+/// # Errors
+///
+/// Returns `AppError::InvalidQuery` if a bound of the time range isn't a valid time.
+pub fn explorer_url(
+    links: &DatadogLinks,
+    domain: SearchDomain,
+    query: &str,
+    time_range: &TimeRange,
+    indexes: &[String],
+    timezone: Timezone,
+) -> Result<String, AppError> {
+    let from = resolve_absolute(&time_range.from, timezone)?.timestamp_millis();
+    let to = resolve_absolute(&time_range.to, timezone)?.timestamp_millis();
+    Ok(match domain {
+        SearchDomain::Logs => links.log_explorer(query, from, to, indexes),
+        SearchDomain::Spans => links.trace_explorer(query, from, to),
+    })
+}
+
+/// Prints the URL to stderr and opens it in the default browser.
+///
+/// A browser that can't be started is only reported, since the URL can
+/// still be copied from stderr (e.g., over SSH).
+pub fn run(url: &str, logger: VerboseLogger) -> Result<(), AppError> {
+    eprintln!("{}", url);
+    match open_in_browser(url) {
+        Ok(()) => logger.log("Opened the URL in the default browser"),
+        Err(e) => eprintln!(
+            "Could not open a browser ({}); open the URL above manually",
+            e
+        ),
+    }
+    Ok(())
+}
@@ -0,0 +1,59 @@
+//! Query lint command implementation.
+//!
+//! Handles the `ddog query lint` command and the pre-flight check run before
+//! logs and spans searches.
+
+use crate::logging::{self, VerboseLogger};
+use crate::output::RecordWriter;
+use ddog::error::AppError;
+use ddog::lint::{LintIssue, SearchDomain, Severity, lint};
+
+/// Executes the query lint command.
+///
+/// Writes one record per issue and fails with `AppError::InvalidQuery` if
+/// any issue is an error.
+pub fn run(
+    query: &str,
+    domain: SearchDomain,
+    mut writer: RecordWriter,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let issues = lint(query, domain);
+    for issue in &issues {
+        writer.write(issue)?;
+    }
+    logger.log(&format!("Found {} issue(s)", issues.len()));
+
+    match render_errors(query, &issues) {
+        Some(errors) => Err(AppError::InvalidQuery(errors)),
+        None => Ok(()),
+    }
+}
+
+/// Lints a search query before it is sent.
+///
+/// Warnings are printed to stderr; errors abort the search.
+pub fn preflight(query: &str, domain: SearchDomain) -> Result<(), AppError> {
+    let issues = lint(query, domain);
+    for issue in issues.iter().filter(|i| i.severity == Severity::Warning) {
+        logging::warn(&issue.render(query));
+    }
+
+    match render_errors(query, &issues) {
+        Some(errors) => Err(AppError::InvalidQuery(format!(
+            "{}\n(pass --no-validate to send the query anyway)",
+            errors
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Renders error-level issues, or `None` if there are none.
+fn render_errors(query: &str, issues: &[LintIssue]) -> Option<String> {
+    let errors: Vec<String> = issues
+        .iter()
+        .filter(|i| i.severity == Severity::Error)
+        .map(|i| i.render(query))
+        .collect();
+    (!errors.is_empty()).then(|| errors.join("\n"))
+}
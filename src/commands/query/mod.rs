@@ -0,0 +1,4 @@
+//! Query domain commands.
+
+pub mod lint;
+pub mod save;
@@ -0,0 +1,50 @@
+//! Query save command implementation.
+//!
+//! Handles the `ddog query save` command, storing a named query in the config
+//! directory for reuse as `@name`.
+
+use serde::Serialize;
+
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::error::AppError;
+use ddog::lint::SearchDomain;
+use ddog::saved_queries::{SavedQueries, SavedQuery, saved_queries_path};
+
+/// Record describing a saved query.
+#[derive(Serialize)]
+struct SavedRecord<'a> {
+    name: &'a str,
+    #[serde(flatten)]
+    saved: &'a SavedQuery,
+    path: String,
+}
+
+/// Executes the query save command.
+///
+/// Adds the query to the saved queries file and writes one record describing it.
+pub fn run(
+    name: &str,
+    query: String,
+    domain: SearchDomain,
+    force: bool,
+    mut writer: RecordWriter,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let path = saved_queries_path().ok_or_else(|| {
+        AppError::Config("Cannot locate the config directory (HOME is not set)".to_string())
+    })?;
+
+    let mut queries = SavedQueries::load(&path)?;
+    let saved = SavedQuery { query, domain };
+    queries.insert(name, saved.clone(), force)?;
+    queries.store(&path)?;
+    logger.log(&format!("Saved '@{}' to {}", name, path.display()));
+
+    writer.write(&SavedRecord {
+        name,
+        saved: &saved,
+        path: path.display().to_string(),
+    })?;
+    Ok(())
+}
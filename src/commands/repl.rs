@@ -0,0 +1,221 @@
+//! Interactive REPL command implementation.
+//!
+//! Handles the `ddog repl` command: reads queries and backslash commands
+//! from stdin, runs searches with the session's sticky settings, and pages
+//! through results as text.
+
+use futures_util::StreamExt;
+use serde::Serialize;
+use serde_json::Value;
+use std::fs::File;
+use std::io::{self, Write};
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+use crate::cli::TimeRange;
+use crate::commands::logs::search::search_error;
+use crate::commands::query::lint::preflight;
+use crate::logging::VerboseLogger;
+use crate::output::{self, NdjsonWriter, TextOptions, TextWriter};
+use ddog::client::{LogsClient, SearchStream, SpansClient};
+use ddog::error::AppError;
+use ddog::lint::SearchDomain;
+use ddog::repl::{HELP, Input, Session, parse_input};
+use ddog::saved_queries::{self, SavedQueries, saved_queries_path};
+use ddog::time::Timezone;
+use ddog::workspace::Workspace;
+
+/// Clients and settings shared by every query of a session.
+pub struct Repl<'a> {
+    /// Logs API client
+    pub logs: LogsClient,
+
+    /// Spans API client
+    pub spans: SpansClient,
+
+    /// Workspace for saved queries and default filters
+    pub workspace: Option<&'a Workspace>,
+
+    /// Timezone for resolving time ranges
+    pub timezone: Timezone,
+
+    /// Whether to skip the query lint pre-flight check
+    pub no_validate: bool,
+}
+
+/// Executes the REPL until `\quit` or end of input.
+///
+/// Results are printed as text with `text_options`; prompts and messages go
+/// to stderr. A failed query or command is reported without ending the session.
+pub async fn run(
+    repl: Repl<'_>,
+    text_options: TextOptions,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let mut session = Session::default();
+    let mut text = TextWriter::new(io::stdout(), text_options);
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+
+    eprintln!("ddog repl - type \\help for commands, \\quit to leave");
+    loop {
+        eprint!("{}", session.prompt());
+        io::stderr().flush()?;
+        let Some(line) = lines.next_line().await? else {
+            eprintln!();
+            break;
+        };
+
+        let result = match parse_input(&line) {
+            Ok(Input::Quit) => break,
+            Ok(input) => handle(&repl, &mut session, &mut text, input, &logger).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            eprintln!("Error: {}", e);
+        }
+    }
+    Ok(())
+}
+
+async fn handle(
+    repl: &Repl<'_>,
+    session: &mut Session,
+    text: &mut TextWriter,
+    input: Input,
+    logger: &VerboseLogger,
+) -> Result<(), AppError> {
+    match input {
+        Input::Query(query) => {
+            let query = resolve_query(repl, session.domain, &query)?;
+            logger.log(&format!("Query: {}", query));
+            let results = tokio::select! {
+                results = search(repl, session, &query, logger) => results?,
+                _ = tokio::signal::ctrl_c() => {
+                    eprintln!("Cancelled");
+                    return Ok(());
+                }
+            };
+            eprintln!("{} result(s)", results.len());
+            session.set_results(results);
+            show_page(session, text)?;
+        }
+        Input::More => {
+            if session.remaining() > 0 {
+                show_page(session, text)?;
+            } else if !session.results.is_empty() {
+                eprintln!("(end of results)");
+            }
+        }
+        Input::Ndjson(path) => {
+            let records = &session.results;
+            match &path {
+                Some(path) => {
+                    write_ndjson(NdjsonWriter::with_writer(File::create(path)?), records)?
+                }
+                None => write_ndjson(NdjsonWriter::new(), records)?,
+            }
+            if let Some(path) = path {
+                eprintln!("Wrote {} record(s) to {}", records.len(), path.display());
+            }
+        }
+        Input::Domain(domain) => session.domain = domain,
+        Input::Time { from, to } => {
+            time_range(&from, &to).resolve(repl.timezone)?;
+            session.from = from;
+            session.to = to;
+        }
+        Input::Limit(limit) => session.limit = limit,
+        Input::Indexes(indexes) => session.indexes = indexes,
+        Input::Settings => eprintln!("{}", session.settings()),
+        Input::Help => eprintln!("{}", HELP),
+        Input::Quit => {}
+    }
+    Ok(())
+}
+
+/// Expands `@name` saved queries, lints, and applies workspace defaults.
+fn resolve_query(repl: &Repl<'_>, domain: SearchDomain, query: &str) -> Result<String, AppError> {
+    let saved = match saved_queries_path() {
+        Some(path) => SavedQueries::load(&path)?,
+        None => SavedQueries::default(),
+    };
+    let mut query = saved_queries::expand_query(query, domain, repl.workspace, &saved)?;
+    if !repl.no_validate {
+        preflight(&query, domain)?;
+    }
+    if let Some(ws) = repl.workspace {
+        query = ws.scope_query(&query);
+    }
+    Ok(query)
+}
+
+/// Runs a search with the session's settings, returning up to its limit of records.
+async fn search(
+    repl: &Repl<'_>,
+    session: &Session,
+    query: &str,
+    logger: &VerboseLogger,
+) -> Result<Vec<Value>, AppError> {
+    let mut range = time_range(&session.from, &session.to);
+    range.resolve(repl.timezone)?;
+    let limit = session.limit as usize;
+
+    match session.domain {
+        SearchDomain::Logs => {
+            logger.log_api_endpoint("/api/v2/logs/events", "POST");
+            let stream = repl
+                .logs
+                .search(&range.logs_request(query, &session.indexes));
+            collect(stream.take_records(limit), logger).await
+        }
+        SearchDomain::Spans => {
+            logger.log_api_endpoint("/api/v2/spans/events/search", "POST");
+            let stream = repl.spans.search(&range.spans_request(query));
+            collect(stream.take_records(limit), logger).await
+        }
+    }
+}
+
+async fn collect<T: Serialize + Send>(
+    mut stream: SearchStream<'_, T>,
+    logger: &VerboseLogger,
+) -> Result<Vec<Value>, AppError> {
+    let mut records = Vec::new();
+    while let Some(result) = stream.next().await {
+        let record = result.map_err(|e| search_error(e, logger))?;
+        records.push(serde_json::to_value(record)?);
+    }
+    Ok(records)
+}
+
+fn show_page(session: &mut Session, text: &mut TextWriter) -> Result<(), AppError> {
+    for record in session.next_page() {
+        match output::transform(record)? {
+            Some(transformed) => text.write(&transformed)?,
+            None => text.write(record)?,
+        }
+    }
+    let remaining = session.remaining();
+    if remaining > 0 {
+        eprintln!("-- {} more, press Enter or \\more --", remaining);
+    }
+    Ok(())
+}
+
+fn write_ndjson<W: Write>(mut writer: NdjsonWriter<W>, records: &[Value]) -> io::Result<()> {
+    for record in records {
+        match output::transform(record)? {
+            Some(transformed) => writer.write(&transformed)?,
+            None => writer.write(record)?,
+        }
+    }
+    Ok(())
+}
+
+fn time_range(from: &str, to: &str) -> TimeRange {
+    TimeRange {
+        from: from.to_string(),
+        to: to.to_string(),
+        last: None,
+        align: None,
+    }
+}
@@ -0,0 +1,99 @@
+//! Self-update command implementation.
+//!
+//! Handles the `ddog self-update` command, replacing the running binary with
+//! the latest GitHub release for this platform.
+
+use serde::Serialize;
+
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::error::AppError;
+use ddog::update::{
+    self, CURRENT_VERSION, Updater, extract_binary, is_newer, replace_executable, verify_checksum,
+};
+
+/// Outcome of a self-update.
+#[derive(Debug, Serialize)]
+pub struct UpdateRecord {
+    /// Version running before the update
+    pub current: String,
+
+    /// Version of the latest release
+    pub latest: String,
+
+    /// Whether the binary was replaced
+    pub updated: bool,
+
+    /// Path of the replaced binary
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+/// Executes the self-update command.
+///
+/// With `check`, only reports whether a newer release exists.
+pub async fn run(
+    check: bool,
+    mut writer: RecordWriter,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let updater = Updater::new();
+    logger.log(&format!(
+        "Looking up the latest release of {}",
+        update::REPOSITORY
+    ));
+    let release = updater.latest().await?;
+    let mut record = UpdateRecord {
+        current: CURRENT_VERSION.to_string(),
+        latest: release.version().to_string(),
+        updated: false,
+        path: None,
+    };
+    if check || !is_newer(CURRENT_VERSION, release.version()) {
+        writer.write(&record)?;
+        return Ok(());
+    }
+
+    let name = update::asset_name().ok_or_else(|| {
+        AppError::Config(format!(
+            "no release binary is published for {}-{}; build from source instead",
+            std::env::consts::OS,
+            std::env::consts::ARCH
+        ))
+    })?;
+    let missing = |file: &str| {
+        AppError::Api(format!(
+            "release {} has no {}; not updating",
+            release.tag, file
+        ))
+    };
+    let archive = release.asset(name).ok_or_else(|| missing(name))?;
+    let checksum_name = format!("{}.sha256", name);
+    let checksum = release
+        .asset(&checksum_name)
+        .ok_or_else(|| missing(&checksum_name))?;
+
+    logger.log(&format!("Downloading {}", archive.url));
+    let data = updater.download(archive).await?;
+    let checksum = updater.download(checksum).await?;
+    verify_checksum(&data, &String::from_utf8_lossy(&checksum))?;
+    logger.log("Checksum verified");
+    let binary = extract_binary(&data)?;
+
+    let path = std::env::current_exe()?.canonicalize()?;
+    replace_executable(&path, &binary).map_err(|e| match e {
+        AppError::Io(io) => AppError::Io(std::io::Error::new(
+            io.kind(),
+            format!(
+                "{}: {} (rerun with permission to write it, e.g. sudo)",
+                path.display(),
+                io
+            ),
+        )),
+        e => e,
+    })?;
+    record.updated = true;
+    record.path = Some(path.display().to_string());
+    writer.write(&record)?;
+    Ok(())
+}
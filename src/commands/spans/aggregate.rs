@@ -0,0 +1,32 @@
+//! Spans aggregate command implementation.
+//!
+//! Handles the `ddog spans aggregate` command, counting spans per time bucket
+//! and facet value.
+
+use crate::cli::{Aggregation, TimeRange};
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::SpansClient;
+use ddog::error::AppError;
+
+/// Executes the spans aggregate command.
+pub async fn run(
+    client: SpansClient,
+    query: String,
+    time_range: TimeRange,
+    aggregation: Aggregation,
+    mut writer: RecordWriter,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let points = client
+        .volume(
+            &query,
+            &time_range.from,
+            &time_range.to,
+            &aggregation.grouping(),
+        )
+        .await?;
+
+    let title = format!("Spans matching `{}` by {}", query, aggregation.group_by);
+    crate::commands::aggregate::write(&points, &aggregation, &title, &mut writer, &logger)
+}
@@ -0,0 +1,59 @@
+//! Spans deps command implementation.
+//!
+//! Handles the `ddog spans deps` command, summarizing the calls between
+//! services in the spans matching a query as dependency edges.
+
+use std::io::Write;
+
+use futures_util::TryStreamExt;
+
+use crate::cli::TimeRange;
+use crate::logging::{self, VerboseLogger};
+use crate::output::{self, RecordWriter};
+use ddog::client::SpansClient;
+use ddog::deps::{dependency_edges, render_dot};
+use ddog::error::AppError;
+
+/// Executes the spans deps command.
+///
+/// Collects up to `limit` spans (0 for all of them) and writes one record
+/// per caller and callee, busiest first, or with `dot` a Graphviz graph of
+/// the edges.
+pub async fn run(
+    client: SpansClient,
+    query: String,
+    time_range: TimeRange,
+    limit: u64,
+    dot: bool,
+    mut writer: RecordWriter,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let spans: Vec<_> = client
+        .trace_spans(&time_range.spans_request(&query))
+        .take_records(limit as usize)
+        .try_collect()
+        .await?;
+    logger.log(&format!("Fetched {} span(s)", spans.len()));
+    if limit > 0 && spans.len() as u64 == limit {
+        logging::warn(&format!(
+            "stopped at --limit {} spans; edges cover only the calls among them",
+            limit
+        ));
+    }
+
+    let edges = dependency_edges(&spans);
+    logger.log(&format!("Found {} edge(s)", edges.len()));
+    if dot {
+        if !edges.is_empty() {
+            output::count_record();
+        }
+        std::io::stdout()
+            .lock()
+            .write_all(render_dot(&edges).as_bytes())?;
+    } else {
+        for edge in &edges {
+            writer.write(edge)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,44 @@
+//! Spans error-rate command implementation.
+//!
+//! Handles the `ddog spans error-rate` command, reporting the total spans,
+//! error spans, and error percentage per facet value.
+
+use crate::cli::TimeRange;
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::SpansClient;
+use ddog::error::AppError;
+use ddog::error_rate::{STATUS, error_rates};
+
+/// Statuses kept within each group; spans are "ok" or "error".
+const STATUSES_PER_GROUP: i64 = 5;
+
+/// Executes the spans error-rate command.
+///
+/// Writes one record per value of `group_by`, up to `top` values by span
+/// count, most errors first.
+pub async fn run(
+    client: SpansClient,
+    query: String,
+    time_range: TimeRange,
+    group_by: String,
+    top: i64,
+    mut writer: RecordWriter,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let counts = client
+        .group_counts(
+            &query,
+            &time_range.from,
+            &time_range.to,
+            &[(&group_by, top), (STATUS, STATUSES_PER_GROUP)],
+        )
+        .await?;
+
+    let rates = error_rates(&counts, &group_by);
+    logger.log(&format!("Reporting {} group(s)", rates.len()));
+    for rate in &rates {
+        writer.write(rate)?;
+    }
+    Ok(())
+}
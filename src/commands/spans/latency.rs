@@ -0,0 +1,43 @@
+//! Spans latency command implementation.
+//!
+//! Handles the `ddog spans latency` command, computing latency percentiles of
+//! the spans matching a query per time bucket.
+
+use crate::cli::TimeRange;
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::{Percentile, SpansClient};
+use ddog::error::AppError;
+
+/// Executes the spans latency command.
+///
+/// Writes one record per time bucket of `interval`, with a field per
+/// percentile.
+pub async fn run(
+    client: SpansClient,
+    query: String,
+    time_range: TimeRange,
+    mut percentiles: Vec<Percentile>,
+    interval: String,
+    mut writer: RecordWriter,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    percentiles.sort();
+    percentiles.dedup();
+
+    let points = client
+        .latency(
+            &query,
+            &time_range.from,
+            &time_range.to,
+            &percentiles,
+            &interval,
+        )
+        .await?;
+    logger.log(&format!("Received {} time bucket(s)", points.len()));
+
+    for point in &points {
+        writer.write(point)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,22 @@
+//! Span-based metrics command implementation.
+//!
+//! Handles `ddog spans metrics list`.
+
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::ApmConfigClient;
+use ddog::error::AppError;
+
+/// Executes the spans metrics list command.
+pub async fn list(
+    client: ApmConfigClient,
+    mut writer: RecordWriter,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let metrics = client.metrics().await?;
+    logger.log(&format!("Found {} span-based metric(s)", metrics.len()));
+    for metric in &metrics {
+        writer.write(metric)?;
+    }
+    Ok(())
+}
@@ -1,3 +1,11 @@
 //! Spans domain commands.
 
+pub mod aggregate;
+pub mod deps;
+pub mod error_rate;
+pub mod latency;
+pub mod metrics;
+pub mod retention_filters;
+pub mod sampling_report;
 pub mod search;
+pub mod trace;
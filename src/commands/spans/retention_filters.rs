@@ -0,0 +1,22 @@
+//! APM retention filters command implementation.
+//!
+//! Handles `ddog spans retention-filters list`.
+
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::ApmConfigClient;
+use ddog::error::AppError;
+
+/// Executes the spans retention-filters list command.
+pub async fn list(
+    client: ApmConfigClient,
+    mut writer: RecordWriter,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let filters = client.retention_filters().await?;
+    logger.log(&format!("Found {} retention filter(s)", filters.len()));
+    for filter in &filters {
+        writer.write(filter)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,51 @@
+//! Spans sampling report command implementation.
+//!
+//! Handles the `ddog spans sampling-report` command, breaking spans down per
+//! service by ingestion reason, sampling priority, and retention.
+
+use crate::cli::TimeRange;
+use crate::logging::VerboseLogger;
+use crate::output::RecordWriter;
+use ddog::client::SpansClient;
+use ddog::error::AppError;
+use ddog::sampling::{INGESTION_REASON, RETAINED_BY, SAMPLING_PRIORITY, report};
+
+/// Values kept per breakdown facet within each service.
+const VALUES_PER_SERVICE: i64 = 20;
+
+/// Executes the spans sampling report command.
+///
+/// Writes one breakdown per service, most spans first.
+pub async fn run(
+    client: SpansClient,
+    query: String,
+    time_range: TimeRange,
+    top: i64,
+    mut writer: RecordWriter,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let (query, from, to) = (&query, &time_range.from, &time_range.to);
+    let client = &client;
+    let counts = |facet| async move {
+        client
+            .group_counts(
+                query,
+                from,
+                to,
+                &[("service", top), (facet, VALUES_PER_SERVICE)],
+            )
+            .await
+    };
+    let (ingestion, priority, retention) = tokio::join!(
+        counts(INGESTION_REASON),
+        counts(SAMPLING_PRIORITY),
+        counts(RETAINED_BY),
+    );
+
+    let services = report(&ingestion?, &priority?, &retention?);
+    logger.log(&format!("Reporting {} service(s)", services.len()));
+    for service in &services {
+        writer.write(service)?;
+    }
+    Ok(())
+}
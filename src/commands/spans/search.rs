@@ -5,120 +5,210 @@
 use futures_util::StreamExt;
 
 use crate::cli::{Pagination, TimeRange};
+use crate::commands::watch::{self, EventInfo, Watcher};
+use crate::interrupt;
 use crate::logging::VerboseLogger;
-use crate::output::NdjsonWriter;
-use ddog::client::SpansClient;
+use crate::routing::SearchSink;
+use datadog_api_client::datadogV2::model::Span;
+use ddog::client::{MAX_SPANS_PAGE_SIZE, SpansClient, SpansSearchRequest, SpansSearchType};
 use ddog::error::AppError;
+use ddog::watch::is_severe;
 
 /// Executes the spans search command.
 ///
-/// Streams matching span records to stdout as NDJSON until the limit is reached
+/// Streams matching span records to stdout until the limit is reached
 /// or all results are exhausted.
 pub async fn run(
     client: SpansClient,
     query: String,
     time_range: TimeRange,
+    search_type: SpansSearchType,
     pagination: Pagination,
+    mut sink: SearchSink,
     logger: VerboseLogger,
 ) -> Result<(), AppError> {
-    let mut writer = NdjsonWriter::new();
-    let mut stream = std::pin::pin!(client.search(&query, &time_range.from, &time_range.to));
+    let mut request = request(&query, &time_range, search_type);
+    request.page_limit = pagination.page_limit(MAX_SPANS_PAGE_SIZE);
+    let interrupt = interrupt::ctrl_c_token();
+    let mut stream = client.search(&request);
+    if pagination.prefetches() {
+        stream = stream.prefetch(pagination.buffer(request.page_limit));
+    }
+    let mut stream = stream.cancel_with(interrupt.clone());
+    let mut budget = pagination.budget();
+    let mut sampler = pagination.sampler();
     let mut count: u64 = 0;
+    let mut latest = None;
+    let mut truncated = None;
 
     while let Some(result) = stream.next().await {
-        let span = result.map_err(|e| {
-            let msg = format!("{}", e);
-            logger.log_error(&msg, "spans API request");
-
-            if msg.contains("401") {
-                AppError::Auth(format!("Authentication failed (401): Invalid API or App key. {}", msg))
-            } else if msg.contains("403") || msg.contains("Forbidden") {
-                AppError::Auth(format!(
-                    "Access denied (403): Your API key may not have permission to access APM spans. \
-                    Note: APM spans require different permissions than logs. \
-                    Ensure your API key has 'APM and Infrastructure' read permissions. {}",
-                    msg
-                ))
-            } else if msg.contains("400") || msg.contains("Bad Request") {
-                AppError::InvalidQuery(msg)
+        // Pages are checked first, so a failed page past the cap truncates
+        if let Err(cap) = budget.admit_page(stream.page()) {
+            logger.log(&format!("Reached {}", cap));
+            truncated = Some(cap);
+            break;
+        }
+        let span = result.map_err(|e| search_error(e, &logger))?;
+
+        // Records left out of a sample still count towards their page
+        if sampler.as_mut().is_none_or(|s| s.keep()) {
+            let bytes = if budget.counts_bytes() {
+                serde_json::to_vec(&span)?.len() as u64 + 1
             } else {
-                AppError::Api(msg)
+                0
+            };
+            if let Err(cap) = budget.admit(bytes) {
+                logger.log(&format!("Reached {}", cap));
+                truncated = Some(cap);
+                break;
             }
-        })?;
 
-        writer.write(&span)?;
-        count += 1;
+            let timestamp = span.attributes.as_ref().and_then(|a| a.start_timestamp);
+            sink.write(&span)?;
+            sink.advance(timestamp);
+            latest = timestamp.or(latest);
+            count += 1;
+        }
 
-        if pagination.limit > 0 && count >= pagination.limit {
-            logger.log(&format!("Reached limit of {} results", pagination.limit));
+        if let Some(cap) = budget.exhausted() {
+            logger.log(&format!("Reached {}", cap));
             break;
         }
     }
 
     logger.log(&format!("Returned {} span(s)", count));
-    Ok(())
+    // Stopped by Ctrl-C rather than by a cap or the end of the results
+    let interrupted =
+        truncated.is_none() && budget.exhausted().is_none() && interrupt.is_cancelled();
+
+    let description = format!(
+        "spans matching `{}` ({} to {})",
+        query, time_range.from, time_range.to
+    );
+    sink.finish(&description, &logger).await?;
+
+    match truncated {
+        Some(cap) => Err(cap.truncated("spans")),
+        None if interrupted => Err(interrupt::interrupted("spans", count, latest)),
+        None => Ok(()),
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use ddog::error::AppError;
+/// Executes the spans search command with `--watch`.
+///
+/// Re-runs the search until Ctrl-C, writing each span once.
+pub async fn watch(
+    client: SpansClient,
+    query: String,
+    time_range: TimeRange,
+    search_type: SpansSearchType,
+    mut sink: SearchSink,
+    watcher: Watcher,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let search = |from: String| {
+        let range = time_range.with_bounds(Some(&from), None);
+        client
+            .search(&request(&query, &range, search_type))
+            .map(|result| result.map_err(|e| search_error(e, &logger)))
+    };
+    watch::search(
+        search,
+        describe,
+        &time_range,
+        watcher,
+        &mut sink,
+        "spans",
+        &logger,
+    )
+    .await?;
+
+    let description = format!("spans matching `{}` while watching", query);
+    sink.finish(&description, &logger).await
+}
 
-    fn parse_error_message(msg: &str) -> AppError {
-        if msg.contains("401") || msg.contains("403") || msg.contains("Forbidden") {
-            AppError::Auth(format!("Authentication failed: {}", msg))
-        } else if msg.contains("400") || msg.contains("Bad Request") {
-            AppError::InvalidQuery(msg.to_string())
-        } else {
-            AppError::Api(msg.to_string())
-        }
-    }
+/// A spans search of `query` over `range` returning `search_type` records.
+fn request(query: &str, range: &TimeRange, search_type: SpansSearchType) -> SpansSearchRequest {
+    let mut request = range.spans_request(query);
+    request.search_type = search_type;
+    request
+}
 
-    #[test]
-    fn test_error_parsing_401() {
-        let error = parse_error_message("401 Unauthorized");
-        assert!(matches!(error, AppError::Auth(_)));
-        assert_eq!(error.exit_code(), 2);
+/// Change detection details of a span; its resource stands in for a message.
+fn describe(span: &Span) -> EventInfo<'_> {
+    let attributes = span.attributes.as_ref();
+    let status = attributes
+        .and_then(|a| a.additional_properties.get("status"))
+        .and_then(|status| status.as_str());
+    EventInfo {
+        id: span.id.as_deref(),
+        timestamp: attributes.and_then(|a| a.start_timestamp),
+        message: attributes.and_then(|a| a.resource_name.as_deref()),
+        severe: status.is_some_and(is_severe),
     }
+}
 
-    #[test]
-    fn test_error_parsing_403() {
-        let error = parse_error_message("403 Forbidden");
-        assert!(matches!(error, AppError::Auth(_)));
-        assert_eq!(error.exit_code(), 2);
+/// Logs a failed spans search, already classified by its HTTP status.
+fn search_error(error: AppError, logger: &VerboseLogger) -> AppError {
+    logger.log_error(&error.to_string(), "spans API request");
+    error
+}
+
+#[cfg(test)]
+mod tests {
+    use ddog::client::{MockTransport, SpansClient, SpansSearchRequest};
+    use ddog::error::AppError;
+    use serde_json::{Value, json};
+
+    /// The error a search fails with when the API answers `status` with `body`.
+    async fn search_error(status: u16, body: Value) -> AppError {
+        let transport =
+            MockTransport::new().respond("POST", "/api/v2/spans/events/search", status, body);
+        let client = SpansClient::builder()
+            .api_key("test")
+            .app_key("test")
+            .transport(transport)
+            .build()
+            .unwrap();
+        client
+            .fetch(&SpansSearchRequest::builder().build(), 1)
+            .await
+            .unwrap_err()
     }
 
-    #[test]
-    fn test_error_parsing_forbidden() {
-        let error = parse_error_message("Forbidden access");
+    #[tokio::test]
+    async fn test_error_parsing_401() {
+        let error = search_error(401, json!({"errors": ["Unauthorized"]})).await;
         assert!(matches!(error, AppError::Auth(_)));
         assert_eq!(error.exit_code(), 2);
     }
 
-    #[test]
-    fn test_error_parsing_400() {
-        let error = parse_error_message("400 Bad Request");
-        assert!(matches!(error, AppError::InvalidQuery(_)));
-        assert_eq!(error.exit_code(), 4);
+    #[tokio::test]
+    async fn test_error_parsing_403() {
+        let error = search_error(403, json!({"errors": ["Forbidden"]})).await;
+        assert!(matches!(error, AppError::Forbidden(_)));
+        assert_eq!(error.exit_code(), 11);
     }
 
-    #[test]
-    fn test_error_parsing_bad_request() {
-        let error = parse_error_message("Bad Request: invalid syntax");
-        assert!(matches!(error, AppError::InvalidQuery(_)));
+    #[tokio::test]
+    async fn test_error_parsing_400() {
+        let error = search_error(400, json!({"errors": ["unexpected ')'"]})).await;
+        assert!(matches!(&error, AppError::InvalidQuery(msg) if msg.contains("unexpected ')'")));
         assert_eq!(error.exit_code(), 4);
     }
 
-    #[test]
-    fn test_error_parsing_generic_api_error() {
-        let error = parse_error_message("500 Internal Server Error");
-        assert!(matches!(error, AppError::Api(_)));
-        assert_eq!(error.exit_code(), 3);
+    #[tokio::test]
+    async fn test_error_parsing_429() {
+        let error = search_error(429, json!({"errors": ["Too many requests"]})).await;
+        assert!(matches!(error, AppError::RateLimited(_)));
+        assert_eq!(error.exit_code(), 12);
     }
 
-    #[test]
-    fn test_error_parsing_network_error() {
-        let error = parse_error_message("Connection timeout");
-        assert!(matches!(error, AppError::Api(_)));
+    #[tokio::test]
+    async fn test_error_parsing_generic_api_error() {
+        let error = search_error(500, json!({"errors": ["Internal Server Error"]})).await;
+        assert!(matches!(error, AppError::Unavailable(_)));
         assert_eq!(error.exit_code(), 3);
+        assert!(error.context("query q1").is_retryable());
     }
 }
@@ -0,0 +1,66 @@
+//! Spans trace command implementation.
+//!
+//! Handles the `ddog spans trace` command, writing one trace's spans as a
+//! call tree, as a sequence diagram of its service calls, as a waterfall, or
+//! as folded stacks for flamegraphs.
+
+use std::io::{IsTerminal, Write};
+
+use futures_util::TryStreamExt;
+
+use crate::cli::TimeRange;
+use crate::logging::VerboseLogger;
+use crate::output::{self, RecordWriter};
+use ddog::client::SpansClient;
+use ddog::error::AppError;
+use ddog::trace::{TraceDiagram, build_tree, render_folded, render_sequence, render_waterfall};
+
+/// Executes the spans trace command.
+///
+/// Writes the spans in depth-first order, or `diagram` when set. A
+/// waterfall is colored when stdout is a terminal.
+pub async fn run(
+    client: SpansClient,
+    trace_id: String,
+    time_range: TimeRange,
+    diagram: Option<TraceDiagram>,
+    mut writer: RecordWriter,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    let spans: Vec<_> = client
+        .trace(&trace_id, &time_range.from, &time_range.to)
+        .try_collect()
+        .await?;
+    logger.log(&format!("Fetched {} span(s)", spans.len()));
+
+    if spans.is_empty() {
+        return Err(AppError::InvalidQuery(format!(
+            "No spans found for trace {} between {} and {} (widen the range with --last)",
+            trace_id, time_range.from, time_range.to
+        )));
+    }
+
+    let tree = build_tree(spans);
+    if diagram.is_some() {
+        output::count_record();
+    }
+    match diagram {
+        Some(TraceDiagram::Sequence(dialect)) => std::io::stdout()
+            .lock()
+            .write_all(render_sequence(&tree, dialect).as_bytes())?,
+        Some(TraceDiagram::Waterfall) => {
+            let stdout = std::io::stdout();
+            let waterfall = render_waterfall(&tree, stdout.is_terminal());
+            stdout.lock().write_all(waterfall.as_bytes())?
+        }
+        Some(TraceDiagram::Folded) => std::io::stdout()
+            .lock()
+            .write_all(render_folded(&tree).as_bytes())?,
+        None => {
+            for span in &tree {
+                writer.write(span)?;
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,35 @@
+//! Telemetry report command implementation.
+//!
+//! Handles the `ddog telemetry report` command, summarizing the usage
+//! recorded in the state directory.
+
+use crate::logging::{self, VerboseLogger};
+use crate::output::RecordWriter;
+use ddog::config::UserConfig;
+use ddog::error::AppError;
+use ddog::telemetry::{self, summarize};
+
+/// Executes the telemetry report command.
+///
+/// Writes one summary per command, most run first.
+pub fn report(
+    config: &UserConfig,
+    mut writer: RecordWriter,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    if !telemetry::is_enabled(config) {
+        logging::notice(
+            "Usage recording is off; set `[telemetry] enabled = true` in config.toml to turn it on",
+        );
+    }
+    let Some(path) = telemetry::telemetry_path() else {
+        return Ok(());
+    };
+    logger.log(&format!("Reading {}", path.display()));
+    let events = telemetry::load(&path)?;
+    logger.log(&format!("Loaded {} event(s)", events.len()));
+    for usage in summarize(&events) {
+        writer.write(&usage)?;
+    }
+    Ok(())
+}
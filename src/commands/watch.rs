@@ -0,0 +1,206 @@
+//! Watch mode implementation.
+//!
+//! Handles `--watch` on the logs and spans search and aggregate commands and
+//! on monitor status, and `ddog spans tail`: re-runs the command on an
+//! interval until Ctrl-C, writing only new events, changed buckets, or state
+//! changes, and summarizes each run that finds something on stderr, ringing
+//! the terminal bell with `--bell`.
+
+use chrono::{DateTime, Local, Utc};
+use futures_util::{Stream, StreamExt};
+use serde::Serialize;
+use std::future::Future;
+use std::io::Write;
+use std::time::Duration;
+
+use crate::cli::{TimeRange, Watch};
+use crate::logging::{self, VerboseLogger};
+use crate::output::RecordWriter;
+use crate::routing::SearchSink;
+use ddog::client::VolumePoint;
+use ddog::error::AppError;
+use ddog::time::{Timezone, resolve_absolute};
+use ddog::watch::{BucketCounts, SeenEvents, Trigger};
+
+/// Interval and alerting settings of a watched command.
+pub struct Watcher {
+    interval: Duration,
+    bell: bool,
+    limit: u64,
+    timezone: Timezone,
+}
+
+/// What a watched search needs to know about an event.
+pub struct EventInfo<'a> {
+    /// Event ID, for telling events apart between runs
+    pub id: Option<&'a str>,
+
+    /// Event timestamp, for starting the next run
+    pub timestamp: Option<DateTime<Utc>>,
+
+    /// Message (or resource) for the trigger summary's top pattern
+    pub message: Option<&'a str>,
+
+    /// Whether the event rings the bell harder
+    pub severe: bool,
+}
+
+impl Watcher {
+    /// Returns a watcher when `--watch` is given. `limit` applies to each run.
+    pub fn new(watch: &Watch, limit: u64, timezone: Timezone) -> Option<Self> {
+        watch.watch.map(|interval| Self {
+            interval,
+            bell: watch.bell,
+            limit,
+            timezone,
+        })
+    }
+
+    /// Returns a watcher polling every `interval` without a per-run limit,
+    /// for commands that always watch (`spans tail`).
+    pub fn every(interval: Duration, bell: bool, timezone: Timezone) -> Self {
+        Self {
+            interval,
+            bell,
+            limit: 0,
+            timezone,
+        }
+    }
+
+    /// Waits for the next run, returning `false` if Ctrl-C was pressed instead.
+    pub async fn wait(&self) -> bool {
+        tokio::select! {
+            _ = tokio::time::sleep(self.interval) => true,
+            _ = tokio::signal::ctrl_c() => false,
+        }
+    }
+
+    /// Prints the summary of a run that found something, ringing the bell if asked.
+    pub fn report(&self, trigger: &Trigger, noun: &str) {
+        if trigger.count() == 0 {
+            return;
+        }
+        let mut stderr = std::io::stderr().lock();
+        let _ = writeln!(stderr, "{}", trigger.summary(noun, Local::now()));
+        if self.bell {
+            let bells = if trigger.is_severe() { 3 } else { 1 };
+            let _ = stderr.write_all("\x07".repeat(bells).as_bytes());
+        }
+        let _ = stderr.flush();
+    }
+}
+
+/// Runs a search until Ctrl-C, writing each event once.
+///
+/// `search` starts a search from the given start time to the end of
+/// `time_range`, and `describe` extracts what change detection needs from an
+/// event. `noun` names the events in summaries (e.g., "logs").
+pub async fn search<T, S, F, D>(
+    search: F,
+    describe: D,
+    time_range: &TimeRange,
+    watcher: Watcher,
+    sink: &mut SearchSink,
+    noun: &str,
+    logger: &VerboseLogger,
+) -> Result<(), AppError>
+where
+    T: Serialize,
+    S: Stream<Item = Result<T, AppError>>,
+    F: Fn(String) -> S,
+    D: Fn(&T) -> EventInfo<'_>,
+{
+    let limit = match watcher.limit {
+        0 => usize::MAX,
+        limit => limit as usize,
+    };
+    let mut seen = SeenEvents::default();
+    let mut from = time_range.from.clone();
+    let mut first = true;
+
+    loop {
+        let mut trigger = Trigger::default();
+        let run = async {
+            let mut stream = std::pin::pin!(search(from.clone()).take(limit));
+            let mut returned = 0;
+            while let Some(result) = stream.next().await {
+                let record = result?;
+                returned += 1;
+                let event = describe(&record);
+                let timestamp = event.timestamp.map(|t| t.timestamp_millis());
+                if seen.insert(event.id, timestamp) {
+                    sink.write(&record)?;
+                    trigger.add(event.message, event.severe);
+                }
+            }
+            if returned == limit {
+                logging::warn(&format!(
+                    "a --watch run returned --limit {} {}; later ones are picked up by the next run",
+                    limit, noun
+                ));
+            }
+            Ok::<_, AppError>(())
+        };
+        tokio::select! {
+            result = run => result?,
+            _ = tokio::signal::ctrl_c() => break,
+        }
+
+        logger.log(&format!(
+            "Run wrote {} new {}, remembering {} event ID(s)",
+            trigger.count(),
+            noun,
+            seen.len()
+        ));
+        if !first {
+            watcher.report(&trigger, &format!("new {}", noun));
+        }
+        first = false;
+
+        if !watcher.wait().await {
+            break;
+        }
+        let range_from = resolve_absolute(&time_range.from, watcher.timezone)?;
+        from = seen.next_from(range_from.timestamp_millis()).to_string();
+    }
+    Ok(())
+}
+
+/// Runs an aggregation until Ctrl-C, writing the new or changed buckets.
+pub async fn aggregate<F, Fut>(
+    fetch: F,
+    watcher: Watcher,
+    writer: &mut RecordWriter,
+    logger: &VerboseLogger,
+) -> Result<(), AppError>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<Vec<VolumePoint>, AppError>>,
+{
+    let mut counts = BucketCounts::default();
+    let mut first = true;
+
+    loop {
+        let points = tokio::select! {
+            points = fetch() => points?,
+            _ = tokio::signal::ctrl_c() => break,
+        };
+
+        let changed = counts.update(points);
+        let mut trigger = Trigger::default();
+        for point in &changed {
+            writer.write(point)?;
+            trigger.add(None, false);
+        }
+        logger.log(&format!("Run wrote {} changed bucket(s)", changed.len()));
+        if !first {
+            watcher.report(&trigger, "new or changed buckets");
+        }
+        first = false;
+
+        if !watcher.wait().await {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,71 @@
+//! Confirming and auditing write requests.
+//!
+//! Commands that change the org (`ddog api post`, `ddog logs metrics create`
+//! and `delete`) ask before sending unless given `--yes`, and append every
+//! request they send to the audit log.
+
+use serde_json::Value;
+use std::io::{self, IsTerminal, Read, Write};
+
+use crate::logging::{self, VerboseLogger};
+use ddog::audit::{self, AuditEntry};
+use ddog::error::AppError;
+
+/// Reads a request body from the argument, a file (`@FILE`), or stdin (`@-`).
+pub fn read_body(body: &str) -> Result<String, AppError> {
+    match body.strip_prefix('@') {
+        Some("-") => {
+            let mut text = String::new();
+            io::stdin().read_to_string(&mut text)?;
+            Ok(text)
+        }
+        Some(path) => std::fs::read_to_string(path)
+            .map_err(|e| AppError::Config(format!("Failed to read body file {}: {}", path, e))),
+        None => Ok(body.to_string()),
+    }
+}
+
+/// Shows the request on stderr and asks whether to send it.
+///
+/// # Errors
+///
+/// Returns `AppError::Config` when stdin or stderr isn't a terminal to ask on.
+pub fn confirm(method: &str, url: &str, body: Option<&Value>) -> Result<bool, AppError> {
+    if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+        return Err(AppError::Config(
+            "Refusing to send without confirmation; pass --yes to send from a script, or --dry-run to preview"
+                .to_string(),
+        ));
+    }
+
+    let mut stderr = io::stderr();
+    writeln!(stderr, "{} {}", method, url)?;
+    if let Some(body) = body {
+        writeln!(stderr, "{}", serde_json::to_string_pretty(body)?)?;
+    }
+    write!(stderr, "Send? [y/N] ")?;
+    stderr.flush()?;
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Appends the request to the audit log; failing to do so only warns.
+pub fn record<T>(method: &str, url: &str, result: &Result<T, AppError>, logger: &VerboseLogger) {
+    let Some(path) = audit::audit_path() else {
+        return;
+    };
+    let entry = AuditEntry {
+        timestamp: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        method: method.to_string(),
+        url: url.to_string(),
+        ok: result.is_ok(),
+        error: result.as_ref().err().map(ToString::to_string),
+        args: std::env::args().skip(1).collect(),
+    };
+    match audit::append(&path, &entry) {
+        Ok(()) => logger.log(&format!("Recorded request in {}", path.display())),
+        Err(e) => logging::warn(&format!("request not recorded in audit log: {}", e)),
+    }
+}
@@ -1,12 +1,148 @@
-//! Configuration loading from environment variables.
+//! Configuration loading from environment variables and the user config file.
 //!
 //! Validates that required Datadog credentials are set before creating
-//! the API client configuration.
+//! the API client configuration. Named profiles in `~/.config/ddog/config.toml`
+//! can point at a different site and credential variables.
 
-use datadog_api_client::datadog::Configuration;
+use datadog_api_client::datadog::{APIKey, Configuration};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
 
 use crate::error::AppError;
 
+/// A named connection profile from the user configuration file.
+///
+/// ```toml
+/// [profiles.eu]
+/// site = "datadoghq.eu"
+/// api_key_env = "DD_EU_API_KEY"
+/// app_key_env = "DD_EU_APP_KEY"
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    /// Datadog site, overriding `DD_SITE`
+    pub site: Option<String>,
+
+    /// Environment variable holding the API key (default: `DD_API_KEY`)
+    pub api_key_env: Option<String>,
+
+    /// Environment variable holding the application key (default: `DD_APP_KEY`)
+    pub app_key_env: Option<String>,
+}
+
+/// Local usage metrics settings (see [`telemetry`](crate::telemetry)).
+///
+/// ```toml
+/// [telemetry]
+/// enabled = true
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TelemetryConfig {
+    /// Whether usage is recorded; off unless set
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+/// Contents of the user configuration file.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct UserConfig {
+    /// Named profiles
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+
+    /// Local usage metrics
+    #[serde(default)]
+    pub telemetry: TelemetryConfig,
+}
+
+impl UserConfig {
+    /// Looks up a profile by name.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Config` if no profile with that name is defined.
+    pub fn profile(&self, name: &str) -> Result<&Profile, AppError> {
+        self.profiles.get(name).ok_or_else(|| {
+            AppError::Config(format!(
+                "Profile '{}' not found in {}",
+                name,
+                user_config_path()
+                    .map(|p| p.display().to_string())
+                    .unwrap_or_else(|| "user config".into())
+            ))
+        })
+    }
+}
+
+/// Returns the ddog configuration directory.
+///
+/// Uses `$XDG_CONFIG_HOME/ddog` when set, otherwise `~/.config/ddog`.
+pub fn config_dir() -> Option<PathBuf> {
+    if let Some(xdg) = std::env::var_os("XDG_CONFIG_HOME").filter(|v| !v.is_empty()) {
+        return Some(PathBuf::from(xdg).join("ddog"));
+    }
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config").join("ddog"))
+}
+
+/// Returns the ddog state directory, for files ddog maintains itself.
+///
+/// Uses `$XDG_STATE_HOME/ddog` when set, otherwise `~/.local/state/ddog`.
+pub fn state_dir() -> Option<PathBuf> {
+    if let Some(xdg) = std::env::var_os("XDG_STATE_HOME").filter(|v| !v.is_empty()) {
+        return Some(PathBuf::from(xdg).join("ddog"));
+    }
+    std::env::var_os("HOME").map(|home| {
+        PathBuf::from(home)
+            .join(".local")
+            .join("state")
+            .join("ddog")
+    })
+}
+
+/// Returns the ddog cache directory, for files that can be deleted at any time.
+///
+/// Uses `$XDG_CACHE_HOME/ddog` when set, otherwise `~/.cache/ddog`.
+pub fn cache_dir() -> Option<PathBuf> {
+    if let Some(xdg) = std::env::var_os("XDG_CACHE_HOME").filter(|v| !v.is_empty()) {
+        return Some(PathBuf::from(xdg).join("ddog"));
+    }
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache").join("ddog"))
+}
+
+/// Returns the path of the user configuration file (`config.toml` in [`config_dir`]).
+pub fn user_config_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("config.toml"))
+}
+
+/// Loads the user configuration file.
+///
+/// A missing file is not an error and yields an empty configuration.
+///
+/// # Errors
+///
+/// Returns `AppError::Config` if the file exists but cannot be read or parsed.
+pub fn load_user_config() -> Result<UserConfig, AppError> {
+    let Some(path) = user_config_path().filter(|p| p.is_file()) else {
+        return Ok(UserConfig::default());
+    };
+
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| AppError::Config(format!("Failed to read {}: {}", path.display(), e)))?;
+    toml::from_str(&contents)
+        .map_err(|e| AppError::Config(format!("Invalid {}: {}", path.display(), e)))
+}
+
+/// Resolves the Datadog site for a profile, falling back to `DD_SITE` and then `datadoghq.com`.
+pub fn resolve_site(profile: Option<&Profile>) -> String {
+    profile
+        .and_then(|p| p.site.clone())
+        .or_else(|| std::env::var("DD_SITE").ok())
+        .unwrap_or_else(|| "datadoghq.com".to_string())
+}
+
 /// Loads and validates Datadog configuration from environment variables.
 ///
 /// # Required Environment Variables
@@ -22,23 +158,115 @@ use crate::error::AppError;
 ///
 /// Returns `AppError::Config` if required environment variables are missing or empty.
 pub fn load_config() -> Result<Configuration, AppError> {
-    let api_key = std::env::var("DD_API_KEY")
-        .map_err(|_| AppError::Config("DD_API_KEY environment variable not set".into()))?;
+    load_config_for_profile(None)
+}
+
+/// Loads Datadog configuration, applying a profile's site and credential variables.
+///
+/// With no profile this behaves exactly like [`load_config`].
+///
+/// # Errors
+///
+/// Returns `AppError::Config` if the credential variables are missing or empty.
+pub fn load_config_for_profile(profile: Option<&Profile>) -> Result<Configuration, AppError> {
+    let Credentials { api_key, app_key } = load_credentials(profile)?;
 
-    let app_key = std::env::var("DD_APP_KEY")
-        .map_err(|_| AppError::Config("DD_APP_KEY environment variable not set".into()))?;
+    // DD_SITE is optional - the SDK reads it automatically
+    // Defaults to datadoghq.com if not set
+    let mut config = Configuration::new();
 
-    if api_key.is_empty() {
-        return Err(AppError::Config("DD_API_KEY is empty".into()));
+    if let Some(profile) = profile {
+        if let Some(site) = &profile.site {
+            config
+                .server_variables
+                .insert("site".to_string(), site.clone());
+        }
+        config.set_auth_key(
+            "apiKeyAuth",
+            APIKey {
+                key: api_key,
+                prefix: String::new(),
+            },
+        );
+        config.set_auth_key(
+            "appKeyAuth",
+            APIKey {
+                key: app_key,
+                prefix: String::new(),
+            },
+        );
     }
-    if app_key.is_empty() {
-        return Err(AppError::Config("DD_APP_KEY is empty".into()));
+
+    Ok(config)
+}
+
+/// Configuration for a profile's site without credentials, for runs where no
+/// request reaches Datadog, such as replaying a cassette.
+pub fn offline_config(profile: Option<&Profile>) -> Configuration {
+    let mut config = Configuration::new();
+    if let Some(site) = profile.and_then(|p| p.site.clone()) {
+        config.server_variables.insert("site".to_string(), site);
     }
+    config
+}
 
-    // DD_SITE is optional - the SDK reads it automatically
-    // Defaults to datadoghq.com if not set
+/// Datadog API and application keys.
+#[derive(Clone)]
+pub struct Credentials {
+    /// API key, sent as `DD-API-KEY`
+    pub api_key: String,
+
+    /// Application key, sent as `DD-APPLICATION-KEY`
+    pub app_key: String,
+}
+
+impl std::fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Credentials").finish_non_exhaustive()
+    }
+}
+
+/// Reads the API and application keys from a profile's credential variables
+/// (`DD_API_KEY` and `DD_APP_KEY` by default).
+///
+/// # Errors
+///
+/// Returns `AppError::Config` if either variable is missing or empty.
+pub fn load_credentials(profile: Option<&Profile>) -> Result<Credentials, AppError> {
+    let (api_key_env, app_key_env) = credential_vars(profile);
+    Ok(Credentials {
+        api_key: read_key(api_key_env)?,
+        app_key: read_key(app_key_env)?,
+    })
+}
 
-    Ok(Configuration::new())
+/// Whether the API and application keys [`load_credentials`] reads for a
+/// profile are set, for reporting without reading them.
+pub fn credentials_set(profile: Option<&Profile>) -> (bool, bool) {
+    let (api_key_env, app_key_env) = credential_vars(profile);
+    (read_key(api_key_env).is_ok(), read_key(app_key_env).is_ok())
+}
+
+/// Names of the variables holding a profile's API and application keys.
+fn credential_vars(profile: Option<&Profile>) -> (&str, &str) {
+    (
+        profile
+            .and_then(|p| p.api_key_env.as_deref())
+            .unwrap_or("DD_API_KEY"),
+        profile
+            .and_then(|p| p.app_key_env.as_deref())
+            .unwrap_or("DD_APP_KEY"),
+    )
+}
+
+/// Reads a credential from the named environment variable, rejecting missing or empty values.
+pub(crate) fn read_key(var: &str) -> Result<String, AppError> {
+    let key = std::env::var(var)
+        .map_err(|_| AppError::Config(format!("{} environment variable not set", var)))?;
+    if key.is_empty() {
+        return Err(AppError::Config(format!("{} is empty", var)));
+    }
+    Ok(key)
 }
 
 #[cfg(test)]
@@ -233,6 +461,80 @@ mod tests {
         );
     }
 
+    #[test]
+    #[serial]
+    fn test_load_config_for_profile_uses_custom_key_vars() {
+        with_env(
+            &[
+                ("DD_API_KEY", None),
+                ("DD_APP_KEY", None),
+                ("DD_EU_API_KEY", Some("eu-api-key")),
+                ("DD_EU_APP_KEY", Some("eu-app-key")),
+            ],
+            || {
+                let profile = Profile {
+                    site: Some("datadoghq.eu".into()),
+                    api_key_env: Some("DD_EU_API_KEY".into()),
+                    app_key_env: Some("DD_EU_APP_KEY".into()),
+                };
+                let config = load_config_for_profile(Some(&profile)).unwrap();
+                assert_eq!(
+                    config.server_variables.get("site").map(String::as_str),
+                    Some("datadoghq.eu")
+                );
+                assert_eq!(credentials_set(Some(&profile)), (true, true));
+                assert_eq!(credentials_set(None), (false, false));
+            },
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_load_config_for_profile_missing_custom_key() {
+        with_env(&[("DD_EU_API_KEY", None)], || {
+            let profile = Profile {
+                api_key_env: Some("DD_EU_API_KEY".into()),
+                ..Profile::default()
+            };
+            match load_config_for_profile(Some(&profile)) {
+                Err(AppError::Config(msg)) => assert!(msg.contains("DD_EU_API_KEY")),
+                other => panic!("Expected Config error, got: {:?}", other),
+            }
+        });
+    }
+
+    #[test]
+    fn test_parse_user_config_profiles() {
+        let config: UserConfig = toml::from_str(
+            r#"
+            [profiles.eu]
+            site = "datadoghq.eu"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.profile("eu").unwrap().site.as_deref(),
+            Some("datadoghq.eu")
+        );
+        assert!(matches!(config.profile("us"), Err(AppError::Config(_))));
+    }
+
+    #[test]
+    #[serial]
+    fn test_resolve_site() {
+        with_env(&[("DD_SITE", Some("us5.datadoghq.com"))], || {
+            assert_eq!(resolve_site(None), "us5.datadoghq.com");
+            let profile = Profile {
+                site: Some("datadoghq.eu".into()),
+                ..Profile::default()
+            };
+            assert_eq!(resolve_site(Some(&profile)), "datadoghq.eu");
+        });
+        with_env(&[("DD_SITE", None)], || {
+            assert_eq!(resolve_site(None), "datadoghq.com");
+        });
+    }
+
     #[test]
     #[serial]
     fn test_load_config_with_site() {
@@ -0,0 +1,204 @@
+//! Service dependencies from spans.
+//!
+//! A span whose parent belongs to another service is a call between the two.
+//! [`dependency_edges`] folds the calls in a set of spans into one
+//! [`DependencyEdge`] per caller and callee, with the number of calls and the
+//! callee's latency, and [`render_dot`] draws the edges as a Graphviz graph.
+
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::client::TraceSpan;
+use crate::stats::percentile;
+
+/// Calls from one service to another.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DependencyEdge {
+    /// Calling service
+    pub caller: String,
+
+    /// Called service
+    pub callee: String,
+
+    /// Number of calls
+    pub requests: u64,
+
+    /// Calls whose callee span has an error status
+    pub errors: u64,
+
+    /// 95th percentile duration of the callee spans, in milliseconds
+    pub p95_ms: f64,
+}
+
+/// Folds the calls between services in `spans` into edges, busiest first.
+///
+/// A call is a span whose parent is among `spans` and belongs to a different
+/// service; calls within a service and spans whose parent wasn't fetched are
+/// left out.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::client::TraceSpan;
+/// use ddog::deps::dependency_edges;
+///
+/// let span = |id: &str, parent: &str, service: &str, duration_ms: f64| TraceSpan {
+///     span_id: id.to_string(),
+///     parent_id: Some(parent.to_string()),
+///     service: service.to_string(),
+///     resource: String::new(),
+///     operation: String::new(),
+///     start: String::new(),
+///     start_ns: 0,
+///     duration_ms,
+///     error: false,
+/// };
+/// let edges = dependency_edges(&[
+///     span("1", "0", "web", 50.0),
+///     span("2", "1", "api", 30.0),
+///     span("3", "1", "api", 10.0),
+///     span("4", "2", "api", 5.0),
+/// ]);
+/// assert_eq!(edges.len(), 1);
+/// assert_eq!((edges[0].caller.as_str(), edges[0].callee.as_str()), ("web", "api"));
+/// assert_eq!(edges[0].requests, 2);
+/// assert_eq!(edges[0].p95_ms, 30.0);
+/// ```
+pub fn dependency_edges(spans: &[TraceSpan]) -> Vec<DependencyEdge> {
+    let services: HashMap<&str, &str> = spans
+        .iter()
+        .map(|s| (s.span_id.as_str(), s.service.as_str()))
+        .collect();
+
+    let mut calls: BTreeMap<(&str, &str), (Vec<f64>, u64)> = BTreeMap::new();
+    for span in spans {
+        let Some(caller) = span.parent_id.as_deref().and_then(|id| services.get(id)) else {
+            continue;
+        };
+        if *caller == span.service {
+            continue;
+        }
+        let (durations, errors) = calls.entry((caller, &span.service)).or_default();
+        durations.push(span.duration_ms);
+        *errors += u64::from(span.error);
+    }
+
+    let mut edges: Vec<DependencyEdge> = calls
+        .into_iter()
+        .map(|((caller, callee), (mut durations, errors))| {
+            durations.sort_by(f64::total_cmp);
+            DependencyEdge {
+                caller: caller.to_string(),
+                callee: callee.to_string(),
+                requests: durations.len() as u64,
+                errors,
+                p95_ms: percentile(&durations, 95.0),
+            }
+        })
+        .collect();
+    edges.sort_by_key(|edge| Reverse(edge.requests));
+    edges
+}
+
+/// Renders edges as a Graphviz `digraph`, each labelled with its calls and
+/// p95 latency, and drawn red when some calls failed.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::deps::{DependencyEdge, render_dot};
+///
+/// let edge = DependencyEdge {
+///     caller: "web".to_string(),
+///     callee: "api".to_string(),
+///     requests: 120,
+///     errors: 0,
+///     p95_ms: 45.25,
+/// };
+/// assert_eq!(
+///     render_dot(&[edge]),
+///     "digraph deps {\n    rankdir=LR;\n    node [shape=box];\n    \"web\" -> \"api\" [label=\"120 req\\np95 45.2 ms\"];\n}\n"
+/// );
+/// ```
+pub fn render_dot(edges: &[DependencyEdge]) -> String {
+    let mut out = String::from("digraph deps {\n    rankdir=LR;\n    node [shape=box];\n");
+    for edge in edges {
+        let mut label = format!("{} req\\np95 {:.1} ms", edge.requests, edge.p95_ms);
+        let mut style = String::new();
+        if edge.errors > 0 {
+            label.push_str(&format!("\\n{} errors", edge.errors));
+            style.push_str(", color=red, fontcolor=red");
+        }
+        out.push_str(&format!(
+            "    \"{}\" -> \"{}\" [label=\"{}\"{}];\n",
+            escape_dot(&edge.caller),
+            escape_dot(&edge.callee),
+            label,
+            style
+        ));
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// Escapes a service name for a quoted DOT identifier.
+fn escape_dot(name: &str) -> String {
+    name.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(id: &str, parent: &str, service: &str, duration_ms: f64, error: bool) -> TraceSpan {
+        TraceSpan {
+            span_id: id.to_string(),
+            parent_id: Some(parent.to_string()),
+            service: service.to_string(),
+            resource: String::new(),
+            operation: String::new(),
+            start: String::new(),
+            start_ns: 0,
+            duration_ms,
+            error,
+        }
+    }
+
+    #[test]
+    fn test_edges_busiest_first() {
+        let mut spans = vec![span("1", "0", "web", 100.0, false)];
+        for i in 0..20 {
+            let id = format!("api-{}", i);
+            spans.push(span(&id, "1", "api", (i + 1) as f64, i == 0));
+            spans.push(span(&format!("db-{}", i), &id, "db", 1.0, false));
+        }
+        spans.push(span("db-web", "1", "db", 2.0, false));
+        // Parent not fetched
+        spans.push(span("orphan", "missing", "cache", 1.0, false));
+
+        let edges = dependency_edges(&spans);
+        let pairs: Vec<(&str, &str, u64)> = edges
+            .iter()
+            .map(|e| (e.caller.as_str(), e.callee.as_str(), e.requests))
+            .collect();
+        assert_eq!(
+            pairs,
+            [("api", "db", 20), ("web", "api", 20), ("web", "db", 1)]
+        );
+        assert_eq!(edges[1].p95_ms, 19.0);
+        assert_eq!(edges[1].errors, 1);
+    }
+
+    #[test]
+    fn test_dot_marks_errors() {
+        let edges = dependency_edges(&[
+            span("1", "0", "web \"edge\"", 10.0, false),
+            span("2", "1", "api", 5.0, true),
+        ]);
+        let dot = render_dot(&edges);
+        assert!(dot.contains(
+            "\"web \\\"edge\\\"\" -> \"api\" [label=\"1 req\\np95 5.0 ms\\n1 errors\", color=red, fontcolor=red];"
+        ));
+    }
+}
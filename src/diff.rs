@@ -0,0 +1,260 @@
+//! Per-group deltas between two time windows for `ddog logs diff`.
+//!
+//! Each window is aggregated separately into the top values of a facet;
+//! [`diff`] joins the two lists by value. A value missing from a window that
+//! returned fewer values than asked for had no matching logs there, but one
+//! missing from a full window may just have ranked below the cut, so its count
+//! in that window is unknown rather than 0.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::client::FacetValue;
+
+/// A time window given as `FROM..TO`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Window {
+    /// Start of the window
+    pub from: String,
+
+    /// End of the window
+    pub to: String,
+}
+
+/// Parses a `FROM..TO` window; an empty `TO` means now.
+///
+/// The bounds are only split here; they are validated when resolved as a
+/// time range.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::diff::parse_window;
+///
+/// let window = parse_window("now-2h..now-1h").unwrap();
+/// assert_eq!((window.from.as_str(), window.to.as_str()), ("now-2h", "now-1h"));
+/// assert_eq!(parse_window("now-1h..").unwrap().to, "now");
+/// ```
+///
+/// # Errors
+///
+/// Returns an error without `..` or without a start.
+pub fn parse_window(s: &str) -> Result<Window, String> {
+    let Some((from, to)) = s.split_once("..") else {
+        return Err(format!(
+            "expected FROM..TO (e.g. now-2h..now-1h), got '{}'",
+            s
+        ));
+    };
+    let (from, to) = (from.trim(), to.trim());
+    if from.is_empty() {
+        return Err(format!("window '{}' has no start", s));
+    }
+    Ok(Window {
+        from: from.to_string(),
+        to: if to.is_empty() { "now" } else { to }.to_string(),
+    })
+}
+
+/// How a group's count moved between the windows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Movement {
+    /// No logs in the baseline window
+    New,
+    /// No logs in the compare window
+    Gone,
+    /// More logs in the compare window
+    Up,
+    /// Fewer logs in the compare window
+    Down,
+    /// As many logs in both windows
+    Same,
+    /// Below the top values in one window, so its count there is unknown
+    Unknown,
+}
+
+/// The counts of one facet value in both windows.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GroupDelta {
+    /// Facet grouped by
+    pub facet: String,
+
+    /// Facet value (e.g., "checkout" for the service facet)
+    pub group: String,
+
+    /// Number of logs in the baseline window, if known
+    pub baseline: Option<f64>,
+
+    /// Number of logs in the compare window, if known
+    pub compare: Option<f64>,
+
+    /// `compare - baseline`, when both are known
+    pub delta: Option<f64>,
+
+    /// Relative change (`delta / baseline`, 1.0 is +100%), when the baseline is non-zero
+    pub change: Option<f64>,
+
+    /// Direction of the change
+    pub movement: Movement,
+}
+
+/// Joins the top values of both windows into per-group deltas.
+///
+/// `limit` is the number of values asked for per window. Groups are sorted by
+/// the size of their delta, largest first, then groups with unknown deltas.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::client::FacetValue;
+/// use ddog::diff::{Movement, diff};
+///
+/// let value = |value: &str, count| FacetValue { value: value.to_string(), count };
+/// let deltas = diff(
+///     "service",
+///     vec![value("api", 10.0), value("web", 5.0)],
+///     vec![value("api", 40.0), value("db", 2.0)],
+///     10,
+/// );
+/// assert_eq!(deltas[0].group, "api");
+/// assert_eq!(deltas[0].delta, Some(30.0));
+/// assert_eq!(deltas[0].change, Some(3.0));
+/// assert_eq!(deltas[1].movement, Movement::Gone);
+/// assert_eq!(deltas[2].movement, Movement::New);
+/// ```
+pub fn diff(
+    facet: &str,
+    baseline: Vec<FacetValue>,
+    compare: Vec<FacetValue>,
+    limit: usize,
+) -> Vec<GroupDelta> {
+    // A window that returned fewer values than asked for returned all of them
+    let missing = |values: &[FacetValue]| (values.len() < limit).then_some(0.0);
+    let (baseline_missing, compare_missing) = (missing(&baseline), missing(&compare));
+
+    let mut groups: BTreeMap<String, (Option<f64>, Option<f64>)> = BTreeMap::new();
+    for value in baseline {
+        groups
+            .entry(value.value)
+            .or_insert((None, compare_missing))
+            .0 = Some(value.count);
+    }
+    for value in compare {
+        groups
+            .entry(value.value)
+            .or_insert((baseline_missing, None))
+            .1 = Some(value.count);
+    }
+
+    let mut deltas: Vec<GroupDelta> = groups
+        .into_iter()
+        .map(|(group, (baseline, compare))| {
+            let delta = baseline.zip(compare).map(|(b, c)| c - b);
+            GroupDelta {
+                facet: facet.to_string(),
+                group,
+                baseline,
+                compare,
+                delta,
+                change: baseline
+                    .filter(|b| *b != 0.0)
+                    .zip(delta)
+                    .map(|(b, d)| d / b),
+                movement: movement(baseline, compare),
+            }
+        })
+        .collect();
+    deltas.sort_by(|a, b| {
+        let size = |d: &GroupDelta| d.delta.map(f64::abs);
+        match (size(a), size(b)) {
+            (Some(a), Some(b)) => b.total_cmp(&a),
+            (a, b) => b.is_some().cmp(&a.is_some()),
+        }
+    });
+    deltas
+}
+
+fn movement(baseline: Option<f64>, compare: Option<f64>) -> Movement {
+    match (baseline, compare) {
+        (Some(b), Some(c)) if b == c => Movement::Same,
+        (Some(0.0), Some(_)) => Movement::New,
+        (Some(_), Some(0.0)) => Movement::Gone,
+        (Some(b), Some(c)) if c > b => Movement::Up,
+        (Some(_), Some(_)) => Movement::Down,
+        _ => Movement::Unknown,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(pairs: &[(&str, f64)]) -> Vec<FacetValue> {
+        pairs
+            .iter()
+            .map(|(value, count)| FacetValue {
+                value: value.to_string(),
+                count: *count,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_window_errors() {
+        assert!(parse_window("now-1h").is_err());
+        assert!(parse_window("..now").is_err());
+        assert_eq!(
+            parse_window(" 1705315200000 .. 1705318800000 ").unwrap(),
+            Window {
+                from: "1705315200000".to_string(),
+                to: "1705318800000".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_diff_truncated_window_is_unknown() {
+        // The compare window is full, so "web" may have ranked below the cut
+        let deltas = diff(
+            "service",
+            values(&[("web", 5.0), ("api", 3.0)]),
+            values(&[("api", 9.0), ("db", 4.0), ("worker", 1.0)]),
+            3,
+        );
+        let web = deltas.iter().find(|d| d.group == "web").unwrap();
+        assert_eq!(web.compare, None);
+        assert_eq!(web.delta, None);
+        assert_eq!(web.movement, Movement::Unknown);
+        assert_eq!(deltas.last().unwrap().group, "web");
+
+        // The baseline window is not, so "db" had no logs there
+        let db = deltas.iter().find(|d| d.group == "db").unwrap();
+        assert_eq!(db.baseline, Some(0.0));
+        assert_eq!(db.change, None);
+        assert_eq!(db.movement, Movement::New);
+    }
+
+    #[test]
+    fn test_diff_order_and_movement() {
+        let deltas = diff(
+            "service",
+            values(&[("a", 10.0), ("b", 10.0), ("c", 10.0)]),
+            values(&[("a", 8.0), ("b", 10.0), ("c", 30.0)]),
+            10,
+        );
+        let order: Vec<_> = deltas
+            .iter()
+            .map(|d| (d.group.as_str(), d.movement))
+            .collect();
+        assert_eq!(
+            order,
+            [
+                ("c", Movement::Up),
+                ("a", Movement::Down),
+                ("b", Movement::Same)
+            ]
+        );
+        assert_eq!(deltas[1].change, Some(-0.2));
+    }
+}
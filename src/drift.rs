@@ -0,0 +1,358 @@
+//! Configuration drift between a desired-state bundle and the live org.
+//!
+//! A bundle is a directory of JSON definitions kept in version control:
+//!
+//! ```text
+//! bundle/
+//!   monitors/high-cpu.json      {"id": 123, "query": "...", "options": {...}}
+//!   slos/checkout.json          {"id": "abc123", "target_threshold": 99.9}
+//!   dashboards/overview.json    {"id": "abc-def-ghi", "title": "..."}
+//!   indexes/main.json           {"name": "main", "daily_limit": 100000000}
+//! ```
+//!
+//! Each definition only needs the fields it pins down; [`diff`] compares
+//! those against the live definition and ignores everything else, so a full
+//! API export and a hand-written subset both work.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::client::ResourceKind;
+use crate::error::AppError;
+
+/// Fields Datadog manages itself, which never count as drift.
+const SERVER_MANAGED: [&str; 12] = [
+    "created",
+    "created_at",
+    "creator",
+    "deleted",
+    "modified",
+    "modified_at",
+    "overall_state",
+    "overall_state_modified",
+    "matching_downtimes",
+    "url",
+    "author_handle",
+    "author_name",
+];
+
+/// One resource definition from a bundle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Desired {
+    /// Kind of resource
+    pub kind: ResourceKind,
+
+    /// Resource ID (index name for log indexes)
+    pub id: String,
+
+    /// File the definition was read from
+    pub file: PathBuf,
+
+    /// Desired definition
+    pub definition: Value,
+}
+
+/// How a live resource differs from its definition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DriftStatus {
+    /// The resource doesn't exist in the org
+    Missing,
+    /// A field's live value differs from the definition
+    Changed,
+}
+
+/// One difference between the bundle and the live org.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Drift {
+    /// Kind of resource (e.g., "monitor")
+    pub resource: &'static str,
+
+    /// Resource ID
+    pub id: String,
+
+    /// Bundle file defining the resource
+    pub file: String,
+
+    /// Whether the resource is missing or a field changed
+    pub status: DriftStatus,
+
+    /// JSON pointer to the field that differs; empty for a missing resource
+    pub path: String,
+
+    /// Value in the bundle
+    pub expected: Value,
+
+    /// Live value; null if the field or resource is absent
+    pub actual: Value,
+}
+
+/// Reads every definition in a bundle, ordered by kind then file name.
+///
+/// Definitions are read from `*.json` files in the `monitors/`, `slos/`,
+/// `dashboards/`, and `indexes/` subdirectories; missing subdirectories are
+/// skipped. IDs come from the `id` field, or `name` for log indexes.
+///
+/// # Errors
+///
+/// Returns `AppError::Config` if the bundle has no definitions, or a
+/// definition isn't a JSON object with an ID.
+pub fn load_bundle(dir: &Path) -> Result<Vec<Desired>, AppError> {
+    let mut bundle = Vec::new();
+    for kind in ResourceKind::ALL {
+        let subdir = dir.join(kind.directory());
+        if !subdir.is_dir() {
+            continue;
+        }
+        let mut files: Vec<PathBuf> = fs::read_dir(&subdir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        files.sort();
+
+        for file in files {
+            let definition: Value = serde_json::from_str(&fs::read_to_string(&file)?)
+                .map_err(|e| AppError::Config(format!("{}: {}", file.display(), e)))?;
+            let id_field = match kind {
+                ResourceKind::LogIndex => "name",
+                _ => "id",
+            };
+            let id = match definition.get(id_field) {
+                Some(Value::String(id)) => id.clone(),
+                Some(Value::Number(id)) => id.to_string(),
+                _ => {
+                    return Err(AppError::Config(format!(
+                        "{}: expected a JSON object with an \"{}\" field",
+                        file.display(),
+                        id_field
+                    )));
+                }
+            };
+            bundle.push(Desired {
+                kind,
+                id,
+                file,
+                definition,
+            });
+        }
+    }
+
+    if bundle.is_empty() {
+        return Err(AppError::Config(format!(
+            "No definitions found in {}: expected *.json files under monitors/, slos/, dashboards/, or indexes/",
+            dir.display()
+        )));
+    }
+    Ok(bundle)
+}
+
+/// Compares a definition with the live resource (`None` if it doesn't exist).
+pub fn check(desired: &Desired, live: Option<&Value>) -> Vec<Drift> {
+    let drift = |status, path: String, expected: &Value, actual: Option<&Value>| Drift {
+        resource: desired.kind.name(),
+        id: desired.id.clone(),
+        file: desired.file.display().to_string(),
+        status,
+        path,
+        expected: expected.clone(),
+        actual: actual.cloned().unwrap_or(Value::Null),
+    };
+
+    match live {
+        None => vec![drift(
+            DriftStatus::Missing,
+            String::new(),
+            &desired.definition,
+            None,
+        )],
+        Some(live) => diff(&desired.definition, live)
+            .into_iter()
+            .map(|(path, expected, actual)| drift(DriftStatus::Changed, path, expected, actual))
+            .collect(),
+    }
+}
+
+/// Lists the fields of `expected` whose value in `actual` differs, as
+/// `(JSON pointer, expected, actual)`.
+///
+/// Only fields present in `expected` are compared, recursively through
+/// objects; server-managed fields such as `modified` are ignored. Arrays are
+/// compared element by element when both have the same length, and whole
+/// otherwise. Numbers compare by value, so `80` matches `80.0`.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::drift::diff;
+/// use serde_json::json;
+///
+/// let expected = json!({"options": {"thresholds": {"critical": 90}}});
+/// let actual = json!({"name": "CPU", "options": {"thresholds": {"critical": 80.0}}});
+/// let changes = diff(&expected, &actual);
+/// assert_eq!(changes[0].0, "/options/thresholds/critical");
+/// ```
+pub fn diff<'a>(
+    expected: &'a Value,
+    actual: &'a Value,
+) -> Vec<(String, &'a Value, Option<&'a Value>)> {
+    let mut changes = Vec::new();
+    diff_at(String::new(), expected, Some(actual), &mut changes);
+    changes
+}
+
+fn diff_at<'a>(
+    path: String,
+    expected: &'a Value,
+    actual: Option<&'a Value>,
+    changes: &mut Vec<(String, &'a Value, Option<&'a Value>)>,
+) {
+    match (expected, actual) {
+        (Value::Object(fields), Some(Value::Object(live))) => {
+            for (key, value) in fields {
+                if path.is_empty() && (SERVER_MANAGED.contains(&key.as_str()) || key == "id") {
+                    continue;
+                }
+                diff_at(
+                    format!("{}/{}", path, escape_pointer(key)),
+                    value,
+                    live.get(key),
+                    changes,
+                );
+            }
+        }
+        (Value::Array(items), Some(Value::Array(live))) if items.len() == live.len() => {
+            for (index, (item, live_item)) in items.iter().zip(live).enumerate() {
+                diff_at(
+                    format!("{}/{}", path, index),
+                    item,
+                    Some(live_item),
+                    changes,
+                );
+            }
+        }
+        (Value::Number(a), Some(Value::Number(b))) if a.as_f64() == b.as_f64() => {}
+        (Value::Null, None) => {}
+        (expected, actual) if Some(expected) == actual => {}
+        (expected, actual) => changes.push((path, expected, actual)),
+    }
+}
+
+/// Escapes a key for use in a JSON pointer.
+fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn temp_bundle(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ddog-drift-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_diff() {
+        let expected = json!({
+            "id": 123,
+            "modified": "2024-01-01",
+            "name": "High CPU",
+            "tags": ["team:infra", "env:prod"],
+            "options": {"thresholds": {"critical": 90, "warning": 80}, "notify_no_data": null}
+        });
+        let actual = json!({
+            "id": 123,
+            "modified": "2024-02-01",
+            "name": "High CPU",
+            "tags": ["team:infra"],
+            "options": {"thresholds": {"critical": 90.0, "warning": 70.0}}
+        });
+
+        let paths: Vec<String> = diff(&expected, &actual)
+            .into_iter()
+            .map(|(path, _, _)| path)
+            .collect();
+        assert_eq!(paths, vec!["/options/thresholds/warning", "/tags"]);
+    }
+
+    #[test]
+    fn test_diff_missing_field_and_escaping() {
+        let expected = json!({"a/b": {"c": [1, {"d": true}]}});
+        let actual = json!({"a/b": {"c": [1, {}]}});
+        let changes = diff(&expected, &actual);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].0, "/a~1b/c/1/d");
+        assert_eq!(changes[0].2, None);
+    }
+
+    #[test]
+    fn test_check() {
+        let desired = Desired {
+            kind: ResourceKind::Slo,
+            id: "abc".to_string(),
+            file: PathBuf::from("slos/checkout.json"),
+            definition: json!({"id": "abc", "target_threshold": 99.9}),
+        };
+
+        let missing = check(&desired, None);
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].status, DriftStatus::Missing);
+        assert_eq!(missing[0].actual, Value::Null);
+
+        let changed = check(
+            &desired,
+            Some(&json!({"id": "abc", "target_threshold": 99.5})),
+        );
+        assert_eq!(changed[0].resource, "slo");
+        assert_eq!(changed[0].status, DriftStatus::Changed);
+        assert_eq!(changed[0].path, "/target_threshold");
+        assert_eq!(changed[0].actual, json!(99.5));
+
+        assert!(
+            check(
+                &desired,
+                Some(&json!({"id": "abc", "target_threshold": 99.9}))
+            )
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_load_bundle() {
+        let dir = temp_bundle("load");
+        fs::create_dir_all(dir.join("monitors")).unwrap();
+        fs::create_dir_all(dir.join("indexes")).unwrap();
+        fs::write(dir.join("monitors/b.json"), r#"{"id": 2}"#).unwrap();
+        fs::write(dir.join("monitors/a.json"), r#"{"id": 1}"#).unwrap();
+        fs::write(dir.join("monitors/README.md"), "notes").unwrap();
+        fs::write(dir.join("indexes/main.json"), r#"{"name": "main"}"#).unwrap();
+
+        let bundle = load_bundle(&dir).unwrap();
+        let ids: Vec<(ResourceKind, &str)> =
+            bundle.iter().map(|d| (d.kind, d.id.as_str())).collect();
+        assert_eq!(
+            ids,
+            vec![
+                (ResourceKind::Monitor, "1"),
+                (ResourceKind::Monitor, "2"),
+                (ResourceKind::LogIndex, "main"),
+            ]
+        );
+
+        fs::write(dir.join("monitors/c.json"), r#"{"name": "no id"}"#).unwrap();
+        assert!(matches!(load_bundle(&dir), Err(AppError::Config(_))));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_load_empty_bundle() {
+        let dir = temp_bundle("empty");
+        assert!(matches!(load_bundle(&dir), Err(AppError::Config(_))));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
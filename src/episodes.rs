@@ -0,0 +1,251 @@
+//! Pairing monitor state changes into alert episodes.
+//!
+//! A monitor's event history is a sequence of transitions (triggered, warn,
+//! no data, recovered) per monitor group. [`pair_episodes`] folds them into
+//! episodes running from the first non-OK transition to the recovery, which
+//! is the unit MTTR and alert-noise reporting is built on.
+
+use chrono::{SecondsFormat, TimeZone, Utc};
+use serde::Serialize;
+
+use crate::client::MonitorEvent;
+
+/// Kind of state change a monitor event records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Transition {
+    /// Entered (or re-entered) the warning state
+    Warn,
+    /// Stopped reporting data
+    NoData,
+    /// Entered (or re-entered) the alert state
+    Alert,
+    /// Returned to OK
+    Recover,
+}
+
+impl Transition {
+    /// Classifies an event by its title prefix, falling back to its status.
+    ///
+    /// Returns `None` for events that don't change state, such as renotifications
+    /// or monitor edits.
+    pub fn of(event: &MonitorEvent) -> Option<Self> {
+        let title = event.title.trim_start();
+        let by_title = [
+            ("[Recovered", Transition::Recover),
+            ("[Triggered", Transition::Alert),
+            ("[Re-Triggered", Transition::Alert),
+            ("[Warn", Transition::Warn),
+            ("[Re-Warn", Transition::Warn),
+            ("[No Data", Transition::NoData),
+            ("[Re-No Data", Transition::NoData),
+        ]
+        .into_iter()
+        .find(|(prefix, _)| title.starts_with(prefix))
+        .map(|(_, transition)| transition);
+
+        by_title.or(match event.status.as_deref() {
+            Some("error") | Some("failure") => Some(Transition::Alert),
+            Some("warning") => Some(Transition::Warn),
+            Some("success") => Some(Transition::Recover),
+            _ => None,
+        })
+    }
+
+    fn severity(self) -> &'static str {
+        match self {
+            Transition::Warn => "warn",
+            Transition::NoData => "no_data",
+            Transition::Alert | Transition::Recover => "alert",
+        }
+    }
+}
+
+/// One alert episode: from the first non-OK transition to recovery.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Episode {
+    /// ID of the monitor
+    pub monitor_id: i64,
+
+    /// Monitor group, for multi-alert monitors
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+
+    /// When the episode started (RFC3339, UTC)
+    pub start: String,
+
+    /// When the monitor recovered (RFC3339, UTC); null if still open at the end of the range
+    pub end: Option<String>,
+
+    /// Seconds from start to recovery; null if still open
+    pub duration_seconds: Option<i64>,
+
+    /// Worst state reached: warn, no_data, or alert
+    pub severity: &'static str,
+
+    /// Number of non-OK transitions within the episode (re-triggers included)
+    pub transitions: usize,
+
+    /// Title of the event that opened the episode
+    pub title: String,
+}
+
+/// An episode still waiting for its recovery.
+struct OpenEpisode {
+    group: Option<String>,
+    start: i64,
+    worst: Transition,
+    transitions: usize,
+    title: String,
+}
+
+/// Pairs a monitor's events into episodes, ordered by start time.
+///
+/// Events are grouped by monitor group. A recovery with no open episode (its
+/// trigger fell before the range) is ignored; an episode with no recovery is
+/// emitted with a null end.
+pub fn pair_episodes(monitor_id: i64, events: &[MonitorEvent]) -> Vec<Episode> {
+    let mut sorted: Vec<&MonitorEvent> = events.iter().collect();
+    sorted.sort_by_key(|e| e.timestamp);
+
+    let mut open: Vec<OpenEpisode> = Vec::new();
+    let mut episodes = Vec::new();
+
+    for event in sorted {
+        let Some(transition) = Transition::of(event) else {
+            continue;
+        };
+        let position = open.iter().position(|o| o.group == event.group);
+
+        match (transition, position) {
+            (Transition::Recover, Some(index)) => {
+                let episode = open.remove(index);
+                episodes.push(close(monitor_id, episode, Some(event.timestamp)));
+            }
+            (Transition::Recover, None) => {}
+            (transition, Some(index)) => {
+                let episode = &mut open[index];
+                episode.worst = episode.worst.max(transition);
+                episode.transitions += 1;
+            }
+            (transition, None) => open.push(OpenEpisode {
+                group: event.group.clone(),
+                start: event.timestamp,
+                worst: transition,
+                transitions: 1,
+                title: event.title.clone(),
+            }),
+        }
+    }
+
+    episodes.extend(open.into_iter().map(|o| close(monitor_id, o, None)));
+    episodes.sort_by(|a, b| a.start.cmp(&b.start));
+    episodes
+}
+
+fn close(monitor_id: i64, episode: OpenEpisode, end: Option<i64>) -> Episode {
+    Episode {
+        monitor_id,
+        group: episode.group,
+        start: format_millis(episode.start),
+        end: end.map(format_millis),
+        duration_seconds: end.map(|end| (end - episode.start) / 1000),
+        severity: episode.worst.severity(),
+        transitions: episode.transitions,
+        title: episode.title,
+    }
+}
+
+fn format_millis(millis: i64) -> String {
+    Utc.timestamp_millis_opt(millis)
+        .single()
+        .map(|t| t.to_rfc3339_opts(SecondsFormat::Millis, true))
+        .unwrap_or_else(|| millis.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(timestamp: i64, title: &str, group: Option<&str>) -> MonitorEvent {
+        MonitorEvent {
+            monitor_id: Some(42),
+            timestamp,
+            title: title.to_string(),
+            status: None,
+            group: group.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_transition_of() {
+        assert_eq!(
+            Transition::of(&event(0, "[Triggered on {host:a}] CPU", None)),
+            Some(Transition::Alert)
+        );
+        assert_eq!(
+            Transition::of(&event(0, "[Recovered] CPU", None)),
+            Some(Transition::Recover)
+        );
+        assert_eq!(
+            Transition::of(&event(0, "[Warn] CPU", None)),
+            Some(Transition::Warn)
+        );
+        assert_eq!(Transition::of(&event(0, "CPU renotify", None)), None);
+
+        let mut by_status = event(0, "CPU", None);
+        by_status.status = Some("success".to_string());
+        assert_eq!(Transition::of(&by_status), Some(Transition::Recover));
+    }
+
+    #[test]
+    fn test_pair_episodes() {
+        let episodes = pair_episodes(
+            42,
+            &[
+                event(1_705_312_800_000, "[Warn] CPU", None),
+                event(1_705_313_100_000, "[Triggered] CPU", None),
+                event(1_705_314_000_000, "[Recovered] CPU", None),
+            ],
+        );
+        assert_eq!(episodes.len(), 1);
+        assert_eq!(episodes[0].start, "2024-01-15T10:00:00.000Z");
+        assert_eq!(episodes[0].end.as_deref(), Some("2024-01-15T10:20:00.000Z"));
+        assert_eq!(episodes[0].duration_seconds, Some(1200));
+        assert_eq!(episodes[0].severity, "alert");
+        assert_eq!(episodes[0].transitions, 2);
+        assert_eq!(episodes[0].title, "[Warn] CPU");
+    }
+
+    #[test]
+    fn test_pair_episodes_per_group() {
+        let episodes = pair_episodes(
+            42,
+            &[
+                event(0, "[Triggered on {host:a}] CPU", Some("host:a")),
+                event(1_000, "[Triggered on {host:b}] CPU", Some("host:b")),
+                event(5_000, "[Recovered on {host:a}] CPU", Some("host:a")),
+            ],
+        );
+        assert_eq!(episodes.len(), 2);
+        assert_eq!(episodes[0].group.as_deref(), Some("host:a"));
+        assert_eq!(episodes[0].duration_seconds, Some(5));
+        assert_eq!(episodes[1].group.as_deref(), Some("host:b"));
+        assert_eq!(episodes[1].end, None);
+        assert_eq!(episodes[1].duration_seconds, None);
+    }
+
+    #[test]
+    fn test_pair_episodes_ignores_leading_recovery() {
+        let episodes = pair_episodes(
+            42,
+            &[
+                event(0, "[Recovered] CPU", None),
+                event(1_000, "[No Data] CPU", None),
+                event(3_000, "[Recovered] CPU", None),
+            ],
+        );
+        assert_eq!(episodes.len(), 1);
+        assert_eq!(episodes[0].severity, "no_data");
+        assert_eq!(episodes[0].duration_seconds, Some(2));
+    }
+}
@@ -1,8 +1,10 @@
 //! Application error types and exit codes.
 //!
 //! Provides a unified error type for the application with appropriate
-//! exit codes for different failure modes.
+//! exit codes for different failure modes, and an [`ErrorReport`] describing
+//! an error for programs that run ddog.
 
+use serde::Serialize;
 use std::io;
 use thiserror::Error;
 
@@ -14,9 +16,20 @@ pub enum AppError {
     #[error("Authentication failed: {0}")]
     Auth(String),
 
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    #[error("Rate limited: {0}")]
+    RateLimited(String),
+
     #[error("API error: {0}")]
     Api(String),
 
+    /// An API error that may not recur: a server error (5xx), or a failure
+    /// to reach Datadog or read its response
+    #[error("API error: {0}")]
+    Unavailable(String),
+
     #[error("Invalid query: {0}")]
     InvalidQuery(String),
 
@@ -28,30 +41,174 @@ pub enum AppError {
 
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
+
+    #[error("Drift detected: {0}")]
+    Drift(String),
+
+    #[error("Results truncated: {0}")]
+    Truncated(String),
+
+    #[error("Check failed: {0}")]
+    CheckFailed(String),
+
+    #[error("Check warning: {0}")]
+    CheckWarning(String),
+
+    #[error("Interrupted: {0}")]
+    Interrupted(String),
+
+    #[error("No results: {0}")]
+    Empty(String),
 }
 
 impl AppError {
+    /// Prefixes the error's message with `context`, keeping its kind and exit code.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ddog::error::AppError;
+    ///
+    /// let error = AppError::InvalidQuery("unbalanced quotes".to_string()).context("query q1");
+    /// assert_eq!(error.to_string(), "Invalid query: query q1: unbalanced quotes");
+    /// ```
+    pub fn context(self, context: &str) -> Self {
+        let prefix = |msg: String| format!("{}: {}", context, msg);
+        match self {
+            AppError::Auth(msg) => AppError::Auth(prefix(msg)),
+            AppError::Forbidden(msg) => AppError::Forbidden(prefix(msg)),
+            AppError::RateLimited(msg) => AppError::RateLimited(prefix(msg)),
+            AppError::Api(msg) => AppError::Api(prefix(msg)),
+            AppError::Unavailable(msg) => AppError::Unavailable(prefix(msg)),
+            AppError::InvalidQuery(msg) => AppError::InvalidQuery(prefix(msg)),
+            AppError::Config(msg) => AppError::Config(prefix(msg)),
+            AppError::Drift(msg) => AppError::Drift(prefix(msg)),
+            AppError::Truncated(msg) => AppError::Truncated(prefix(msg)),
+            AppError::CheckFailed(msg) => AppError::CheckFailed(prefix(msg)),
+            AppError::CheckWarning(msg) => AppError::CheckWarning(prefix(msg)),
+            AppError::Interrupted(msg) => AppError::Interrupted(prefix(msg)),
+            AppError::Empty(msg) => AppError::Empty(prefix(msg)),
+            e @ (AppError::Io(_) | AppError::Serialization(_)) => e,
+        }
+    }
+
     /// Returns the exit code for this error type.
     ///
     /// Exit codes:
-    /// - 2: Authentication failure (401/403)
-    /// - 3: API error
+    /// - 2: Authentication failure (401)
+    /// - 3: API error, including server errors (5xx)
     /// - 4: Invalid query syntax
     /// - 5: Configuration error
     /// - 6: IO error
     /// - 7: Serialization error
+    /// - 8: Live configuration differs from the desired state
+    /// - 9: A search stopped at --max-pages or --max-bytes with results left
+    /// - 10: A count is outside the `logs check` threshold, or a
+    ///   `metrics scalar` value is past `--crit`
+    /// - 11: The API key lacks permission for the resource (403)
+    /// - 12: Rate limited by Datadog (429)
+    /// - 13: A `metrics scalar` value is past `--warn`
+    /// - 14: A command run with `--fail-if-empty` found no results
+    /// - 130: A search was stopped with Ctrl-C (as for SIGINT)
     pub fn exit_code(&self) -> i32 {
         match self {
             AppError::Auth(_) => 2,
-            AppError::Api(_) => 3,
+            AppError::Api(_) | AppError::Unavailable(_) => 3,
             AppError::InvalidQuery(_) => 4,
             AppError::Config(_) => 5,
             AppError::Io(_) => 6,
             AppError::Serialization(_) => 7,
+            AppError::Drift(_) => 8,
+            AppError::Truncated(_) => 9,
+            AppError::CheckFailed(_) => 10,
+            AppError::Forbidden(_) => 11,
+            AppError::RateLimited(_) => 12,
+            AppError::CheckWarning(_) => 13,
+            AppError::Empty(_) => 14,
+            AppError::Interrupted(_) => 130,
+        }
+    }
+
+    /// Returns the name of this error's kind, e.g. "invalid_query".
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AppError::Auth(_) => "auth",
+            AppError::Forbidden(_) => "forbidden",
+            AppError::RateLimited(_) => "rate_limited",
+            AppError::Api(_) | AppError::Unavailable(_) => "api",
+            AppError::InvalidQuery(_) => "invalid_query",
+            AppError::Config(_) => "config",
+            AppError::Io(_) => "io",
+            AppError::Serialization(_) => "serialization",
+            AppError::Drift(_) => "drift",
+            AppError::Truncated(_) => "truncated",
+            AppError::CheckFailed(_) => "check_failed",
+            AppError::CheckWarning(_) => "check_warning",
+            AppError::Interrupted(_) => "interrupted",
+            AppError::Empty(_) => "empty",
+        }
+    }
+
+    /// Whether Datadog rejected the request with 429 Too Many Requests.
+    pub fn is_rate_limited(&self) -> bool {
+        matches!(self, AppError::RateLimited(_))
+    }
+
+    /// Whether running the command again later may succeed: rate limits,
+    /// server errors (5xx), and failures to reach Datadog or read its
+    /// response.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            AppError::Unavailable(_) | AppError::RateLimited(_) | AppError::Io(_)
+        )
+    }
+
+    /// Describes this error for machines, with `rate_limit_reset` (seconds
+    /// until the rate limit resets) kept only if the error is a rate limit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ddog::error::AppError;
+    ///
+    /// let error = AppError::RateLimited("Too many requests for logs (429).".into());
+    /// let report = error.report(Some(12));
+    /// assert_eq!(report.kind, "rate_limited");
+    /// assert!(report.retryable);
+    /// assert_eq!(report.rate_limit_reset, Some(12));
+    /// ```
+    pub fn report(&self, rate_limit_reset: Option<u64>) -> ErrorReport {
+        ErrorReport {
+            code: self.exit_code(),
+            kind: self.kind(),
+            message: self.to_string(),
+            retryable: self.is_retryable(),
+            rate_limit_reset: rate_limit_reset.filter(|_| self.is_rate_limited()),
         }
     }
 }
 
+/// A machine-readable description of an [`AppError`], as printed by
+/// `--error-format json`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ErrorReport {
+    /// Exit code, see [`AppError::exit_code`]
+    pub code: i32,
+
+    /// Kind of error, see [`AppError::kind`]
+    pub kind: &'static str,
+
+    /// The human-readable message
+    pub message: String,
+
+    /// Whether running the command again later may succeed
+    pub retryable: bool,
+
+    /// Seconds until the rate limit resets, for rate-limited requests
+    pub rate_limit_reset: Option<u64>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -62,6 +219,19 @@ mod tests {
         assert_eq!(error.exit_code(), 2);
     }
 
+    #[test]
+    fn test_forbidden_error_exit_code() {
+        let error = AppError::Forbidden("test".to_string());
+        assert_eq!(error.exit_code(), 11);
+    }
+
+    #[test]
+    fn test_rate_limited_error_exit_code() {
+        let error = AppError::RateLimited("test".to_string());
+        assert_eq!(error.exit_code(), 12);
+        assert!(error.is_retryable());
+    }
+
     #[test]
     fn test_api_error_exit_code() {
         let error = AppError::Api("test".to_string());
@@ -95,6 +265,78 @@ mod tests {
         assert_eq!(error.exit_code(), 7);
     }
 
+    #[test]
+    fn test_empty_error_exit_code() {
+        let error = AppError::Empty("test".to_string());
+        assert_eq!(error.exit_code(), 14);
+        assert_eq!(error.kind(), "empty");
+        assert!(!error.is_retryable());
+    }
+
+    #[test]
+    fn test_drift_error_exit_code() {
+        let error = AppError::Drift("test".to_string());
+        assert_eq!(error.exit_code(), 8);
+    }
+
+    #[test]
+    fn test_truncated_error_exit_code() {
+        let error = AppError::Truncated("test".to_string());
+        assert_eq!(error.exit_code(), 9);
+    }
+
+    #[test]
+    fn test_check_failed_error_exit_code() {
+        let error = AppError::CheckFailed("test".to_string());
+        assert_eq!(error.exit_code(), 10);
+    }
+
+    #[test]
+    fn test_check_warning_error_exit_code() {
+        let error = AppError::CheckWarning("test".to_string());
+        assert_eq!(error.exit_code(), 13);
+    }
+
+    #[test]
+    fn test_interrupted_error_exit_code() {
+        let error = AppError::Interrupted("test".to_string());
+        assert_eq!(error.exit_code(), 130);
+    }
+
+    #[test]
+    fn test_report() {
+        let error = AppError::InvalidQuery("unbalanced quotes".to_string());
+        let report = serde_json::to_value(error.report(Some(30))).unwrap();
+        assert_eq!(
+            report,
+            serde_json::json!({
+                "code": 4,
+                "kind": "invalid_query",
+                "message": "Invalid query: unbalanced quotes",
+                "retryable": false,
+                "rate_limit_reset": null
+            })
+        );
+
+        let error = AppError::Unavailable("HTTP 503 Service Unavailable: ".to_string());
+        assert!(error.is_retryable());
+        assert!(!error.is_rate_limited());
+        assert_eq!(error.report(Some(30)).rate_limit_reset, None);
+        assert_eq!(error.kind(), "api");
+        assert!(!AppError::Api("HTTP 404 Not Found: ".to_string()).is_retryable());
+    }
+
+    #[test]
+    fn test_context_keeps_retryable() {
+        let error = AppError::Unavailable("HTTP 502 Bad Gateway: ".to_string()).context("query q1");
+        assert_eq!(
+            error.to_string(),
+            "API error: query q1: HTTP 502 Bad Gateway: "
+        );
+        assert!(error.report(None).retryable);
+        assert_eq!(error.exit_code(), 3);
+    }
+
     #[test]
     fn test_error_display() {
         let auth_error = AppError::Auth("invalid credentials".to_string());
@@ -0,0 +1,128 @@
+//! Error rates per facet value from span counts.
+//!
+//! Spans are counted per value of a facet and per `status` ("ok" or
+//! "error") in one aggregation; [`error_rates`] folds the counts into one
+//! [`ErrorRate`] per value, with its share of error spans.
+
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+use crate::client::GroupCount;
+
+/// Facet holding a span's status.
+pub const STATUS: &str = "status";
+
+/// Spans and error spans sharing one facet value.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ErrorRate {
+    /// Facet value (e.g., "GET /checkout" for --group-by resource_name)
+    pub group: String,
+
+    /// Spans matching the query
+    pub total: u64,
+
+    /// Spans with an error status
+    pub errors: u64,
+
+    /// Errors as a percentage of the total, rounded to two decimals
+    pub error_pct: f64,
+}
+
+/// Folds span counts grouped by `facet` and [`STATUS`] into error rates,
+/// most errors first, then busiest first.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::client::GroupCount;
+/// use ddog::error_rate::error_rates;
+/// use std::collections::BTreeMap;
+///
+/// let count = |service: &str, status: &str, count: f64| GroupCount {
+///     by: BTreeMap::from([
+///         ("service".to_string(), service.to_string()),
+///         ("status".to_string(), status.to_string()),
+///     ]),
+///     count,
+/// };
+/// let rates = error_rates(
+///     &[count("api", "ok", 970.0), count("api", "error", 30.0), count("web", "ok", 500.0)],
+///     "service",
+/// );
+/// assert_eq!(rates[0].group, "api");
+/// assert_eq!((rates[0].total, rates[0].errors, rates[0].error_pct), (1000, 30, 3.0));
+/// assert_eq!(rates[1].error_pct, 0.0);
+/// ```
+pub fn error_rates(counts: &[GroupCount], facet: &str) -> Vec<ErrorRate> {
+    let mut groups: BTreeMap<&str, (u64, u64)> = BTreeMap::new();
+    for count in counts {
+        let Some(group) = count.by.get(facet) else {
+            continue;
+        };
+        let spans = count.count as u64;
+        let (total, errors) = groups.entry(group).or_default();
+        *total += spans;
+        if count.by.get(STATUS).is_some_and(|status| status == "error") {
+            *errors += spans;
+        }
+    }
+
+    let mut rates: Vec<ErrorRate> = groups
+        .into_iter()
+        .map(|(group, (total, errors))| ErrorRate {
+            group: group.to_string(),
+            total,
+            errors,
+            error_pct: percentage(errors, total),
+        })
+        .collect();
+    rates.sort_by_key(|rate| (Reverse(rate.errors), Reverse(rate.total)));
+    rates
+}
+
+/// `part` as a percentage of `total` rounded to two decimals; 0 for no total.
+fn percentage(part: u64, total: u64) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    (part as f64 / total as f64 * 10_000.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count(resource: &str, status: &str, count: f64) -> GroupCount {
+        GroupCount {
+            by: BTreeMap::from([
+                ("resource_name".to_string(), resource.to_string()),
+                (STATUS.to_string(), status.to_string()),
+            ]),
+            count,
+        }
+    }
+
+    #[test]
+    fn test_most_errors_first() {
+        let rates = error_rates(
+            &[
+                count("GET /health", "ok", 5000.0),
+                count("POST /checkout", "ok", 294.0),
+                count("POST /checkout", "error", 6.0),
+                count("GET /cart", "error", 6.0),
+                count("GET /cart", "ok", 2994.0),
+                count("GET /users", "N/A", 3.0),
+            ],
+            "resource_name",
+        );
+        let groups: Vec<&str> = rates.iter().map(|r| r.group.as_str()).collect();
+        assert_eq!(
+            groups,
+            ["GET /cart", "POST /checkout", "GET /health", "GET /users"]
+        );
+        assert_eq!(rates[0].error_pct, 0.2);
+        assert_eq!(rates[1].error_pct, 2.0);
+        assert_eq!(rates[3].total, 3);
+    }
+}
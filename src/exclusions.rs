@@ -0,0 +1,372 @@
+//! Log index exclusion filters.
+//!
+//! An exclusion filter drops logs matching its query from an index before
+//! they're stored (all of them, or a `sample_rate` share), so they never
+//! show up in searches. [`preview`] tells which filters would drop the logs
+//! a search is looking for, by comparing the search's query with each
+//! filter's and its index's, without fetching any logs: excluded logs can't
+//! be searched for.
+//!
+//! The comparison understands queries that are plain lists of `facet:value`,
+//! `-facet:value`, and free-text terms, with `*` wildcards in values. A
+//! filter whose query it can't rule out (one with `OR`, groups, or ranges,
+//! for instance) is reported as one that may drop the logs.
+
+use serde::Serialize;
+
+use crate::redact::glob_match;
+
+/// Facets a log has exactly one value of, so that two different values rule
+/// each other out. Tags can have several values and never do.
+const SINGLE_VALUED: [&str; 4] = ["service", "status", "host", "source"];
+
+/// An exclusion filter of a log index.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Exclusion {
+    /// Name of the index
+    pub index: String,
+
+    /// Query of the logs routed to the index (`None` for all logs)
+    pub index_query: Option<String>,
+
+    /// Name of the filter
+    pub name: String,
+
+    /// Whether the filter is applied
+    pub enabled: bool,
+
+    /// Query of the logs the filter drops (`None` for all logs)
+    pub query: Option<String>,
+
+    /// Share of the matching logs dropped, from 0.0 to 1.0
+    pub sample_rate: f64,
+}
+
+/// Whether an exclusion filter drops the logs matching a query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    /// Every matching log routed to the index is subject to the filter
+    Drops,
+    /// Some matching logs may be subject to the filter
+    MayDrop,
+    /// No matching log is subject to the filter
+    NoMatch,
+}
+
+/// An enabled exclusion filter that would drop logs matching a query.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExclusionMatch {
+    /// Name of the index
+    pub index: String,
+
+    /// Name of the filter
+    pub name: String,
+
+    /// Query of the logs the filter drops (`None` for all logs)
+    pub query: Option<String>,
+
+    /// Share of the matching logs dropped, from 0.0 to 1.0
+    pub sample_rate: f64,
+
+    /// `drops` or `may_drop`
+    pub verdict: Verdict,
+}
+
+/// A term of a query: `facet:value`, `-facet:value`, or free text.
+#[derive(Debug, Clone, PartialEq)]
+struct Term {
+    negated: bool,
+    facet: Option<String>,
+    value: String,
+}
+
+impl Term {
+    fn single_valued(&self) -> bool {
+        self.facet
+            .as_deref()
+            .is_some_and(|facet| facet.starts_with('@') || SINGLE_VALUED.contains(&facet))
+    }
+}
+
+/// Compares a query with an exclusion filter's query.
+///
+/// `filter` is the terms a log must match to be dropped: the filter's query
+/// and its index's, `None` standing for all logs.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::exclusions::{Verdict, verdict};
+///
+/// let debug = [Some("status:debug")];
+/// assert_eq!(verdict("service:checkout status:debug", &debug), Verdict::Drops);
+/// assert_eq!(verdict("service:checkout", &debug), Verdict::MayDrop);
+/// assert_eq!(verdict("service:checkout status:error", &debug), Verdict::NoMatch);
+/// assert_eq!(verdict("status:error", &[None]), Verdict::Drops);
+/// ```
+pub fn verdict(query: &str, filter: &[Option<&str>]) -> Verdict {
+    let Some(query) = terms(query) else {
+        return Verdict::MayDrop;
+    };
+    let mut dropped = Vec::new();
+    let mut understood = true;
+    for part in filter.iter().flatten() {
+        match terms(part) {
+            Some(part) => dropped.extend(part),
+            None => understood = false,
+        }
+    }
+
+    if dropped
+        .iter()
+        .any(|f| query.iter().any(|q| contradicts(q, f)))
+    {
+        return Verdict::NoMatch;
+    }
+    if understood && dropped.iter().all(|f| query.iter().any(|q| implies(q, f))) {
+        Verdict::Drops
+    } else {
+        Verdict::MayDrop
+    }
+}
+
+/// The enabled exclusion filters that would drop logs matching a query,
+/// those that drop all of them first.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::exclusions::{Exclusion, Verdict, preview};
+///
+/// let filter = |name: &str, query: &str| Exclusion {
+///     index: "main".to_string(),
+///     index_query: None,
+///     name: name.to_string(),
+///     enabled: true,
+///     query: Some(query.to_string()),
+///     sample_rate: 1.0,
+/// };
+/// let filters = [filter("health checks", "@http.url_details.path:/health"), filter("debug", "status:debug")];
+/// let matches = preview("service:checkout status:debug", &filters);
+/// assert_eq!(matches.len(), 2);
+/// assert_eq!((matches[0].name.as_str(), matches[0].verdict), ("debug", Verdict::Drops));
+/// assert_eq!(matches[1].verdict, Verdict::MayDrop);
+/// ```
+pub fn preview(query: &str, exclusions: &[Exclusion]) -> Vec<ExclusionMatch> {
+    let mut matches: Vec<ExclusionMatch> = exclusions
+        .iter()
+        .filter(|exclusion| exclusion.enabled && exclusion.sample_rate > 0.0)
+        .filter_map(|exclusion| {
+            let filter = [exclusion.index_query.as_deref(), exclusion.query.as_deref()];
+            let verdict = verdict(query, &filter);
+            (verdict != Verdict::NoMatch).then(|| ExclusionMatch {
+                index: exclusion.index.clone(),
+                name: exclusion.name.clone(),
+                query: exclusion.query.clone(),
+                sample_rate: exclusion.sample_rate,
+                verdict,
+            })
+        })
+        .collect();
+    matches.sort_by_key(|m| m.verdict);
+    matches
+}
+
+/// Splits a query into its terms, or `None` if it isn't a plain list of
+/// terms all of which must match.
+fn terms(query: &str) -> Option<Vec<Term>> {
+    let mut terms = Vec::new();
+    let mut negate_next = false;
+    for token in tokens(query)? {
+        match token.as_str() {
+            "AND" => continue,
+            "NOT" => {
+                negate_next = true;
+                continue;
+            }
+            "OR" | "TO" => return None,
+            _ => {}
+        }
+        let (negated, body) = match token.strip_prefix('-') {
+            Some(body) => (true, body),
+            None => (negate_next, token.as_str()),
+        };
+        negate_next = false;
+        if body == "*" {
+            continue;
+        }
+        let (facet, value) = match body.split_once(':') {
+            Some((facet, value)) if !facet.starts_with('"') => (Some(facet.to_string()), value),
+            _ => (None, body),
+        };
+        if value.is_empty() || value.starts_with(['[', '{', '<', '>', '(']) {
+            return None;
+        }
+        terms.push(Term {
+            negated,
+            facet,
+            value: value.trim_matches('"').to_string(),
+        });
+    }
+    Some(terms)
+}
+
+/// Splits a query on whitespace outside double quotes, or returns `None` if
+/// it groups terms with parentheses.
+fn tokens(query: &str) -> Option<Vec<String>> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut chars = query.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                current.push(c);
+                current.extend(chars.next());
+            }
+            '"' => {
+                quoted = !quoted;
+                current.push(c);
+            }
+            '(' | ')' if !quoted => return None,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    Some(tokens)
+}
+
+/// Whether two values can be the same, either being a `*` pattern.
+fn overlaps(a: &str, b: &str) -> bool {
+    match (a.contains('*'), b.contains('*')) {
+        (false, false) => a == b,
+        (true, false) => glob_match(a, b),
+        (false, true) => glob_match(b, a),
+        (true, true) => {
+            // Two patterns overlap unless their fixed starts or ends differ
+            let (a_start, a_end) = (a.split('*').next(), a.rsplit('*').next());
+            let (b_start, b_end) = (b.split('*').next(), b.rsplit('*').next());
+            let starts = a_start.zip(b_start);
+            let ends = a_end.zip(b_end);
+            starts.is_none_or(|(x, y)| x.starts_with(y) || y.starts_with(x))
+                && ends.is_none_or(|(x, y)| x.ends_with(y) || y.ends_with(x))
+        }
+    }
+}
+
+/// Whether every value `value` can be is one `pattern` matches.
+fn covers(pattern: &str, value: &str) -> bool {
+    (!value.contains('*') && glob_match(pattern, value)) || pattern == value
+}
+
+/// Whether a log matching `q` can't match `f`.
+fn contradicts(q: &Term, f: &Term) -> bool {
+    if q.facet != f.facet {
+        return false;
+    }
+    match (q.negated, f.negated) {
+        (false, false) => q.single_valued() && !overlaps(&q.value, &f.value),
+        (false, true) => covers(&f.value, &q.value),
+        (true, false) => covers(&q.value, &f.value),
+        (true, true) => false,
+    }
+}
+
+/// Whether every log matching `q` matches `f`.
+fn implies(q: &Term, f: &Term) -> bool {
+    if q.facet != f.facet {
+        return false;
+    }
+    match (q.negated, f.negated) {
+        (false, false) => covers(&f.value, &q.value),
+        (true, true) => covers(&q.value, &f.value),
+        (false, true) => q.single_valued() && !overlaps(&q.value, &f.value),
+        (true, false) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verdict_terms() {
+        let filter = [Some("service:checkout -status:error")];
+        assert_eq!(
+            verdict("service:checkout status:info", &filter),
+            Verdict::Drops
+        );
+        assert_eq!(verdict("service:checkout", &filter), Verdict::MayDrop);
+        assert_eq!(
+            verdict("service:checkout status:error", &filter),
+            Verdict::NoMatch
+        );
+        assert_eq!(verdict("service:search", &filter), Verdict::NoMatch);
+
+        // Wildcards and tags, which can have several values
+        let filter = [Some("env:staging @http.url:/health*")];
+        assert_eq!(
+            verdict("env:staging @http.url:/healthz", &filter),
+            Verdict::Drops
+        );
+        assert_eq!(
+            verdict("env:prod @http.url:/health", &filter),
+            Verdict::MayDrop
+        );
+        assert_eq!(verdict("@http.url:/api/*", &filter), Verdict::NoMatch);
+        assert_eq!(verdict("-env:staging", &filter), Verdict::NoMatch);
+    }
+
+    #[test]
+    fn test_verdict_index_and_unparsed_queries() {
+        // Logs the index doesn't receive aren't dropped by its filters
+        let filter = [Some("source:nginx"), Some("status:info")];
+        assert_eq!(verdict("source:nginx status:info", &filter), Verdict::Drops);
+        assert_eq!(
+            verdict("source:java status:info", &filter),
+            Verdict::NoMatch
+        );
+
+        // Groups and OR can't be ruled out
+        let filter = [None, Some("status:(debug OR info)")];
+        assert_eq!(verdict("status:debug", &filter), Verdict::MayDrop);
+        assert_eq!(
+            verdict("service:a OR service:b", &[Some("service:c")]),
+            Verdict::MayDrop
+        );
+        assert_eq!(
+            verdict("NOT status:debug", &[Some("status:debug")]),
+            Verdict::NoMatch
+        );
+        assert_eq!(
+            verdict("\"connection reset\"", &[Some("\"connection reset\"")]),
+            Verdict::Drops
+        );
+    }
+
+    #[test]
+    fn test_preview_skips_disabled_filters() {
+        let exclusion = Exclusion {
+            index: "main".to_string(),
+            index_query: None,
+            name: "all".to_string(),
+            enabled: false,
+            query: None,
+            sample_rate: 1.0,
+        };
+        assert!(preview("*", std::slice::from_ref(&exclusion)).is_empty());
+        let enabled = Exclusion {
+            enabled: true,
+            ..exclusion
+        };
+        assert_eq!(preview("*", &[enabled])[0].verdict, Verdict::Drops);
+    }
+}
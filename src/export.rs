@@ -0,0 +1,474 @@
+//! Bulk export specs (`ddog export`).
+//!
+//! An export spec is a TOML file listing searches and where to write their
+//! results:
+//!
+//! ```toml
+//! concurrency = 4
+//! manifest = "out/manifest.json"
+//!
+//! [[job]]
+//! id = "api-errors"
+//! domain = "logs"
+//! query = "service:api status:error"
+//! from = "now-1d"
+//! output = "out/api-errors.ndjson"
+//!
+//! [[job]]
+//! id = "checkout-latency"
+//! domain = "metrics"
+//! query = "p99:trace.http.request.duration{service:checkout}"
+//! from = "now-1d"
+//! output = "out/checkout-latency.ndjson"
+//! ```
+//!
+//! Relative paths are relative to the spec's directory. Every job that
+//! finishes is appended to a checkpoint file next to the manifest, so a
+//! failed or interrupted export can be re-run and only does the jobs left;
+//! once every job has finished, the manifest lists them all and the
+//! checkpoint is removed.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::error::AppError;
+
+/// Jobs run at once when the spec doesn't say.
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+/// What an export job searches.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportDomain {
+    /// Logs, written as the API returns them
+    #[default]
+    Logs,
+
+    /// APM spans, written as the API returns them
+    Spans,
+
+    /// Metric timeseries, written one `{metric, scope, tags, ts, value}` row per point
+    Metrics,
+}
+
+/// How an export job's records are written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// One JSON object per line
+    #[default]
+    Ndjson,
+
+    /// One human-readable line per record
+    Text,
+}
+
+/// One search of an export spec.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExportJob {
+    /// Unique name of the job
+    pub id: String,
+
+    /// What to search (default: logs)
+    #[serde(default)]
+    pub domain: ExportDomain,
+
+    /// Search query, or metric query for metrics
+    pub query: String,
+
+    /// Start time (default: now-1h)
+    #[serde(default = "default_from")]
+    pub from: String,
+
+    /// End time (default: now)
+    #[serde(default = "default_to")]
+    pub to: String,
+
+    /// File the records are written to
+    pub output: PathBuf,
+
+    /// Output format (default: ndjson)
+    #[serde(default)]
+    pub format: ExportFormat,
+
+    /// Maximum records (or metric points) to write; 0 for all
+    #[serde(default)]
+    pub limit: u64,
+}
+
+fn default_from() -> String {
+    "now-1h".to_string()
+}
+
+fn default_to() -> String {
+    "now".to_string()
+}
+
+/// A declarative bulk export.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExportSpec {
+    /// Jobs run at once
+    #[serde(default = "default_concurrency")]
+    pub concurrency: usize,
+
+    /// Manifest written once every job has finished
+    #[serde(default = "default_manifest")]
+    pub manifest: PathBuf,
+
+    /// Jobs, in the order they are started
+    #[serde(rename = "job", default)]
+    pub jobs: Vec<ExportJob>,
+}
+
+fn default_concurrency() -> usize {
+    DEFAULT_CONCURRENCY
+}
+
+fn default_manifest() -> PathBuf {
+    PathBuf::from("manifest.json")
+}
+
+impl ExportSpec {
+    /// Loads a spec, resolving its paths against the spec's directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Config` if the file cannot be read or parsed, has
+    /// no jobs, or reuses a job id or output path.
+    pub fn load(path: &Path) -> Result<Self, AppError> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| AppError::Config(format!("Failed to read {}: {}", path.display(), e)))?;
+        let base = path.parent().unwrap_or(Path::new(""));
+        Self::parse(&contents, base)
+            .map_err(|e| AppError::Config(format!("Invalid {}: {}", path.display(), e)))
+    }
+
+    /// Parses a spec, resolving its paths against `base`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a description of the problem if the spec is malformed, has no
+    /// jobs, or reuses a job id or output path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ddog::export::{ExportDomain, ExportSpec};
+    /// use std::path::Path;
+    ///
+    /// let spec = ExportSpec::parse(
+    ///     "[[job]]\nid = \"errors\"\nquery = \"status:error\"\noutput = \"errors.ndjson\"\n",
+    ///     Path::new("exports"),
+    /// )
+    /// .unwrap();
+    /// assert_eq!(spec.jobs[0].domain, ExportDomain::Logs);
+    /// assert_eq!(spec.jobs[0].output, Path::new("exports/errors.ndjson"));
+    /// assert_eq!(spec.manifest, Path::new("exports/manifest.json"));
+    /// ```
+    pub fn parse(contents: &str, base: &Path) -> Result<Self, String> {
+        let mut spec: Self = toml::from_str(contents).map_err(|e| e.to_string())?;
+        if spec.jobs.is_empty() {
+            return Err("no [[job]] entries".to_string());
+        }
+        if spec.concurrency == 0 {
+            return Err("concurrency must be at least 1".to_string());
+        }
+        let mut ids = HashSet::new();
+        let mut outputs = HashSet::new();
+        for job in &mut spec.jobs {
+            job.output = base.join(&job.output);
+            if !ids.insert(job.id.clone()) {
+                return Err(format!("job id '{}' is used more than once", job.id));
+            }
+            if !outputs.insert(job.output.clone()) {
+                return Err(format!(
+                    "output {} is used by more than one job",
+                    job.output.display()
+                ));
+            }
+        }
+        spec.manifest = base.join(&spec.manifest);
+        Ok(spec)
+    }
+
+    /// The checkpoint file, next to the manifest (e.g. `manifest.json.checkpoint`).
+    pub fn checkpoint_path(&self) -> PathBuf {
+        let mut path = self.manifest.clone().into_os_string();
+        path.push(".checkpoint");
+        PathBuf::from(path)
+    }
+}
+
+/// A finished job, as recorded in the checkpoint and the manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FinishedJob {
+    /// The job as it was specified
+    pub job: ExportJob,
+
+    /// Records written
+    pub records: u64,
+
+    /// When the job finished (RFC 3339)
+    pub finished_at: String,
+}
+
+/// Jobs finished by earlier runs of an export.
+#[derive(Debug, Default)]
+pub struct Checkpoint {
+    finished: Vec<FinishedJob>,
+}
+
+impl Checkpoint {
+    /// Loads the jobs recorded in a checkpoint file; a missing file has none.
+    ///
+    /// Lines that don't parse, such as one cut short by a crash, are skipped,
+    /// so their jobs run again.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Io` if the file exists but cannot be read.
+    pub fn load(path: &Path) -> Result<Self, AppError> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self {
+            finished: contents
+                .lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect(),
+        })
+    }
+
+    /// The earlier run of `job`, if one finished with the same definition.
+    pub fn finished(&self, job: &ExportJob) -> Option<&FinishedJob> {
+        self.finished.iter().rev().find(|f| &f.job == job)
+    }
+
+    /// Appends a finished job to the checkpoint file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Io` if the file cannot be written.
+    pub fn append(path: &Path, finished: &FinishedJob) -> Result<(), AppError> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        let mut line = serde_json::to_string(finished)?;
+        line.push('\n');
+        file.write_all(line.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// How a job of the manifest ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    /// Finished in this run
+    Done,
+
+    /// Finished by an earlier run, per the checkpoint
+    Resumed,
+
+    /// Failed in this run
+    Failed,
+}
+
+/// A job's outcome, as written to stdout as it ends and listed in the manifest.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JobReport {
+    /// Job id
+    pub id: String,
+
+    /// Searched domain
+    pub domain: ExportDomain,
+
+    /// Search query
+    pub query: String,
+
+    /// Start time, as specified
+    pub from: String,
+
+    /// End time, as specified
+    pub to: String,
+
+    /// File the records were written to
+    pub output: PathBuf,
+
+    /// How the job ended
+    pub status: JobStatus,
+
+    /// Records written
+    pub records: u64,
+
+    /// When the job finished (RFC 3339)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<String>,
+
+    /// Why the job failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl JobReport {
+    /// Reports a finished job.
+    pub fn finished(finished: &FinishedJob, status: JobStatus) -> Self {
+        let job = &finished.job;
+        Self {
+            id: job.id.clone(),
+            domain: job.domain,
+            query: job.query.clone(),
+            from: job.from.clone(),
+            to: job.to.clone(),
+            output: job.output.clone(),
+            status,
+            records: finished.records,
+            finished_at: Some(finished.finished_at.clone()),
+            error: None,
+        }
+    }
+
+    /// Reports a failed job.
+    pub fn failed(job: &ExportJob, error: &AppError) -> Self {
+        Self {
+            id: job.id.clone(),
+            domain: job.domain,
+            query: job.query.clone(),
+            from: job.from.clone(),
+            to: job.to.clone(),
+            output: job.output.clone(),
+            status: JobStatus::Failed,
+            records: 0,
+            finished_at: None,
+            error: Some(error.to_string()),
+        }
+    }
+}
+
+/// The manifest of an export, listing every job in spec order.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Manifest {
+    /// Spec file the export ran
+    pub spec: PathBuf,
+
+    /// When the export finished (RFC 3339)
+    pub finished_at: String,
+
+    /// Records written across all jobs
+    pub records: u64,
+
+    /// Jobs, in spec order
+    pub jobs: Vec<JobReport>,
+}
+
+impl Manifest {
+    /// Writes the manifest as pretty-printed JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Io` if the file cannot be written.
+    pub fn write(&self, path: &Path) -> Result<(), AppError> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        let mut json = serde_json::to_string_pretty(self)?;
+        json.push('\n');
+        fs::write(path, json)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEC: &str = r#"
+concurrency = 2
+manifest = "out/manifest.json"
+
+[[job]]
+id = "errors"
+query = "status:error"
+from = "now-1d"
+output = "out/errors.ndjson"
+
+[[job]]
+id = "cpu"
+domain = "metrics"
+query = "avg:system.cpu.user{*}"
+output = "out/cpu.txt"
+format = "text"
+limit = 100
+"#;
+
+    #[test]
+    fn test_parse_spec() {
+        let spec = ExportSpec::parse(SPEC, Path::new("/data")).unwrap();
+        assert_eq!(spec.concurrency, 2);
+        assert_eq!(spec.manifest, Path::new("/data/out/manifest.json"));
+        assert_eq!(
+            spec.checkpoint_path(),
+            Path::new("/data/out/manifest.json.checkpoint")
+        );
+        assert_eq!(spec.jobs[0].to, "now");
+        assert_eq!(spec.jobs[1].domain, ExportDomain::Metrics);
+        assert_eq!(spec.jobs[1].format, ExportFormat::Text);
+        assert_eq!(spec.jobs[1].limit, 100);
+    }
+
+    #[test]
+    fn test_parse_spec_rejects_invalid() {
+        let base = Path::new("");
+        assert!(ExportSpec::parse("concurrency = 2", base).is_err());
+        let twice = "[[job]]\nid = \"a\"\nquery = \"*\"\noutput = \"a\"\n".repeat(2);
+        assert!(
+            ExportSpec::parse(&twice, base)
+                .unwrap_err()
+                .contains("used more than once")
+        );
+        let typo = "[[job]]\nid = \"a\"\nquery = \"*\"\noutput = \"a\"\ndomian = \"logs\"\n";
+        assert!(ExportSpec::parse(typo, base).is_err());
+    }
+
+    #[test]
+    fn test_checkpoint_round_trip() {
+        let dir = std::env::temp_dir().join(format!("ddog-export-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("manifest.json.checkpoint");
+        let spec = ExportSpec::parse(SPEC, &dir).unwrap();
+
+        let finished = FinishedJob {
+            job: spec.jobs[0].clone(),
+            records: 42,
+            finished_at: "2024-01-15T10:00:00.000Z".to_string(),
+        };
+        Checkpoint::append(&path, &finished).unwrap();
+        // A line cut short by a crash is skipped
+        fs::write(
+            &path,
+            fs::read_to_string(&path).unwrap() + "{\"job\": {\"id\"",
+        )
+        .unwrap();
+
+        let checkpoint = Checkpoint::load(&path).unwrap();
+        assert_eq!(checkpoint.finished(&spec.jobs[0]), Some(&finished));
+        assert_eq!(checkpoint.finished(&spec.jobs[1]), None);
+
+        let mut changed = spec.jobs[0].clone();
+        changed.from = "now-2d".to_string();
+        assert_eq!(checkpoint.finished(&changed), None);
+
+        assert!(
+            Checkpoint::load(&dir.join("missing"))
+                .unwrap()
+                .finished(&spec.jobs[0])
+                .is_none()
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
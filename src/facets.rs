@@ -0,0 +1,220 @@
+//! Facet discovery from sampled logs.
+//!
+//! Datadog has no API listing the attributes present in a set of logs, so
+//! [`discover`] walks a sample of matching logs and collects the reserved
+//! attributes, custom `@` attributes, and tag keys they carry. Each candidate
+//! is then summarized with its cardinality and top values via the aggregate
+//! API. [`rank`] turns one facet's top values into a ranked list for
+//! `ddog logs top`.
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+use crate::client::FacetValue;
+
+/// Reserved log attributes, faceted without an `@` prefix.
+const RESERVED: [&str; 4] = ["service", "host", "status", "source"];
+
+/// A facet seen in the sample.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FacetCandidate {
+    /// Facet name as used in queries (e.g., "service", "@http.status_code", "env")
+    pub facet: String,
+
+    /// Number of sampled logs carrying the facet
+    pub seen: usize,
+}
+
+/// Summary of one facet over the query's time range.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FacetSummary {
+    /// Facet name as used in queries
+    pub facet: String,
+
+    /// Share of sampled logs carrying the facet, from 0.0 to 1.0
+    pub coverage: f64,
+
+    /// Number of distinct values across all matching logs, if known
+    pub cardinality: Option<u64>,
+
+    /// Most frequent values across all matching logs
+    pub top: Vec<FacetValue>,
+}
+
+/// One value in a facet's top-N ranking.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RankedValue {
+    /// Position in the ranking, starting at 1
+    pub rank: usize,
+
+    /// Facet the value belongs to
+    pub facet: String,
+
+    /// Facet value (e.g., "api" for the service facet)
+    pub value: String,
+
+    /// Number of logs with the value, or distinct values for `--by unique:<facet>`
+    pub count: f64,
+
+    /// Share of all matching logs with the value, from 0.0 to 1.0; only for counts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub share: Option<f64>,
+}
+
+/// Ranks a facet's values as returned by the aggregate API, largest first.
+///
+/// `total` is the number of matching logs, when the values are log counts;
+/// each value's share is computed from it.
+pub fn rank(facet: &str, mut values: Vec<FacetValue>, total: Option<f64>) -> Vec<RankedValue> {
+    values.sort_by(|a, b| b.count.total_cmp(&a.count));
+    values
+        .into_iter()
+        .enumerate()
+        .map(|(index, v)| RankedValue {
+            rank: index + 1,
+            facet: facet.to_string(),
+            share: total.filter(|t| *t > 0.0).map(|t| v.count / t),
+            value: v.value,
+            count: v.count,
+        })
+        .collect()
+}
+
+/// Collects the facets carried by sampled logs, most common first.
+///
+/// Logs are read in the shape the logs search API returns them: reserved
+/// attributes and `tags` under `attributes`, and custom attributes under
+/// `attributes.attributes`. Nested custom attributes become dotted paths.
+pub fn discover(logs: &[Value]) -> Vec<FacetCandidate> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+
+    for log in logs {
+        let Some(attributes) = log.get("attributes").and_then(Value::as_object) else {
+            continue;
+        };
+        let mut facets = Vec::new();
+        for reserved in RESERVED {
+            if attributes.get(reserved).is_some_and(|v| !v.is_null()) {
+                facets.push(reserved.to_string());
+            }
+        }
+        if let Some(custom) = attributes.get("attributes").and_then(Value::as_object) {
+            custom_paths(custom, "@", &mut facets);
+        }
+        for tag in attributes
+            .get("tags")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+        {
+            if let Some((key, _)) = tag.split_once(':')
+                && !key.is_empty()
+                && !RESERVED.contains(&key)
+            {
+                facets.push(key.to_string());
+            }
+        }
+
+        facets.sort_unstable();
+        facets.dedup();
+        for facet in facets {
+            *seen.entry(facet).or_default() += 1;
+        }
+    }
+
+    let mut candidates: Vec<FacetCandidate> = seen
+        .into_iter()
+        .map(|(facet, seen)| FacetCandidate { facet, seen })
+        .collect();
+    candidates.sort_by(|a, b| b.seen.cmp(&a.seen).then_with(|| a.facet.cmp(&b.facet)));
+    candidates
+}
+
+/// Appends the dotted paths of a custom attribute object's scalar leaves.
+fn custom_paths(object: &Map<String, Value>, prefix: &str, paths: &mut Vec<String>) {
+    for (key, value) in object {
+        let path = format!("{}{}", prefix, key);
+        match value {
+            Value::Object(child) => custom_paths(child, &format!("{}.", path), paths),
+            Value::Null => {}
+            _ => paths.push(path),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_discover() {
+        let logs = vec![
+            json!({"attributes": {
+                "service": "api",
+                "host": "web-1",
+                "status": "error",
+                "tags": ["env:prod", "service:api", "team:payments"],
+                "attributes": {"http": {"status_code": 500, "url": "/pay"}, "trace_id": null}
+            }}),
+            json!({"attributes": {
+                "service": "api",
+                "host": null,
+                "tags": ["env:prod", "env:canary"],
+                "attributes": {"http": {"status_code": 200}}
+            }}),
+        ];
+
+        let facets: Vec<(String, usize)> = discover(&logs)
+            .into_iter()
+            .map(|c| (c.facet, c.seen))
+            .collect();
+        assert_eq!(
+            facets,
+            vec![
+                ("@http.status_code".to_string(), 2),
+                ("env".to_string(), 2),
+                ("service".to_string(), 2),
+                ("@http.url".to_string(), 1),
+                ("host".to_string(), 1),
+                ("status".to_string(), 1),
+                ("team".to_string(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rank() {
+        let values = vec![
+            FacetValue {
+                value: "web".to_string(),
+                count: 10.0,
+            },
+            FacetValue {
+                value: "api".to_string(),
+                count: 30.0,
+            },
+        ];
+
+        let ranked = rank("service", values.clone(), Some(50.0));
+        assert_eq!(ranked[0].rank, 1);
+        assert_eq!(ranked[0].value, "api");
+        assert_eq!(ranked[0].share, Some(0.6));
+        assert_eq!(ranked[1].rank, 2);
+        assert_eq!(ranked[1].facet, "service");
+        assert_eq!(ranked[1].share, Some(0.2));
+
+        assert!(
+            rank("service", values, None)
+                .iter()
+                .all(|v| v.share.is_none())
+        );
+    }
+
+    #[test]
+    fn test_discover_skips_other_shapes() {
+        assert!(discover(&[json!({"id": "x"}), json!("text")]).is_empty());
+    }
+}
@@ -0,0 +1,242 @@
+//! Unstable host detection from agent restarts and reporting gaps.
+//!
+//! An agent that keeps restarting, or a host whose agent keeps going quiet,
+//! shows up in three places: agent start events, gaps in the agent's own
+//! `datadog.agent.running` metric, and the host's metadata. [`report`] joins
+//! them per host, resolving host aliases, and ranks the hosts with repeated
+//! restarts or gaps most unstable first.
+
+use chrono::{SecondsFormat, TimeZone, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::client::{AgentStart, HostSummary};
+use crate::gaps::Gap;
+
+/// Metric every running agent reports, split by host.
+pub const AGENT_RUNNING: &str = "datadog.agent.running";
+
+/// Instability summary for one host.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HostFlaps {
+    /// Position in the most-unstable-first ranking, starting at 1
+    pub rank: usize,
+
+    /// Host name
+    pub host: String,
+
+    /// Number of agent starts in the range
+    pub restarts: usize,
+
+    /// Number of stretches in which the agent stopped reporting
+    pub gaps: usize,
+
+    /// Total seconds the agent wasn't reporting
+    pub gap_seconds: i64,
+
+    /// Whether the agent was still not reporting at the end of the range
+    pub ongoing_gap: bool,
+
+    /// Most recent agent start (RFC3339, UTC)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_restart: Option<String>,
+
+    /// Whether the host is currently up; absent for hosts without metadata
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub up: Option<bool>,
+
+    /// Version of the Datadog agent on the host
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent_version: Option<String>,
+}
+
+impl HostFlaps {
+    fn new(host: &str) -> Self {
+        Self {
+            rank: 0,
+            host: host.to_string(),
+            restarts: 0,
+            gaps: 0,
+            gap_seconds: 0,
+            ongoing_gap: false,
+            last_restart: None,
+            up: None,
+            agent_version: None,
+        }
+    }
+}
+
+/// Joins agent starts and gaps per host, keeping hosts with at least
+/// `min_flaps` restarts or `min_flaps` gaps, most unstable first.
+///
+/// Starts and gaps are attributed to hosts by name or alias. With `scoped`,
+/// only hosts in `hosts` are reported, since agent start events can't be
+/// filtered by host tags.
+pub fn report(
+    hosts: &[HostSummary],
+    starts: &[AgentStart],
+    gaps: &[Gap],
+    min_flaps: usize,
+    scoped: bool,
+) -> Vec<HostFlaps> {
+    let canonical: HashMap<&str, &str> = hosts
+        .iter()
+        .flat_map(|h| {
+            h.aliases
+                .iter()
+                .map(|alias| (alias.as_str(), h.name.as_str()))
+                .chain([(h.name.as_str(), h.name.as_str())])
+        })
+        .collect();
+    let resolve = |name: &str| -> Option<String> {
+        match canonical.get(name) {
+            Some(host) => Some(host.to_string()),
+            None if scoped => None,
+            None => Some(name.to_string()),
+        }
+    };
+
+    let mut flaps: BTreeMap<String, HostFlaps> = BTreeMap::new();
+    for start in starts {
+        let Some(host) = resolve(&start.host) else {
+            continue;
+        };
+        let entry = flaps
+            .entry(host.clone())
+            .or_insert_with(|| HostFlaps::new(&host));
+        entry.restarts += 1;
+        entry.last_restart = Some(format_millis(start.timestamp));
+    }
+    for gap in gaps {
+        let Some(host) = gap_host(&gap.series).and_then(resolve) else {
+            continue;
+        };
+        let entry = flaps
+            .entry(host.clone())
+            .or_insert_with(|| HostFlaps::new(&host));
+        entry.gaps += 1;
+        entry.gap_seconds += gap.duration_seconds;
+        entry.ongoing_gap |= gap.ongoing;
+    }
+
+    let mut ranked: Vec<HostFlaps> = flaps
+        .into_values()
+        .filter(|f| f.restarts >= min_flaps || f.gaps >= min_flaps)
+        .map(|mut f| {
+            if let Some(host) = hosts.iter().find(|h| h.name == f.host) {
+                f.up = Some(host.up);
+                f.agent_version = host.agent_version.clone();
+            }
+            f
+        })
+        .collect();
+    ranked.sort_by(|a, b| {
+        (b.restarts + b.gaps)
+            .cmp(&(a.restarts + a.gaps))
+            .then(b.gap_seconds.cmp(&a.gap_seconds))
+            .then_with(|| a.host.cmp(&b.host))
+    });
+    for (index, host) in ranked.iter_mut().enumerate() {
+        host.rank = index + 1;
+    }
+    ranked
+}
+
+/// The host named by a series scope such as "env:prod,host:web-1".
+fn gap_host(scope: &str) -> Option<&str> {
+    scope
+        .split(',')
+        .find_map(|tag| tag.trim().strip_prefix("host:"))
+}
+
+fn format_millis(millis: i64) -> String {
+    Utc.timestamp_millis_opt(millis)
+        .single()
+        .map(|t| t.to_rfc3339_opts(SecondsFormat::Millis, true))
+        .unwrap_or_else(|| millis.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn host(name: &str, aliases: &[&str]) -> HostSummary {
+        HostSummary {
+            name: name.to_string(),
+            aliases: aliases.iter().map(|a| a.to_string()).collect(),
+            up: true,
+            last_reported: None,
+            agent_version: Some("7.50.0".to_string()),
+        }
+    }
+
+    fn start(host: &str, timestamp: i64) -> AgentStart {
+        AgentStart {
+            host: host.to_string(),
+            timestamp,
+        }
+    }
+
+    fn gap(series: &str, duration_seconds: i64, ongoing: bool) -> Gap {
+        Gap {
+            series: series.to_string(),
+            start: 0,
+            end: duration_seconds,
+            duration_seconds,
+            missing_points: 1,
+            ongoing,
+        }
+    }
+
+    #[test]
+    fn test_report() {
+        let hosts = [host("web-1", &["i-0abc"]), host("web-2", &[])];
+        let starts = [
+            start("web-1", 1_705_312_800_000),
+            start("i-0abc", 1_705_316_400_000),
+            start("web-2", 1_705_312_800_000),
+            start("db-9", 1_705_312_800_000),
+            start("db-9", 1_705_316_400_000),
+        ];
+        let gaps = [
+            gap("env:prod,host:web-2", 600, false),
+            gap("env:prod,host:web-2", 300, true),
+            gap("env:prod,host:web-1", 60, false),
+        ];
+
+        let ranked = report(&hosts, &starts, &gaps, 2, false);
+        let order: Vec<(usize, &str)> = ranked.iter().map(|h| (h.rank, h.host.as_str())).collect();
+        assert_eq!(order, vec![(1, "web-2"), (2, "web-1"), (3, "db-9")]);
+
+        assert_eq!(ranked[0].restarts, 1);
+        assert_eq!(ranked[0].gaps, 2);
+        assert_eq!(ranked[0].gap_seconds, 900);
+        assert!(ranked[0].ongoing_gap);
+        assert_eq!(ranked[1].restarts, 2);
+        assert_eq!(
+            ranked[1].last_restart.as_deref(),
+            Some("2024-01-15T11:00:00.000Z")
+        );
+        assert_eq!(ranked[1].up, Some(true));
+        assert_eq!(ranked[2].up, None);
+        assert_eq!(ranked[2].agent_version, None);
+    }
+
+    #[test]
+    fn test_report_scoped_and_threshold() {
+        let hosts = [host("web-1", &[])];
+        let starts = [start("db-9", 0), start("db-9", 1), start("web-1", 0)];
+
+        assert!(report(&hosts, &starts, &[], 2, true).is_empty());
+        let ranked = report(&hosts, &starts, &[], 1, true);
+        assert_eq!(ranked.len(), 1);
+        assert_eq!(ranked[0].host, "web-1");
+    }
+
+    #[test]
+    fn test_gap_host() {
+        assert_eq!(gap_host("env:prod,host:web-1"), Some("web-1"));
+        assert_eq!(gap_host("host:web-1"), Some("web-1"));
+        assert_eq!(gap_host("env:prod"), None);
+    }
+}
@@ -0,0 +1,168 @@
+//! Missing-data detection for metric series.
+//!
+//! A series reports at a steady interval, so a stretch with no points longer
+//! than that interval means the source stopped reporting. [`find_gaps`] walks
+//! each series of a metrics query and reports those stretches, including a
+//! series that started late or has stopped by the end of the range.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::client::MetricPoint;
+
+/// Intervals between points over which a stretch counts as a gap.
+const GAP_FACTOR: f64 = 1.5;
+
+/// Extra intervals allowed at the edges of the range, for ingestion delay.
+const EDGE_ALLOWANCE: f64 = 1.0;
+
+/// A stretch of a series with no points.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Gap {
+    /// Series scope, naming its tag combination (e.g., "env:prod,host:web-1")
+    pub series: String,
+
+    /// Last point before the gap, or the start of the range (Unix seconds)
+    pub start: i64,
+
+    /// First point after the gap, or the end of the range (Unix seconds)
+    pub end: i64,
+
+    /// Length of the gap in seconds
+    pub duration_seconds: i64,
+
+    /// Number of points expected within the gap
+    pub missing_points: i64,
+
+    /// Whether the series was still missing at the end of the range
+    pub ongoing: bool,
+}
+
+/// Finds gaps in every series of a metrics query result, ordered by series
+/// then start.
+///
+/// `interval` is the expected reporting interval in seconds; when `None`, each
+/// series' median spacing between points is used (or the median across all
+/// series, for series with a single point).
+pub fn find_gaps(points: &[MetricPoint], from: i64, to: i64, interval: Option<i64>) -> Vec<Gap> {
+    let mut series: BTreeMap<&str, Vec<i64>> = BTreeMap::new();
+    for point in points {
+        series
+            .entry(&point.scope)
+            .or_default()
+            .push(point.timestamp);
+    }
+    for timestamps in series.values_mut() {
+        timestamps.sort_unstable();
+        timestamps.dedup();
+    }
+
+    let overall = median_spacing(series.values().flat_map(|t| spacings(t)).collect());
+    let mut gaps = Vec::new();
+
+    for (scope, timestamps) in &series {
+        let Some(step) = interval
+            .or_else(|| median_spacing(spacings(timestamps).collect()))
+            .or(overall)
+            .filter(|step| *step > 0)
+        else {
+            continue;
+        };
+        let step = step as f64;
+        let gap = |start: i64, end: i64, ongoing: bool| Gap {
+            series: scope.to_string(),
+            start,
+            end,
+            duration_seconds: end - start,
+            missing_points: ((end - start) as f64 / step).round() as i64 - 1,
+            ongoing,
+        };
+
+        let (Some(first), Some(last)) = (timestamps.first(), timestamps.last()) else {
+            continue;
+        };
+        if (first - from) as f64 > step * (GAP_FACTOR + EDGE_ALLOWANCE) {
+            gaps.push(gap(from, *first, false));
+        }
+        for pair in timestamps.windows(2) {
+            if (pair[1] - pair[0]) as f64 > step * GAP_FACTOR {
+                gaps.push(gap(pair[0], pair[1], false));
+            }
+        }
+        if (to - last) as f64 > step * (GAP_FACTOR + EDGE_ALLOWANCE) {
+            gaps.push(gap(*last, to, true));
+        }
+    }
+    gaps
+}
+
+fn spacings(timestamps: &[i64]) -> impl Iterator<Item = i64> + '_ {
+    timestamps.windows(2).map(|pair| pair[1] - pair[0])
+}
+
+fn median_spacing(mut spacings: Vec<i64>) -> Option<i64> {
+    spacings.sort_unstable();
+    spacings.get(spacings.len() / 2).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(scope: &str, timestamp: i64) -> MetricPoint {
+        MetricPoint {
+            metric: "system.cpu.user".to_string(),
+            display_name: None,
+            query_index: Some(0),
+            aggr: Some("avg".to_string()),
+            scope: scope.to_string(),
+            tag_set: vec![scope.to_string()],
+            timestamp,
+            value: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_find_gaps() {
+        let mut points: Vec<MetricPoint> = [0, 60, 120, 360, 420, 480]
+            .into_iter()
+            .map(|t| point("host:a", t))
+            .collect();
+        points.extend([0, 60, 120, 180].into_iter().map(|t| point("host:b", t)));
+
+        let gaps = find_gaps(&points, 0, 500, None);
+        assert_eq!(
+            gaps,
+            vec![
+                Gap {
+                    series: "host:a".to_string(),
+                    start: 120,
+                    end: 360,
+                    duration_seconds: 240,
+                    missing_points: 3,
+                    ongoing: false,
+                },
+                Gap {
+                    series: "host:b".to_string(),
+                    start: 180,
+                    end: 500,
+                    duration_seconds: 320,
+                    missing_points: 4,
+                    ongoing: true,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_gaps_with_interval() {
+        let points: Vec<MetricPoint> = [300, 320, 340].into_iter().map(|t| point("*", t)).collect();
+
+        assert!(find_gaps(&points, 300, 340, Some(30)).is_empty());
+
+        let gaps = find_gaps(&points, 0, 340, Some(20));
+        assert_eq!(gaps.len(), 1);
+        assert_eq!((gaps[0].start, gaps[0].end), (0, 300));
+        assert_eq!(gaps[0].missing_points, 14);
+    }
+}
@@ -0,0 +1,297 @@
+//! On-call handoff summaries.
+//!
+//! Collects what happened to a team over a shift (triggered monitors,
+//! declared incidents, new error patterns, and SLO burn) and renders it as
+//! Markdown for pasting into a handoff document. Sections are gathered
+//! independently, so an API that fails leaves a note in its own section
+//! rather than losing the whole summary.
+
+use serde::Serialize;
+
+use crate::client::IncidentSummary;
+use crate::episodes::Episode;
+use crate::links::DatadogLinks;
+use crate::patterns::PatternCount;
+
+/// SLOs burning error budget at least this many times faster than sustainable are notable.
+pub const NOTABLE_BURN_RATE: f64 = 1.0;
+
+/// New error patterns listed in the summary.
+pub const TOP_PATTERNS: usize = 5;
+
+/// One section of the summary: its items, or why they couldn't be fetched.
+pub type Section<T> = Result<Vec<T>, String>;
+
+/// A monitor that alerted during the handoff window.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TriggeredMonitor {
+    /// ID of the monitor
+    pub monitor_id: i64,
+
+    /// Monitor name
+    pub name: String,
+
+    /// Number of alert episodes in the window
+    pub episodes: usize,
+
+    /// Episodes still open at the end of the window
+    pub open: usize,
+
+    /// Worst state reached: warn, no_data, or alert
+    pub severity: &'static str,
+}
+
+impl TriggeredMonitor {
+    /// Summarizes a monitor's episodes; `None` if it never left OK.
+    pub fn from_episodes(monitor_id: i64, name: &str, episodes: &[Episode]) -> Option<Self> {
+        let severity =
+            episodes
+                .iter()
+                .map(|e| e.severity)
+                .max_by_key(|severity| match *severity {
+                    "alert" => 2,
+                    "no_data" => 1,
+                    _ => 0,
+                })?;
+
+        Some(Self {
+            monitor_id,
+            name: name.to_string(),
+            episodes: episodes.len(),
+            open: episodes.iter().filter(|e| e.end.is_none()).count(),
+            severity,
+        })
+    }
+}
+
+/// An SLO's performance over the handoff window.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SloBurn {
+    /// SLO ID
+    pub slo_id: String,
+
+    /// SLO name
+    pub name: String,
+
+    /// Target, in percent good
+    pub target: f64,
+
+    /// SLI over the window, in percent good
+    pub sli: f64,
+
+    /// Error rate relative to the rate the target allows; null for 100% targets
+    pub burn_rate: Option<f64>,
+}
+
+impl SloBurn {
+    /// Computes the burn rate of an SLO from its target and SLI.
+    pub fn new(slo_id: &str, name: &str, target: f64, sli: f64) -> Self {
+        let budget = 100.0 - target;
+        Self {
+            slo_id: slo_id.to_string(),
+            name: name.to_string(),
+            target,
+            sli,
+            burn_rate: (budget > 0.0).then(|| (100.0 - sli) / budget),
+        }
+    }
+
+    /// Whether the SLO is below target or burning budget at [`NOTABLE_BURN_RATE`] or faster.
+    pub fn is_notable(&self) -> bool {
+        self.sli < self.target || self.burn_rate.is_some_and(|r| r >= NOTABLE_BURN_RATE)
+    }
+}
+
+/// Everything that goes into a handoff summary.
+#[derive(Debug, Clone)]
+pub struct Handoff {
+    /// Team the summary covers
+    pub team: String,
+
+    /// Start of the window, as displayed
+    pub from: String,
+
+    /// End of the window, as displayed
+    pub to: String,
+
+    /// Monitors that alerted, noisiest first
+    pub monitors: Section<TriggeredMonitor>,
+
+    /// Incidents declared in the window, newest first
+    pub incidents: Section<IncidentSummary>,
+
+    /// Error patterns absent from the previous window, most frequent first
+    pub patterns: Section<PatternCount>,
+
+    /// Notable SLOs, fastest burning first
+    pub slos: Section<SloBurn>,
+}
+
+/// Renders a handoff summary as Markdown, linking items to the Datadog UI.
+pub fn render_markdown(handoff: &Handoff, links: &DatadogLinks) -> String {
+    let mut out = format!(
+        "# On-call handoff: {}\n\n_{} to {}_\n",
+        handoff.team, handoff.from, handoff.to
+    );
+
+    section(&mut out, "Triggered monitors", &handoff.monitors, |m| {
+        format!(
+            "{}: {} {}{}, worst state {}",
+            link(&m.name, &links.monitor(m.monitor_id)),
+            m.episodes,
+            if m.episodes == 1 {
+                "episode"
+            } else {
+                "episodes"
+            },
+            if m.open > 0 {
+                format!(" ({} still open)", m.open)
+            } else {
+                String::new()
+            },
+            m.severity
+        )
+    });
+
+    section(&mut out, "Incidents", &handoff.incidents, |i| {
+        format!(
+            "{}: {}, {}, declared {}",
+            link(
+                &format!("#{} {}", i.public_id, i.title),
+                &links.incident(i.public_id)
+            ),
+            i.severity,
+            i.state.as_deref().unwrap_or("unknown state"),
+            i.created
+        )
+    });
+
+    section(&mut out, "New error patterns", &handoff.patterns, |p| {
+        format!(
+            "`{}`: {} {}",
+            p.pattern.replace('`', "'"),
+            p.count,
+            if p.count == 1 { "log" } else { "logs" }
+        )
+    });
+
+    section(&mut out, "SLO burn", &handoff.slos, |s| {
+        let burn = s
+            .burn_rate
+            .map_or_else(String::new, |r| format!(", burn rate {:.1}x", r));
+        format!(
+            "{}: {:.2}% against a {}% target{}",
+            link(&s.name, &links.slo(&s.slo_id)),
+            s.sli,
+            s.target,
+            burn
+        )
+    });
+
+    out
+}
+
+fn section<T>(out: &mut String, title: &str, items: &Section<T>, line: impl Fn(&T) -> String) {
+    out.push_str(&format!("\n## {}\n\n", title));
+    match items {
+        Ok(items) if items.is_empty() => out.push_str("_None._\n"),
+        Ok(items) => {
+            for item in items {
+                out.push_str(&format!("- {}\n", line(item)));
+            }
+        }
+        Err(reason) => out.push_str(&format!("_Unavailable: {}_\n", reason)),
+    }
+}
+
+/// Markdown link with brackets in the text escaped.
+fn link(text: &str, url: &str) -> String {
+    format!(
+        "[{}]({})",
+        text.replace('[', "\\[").replace(']', "\\]"),
+        url
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn episode(severity: &'static str, end: Option<&str>) -> Episode {
+        Episode {
+            monitor_id: 1,
+            group: None,
+            start: "2024-01-15T10:00:00.000Z".to_string(),
+            end: end.map(String::from),
+            duration_seconds: end.map(|_| 60),
+            severity,
+            transitions: 1,
+            title: "[Triggered] CPU".to_string(),
+        }
+    }
+
+    fn handoff() -> Handoff {
+        Handoff {
+            team: "payments".to_string(),
+            from: "2024-01-14T10:00:00.000Z".to_string(),
+            to: "2024-01-15T10:00:00.000Z".to_string(),
+            monitors: Ok(vec![TriggeredMonitor {
+                monitor_id: 42,
+                name: "[prod] High CPU".to_string(),
+                episodes: 2,
+                open: 1,
+                severity: "alert",
+            }]),
+            incidents: Ok(vec![]),
+            patterns: Ok(vec![PatternCount {
+                pattern: "timeout after <*>".to_string(),
+                count: 3,
+                example: "timeout after 30s".to_string(),
+            }]),
+            slos: Err("Access denied".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_triggered_monitor() {
+        let monitor = TriggeredMonitor::from_episodes(
+            1,
+            "CPU",
+            &[
+                episode("warn", Some("2024-01-15T10:01:00.000Z")),
+                episode("alert", None),
+            ],
+        )
+        .unwrap();
+        assert_eq!(monitor.episodes, 2);
+        assert_eq!(monitor.open, 1);
+        assert_eq!(monitor.severity, "alert");
+
+        assert!(TriggeredMonitor::from_episodes(1, "CPU", &[]).is_none());
+    }
+
+    #[test]
+    fn test_slo_burn() {
+        let burn = SloBurn::new("abc", "Checkout", 99.9, 99.5);
+        assert!((burn.burn_rate.unwrap() - 5.0).abs() < 1e-9);
+        assert!(burn.is_notable());
+
+        assert!(!SloBurn::new("abc", "Checkout", 99.0, 99.8).is_notable());
+
+        let strict = SloBurn::new("abc", "Checkout", 100.0, 99.99);
+        assert_eq!(strict.burn_rate, None);
+        assert!(strict.is_notable());
+    }
+
+    #[test]
+    fn test_render_markdown() {
+        let markdown = render_markdown(&handoff(), &DatadogLinks::new("datadoghq.com"));
+        assert!(markdown.starts_with("# On-call handoff: payments\n"));
+        assert!(markdown.contains(
+            "- [\\[prod\\] High CPU](https://app.datadoghq.com/monitors/42): 2 episodes (1 still open), worst state alert\n"
+        ));
+        assert!(markdown.contains("## Incidents\n\n_None._\n"));
+        assert!(markdown.contains("- `timeout after <*>`: 3 logs\n"));
+        assert!(markdown.contains("## SLO burn\n\n_Unavailable: Access denied_\n"));
+    }
+}
@@ -0,0 +1,264 @@
+//! Heatmaps of event volume over time per facet value.
+//!
+//! Lays [`VolumePoint`]s out as a grid with one row per facet value and one
+//! column per time bucket. Rows are ordered by when they first saw events, so
+//! the question "which service started erroring first" is answered by the top
+//! row. The grid renders as shaded terminal text or as a standalone HTML page.
+
+use std::collections::BTreeMap;
+
+use chrono::DateTime;
+
+use crate::client::VolumePoint;
+
+/// Shades for increasing volume; the first marks an empty bucket.
+const SHADES: [char; 5] = ['·', '░', '▒', '▓', '█'];
+
+/// Volume per facet value and time bucket.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Heatmap {
+    /// Facet the rows are grouped by (e.g., "service")
+    pub facet: String,
+
+    /// Time bucket starts, oldest first
+    pub times: Vec<String>,
+
+    /// One row per facet value, earliest activity first
+    pub rows: Vec<HeatmapRow>,
+}
+
+/// One facet value's volume per time bucket.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeatmapRow {
+    /// Facet value
+    pub group: String,
+
+    /// Event count per time bucket, aligned with [`Heatmap::times`]
+    pub counts: Vec<f64>,
+}
+
+impl HeatmapRow {
+    fn first_active(&self) -> usize {
+        self.counts
+            .iter()
+            .position(|c| *c > 0.0)
+            .unwrap_or(self.counts.len())
+    }
+
+    fn total(&self) -> f64 {
+        self.counts.iter().sum()
+    }
+}
+
+impl Heatmap {
+    /// Builds the grid from volume points.
+    ///
+    /// Buckets missing from the points count as zero. Rows are ordered by
+    /// their first non-empty bucket, then by total volume.
+    pub fn from_points(facet: &str, points: &[VolumePoint]) -> Self {
+        let mut times: Vec<String> = points.iter().map(|p| p.timestamp.clone()).collect();
+        times.sort();
+        times.dedup();
+
+        let mut rows: BTreeMap<&str, Vec<f64>> = BTreeMap::new();
+        for point in points {
+            let column = times.binary_search(&point.timestamp).unwrap_or_default();
+            rows.entry(&point.group)
+                .or_insert_with(|| vec![0.0; times.len()])[column] += point.count;
+        }
+
+        let mut rows: Vec<HeatmapRow> = rows
+            .into_iter()
+            .map(|(group, counts)| HeatmapRow {
+                group: group.to_string(),
+                counts,
+            })
+            .collect();
+        rows.sort_by(|a, b| {
+            a.first_active()
+                .cmp(&b.first_active())
+                .then(b.total().total_cmp(&a.total()))
+        });
+
+        Self {
+            facet: facet.to_string(),
+            times,
+            rows,
+        }
+    }
+
+    fn max(&self) -> f64 {
+        self.rows
+            .iter()
+            .flat_map(|r| r.counts.iter().copied())
+            .fold(0.0, f64::max)
+    }
+
+    /// Renders the grid as shaded text, one character per time bucket.
+    ///
+    /// Shades are scaled to the busiest bucket; each row ends with its total.
+    pub fn render_text(&self) -> String {
+        let label_width = self
+            .rows
+            .iter()
+            .map(|r| r.group.chars().count())
+            .chain([self.facet.chars().count()])
+            .max()
+            .unwrap_or_default();
+        let max = self.max();
+
+        let start = self
+            .times
+            .first()
+            .map(|t| short_time(t))
+            .unwrap_or_default();
+        let end = self.times.last().map(|t| short_time(t)).unwrap_or_default();
+        let axis_width = self.times.len().max(start.len() + end.len() + 1);
+        let mut out = format!(
+            "{:label_width$}  {}{:>pad$}\n",
+            self.facet,
+            start,
+            end,
+            pad = axis_width - start.len()
+        );
+
+        for row in &self.rows {
+            let cells: String = row.counts.iter().map(|c| shade(*c, max)).collect();
+            out.push_str(&format!(
+                "{:label_width$}  {:axis_width$}  {}\n",
+                row.group,
+                cells,
+                row.total()
+            ));
+        }
+        out
+    }
+
+    /// Renders the grid as a standalone HTML page titled `title`.
+    pub fn render_html(&self, title: &str) -> String {
+        let max = self.max();
+        let mut out = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+             <style>\n\
+             body {{ font-family: sans-serif; }}\n\
+             table {{ border-collapse: collapse; }}\n\
+             td {{ width: 14px; height: 18px; padding: 0; }}\n\
+             th {{ text-align: right; padding-right: 8px; font-weight: normal; white-space: nowrap; }}\n\
+             </style>\n</head>\n<body>\n<h1>{title}</h1>\n<table>\n",
+            title = escape_html(title)
+        );
+
+        out.push_str(&format!(
+            "<tr><th>{}</th><td colspan=\"{}\">{} to {}</td></tr>\n",
+            escape_html(&self.facet),
+            self.times.len().max(1),
+            escape_html(self.times.first().map(String::as_str).unwrap_or_default()),
+            escape_html(self.times.last().map(String::as_str).unwrap_or_default())
+        ));
+
+        for row in &self.rows {
+            out.push_str(&format!("<tr><th>{}</th>", escape_html(&row.group)));
+            for (time, count) in self.times.iter().zip(&row.counts) {
+                let alpha = if max > 0.0 { count / max } else { 0.0 };
+                out.push_str(&format!(
+                    "<td style=\"background: rgba(99, 44, 166, {:.3})\" title=\"{} {}: {}\"></td>",
+                    alpha,
+                    escape_html(&row.group),
+                    escape_html(time),
+                    count
+                ));
+            }
+            out.push_str("</tr>\n");
+        }
+
+        out.push_str("</table>\n</body>\n</html>\n");
+        out
+    }
+}
+
+/// Shade for `count` relative to the busiest bucket.
+fn shade(count: f64, max: f64) -> char {
+    if count <= 0.0 || max <= 0.0 {
+        return SHADES[0];
+    }
+    let steps = (SHADES.len() - 1) as f64;
+    let level = (count / max * steps).ceil().clamp(1.0, steps) as usize;
+    SHADES[level]
+}
+
+/// Formats a bucket time as `MM-DD HH:MM`, or returns it unchanged if it isn't RFC3339.
+fn short_time(time: &str) -> String {
+    DateTime::parse_from_rfc3339(time)
+        .map(|t| t.format("%m-%d %H:%M").to_string())
+        .unwrap_or_else(|_| time.to_string())
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(minute: u32, group: &str, count: f64) -> VolumePoint {
+        VolumePoint {
+            timestamp: format!("2024-01-15T10:{:02}:00.000Z", minute),
+            group: group.to_string(),
+            count,
+        }
+    }
+
+    fn heatmap() -> Heatmap {
+        Heatmap::from_points(
+            "service",
+            &[
+                point(0, "web", 1.0),
+                point(10, "web", 8.0),
+                point(20, "web", 4.0),
+                point(10, "api", 2.0),
+                point(0, "db", 2.0),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_from_points() {
+        let heatmap = heatmap();
+        assert_eq!(heatmap.times.len(), 3);
+
+        let groups: Vec<&str> = heatmap.rows.iter().map(|r| r.group.as_str()).collect();
+        assert_eq!(groups, vec!["web", "db", "api"]);
+        assert_eq!(heatmap.rows[2].counts, vec![0.0, 2.0, 0.0]);
+    }
+
+    #[test]
+    fn test_shade() {
+        assert_eq!(shade(0.0, 8.0), '·');
+        assert_eq!(shade(1.0, 8.0), '░');
+        assert_eq!(shade(4.0, 8.0), '▒');
+        assert_eq!(shade(8.0, 8.0), '█');
+    }
+
+    #[test]
+    fn test_render_text() {
+        let text = heatmap().render_text();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "service  01-15 10:00 01-15 10:20");
+        assert!(lines[1].starts_with("web      ░█▒"));
+        assert!(lines[1].ends_with("13"));
+        assert!(lines[3].starts_with("api      ·░·"));
+    }
+
+    #[test]
+    fn test_render_html_escapes() {
+        let heatmap = Heatmap::from_points("service", &[point(0, "<script>", 1.0)]);
+        let html = heatmap.render_html("errors & more");
+        assert!(html.contains("<title>errors &amp; more</title>"));
+        assert!(html.contains("&lt;script&gt;"));
+        assert!(!html.contains("<script>"));
+    }
+}
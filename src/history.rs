@@ -0,0 +1,189 @@
+//! Query history (`history.ndjson` in the state dir).
+//!
+//! Every logs, spans, and metrics query ddog runs is appended as one line,
+//! with its time range, result count, and the command line that ran it, so
+//! `ddog history rerun` can replay an earlier investigation. Set
+//! `DDOG_NO_HISTORY=1` to stop recording.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::config::state_dir;
+use crate::error::AppError;
+
+/// Name of the history file in the state directory.
+pub const HISTORY_FILE: &str = "history.ndjson";
+
+/// Environment variable that turns history recording off.
+pub const NO_HISTORY_ENV: &str = "DDOG_NO_HISTORY";
+
+/// Most entries kept; older entries are dropped when the file grows past this.
+pub const MAX_ENTRIES: usize = 1000;
+
+/// One executed query.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// When the query ran (RFC3339, UTC)
+    pub timestamp: String,
+
+    /// Domain that was queried: logs, spans, or metrics
+    pub domain: String,
+
+    /// Query as sent to Datadog, after saved-query expansion and filters
+    pub query: String,
+
+    /// Start of the time range, as resolved
+    pub from: String,
+
+    /// End of the time range, as resolved
+    pub to: String,
+
+    /// Number of records written
+    pub results: u64,
+
+    /// Command-line arguments, without the program name
+    pub args: Vec<String>,
+}
+
+/// Returns the path of the history file (`history.ndjson` in [`state_dir`]).
+pub fn history_path() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join(HISTORY_FILE))
+}
+
+/// Checks whether recording is turned off with [`NO_HISTORY_ENV`].
+///
+/// Any value other than empty or `0` turns it off.
+pub fn is_disabled() -> bool {
+    std::env::var(NO_HISTORY_ENV).is_ok_and(|v| !v.is_empty() && v != "0")
+}
+
+/// Loads history entries, oldest first.
+///
+/// A missing file yields no entries, and lines that don't parse (such as a
+/// write cut short) are skipped.
+///
+/// # Errors
+///
+/// Returns `AppError::Config` if the file exists but cannot be read.
+pub fn load(path: &Path) -> Result<Vec<HistoryEntry>, AppError> {
+    load_lines(path)
+}
+
+/// Loads the lines of an NDJSON file in the state directory that parse as `T`.
+pub(crate) fn load_lines<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>, AppError> {
+    if !path.is_file() {
+        return Ok(Vec::new());
+    }
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| AppError::Config(format!("Failed to read {}: {}", path.display(), e)))?;
+    Ok(contents
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Appends an entry, creating the file and its directory if needed.
+///
+/// Once the file holds more than [`MAX_ENTRIES`], it is rewritten with only
+/// the most recent ones.
+///
+/// # Errors
+///
+/// Returns `AppError::Config` if the file cannot be written.
+pub fn append(path: &Path, entry: &HistoryEntry) -> Result<(), AppError> {
+    append_line(path, entry, MAX_ENTRIES)
+}
+
+/// Appends a line to an NDJSON file in the state directory, keeping the
+/// most recent `max` lines.
+pub(crate) fn append_line<T: Serialize + DeserializeOwned>(
+    path: &Path,
+    entry: &T,
+    max: usize,
+) -> Result<(), AppError> {
+    let write_error =
+        |e: std::io::Error| AppError::Config(format!("Failed to write {}: {}", path.display(), e));
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| AppError::Config(format!("Failed to create {}: {}", dir.display(), e)))?;
+    }
+    let mut line = serde_json::to_string(entry)?;
+    line.push('\n');
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(line.as_bytes()))
+        .map_err(write_error)?;
+
+    let lines = std::fs::read_to_string(path)
+        .map_err(|e| AppError::Config(format!("Failed to read {}: {}", path.display(), e)))?
+        .lines()
+        .count();
+    if lines > max {
+        let entries: Vec<T> = load_lines(path)?;
+        let mut contents = String::new();
+        for entry in &entries[entries.len().saturating_sub(max)..] {
+            contents.push_str(&serde_json::to_string(entry)?);
+            contents.push('\n');
+        }
+        std::fs::write(path, contents).map_err(write_error)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(query: &str) -> HistoryEntry {
+        HistoryEntry {
+            timestamp: "2024-01-15T10:00:00Z".to_string(),
+            domain: "logs".to_string(),
+            query: query.to_string(),
+            from: "now-1h".to_string(),
+            to: "now".to_string(),
+            results: 3,
+            args: vec!["logs".to_string(), "search".to_string(), query.to_string()],
+        }
+    }
+
+    #[test]
+    fn test_append_and_load() {
+        let dir = std::env::temp_dir().join(format!("ddog-history-{}", std::process::id()));
+        let path = dir.join(HISTORY_FILE);
+
+        assert!(load(&path).unwrap().is_empty());
+        append(&path, &entry("status:error")).unwrap();
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"{\"truncated\n")
+            .unwrap();
+        append(&path, &entry("service:api")).unwrap();
+
+        let entries = load(&path).unwrap();
+        assert_eq!(entries, vec![entry("status:error"), entry("service:api")]);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_append_trims_old_entries() {
+        let dir = std::env::temp_dir().join(format!("ddog-history-trim-{}", std::process::id()));
+        let path = dir.join(HISTORY_FILE);
+
+        for n in 0..=MAX_ENTRIES {
+            append(&path, &entry(&format!("n:{}", n))).unwrap();
+        }
+        let entries = load(&path).unwrap();
+        assert_eq!(entries.len(), MAX_ENTRIES);
+        assert_eq!(entries[0].query, "n:1");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,61 @@
+//! Ctrl-C and SIGTERM handling for long searches.
+//!
+//! The first signal cancels the search, which stops after the page it is
+//! reading so everything fetched is written and the output finished as usual;
+//! the command then exits with the interrupted exit code (130) and a summary
+//! of what it wrote. A second signal exits at once.
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use ddog::client::CancellationToken;
+use ddog::error::AppError;
+
+use crate::logging;
+
+/// Returns a token cancelled by the first Ctrl-C or SIGTERM.
+pub fn ctrl_c_token() -> CancellationToken {
+    let token = CancellationToken::new();
+    let cancel = token.clone();
+    tokio::spawn(async move {
+        if signal().await.is_err() {
+            return;
+        }
+        logging::notice("Interrupted, finishing the current page (press Ctrl-C again to exit now)");
+        cancel.cancel();
+        if signal().await.is_ok() {
+            std::process::exit(130);
+        }
+    });
+    token
+}
+
+/// The error for a search interrupted after writing `count` records, the
+/// latest from `latest`, summarizing what was written and where to resume.
+pub fn interrupted(noun: &str, count: u64, latest: Option<DateTime<Utc>>) -> AppError {
+    match latest {
+        Some(latest) => {
+            let latest = latest.to_rfc3339_opts(SecondsFormat::Millis, true);
+            AppError::Interrupted(format!(
+                "wrote {} {} up to {}; resume with --from {}",
+                count, noun, latest, latest
+            ))
+        }
+        None => AppError::Interrupted(format!("wrote {} {}", count, noun)),
+    }
+}
+
+/// Waits for Ctrl-C, or SIGTERM on Unix.
+async fn signal() -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+        let mut terminate = signal(SignalKind::terminate())?;
+        tokio::select! {
+            result = tokio::signal::ctrl_c() => result,
+            _ = terminate.recv() => Ok(()),
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        tokio::signal::ctrl_c().await
+    }
+}
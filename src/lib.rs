@@ -1,9 +1,159 @@
 //! # ddog library
 //!
-//! Library crate exposing the core functionality for integration tests.
+//! Datadog logs, APM spans, and metrics search for Rust programs, without
+//! shelling out to the `ddog` CLI.
+//!
+//! The public API is the clients in [`client`], re-exported here:
+//! [`LogsClient`], [`SpansClient`], and [`MetricsClient`]. Build one with its
+//! `builder()`, then describe a search with [`LogsSearchRequest`] or
+//! [`SpansSearchRequest`] and collect its results as typed [`LogEvent`]s or
+//! [`SpanEvent`]s with `fetch`, or stream the API's records with `search`.
+//! A [`SearchStream`] can stop after a number of records or a timestamp,
+//! report progress, and be cancelled. Every failure is an [`AppError`], whose
+//! variants tell rejected credentials from invalid queries and other API
+//! errors.
+//!
+//! ```no_run
+//! use ddog::{AppError, LogsClient, LogsSearchRequest};
+//!
+//! # async fn example() -> Result<(), AppError> {
+//! let client = LogsClient::builder()
+//!     .api_key(std::env::var("MY_DD_API_KEY").unwrap())
+//!     .app_key(std::env::var("MY_DD_APP_KEY").unwrap())
+//!     .site("datadoghq.eu")
+//!     .build()?;
+//!
+//! let request = LogsSearchRequest::builder()
+//!     .query("service:checkout status:error")
+//!     .from("now-15m")
+//!     .build();
+//! let logs = client.fetch(&request, 50).await?;
+//! println!("{} error logs", logs.len());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Synchronous programs can use the [`blocking`] clients instead, whose
+//! searches return iterators.
+//!
+//! The documented modules, [`client`], [`blocking`], [`error`], [`records`],
+//! and [`time`], are the supported API. The others hold the CLI's building
+//! blocks (query composition, output analysis, integrations); they are public
+//! only so the binary and the integration tests can use them, are hidden from
+//! these docs, and may change in any release.
+
+pub use client::{
+    LogsClient, LogsSearchRequest, MetricsClient, SearchStream, SpansClient, SpansSearchRequest,
+};
+pub use error::AppError;
+pub use records::{LogEvent, SpanEvent, Status};
 
-// Re-export modules that are also used in main.rs
+#[doc(hidden)]
+pub mod anonymize;
+#[doc(hidden)]
+pub mod audit;
+#[doc(hidden)]
+pub mod batch;
+#[doc(hidden)]
+pub mod bench;
+pub mod blocking;
+#[doc(hidden)]
+pub mod browser;
+#[doc(hidden)]
+pub mod cardinality;
 pub mod client;
+#[doc(hidden)]
 pub mod config;
+#[doc(hidden)]
+pub mod deps;
+#[doc(hidden)]
+pub mod diff;
+#[doc(hidden)]
+pub mod drift;
+#[doc(hidden)]
+pub mod episodes;
 pub mod error;
+#[doc(hidden)]
+pub mod error_rate;
+#[doc(hidden)]
+pub mod exclusions;
+#[doc(hidden)]
+pub mod export;
+#[doc(hidden)]
+pub mod facets;
+#[doc(hidden)]
+pub mod flaps;
+#[doc(hidden)]
+pub mod gaps;
+#[doc(hidden)]
+pub mod handoff;
+#[doc(hidden)]
+pub mod heatmap;
+#[doc(hidden)]
+pub mod history;
+#[doc(hidden)]
+pub mod links;
+#[doc(hidden)]
+pub mod lint;
+#[doc(hidden)]
+pub mod loki;
+#[doc(hidden)]
+pub mod metric_query;
+#[doc(hidden)]
+pub mod noise;
+#[doc(hidden)]
+pub mod ownership;
+#[doc(hidden)]
+pub mod pagination;
+#[doc(hidden)]
+pub mod patterns;
+#[doc(hidden)]
+pub mod progress;
+#[doc(hidden)]
+pub mod query;
+pub mod records;
+#[doc(hidden)]
+pub mod redact;
+#[doc(hidden)]
+pub mod repl;
+#[doc(hidden)]
+pub mod repo;
+#[doc(hidden)]
+pub mod sample;
+#[doc(hidden)]
+pub mod sampling;
+#[doc(hidden)]
+pub mod saved_queries;
+#[doc(hidden)]
+pub mod schema;
+#[doc(hidden)]
+pub mod sink;
+#[doc(hidden)]
+pub mod slack;
+#[doc(hidden)]
+pub mod splunk;
+#[doc(hidden)]
+pub mod stats;
+#[doc(hidden)]
+pub mod telemetry;
+#[doc(hidden)]
+pub mod template;
+#[doc(hidden)]
+pub mod threshold;
 pub mod time;
+#[doc(hidden)]
+pub mod timeseries;
+#[doc(hidden)]
+pub mod trace;
+#[doc(hidden)]
+pub mod transform;
+#[doc(hidden)]
+pub mod update;
+#[doc(hidden)]
+pub mod upload;
+#[doc(hidden)]
+pub mod viz;
+#[doc(hidden)]
+pub mod watch;
+#[doc(hidden)]
+pub mod workspace;
@@ -0,0 +1,225 @@
+//! Datadog UI links and terminal hyperlinks.
+//!
+//! Builds per-site URLs for Datadog UI pages (traces, logs, monitors,
+//! incidents, SLOs, hosts) and wraps text in [OSC 8] escape sequences so terminals
+//! render it as a clickable link.
+//!
+//! [OSC 8]: https://gist.github.com/egmontkob/eb114294efbcd5adb1944c9f3cb5feda
+
+/// Returns the Datadog UI base URL for a site.
+///
+/// The US1 and EU sites and gov cloud serve the UI from `app.<site>`, while
+/// regional sites such as `us3.datadoghq.com` serve it from the site itself.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::links::app_base_url;
+///
+/// assert_eq!(app_base_url("datadoghq.com"), "https://app.datadoghq.com");
+/// assert_eq!(app_base_url("us3.datadoghq.com"), "https://us3.datadoghq.com");
+/// ```
+pub fn app_base_url(site: &str) -> String {
+    if site.matches('.').count() >= 2 {
+        format!("https://{}", site)
+    } else {
+        format!("https://app.{}", site)
+    }
+}
+
+/// Builds Datadog UI URLs for a single site.
+#[derive(Debug, Clone)]
+pub struct DatadogLinks {
+    base_url: String,
+}
+
+impl DatadogLinks {
+    /// Creates links for the given Datadog site (e.g., "datadoghq.eu").
+    pub fn new(site: &str) -> Self {
+        Self {
+            base_url: app_base_url(site),
+        }
+    }
+
+    /// URL of a trace in APM.
+    pub fn trace(&self, trace_id: &str) -> String {
+        format!(
+            "{}/apm/trace/{}",
+            self.base_url,
+            urlencoding::encode(trace_id)
+        )
+    }
+
+    /// URL of a log event in the Log Explorer, by its event ID.
+    pub fn log(&self, event_id: &str) -> String {
+        format!(
+            "{}/logs?event={}",
+            self.base_url,
+            urlencoding::encode(event_id)
+        )
+    }
+
+    /// URL of a Log Explorer search between two Unix millisecond timestamps.
+    ///
+    /// Indexes other than `*` are passed as a comma-separated `index` filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ddog::links::DatadogLinks;
+    ///
+    /// let links = DatadogLinks::new("datadoghq.com");
+    /// assert_eq!(
+    ///     links.log_explorer("service:api status:error", 1000, 2000, &["main".to_string()]),
+    ///     "https://app.datadoghq.com/logs?query=service%3Aapi%20status%3Aerror&from_ts=1000&to_ts=2000&live=false&index=main"
+    /// );
+    /// ```
+    pub fn log_explorer(
+        &self,
+        query: &str,
+        from_ms: i64,
+        to_ms: i64,
+        indexes: &[String],
+    ) -> String {
+        let mut url = format!(
+            "{}/logs?query={}&from_ts={}&to_ts={}&live=false",
+            self.base_url,
+            urlencoding::encode(query),
+            from_ms,
+            to_ms
+        );
+        let indexes: Vec<&str> = indexes
+            .iter()
+            .map(String::as_str)
+            .filter(|index| *index != "*")
+            .collect();
+        if !indexes.is_empty() {
+            url.push_str(&format!(
+                "&index={}",
+                urlencoding::encode(&indexes.join(","))
+            ));
+        }
+        url
+    }
+
+    /// URL of a Trace Explorer search between two Unix millisecond timestamps.
+    pub fn trace_explorer(&self, query: &str, from_ms: i64, to_ms: i64) -> String {
+        format!(
+            "{}/apm/traces?query={}&start={}&end={}&paused=true",
+            self.base_url,
+            urlencoding::encode(query),
+            from_ms,
+            to_ms
+        )
+    }
+
+    /// URL of a monitor's status page.
+    pub fn monitor(&self, monitor_id: i64) -> String {
+        format!("{}/monitors/{}", self.base_url, monitor_id)
+    }
+
+    /// URL of an incident, by its public number.
+    pub fn incident(&self, public_id: i64) -> String {
+        format!("{}/incidents/{}", self.base_url, public_id)
+    }
+
+    /// URL of an SLO's details panel.
+    pub fn slo(&self, slo_id: &str) -> String {
+        format!(
+            "{}/slo?slo_id={}",
+            self.base_url,
+            urlencoding::encode(slo_id)
+        )
+    }
+
+    /// URL of a host in the infrastructure list.
+    pub fn host(&self, host: &str) -> String {
+        format!(
+            "{}/infrastructure?host={}",
+            self.base_url,
+            urlencoding::encode(host)
+        )
+    }
+}
+
+/// Opens `url` in the default browser (`open` on macOS, `xdg-open` elsewhere).
+///
+/// # Errors
+///
+/// Returns the error from starting the opener, e.g. if it isn't installed.
+pub fn open_in_browser(url: &str) -> std::io::Result<()> {
+    let opener = if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    std::process::Command::new(opener)
+        .arg(url)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .map(drop)
+}
+
+/// Wraps `text` in an OSC 8 terminal hyperlink pointing at `url`.
+///
+/// Terminals without OSC 8 support ignore the escape sequences and show
+/// `text` as-is.
+pub fn hyperlink(text: &str, url: &str) -> String {
+    format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_app_base_url() {
+        assert_eq!(app_base_url("datadoghq.eu"), "https://app.datadoghq.eu");
+        assert_eq!(app_base_url("ddog-gov.com"), "https://app.ddog-gov.com");
+        assert_eq!(
+            app_base_url("ap1.datadoghq.com"),
+            "https://ap1.datadoghq.com"
+        );
+    }
+
+    #[test]
+    fn test_links() {
+        let links = DatadogLinks::new("datadoghq.eu");
+        assert_eq!(
+            links.trace("12345"),
+            "https://app.datadoghq.eu/apm/trace/12345"
+        );
+        assert_eq!(
+            links.log_explorer("*", 1, 2, &["*".to_string()]),
+            "https://app.datadoghq.eu/logs?query=%2A&from_ts=1&to_ts=2&live=false"
+        );
+        assert_eq!(
+            links.log_explorer("a", 1, 2, &["main".to_string(), "web".to_string()]),
+            "https://app.datadoghq.eu/logs?query=a&from_ts=1&to_ts=2&live=false&index=main%2Cweb"
+        );
+        assert_eq!(
+            links.trace_explorer("service:web", 1, 2),
+            "https://app.datadoghq.eu/apm/traces?query=service%3Aweb&start=1&end=2&paused=true"
+        );
+        assert_eq!(links.monitor(42), "https://app.datadoghq.eu/monitors/42");
+        assert_eq!(links.incident(7), "https://app.datadoghq.eu/incidents/7");
+        assert_eq!(
+            links.slo("abc123"),
+            "https://app.datadoghq.eu/slo?slo_id=abc123"
+        );
+        assert_eq!(
+            links.host("web 1"),
+            "https://app.datadoghq.eu/infrastructure?host=web%201"
+        );
+    }
+
+    #[test]
+    fn test_hyperlink() {
+        assert_eq!(
+            hyperlink("abc", "https://example.com"),
+            "\x1b]8;;https://example.com\x1b\\abc\x1b]8;;\x1b\\"
+        );
+    }
+}
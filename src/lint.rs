@@ -0,0 +1,544 @@
+//! Client-side checks for Datadog search syntax.
+//!
+//! Catches mistakes that Datadog either rejects with an opaque 400 or, worse,
+//! accepts and answers with nothing: unbalanced parentheses and quotes,
+//! malformed ranges (`[200 to 299]`), reversed comparisons (`:=>`), operators
+//! without operands, lowercase boolean operators (searched as text), and
+//! misspelled reserved attributes such as `stauts:error`.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
+use crate::time::edit_distance;
+
+/// Search syntax flavor, which determines the reserved attributes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchDomain {
+    #[default]
+    Logs,
+    Spans,
+}
+
+impl FromStr for SearchDomain {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "logs" => Ok(SearchDomain::Logs),
+            "spans" => Ok(SearchDomain::Spans),
+            _ => Err(format!("invalid domain '{}' (expected logs or spans)", s)),
+        }
+    }
+}
+
+impl fmt::Display for SearchDomain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SearchDomain::Logs => "logs",
+            SearchDomain::Spans => "spans",
+        })
+    }
+}
+
+impl SearchDomain {
+    /// Reserved attributes and common tags searchable without `@`.
+    fn known_keys(self) -> &'static [&'static str] {
+        match self {
+            SearchDomain::Logs => &[
+                "host", "source", "status", "service", "trace_id", "message", "env", "version",
+            ],
+            SearchDomain::Spans => &[
+                "service",
+                "env",
+                "version",
+                "host",
+                "status",
+                "operation_name",
+                "resource_name",
+                "trace_id",
+                "span_id",
+            ],
+        }
+    }
+}
+
+/// How serious a lint issue is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The query is malformed; Datadog will reject it or misread it
+    Error,
+    /// The query is valid but probably doesn't mean what was intended
+    Warning,
+}
+
+/// One problem found in a query.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LintIssue {
+    /// Whether the issue blocks the query
+    pub severity: Severity,
+
+    /// What is wrong
+    pub message: String,
+
+    /// Byte offset where the offending token starts
+    pub start: usize,
+
+    /// Byte offset just past the offending token
+    pub end: usize,
+
+    /// Corrected query, if a fix is likely
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+}
+
+impl LintIssue {
+    fn new(severity: Severity, span: Range<usize>, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            message: message.into(),
+            start: span.start,
+            end: span.end,
+            suggestion: None,
+        }
+    }
+
+    fn error(span: Range<usize>, message: impl Into<String>) -> Self {
+        Self::new(Severity::Error, span, message)
+    }
+
+    fn warning(span: Range<usize>, message: impl Into<String>) -> Self {
+        Self::new(Severity::Warning, span, message)
+    }
+
+    fn suggest(mut self, suggestion: Option<String>) -> Self {
+        self.suggestion = suggestion;
+        self
+    }
+
+    /// Renders the issue with the offending part of `query` underlined.
+    pub fn render(&self, query: &str) -> String {
+        let column = query[..self.start].chars().count();
+        let width = query[self.start..self.end].chars().count().max(1);
+        let mut rendered = format!(
+            "{}\n  {}\n  {}{}",
+            self,
+            query,
+            " ".repeat(column),
+            "^".repeat(width)
+        );
+        if let Some(suggestion) = &self.suggestion {
+            rendered.push_str(&format!("\n  did you mean: {}", suggestion));
+        }
+        rendered
+    }
+}
+
+impl fmt::Display for LintIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{}: {}", severity, self.message)
+    }
+}
+
+/// Checks a logs or spans search query, returning issues in query order.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::lint::{SearchDomain, Severity, lint};
+///
+/// assert!(lint("service:api AND (status:error OR status:warn)", SearchDomain::Logs).is_empty());
+///
+/// let issues = lint("@http.status_code:[500 to 599]", SearchDomain::Logs);
+/// assert_eq!(issues[0].severity, Severity::Error);
+/// assert_eq!(issues[0].suggestion.as_deref(), Some("@http.status_code:[500 TO 599]"));
+/// ```
+pub fn lint(query: &str, domain: SearchDomain) -> Vec<LintIssue> {
+    let mut issues = Vec::new();
+    let tokens = scan(query, &mut issues);
+    check_operators(query, &tokens, &mut issues);
+    for token in &tokens {
+        check_term(query, token.clone(), domain, &mut issues);
+    }
+    issues.sort_by_key(|issue| issue.start);
+    issues
+}
+
+/// Walks the query checking parentheses, quotes, and ranges, and returns
+/// the byte ranges of the whitespace-separated terms between parentheses.
+fn scan(query: &str, issues: &mut Vec<LintIssue>) -> Vec<Range<usize>> {
+    let mut tokens = Vec::new();
+    let mut token_start: Option<usize> = None;
+    let mut open_parens: Vec<usize> = Vec::new();
+    let mut chars = query.char_indices().peekable();
+
+    let mut end_token = |start: &mut Option<usize>, end: usize| {
+        if let Some(start) = start.take() {
+            tokens.push(start..end);
+        }
+    };
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => {
+                token_start.get_or_insert(i);
+                chars.next();
+            }
+            '"' => {
+                token_start.get_or_insert(i);
+                let mut closed = false;
+                while let Some((_, c)) = chars.next() {
+                    match c {
+                        '\\' => {
+                            chars.next();
+                        }
+                        '"' => {
+                            closed = true;
+                            break;
+                        }
+                        _ => {}
+                    }
+                }
+                if !closed {
+                    issues.push(
+                        LintIssue::error(i..i + 1, "unterminated quote")
+                            .suggest(Some(format!("{}\"", query))),
+                    );
+                }
+            }
+            '[' | '{' if query[..i].ends_with(':') => {
+                token_start.get_or_insert(i);
+                let close = chars.find(|(_, c)| matches!(c, ']' | '}')).map(|(j, _)| j);
+                match close {
+                    Some(close) => check_range(query, i..close + 1, issues),
+                    None => issues.push(LintIssue::error(
+                        i..query.len(),
+                        "unclosed range (expected [<low> TO <high>])",
+                    )),
+                }
+            }
+            '(' => {
+                end_token(&mut token_start, i);
+                open_parens.push(i);
+            }
+            ')' => {
+                end_token(&mut token_start, i);
+                if open_parens.pop().is_none() {
+                    issues.push(
+                        LintIssue::error(i..i + 1, "unmatched ')'").suggest(Some(format!(
+                            "{}{}",
+                            &query[..i],
+                            &query[i + 1..]
+                        ))),
+                    );
+                }
+            }
+            c if c.is_whitespace() => end_token(&mut token_start, i),
+            _ => {
+                token_start.get_or_insert(i);
+            }
+        }
+    }
+    end_token(&mut token_start, query.len());
+
+    let unclosed = open_parens.len();
+    for (index, open) in open_parens.into_iter().enumerate() {
+        let suggestion =
+            (index == 0).then(|| format!("{}{}", query.trim_end(), ")".repeat(unclosed)));
+        issues.push(LintIssue::error(open..open + 1, "unclosed '('").suggest(suggestion));
+    }
+    tokens
+}
+
+/// Checks a `[low TO high]` range, including its brackets.
+fn check_range(query: &str, span: Range<usize>, issues: &mut Vec<LintIssue>) {
+    let words: Vec<&str> = query[span.start + 1..span.end - 1]
+        .split_whitespace()
+        .collect();
+    let Some(to) = words.iter().position(|w| *w == "TO") else {
+        let suggestion = words.iter().any(|w| w.eq_ignore_ascii_case("to")).then(|| {
+            let fixed: Vec<&str> = words
+                .iter()
+                .map(|w| {
+                    if w.eq_ignore_ascii_case("to") {
+                        "TO"
+                    } else {
+                        w
+                    }
+                })
+                .collect();
+            format!(
+                "{}{}{}",
+                &query[..span.start + 1],
+                fixed.join(" "),
+                &query[span.end - 1..]
+            )
+        });
+        issues.push(
+            LintIssue::error(
+                span,
+                "range must be written as [<low> TO <high>], with TO in uppercase",
+            )
+            .suggest(suggestion),
+        );
+        return;
+    };
+    if to == 0 || to == words.len() - 1 {
+        issues.push(LintIssue::error(
+            span,
+            "range is missing a bound (use * for an open end, e.g. [100 TO *])",
+        ));
+    }
+}
+
+/// Checks boolean operators for missing operands and lowercase spellings.
+fn check_operators(query: &str, tokens: &[Range<usize>], issues: &mut Vec<LintIssue>) {
+    let is_binary = |t: &str| t == "AND" || t == "OR";
+
+    for (index, token) in tokens.iter().enumerate() {
+        let text = &query[token.clone()];
+
+        if matches!(text, "and" | "or" | "not") {
+            let upper = text.to_ascii_uppercase();
+            issues.push(
+                LintIssue::warning(
+                    token.clone(),
+                    format!(
+                        "lowercase '{}' is searched as text; use {} for the operator",
+                        text, upper
+                    ),
+                )
+                .suggest(Some(format!(
+                    "{}{}{}",
+                    &query[..token.start],
+                    upper,
+                    &query[token.end..]
+                ))),
+            );
+            continue;
+        }
+
+        if !is_binary(text) && text != "NOT" {
+            continue;
+        }
+        let next = tokens.get(index + 1).map(|t| &query[t.clone()]);
+        let previous = index.checked_sub(1).map(|i| &query[tokens[i].clone()]);
+        let missing_left = is_binary(text)
+            && (previous.is_none_or(|p| is_binary(p) || p == "NOT")
+                || query[..token.start].trim_end().ends_with('('));
+        let missing_right =
+            next.is_none_or(is_binary) || query[token.end..].trim_start().starts_with(')');
+        if missing_left || missing_right {
+            issues.push(LintIssue::error(
+                token.clone(),
+                format!("'{}' is missing an operand", text),
+            ));
+        }
+    }
+}
+
+/// Checks a single `key:value` term.
+fn check_term(query: &str, token: Range<usize>, domain: SearchDomain, issues: &mut Vec<LintIssue>) {
+    let text = &query[token.clone()];
+    let negation = text.len() - text.trim_start_matches(['-', '+']).len();
+    let term = &text[negation..];
+    if term.starts_with('"') {
+        return;
+    }
+    let Some(colon) = unescaped_colon(term) else {
+        return;
+    };
+    let key = &term[..colon];
+    let value = &term[colon + 1..];
+    let key_start = token.start + negation;
+    let value_start = key_start + colon + 1;
+
+    // `service:(api OR web)` groups several values under one key
+    let grouped = query[token.end..].starts_with('(');
+    if value.is_empty() && !grouped {
+        issues.push(LintIssue::error(
+            token,
+            format!("missing value after '{}:'", key),
+        ));
+        return;
+    }
+
+    for (wrong, right) in [("=>", ">="), ("=<", "<=")] {
+        if value.starts_with(wrong) {
+            issues.push(
+                LintIssue::error(
+                    value_start..value_start + 2,
+                    format!("unknown comparison '{}'", wrong),
+                )
+                .suggest(Some(format!(
+                    "{}{}{}",
+                    &query[..value_start],
+                    right,
+                    &query[value_start + 2..]
+                ))),
+            );
+        }
+    }
+
+    if key.starts_with('@') || key.is_empty() {
+        return;
+    }
+    let known = domain.known_keys();
+    if known.contains(&key) {
+        return;
+    }
+    let threshold = if key.len() <= 4 { 1 } else { 2 };
+    if let Some(closest) = known
+        .iter()
+        .map(|k| (edit_distance(key, k), *k))
+        .filter(|(distance, _)| *distance <= threshold)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, k)| k)
+    {
+        issues.push(
+            LintIssue::warning(
+                key_start..key_start + key.len(),
+                format!(
+                    "'{}' is not a reserved attribute, so it is searched as a tag",
+                    key
+                ),
+            )
+            .suggest(Some(format!(
+                "{}{}{}",
+                &query[..key_start],
+                closest,
+                &query[key_start + key.len()..]
+            ))),
+        );
+    }
+}
+
+/// Finds the first `:` not escaped with a backslash.
+fn unescaped_colon(term: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in term.char_indices() {
+        match c {
+            '\\' if !escaped => escaped = true,
+            ':' if !escaped => return Some(i),
+            _ => escaped = false,
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(query: &str) -> Vec<String> {
+        lint(query, SearchDomain::Logs)
+            .into_iter()
+            .map(|issue| issue.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_lint_clean_queries() {
+        for query in [
+            "*",
+            "service:api AND status:error",
+            "(service:api OR service:web) AND -env:staging",
+            "@http.status_code:[200 TO 299] @duration:>=1000000000",
+            "\"connection (refused\" host:web-1",
+            "@url:https\\://example.com NOT status:info",
+            "@http.status_code:{400 TO *]",
+        ] {
+            assert!(lint(query, SearchDomain::Logs).is_empty(), "{}", query);
+        }
+    }
+
+    #[test]
+    fn test_lint_parentheses() {
+        let issues = lint("(service:api OR service:web", SearchDomain::Logs);
+        assert_eq!(issues.len(), 1);
+        assert_eq!((issues[0].start, issues[0].end), (0, 1));
+        assert_eq!(
+            issues[0].suggestion.as_deref(),
+            Some("(service:api OR service:web)")
+        );
+
+        let issues = lint("service:api)", SearchDomain::Logs);
+        assert_eq!(issues[0].message, "unmatched ')'");
+        assert_eq!(issues[0].suggestion.as_deref(), Some("service:api"));
+    }
+
+    #[test]
+    fn test_lint_quotes_and_ranges() {
+        assert_eq!(messages("\"timeout"), vec!["error: unterminated quote"]);
+        assert!(messages("@duration:[100 TO")[0].contains("unclosed range"));
+        assert!(messages("@duration:[TO 100]")[0].contains("missing a bound"));
+        assert_eq!(
+            lint("@a:[1 to 2] service:x", SearchDomain::Logs)[0]
+                .suggestion
+                .as_deref(),
+            Some("@a:[1 TO 2] service:x")
+        );
+    }
+
+    #[test]
+    fn test_lint_operators() {
+        assert_eq!(
+            messages("AND service:api"),
+            vec!["error: 'AND' is missing an operand"]
+        );
+        assert_eq!(
+            messages("service:api OR"),
+            vec!["error: 'OR' is missing an operand"]
+        );
+        assert_eq!(
+            messages("(service:api AND) status:error"),
+            vec!["error: 'AND' is missing an operand"]
+        );
+        let issues = lint("service:api or service:web", SearchDomain::Logs);
+        assert_eq!(issues[0].severity, Severity::Warning);
+        assert_eq!(
+            issues[0].suggestion.as_deref(),
+            Some("service:api OR service:web")
+        );
+    }
+
+    #[test]
+    fn test_lint_terms() {
+        let issues = lint("stauts:error", SearchDomain::Logs);
+        assert_eq!(issues[0].severity, Severity::Warning);
+        assert_eq!(issues[0].suggestion.as_deref(), Some("status:error"));
+
+        let issues = lint("-resource_nam:/api", SearchDomain::Spans);
+        assert_eq!(issues[0].suggestion.as_deref(), Some("-resource_name:/api"));
+        assert!(lint("resource_nam:/api", SearchDomain::Logs).is_empty());
+
+        assert!(lint("service:(api OR web) env:prod", SearchDomain::Logs).is_empty());
+        assert_eq!(
+            messages("service:"),
+            vec!["error: missing value after 'service:'"]
+        );
+        assert_eq!(
+            lint("@duration:=>100", SearchDomain::Logs)[0]
+                .suggestion
+                .as_deref(),
+            Some("@duration:>=100")
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let query = "stauts:error";
+        let issue = &lint(query, SearchDomain::Logs)[0];
+        assert_eq!(
+            issue.render(query),
+            "warning: 'stauts' is not a reserved attribute, so it is searched as a tag\n  stauts:error\n  ^^^^^^\n  did you mean: status:error"
+        );
+    }
+}
@@ -1,10 +1,77 @@
 //! Verbose logging utilities.
 //!
-//! Provides functions for verbose/debug output when the --verbose flag is enabled.
+//! Provides functions for verbose/debug output when the --verbose flag is enabled,
+//! installs the `tracing` subscriber that prints per-request HTTP traces, and
+//! writes the warnings and notices that `--no-warnings` and `--quiet` silence.
+
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::Level;
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+/// Installs a `tracing` subscriber writing the library's HTTP traces to stderr.
+///
+/// # Arguments
+///
+/// * `verbosity` - Number of `-v` flags: `0` disables tracing, `1` traces
+///   requests (URL, status, latency, cursor, rate limits), `2+` also dumps
+///   response bodies
+pub fn init_tracing(verbosity: u8) {
+    let level = match verbosity {
+        0 => return,
+        1 => Level::DEBUG,
+        _ => Level::TRACE,
+    };
+
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_writer(std::io::stderr)
+                .with_ansi(std::io::stderr().is_terminal())
+                .with_target(false),
+        )
+        .with(Targets::new().with_target("ddog", level))
+        .init();
+}
+
+/// Whether notices, warnings, and progress lines are silenced (`--quiet`).
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Whether warnings are silenced (`--no-warnings`).
+static NO_WARNINGS: AtomicBool = AtomicBool::new(false);
+
+/// Sets what is written to stderr besides errors and `--verbose` output.
+pub fn set_stderr(quiet: bool, no_warnings: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+    NO_WARNINGS.store(no_warnings, Ordering::Relaxed);
+}
+
+/// Whether `--quiet` is set.
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// Writes a warning about a partial or doubtful result to stderr, unless
+/// `--quiet` or `--no-warnings` is set.
+pub fn warn(message: &str) {
+    if !is_quiet() && !NO_WARNINGS.load(Ordering::Relaxed) {
+        eprintln!("Warning: {}", message);
+    }
+}
+
+/// Writes an informational line to stderr, unless `--quiet` is set.
+pub fn notice(message: &str) {
+    if !is_quiet() {
+        eprintln!("{}", message);
+    }
+}
 
 /// Logger for verbose output.
 ///
 /// Writes to stderr to avoid interfering with NDJSON output on stdout.
+#[derive(Clone)]
 pub struct VerboseLogger {
     enabled: bool,
 }
@@ -49,13 +116,7 @@ impl VerboseLogger {
 
         let query_param = urlencoding::encode(query);
 
-        let base_url = if site == "datadoghq.com" {
-            "https://app.datadoghq.com"
-        } else if site == "datadoghq.eu" {
-            "https://app.datadoghq.eu"
-        } else {
-            &format!("https://app.{}", site)
-        };
+        let base_url = ddog::links::app_base_url(site);
 
         // For Datadog UI, we need to convert times to milliseconds since epoch
         // For relative times like "now-1h", we'll approximate or note that user needs to adjust
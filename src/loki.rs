@@ -0,0 +1,230 @@
+//! Grafana Loki push payloads (`--format loki`).
+//!
+//! Log records are turned into Loki entries: the log message as the line,
+//! its timestamp in nanoseconds, and a label set taken from selected fields
+//! of the log. Entries sharing a label set are grouped into one stream of a
+//! push API payload.
+
+use chrono::{DateTime, Utc};
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+
+/// Labels attached to each entry when none are selected.
+pub const DEFAULT_LABELS: [&str; 3] = ["service", "host", "status"];
+
+/// Loki's push API path, appended to a base URL without a path.
+pub const PUSH_PATH: &str = "/loki/api/v1/push";
+
+/// One log line for Loki.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LokiEntry {
+    /// Stream labels
+    pub labels: BTreeMap<String, String>,
+
+    /// Unix nanoseconds
+    pub timestamp_ns: i64,
+
+    /// Log line
+    pub line: String,
+}
+
+impl LokiEntry {
+    /// Builds the entry for a log record (as returned by the logs search API),
+    /// labelled with the `labels` fields it has.
+    ///
+    /// A label is looked up as a top-level attribute (`service`, `host`,
+    /// `status`), then a `key:value` tag, then a custom attribute, where a
+    /// dotted name follows nested objects. Label names are sanitized to
+    /// Loki's `[a-zA-Z_][a-zA-Z0-9_]*`. Logs without a message are sent as
+    /// their compact JSON attributes, and logs without a timestamp as of now.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ddog::loki::LokiEntry;
+    /// use serde_json::json;
+    ///
+    /// let log = json!({"attributes": {
+    ///     "message": "connection refused",
+    ///     "timestamp": "2024-01-15T10:00:00Z",
+    ///     "service": "api",
+    ///     "tags": ["env:prod", "kube.namespace:payments"],
+    /// }});
+    /// let labels = ["service".to_string(), "env".to_string(), "kube.namespace".to_string()];
+    /// let entry = LokiEntry::from_log(&log, &labels);
+    /// assert_eq!(entry.line, "connection refused");
+    /// assert_eq!(entry.timestamp_ns, 1_705_312_800_000_000_000);
+    /// assert_eq!(entry.labels["env"], "prod");
+    /// assert_eq!(entry.labels["kube_namespace"], "payments");
+    /// ```
+    pub fn from_log(log: &Value, labels: &[String]) -> Self {
+        let attributes = log.get("attributes").unwrap_or(log);
+        let labels = labels
+            .iter()
+            .filter_map(|name| label_value(attributes, name).map(|value| (label_name(name), value)))
+            .collect();
+        let timestamp_ns = attributes
+            .get("timestamp")
+            .and_then(Value::as_str)
+            .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+            .map(|t| t.with_timezone(&Utc))
+            .unwrap_or_else(Utc::now)
+            .timestamp_nanos_opt()
+            .unwrap_or_default();
+        let line = match attributes.get("message").and_then(Value::as_str) {
+            Some(message) => message.to_string(),
+            None => attributes.to_string(),
+        };
+        Self {
+            labels,
+            timestamp_ns,
+            line,
+        }
+    }
+}
+
+/// The value of a label field of a log's attributes, if it has one.
+fn label_value(attributes: &Value, name: &str) -> Option<String> {
+    let scalar = |value: &Value| match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(_) | Value::Bool(_) => Some(value.to_string()),
+        _ => None,
+    };
+    if let Some(value) = attributes.get(name).and_then(scalar) {
+        return Some(value);
+    }
+    let prefix = format!("{}:", name);
+    let tag = attributes
+        .get("tags")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .find_map(|tag| tag.strip_prefix(&prefix));
+    if let Some(tag) = tag {
+        return Some(tag.to_string());
+    }
+    let custom = attributes.get("attributes")?;
+    custom
+        .get(name)
+        .or_else(|| {
+            name.split('.')
+                .try_fold(custom, |value, key| value.get(key))
+        })
+        .and_then(scalar)
+}
+
+/// Sanitizes a field name into a Loki label name.
+fn label_name(name: &str) -> String {
+    let mut label: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if label.starts_with(|c: char| c.is_ascii_digit()) {
+        label.insert(0, '_');
+    }
+    label
+}
+
+/// Builds a push API payload, grouping entries by label set.
+///
+/// Entries without any label get `{job="ddog"}`, since Loki rejects streams
+/// with no labels.
+pub fn push_payload(entries: &[LokiEntry]) -> Value {
+    let mut streams: BTreeMap<&BTreeMap<String, String>, Vec<Value>> = BTreeMap::new();
+    for entry in entries {
+        streams
+            .entry(&entry.labels)
+            .or_default()
+            .push(json!([entry.timestamp_ns.to_string(), entry.line]));
+    }
+    let streams: Vec<Value> = streams
+        .into_iter()
+        .map(|(labels, values)| {
+            let labels = if labels.is_empty() {
+                json!({"job": "ddog"})
+            } else {
+                json!(labels)
+            };
+            json!({"stream": labels, "values": values})
+        })
+        .collect();
+    json!({ "streams": streams })
+}
+
+/// The push API URL for a Loki URL given with or without its push path.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::loki::push_url;
+///
+/// assert_eq!(push_url("http://loki:3100"), "http://loki:3100/loki/api/v1/push");
+/// assert_eq!(push_url("http://loki:3100/loki/api/v1/push"), "http://loki:3100/loki/api/v1/push");
+/// ```
+pub fn push_url(url: &str) -> String {
+    let base = url.trim_end_matches('/');
+    if base.ends_with(PUSH_PATH) {
+        base.to_string()
+    } else {
+        format!("{}{}", base, PUSH_PATH)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_entry_labels_from_fields() {
+        let log = json!({"attributes": {
+            "timestamp": "2024-01-15T10:00:00.5Z",
+            "host": "web-1",
+            "status": "error",
+            "attributes": {"http": {"status_code": 502}, "1st-party": true},
+        }});
+        let entry = LokiEntry::from_log(
+            &log,
+            &labels(&["host", "status", "http.status_code", "1st-party", "missing"]),
+        );
+        assert_eq!(entry.timestamp_ns, 1_705_312_800_500_000_000);
+        assert_eq!(entry.labels.len(), 4);
+        assert_eq!(entry.labels["http_status_code"], "502");
+        assert_eq!(entry.labels["_1st_party"], "true");
+        // No message: the attributes are sent instead
+        assert!(entry.line.starts_with('{'));
+    }
+
+    #[test]
+    fn test_push_payload_groups_streams() {
+        let entry = |service: &str, ts: i64| LokiEntry {
+            labels: BTreeMap::from([("service".to_string(), service.to_string())]),
+            timestamp_ns: ts,
+            line: format!("{} line", service),
+        };
+        let unlabelled = LokiEntry {
+            labels: BTreeMap::new(),
+            timestamp_ns: 3,
+            line: "bare".to_string(),
+        };
+        let payload = push_payload(&[
+            entry("api", 1),
+            entry("web", 2),
+            entry("api", 4),
+            unlabelled,
+        ]);
+
+        let streams = payload["streams"].as_array().unwrap();
+        assert_eq!(streams.len(), 3);
+        assert_eq!(streams[0]["stream"], json!({"job": "ddog"}));
+        assert_eq!(streams[1]["stream"], json!({"service": "api"}));
+        assert_eq!(
+            streams[1]["values"],
+            json!([["1", "api line"], ["4", "api line"]])
+        );
+    }
+}
@@ -16,92 +16,1858 @@
 //! - `DD_API_KEY` - Datadog API key (required)
 //! - `DD_APP_KEY` - Datadog application key (required)
 //! - `DD_SITE` - Datadog site (optional, defaults to datadoghq.com)
+//!
+//! ## Workspace
+//!
+//! A `.ddog.toml` file in the current directory or any parent can select a
+//! profile, add default `service`/`env` filters, and define saved queries.
 
-use clap::Parser;
+use clap::error::ErrorKind;
+use clap::{ArgMatches, CommandFactory, FromArgMatches};
+use clap_complete::CompleteEnv;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 mod cli;
 mod commands;
+mod interrupt;
 mod logging;
 mod output;
+mod routing;
 
 // Import from library crate
 use ddog::client;
 use ddog::config;
 use ddog::error::AppError;
+use ddog::history::{self, HistoryEntry};
+use ddog::links::DatadogLinks;
+use ddog::lint::SearchDomain;
+use ddog::loki;
+use ddog::progress::Progress;
+use ddog::redact::Redactor;
+use ddog::repo::ServiceContext;
+use ddog::saved_queries::{self, SavedQueries, saved_queries_path};
+use ddog::sink::BatchEncoding;
+use ddog::slack::SlackWebhook;
+use ddog::splunk::{self, HecOptions};
+use ddog::telemetry::{self, UsageEvent};
+use ddog::threshold::{Levels, Threshold};
+use ddog::time::{self, Timezone};
+use ddog::transform::{Pipeline, Stage};
+use ddog::workspace::{Workspace, saved_query_name};
 
-use cli::{Cli, Domain, LogsAction, MetricsAction, SpansAction};
+use cli::{
+    ApiAction, BenchAction, Cli, DocsAction, Domain, DriftAction, ErrorFormat, HistoryAction,
+    HostsAction, LogsAction, LogsExclusionsAction, LogsMetricsAction, MetricsAction,
+    MonitorsAction, QueryAction, QueryFilters, SpansAction, SpansMetricsAction,
+    SpansRetentionFiltersAction, TelemetryAction, TimeRange,
+};
+use commands::count::CountRecord;
+use commands::logs::search::{BatchRecord, BatchSearch};
 use logging::VerboseLogger;
+use output::{MetaRecord, OutputFormat, ProgressLine, RecordWriter};
+use routing::{OwnerRouter, SearchSink};
+
+fn main() {
+    // Answers the shell's completion requests (see `ddog completions`) and exits
+    CompleteEnv::with_factory(Cli::command)
+        .var(commands::completions::COMPLETE_VAR)
+        .complete();
+    start();
+}
 
 #[tokio::main]
-async fn main() {
-    if let Err(e) = run().await {
-        eprintln!("Error: {}", e);
+async fn start() {
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    let usage = Usage::start(&matches);
+    let error_format = cli.error_format;
+    let fail_if_empty = cli.fail_if_empty;
+    let result = run(cli).await.and_then(|()| {
+        if fail_if_empty && output::records_written() == 0 {
+            return Err(AppError::Empty(
+                "the command wrote no records (--fail-if-empty)".to_string(),
+            ));
+        }
+        Ok(())
+    });
+    let result = output::finish_sink(result);
+    if let Some(usage) = usage {
+        usage.finish(result.as_ref().err());
+    }
+    if let Err(e) = result {
+        match error_format {
+            ErrorFormat::Text => eprintln!("Error: {}", e),
+            ErrorFormat::Json => {
+                let report = e.report(client::http::last_rate_limit_reset());
+                match serde_json::to_string(&report) {
+                    Ok(json) => eprintln!("{}", json),
+                    Err(_) => eprintln!("Error: {}", e),
+                }
+            }
+        }
         std::process::exit(e.exit_code());
     }
 }
 
-async fn run() -> Result<(), AppError> {
-    let cli = Cli::parse();
-    let logger = VerboseLogger::new(cli.verbose);
-    let config = config::load_config()?;
+async fn run(cli: Cli) -> Result<(), AppError> {
+    let is_trace = matches!(
+        cli.domain,
+        Domain::Spans {
+            action: SpansAction::Trace { .. }
+        }
+    );
+    if cli.format.diagram().is_some() && !is_trace {
+        Cli::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "diagram formats are only supported by `ddog spans trace`",
+            )
+            .exit();
+    }
+    let is_deps = matches!(
+        cli.domain,
+        Domain::Spans {
+            action: SpansAction::Deps { .. }
+        }
+    );
+    if cli.format == OutputFormat::Dot && !is_deps {
+        Cli::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "--format dot is only supported by `ddog spans deps`",
+            )
+            .exit();
+    }
+    let is_metrics_query = matches!(
+        cli.domain,
+        Domain::Metrics {
+            action: MetricsAction::Query { .. }
+        }
+    );
+    if cli.format == OutputFormat::Wide && !is_metrics_query {
+        Cli::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "--format wide is only supported by `ddog metrics query`",
+            )
+            .exit();
+    }
+    if cli.output.is_some() && cli.format == OutputFormat::Text {
+        Cli::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "--output uploads NDJSON and can't be combined with --format text",
+            )
+            .exit();
+    }
+    let is_logs_search = matches!(
+        cli.domain,
+        Domain::Logs {
+            action: LogsAction::Search { .. }
+        }
+    );
+    if cli.format == OutputFormat::Loki && !is_logs_search {
+        Cli::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "--format loki is only supported by `ddog logs search`",
+            )
+            .exit();
+    }
+    if (cli.format == OutputFormat::Loki) != cli.push.is_some() {
+        Cli::command()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "--format loki and --push <URL> must be used together",
+            )
+            .exit();
+    }
+    let is_splunk = cli.format == OutputFormat::SplunkHec;
+    if !is_splunk && (cli.splunk_sourcetype.is_some() || cli.splunk_index.is_some()) {
+        Cli::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "--splunk-sourcetype and --splunk-index require --format splunk-hec",
+            )
+            .exit();
+    }
+    if is_splunk && (cli.output.is_some() || cli.sink.is_some()) {
+        Cli::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "--format splunk-hec writes to stdout and can't be combined with --output or --sink",
+            )
+            .exit();
+    }
+    if cli.sink.is_some() && cli.format == OutputFormat::Text {
+        Cli::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "--sink posts JSON and can't be combined with --format text",
+            )
+            .exit();
+    }
+    // --explain is global, so clap can't check it against a search's flags
+    let skips_search = match &cli.domain {
+        Domain::Logs {
+            action: LogsAction::Search {
+                batch, open, count, ..
+            },
+        } => batch.is_some() || *open || *count,
+        Domain::Spans {
+            action: SpansAction::Search { open, count, .. },
+        } => *open || *count,
+        _ => false,
+    };
+    if cli.explain && skips_search {
+        Cli::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "--explain describes a single search and can't be combined with --batch, --open, or --count",
+            )
+            .exit();
+    }
+    logging::init_tracing(cli.verbose);
+    logging::set_stderr(cli.quiet, cli.no_warnings);
+    let logger = VerboseLogger::new(cli.verbose > 0);
 
+    let workspace = if cli.no_workspace {
+        None
+    } else {
+        Workspace::discover(&std::env::current_dir()?)?
+    };
+    if let Some(ws) = &workspace {
+        logger.log(&format!("Workspace: {}", ws.path.display()));
+    }
+
+    // An explicit --profile wins over the workspace's profile
+    let user_config = config::load_user_config()?;
+    let profile_name = cli
+        .profile
+        .clone()
+        .or_else(|| workspace.as_ref().and_then(|ws| ws.profile.clone()));
+    let profile = match &profile_name {
+        Some(name) => {
+            logger.log(&format!("Profile: {}", name));
+            Some(user_config.profile(name)?)
+        }
+        None => None,
+    };
     // Get site for URL construction
-    let site = std::env::var("DD_SITE").unwrap_or_else(|_| "datadoghq.com".to_string());
-    let has_api_key = std::env::var("DD_API_KEY").is_ok();
-    let has_app_key = std::env::var("DD_APP_KEY").is_ok();
+    let site = config::resolve_site(profile);
+    output::set_pipeline(output_pipeline(&cli));
+    let mut writer = match &cli.output {
+        Some(url) => RecordWriter::upload(url)?,
+        None => match (&cli.sink, &cli.push) {
+            (Some(url), _) => {
+                RecordWriter::post(url, cli.batch_size as usize, BatchEncoding::JsonArray)
+            }
+            (None, Some(url)) => RecordWriter::post(
+                &loki::push_url(url),
+                cli.batch_size as usize,
+                BatchEncoding::Loki(cli.labels.clone()),
+            ),
+            (None, None) if is_splunk => RecordWriter::splunk_hec(HecOptions {
+                sourcetype: cli
+                    .splunk_sourcetype
+                    .clone()
+                    .unwrap_or_else(|| splunk::DEFAULT_SOURCETYPE.to_string()),
+                index: cli.splunk_index.clone(),
+            }),
+            (None, None) => RecordWriter::new(
+                cli.format,
+                &site,
+                cli.local_timestamps.then_some(cli.timezone),
+            ),
+        },
+    };
 
+    // Query tools, history, completions, docs, telemetry, and self-update need no credentials, so they run before credentials are required
+    let domain = match cli.domain {
+        Domain::Query { action } => {
+            return run_query_tool(action, workspace.as_ref(), cli.no_validate, writer, logger);
+        }
+        Domain::History { action } => {
+            return match action {
+                HistoryAction::List { limit } => {
+                    commands::history::list::run(limit, writer, logger)
+                }
+                HistoryAction::Rerun { n } => commands::history::rerun::run(n, logger),
+            };
+        }
+        Domain::Completions {
+            shell,
+            static_script,
+        } => return commands::completions::run(shell, static_script),
+        Domain::Docs {
+            action: DocsAction::Man { dir },
+        } => return commands::docs::man(&dir),
+        Domain::HelpAll => return commands::docs::help_all(),
+        Domain::Telemetry {
+            action: TelemetryAction::Report,
+        } => return commands::telemetry::report(&user_config, writer, logger),
+        Domain::SelfUpdate { check } => {
+            return commands::self_update::run(check, writer, logger).await;
+        }
+        domain => domain,
+    };
+
+    // A cassette must be installed before any client is built
+    let cassette = match (cli.record, cli.replay) {
+        (Some(path), _) => Some(client::CassetteTransport::record(path)),
+        (_, Some(path)) => Some(client::CassetteTransport::replay(path)?),
+        _ => client::CassetteTransport::from_env()?,
+    };
+    let replaying = cassette.as_ref().is_some_and(|c| c.is_replay());
+    if let Some(cassette) = cassette {
+        logger.log(if replaying {
+            "Replaying API responses from cassette"
+        } else {
+            "Recording API responses to cassette"
+        });
+        client::http::set_default_transport(Arc::new(cassette));
+    } else if !cli.no_cache
+        && cli.cache_ttl > 0
+        // Benchmarks time Datadog, not the cache
+        && !matches!(domain, Domain::Bench { .. })
+        && let Some(dir) = config::cache_dir()
+    {
+        let cache = client::CacheTransport::new(
+            dir.join("responses"),
+            Duration::from_secs(cli.cache_ttl as u64),
+        );
+        if let Err(e) = cache.prune() {
+            logger.log(&format!("Failed to prune the response cache: {}", e));
+        }
+        client::http::set_default_transport(Arc::new(cache));
+    }
+    client::http::stamp_request_ids(cli.stamp_request_id);
+    client::http::set_compression(!cli.no_compression);
+
+    // Replayed requests never reach Datadog, so credentials are optional
+    let config = match config::load_config_for_profile(profile) {
+        Err(AppError::Config(_)) if replaying => config::offline_config(profile),
+        result => result?,
+    };
+    let credentials = || match config::load_credentials(profile) {
+        Err(AppError::Config(_)) if replaying => Ok(config::Credentials {
+            api_key: String::new(),
+            app_key: String::new(),
+        }),
+        result => result,
+    };
+    let (has_api_key, has_app_key) = config::credentials_set(profile);
     logger.log_config(&site, has_api_key, has_app_key);
 
-    match cli.domain {
+    let mut recording = None;
+    let result = match domain {
         Domain::Logs { action } => match action {
             LogsAction::Search {
                 query,
-                time_range,
+                mut time_range,
                 pagination,
-                indexes,
+                filters,
+                routing,
+                anonymization,
+                source,
+                batch,
+                concurrency,
+                interactive,
+                open,
+                count,
+                raw,
+                watch,
             } => {
+                let anonymizer = anonymization.anonymizer()?;
+                if let Some(batch) = batch {
+                    let searches = batch_searches(
+                        &batch,
+                        &time_range,
+                        &filters,
+                        workspace.as_ref(),
+                        cli.timezone,
+                        cli.no_validate,
+                        &logger,
+                    )?;
+                    logger.log_api_endpoint("/api/v2/logs/events", "POST");
+                    if cli.emit_meta {
+                        for search in &searches {
+                            let meta = MetaRecord::new(
+                                "logs",
+                                Some(&search.query),
+                                &search.time_range.from,
+                                &search.time_range.to,
+                                cli.timezone,
+                            )?;
+                            writer.write_meta(&BatchRecord {
+                                query_id: &search.id,
+                                record: meta.with_indexes(&source.indexes),
+                            })?;
+                        }
+                    }
+
+                    let router = OwnerRouter::from_args(&routing, &config, &logger).await?;
+                    let sink = SearchSink::new(writer, router).with_anonymizer(anonymizer);
+                    let client = client::LogsClient::new(config);
+                    return commands::logs::search::run_batch(
+                        client,
+                        searches,
+                        pagination,
+                        source,
+                        usize::from(concurrency),
+                        sink,
+                        logger,
+                    )
+                    .await;
+                }
+
+                time_range.resolve(cli.timezone)?;
+                let query = resolve_search_query(
+                    &query,
+                    SearchDomain::Logs,
+                    &filters,
+                    workspace.as_ref(),
+                    &logger,
+                )?;
+                if !cli.no_validate {
+                    commands::query::lint::preflight(&query, SearchDomain::Logs)?;
+                }
                 logger.log_request("logs", &query, &time_range.from, &time_range.to);
                 logger.log_api_endpoint("/api/v2/logs/events", "POST");
                 logger.log_datadog_url("logs", &query, &time_range.from, &time_range.to, &site);
+                if open {
+                    let url = commands::open::explorer_url(
+                        &DatadogLinks::new(&site),
+                        SearchDomain::Logs,
+                        &query,
+                        &time_range,
+                        &source.indexes,
+                        cli.timezone,
+                    )?;
+                    return commands::open::run(&url, logger);
+                }
+                if count {
+                    let client = client::LogsClient::new(config);
+                    let total = client
+                        .count(&query, &time_range.from, &time_range.to, source.indexes)
+                        .await?;
+                    let record = CountRecord::new(&query, &time_range.from, &time_range.to, total);
+                    return commands::count::write(&record, raw, &mut writer, &logger);
+                }
+                let explanation = if cli.explain {
+                    let explanation = commands::explain::logs(
+                        &config,
+                        &query,
+                        &time_range,
+                        &source,
+                        &pagination,
+                        cli.timezone,
+                        &logger,
+                    )
+                    .await?;
+                    explanation.print();
+                    Some(explanation)
+                } else {
+                    None
+                };
+                if let Some(explanation) = explanation.filter(|_| cli.emit_meta) {
+                    writer.write_meta(&explanation)?;
+                } else if cli.emit_meta {
+                    let meta = MetaRecord::new(
+                        "logs",
+                        Some(&query),
+                        &time_range.from,
+                        &time_range.to,
+                        cli.timezone,
+                    )?;
+                    writer.write_meta(&meta.with_indexes(&source.indexes))?;
+                }
+
+                recording = Recording::start("logs", &query, &time_range.from, &time_range.to);
+
+                if interactive {
+                    let options = commands::logs::browse::BrowseOptions {
+                        links: DatadogLinks::new(&site),
+                        timezone: cli.local_timestamps.then_some(cli.timezone),
+                        anonymizer,
+                    };
+                    let client = client::LogsClient::new(config);
+                    commands::logs::browse::run(
+                        client, query, time_range, pagination, source, options, logger,
+                    )
+                    .await
+                } else if let Some(watcher) =
+                    commands::watch::Watcher::new(&watch, pagination.limit, cli.timezone)
+                {
+                    let router = OwnerRouter::from_args(&routing, &config, &logger).await?;
+                    let sink = SearchSink::new(writer, router).with_anonymizer(anonymizer);
+                    let client = client::LogsClient::new(config);
+                    commands::logs::search::watch(
+                        client, query, time_range, source, sink, watcher, logger,
+                    )
+                    .await
+                } else {
+                    let router = OwnerRouter::from_args(&routing, &config, &logger).await?;
+                    let sink = SearchSink::new(writer, router)
+                        .with_anonymizer(anonymizer)
+                        .with_progress(search_progress(
+                            &time_range,
+                            pagination.limit,
+                            pagination.page_limit(client::MAX_LOGS_PAGE_SIZE),
+                            cli.timezone,
+                            !cli.quiet && cli.verbose == 0,
+                        ));
+                    let restrictions = client::RestrictionsClient::new(config.clone());
+                    let client = client::LogsClient::new(config);
+                    let count = commands::logs::search::run(
+                        client,
+                        query,
+                        time_range,
+                        pagination,
+                        source,
+                        sink,
+                        logger.clone(),
+                    )
+                    .await?;
+                    if count == 0 {
+                        commands::logs::search::warn_if_restricted(&restrictions, &logger).await;
+                    }
+                    Ok(())
+                }
+            }
+            LogsAction::Aggregate {
+                query,
+                mut time_range,
+                filters,
+                aggregation,
+                indexes,
+                watch,
+            } => {
+                time_range.resolve(cli.timezone)?;
+                let query = resolve_search_query(
+                    &query,
+                    SearchDomain::Logs,
+                    &filters,
+                    workspace.as_ref(),
+                    &logger,
+                )?;
+                if !cli.no_validate {
+                    commands::query::lint::preflight(&query, SearchDomain::Logs)?;
+                }
+                logger.log_request("logs", &query, &time_range.from, &time_range.to);
+                logger.log_api_endpoint("/api/v2/logs/analytics/aggregate", "POST");
+                if cli.emit_meta {
+                    let meta = MetaRecord::new(
+                        "logs",
+                        Some(&query),
+                        &time_range.from,
+                        &time_range.to,
+                        cli.timezone,
+                    )?;
+                    writer.write_meta(&meta.with_indexes(&indexes))?;
+                }
+
+                recording = Recording::start("logs", &query, &time_range.from, &time_range.to);
+
+                let client = client::LogsClient::new(config);
+                if let Some(watcher) = commands::watch::Watcher::new(&watch, 0, cli.timezone) {
+                    let grouping = aggregation.grouping();
+                    let fetch = || {
+                        client.volume(
+                            &query,
+                            &time_range.from,
+                            &time_range.to,
+                            indexes.clone(),
+                            &grouping,
+                        )
+                    };
+                    return commands::watch::aggregate(fetch, watcher, &mut writer, &logger).await;
+                }
+                commands::logs::aggregate::run(
+                    client,
+                    query,
+                    time_range,
+                    aggregation,
+                    indexes,
+                    writer,
+                    logger,
+                )
+                .await
+            }
+            LogsAction::Facets {
+                query,
+                mut time_range,
+                filters,
+                discovery,
+                indexes,
+            } => {
+                time_range.resolve(cli.timezone)?;
+                let query = resolve_search_query(
+                    &query,
+                    SearchDomain::Logs,
+                    &filters,
+                    workspace.as_ref(),
+                    &logger,
+                )?;
+                if !cli.no_validate {
+                    commands::query::lint::preflight(&query, SearchDomain::Logs)?;
+                }
+                logger.log_request("logs", &query, &time_range.from, &time_range.to);
+                logger.log_api_endpoint("/api/v2/logs/events", "POST");
+                logger.log_api_endpoint("/api/v2/logs/analytics/aggregate", "POST");
+                if cli.emit_meta {
+                    let meta = MetaRecord::new(
+                        "logs",
+                        Some(&query),
+                        &time_range.from,
+                        &time_range.to,
+                        cli.timezone,
+                    )?;
+                    writer.write_meta(&meta.with_indexes(&indexes))?;
+                }
+
+                let client = client::LogsClient::new(config);
+                commands::logs::facets::run(
+                    client, query, time_range, discovery, indexes, writer, logger,
+                )
+                .await
+            }
+            LogsAction::Patterns {
+                query,
+                mut time_range,
+                filters,
+                indexes,
+                limit,
+                top,
+            } => {
+                time_range.resolve(cli.timezone)?;
+                let query = resolve_search_query(
+                    &query,
+                    SearchDomain::Logs,
+                    &filters,
+                    workspace.as_ref(),
+                    &logger,
+                )?;
+                if !cli.no_validate {
+                    commands::query::lint::preflight(&query, SearchDomain::Logs)?;
+                }
+                logger.log_request("logs", &query, &time_range.from, &time_range.to);
+                logger.log_api_endpoint("/api/v2/logs/events/search", "POST");
+                recording = Recording::start("logs", &query, &time_range.from, &time_range.to);
+
+                let client = client::LogsClient::new(config);
+                let request = time_range.logs_request(&query, &indexes);
+                commands::logs::patterns::run(client, request, limit, top, writer, logger).await
+            }
+            LogsAction::Stats {
+                query,
+                field,
+                mut time_range,
+                filters,
+                indexes,
+                limit,
+            } => {
+                time_range.resolve(cli.timezone)?;
+                let query = resolve_search_query(
+                    &query,
+                    SearchDomain::Logs,
+                    &filters,
+                    workspace.as_ref(),
+                    &logger,
+                )?;
+                if !cli.no_validate {
+                    commands::query::lint::preflight(&query, SearchDomain::Logs)?;
+                }
+                logger.log_request("logs", &query, &time_range.from, &time_range.to);
+                logger.log_api_endpoint("/api/v2/logs/events/search", "POST");
+                recording = Recording::start("logs", &query, &time_range.from, &time_range.to);
 
                 let client = client::LogsClient::new(config);
-                commands::logs::search::run(client, query, time_range, pagination, indexes, logger)
+                let request = time_range.logs_request(&query, &indexes);
+                commands::logs::stats::run(client, request, field, limit, writer, logger).await
+            }
+            LogsAction::Schema {
+                query,
+                mut time_range,
+                filters,
+                indexes,
+                sample,
+            } => {
+                time_range.resolve(cli.timezone)?;
+                let query = resolve_search_query(
+                    &query,
+                    SearchDomain::Logs,
+                    &filters,
+                    workspace.as_ref(),
+                    &logger,
+                )?;
+                if !cli.no_validate {
+                    commands::query::lint::preflight(&query, SearchDomain::Logs)?;
+                }
+                logger.log_request("logs", &query, &time_range.from, &time_range.to);
+                logger.log_api_endpoint("/api/v2/logs/events/search", "POST");
+
+                let client = client::LogsClient::new(config);
+                let request = time_range.logs_request(&query, &indexes);
+                commands::logs::schema::run(client, request, sample, logger).await
+            }
+            LogsAction::Top {
+                facet,
+                query,
+                by,
+                limit,
+                mut time_range,
+                filters,
+                indexes,
+            } => {
+                time_range.resolve(cli.timezone)?;
+                let query = resolve_search_query(
+                    &query,
+                    SearchDomain::Logs,
+                    &filters,
+                    workspace.as_ref(),
+                    &logger,
+                )?;
+                if !cli.no_validate {
+                    commands::query::lint::preflight(&query, SearchDomain::Logs)?;
+                }
+                logger.log_request("logs", &query, &time_range.from, &time_range.to);
+                logger.log_api_endpoint("/api/v2/logs/analytics/aggregate", "POST");
+                if cli.emit_meta {
+                    let meta = MetaRecord::new(
+                        "logs",
+                        Some(&query),
+                        &time_range.from,
+                        &time_range.to,
+                        cli.timezone,
+                    )?;
+                    writer.write_meta(&meta.with_indexes(&indexes))?;
+                }
+                recording = Recording::start("logs", &query, &time_range.from, &time_range.to);
+
+                let client = client::LogsClient::new(config);
+                let ranking = client::Ranking {
+                    facet,
+                    limit,
+                    measure: by,
+                };
+                commands::logs::top::run(
+                    client, query, time_range, ranking, indexes, writer, logger,
+                )
+                .await
+            }
+            LogsAction::Check {
+                query,
+                max,
+                min,
+                mut time_range,
+                filters,
+                indexes,
+                notify_slack,
+            } => {
+                time_range.resolve(cli.timezone)?;
+                let query = resolve_search_query(
+                    &query,
+                    SearchDomain::Logs,
+                    &filters,
+                    workspace.as_ref(),
+                    &logger,
+                )?;
+                if !cli.no_validate {
+                    commands::query::lint::preflight(&query, SearchDomain::Logs)?;
+                }
+                logger.log_request("logs", &query, &time_range.from, &time_range.to);
+                logger.log_api_endpoint("/api/v2/logs/analytics/aggregate", "POST");
+
+                let notify = match notify_slack {
+                    Some(url) => {
+                        let explorer_url = commands::open::explorer_url(
+                            &DatadogLinks::new(&site),
+                            SearchDomain::Logs,
+                            &query,
+                            &time_range,
+                            &indexes,
+                            cli.timezone,
+                        )?;
+                        Some((SlackWebhook::new(&url), explorer_url))
+                    }
+                    None => None,
+                };
+                let client = client::LogsClient::new(config);
+                let check = commands::logs::check::Check {
+                    query,
+                    threshold: Threshold::new(min, max),
+                    indexes,
+                    notify,
+                };
+                commands::logs::check::run(client, check, time_range, writer, logger).await
+            }
+            LogsAction::Diff {
+                query,
+                baseline,
+                compare,
+                group_by,
+                limit,
+                filters,
+                indexes,
+            } => {
+                let mut windows = [TimeRange::from(baseline), TimeRange::from(compare)];
+                for window in &mut windows {
+                    window.resolve(cli.timezone)?;
+                }
+                commands::logs::diff::warn_uneven(&windows, cli.timezone)?;
+                let query = resolve_search_query(
+                    &query,
+                    SearchDomain::Logs,
+                    &filters,
+                    workspace.as_ref(),
+                    &logger,
+                )?;
+                if !cli.no_validate {
+                    commands::query::lint::preflight(&query, SearchDomain::Logs)?;
+                }
+                for window in &windows {
+                    logger.log_request("logs", &query, &window.from, &window.to);
+                }
+                logger.log_api_endpoint("/api/v2/logs/analytics/aggregate", "POST");
+
+                let client = client::LogsClient::new(config);
+                let ranking = client::Ranking {
+                    facet: group_by,
+                    limit,
+                    measure: client::Measure::Count,
+                };
+                commands::logs::diff::run(client, query, windows, ranking, indexes, writer, logger)
                     .await
             }
+            LogsAction::Metrics { action } => {
+                let client = client::LogMetricsClient::new(config);
+                match action {
+                    LogsMetricsAction::List => {
+                        logger.log_api_endpoint(client::LOG_METRICS_PATH, "GET");
+                        commands::logs::metrics::list(client, writer, logger).await
+                    }
+                    LogsMetricsAction::Create {
+                        definition,
+                        dry_run,
+                        yes,
+                    } => {
+                        logger.log_api_endpoint(client::LOG_METRICS_PATH, "POST");
+                        commands::logs::metrics::create(
+                            client,
+                            &definition,
+                            dry_run,
+                            yes,
+                            writer,
+                            logger,
+                        )
+                        .await
+                    }
+                    LogsMetricsAction::Delete { id, dry_run, yes } => {
+                        logger.log_api_endpoint(
+                            &format!("{}/{}", client::LOG_METRICS_PATH, id),
+                            "DELETE",
+                        );
+                        commands::logs::metrics::delete(client, &id, dry_run, yes, writer, logger)
+                            .await
+                    }
+                }
+            }
+            LogsAction::Exclusions { action } => {
+                logger.log_api_endpoint("/api/v1/logs/config/indexes", "GET");
+                let client = client::ResourcesClient::new(config);
+                match action {
+                    LogsExclusionsAction::List => {
+                        commands::logs::exclusions::list(client, writer, logger).await
+                    }
+                    LogsExclusionsAction::Test { query, filters } => {
+                        let query = resolve_search_query(
+                            &query,
+                            SearchDomain::Logs,
+                            &filters,
+                            workspace.as_ref(),
+                            &logger,
+                        )?;
+                        commands::logs::exclusions::test(client, &query, writer, logger).await
+                    }
+                }
+            }
         },
         Domain::Spans { action } => match action {
             SpansAction::Search {
                 query,
-                time_range,
+                mut time_range,
                 pagination,
+                filters,
+                routing,
+                anonymization,
+                search_type,
+                open,
+                count,
+                raw,
+                watch,
             } => {
+                time_range.resolve(cli.timezone)?;
+                let anonymizer = anonymization.anonymizer()?;
+                let query = resolve_search_query(
+                    &query,
+                    SearchDomain::Spans,
+                    &filters,
+                    workspace.as_ref(),
+                    &logger,
+                )?;
+                if !cli.no_validate {
+                    commands::query::lint::preflight(&query, SearchDomain::Spans)?;
+                }
                 logger.log_request("spans", &query, &time_range.from, &time_range.to);
                 logger.log_api_endpoint("/api/v2/spans/events/search", "POST");
                 logger.log_datadog_url("spans", &query, &time_range.from, &time_range.to, &site);
+                if open {
+                    let url = commands::open::explorer_url(
+                        &DatadogLinks::new(&site),
+                        SearchDomain::Spans,
+                        &query,
+                        &time_range,
+                        &[],
+                        cli.timezone,
+                    )?;
+                    return commands::open::run(&url, logger);
+                }
+                if count {
+                    let client = client::SpansClient::new(config);
+                    let total = client
+                        .count(&query, &time_range.from, &time_range.to)
+                        .await?;
+                    let record = CountRecord::new(&query, &time_range.from, &time_range.to, total);
+                    return commands::count::write(&record, raw, &mut writer, &logger);
+                }
+                let explanation = if cli.explain {
+                    let explanation = commands::explain::spans(
+                        &config,
+                        &query,
+                        &time_range,
+                        search_type,
+                        &pagination,
+                        cli.timezone,
+                        &logger,
+                    )
+                    .await?;
+                    explanation.print();
+                    Some(explanation)
+                } else {
+                    None
+                };
+                if let Some(explanation) = explanation.filter(|_| cli.emit_meta) {
+                    writer.write_meta(&explanation)?;
+                } else if cli.emit_meta {
+                    writer.write_meta(&MetaRecord::new(
+                        "spans",
+                        Some(&query),
+                        &time_range.from,
+                        &time_range.to,
+                        cli.timezone,
+                    )?)?;
+                }
+
+                recording = Recording::start("spans", &query, &time_range.from, &time_range.to);
+
+                let router = OwnerRouter::from_args(&routing, &config, &logger).await?;
+                if let Some(watcher) =
+                    commands::watch::Watcher::new(&watch, pagination.limit, cli.timezone)
+                {
+                    let sink = SearchSink::new(writer, router).with_anonymizer(anonymizer);
+                    let client = client::SpansClient::new(config);
+                    return commands::spans::search::watch(
+                        client,
+                        query,
+                        time_range,
+                        search_type,
+                        sink,
+                        watcher,
+                        logger,
+                    )
+                    .await;
+                }
+                let sink = SearchSink::new(writer, router)
+                    .with_anonymizer(anonymizer)
+                    .with_progress(search_progress(
+                        &time_range,
+                        pagination.limit,
+                        pagination.page_limit(client::MAX_SPANS_PAGE_SIZE),
+                        cli.timezone,
+                        !cli.quiet && cli.verbose == 0,
+                    ));
+                let client = client::SpansClient::new(config);
+                commands::spans::search::run(
+                    client,
+                    query,
+                    time_range,
+                    search_type,
+                    pagination,
+                    sink,
+                    logger,
+                )
+                .await
+            }
+            SpansAction::Tail {
+                query,
+                from,
+                interval,
+                bell,
+                filters,
+                anonymization,
+            } => {
+                let mut time_range = TimeRange {
+                    from,
+                    to: "now".to_string(),
+                    last: None,
+                    align: None,
+                };
+                time_range.resolve(cli.timezone)?;
+                let anonymizer = anonymization.anonymizer()?;
+                let query = resolve_search_query(
+                    &query,
+                    SearchDomain::Spans,
+                    &filters,
+                    workspace.as_ref(),
+                    &logger,
+                )?;
+                if !cli.no_validate {
+                    commands::query::lint::preflight(&query, SearchDomain::Spans)?;
+                }
+                logger.log_request("spans", &query, &time_range.from, &time_range.to);
+                logger.log_api_endpoint("/api/v2/spans/events/search", "POST");
+
+                let watcher = commands::watch::Watcher::every(interval, bell, cli.timezone);
+                let sink = SearchSink::new(writer, None).with_anonymizer(anonymizer);
+                let client = client::SpansClient::new(config);
+                commands::spans::search::watch(
+                    client,
+                    query,
+                    time_range,
+                    client::SpansSearchType::Spans,
+                    sink,
+                    watcher,
+                    logger,
+                )
+                .await
+            }
+            SpansAction::Aggregate {
+                query,
+                mut time_range,
+                filters,
+                aggregation,
+                watch,
+            } => {
+                time_range.resolve(cli.timezone)?;
+                let query = resolve_search_query(
+                    &query,
+                    SearchDomain::Spans,
+                    &filters,
+                    workspace.as_ref(),
+                    &logger,
+                )?;
+                if !cli.no_validate {
+                    commands::query::lint::preflight(&query, SearchDomain::Spans)?;
+                }
+                logger.log_request("spans", &query, &time_range.from, &time_range.to);
+                logger.log_api_endpoint("/api/v2/spans/analytics/aggregate", "POST");
+                if cli.emit_meta {
+                    writer.write_meta(&MetaRecord::new(
+                        "spans",
+                        Some(&query),
+                        &time_range.from,
+                        &time_range.to,
+                        cli.timezone,
+                    )?)?;
+                }
+
+                recording = Recording::start("spans", &query, &time_range.from, &time_range.to);
+
+                let client = client::SpansClient::new(config);
+                if let Some(watcher) = commands::watch::Watcher::new(&watch, 0, cli.timezone) {
+                    let grouping = aggregation.grouping();
+                    let fetch =
+                        || client.volume(&query, &time_range.from, &time_range.to, &grouping);
+                    return commands::watch::aggregate(fetch, watcher, &mut writer, &logger).await;
+                }
+                commands::spans::aggregate::run(
+                    client,
+                    query,
+                    time_range,
+                    aggregation,
+                    writer,
+                    logger,
+                )
+                .await
+            }
+            SpansAction::Latency {
+                query,
+                mut time_range,
+                filters,
+                percentiles,
+                interval,
+            } => {
+                time_range.resolve(cli.timezone)?;
+                let query = resolve_search_query(
+                    &query,
+                    SearchDomain::Spans,
+                    &filters,
+                    workspace.as_ref(),
+                    &logger,
+                )?;
+                if !cli.no_validate {
+                    commands::query::lint::preflight(&query, SearchDomain::Spans)?;
+                }
+                logger.log_request("spans", &query, &time_range.from, &time_range.to);
+                logger.log_api_endpoint("/api/v2/spans/analytics/aggregate", "POST");
+                recording = Recording::start("spans", &query, &time_range.from, &time_range.to);
 
                 let client = client::SpansClient::new(config);
-                commands::spans::search::run(client, query, time_range, pagination, logger).await
+                commands::spans::latency::run(
+                    client,
+                    query,
+                    time_range,
+                    percentiles,
+                    interval,
+                    writer,
+                    logger,
+                )
+                .await
+            }
+            SpansAction::ErrorRate {
+                query,
+                mut time_range,
+                filters,
+                group_by,
+                top,
+            } => {
+                time_range.resolve(cli.timezone)?;
+                let query = resolve_search_query(
+                    &query,
+                    SearchDomain::Spans,
+                    &filters,
+                    workspace.as_ref(),
+                    &logger,
+                )?;
+                if !cli.no_validate {
+                    commands::query::lint::preflight(&query, SearchDomain::Spans)?;
+                }
+                logger.log_request("spans", &query, &time_range.from, &time_range.to);
+                logger.log_api_endpoint("/api/v2/spans/analytics/aggregate", "POST");
+                recording = Recording::start("spans", &query, &time_range.from, &time_range.to);
+
+                let client = client::SpansClient::new(config);
+                commands::spans::error_rate::run(
+                    client, query, time_range, group_by, top, writer, logger,
+                )
+                .await
+            }
+            SpansAction::Trace {
+                trace_id,
+                mut time_range,
+            } => {
+                time_range.resolve(cli.timezone)?;
+                let query = format!("trace_id:{}", trace_id);
+                logger.log_request("spans", &query, &time_range.from, &time_range.to);
+                logger.log_api_endpoint("/api/v2/spans/events/search", "POST");
+                recording = Recording::start("spans", &query, &time_range.from, &time_range.to);
+
+                let client = client::SpansClient::new(config);
+                commands::spans::trace::run(
+                    client,
+                    trace_id,
+                    time_range,
+                    cli.format.diagram(),
+                    writer,
+                    logger,
+                )
+                .await
+            }
+            SpansAction::Deps {
+                query,
+                mut time_range,
+                filters,
+                limit,
+            } => {
+                time_range.resolve(cli.timezone)?;
+                let query = resolve_search_query(
+                    &query,
+                    SearchDomain::Spans,
+                    &filters,
+                    workspace.as_ref(),
+                    &logger,
+                )?;
+                if !cli.no_validate {
+                    commands::query::lint::preflight(&query, SearchDomain::Spans)?;
+                }
+                logger.log_request("spans", &query, &time_range.from, &time_range.to);
+                logger.log_api_endpoint("/api/v2/spans/events/search", "POST");
+
+                let client = client::SpansClient::new(config);
+                let dot = cli.format == OutputFormat::Dot;
+                commands::spans::deps::run(client, query, time_range, limit, dot, writer, logger)
+                    .await
+            }
+            SpansAction::SamplingReport {
+                query,
+                mut time_range,
+                filters,
+                top,
+            } => {
+                time_range.resolve(cli.timezone)?;
+                let query = resolve_search_query(
+                    &query,
+                    SearchDomain::Spans,
+                    &filters,
+                    workspace.as_ref(),
+                    &logger,
+                )?;
+                if !cli.no_validate {
+                    commands::query::lint::preflight(&query, SearchDomain::Spans)?;
+                }
+                logger.log_request("spans", &query, &time_range.from, &time_range.to);
+                logger.log_api_endpoint("/api/v2/spans/analytics/aggregate", "POST");
+                if cli.emit_meta {
+                    writer.write_meta(&MetaRecord::new(
+                        "spans",
+                        Some(&query),
+                        &time_range.from,
+                        &time_range.to,
+                        cli.timezone,
+                    )?)?;
+                }
+
+                let client = client::SpansClient::new(config);
+                commands::spans::sampling_report::run(
+                    client, query, time_range, top, writer, logger,
+                )
+                .await
+            }
+            SpansAction::RetentionFilters {
+                action: SpansRetentionFiltersAction::List,
+            } => {
+                logger.log_api_endpoint(client::RETENTION_FILTERS_PATH, "GET");
+                let client = client::ApmConfigClient::new(config);
+                commands::spans::retention_filters::list(client, writer, logger).await
+            }
+            SpansAction::Metrics {
+                action: SpansMetricsAction::List,
+            } => {
+                logger.log_api_endpoint(client::SPAN_METRICS_PATH, "GET");
+                let client = client::ApmConfigClient::new(config);
+                commands::spans::metrics::list(client, writer, logger).await
             }
         },
         Domain::Metrics { action } => match action {
             MetricsAction::Query {
                 query,
-                time_range,
+                queries,
+                formulas,
+                mut time_range,
                 limit,
+                join,
+                viz,
             } => {
+                time_range.resolve(cli.timezone)?;
+                let queries: Vec<String> = query.into_iter().chain(queries).collect();
+                let query = match formulas.is_empty() {
+                    true => queries.join(", "),
+                    false => format!("{} => {}", queries.join(", "), formulas.join(", ")),
+                };
                 logger.log_request("metrics", &query, &time_range.from, &time_range.to);
-                logger.log_api_endpoint("/api/v1/query", "GET");
+                if formulas.is_empty() && queries.len() == 1 {
+                    logger.log_api_endpoint("/api/v1/query", "GET");
+                } else {
+                    logger.log_api_endpoint("/api/v2/query/timeseries", "POST");
+                }
+                if cli.emit_meta {
+                    writer.write_meta(&MetaRecord::new(
+                        "metrics",
+                        Some(&query),
+                        &time_range.from,
+                        &time_range.to,
+                        cli.timezone,
+                    )?)?;
+                }
+
+                recording = Recording::start("metrics", &query, &time_range.from, &time_range.to);
 
                 let client = client::MetricsClient::new(config);
-                commands::metrics::query::run(client, query, time_range, limit, logger).await
+                let wide = cli.format == OutputFormat::Wide;
+                if wide && join {
+                    Cli::command()
+                        .error(
+                            ErrorKind::ArgumentConflict,
+                            "--join cannot be used with --format wide",
+                        )
+                        .exit();
+                }
+                let output = commands::metrics::query::QueryOutput {
+                    limit,
+                    join,
+                    wide,
+                    viz,
+                };
+                commands::metrics::query::run(
+                    client, queries, formulas, time_range, output, writer, logger,
+                )
+                .await
             }
-            MetricsAction::List { time_from } => {
+            MetricsAction::List { mut time_from } => {
+                time_from.resolve(cli.timezone)?;
                 logger.log(&format!("Listing active metrics from {}", time_from.from));
                 logger.log_api_endpoint("/api/v1/metrics", "GET");
+                if cli.emit_meta {
+                    writer.write_meta(&MetaRecord::new(
+                        "metrics",
+                        None,
+                        &time_from.from,
+                        "now",
+                        cli.timezone,
+                    )?)?;
+                }
 
                 let client = client::MetricsClient::new(config);
-                commands::metrics::list::run(client, time_from, logger).await
+                commands::metrics::list::run(client, time_from, writer, logger).await
+            }
+            MetricsAction::Gaps {
+                query,
+                mut time_range,
+                interval,
+            } => {
+                time_range.resolve(cli.timezone)?;
+                logger.log_request("metrics", &query, &time_range.from, &time_range.to);
+                logger.log_api_endpoint("/api/v1/query", "GET");
+                if cli.emit_meta {
+                    writer.write_meta(&MetaRecord::new(
+                        "metrics",
+                        Some(&query),
+                        &time_range.from,
+                        &time_range.to,
+                        cli.timezone,
+                    )?)?;
+                }
+
+                let client = client::MetricsClient::new(config);
+                commands::metrics::gaps::run(client, query, time_range, interval, writer, logger)
+                    .await
+            }
+            MetricsAction::Cardinality { metric } => {
+                logger.log(&format!("Reporting tag cardinality of {}", metric));
+                logger.log_api_endpoint(&format!("/api/v2/metrics/{}/all-tags", metric), "GET");
+
+                let client = client::MetricsClient::new(config);
+                commands::metrics::cardinality::run(client, metric, writer, logger).await
+            }
+            MetricsAction::Scalar {
+                query,
+                mut time_range,
+                reduce,
+                warn,
+                crit,
+            } => {
+                time_range.resolve(cli.timezone)?;
+                logger.log_request("metrics", &query, &time_range.from, &time_range.to);
+                logger.log_api_endpoint("/api/v2/query/scalar", "POST");
+                if cli.emit_meta {
+                    writer.write_meta(&MetaRecord::new(
+                        "metrics",
+                        Some(&query),
+                        &time_range.from,
+                        &time_range.to,
+                        cli.timezone,
+                    )?)?;
+                }
+
+                let client = client::MetricsClient::new(config);
+                let levels = Levels { warn, crit };
+                commands::metrics::scalar::run(
+                    client, query, time_range, reduce, levels, writer, logger,
+                )
+                .await
+            }
+        },
+        Domain::Monitors { action } => match action {
+            MonitorsAction::History {
+                monitor_id,
+                mut time_range,
+            } => {
+                time_range.resolve(cli.timezone)?;
+                let query = format!("source:alert @monitor_id:{}", monitor_id);
+                logger.log_request("events", &query, &time_range.from, &time_range.to);
+                logger.log_api_endpoint("/api/v2/events", "GET");
+                if cli.emit_meta {
+                    writer.write_meta(&MetaRecord::new(
+                        "monitors",
+                        Some(&query),
+                        &time_range.from,
+                        &time_range.to,
+                        cli.timezone,
+                    )?)?;
+                }
+
+                let client = client::EventsClient::new(config);
+                commands::monitors::history::run(client, monitor_id, time_range, writer, logger)
+                    .await
+            }
+            MonitorsAction::Status {
+                monitor_id,
+                watch,
+                notify_slack,
+            } => {
+                logger.log_api_endpoint(&format!("/api/v1/monitor/{}", monitor_id), "GET");
+
+                let client = client::MonitorsClient::new(config);
+                let watcher = commands::watch::Watcher::new(&watch, 0, cli.timezone);
+                let notify = notify_slack.map(|url| {
+                    (
+                        SlackWebhook::new(&url),
+                        DatadogLinks::new(&site).monitor(monitor_id),
+                    )
+                });
+                commands::monitors::status::run(client, monitor_id, watcher, notify, writer, logger)
+                    .await
+            }
+            MonitorsAction::NoiseReport {
+                tags,
+                mut time_range,
+            } => {
+                time_range.resolve(cli.timezone)?;
+                let query = format!("source:alert monitor_tags:{}", tags.join(","));
+                logger.log_request("events", &query, &time_range.from, &time_range.to);
+                logger.log_api_endpoint("/api/v1/monitor", "GET");
+                logger.log_api_endpoint("/api/v2/events", "GET");
+                if cli.emit_meta {
+                    writer.write_meta(&MetaRecord::new(
+                        "monitors",
+                        Some(&query),
+                        &time_range.from,
+                        &time_range.to,
+                        cli.timezone,
+                    )?)?;
+                }
+
+                let monitors = client::MonitorsClient::new(config.clone());
+                let events = client::EventsClient::new(config);
+                commands::monitors::noise_report::run(
+                    monitors, events, tags, time_range, writer, logger,
+                )
+                .await
+            }
+        },
+        Domain::Hosts { action } => match action {
+            HostsAction::Flaps {
+                mut time_range,
+                scope,
+                min_flaps,
+            } => {
+                time_range.resolve(cli.timezone)?;
+                logger.log(&format!(
+                    "Finding unstable hosts from {} to {}",
+                    time_range.from, time_range.to
+                ));
+                logger.log_api_endpoint("/api/v1/hosts", "GET");
+                logger.log_api_endpoint("/api/v2/events", "GET");
+                logger.log_api_endpoint("/api/v1/query", "GET");
+                if cli.emit_meta {
+                    writer.write_meta(&MetaRecord::new(
+                        "hosts",
+                        scope.as_deref(),
+                        &time_range.from,
+                        &time_range.to,
+                        cli.timezone,
+                    )?)?;
+                }
+
+                commands::hosts::flaps::run(config, scope, min_flaps, time_range, writer, logger)
+                    .await
             }
         },
+        Domain::Drift { action } => match action {
+            DriftAction::Check { bundle } => {
+                logger.log(&format!("Checking drift against {}", bundle.display()));
+                logger.log_api_endpoint("/api/v1/monitor/{id}", "GET");
+                logger.log_api_endpoint("/api/v1/slo/{id}", "GET");
+                logger.log_api_endpoint("/api/v1/dashboard/{id}", "GET");
+                logger.log_api_endpoint("/api/v1/logs/config/indexes/{name}", "GET");
+
+                let client = client::ResourcesClient::new(config);
+                commands::drift::check::run(client, &bundle, writer, logger).await
+            }
+        },
+        Domain::Handoff { since, team } => {
+            logger.log(&format!(
+                "Handoff summary for team {} since {}",
+                team, since
+            ));
+            logger.log_api_endpoint("/api/v1/monitor", "GET");
+            logger.log_api_endpoint("/api/v2/events", "GET");
+            logger.log_api_endpoint("/api/v2/incidents/search", "GET");
+            logger.log_api_endpoint("/api/v2/logs/events", "POST");
+            logger.log_api_endpoint("/api/v1/slo", "GET");
+
+            commands::handoff::run(config, team, since, &site, logger).await
+        }
+        Domain::Bench {
+            action:
+                BenchAction::Logs {
+                    query,
+                    mut time_range,
+                    source,
+                    filters,
+                    iterations,
+                    limit,
+                    page_size,
+                },
+        } => {
+            time_range.resolve(cli.timezone)?;
+            let query = resolve_search_query(
+                &query,
+                SearchDomain::Logs,
+                &filters,
+                workspace.as_ref(),
+                &logger,
+            )?;
+            if !cli.no_validate {
+                commands::query::lint::preflight(&query, SearchDomain::Logs)?;
+            }
+            logger.log_request("logs", &query, &time_range.from, &time_range.to);
+            logger.log_api_endpoint("/api/v2/logs/events", "POST");
+
+            let client = client::LogsClient::new(config);
+            let options = commands::bench::BenchOptions {
+                iterations,
+                limit,
+                page_size,
+            };
+            commands::bench::logs(client, query, time_range, source, options, writer, logger).await
+        }
+        Domain::Export { spec, restart } => {
+            logger.log(&format!("Running export spec {}", spec.display()));
+            logger.log_api_endpoint("/api/v2/logs/events/search", "POST");
+            logger.log_api_endpoint("/api/v2/spans/events/search", "POST");
+            logger.log_api_endpoint("/api/v1/query", "GET");
+
+            commands::export::run(config, spec, restart, cli.timezone, writer, logger).await
+        }
+        Domain::Repl => {
+            let repl = commands::repl::Repl {
+                logs: client::LogsClient::new(config.clone()),
+                spans: client::SpansClient::new(config),
+                workspace: workspace.as_ref(),
+                timezone: cli.timezone,
+                no_validate: cli.no_validate,
+            };
+            let text_options =
+                output::TextOptions::stdout(&site, cli.local_timestamps.then_some(cli.timezone));
+            commands::repl::run(repl, text_options, logger).await
+        }
+        Domain::Api { action } => match action {
+            ApiAction::Get { path, query, raw } => {
+                logger.log_api_endpoint(&path, "GET");
+
+                let client = client::ApiClient::new(&config, credentials()?);
+                commands::api::get::run(client, &path, &query, raw, writer, logger).await
+            }
+            ApiAction::Post {
+                path,
+                body,
+                vars,
+                query,
+                dry_run,
+                yes,
+                raw,
+            } => {
+                logger.log_api_endpoint(&path, "POST");
+
+                let client = client::ApiClient::new(&config, credentials()?);
+                let post = commands::api::post::Post {
+                    path,
+                    body,
+                    vars,
+                    query,
+                    dry_run,
+                    yes,
+                    raw,
+                };
+                commands::api::post::run(client, post, writer, logger).await
+            }
+        },
+        Domain::Query { .. }
+        | Domain::History { .. }
+        | Domain::Completions { .. }
+        | Domain::Docs { .. }
+        | Domain::HelpAll
+        | Domain::Telemetry { .. }
+        | Domain::SelfUpdate { .. } => {
+            unreachable!(
+                "query tools, history, completions, docs, telemetry, and self-update are dispatched before loading credentials"
+            )
+        }
+    };
+
+    if let Some(recording) = recording
+        && result.is_ok()
+    {
+        recording.finish();
+    }
+    result
+}
+
+/// The transforms applied to every record written, in their fixed order.
+fn output_pipeline(cli: &Cli) -> Pipeline {
+    let mut pipeline = Pipeline::default();
+    let redactor = Redactor::new(&cli.redact, cli.redact_pii);
+    if !redactor.is_empty() {
+        pipeline = pipeline.then(Stage::Redact(redactor));
+    }
+    if let Some(format) = cli.timestamp_format {
+        pipeline = pipeline.then(Stage::Timestamp {
+            format,
+            field: cli.timestamp_field.clone(),
+        });
+    }
+    for path in &cli.drop_field {
+        pipeline = pipeline.then(Stage::Drop(path.clone()));
+    }
+    for (from, to) in &cli.rename_field {
+        pipeline = pipeline.then(Stage::Rename {
+            from: from.clone(),
+            to: to.clone(),
+        });
+    }
+    for (path, value) in &cli.add_field {
+        pipeline = pipeline.then(Stage::Add {
+            path: path.clone(),
+            value: value.clone(),
+        });
+    }
+    if cli.flatten {
+        pipeline = pipeline.then(Stage::Flatten);
+    }
+    pipeline
+}
+
+/// Progress line for a search, when `enabled` and stderr can show it.
+///
+/// Searches whose time range can't be resolved get no progress line.
+fn search_progress(
+    time_range: &TimeRange,
+    limit: u64,
+    page_size: i32,
+    timezone: Timezone,
+    enabled: bool,
+) -> Option<ProgressLine> {
+    if !enabled {
+        return None;
+    }
+    let from = time::resolve_absolute(&time_range.from, timezone).ok()?;
+    let to = time::resolve_absolute(&time_range.to, timezone).ok()?;
+    ProgressLine::for_terminal(Progress::new(
+        from.timestamp_millis(),
+        to.timestamp_millis(),
+        page_size as u64,
+        limit,
+    ))
+}
+
+/// A query to record in the history once its command succeeds.
+struct Recording {
+    entry: HistoryEntry,
+    written_before: u64,
+}
+
+impl Recording {
+    /// Starts recording a query, or returns `None` if history is turned off.
+    ///
+    /// Records written before this point are not counted as results.
+    fn start(domain: &str, query: &str, from: &str, to: &str) -> Option<Self> {
+        if history::is_disabled() {
+            return None;
+        }
+        let entry = HistoryEntry {
+            timestamp: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            domain: domain.to_string(),
+            query: query.to_string(),
+            from: from.to_string(),
+            to: to.to_string(),
+            results: 0,
+            args: std::env::args_os()
+                .skip(1)
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect(),
+        };
+        Some(Self {
+            entry,
+            written_before: output::records_written(),
+        })
+    }
+
+    /// Appends the entry to the history file.
+    ///
+    /// Failing to record is reported on stderr but doesn't fail the command.
+    fn finish(mut self) {
+        self.entry.results = output::records_written() - self.written_before;
+        let Some(path) = history::history_path() else {
+            return;
+        };
+        if let Err(e) = history::append(&path, &self.entry) {
+            logging::warn(&format!("query not recorded in history: {}", e));
+        }
+    }
+}
+
+/// A command run to record in the local usage metrics once it finishes.
+struct Usage {
+    command: String,
+    started: Instant,
+}
+
+impl Usage {
+    /// Starts timing the command, or returns `None` unless usage recording
+    /// is turned on in the user configuration file.
+    fn start(matches: &ArgMatches) -> Option<Self> {
+        let enabled = config::load_user_config().is_ok_and(|c| telemetry::is_enabled(&c));
+        if !enabled {
+            return None;
+        }
+        let mut command = Vec::new();
+        let mut matches = matches;
+        while let Some((name, subcommand)) = matches.subcommand() {
+            command.push(name);
+            matches = subcommand;
+        }
+        Some(Self {
+            command: command.join(" "),
+            started: Instant::now(),
+        })
+    }
+
+    /// Appends the run to the telemetry file.
+    ///
+    /// Failing to record is reported on stderr but doesn't fail the command.
+    fn finish(self, error: Option<&AppError>) {
+        let Some(path) = telemetry::telemetry_path() else {
+            return;
+        };
+        let event = UsageEvent {
+            timestamp: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            command: self.command,
+            duration_ms: self.started.elapsed().as_millis() as u64,
+            error: error.map(|e| e.kind().to_string()),
+        };
+        if let Err(e) = telemetry::append(&path, &event) {
+            logging::warn(&format!("usage not recorded: {}", e));
+        }
+    }
+}
+
+/// Runs a `ddog query` tool, which needs no Datadog credentials.
+fn run_query_tool(
+    action: QueryAction,
+    workspace: Option<&Workspace>,
+    no_validate: bool,
+    writer: RecordWriter,
+    logger: VerboseLogger,
+) -> Result<(), AppError> {
+    match action {
+        QueryAction::Lint { query, domain } => {
+            let query = expand_saved_query(&query, domain, workspace)?;
+            commands::query::lint::run(&query, domain, writer, logger)
+        }
+        QueryAction::Save {
+            name,
+            query,
+            domain,
+            force,
+        } => {
+            if !no_validate {
+                commands::query::lint::preflight(&query, domain)?;
+            }
+            commands::query::save::run(&name, query, domain, force, writer, logger)
+        }
+    }
+}
+
+/// Reads `--batch` jobs and resolves each one's time range and query as a
+/// single logs search would.
+fn batch_searches(
+    path: &Path,
+    time_range: &TimeRange,
+    filters: &QueryFilters,
+    workspace: Option<&Workspace>,
+    timezone: Timezone,
+    no_validate: bool,
+    logger: &VerboseLogger,
+) -> Result<Vec<BatchSearch>, AppError> {
+    let jobs = ddog::batch::read_jobs(path)?;
+    logger.log(&format!("Read {} batch job(s)", jobs.len()));
+
+    jobs.into_iter()
+        .map(|job| {
+            let mut time_range = time_range.with_bounds(job.from.as_deref(), job.to.as_deref());
+            time_range.resolve(timezone)?;
+            let query =
+                resolve_search_query(&job.query, SearchDomain::Logs, filters, workspace, logger)?;
+            if !no_validate {
+                commands::query::lint::preflight(&query, SearchDomain::Logs)?;
+            }
+            Ok(BatchSearch {
+                id: job.id,
+                query,
+                time_range,
+            })
+        })
+        .collect()
+}
+
+/// Expands a `@name` reference from the workspace or the saved queries file.
+///
+/// The saved queries file is only read when the query is a reference.
+fn expand_saved_query(
+    query: &str,
+    domain: SearchDomain,
+    workspace: Option<&Workspace>,
+) -> Result<String, AppError> {
+    if saved_query_name(query).is_none() {
+        return Ok(query.to_string());
+    }
+    let saved = match saved_queries_path() {
+        Some(path) => SavedQueries::load(&path)?,
+        None => SavedQueries::default(),
+    };
+    saved_queries::expand_query(query, domain, workspace, &saved)
+}
+
+/// Expands saved query references and applies builder, repository, and workspace filters.
+///
+/// `--this-service` filters are applied before workspace defaults so the
+/// inferred service takes precedence over the workspace's default service.
+fn resolve_search_query(
+    query: &str,
+    domain: SearchDomain,
+    filters: &QueryFilters,
+    workspace: Option<&Workspace>,
+    logger: &VerboseLogger,
+) -> Result<String, AppError> {
+    let mut query = expand_saved_query(query, domain, workspace)?;
+    query = ddog::query::and_terms(&query, &filters.terms());
+
+    if filters.this_service {
+        let context = ServiceContext::detect(&std::env::current_dir()?);
+        logger.log(&format!(
+            "Inferred service: {} version: {}",
+            context.service.as_deref().unwrap_or("-"),
+            context.version.as_deref().unwrap_or("-")
+        ));
+        query = ddog::query::scope(&query, &context.filters()?);
+    }
+
+    if let Some(ws) = workspace {
+        query = ws.scope_query(&query);
     }
+    Ok(query)
 }
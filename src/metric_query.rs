@@ -0,0 +1,163 @@
+//! Metric query inspection.
+//!
+//! Extracts the `<aggregator>:<metric>{...}` terms from a Datadog metric query
+//! so aggregators can be checked before the query is sent. Datadog answers a
+//! percentile aggregator on a non-distribution metric with an empty result
+//! rather than an error, so catching it up front saves a confusing round trip.
+
+use crate::error::AppError;
+
+/// Space aggregators valid for every metric type.
+pub const SPACE_AGGREGATORS: [&str; 5] = ["avg", "sum", "min", "max", "count"];
+
+/// Metric type reported by Datadog for distribution metrics.
+const DISTRIBUTION: &str = "distribution";
+
+/// One `<aggregator>:<metric>` term of a metric query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetricTerm {
+    /// Aggregator as written (e.g., "avg", "p99")
+    pub aggregator: String,
+
+    /// Metric name (e.g., "trace.http.request.duration")
+    pub metric: String,
+}
+
+/// Extracts the `<aggregator>:<metric>{...}` terms of a metric query, in order.
+///
+/// Terms without an explicit aggregator are skipped, as are the contents of
+/// tag filters and function arguments.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::metric_query::parse_terms;
+///
+/// let terms = parse_terms("p99:trace.http.request.duration{env:prod} / avg:system.load.1{*}");
+/// assert_eq!(terms[0].aggregator, "p99");
+/// assert_eq!(terms[0].metric, "trace.http.request.duration");
+/// assert_eq!(terms[1].metric, "system.load.1");
+/// ```
+pub fn parse_terms(query: &str) -> Vec<MetricTerm> {
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '.';
+
+    query
+        .match_indices('{')
+        .filter_map(|(brace, _)| {
+            let before = &query[..brace];
+            let metric_start = before
+                .rfind(|c: char| !is_name_char(c))
+                .map_or(0, |i| i + 1);
+            let metric = &before[metric_start..];
+            let before = before[..metric_start].strip_suffix(':')?;
+            let aggregator_start = before
+                .rfind(|c: char| !is_name_char(c))
+                .map_or(0, |i| i + 1);
+            let aggregator = &before[aggregator_start..];
+
+            (!metric.is_empty() && !aggregator.is_empty()).then(|| MetricTerm {
+                aggregator: aggregator.to_string(),
+                metric: metric.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Checks whether an aggregator is a percentile such as `p99` or `p99.9`.
+pub fn is_percentile(aggregator: &str) -> bool {
+    aggregator
+        .strip_prefix('p')
+        .and_then(|n| n.parse::<f64>().ok())
+        .is_some_and(|n| n > 0.0 && n <= 100.0)
+}
+
+/// Checks that a term's aggregator is legal for its metric type.
+///
+/// `metric_type` is the type from the metric's metadata (e.g., "gauge",
+/// "distribution"); when it's unknown only the aggregator itself is checked.
+pub fn check_aggregator(term: &MetricTerm, metric_type: Option<&str>) -> Result<(), AppError> {
+    let aggregator = term.aggregator.to_ascii_lowercase();
+    if SPACE_AGGREGATORS.contains(&aggregator.as_str()) {
+        return Ok(());
+    }
+
+    if !is_percentile(&aggregator) {
+        return Err(AppError::InvalidQuery(format!(
+            "Unknown aggregator '{}' in '{}:{}'. Use one of {}, or a percentile (p50, p75, p90, p95, p99) for distribution metrics",
+            term.aggregator,
+            term.aggregator,
+            term.metric,
+            SPACE_AGGREGATORS.join(", ")
+        )));
+    }
+
+    match metric_type {
+        Some(kind) if kind != DISTRIBUTION => Err(AppError::InvalidQuery(format!(
+            "Percentile aggregator '{}' requires a distribution metric, but {} is a {} metric. Use one of {} instead",
+            term.aggregator,
+            term.metric,
+            kind,
+            SPACE_AGGREGATORS.join(", ")
+        ))),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn term(aggregator: &str, metric: &str) -> MetricTerm {
+        MetricTerm {
+            aggregator: aggregator.to_string(),
+            metric: metric.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_terms() {
+        assert_eq!(
+            parse_terms("avg:system.cpu.user{env:prod,service:web},max:system.mem.used{*}"),
+            vec![
+                term("avg", "system.cpu.user"),
+                term("max", "system.mem.used")
+            ]
+        );
+        assert_eq!(
+            parse_terms("abs(p99.9:trace.http.request.duration{*}.rollup(avg, 60))"),
+            vec![term("p99.9", "trace.http.request.duration")]
+        );
+    }
+
+    #[test]
+    fn test_parse_terms_without_aggregator() {
+        assert!(parse_terms("system.cpu.user{*}").is_empty());
+        assert!(parse_terms("").is_empty());
+    }
+
+    #[test]
+    fn test_is_percentile() {
+        assert!(is_percentile("p50"));
+        assert!(is_percentile("p99.9"));
+        assert!(is_percentile("p100"));
+        assert!(!is_percentile("p0"));
+        assert!(!is_percentile("p101"));
+        assert!(!is_percentile("pct"));
+        assert!(!is_percentile("avg"));
+    }
+
+    #[test]
+    fn test_check_aggregator() {
+        assert!(check_aggregator(&term("avg", "x"), Some("gauge")).is_ok());
+        assert!(check_aggregator(&term("SUM", "x"), None).is_ok());
+        assert!(check_aggregator(&term("p99", "x"), Some("distribution")).is_ok());
+        assert!(check_aggregator(&term("p99", "x"), None).is_ok());
+
+        let err = check_aggregator(&term("p99", "system.load.1"), Some("gauge")).unwrap_err();
+        assert!(matches!(err, AppError::InvalidQuery(_)));
+        assert!(err.to_string().contains("system.load.1 is a gauge metric"));
+
+        let err = check_aggregator(&term("median", "x"), None).unwrap_err();
+        assert!(err.to_string().contains("Unknown aggregator 'median'"));
+    }
+}
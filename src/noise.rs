@@ -0,0 +1,154 @@
+//! Alert-noise statistics across monitors.
+//!
+//! Summarizes each monitor's [`Episode`]s into alert counts, time-to-recover
+//! figures, and a flappiness score, then ranks monitors noisiest first for
+//! alert-hygiene reviews.
+
+use serde::Serialize;
+
+use crate::episodes::Episode;
+
+/// Episodes recovering within this many seconds count as flaps.
+pub const FLAP_SECONDS: i64 = 300;
+
+/// Alert-noise summary for one monitor.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MonitorNoise {
+    /// Position in the noisiest-first ranking, starting at 1
+    pub rank: usize,
+
+    /// ID of the monitor
+    pub monitor_id: i64,
+
+    /// Monitor name
+    pub name: String,
+
+    /// Number of alert episodes in the range
+    pub alerts: usize,
+
+    /// Episodes still open at the end of the range
+    pub open: usize,
+
+    /// Mean seconds to recover, over recovered episodes
+    pub mean_ttr_seconds: Option<f64>,
+
+    /// Median seconds to recover, over recovered episodes
+    pub median_ttr_seconds: Option<f64>,
+
+    /// Episodes that recovered within [`FLAP_SECONDS`]
+    pub flaps: usize,
+
+    /// Share of episodes that were flaps, from 0.0 to 1.0
+    pub flappiness: f64,
+}
+
+impl MonitorNoise {
+    /// Summarizes a monitor's episodes; the rank is assigned by [`rank`].
+    pub fn new(monitor_id: i64, name: &str, episodes: &[Episode]) -> Self {
+        let mut durations: Vec<i64> = episodes.iter().filter_map(|e| e.duration_seconds).collect();
+        durations.sort_unstable();
+
+        let flaps = durations.iter().filter(|d| **d < FLAP_SECONDS).count();
+        let mean = (!durations.is_empty())
+            .then(|| durations.iter().sum::<i64>() as f64 / durations.len() as f64);
+
+        Self {
+            rank: 0,
+            monitor_id,
+            name: name.to_string(),
+            alerts: episodes.len(),
+            open: episodes.len() - durations.len(),
+            mean_ttr_seconds: mean,
+            median_ttr_seconds: median(&durations),
+            flaps,
+            flappiness: if episodes.is_empty() {
+                0.0
+            } else {
+                flaps as f64 / episodes.len() as f64
+            },
+        }
+    }
+}
+
+/// Ranks monitors noisiest first: by alert count, then flappiness.
+pub fn rank(mut monitors: Vec<MonitorNoise>) -> Vec<MonitorNoise> {
+    monitors.sort_by(|a, b| {
+        b.alerts
+            .cmp(&a.alerts)
+            .then(b.flappiness.total_cmp(&a.flappiness))
+            .then(a.monitor_id.cmp(&b.monitor_id))
+    });
+    for (index, monitor) in monitors.iter_mut().enumerate() {
+        monitor.rank = index + 1;
+    }
+    monitors
+}
+
+/// Median of sorted values.
+fn median(sorted: &[i64]) -> Option<f64> {
+    let mid = sorted.len() / 2;
+    match sorted.len() {
+        0 => None,
+        n if n % 2 == 0 => Some((sorted[mid - 1] + sorted[mid]) as f64 / 2.0),
+        _ => Some(sorted[mid] as f64),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn episode(duration_seconds: Option<i64>) -> Episode {
+        Episode {
+            monitor_id: 1,
+            group: None,
+            start: "2024-01-15T10:00:00.000Z".to_string(),
+            end: None,
+            duration_seconds,
+            severity: "alert",
+            transitions: 1,
+            title: "[Triggered] CPU".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_monitor_noise() {
+        let noise = MonitorNoise::new(
+            1,
+            "CPU",
+            &[
+                episode(Some(60)),
+                episode(Some(600)),
+                episode(Some(1200)),
+                episode(Some(120)),
+                episode(None),
+            ],
+        );
+        assert_eq!(noise.alerts, 5);
+        assert_eq!(noise.open, 1);
+        assert_eq!(noise.mean_ttr_seconds, Some(495.0));
+        assert_eq!(noise.median_ttr_seconds, Some(360.0));
+        assert_eq!(noise.flaps, 2);
+        assert_eq!(noise.flappiness, 0.4);
+    }
+
+    #[test]
+    fn test_monitor_noise_quiet() {
+        let noise = MonitorNoise::new(1, "CPU", &[]);
+        assert_eq!(noise.alerts, 0);
+        assert_eq!(noise.mean_ttr_seconds, None);
+        assert_eq!(noise.median_ttr_seconds, None);
+        assert_eq!(noise.flappiness, 0.0);
+    }
+
+    #[test]
+    fn test_rank() {
+        let ranked = rank(vec![
+            MonitorNoise::new(1, "quiet", &[episode(Some(600))]),
+            MonitorNoise::new(2, "steady", &[episode(Some(600)), episode(Some(600))]),
+            MonitorNoise::new(3, "flappy", &[episode(Some(10)), episode(Some(10))]),
+        ]);
+        let order: Vec<(usize, &str)> = ranked.iter().map(|m| (m.rank, m.name.as_str())).collect();
+        assert_eq!(order, vec![(1, "flappy"), (2, "steady"), (3, "quiet")]);
+    }
+}
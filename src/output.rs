@@ -1,24 +1,359 @@
-//! NDJSON (Newline Delimited JSON) output writer.
+//! Record output writers.
 //!
-//! Provides streaming output of JSON records, one per line, suitable for
-//! piping to tools like `jq` or processing line-by-line.
+//! Records are written as NDJSON (Newline Delimited JSON) by default, one per
+//! line, suitable for piping to tools like `jq` or processing line-by-line.
+//! The `text` format renders one human-readable line per record instead, with
+//! trace IDs and hosts as clickable terminal hyperlinks when stdout is a TTY
+//! and, optionally, timestamps shown in a chosen timezone.
 
+use chrono::{DateTime, SecondsFormat, TimeZone, Utc};
+use clap::ValueEnum;
+use ddog::error::AppError;
+use ddog::links::{DatadogLinks, hyperlink};
+use ddog::progress::Progress;
+use ddog::sink::{BatchEncoding, HttpSink};
+use ddog::splunk::{self, HecOptions};
+use ddog::time::{Timezone, resolve_absolute};
+use ddog::trace::{Dialect, TraceDiagram};
+use ddog::transform::Pipeline;
+use ddog::upload::{Credentials, ObjectUrl, UploadWriter};
 use serde::Serialize;
-use std::io::{self, BufWriter, Stdout, Write};
+use serde_json::Value;
+use std::io::{self, BufWriter, IsTerminal, Stdout, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
 
-/// Writes JSON records as newline-delimited JSON (NDJSON) to stdout.
+use crate::logging;
+
+/// Records written by this process, for the query history's result count.
+static RECORDS_WRITTEN: AtomicU64 = AtomicU64::new(0);
+
+/// Counts a record written outside a [`RecordWriter`], such as to a routed file.
+pub fn count_record() {
+    RECORDS_WRITTEN.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Returns the number of records written so far, not counting
+/// `--emit-meta` records.
+pub fn records_written() -> u64 {
+    RECORDS_WRITTEN.load(Ordering::Relaxed)
+}
+
+/// Transforms applied to every record written (`--redact`, `--redact-pii`,
+/// `--drop-field`, `--rename-field`, `--add-field`).
+static PIPELINE: OnceLock<Pipeline> = OnceLock::new();
+
+/// Runs every record written from now on through `pipeline`.
+pub fn set_pipeline(pipeline: Pipeline) {
+    if !pipeline.is_empty() {
+        let _ = PIPELINE.set(pipeline);
+    }
+}
+
+/// The record with the output transforms applied, or `None` when there are
+/// none.
+///
+/// Writers to destinations other than a [`RecordWriter`], such as routed and
+/// exported files, write this instead of the record when there is one.
+pub fn transform<T: Serialize>(record: &T) -> io::Result<Option<Value>> {
+    let Some(pipeline) = PIPELINE.get() else {
+        return Ok(None);
+    };
+    let mut value = serde_json::to_value(record)?;
+    pipeline.apply(&mut value);
+    Ok(Some(value))
+}
+
+/// Outcome of the `--output` upload or `--sink` posts, set when the writer
+/// is dropped: a summary for stderr, or why delivery failed.
+static SINK_RESULT: Mutex<Option<Result<String, AppError>>> = Mutex::new(None);
+
+fn set_sink_result(result: Result<String, AppError>) {
+    *SINK_RESULT.lock().unwrap_or_else(|e| e.into_inner()) = Some(result);
+}
+
+/// Settles a command's result with the outcome of its `--output` upload or
+/// `--sink` posts, if any: failed delivery fails the command with the
+/// storage or endpoint error, and completed delivery is reported on stderr.
+pub fn finish_sink(result: Result<(), AppError>) -> Result<(), AppError> {
+    let sink = SINK_RESULT.lock().unwrap_or_else(|e| e.into_inner()).take();
+    match sink {
+        Some(Err(e)) => Err(e),
+        Some(Ok(summary)) => {
+            if result.is_ok() {
+                logging::notice(&summary);
+            }
+            result
+        }
+        None => result,
+    }
+}
+
+/// Uploads records to object storage, completing the upload when dropped.
+pub struct UploadSink(UploadWriter);
+
+impl Write for UploadSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl Drop for UploadSink {
+    fn drop(&mut self) {
+        set_sink_result(self.0.finish().map(|url| format!("Uploaded {}", url)));
+    }
+}
+
+/// Posts records to an HTTP endpoint, sending the last batch when dropped.
+pub struct PostSink {
+    sink: HttpSink,
+    url: String,
+}
+
+impl Drop for PostSink {
+    fn drop(&mut self) {
+        set_sink_result(self.sink.finish().map(|summary| {
+            format!(
+                "Posted {} records in {} batches to {}",
+                summary.records, summary.batches, self.url
+            )
+        }));
+    }
+}
+
+/// Format of records written to stdout.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// One JSON object per line
+    #[default]
+    Ndjson,
+
+    /// One human-readable line per record
+    Text,
+
+    /// Mermaid sequence diagram (`spans trace` only)
+    MermaidSequence,
+
+    /// PlantUML sequence diagram (`spans trace` only)
+    PlantumlSequence,
+
+    /// Gantt-style waterfall of every span (`spans trace` only)
+    Waterfall,
+
+    /// Folded stacks for flamegraph tools (`spans trace` only)
+    Folded,
+
+    /// Graphviz graph of service dependencies (`spans deps` only)
+    Dot,
+
+    /// One JSON object per series with all of its points (`metrics query` only)
+    Wide,
+
+    /// Grafana Loki push payloads sent to --push (`logs search` only)
+    Loki,
+
+    /// One Splunk HTTP Event Collector envelope per line
+    SplunkHec,
+}
+
+impl OutputFormat {
+    /// The diagram dialect, for formats that render a diagram instead of records.
+    pub fn diagram(self) -> Option<TraceDiagram> {
+        match self {
+            OutputFormat::MermaidSequence => Some(TraceDiagram::Sequence(Dialect::Mermaid)),
+            OutputFormat::PlantumlSequence => Some(TraceDiagram::Sequence(Dialect::PlantUml)),
+            OutputFormat::Waterfall => Some(TraceDiagram::Waterfall),
+            OutputFormat::Folded => Some(TraceDiagram::Folded),
+            OutputFormat::Ndjson
+            | OutputFormat::Text
+            | OutputFormat::Dot
+            | OutputFormat::Wide
+            | OutputFormat::Loki
+            | OutputFormat::SplunkHec => None,
+        }
+    }
+}
+
+/// Writes records to stdout in the selected [`OutputFormat`].
+pub enum RecordWriter {
+    Ndjson(NdjsonWriter),
+    Text(TextWriter),
+    Upload(NdjsonWriter<UploadSink>),
+    Post(PostSink),
+    SplunkHec(NdjsonWriter, HecOptions),
+}
+
+impl RecordWriter {
+    /// Creates a writer to stdout.
+    ///
+    /// Text output links to the Datadog UI for `site`, but only when stdout
+    /// is a terminal so that piped output stays free of escape sequences.
+    /// When `timezone` is set, text output shows timestamps in that zone.
+    ///
+    /// Diagram formats write no records themselves, so they fall back to NDJSON,
+    /// as does the wide format, which only changes the shape of the records,
+    /// and the Loki format, whose records are sent with [`post`](Self::post).
+    pub fn new(format: OutputFormat, site: &str, timezone: Option<Timezone>) -> Self {
+        match format {
+            OutputFormat::Ndjson
+            | OutputFormat::Wide
+            | OutputFormat::Loki
+            | OutputFormat::MermaidSequence
+            | OutputFormat::PlantumlSequence
+            | OutputFormat::Waterfall
+            | OutputFormat::Folded
+            | OutputFormat::Dot => Self::Ndjson(NdjsonWriter::new()),
+            OutputFormat::Text => Self::Text(TextWriter::new(
+                io::stdout(),
+                TextOptions::stdout(site, timezone),
+            )),
+            OutputFormat::SplunkHec => Self::splunk_hec(HecOptions::default()),
+        }
+    }
+
+    /// Creates a writer of Splunk HEC envelopes to stdout.
+    pub fn splunk_hec(options: HecOptions) -> Self {
+        Self::SplunkHec(NdjsonWriter::new(), options)
+    }
+
+    /// Writes a single record, transformed if requested, and flushes it.
+    pub fn write<T: Serialize>(&mut self, record: &T) -> io::Result<()> {
+        count_record();
+        self.write_meta(record)
+    }
+
+    /// Writes a record that isn't a result, such as a leading `--emit-meta`
+    /// record, like [`write`](Self::write) without counting it.
+    pub fn write_meta<T: Serialize>(&mut self, record: &T) -> io::Result<()> {
+        match transform(record)? {
+            Some(transformed) => self.emit(&transformed),
+            None => self.emit(record),
+        }
+    }
+
+    fn emit<T: Serialize>(&mut self, record: &T) -> io::Result<()> {
+        match self {
+            Self::Ndjson(writer) => writer.write(record),
+            Self::Text(writer) => writer.write(record),
+            Self::Upload(writer) => writer.write(record),
+            Self::Post(writer) => writer
+                .sink
+                .push(record)
+                .map_err(|e| io::Error::other(e.to_string())),
+            Self::SplunkHec(writer, options) => {
+                writer.write(&splunk::envelope(&serde_json::to_value(record)?, options))
+            }
+        }
+    }
+
+    /// Creates a writer uploading NDJSON to object storage (`--output`).
+    ///
+    /// A prefix URL gets an object named after the current time.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Config` if the storage credentials are missing.
+    pub fn upload(url: &ObjectUrl) -> Result<Self, AppError> {
+        let credentials = Credentials::from_env(url.provider)?;
+        let name = format!("ddog-{}.ndjson.gz", Utc::now().format("%Y%m%dT%H%M%SZ"));
+        let writer = UploadWriter::new(url.object(&name), credentials);
+        Ok(Self::Upload(NdjsonWriter::with_writer(UploadSink(writer))))
+    }
+
+    /// Creates a writer POSTing batches of `batch_size` records to `url`,
+    /// as JSON arrays for `--sink` or Loki push payloads for `--push`.
+    pub fn post(url: &str, batch_size: usize, encoding: BatchEncoding) -> Self {
+        Self::Post(PostSink {
+            sink: HttpSink::new(url, batch_size).with_encoding(encoding),
+            url: url.to_string(),
+        })
+    }
+}
+
+/// Leading record describing exactly what an export queried (`--emit-meta`).
+#[derive(Debug, Serialize)]
+pub struct MetaRecord {
+    /// Always `meta`, to tell this record apart from results
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+
+    /// Queried domain: logs, spans, metrics, or monitors
+    pub domain: &'static str,
+
+    /// Final query string sent to Datadog
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+
+    /// Absolute start of the window (RFC3339, UTC)
+    pub from: String,
+
+    /// Absolute end of the window (RFC3339, UTC)
+    pub to: String,
+
+    /// Log indexes searched
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub indexes: Option<Vec<String>>,
+}
+
+impl MetaRecord {
+    /// Builds a meta record, resolving `from` and `to` to absolute UTC times.
+    ///
+    /// Relative bounds such as `now-1h` are resolved against the local clock,
+    /// which matches what Datadog resolves them to up to clock skew.
+    pub fn new(
+        domain: &'static str,
+        query: Option<&str>,
+        from: &str,
+        to: &str,
+        timezone: Timezone,
+    ) -> Result<Self, AppError> {
+        let absolute = |time: &str| -> Result<String, AppError> {
+            Ok(resolve_absolute(time, timezone)?
+                .with_timezone(&Utc)
+                .to_rfc3339_opts(SecondsFormat::Millis, true))
+        };
+        Ok(Self {
+            kind: "meta",
+            domain,
+            query: query.map(String::from),
+            from: absolute(from)?,
+            to: absolute(to)?,
+            indexes: None,
+        })
+    }
+
+    /// Records the log indexes searched.
+    pub fn with_indexes(mut self, indexes: &[String]) -> Self {
+        self.indexes = Some(indexes.to_vec());
+        self
+    }
+}
+
+/// Writes JSON records as newline-delimited JSON (NDJSON), to stdout by default.
 ///
 /// Each record is serialized as compact JSON followed by a newline.
 /// Output is flushed after each record for real-time streaming.
-pub struct NdjsonWriter {
-    writer: BufWriter<Stdout>,
+pub struct NdjsonWriter<W: Write = Stdout> {
+    writer: BufWriter<W>,
 }
 
 impl NdjsonWriter {
     /// Creates a new NDJSON writer to stdout.
     pub fn new() -> Self {
+        Self::with_writer(io::stdout())
+    }
+}
+
+impl<W: Write> NdjsonWriter<W> {
+    /// Creates a new NDJSON writer to an arbitrary destination, such as a file.
+    pub fn with_writer(writer: W) -> Self {
         Self {
-            writer: BufWriter::new(io::stdout()),
+            writer: BufWriter::new(writer),
         }
     }
 
@@ -38,6 +373,211 @@ impl Default for NdjsonWriter {
     }
 }
 
+/// Writes records as one human-readable line each.
+pub struct TextWriter<W: Write = Stdout> {
+    writer: BufWriter<W>,
+    options: TextOptions,
+}
+
+/// Presentation options for text output.
+#[derive(Debug, Clone, Default)]
+pub struct TextOptions {
+    /// Datadog UI links for trace IDs and hosts; `None` disables hyperlinks
+    pub links: Option<DatadogLinks>,
+
+    /// Timezone to show timestamps in; `None` keeps them as returned
+    pub timezone: Option<Timezone>,
+}
+
+impl TextOptions {
+    /// Options for text written to stdout: links to the Datadog UI for
+    /// `site` only when stdout is a terminal, and timestamps in `timezone`.
+    pub fn stdout(site: &str, timezone: Option<Timezone>) -> Self {
+        Self {
+            links: io::stdout().is_terminal().then(|| DatadogLinks::new(site)),
+            timezone,
+        }
+    }
+}
+
+impl<W: Write> TextWriter<W> {
+    /// Creates a text writer with the given presentation options.
+    pub fn new(writer: W, options: TextOptions) -> Self {
+        Self {
+            writer: BufWriter::new(writer),
+            options,
+        }
+    }
+
+    /// Writes a single record as a line of text.
+    ///
+    /// The output is flushed immediately to support real-time streaming.
+    pub fn write<T: Serialize>(&mut self, record: &T) -> io::Result<()> {
+        let value = serde_json::to_value(record)?;
+        writeln!(self.writer, "{}", render_line(&value, &self.options))?;
+        self.writer.flush()
+    }
+}
+
+/// Renders a log, span, or metric record as a single line.
+///
+/// Records of unknown shape fall back to compact JSON.
+pub fn render_line(value: &Value, options: &TextOptions) -> String {
+    let links = options.links.as_ref();
+    let field = |pointer: &str| value.pointer(pointer).and_then(Value::as_str);
+    let timestamp = |raw: &str| match options.timezone {
+        Some(tz) => DateTime::parse_from_rfc3339(raw)
+            .map(|t| format_timestamp(&t, tz))
+            .unwrap_or_else(|_| raw.to_string()),
+        None => raw.to_string(),
+    };
+    let host = |name: &str| match links {
+        Some(links) => hyperlink(name, &links.host(name)),
+        None => name.to_string(),
+    };
+
+    if let Some(trace_id) = field("/attributes/trace_id") {
+        let trace = match links {
+            Some(links) => hyperlink(trace_id, &links.trace(trace_id)),
+            None => trace_id.to_string(),
+        };
+        return join_present([
+            field("/attributes/start_timestamp").map(timestamp),
+            field("/attributes/service").map(String::from),
+            field("/attributes/resource_name").map(String::from),
+            Some(format!("trace:{}", trace)),
+            field("/attributes/host").map(host),
+        ]);
+    }
+
+    if value.get("type").and_then(Value::as_str) == Some("log") {
+        return join_present([
+            field("/attributes/timestamp").map(timestamp),
+            field("/attributes/status").map(|s| s.to_uppercase()),
+            field("/attributes/service").map(String::from),
+            field("/attributes/host").map(host),
+            field("/attributes/message").map(String::from),
+        ]);
+    }
+
+    if let Some(reduce) = field("/reduce") {
+        return join_present([
+            field("/status").map(|s| s.to_uppercase()),
+            field("/query").map(|q| format!("{}  {}", reduce, q)),
+            field("/scope").map(String::from),
+            value.get("value").map(Value::to_string),
+        ]);
+    }
+
+    if let Some(metric) = field("/metric") {
+        return join_present([
+            value.get("ts").or(value.get("timestamp")).map(|ts| {
+                let seconds = ts.as_i64().and_then(|s| Utc.timestamp_opt(s, 0).single());
+                match (options.timezone, seconds) {
+                    (Some(tz), Some(t)) => format_timestamp(&t, tz),
+                    _ => ts.to_string(),
+                }
+            }),
+            Some(metric.to_string()),
+            field("/scope").map(String::from),
+            value.get("value").map(Value::to_string),
+        ]);
+    }
+
+    if let Some(flappiness) = value.get("flappiness").and_then(Value::as_f64) {
+        let name = field("/name").unwrap_or_default();
+        let name = match (links, value.get("monitor_id").and_then(Value::as_i64)) {
+            (Some(links), Some(id)) => hyperlink(name, &links.monitor(id)),
+            _ => name.to_string(),
+        };
+        return join_present([
+            value.get("rank").map(|rank| format!("#{}", rank)),
+            value
+                .get("alerts")
+                .map(|alerts| format!("{} alerts", alerts)),
+            value
+                .get("median_ttr_seconds")
+                .and_then(Value::as_f64)
+                .map(|ttr| format!("median ttr {:.0}s", ttr)),
+            Some(format!("flappiness {:.2}", flappiness)),
+            Some(name),
+        ]);
+    }
+
+    if let (Some(rank), Some(name), Some(count)) = (
+        value.get("rank"),
+        field("/value"),
+        value.get("count").and_then(Value::as_f64),
+    ) {
+        return join_present([
+            Some(format!("#{}", rank)),
+            Some(name.to_string()),
+            Some(count.to_string()),
+            value
+                .get("share")
+                .and_then(Value::as_f64)
+                .map(|share| format!("{:.1}%", share * 100.0)),
+        ]);
+    }
+
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Progress line for a long search, redrawn in place on stderr.
+///
+/// The line is erased when the search finishes or fails, so it never mixes
+/// with later messages.
+pub struct ProgressLine {
+    progress: Progress,
+    drawn: bool,
+}
+
+impl ProgressLine {
+    /// Returns a progress line when it can be shown: stderr is a terminal
+    /// and stdout isn't (records on the same terminal would break the line).
+    pub fn for_terminal(progress: Progress) -> Option<Self> {
+        (io::stderr().is_terminal() && !io::stdout().is_terminal()).then_some(Self {
+            progress,
+            drawn: false,
+        })
+    }
+
+    /// Counts an emitted record and redraws the line if it is due.
+    pub fn record(&mut self, timestamp: Option<DateTime<Utc>>) {
+        self.progress
+            .record(timestamp.map(|t| t.timestamp_millis()));
+        let now = Instant::now();
+        if self.progress.should_draw(now) {
+            eprint!("\r\x1b[K{}", self.progress.line(now));
+            self.drawn = true;
+        }
+    }
+}
+
+impl Drop for ProgressLine {
+    fn drop(&mut self) {
+        if self.drawn {
+            eprint!("\r\x1b[K");
+        }
+    }
+}
+
+/// Formats a timestamp in `timezone` for human readers.
+fn format_timestamp<Z: TimeZone>(time: &DateTime<Z>, timezone: Timezone) -> String {
+    timezone
+        .convert(time)
+        .format("%Y-%m-%d %H:%M:%S%.3f %:z")
+        .to_string()
+}
+
+/// Joins the fields that are present with two spaces.
+fn join_present<const N: usize>(fields: [Option<String>; N]) -> String {
+    fields.into_iter().flatten().collect::<Vec<_>>().join("  ")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -102,6 +642,162 @@ mod tests {
         drop(writer);
     }
 
+    #[test]
+    fn test_ndjson_writer_with_writer() {
+        let mut buffer = Vec::new();
+        {
+            let mut writer = NdjsonWriter::with_writer(&mut buffer);
+            writer
+                .write(&TestRecord {
+                    id: 7,
+                    name: "file".to_string(),
+                })
+                .unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "{\"id\":7,\"name\":\"file\"}\n"
+        );
+    }
+
+    #[test]
+    fn test_render_log_line() {
+        let log = serde_json::json!({
+            "id": "AAA",
+            "type": "log",
+            "attributes": {
+                "timestamp": "2024-01-15T10:00:00Z",
+                "status": "error",
+                "service": "api",
+                "host": "web-1",
+                "message": "boom"
+            }
+        });
+        assert_eq!(
+            render_line(&log, &TextOptions::default()),
+            "2024-01-15T10:00:00Z  ERROR  api  web-1  boom"
+        );
+
+        let local = TextOptions {
+            timezone: Some("Europe/Paris".parse().unwrap()),
+            ..TextOptions::default()
+        };
+        assert_eq!(
+            render_line(&log, &local),
+            "2024-01-15 11:00:00.000 +01:00  ERROR  api  web-1  boom"
+        );
+    }
+
+    #[test]
+    fn test_render_span_line_with_links() {
+        let span = serde_json::json!({
+            "type": "spans",
+            "attributes": {"service": "web", "resource_name": "GET /", "trace_id": "123"}
+        });
+        let linked = TextOptions {
+            links: Some(DatadogLinks::new("datadoghq.com")),
+            ..TextOptions::default()
+        };
+        let line = render_line(&span, &linked);
+        assert!(
+            line.starts_with("web  GET /  trace:\x1b]8;;https://app.datadoghq.com/apm/trace/123")
+        );
+        assert_eq!(
+            render_line(&span, &TextOptions::default()),
+            "web  GET /  trace:123"
+        );
+    }
+
+    #[test]
+    fn test_render_noise_line() {
+        let noise = serde_json::json!({
+            "rank": 1, "monitor_id": 42, "name": "High CPU", "alerts": 14,
+            "median_ttr_seconds": 180.0, "flappiness": 0.64
+        });
+        assert_eq!(
+            render_line(&noise, &TextOptions::default()),
+            "#1  14 alerts  median ttr 180s  flappiness 0.64  High CPU"
+        );
+    }
+
+    #[test]
+    fn test_render_ranked_value_line() {
+        let ranked = serde_json::json!({
+            "rank": 2, "facet": "service", "value": "api", "count": 1234.0, "share": 0.6125
+        });
+        assert_eq!(
+            render_line(&ranked, &TextOptions::default()),
+            "#2  api  1234  61.3%"
+        );
+    }
+
+    #[test]
+    fn test_render_metric_and_fallback_lines() {
+        let point = serde_json::json!({
+            "metric": "system.cpu.user", "scope": "host:a", "ts": 1, "value": 0.5
+        });
+        let plain = TextOptions::default();
+        assert_eq!(
+            render_line(&point, &plain),
+            "1  system.cpu.user  host:a  0.5"
+        );
+        assert_eq!(
+            render_line(&serde_json::json!({"metric": "system.load.1"}), &plain),
+            "system.load.1"
+        );
+        let scalar = serde_json::json!({
+            "query": "avg:system.load.1{*}", "reduce": "avg", "scope": "*",
+            "value": 2.5, "warn": 2.0, "status": "warning"
+        });
+        assert_eq!(
+            render_line(&scalar, &plain),
+            "WARNING  avg  avg:system.load.1{*}  *  2.5"
+        );
+        assert_eq!(
+            render_line(&serde_json::json!({"a": 1}), &plain),
+            "{\"a\":1}"
+        );
+
+        let utc = TextOptions {
+            timezone: Some("UTC".parse().unwrap()),
+            ..TextOptions::default()
+        };
+        assert!(render_line(&point, &utc).starts_with("1970-01-01 00:00:01.000 +00:00  "));
+    }
+
+    #[test]
+    fn test_meta_record() {
+        let utc = Timezone::Named(chrono_tz::UTC);
+        let meta = MetaRecord::new(
+            "logs",
+            Some("status:error"),
+            "2024-01-15T10:00:00+02:00",
+            "1705315200000",
+            utc,
+        )
+        .unwrap()
+        .with_indexes(&["main".to_string()]);
+        assert_eq!(
+            serde_json::to_string(&meta).unwrap(),
+            r#"{"type":"meta","domain":"logs","query":"status:error","from":"2024-01-15T08:00:00.000Z","to":"2024-01-15T10:40:00.000Z","indexes":["main"]}"#
+        );
+
+        let meta = MetaRecord::new("metrics", None, "now-1h", "now", utc).unwrap();
+        let json = serde_json::to_value(&meta).unwrap();
+        assert!(json.get("query").is_none());
+        assert!(json.get("indexes").is_none());
+    }
+
+    #[test]
+    fn test_text_writer() {
+        let mut buffer = Vec::new();
+        {
+            let mut writer = TextWriter::new(&mut buffer, TextOptions::default());
+            writer.write(&"plain").unwrap();
+        }
+        assert_eq!(String::from_utf8(buffer).unwrap(), "plain\n");
+    }
+
     #[test]
     fn test_ndjson_writer_new() {
         let writer = NdjsonWriter::new();
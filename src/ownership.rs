@@ -0,0 +1,163 @@
+//! Service-to-team ownership mappings.
+//!
+//! Ownership can come from a local TOML file or from the Datadog Service
+//! Catalog. A local file can also carry a webhook URL per team:
+//!
+//! ```toml
+//! [teams.payments]
+//! services = ["checkout", "billing-*"]
+//! webhook = "https://hooks.slack.com/services/T000/B000/XXXX"
+//!
+//! [teams.platform]
+//! services = ["gateway"]
+//! ```
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::error::AppError;
+
+/// Team name used for records whose service has no known owner.
+pub const UNOWNED_TEAM: &str = "unowned";
+
+/// Ownership settings for a single team.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TeamOwnership {
+    /// Services owned by the team. A trailing `*` matches any suffix.
+    #[serde(default)]
+    pub services: Vec<String>,
+
+    /// Webhook that receives the team's result summary
+    pub webhook: Option<String>,
+}
+
+/// Mapping from services to owning teams.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OwnershipMap {
+    /// Teams keyed by team name
+    #[serde(default)]
+    pub teams: BTreeMap<String, TeamOwnership>,
+}
+
+impl OwnershipMap {
+    /// Loads an ownership mapping from a TOML file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Config` if the file cannot be read or parsed.
+    pub fn load(path: &Path) -> Result<Self, AppError> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| AppError::Config(format!("Failed to read {}: {}", path.display(), e)))?;
+        toml::from_str(&contents)
+            .map_err(|e| AppError::Config(format!("Invalid {}: {}", path.display(), e)))
+    }
+
+    /// Builds a mapping from `(service, team)` pairs, e.g. from the Service Catalog.
+    pub fn from_pairs<I, S, T>(pairs: I) -> Self
+    where
+        I: IntoIterator<Item = (S, T)>,
+        S: Into<String>,
+        T: Into<String>,
+    {
+        let mut map = Self::default();
+        for (service, team) in pairs {
+            map.teams
+                .entry(team.into())
+                .or_default()
+                .services
+                .push(service.into());
+        }
+        map
+    }
+
+    /// Returns the team owning `service`.
+    ///
+    /// Exact matches win over `*` patterns; among patterns the longest prefix wins.
+    pub fn owner_of(&self, service: &str) -> Option<&str> {
+        let mut best: Option<(&str, usize)> = None;
+
+        for (team, ownership) in &self.teams {
+            for pattern in &ownership.services {
+                let score = match pattern.strip_suffix('*') {
+                    Some(prefix) if service.starts_with(prefix) => prefix.len(),
+                    Some(_) => continue,
+                    None if pattern == service => usize::MAX,
+                    None => continue,
+                };
+                if best.is_none_or(|(_, s)| score > s) {
+                    best = Some((team, score));
+                }
+            }
+        }
+
+        best.map(|(team, _)| team)
+    }
+
+    /// Returns the webhook configured for `team`, if any.
+    pub fn webhook(&self, team: &str) -> Option<&str> {
+        self.teams.get(team)?.webhook.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map() -> OwnershipMap {
+        toml::from_str(
+            r#"
+            [teams.payments]
+            services = ["checkout", "billing-*"]
+            webhook = "https://hooks.example.com/payments"
+
+            [teams.billing-core]
+            services = ["billing-ledger"]
+
+            [teams.platform]
+            services = ["*"]
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_owner_of_exact_and_glob() {
+        let map = map();
+        assert_eq!(map.owner_of("checkout"), Some("payments"));
+        assert_eq!(map.owner_of("billing-api"), Some("payments"));
+        assert_eq!(map.owner_of("billing-ledger"), Some("billing-core"));
+        assert_eq!(map.owner_of("gateway"), Some("platform"));
+    }
+
+    #[test]
+    fn test_owner_of_unknown() {
+        let map = OwnershipMap::from_pairs([("checkout", "payments")]);
+        assert_eq!(map.owner_of("checkout"), Some("payments"));
+        assert_eq!(map.owner_of("gateway"), None);
+    }
+
+    #[test]
+    fn test_webhook() {
+        let map = map();
+        assert_eq!(
+            map.webhook("payments"),
+            Some("https://hooks.example.com/payments")
+        );
+        assert_eq!(map.webhook("platform"), None);
+        assert_eq!(map.webhook("nobody"), None);
+    }
+
+    #[test]
+    fn test_from_pairs_groups_by_team() {
+        let map = OwnershipMap::from_pairs([
+            ("checkout", "payments"),
+            ("billing", "payments"),
+            ("gateway", "platform"),
+        ]);
+        assert_eq!(map.teams.len(), 2);
+        assert_eq!(map.teams["payments"].services, vec!["checkout", "billing"]);
+    }
+}
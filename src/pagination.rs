@@ -0,0 +1,193 @@
+//! Caps on the size of paginated search exports.
+//!
+//! A search can be capped by record count (`--limit`), pages fetched
+//! (`--max-pages`), and NDJSON bytes written (`--max-bytes`). The limit is a
+//! hard cap on records however they fall across pages; the page and byte
+//! caps bound the cost of an export whose size is unknown up front.
+
+use std::fmt;
+
+use crate::error::AppError;
+
+/// The cap that stopped an export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cap {
+    /// `--limit` records were written
+    Limit(u64),
+    /// `--max-pages` pages were fetched
+    MaxPages(u64),
+    /// Writing the next record would exceed `--max-bytes`
+    MaxBytes(u64),
+}
+
+impl fmt::Display for Cap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Cap::Limit(limit) => write!(f, "--limit {}", limit),
+            Cap::MaxPages(pages) => write!(f, "--max-pages {}", pages),
+            Cap::MaxBytes(bytes) => write!(f, "--max-bytes {}", bytes),
+        }
+    }
+}
+
+impl Cap {
+    /// Whether stopping at this cap with results left is an error.
+    ///
+    /// Reaching `--limit` is the expected way to take a sample, so only the
+    /// page and byte caps mark an export as incomplete.
+    pub fn is_truncation(&self) -> bool {
+        !matches!(self, Cap::Limit(_))
+    }
+
+    /// The error for an export this cap stopped with results left.
+    pub fn truncated(&self, what: &str) -> AppError {
+        AppError::Truncated(format!("stopped at {}; more {} match", self, what))
+    }
+}
+
+/// Tracks an export against its caps.
+#[derive(Debug, Clone)]
+pub struct Budget {
+    limit: u64,
+    max_pages: Option<u64>,
+    max_bytes: Option<u64>,
+    records: u64,
+    bytes: u64,
+}
+
+impl Budget {
+    /// Creates a budget. A `limit` of 0 means no record limit.
+    pub fn new(limit: u64, max_pages: Option<u64>, max_bytes: Option<u64>) -> Self {
+        Self {
+            limit,
+            max_pages,
+            max_bytes,
+            records: 0,
+            bytes: 0,
+        }
+    }
+
+    /// Checks whether a result from page `page` (counting from 1, as told by
+    /// [`SearchStream::page`](crate::client::SearchStream::page)) can be
+    /// used, written or not.
+    ///
+    /// Returns the page cap if the result is past it, in which case more
+    /// results match than were used.
+    pub fn admit_page(&self, page: u64) -> Result<(), Cap> {
+        match self.max_pages {
+            Some(max_pages) if page > max_pages => Err(Cap::MaxPages(max_pages)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether record sizes are needed, i.e. there is a byte cap.
+    pub fn counts_bytes(&self) -> bool {
+        self.max_bytes.is_some()
+    }
+
+    /// Checks whether a record of `bytes` can be written; if so, counts it.
+    ///
+    /// Returns the byte cap if the record doesn't fit.
+    pub fn admit(&mut self, bytes: u64) -> Result<(), Cap> {
+        if let Some(max_bytes) = self.max_bytes
+            && self.bytes + bytes > max_bytes
+        {
+            return Err(Cap::MaxBytes(max_bytes));
+        }
+        self.records += 1;
+        self.bytes += bytes;
+        Ok(())
+    }
+
+    /// The record cap reached after the records admitted so far, if any.
+    pub fn exhausted(&self) -> Option<Cap> {
+        (self.limit > 0 && self.records >= self.limit).then_some(Cap::Limit(self.limit))
+    }
+}
+
+/// Parses a byte size: a number with an optional `K`, `M`, or `G` suffix
+/// (powers of 1024), optionally followed by `B` or `iB`.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::pagination::parse_size;
+///
+/// assert_eq!(parse_size("512").unwrap(), 512);
+/// assert_eq!(parse_size("10M").unwrap(), 10 * 1024 * 1024);
+/// assert_eq!(parse_size("2GiB").unwrap(), 2 * 1024 * 1024 * 1024);
+/// ```
+///
+/// # Errors
+///
+/// Returns an error message for anything else, or a size that overflows.
+pub fn parse_size(size: &str) -> Result<u64, String> {
+    let trimmed = size.trim();
+    let unit_start = trimmed
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(trimmed.len());
+    let (number, unit) = trimmed.split_at(unit_start);
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" | "kib" => 1 << 10,
+        "m" | "mb" | "mib" => 1 << 20,
+        "g" | "gb" | "gib" => 1 << 30,
+        _ => return Err(format!("invalid size '{}' (e.g. 500K, 10M, 1G)", size)),
+    };
+    number
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(multiplier))
+        .ok_or_else(|| format!("invalid size '{}' (e.g. 500K, 10M, 1G)", size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_limit_and_pages() {
+        let budget = Budget::new(0, Some(2), None);
+        assert_eq!(budget.admit_page(1), Ok(()));
+        assert_eq!(budget.admit_page(2), Ok(()));
+        assert_eq!(budget.admit_page(3), Err(Cap::MaxPages(2)));
+        assert_eq!(Budget::new(0, None, None).admit_page(1000), Ok(()));
+
+        let mut budget = Budget::new(2, Some(2), None);
+        budget.admit(10).unwrap();
+        assert_eq!(budget.exhausted(), None);
+        budget.admit(10).unwrap();
+        assert_eq!(budget.exhausted(), Some(Cap::Limit(2)));
+    }
+
+    #[test]
+    fn test_budget_bytes() {
+        let mut budget = Budget::new(0, None, Some(25));
+        assert!(budget.counts_bytes());
+        budget.admit(10).unwrap();
+        budget.admit(15).unwrap();
+        assert_eq!(budget.admit(1), Err(Cap::MaxBytes(25)));
+        assert_eq!(budget.exhausted(), None);
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1kb").unwrap(), 1024);
+        assert_eq!(parse_size(" 3 K ").unwrap(), 3072);
+        for size in ["", "M", "1T", "1.5M", "-1", "99999999999999G"] {
+            assert!(parse_size(size).is_err(), "{}", size);
+        }
+    }
+
+    #[test]
+    fn test_cap_truncation() {
+        assert!(!Cap::Limit(100).is_truncation());
+        assert!(Cap::MaxPages(5).is_truncation());
+        let error = Cap::MaxBytes(1024).truncated("logs");
+        assert_eq!(error.exit_code(), 9);
+        assert_eq!(
+            error.to_string(),
+            "Results truncated: stopped at --max-bytes 1024; more logs match"
+        );
+    }
+}
@@ -0,0 +1,191 @@
+//! Grouping log messages into patterns.
+//!
+//! Masks the variable parts of a message (IDs, counts, durations, addresses)
+//! so that messages differing only in those collapse into a single pattern,
+//! then counts occurrences per pattern.
+
+use std::collections::{BTreeMap, HashSet};
+
+use serde::Serialize;
+
+/// Placeholder for a masked token.
+pub const WILDCARD: &str = "<*>";
+
+/// Patterns are truncated to this many characters.
+const MAX_PATTERN_CHARS: usize = 160;
+
+/// Occurrences of one message pattern.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PatternCount {
+    /// Message with variable tokens replaced by `<*>`
+    pub pattern: String,
+
+    /// Number of messages matching the pattern
+    pub count: usize,
+
+    /// First message seen with this pattern
+    pub example: String,
+}
+
+/// Reduces a message to its pattern.
+///
+/// Only the first line is kept. Tokens containing a digit are replaced by
+/// `<*>`, keeping a `key=` prefix when the key itself has no digits, and runs
+/// of consecutive wildcards collapse into one.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::patterns::template;
+///
+/// assert_eq!(
+///     template("Timeout after 3000ms calling 10.0.0.7 user_id=42"),
+///     "Timeout after <*> calling <*> user_id=<*>"
+/// );
+/// ```
+pub fn template(message: &str) -> String {
+    let first_line = message.lines().next().unwrap_or_default();
+    let mut words: Vec<String> = Vec::new();
+
+    for token in first_line.split_whitespace() {
+        let masked = mask(token);
+        if masked == WILDCARD && words.last().is_some_and(|w| w == WILDCARD) {
+            continue;
+        }
+        words.push(masked);
+    }
+
+    let pattern = words.join(" ");
+    match pattern.char_indices().nth(MAX_PATTERN_CHARS) {
+        Some((end, _)) => format!("{}…", &pattern[..end]),
+        None => pattern,
+    }
+}
+
+fn mask(token: &str) -> String {
+    if !token.contains(|c: char| c.is_ascii_digit()) {
+        return token.to_string();
+    }
+    match token.split_once('=') {
+        Some((key, _)) if !key.is_empty() && !key.contains(|c: char| c.is_ascii_digit()) => {
+            format!("{}={}", key, WILDCARD)
+        }
+        _ => WILDCARD.to_string(),
+    }
+}
+
+/// Counts messages per pattern, most frequent first.
+///
+/// Ties are ordered by pattern so results are stable across runs.
+pub fn count_patterns<'a, I>(messages: I) -> Vec<PatternCount>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let mut counter = PatternCounter::default();
+    for message in messages {
+        counter.add(message);
+    }
+    counter.into_patterns()
+}
+
+/// Counts messages per pattern one at a time, keeping only the patterns, for
+/// messages that arrive as a stream.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::patterns::PatternCounter;
+///
+/// let mut counter = PatternCounter::default();
+/// counter.add("timeout after 30s");
+/// counter.add("timeout after 45s");
+/// assert_eq!(counter.top().unwrap().count, 2);
+/// let patterns = counter.into_patterns();
+/// assert_eq!((patterns[0].pattern.as_str(), patterns[0].count), ("timeout after <*>", 2));
+/// ```
+#[derive(Debug, Default)]
+pub struct PatternCounter {
+    counts: BTreeMap<String, PatternCount>,
+}
+
+impl PatternCounter {
+    /// Counts one message.
+    pub fn add(&mut self, message: &str) {
+        let pattern = template(message);
+        self.counts
+            .entry(pattern.clone())
+            .or_insert_with(|| PatternCount {
+                pattern,
+                count: 0,
+                example: message.to_string(),
+            })
+            .count += 1;
+    }
+
+    /// The most frequent pattern so far, the first of [`Self::into_patterns`].
+    pub fn top(&self) -> Option<&PatternCount> {
+        self.counts
+            .values()
+            .max_by(|a, b| a.count.cmp(&b.count).then(b.pattern.cmp(&a.pattern)))
+    }
+
+    /// The patterns counted, most frequent first, like [`count_patterns`].
+    pub fn into_patterns(self) -> Vec<PatternCount> {
+        let mut patterns: Vec<PatternCount> = self.counts.into_values().collect();
+        patterns.sort_by(|a, b| b.count.cmp(&a.count).then(a.pattern.cmp(&b.pattern)));
+        patterns
+    }
+}
+
+/// Keeps the patterns in `current` that don't appear in `baseline`.
+pub fn new_patterns(current: Vec<PatternCount>, baseline: &[PatternCount]) -> Vec<PatternCount> {
+    let known: HashSet<&str> = baseline.iter().map(|p| p.pattern.as_str()).collect();
+    current
+        .into_iter()
+        .filter(|p| !known.contains(p.pattern.as_str()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_template() {
+        assert_eq!(template("connection refused"), "connection refused");
+        assert_eq!(
+            template("retry 1 of 3 for order 9f8e7d"),
+            "retry <*> of <*> for order <*>"
+        );
+        assert_eq!(template("took 12ms 4ms"), "took <*>");
+        assert_eq!(template("first line 1\nsecond line 2"), "first line <*>");
+        assert_eq!(template("k8s=on"), WILDCARD);
+    }
+
+    #[test]
+    fn test_template_truncates() {
+        let pattern = template(&"word ".repeat(100));
+        assert_eq!(pattern.chars().count(), MAX_PATTERN_CHARS + 1);
+        assert!(pattern.ends_with('…'));
+    }
+
+    #[test]
+    fn test_count_patterns() {
+        let patterns =
+            count_patterns(["timeout after 30s", "payment declined", "timeout after 45s"]);
+        assert_eq!(patterns.len(), 2);
+        assert_eq!(patterns[0].pattern, "timeout after <*>");
+        assert_eq!(patterns[0].count, 2);
+        assert_eq!(patterns[0].example, "timeout after 30s");
+        assert_eq!(patterns[1].pattern, "payment declined");
+    }
+
+    #[test]
+    fn test_new_patterns() {
+        let baseline = count_patterns(["timeout after 10s"]);
+        let current = count_patterns(["timeout after 30s", "payment declined"]);
+        let new = new_patterns(current, &baseline);
+        assert_eq!(new.len(), 1);
+        assert_eq!(new[0].pattern, "payment declined");
+    }
+}
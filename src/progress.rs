@@ -0,0 +1,185 @@
+//! Progress of long paginated searches.
+//!
+//! Searches return records in ascending timestamp order, so the timestamp of
+//! the latest record shows how much of the time range has been covered. That
+//! coverage (or the share of `--limit` reached, if further along) drives the
+//! completion estimate of the progress line:
+//!
+//! ```text
+//! 12 pages, 11734 records, 0:42 elapsed, 38% done, ~1:08 left
+//! ```
+
+use std::time::{Duration, Instant};
+
+/// Minimum time between two redraws of the progress line.
+pub const REDRAW_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Progress of one search over `[from, to]`.
+#[derive(Debug, Clone)]
+pub struct Progress {
+    from_ms: i64,
+    to_ms: i64,
+    page_size: u64,
+    limit: u64,
+    started: Instant,
+    last_drawn: Option<Instant>,
+    records: u64,
+    latest_ms: Option<i64>,
+}
+
+impl Progress {
+    /// Starts tracking a search over `[from_ms, to_ms]` (Unix milliseconds)
+    /// fetching `page_size` records per page, stopping at `limit` records
+    /// (0 for no limit).
+    pub fn new(from_ms: i64, to_ms: i64, page_size: u64, limit: u64) -> Self {
+        Self {
+            from_ms,
+            to_ms,
+            page_size: page_size.max(1),
+            limit,
+            started: Instant::now(),
+            last_drawn: None,
+            records: 0,
+            latest_ms: None,
+        }
+    }
+
+    /// Counts an emitted record with its timestamp, if it has one.
+    pub fn record(&mut self, timestamp_ms: Option<i64>) {
+        self.records += 1;
+        if timestamp_ms.is_some() {
+            self.latest_ms = timestamp_ms;
+        }
+    }
+
+    /// Number of records emitted so far.
+    pub fn records(&self) -> u64 {
+        self.records
+    }
+
+    /// Number of pages fetched so far (every page but the last is full).
+    pub fn pages(&self) -> u64 {
+        self.records.div_ceil(self.page_size)
+    }
+
+    /// Fraction of the search done, from 0 to 1.
+    ///
+    /// The larger of the time range covered and the share of the limit
+    /// reached; `None` before the first timestamped record without a limit.
+    pub fn fraction(&self) -> Option<f64> {
+        let span = (self.to_ms - self.from_ms) as f64;
+        let coverage = self
+            .latest_ms
+            .filter(|_| span > 0.0)
+            .map(|latest| ((latest - self.from_ms) as f64 / span).clamp(0.0, 1.0));
+        let limit = (self.limit > 0).then(|| (self.records as f64 / self.limit as f64).min(1.0));
+        match (coverage, limit) {
+            (Some(coverage), Some(limit)) => Some(coverage.max(limit)),
+            (coverage, limit) => coverage.or(limit),
+        }
+    }
+
+    /// Whether the line should be redrawn at `now`, at most every [`REDRAW_INTERVAL`].
+    ///
+    /// Returns `true` at most once per interval, marking the line as drawn.
+    pub fn should_draw(&mut self, now: Instant) -> bool {
+        match self.last_drawn {
+            Some(last) if now.duration_since(last) < REDRAW_INTERVAL => false,
+            _ => {
+                self.last_drawn = Some(now);
+                true
+            }
+        }
+    }
+
+    /// The progress line as of `now`.
+    pub fn line(&self, now: Instant) -> String {
+        let elapsed = now.duration_since(self.started);
+        let mut line = format!(
+            "{} page{}, {} record{}, {} elapsed",
+            self.pages(),
+            if self.pages() == 1 { "" } else { "s" },
+            self.records,
+            if self.records == 1 { "" } else { "s" },
+            format_duration(elapsed)
+        );
+        if let Some(fraction) = self.fraction() {
+            line.push_str(&format!(", {:.0}% done", fraction * 100.0));
+            if fraction > 0.0 && fraction < 1.0 {
+                let left = elapsed.mul_f64((1.0 - fraction) / fraction);
+                line.push_str(&format!(", ~{} left", format_duration(left)));
+            }
+        }
+        line
+    }
+}
+
+/// Formats a duration as `M:SS`, or `H:MM:SS` from one hour.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::progress::format_duration;
+/// use std::time::Duration;
+///
+/// assert_eq!(format_duration(Duration::from_secs(65)), "1:05");
+/// assert_eq!(format_duration(Duration::from_secs(3723)), "1:02:03");
+/// ```
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    } else {
+        format!("{}:{:02}", secs / 60, secs % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_line() {
+        let mut progress = Progress::new(0, 1000, 1000, 0);
+        let start = progress.started;
+        assert_eq!(
+            progress.line(start + Duration::from_secs(1)),
+            "0 pages, 0 records, 0:01 elapsed"
+        );
+
+        for _ in 0..1500 {
+            progress.record(None);
+        }
+        progress.record(Some(250));
+        assert_eq!(progress.pages(), 2);
+        assert_eq!(progress.fraction(), Some(0.25));
+        assert_eq!(
+            progress.line(start + Duration::from_secs(10)),
+            "2 pages, 1501 records, 0:10 elapsed, 25% done, ~0:30 left"
+        );
+
+        progress.record(Some(1000));
+        assert_eq!(
+            progress.line(start + Duration::from_secs(40)),
+            "2 pages, 1502 records, 0:40 elapsed, 100% done"
+        );
+    }
+
+    #[test]
+    fn test_progress_limit() {
+        let mut progress = Progress::new(0, 1000, 1000, 10);
+        progress.record(None);
+        assert_eq!(progress.fraction(), Some(0.1));
+        progress.record(Some(500));
+        assert_eq!(progress.fraction(), Some(0.5));
+    }
+
+    #[test]
+    fn test_should_draw() {
+        let mut progress = Progress::new(0, 1, 1, 0);
+        let now = Instant::now();
+        assert!(progress.should_draw(now));
+        assert!(!progress.should_draw(now + Duration::from_millis(50)));
+        assert!(progress.should_draw(now + REDRAW_INTERVAL));
+    }
+}
@@ -0,0 +1,174 @@
+//! Query string composition helpers.
+//!
+//! Utilities for adding facet filters to Datadog logs and spans search queries
+//! without clobbering filters the user already wrote, and for building search
+//! terms from plain facet/value pairs.
+
+/// Prepends `facet:value` filters to a query.
+///
+/// Filters whose facet is already mentioned in the query are skipped, so an
+/// explicit `service:other` in the user's query always wins. The original query
+/// is wrapped in parentheses so boolean operators keep their meaning.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::query::scope;
+///
+/// assert_eq!(scope("status:error", &[("service", "api")]), "service:api (status:error)");
+/// assert_eq!(scope("*", &[("service", "api")]), "service:api");
+/// assert_eq!(scope("service:web", &[("service", "api")]), "service:web");
+/// ```
+pub fn scope(query: &str, filters: &[(&str, &str)]) -> String {
+    let added: Vec<String> = filters
+        .iter()
+        .filter(|(facet, _)| !mentions_facet(query, facet))
+        .map(|(facet, value)| format!("{}:{}", facet, value))
+        .collect();
+
+    if added.is_empty() {
+        return query.to_string();
+    }
+
+    let trimmed = query.trim();
+    if trimmed.is_empty() || trimmed == "*" {
+        added.join(" ")
+    } else {
+        format!("{} ({})", added.join(" "), trimmed)
+    }
+}
+
+/// Builds a `facet:value` search term, quoting the value when needed.
+///
+/// Several values become `facet:(a OR b)`. Values containing whitespace or
+/// search syntax characters are double-quoted.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::query::term;
+///
+/// assert_eq!(term("service", &["api"]), "service:api");
+/// assert_eq!(term("service", &["api", "web"]), "service:(api OR web)");
+/// assert_eq!(term("@error.message", &["connection refused"]), "@error.message:\"connection refused\"");
+/// ```
+pub fn term<S: AsRef<str>>(facet: &str, values: &[S]) -> String {
+    let values: Vec<String> = values.iter().map(|v| quote(v.as_ref())).collect();
+    match values.as_slice() {
+        [value] => format!("{}:{}", facet, value),
+        values => format!("{}:({})", facet, values.join(" OR ")),
+    }
+}
+
+/// Double-quotes a value that would otherwise be parsed as search syntax.
+fn quote(value: &str) -> String {
+    let needs_quotes = value.is_empty()
+        || value
+            .chars()
+            .any(|c| c.is_whitespace() || "():\"[]{}<>=".contains(c));
+    if needs_quotes {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Combines terms with a query so that results must match all of them.
+///
+/// Unlike [`scope`], every term is added even if the query mentions the facet.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::query::and_terms;
+///
+/// let terms = vec!["service:api".to_string()];
+/// assert_eq!(and_terms("status:error OR status:warn", &terms), "service:api (status:error OR status:warn)");
+/// assert_eq!(and_terms("*", &terms), "service:api");
+/// ```
+pub fn and_terms(query: &str, terms: &[String]) -> String {
+    if terms.is_empty() {
+        return query.to_string();
+    }
+
+    let trimmed = query.trim();
+    if trimmed.is_empty() || trimmed == "*" {
+        terms.join(" ")
+    } else {
+        format!("{} ({})", terms.join(" "), trimmed)
+    }
+}
+
+/// Checks whether a query already contains a `<facet>:` term (negated or not).
+pub fn mentions_facet(query: &str, facet: &str) -> bool {
+    let needle = format!("{}:", facet);
+    query
+        .split(|c: char| c.is_whitespace() || c == '(' || c == ')')
+        .map(|term| term.trim_start_matches('-'))
+        .any(|term| term.starts_with(&needle))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scope_adds_filters_in_order() {
+        assert_eq!(
+            scope("status:error", &[("service", "api"), ("version", "1.2.0")]),
+            "service:api version:1.2.0 (status:error)"
+        );
+    }
+
+    #[test]
+    fn test_scope_skips_mentioned_facets() {
+        assert_eq!(
+            scope("service:web status:error", &[("service", "api")]),
+            "service:web status:error"
+        );
+        assert_eq!(
+            scope(
+                "(service:a OR service:b)",
+                &[("service", "api"), ("env", "prod")]
+            ),
+            "env:prod ((service:a OR service:b))"
+        );
+    }
+
+    #[test]
+    fn test_scope_match_all_query() {
+        assert_eq!(scope("*", &[("env", "prod")]), "env:prod");
+        assert_eq!(scope("  ", &[("env", "prod")]), "env:prod");
+        assert_eq!(scope("*", &[]), "*");
+    }
+
+    #[test]
+    fn test_term_quotes_values() {
+        assert_eq!(term("env", &["prod"]), "env:prod");
+        assert_eq!(term("@http.url", &["/api/v1"]), "@http.url:/api/v1");
+        assert_eq!(term("@msg", &["a:b"]), "@msg:\"a:b\"");
+        assert_eq!(term("@msg", &["say \"hi\""]), "@msg:\"say \\\"hi\\\"\"");
+        assert_eq!(term("@msg", &[""]), "@msg:\"\"");
+    }
+
+    #[test]
+    fn test_and_terms() {
+        assert_eq!(and_terms("status:error", &[]), "status:error");
+        assert_eq!(
+            and_terms(
+                "service:web",
+                &["service:api".to_string(), "env:prod".to_string()]
+            ),
+            "service:api env:prod (service:web)"
+        );
+    }
+
+    #[test]
+    fn test_mentions_facet() {
+        assert!(mentions_facet("service:api", "service"));
+        assert!(mentions_facet("-service:api", "service"));
+        assert!(mentions_facet("(service:api)", "service"));
+        assert!(!mentions_facet("@service:api", "service"));
+        assert!(!mentions_facet("myservice:api", "service"));
+    }
+}
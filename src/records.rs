@@ -0,0 +1,337 @@
+//! Typed log and span records.
+//!
+//! The search APIs return loosely typed records: timestamps and statuses as
+//! strings, tags as `key:value` strings, and everything else as JSON.
+//! [`LogEvent`] and [`SpanEvent`] lift the common fields into Rust types and
+//! keep the record as returned behind [`LogEvent::raw`] and
+//! [`SpanEvent::raw`] for anything else.
+
+use chrono::{DateTime, Utc};
+use datadog_api_client::datadogV2::model::{Log, Span};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Severity of a log, as normalized by Datadog's status remapper.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    /// System is unusable (also "emerg", "fatal")
+    Emergency,
+    /// Action must be taken immediately
+    Alert,
+    /// Critical condition (also "crit")
+    Critical,
+    /// Error condition (also "err")
+    Error,
+    /// Warning condition (also "warn")
+    Warning,
+    /// Normal but significant condition
+    Notice,
+    /// Informational message
+    Info,
+    /// Debug-level message (also "trace", "verbose")
+    Debug,
+    /// Success
+    Ok,
+    /// A status Datadog doesn't know, as given
+    #[serde(untagged)]
+    Other(String),
+}
+
+impl Status {
+    /// Parses a status, accepting the short and syslog forms Datadog does
+    /// (e.g., "warn", "crit", "fatal").
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ddog::records::Status;
+    ///
+    /// assert_eq!(Status::parse("ERR"), Status::Error);
+    /// assert_eq!(Status::parse("fatal"), Status::Emergency);
+    /// assert_eq!(Status::parse("custom"), Status::Other("custom".to_string()));
+    /// ```
+    pub fn parse(status: &str) -> Self {
+        match status.to_ascii_lowercase().as_str() {
+            "emergency" | "emerg" | "fatal" | "f" => Self::Emergency,
+            "alert" | "a" => Self::Alert,
+            "critical" | "crit" | "c" => Self::Critical,
+            "error" | "err" | "e" => Self::Error,
+            "warning" | "warn" | "w" => Self::Warning,
+            "notice" | "n" => Self::Notice,
+            "info" | "information" | "i" => Self::Info,
+            "debug" | "trace" | "verbose" | "d" => Self::Debug,
+            "ok" | "o" | "success" => Self::Ok,
+            _ => Self::Other(status.to_string()),
+        }
+    }
+
+    /// Whether the status is error or worse.
+    pub fn is_severe(&self) -> bool {
+        matches!(
+            self,
+            Self::Emergency | Self::Alert | Self::Critical | Self::Error
+        )
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Emergency => "emergency",
+            Self::Alert => "alert",
+            Self::Critical => "critical",
+            Self::Error => "error",
+            Self::Warning => "warning",
+            Self::Notice => "notice",
+            Self::Info => "info",
+            Self::Debug => "debug",
+            Self::Ok => "ok",
+            Self::Other(status) => status,
+        };
+        f.write_str(name)
+    }
+}
+
+/// Tags keyed by name; a tag without a value (e.g., "canary") has no values.
+pub type Tags = BTreeMap<String, Vec<String>>;
+
+/// Groups `key:value` tags by key.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::records::parse_tags;
+///
+/// let tags = parse_tags(["env:prod", "team:web", "team:api", "canary"]);
+/// assert_eq!(tags["team"], ["web", "api"]);
+/// assert!(tags["canary"].is_empty());
+/// ```
+pub fn parse_tags<I: IntoIterator<Item = S>, S: AsRef<str>>(tags: I) -> Tags {
+    let mut parsed = Tags::new();
+    for tag in tags {
+        let tag = tag.as_ref();
+        match tag.split_once(':') {
+            Some((key, value)) => parsed
+                .entry(key.to_string())
+                .or_default()
+                .push(value.to_string()),
+            None => {
+                parsed.entry(tag.to_string()).or_default();
+            }
+        }
+    }
+    parsed
+}
+
+/// A log with its common fields typed.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LogEvent {
+    /// Event ID
+    pub id: Option<String>,
+
+    /// When the log was emitted
+    pub timestamp: Option<DateTime<Utc>>,
+
+    /// Severity
+    pub status: Option<Status>,
+
+    /// Service that emitted the log
+    pub service: Option<String>,
+
+    /// Host that emitted the log
+    pub host: Option<String>,
+
+    /// Log message
+    pub message: Option<String>,
+
+    /// Tags, grouped by key
+    pub tags: Tags,
+
+    /// Custom `@` attributes
+    pub attributes: BTreeMap<String, Value>,
+
+    #[serde(skip)]
+    raw: Value,
+}
+
+impl LogEvent {
+    /// The log as returned by the API.
+    pub fn raw(&self) -> &Value {
+        &self.raw
+    }
+}
+
+impl From<Log> for LogEvent {
+    fn from(log: Log) -> Self {
+        let raw = serde_json::to_value(&log).unwrap_or_default();
+        let attributes = log.attributes.unwrap_or_default();
+        Self {
+            id: log.id,
+            timestamp: attributes.timestamp,
+            status: attributes.status.as_deref().map(Status::parse),
+            service: attributes.service,
+            host: attributes.host,
+            message: attributes.message,
+            tags: parse_tags(attributes.tags.unwrap_or_default()),
+            attributes: attributes.attributes.unwrap_or_default(),
+            raw,
+        }
+    }
+}
+
+/// An APM span with its common fields typed.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SpanEvent {
+    /// Event ID
+    pub id: Option<String>,
+
+    /// Trace the span belongs to
+    pub trace_id: Option<String>,
+
+    /// Span ID
+    pub span_id: Option<String>,
+
+    /// Parent span ID; the root span's is "0" or missing
+    pub parent_id: Option<String>,
+
+    /// Service that emitted the span
+    pub service: Option<String>,
+
+    /// Resource, such as an endpoint or query
+    pub resource: Option<String>,
+
+    /// Environment
+    pub env: Option<String>,
+
+    /// Host that emitted the span
+    pub host: Option<String>,
+
+    /// When the span started
+    pub start: Option<DateTime<Utc>>,
+
+    /// When the span ended
+    pub end: Option<DateTime<Utc>>,
+
+    /// Span status, such as error or ok
+    pub status: Option<Status>,
+
+    /// Tags, grouped by key
+    pub tags: Tags,
+
+    /// Custom `@` attributes
+    pub custom: BTreeMap<String, Value>,
+
+    #[serde(skip)]
+    raw: Value,
+}
+
+impl SpanEvent {
+    /// The span as returned by the API.
+    pub fn raw(&self) -> &Value {
+        &self.raw
+    }
+
+    /// How long the span took, in milliseconds.
+    pub fn duration_ms(&self) -> Option<f64> {
+        let (start, end) = (self.start?, self.end?);
+        Some((end - start).num_microseconds()? as f64 / 1000.0)
+    }
+}
+
+impl From<Span> for SpanEvent {
+    fn from(span: Span) -> Self {
+        let raw = serde_json::to_value(&span).unwrap_or_default();
+        let attributes = span.attributes.unwrap_or_default();
+        let status = attributes
+            .additional_properties
+            .get("status")
+            .and_then(Value::as_str)
+            .map(Status::parse);
+        Self {
+            id: span.id,
+            trace_id: attributes.trace_id,
+            span_id: attributes.span_id,
+            parent_id: attributes.parent_id,
+            service: attributes.service,
+            resource: attributes.resource_name,
+            env: attributes.env,
+            host: attributes.host,
+            start: attributes.start_timestamp,
+            end: attributes.end_timestamp,
+            status,
+            tags: parse_tags(attributes.tags.unwrap_or_default()),
+            custom: attributes.custom.unwrap_or_default(),
+            raw,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_log_event_from_log() {
+        let log: Log = serde_json::from_value(json!({
+            "id": "AAA",
+            "type": "log",
+            "attributes": {
+                "timestamp": "2024-01-15T10:00:00Z",
+                "status": "warn",
+                "service": "api",
+                "message": "slow request",
+                "tags": ["env:prod", "version:1.2"],
+                "attributes": {"http": {"status_code": 200}}
+            }
+        }))
+        .unwrap();
+        let event = LogEvent::from(log);
+
+        assert_eq!(event.id.as_deref(), Some("AAA"));
+        assert_eq!(event.status, Some(Status::Warning));
+        assert_eq!(
+            event.timestamp.unwrap().to_rfc3339(),
+            "2024-01-15T10:00:00+00:00"
+        );
+        assert_eq!(event.tags["env"], ["prod"]);
+        assert_eq!(event.attributes["http"]["status_code"], 200);
+        assert_eq!(event.raw()["attributes"]["status"], "warn");
+    }
+
+    #[test]
+    fn test_span_event_duration_and_status() {
+        let span: Span = serde_json::from_value(json!({
+            "id": "BBB",
+            "type": "spans",
+            "attributes": {
+                "trace_id": "1",
+                "span_id": "2",
+                "service": "web",
+                "resource_name": "GET /users",
+                "start_timestamp": "2024-01-15T10:00:00Z",
+                "end_timestamp": "2024-01-15T10:00:00.250Z",
+                "status": "error"
+            }
+        }))
+        .unwrap();
+        let event = SpanEvent::from(span);
+
+        assert_eq!(event.duration_ms(), Some(250.0));
+        assert!(event.status.unwrap().is_severe());
+        assert_eq!(event.resource.as_deref(), Some("GET /users"));
+    }
+
+    #[test]
+    fn test_status_serializes_lowercase() {
+        assert_eq!(serde_json::to_value(Status::Critical).unwrap(), "critical");
+        assert_eq!(
+            serde_json::to_value(Status::Other("custom".to_string())).unwrap(),
+            "custom"
+        );
+        assert_eq!(Status::parse("WARN").to_string(), "warning");
+    }
+}
@@ -0,0 +1,351 @@
+//! Masking sensitive values in records (`--redact`, `--redact-pii`).
+//!
+//! Unlike [`anonymize`](crate::anonymize), which replaces values with
+//! consistent pseudonyms, redaction throws values away. A [`Redactor`]
+//! replaces the values at dotted field paths, where a segment may contain
+//! `*` wildcards, with `[REDACTED]`, and can scrub personal data found
+//! anywhere in a string value: email addresses, IPv4 addresses, payment card
+//! numbers, and US social security numbers each become a typed marker such
+//! as `[REDACTED:email]`.
+
+use serde_json::Value;
+
+/// What a redacted value is replaced with.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Kinds of personal data `--redact-pii` scrubs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pii {
+    Email,
+    Ip,
+    Card,
+    Ssn,
+}
+
+impl Pii {
+    fn marker(self) -> &'static str {
+        match self {
+            Pii::Email => "[REDACTED:email]",
+            Pii::Ip => "[REDACTED:ip]",
+            Pii::Card => "[REDACTED:card]",
+            Pii::Ssn => "[REDACTED:ssn]",
+        }
+    }
+}
+
+/// Masks sensitive values in records.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::redact::Redactor;
+/// use serde_json::json;
+///
+/// let redactor = Redactor::new(&["attributes.*.password".to_string()], true);
+/// let mut log = json!({"attributes": {
+///     "db": {"password": "hunter2"},
+///     "message": "login failed for jane@example.com from 10.1.2.3",
+/// }});
+/// redactor.apply(&mut log);
+/// assert_eq!(log["attributes"]["db"]["password"], "[REDACTED]");
+/// assert_eq!(
+///     log["attributes"]["message"],
+///     "login failed for [REDACTED:email] from [REDACTED:ip]"
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Redactor {
+    paths: Vec<Vec<String>>,
+    pii: bool,
+}
+
+impl Redactor {
+    /// Creates a redactor masking the values at `paths` (dotted, with `*`
+    /// wildcards within a segment), and with `pii` also scrubbing personal
+    /// data from every string value.
+    pub fn new(paths: &[String], pii: bool) -> Self {
+        Self {
+            paths: paths
+                .iter()
+                .map(|path| path.split('.').map(str::to_string).collect())
+                .collect(),
+            pii,
+        }
+    }
+
+    /// Whether the redactor changes anything.
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty() && !self.pii
+    }
+
+    /// Masks the sensitive values of a record in place.
+    ///
+    /// Paths are matched first, so a masked value isn't scrubbed again.
+    /// Arrays along a path are searched element by element.
+    pub fn apply(&self, record: &mut Value) {
+        for path in &self.paths {
+            let segments: Vec<&str> = path.iter().map(String::as_str).collect();
+            mask(record, &segments);
+        }
+        if self.pii {
+            scrub_value(record);
+        }
+    }
+}
+
+/// Replaces the values at `path` with [`REDACTED`].
+fn mask(value: &mut Value, path: &[&str]) {
+    let Some((segment, rest)) = path.split_first() else {
+        *value = Value::from(REDACTED);
+        return;
+    };
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map.iter_mut() {
+                if glob_match(segment, key) {
+                    mask(child, rest);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| mask(item, path)),
+        _ => {}
+    }
+}
+
+/// Whether `text` matches `pattern`, where `*` matches any run of characters.
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let Some((first, rest)) = pattern.split_once('*') else {
+        return pattern == text;
+    };
+    let Some(mut remaining) = text.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = rest.split('*').collect();
+    let last = parts.pop().unwrap_or_default();
+    for part in parts {
+        match remaining.find(part) {
+            Some(at) => remaining = &remaining[at + part.len()..],
+            None => return false,
+        }
+    }
+    remaining.len() >= last.len() && remaining.ends_with(last)
+}
+
+/// Scrubs personal data from every string in a value.
+fn scrub_value(value: &mut Value) {
+    match value {
+        Value::String(text) => {
+            if let Some(scrubbed) = scrub(text) {
+                *text = scrubbed;
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(scrub_value),
+        Value::Object(map) => map.values_mut().for_each(scrub_value),
+        _ => {}
+    }
+}
+
+/// Replaces the personal data in `text` with markers, or `None` if it has none.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::redact::scrub;
+///
+/// assert_eq!(
+///     scrub("card 4111 1111 1111 1111, ssn 078-05-1120").as_deref(),
+///     Some("card [REDACTED:card], ssn [REDACTED:ssn]")
+/// );
+/// assert_eq!(scrub("took 1705312800000 ms"), None);
+/// ```
+pub fn scrub(text: &str) -> Option<String> {
+    let mut spans = emails(text);
+    spans.extend(digit_runs(text, |c| c.is_ascii_digit() || c == '.', ipv4));
+    spans.extend(digit_runs(
+        text,
+        |c| c.is_ascii_digit() || c == ' ' || c == '-',
+        card_or_ssn,
+    ));
+    if spans.is_empty() {
+        return None;
+    }
+    spans.sort_by_key(|&(start, _, _)| start);
+
+    let mut scrubbed = String::with_capacity(text.len());
+    let mut end = 0;
+    for (start, stop, kind) in spans {
+        if start < end {
+            continue;
+        }
+        scrubbed.push_str(&text[end..start]);
+        scrubbed.push_str(kind.marker());
+        end = stop;
+    }
+    scrubbed.push_str(&text[end..]);
+    Some(scrubbed)
+}
+
+/// Byte ranges of the email addresses in `text`.
+fn emails(text: &str) -> Vec<(usize, usize, Pii)> {
+    let local = |c: char| c.is_ascii_alphanumeric() || "._%+-".contains(c);
+    let domain = |c: char| c.is_ascii_alphanumeric() || c == '.' || c == '-';
+    let mut spans = Vec::new();
+    for (at, _) in text.match_indices('@') {
+        let start = text[..at].rfind(|c: char| !local(c)).map_or(0, |i| i + 1);
+        let after = &text[at + 1..];
+        let host = after[..after.find(|c: char| !domain(c)).unwrap_or(after.len())]
+            .trim_end_matches(['.', '-']);
+        let tld = host
+            .rsplit_once('.')
+            .map(|(_, tld)| tld)
+            .unwrap_or_default();
+        if start < at && tld.len() >= 2 && tld.chars().all(|c| c.is_ascii_alphabetic()) {
+            spans.push((start, at + 1 + host.len(), Pii::Email));
+        }
+    }
+    spans
+}
+
+/// Byte ranges of the runs of `allowed` characters in `text` that start and
+/// end with a digit, on word boundaries, and that `classify` recognizes.
+fn digit_runs(
+    text: &str,
+    allowed: impl Fn(char) -> bool,
+    classify: impl Fn(&str) -> Option<Pii>,
+) -> Vec<(usize, usize, Pii)> {
+    let bytes = text.as_bytes();
+    let word = |i: usize| bytes.get(i).is_some_and(|b| b.is_ascii_alphanumeric());
+    let mut spans = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if !bytes[i].is_ascii_digit() || (i > 0 && word(i - 1)) {
+            i += 1;
+            continue;
+        }
+        let mut end = i;
+        while end < bytes.len() && allowed(bytes[end] as char) {
+            end += 1;
+        }
+        let run = text[i..end].trim_end_matches(|c: char| !c.is_ascii_digit());
+        let stop = i + run.len();
+        if !word(stop)
+            && let Some(kind) = classify(run)
+        {
+            spans.push((i, stop, kind));
+        }
+        i = end.max(i + 1);
+    }
+    spans
+}
+
+/// An IPv4 address: four dot-separated numbers from 0 to 255.
+fn ipv4(run: &str) -> Option<Pii> {
+    let parts: Vec<&str> = run.split('.').collect();
+    let valid = parts.len() == 4
+        && parts
+            .iter()
+            .all(|p| (1..=3).contains(&p.len()) && p.parse::<u8>().is_ok());
+    valid.then_some(Pii::Ip)
+}
+
+/// A US social security number (ddd-dd-dddd), or a 15 or 16 digit payment
+/// card number, optionally grouped by spaces or dashes, with a card
+/// network's leading digit and a valid Luhn checksum.
+fn card_or_ssn(run: &str) -> Option<Pii> {
+    let groups: Vec<usize> = run.split('-').map(str::len).collect();
+    if groups == [3, 2, 4] && run.chars().filter(char::is_ascii_digit).count() == 9 {
+        return Some(Pii::Ssn);
+    }
+    // Separators must be single, between digits
+    if run.contains("  ") || run.contains("--") || run.contains(" -") || run.contains("- ") {
+        return None;
+    }
+    let digits: Vec<u32> = run.chars().filter_map(|c| c.to_digit(10)).collect();
+    let card = matches!(digits.len(), 15 | 16) && (3..=6).contains(&digits[0]) && luhn(&digits);
+    card.then_some(Pii::Card)
+}
+
+/// Whether digits pass the Luhn checksum.
+fn luhn(digits: &[u32]) -> bool {
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| match i % 2 {
+            0 => d,
+            _ if d * 2 > 9 => d * 2 - 9,
+            _ => d * 2,
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_paths_with_wildcards() {
+        let redactor = Redactor::new(
+            &[
+                "attributes.user.email".to_string(),
+                "attributes.*.pass*".to_string(),
+                "tags".to_string(),
+            ],
+            false,
+        );
+        let mut record = json!({
+            "attributes": {
+                "user": {"email": "jane@example.com", "id": 7},
+                "db": {"password": "a", "passphrase": "b", "host": "db-1"},
+                "items": [{"passcode": "c"}],
+            },
+            "tags": ["env:prod"],
+        });
+        redactor.apply(&mut record);
+        assert_eq!(
+            record,
+            json!({
+                "attributes": {
+                    "user": {"email": REDACTED, "id": 7},
+                    "db": {"password": REDACTED, "passphrase": REDACTED, "host": "db-1"},
+                    "items": [{"passcode": REDACTED}],
+                },
+                "tags": REDACTED,
+            })
+        );
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*", "anything"));
+        assert!(glob_match("*token*", "x_token_id"));
+        assert!(glob_match("a*b*c", "abc"));
+        assert!(!glob_match("a*bc", "abcx"));
+        assert!(!glob_match("pass*", "user"));
+    }
+
+    #[test]
+    fn test_scrub_keeps_lookalikes() {
+        // Versions, timestamps, and IDs that aren't personal data
+        for text in [
+            "v1.2.3 released",
+            "at 2024-01-15 10:00:00",
+            "ts=1705312800000123",
+            "4111 1111 1111 1112",
+            "999.1.1.1",
+            "user@localhost",
+        ] {
+            assert_eq!(scrub(text), None, "{}", text);
+        }
+        assert_eq!(
+            scrub("from 192.168.0.1:8080 <ops@corp.example.org>.").as_deref(),
+            Some("from [REDACTED:ip]:8080 <[REDACTED:email]>.")
+        );
+        assert_eq!(
+            scrub("amex 3782-822463-10005").as_deref(),
+            Some("amex [REDACTED:card]")
+        );
+    }
+}
@@ -0,0 +1,312 @@
+//! Input parsing and session state for the interactive REPL.
+//!
+//! Each line typed at the `ddog repl` prompt is either a search query, run
+//! with the session's sticky settings, or a backslash command that changes
+//! those settings or works with the last result:
+//!
+//! ```text
+//! \domain logs|spans     Switch the search domain
+//! \time FROM [TO]        Set the time range (TO defaults to now)
+//! \last DURATION         Set the time range to the last DURATION
+//! \limit N               Fetch at most N results per query
+//! \indexes A,B           Log indexes to search
+//! \more                  Show the next page of the last result (or press Enter)
+//! \ndjson [FILE]         Export the last result as NDJSON
+//! \settings              Show the current settings
+//! \help                  List commands
+//! \quit                  Leave the REPL (or Ctrl-D)
+//! ```
+
+use serde_json::Value;
+use std::path::PathBuf;
+
+use crate::error::AppError;
+use crate::lint::SearchDomain;
+use crate::time::parse_last;
+
+/// Results shown per page.
+pub const PAGE_SIZE: usize = 20;
+
+/// Help text listing the REPL commands.
+pub const HELP: &str = "\
+Type a query to search with the current settings, or a command:
+  \\domain logs|spans     Switch the search domain
+  \\time FROM [TO]        Set the time range (TO defaults to now)
+  \\last DURATION         Set the time range to the last DURATION (e.g. 15m)
+  \\limit N               Fetch at most N results per query
+  \\indexes A,B           Log indexes to search
+  \\more                  Show the next page of the last result (or press Enter)
+  \\ndjson [FILE]         Export the last result as NDJSON (stdout by default)
+  \\settings              Show the current settings
+  \\help                  Show this help
+  \\quit                  Leave the REPL (or Ctrl-D)";
+
+/// One parsed line of REPL input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Input {
+    /// A search query to run
+    Query(String),
+    /// Switch the search domain
+    Domain(SearchDomain),
+    /// Set the time range
+    Time { from: String, to: String },
+    /// Set the result limit
+    Limit(u64),
+    /// Set the log indexes
+    Indexes(Vec<String>),
+    /// Show the next page of the last result
+    More,
+    /// Export the last result, to a file or stdout
+    Ndjson(Option<PathBuf>),
+    /// Show the current settings
+    Settings,
+    /// Show the command list
+    Help,
+    /// Leave the REPL
+    Quit,
+}
+
+/// Parses a line of REPL input.
+///
+/// An empty line is [`Input::More`], so Enter pages through a result.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::repl::{Input, parse_input};
+///
+/// assert_eq!(
+///     parse_input("\\last 15m").unwrap(),
+///     Input::Time { from: "now-15m".to_string(), to: "now".to_string() }
+/// );
+/// assert_eq!(
+///     parse_input("service:api status:error").unwrap(),
+///     Input::Query("service:api status:error".to_string())
+/// );
+/// ```
+///
+/// # Errors
+///
+/// Returns `AppError::InvalidQuery` for an unknown command or invalid arguments.
+pub fn parse_input(line: &str) -> Result<Input, AppError> {
+    let line = line.trim();
+    let Some(command) = line.strip_prefix('\\') else {
+        return Ok(if line.is_empty() {
+            Input::More
+        } else {
+            Input::Query(line.to_string())
+        });
+    };
+
+    let (name, rest) = command
+        .split_once(char::is_whitespace)
+        .unwrap_or((command, ""));
+    let args: Vec<&str> = rest.split_whitespace().collect();
+    let usage = |usage: &str| AppError::InvalidQuery(format!("usage: \\{}", usage));
+
+    match (name, args.as_slice()) {
+        ("domain", [domain]) => domain
+            .parse()
+            .map(Input::Domain)
+            .map_err(AppError::InvalidQuery),
+        ("domain", _) => Err(usage("domain logs|spans")),
+        ("time", [from]) => Ok(Input::Time {
+            from: from.to_string(),
+            to: "now".to_string(),
+        }),
+        ("time", [from, to]) => Ok(Input::Time {
+            from: from.to_string(),
+            to: to.to_string(),
+        }),
+        ("time", _) => Err(usage("time FROM [TO]")),
+        ("last", [duration]) => Ok(Input::Time {
+            from: parse_last(duration).map_err(AppError::InvalidQuery)?,
+            to: "now".to_string(),
+        }),
+        ("last", _) => Err(usage("last DURATION")),
+        ("limit", [limit]) => match limit.parse() {
+            Ok(limit) if limit > 0 => Ok(Input::Limit(limit)),
+            _ => Err(usage("limit N (a positive number)")),
+        },
+        ("limit", _) => Err(usage("limit N")),
+        ("indexes", [indexes]) => Ok(Input::Indexes(
+            indexes
+                .split(',')
+                .filter(|i| !i.is_empty())
+                .map(String::from)
+                .collect(),
+        )),
+        ("indexes", _) => Err(usage("indexes A,B")),
+        ("more", []) => Ok(Input::More),
+        ("ndjson", []) => Ok(Input::Ndjson(None)),
+        ("ndjson", [file]) => Ok(Input::Ndjson(Some(PathBuf::from(file)))),
+        ("settings", []) => Ok(Input::Settings),
+        ("help" | "?", []) => Ok(Input::Help),
+        ("quit" | "q" | "exit", []) => Ok(Input::Quit),
+        ("more" | "ndjson" | "settings" | "help" | "?" | "quit" | "q" | "exit", _) => Err(
+            AppError::InvalidQuery(format!("\\{} takes no arguments", name)),
+        ),
+        _ => Err(AppError::InvalidQuery(format!(
+            "unknown command \\{} (type \\help for a list)",
+            name
+        ))),
+    }
+}
+
+/// Sticky settings and the last result of a REPL session.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Session {
+    /// Domain queries search
+    pub domain: SearchDomain,
+
+    /// Start of the time range, as typed (relative times move with the clock)
+    pub from: String,
+
+    /// End of the time range, as typed
+    pub to: String,
+
+    /// Maximum number of results fetched per query
+    pub limit: u64,
+
+    /// Log indexes searched
+    pub indexes: Vec<String>,
+
+    /// Records of the last query
+    pub results: Vec<Value>,
+
+    /// Number of records of the last result shown so far
+    pub shown: usize,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self {
+            domain: SearchDomain::Logs,
+            from: "now-1h".to_string(),
+            to: "now".to_string(),
+            limit: 100,
+            indexes: vec!["*".to_string()],
+            results: Vec::new(),
+            shown: 0,
+        }
+    }
+}
+
+impl Session {
+    /// The prompt, showing the domain and time range (e.g., `logs now-1h..now> `).
+    pub fn prompt(&self) -> String {
+        format!("{} {}..{}> ", self.domain, self.from, self.to)
+    }
+
+    /// One-line summary of the settings.
+    pub fn settings(&self) -> String {
+        let mut settings = format!(
+            "domain={} from={} to={} limit={}",
+            self.domain, self.from, self.to, self.limit
+        );
+        if self.domain == SearchDomain::Logs {
+            settings.push_str(&format!(" indexes={}", self.indexes.join(",")));
+        }
+        settings
+    }
+
+    /// Replaces the last result and rewinds paging.
+    pub fn set_results(&mut self, results: Vec<Value>) {
+        self.results = results;
+        self.shown = 0;
+    }
+
+    /// Advances to the next page of the last result and returns it.
+    ///
+    /// Returns an empty slice once every record has been shown.
+    pub fn next_page(&mut self) -> &[Value] {
+        let start = self.shown;
+        self.shown = (start + PAGE_SIZE).min(self.results.len());
+        &self.results[start..self.shown]
+    }
+
+    /// Number of records of the last result not shown yet.
+    pub fn remaining(&self) -> usize {
+        self.results.len() - self.shown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_input() {
+        assert_eq!(parse_input("").unwrap(), Input::More);
+        assert_eq!(
+            parse_input("\\domain spans").unwrap(),
+            Input::Domain(SearchDomain::Spans)
+        );
+        assert_eq!(
+            parse_input("\\time now-2h now-1h").unwrap(),
+            Input::Time {
+                from: "now-2h".to_string(),
+                to: "now-1h".to_string()
+            }
+        );
+        assert_eq!(parse_input("\\limit 50").unwrap(), Input::Limit(50));
+        assert_eq!(
+            parse_input("\\indexes main,web").unwrap(),
+            Input::Indexes(vec!["main".to_string(), "web".to_string()])
+        );
+        assert_eq!(
+            parse_input("\\ndjson out.ndjson").unwrap(),
+            Input::Ndjson(Some(PathBuf::from("out.ndjson")))
+        );
+        assert_eq!(parse_input("\\q").unwrap(), Input::Quit);
+    }
+
+    #[test]
+    fn test_parse_input_errors() {
+        for line in [
+            "\\domain metrics",
+            "\\last 5x",
+            "\\limit 0",
+            "\\time",
+            "\\quit now",
+            "\\frobnicate",
+        ] {
+            assert!(
+                matches!(parse_input(line), Err(AppError::InvalidQuery(_))),
+                "{}",
+                line
+            );
+        }
+    }
+
+    #[test]
+    fn test_session_paging() {
+        let mut session = Session::default();
+        session.set_results((0..45).map(|i| json!({"i": i})).collect());
+
+        assert_eq!(session.next_page().len(), PAGE_SIZE);
+        assert_eq!(session.remaining(), 25);
+        assert_eq!(session.next_page()[0], json!({"i": 20}));
+        assert_eq!(session.next_page().len(), 5);
+        assert!(session.next_page().is_empty());
+
+        session.set_results(vec![json!({})]);
+        assert_eq!(session.remaining(), 1);
+    }
+
+    #[test]
+    fn test_session_prompt_and_settings() {
+        let mut session = Session::default();
+        assert_eq!(session.prompt(), "logs now-1h..now> ");
+        assert_eq!(
+            session.settings(),
+            "domain=logs from=now-1h to=now limit=100 indexes=*"
+        );
+        session.domain = SearchDomain::Spans;
+        assert_eq!(
+            session.settings(),
+            "domain=spans from=now-1h to=now limit=100"
+        );
+    }
+}
@@ -0,0 +1,204 @@
+//! Service context inference from the current repository checkout.
+//!
+//! Backs the `--this-service` flag: the service name and version are read
+//! from files in the repository root, in this order of preference:
+//!
+//! 1. `service.datadog.yaml` (`dd-service`) for the service name
+//! 2. Package metadata (`Cargo.toml`, `package.json`) for name and version
+//! 3. The most recent git tag for the version
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::error::AppError;
+
+/// Service name and version inferred from a repository.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ServiceContext {
+    /// Service name, used as `service:<name>`
+    pub service: Option<String>,
+
+    /// Service version, used as `version:<version>`
+    pub version: Option<String>,
+}
+
+impl ServiceContext {
+    /// Infers the service context for the repository containing `start`.
+    ///
+    /// The repository root is the nearest ancestor containing `.git`; when
+    /// there is none, `start` itself is inspected.
+    pub fn detect(start: &Path) -> Self {
+        let root = repo_root(start);
+        let package = read_package_metadata(&root);
+
+        let service = read_service_definition(&root).or_else(|| package.0.clone());
+        let version = package.1.or_else(|| latest_git_tag(&root));
+
+        Self { service, version }
+    }
+
+    /// Returns the `(facet, value)` filters for this context.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Config` when no service name could be inferred,
+    /// since a version filter alone would match unrelated services.
+    pub fn filters(&self) -> Result<Vec<(&str, &str)>, AppError> {
+        let service = self.service.as_deref().ok_or_else(|| {
+            AppError::Config(
+                "--this-service could not infer a service name. Add a service.datadog.yaml, \
+                 Cargo.toml, or package.json to the repository root"
+                    .into(),
+            )
+        })?;
+
+        let mut filters = vec![("service", service)];
+        if let Some(version) = &self.version {
+            filters.push(("version", version.as_str()));
+        }
+        Ok(filters)
+    }
+}
+
+/// Finds the nearest ancestor of `start` that contains a `.git` entry.
+fn repo_root(start: &Path) -> PathBuf {
+    start
+        .ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .unwrap_or(start)
+        .to_path_buf()
+}
+
+/// Reads `dd-service` from `service.datadog.yaml` (or `.yml`).
+fn read_service_definition(root: &Path) -> Option<String> {
+    ["service.datadog.yaml", "service.datadog.yml"]
+        .iter()
+        .filter_map(|name| std::fs::read_to_string(root.join(name)).ok())
+        .find_map(|contents| parse_dd_service(&contents))
+}
+
+/// Extracts the top-level `dd-service` value from a service definition.
+fn parse_dd_service(contents: &str) -> Option<String> {
+    contents.lines().find_map(|line| {
+        let value = line.strip_prefix("dd-service:")?;
+        let value = value.split('#').next()?.trim().trim_matches(['"', '\'']);
+        (!value.is_empty()).then(|| value.to_string())
+    })
+}
+
+/// Reads `(name, version)` from `Cargo.toml` or `package.json`.
+fn read_package_metadata(root: &Path) -> (Option<String>, Option<String>) {
+    if let Ok(contents) = std::fs::read_to_string(root.join("Cargo.toml"))
+        && let Ok(manifest) = contents.parse::<toml::Table>()
+        && let Some(package) = manifest.get("package").and_then(|p| p.as_table())
+    {
+        let field = |key: &str| package.get(key).and_then(|v| v.as_str()).map(String::from);
+        return (field("name"), field("version"));
+    }
+
+    if let Ok(contents) = std::fs::read_to_string(root.join("package.json"))
+        && let Ok(package) = serde_json::from_str::<serde_json::Value>(&contents)
+    {
+        let field = |key: &str| package.get(key).and_then(|v| v.as_str()).map(String::from);
+        // Strip npm scopes: "@acme/checkout" reports to Datadog as "checkout"
+        let name = field("name").map(|n| n.rsplit('/').next().unwrap_or(&n).to_string());
+        return (name, field("version"));
+    }
+
+    (None, None)
+}
+
+/// Returns the most recent git tag reachable from HEAD, without a leading `v`.
+fn latest_git_tag(root: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .args(["describe", "--tags", "--abbrev=0"])
+        .current_dir(root)
+        .output()
+        .ok()
+        .filter(|o| o.status.success())?;
+    let tag = String::from_utf8(output.stdout).ok()?;
+    let tag = tag.trim();
+    (!tag.is_empty()).then(|| tag.strip_prefix('v').unwrap_or(tag).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_repo(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ddog-repo-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(dir.join(".git")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_parse_dd_service() {
+        assert_eq!(
+            parse_dd_service("schema-version: v2\ndd-service: checkout\nteam: payments"),
+            Some("checkout".to_string())
+        );
+        assert_eq!(
+            parse_dd_service("dd-service: \"checkout\" # the checkout API"),
+            Some("checkout".to_string())
+        );
+        assert_eq!(parse_dd_service("  dd-service: nested"), None);
+        assert_eq!(parse_dd_service("team: payments"), None);
+    }
+
+    #[test]
+    fn test_detect_prefers_service_definition() {
+        let dir = temp_repo("definition");
+        std::fs::write(
+            dir.join("service.datadog.yaml"),
+            "dd-service: checkout-api\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("Cargo.toml"),
+            "[package]\nname = \"checkout\"\nversion = \"1.4.2\"\n",
+        )
+        .unwrap();
+
+        let ctx = ServiceContext::detect(&dir);
+        assert_eq!(ctx.service.as_deref(), Some("checkout-api"));
+        assert_eq!(ctx.version.as_deref(), Some("1.4.2"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_detect_from_package_json() {
+        let dir = temp_repo("npm");
+        let nested = dir.join("src").join("handlers");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(
+            dir.join("package.json"),
+            r#"{"name": "@acme/web", "version": "3.0.1"}"#,
+        )
+        .unwrap();
+
+        let ctx = ServiceContext::detect(&nested);
+        assert_eq!(ctx.service.as_deref(), Some("web"));
+        assert_eq!(ctx.version.as_deref(), Some("3.0.1"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_filters() {
+        let ctx = ServiceContext {
+            service: Some("api".into()),
+            version: Some("2.0.0".into()),
+        };
+        assert_eq!(
+            ctx.filters().unwrap(),
+            vec![("service", "api"), ("version", "2.0.0")]
+        );
+
+        let ctx = ServiceContext {
+            service: None,
+            version: Some("2.0.0".into()),
+        };
+        assert!(matches!(ctx.filters(), Err(AppError::Config(_))));
+    }
+}
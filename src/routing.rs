@@ -0,0 +1,325 @@
+//! Per-team routing of search results.
+//!
+//! Splits records by the team owning their `service`, writing each team's
+//! records to its own NDJSON file and/or posting a per-team summary to the
+//! team's webhook.
+
+use chrono::{DateTime, Utc};
+use datadog_api_client::datadog::Configuration;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::path::PathBuf;
+
+use crate::cli::OwnerRouting;
+use crate::logging::VerboseLogger;
+use crate::output::{self, NdjsonWriter, ProgressLine, RecordWriter};
+use ddog::anonymize::Anonymizer;
+use ddog::client::ServiceCatalogClient;
+use ddog::error::AppError;
+use ddog::ownership::{OwnershipMap, UNOWNED_TEAM};
+
+/// Records routed to a single team.
+#[derive(Debug, Serialize)]
+pub struct TeamSummary {
+    /// Owning team
+    pub team: String,
+
+    /// Number of records routed to the team
+    pub count: u64,
+
+    /// Record counts per service
+    pub services: BTreeMap<String, u64>,
+
+    /// File the team's records were written to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<PathBuf>,
+}
+
+/// Destination for search results: stdout, per-team files, or both.
+pub struct SearchSink {
+    writer: RecordWriter,
+    router: Option<OwnerRouter>,
+    anonymizer: Option<Anonymizer>,
+    progress: Option<ProgressLine>,
+}
+
+impl SearchSink {
+    /// Creates a sink writing to `writer`, routing records through `router` if given.
+    pub fn new(writer: RecordWriter, router: Option<OwnerRouter>) -> Self {
+        Self {
+            writer,
+            router,
+            anonymizer: None,
+            progress: None,
+        }
+    }
+
+    /// Shows `progress` on stderr as records are written.
+    pub fn with_progress(mut self, progress: Option<ProgressLine>) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Advances the progress line, if any, past a record with `timestamp`.
+    pub fn advance(&mut self, timestamp: Option<DateTime<Utc>>) {
+        if let Some(progress) = self.progress.as_mut() {
+            progress.record(timestamp);
+        }
+    }
+
+    /// Pseudonymizes records with `anonymizer` before writing or routing them.
+    pub fn with_anonymizer(mut self, anonymizer: Option<Anonymizer>) -> Self {
+        self.anonymizer = anonymizer;
+        self
+    }
+
+    /// Writes a record to stdout and/or its owning team's file.
+    ///
+    /// Records are anonymized first, so routing sees the pseudonymized record.
+    pub fn write<T: Serialize>(&mut self, record: &T) -> Result<(), AppError> {
+        match &self.anonymizer {
+            Some(anonymizer) => {
+                let mut value = serde_json::to_value(record)?;
+                anonymizer.apply(&mut value);
+                self.emit(&value)
+            }
+            None => self.emit(record),
+        }
+    }
+
+    fn emit<T: Serialize>(&mut self, record: &T) -> Result<(), AppError> {
+        match self.router.as_mut() {
+            Some(router) if router.writes_files() => {
+                router.route(record)?;
+                output::count_record();
+            }
+            Some(router) => {
+                router.route(record)?;
+                self.writer.write(record)?;
+            }
+            None => self.writer.write(record)?,
+        }
+        Ok(())
+    }
+
+    /// Finishes routing, emitting per-team summaries and notifications.
+    pub async fn finish(
+        mut self,
+        description: &str,
+        logger: &VerboseLogger,
+    ) -> Result<(), AppError> {
+        self.progress = None;
+        match self.router {
+            Some(router) => router.finish(description, &mut self.writer, logger).await,
+            None => Ok(()),
+        }
+    }
+}
+
+/// Routes records to per-team outputs based on service ownership.
+pub struct OwnerRouter {
+    owners: OwnershipMap,
+    dir: Option<PathBuf>,
+    notify: bool,
+    files: HashMap<String, NdjsonWriter<File>>,
+    summaries: BTreeMap<String, TeamSummary>,
+}
+
+impl OwnerRouter {
+    /// Builds a router from CLI options, or `None` when routing is not requested.
+    ///
+    /// Ownership comes from `--owners` when given, otherwise from the Service Catalog.
+    pub async fn from_args(
+        routing: &OwnerRouting,
+        config: &Configuration,
+        logger: &VerboseLogger,
+    ) -> Result<Option<Self>, AppError> {
+        if routing.route_by_owner.is_none() && !routing.notify_owners {
+            return Ok(None);
+        }
+
+        let owners = match &routing.owners {
+            Some(path) => OwnershipMap::load(path)?,
+            None => {
+                logger.log("Loading service ownership from the Service Catalog");
+                ServiceCatalogClient::new(config.clone())
+                    .ownership()
+                    .await?
+            }
+        };
+        logger.log(&format!(
+            "Loaded ownership for {} team(s)",
+            owners.teams.len()
+        ));
+
+        if let Some(dir) = &routing.route_by_owner {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        Ok(Some(Self {
+            owners,
+            dir: routing.route_by_owner.clone(),
+            notify: routing.notify_owners,
+            files: HashMap::new(),
+            summaries: BTreeMap::new(),
+        }))
+    }
+
+    /// Returns true when records go to per-team files rather than stdout.
+    pub fn writes_files(&self) -> bool {
+        self.dir.is_some()
+    }
+
+    /// Attributes a record to its owning team, writing it to the team's file if enabled.
+    pub fn route<T: Serialize>(&mut self, record: &T) -> Result<(), AppError> {
+        let value = serde_json::to_value(record)?;
+        let service = record_service(&value).unwrap_or("").to_string();
+        let team = self
+            .owners
+            .owner_of(&service)
+            .unwrap_or(UNOWNED_TEAM)
+            .to_string();
+
+        if let Some(dir) = &self.dir {
+            if !self.files.contains_key(&team) {
+                let path = dir.join(format!("{}.ndjson", file_stem(&team)));
+                self.files.insert(
+                    team.clone(),
+                    NdjsonWriter::with_writer(File::create(&path)?),
+                );
+                self.summary(&team).file = Some(path);
+            }
+            if let Some(writer) = self.files.get_mut(&team) {
+                match output::transform(&value)? {
+                    Some(transformed) => writer.write(&transformed)?,
+                    None => writer.write(&value)?,
+                }
+            }
+        }
+
+        let summary = self.summary(&team);
+        summary.count += 1;
+        *summary.services.entry(service).or_default() += 1;
+        Ok(())
+    }
+
+    /// Emits per-team summaries and posts them to team webhooks if requested.
+    ///
+    /// Summaries are written to `writer` only when records went to files,
+    /// so stdout never mixes records with summaries.
+    pub async fn finish(
+        self,
+        description: &str,
+        writer: &mut RecordWriter,
+        logger: &VerboseLogger,
+    ) -> Result<(), AppError> {
+        for summary in self.summaries.values() {
+            if self.dir.is_some() {
+                writer.write(summary)?;
+            }
+            if !self.notify {
+                continue;
+            }
+            match self.owners.webhook(&summary.team) {
+                Some(url) => {
+                    logger.log(&format!("Notifying {} at {}", summary.team, url));
+                    post_summary(url, summary, description).await?;
+                }
+                None => logger.log(&format!("No webhook for team {}", summary.team)),
+            }
+        }
+        Ok(())
+    }
+
+    fn summary(&mut self, team: &str) -> &mut TeamSummary {
+        self.summaries
+            .entry(team.to_string())
+            .or_insert_with(|| TeamSummary {
+                team: team.to_string(),
+                count: 0,
+                services: BTreeMap::new(),
+                file: None,
+            })
+    }
+}
+
+/// Reads the service of a log or span record.
+fn record_service(value: &serde_json::Value) -> Option<&str> {
+    value
+        .pointer("/attributes/service")
+        .or_else(|| value.get("service"))?
+        .as_str()
+}
+
+/// Makes a team name safe to use as a file name.
+fn file_stem(team: &str) -> String {
+    team.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Posts a team summary as JSON, with a Slack-compatible `text` field.
+async fn post_summary(url: &str, summary: &TeamSummary, description: &str) -> Result<(), AppError> {
+    let services: Vec<String> = summary
+        .services
+        .iter()
+        .map(|(service, count)| format!("{} ({})", service, count))
+        .collect();
+    let body = serde_json::json!({
+        "text": format!(
+            "{} result(s) for team {}: {}\nServices: {}",
+            summary.count,
+            summary.team,
+            description,
+            services.join(", ")
+        ),
+        "team": summary.team,
+        "count": summary.count,
+        "services": summary.services,
+    });
+
+    let response = reqwest::Client::new()
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(serde_json::to_vec(&body)?)
+        .send()
+        .await
+        .map_err(|e| AppError::Api(format!("Webhook request to {} failed: {}", url, e)))?;
+
+    if !response.status().is_success() {
+        return Err(AppError::Api(format!(
+            "Webhook {} returned {}",
+            url,
+            response.status()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_record_service() {
+        let log = json!({"attributes": {"service": "checkout"}});
+        assert_eq!(record_service(&log), Some("checkout"));
+        let flat = json!({"service": "gateway"});
+        assert_eq!(record_service(&flat), Some("gateway"));
+        assert_eq!(record_service(&json!({})), None);
+    }
+
+    #[test]
+    fn test_file_stem() {
+        assert_eq!(file_stem("payments"), "payments");
+        assert_eq!(file_stem("team/payments eu"), "team_payments_eu");
+    }
+}
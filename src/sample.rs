@@ -0,0 +1,104 @@
+//! Client-side random sampling of search results (`--sample`).
+//!
+//! A [`Sampler`] keeps each record with a fixed probability, independently
+//! of the others, so a sample of a large result set stays representative of
+//! it. Given a seed, the same records are kept from the same results, which
+//! makes an exploratory sample reproducible.
+
+use std::hash::{BuildHasher, RandomState};
+
+/// Keeps records with a fixed probability.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::sample::Sampler;
+///
+/// let mut sampler = Sampler::new(0.1, Some(42));
+/// let kept = (0..10_000).filter(|_| sampler.keep()).count();
+/// assert!((800..1200).contains(&kept));
+///
+/// // The same seed keeps the same records
+/// let picks = |seed| {
+///     let mut sampler = Sampler::new(0.5, Some(seed));
+///     (0..64).map(|_| sampler.keep()).collect::<Vec<_>>()
+/// };
+/// assert_eq!(picks(7), picks(7));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Sampler {
+    rate: f64,
+    state: u64,
+}
+
+impl Sampler {
+    /// Creates a sampler keeping records with probability `rate`, seeded with
+    /// `seed`, or randomly per run when `None`.
+    pub fn new(rate: f64, seed: Option<u64>) -> Self {
+        Self {
+            rate,
+            state: seed.unwrap_or_else(|| RandomState::new().hash_one("ddog sample")),
+        }
+    }
+
+    /// Whether to keep the next record.
+    pub fn keep(&mut self) -> bool {
+        // Top 53 bits of the next SplitMix64 output, as a float in [0, 1)
+        let unit = (self.next() >> 11) as f64 / (1u64 << 53) as f64;
+        unit < self.rate
+    }
+
+    /// The next output of SplitMix64.
+    fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+/// Parses a sampling rate: a fraction greater than 0 and at most 1.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::sample::parse_rate;
+///
+/// assert_eq!(parse_rate("0.01"), Ok(0.01));
+/// assert!(parse_rate("0").is_err());
+/// assert!(parse_rate("5").is_err());
+/// ```
+///
+/// # Errors
+///
+/// Returns an error message for anything else.
+pub fn parse_rate(rate: &str) -> Result<f64, String> {
+    match rate.trim().parse::<f64>() {
+        Ok(r) if r > 0.0 && r <= 1.0 => Ok(r),
+        _ => Err(format!(
+            "invalid sampling rate '{}': expected a fraction in (0, 1], e.g. 0.01 for 1%",
+            rate
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_one_keeps_everything() {
+        let mut sampler = Sampler::new(1.0, None);
+        assert!((0..1000).all(|_| sampler.keep()));
+    }
+
+    #[test]
+    fn test_seeds_differ() {
+        let picks = |seed| {
+            let mut sampler = Sampler::new(0.5, Some(seed));
+            (0..64).map(|_| sampler.keep()).collect::<Vec<_>>()
+        };
+        assert_ne!(picks(1), picks(2));
+    }
+}
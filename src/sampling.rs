@@ -0,0 +1,198 @@
+//! APM ingestion and retention breakdown per service.
+//!
+//! Answers "why can't I find my trace?" by splitting a service's spans by the
+//! mechanism that ingested them (`ingestion_reason`), the sampling decision
+//! they carry (`@_sampling_priority_v1`), and what kept them searchable
+//! (`retained_by`), then flagging the patterns that lose data: head-based
+//! sampling keeping almost nothing, rejected sampling decisions, and no
+//! retention filter covering the service.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::client::GroupCount;
+
+/// Facet naming the mechanism that ingested a span (e.g., "auto", "rule", "error").
+pub const INGESTION_REASON: &str = "ingestion_reason";
+
+/// Facet holding a span's sampling priority (-1 to 2).
+pub const SAMPLING_PRIORITY: &str = "@_sampling_priority_v1";
+
+/// Facet naming what retained an indexed span ("retention_filter" or "diversity_sampling").
+pub const RETAINED_BY: &str = "retained_by";
+
+/// Ingestion reasons of spans kept by head-based sampling.
+const HEAD_BASED: [&str; 3] = ["auto", "rule", "manual"];
+
+/// Share of spans below which head-based sampling is flagged as keeping almost nothing.
+const LOW_HEAD_SHARE: f64 = 0.1;
+
+/// Share of spans above which rejected sampling decisions are flagged.
+const HIGH_REJECTED_SHARE: f64 = 0.5;
+
+/// Ingestion and retention breakdown for one service.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ServiceSampling {
+    /// Service name
+    pub service: String,
+
+    /// Spans matching the query
+    pub spans: f64,
+
+    /// Span counts per ingestion reason
+    pub ingestion_reasons: BTreeMap<String, f64>,
+
+    /// Span counts per sampling priority
+    pub sampling_priorities: BTreeMap<String, f64>,
+
+    /// Span counts per retention mechanism
+    pub retained_by: BTreeMap<String, f64>,
+
+    /// Where spans are likely being dropped, in plain words
+    pub findings: Vec<String>,
+}
+
+impl ServiceSampling {
+    fn new(service: &str) -> Self {
+        Self {
+            service: service.to_string(),
+            spans: 0.0,
+            ingestion_reasons: BTreeMap::new(),
+            sampling_priorities: BTreeMap::new(),
+            retained_by: BTreeMap::new(),
+            findings: Vec::new(),
+        }
+    }
+
+    fn find(&mut self) {
+        if self.spans <= 0.0 {
+            return;
+        }
+        let share = |counts: &BTreeMap<String, f64>, keys: &[&str]| {
+            keys.iter().filter_map(|k| counts.get(*k)).sum::<f64>() / self.spans
+        };
+
+        let head = share(&self.ingestion_reasons, &HEAD_BASED);
+        let fallback = share(&self.ingestion_reasons, &["error", "rare"]);
+        if head < LOW_HEAD_SHARE && fallback > 0.0 {
+            self.findings.push(format!(
+                "Only {:.0}% of spans were kept by head-based sampling (auto, rule, manual); most come from the error and rare samplers, so complete traces are unlikely",
+                head * 100.0
+            ));
+        }
+
+        let rejected = share(&self.sampling_priorities, &["-1", "0"]);
+        if rejected > HIGH_REJECTED_SHARE {
+            self.findings.push(format!(
+                "{:.0}% of spans carry a rejecting sampling priority (-1 or 0), so their traces are incomplete",
+                rejected * 100.0
+            ));
+        }
+
+        let diversity = self
+            .retained_by
+            .get("diversity_sampling")
+            .copied()
+            .unwrap_or(0.0);
+        let filtered = self
+            .retained_by
+            .get("retention_filter")
+            .copied()
+            .unwrap_or(0.0);
+        if diversity > 0.0 && filtered == 0.0 {
+            self.findings.push(
+                "No spans were retained by a retention filter; after 15 minutes only diversity sampling keeps spans searchable".to_string(),
+            );
+        }
+    }
+}
+
+/// Builds per-service breakdowns from span counts grouped by service and by
+/// each of [`INGESTION_REASON`], [`SAMPLING_PRIORITY`], and [`RETAINED_BY`].
+///
+/// Services are ordered by span count, highest first; the total comes from
+/// the ingestion reason counts.
+pub fn report(
+    ingestion: &[GroupCount],
+    priority: &[GroupCount],
+    retention: &[GroupCount],
+) -> Vec<ServiceSampling> {
+    let mut services: BTreeMap<String, ServiceSampling> = BTreeMap::new();
+    let breakdowns: [(&[GroupCount], &str); 3] = [
+        (ingestion, INGESTION_REASON),
+        (priority, SAMPLING_PRIORITY),
+        (retention, RETAINED_BY),
+    ];
+
+    for (counts, facet) in breakdowns {
+        for count in counts {
+            let (Some(service), Some(value)) = (count.by.get("service"), count.by.get(facet))
+            else {
+                continue;
+            };
+            let entry = services
+                .entry(service.clone())
+                .or_insert_with(|| ServiceSampling::new(service));
+            let breakdown = match facet {
+                INGESTION_REASON => {
+                    entry.spans += count.count;
+                    &mut entry.ingestion_reasons
+                }
+                SAMPLING_PRIORITY => &mut entry.sampling_priorities,
+                _ => &mut entry.retained_by,
+            };
+            *breakdown.entry(value.clone()).or_default() += count.count;
+        }
+    }
+
+    let mut services: Vec<ServiceSampling> = services.into_values().collect();
+    services.iter_mut().for_each(ServiceSampling::find);
+    services.sort_by(|a, b| b.spans.total_cmp(&a.spans));
+    services
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count(service: &str, facet: &str, value: &str, count: f64) -> GroupCount {
+        GroupCount {
+            by: BTreeMap::from([
+                ("service".to_string(), service.to_string()),
+                (facet.to_string(), value.to_string()),
+            ]),
+            count,
+        }
+    }
+
+    #[test]
+    fn test_report() {
+        let services = report(
+            &[
+                count("web", INGESTION_REASON, "rule", 900.0),
+                count("web", INGESTION_REASON, "error", 100.0),
+                count("db", INGESTION_REASON, "auto", 5.0),
+                count("db", INGESTION_REASON, "error", 95.0),
+            ],
+            &[
+                count("web", SAMPLING_PRIORITY, "2", 900.0),
+                count("db", SAMPLING_PRIORITY, "0", 95.0),
+            ],
+            &[
+                count("web", RETAINED_BY, "retention_filter", 50.0),
+                count("db", RETAINED_BY, "diversity_sampling", 10.0),
+            ],
+        );
+
+        assert_eq!(services[0].service, "web");
+        assert_eq!(services[0].spans, 1000.0);
+        assert_eq!(services[0].ingestion_reasons["error"], 100.0);
+        assert!(services[0].findings.is_empty());
+
+        assert_eq!(services[1].service, "db");
+        assert_eq!(services[1].findings.len(), 3);
+        assert!(services[1].findings[0].starts_with("Only 5% of spans"));
+        assert!(services[1].findings[1].starts_with("95% of spans"));
+        assert!(services[1].findings[2].contains("retention filter"));
+    }
+}
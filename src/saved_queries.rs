@@ -0,0 +1,242 @@
+//! Saved queries shared across checkouts (`queries.toml` in the config dir).
+//!
+//! `ddog query save` stores a named logs or spans query in the user's config
+//! directory; any search can then reference it as `@name`. A workspace's
+//! `[queries]` table takes precedence, so a checkout can override a personal
+//! query of the same name.
+//!
+//! ```toml
+//! [errors-prod]
+//! query = "service:api status:error"
+//! domain = "logs"
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::config::config_dir;
+use crate::error::AppError;
+use crate::lint::SearchDomain;
+use crate::workspace::{Workspace, saved_query_name};
+
+/// Name of the saved queries file in the config directory.
+pub const SAVED_QUERIES_FILE: &str = "queries.toml";
+
+/// One saved query.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SavedQuery {
+    /// Search query text
+    pub query: String,
+
+    /// Search syntax the query was written for
+    pub domain: SearchDomain,
+}
+
+/// The saved queries file, keyed by name.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SavedQueries {
+    /// Saved queries keyed by name
+    pub queries: BTreeMap<String, SavedQuery>,
+}
+
+/// Returns the path of the saved queries file (`queries.toml` in [`config_dir`]).
+pub fn saved_queries_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join(SAVED_QUERIES_FILE))
+}
+
+impl SavedQueries {
+    /// Loads saved queries from a file.
+    ///
+    /// A missing file is not an error and yields no saved queries.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Config` if the file exists but cannot be read or parsed.
+    pub fn load(path: &Path) -> Result<Self, AppError> {
+        if !path.is_file() {
+            return Ok(Self::default());
+        }
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| AppError::Config(format!("Failed to read {}: {}", path.display(), e)))?;
+        toml::from_str(&contents)
+            .map_err(|e| AppError::Config(format!("Invalid {}: {}", path.display(), e)))
+    }
+
+    /// Writes saved queries to a file, creating its directory if needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Config` if the file cannot be written.
+    pub fn store(&self, path: &Path) -> Result<(), AppError> {
+        let contents = toml::to_string(self)
+            .map_err(|e| AppError::Config(format!("Failed to encode saved queries: {}", e)))?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| {
+                AppError::Config(format!("Failed to create {}: {}", dir.display(), e))
+            })?;
+        }
+        std::fs::write(path, contents)
+            .map_err(|e| AppError::Config(format!("Failed to write {}: {}", path.display(), e)))
+    }
+
+    /// Adds a saved query.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::InvalidQuery` if the name can't be referenced as
+    /// `@name`, and `AppError::Config` if it is taken and `replace` is false.
+    pub fn insert(&mut self, name: &str, saved: SavedQuery, replace: bool) -> Result<(), AppError> {
+        if saved_query_name(&format!("@{}", name)) != Some(name) {
+            return Err(AppError::InvalidQuery(format!(
+                "Invalid saved query name '{}' (use letters, digits, '-' and '_')",
+                name
+            )));
+        }
+        if !replace && self.queries.contains_key(name) {
+            return Err(AppError::Config(format!(
+                "Saved query '@{}' already exists (pass --force to replace it)",
+                name
+            )));
+        }
+        self.queries.insert(name.to_string(), saved);
+        Ok(())
+    }
+}
+
+/// Expands a `@name` reference for a `domain` search.
+///
+/// The workspace's `[queries]` are checked first, then the saved queries.
+/// Queries that aren't a bare `@name` are returned unchanged.
+///
+/// # Errors
+///
+/// Returns `AppError::InvalidQuery` if no query has that name, or if the saved
+/// query was written for the other domain.
+pub fn expand_query(
+    query: &str,
+    domain: SearchDomain,
+    workspace: Option<&Workspace>,
+    saved: &SavedQueries,
+) -> Result<String, AppError> {
+    let Some(name) = saved_query_name(query) else {
+        return Ok(query.to_string());
+    };
+
+    if let Some(ws) = workspace
+        && let Some(query) = ws.queries.get(name)
+    {
+        return Ok(query.clone());
+    }
+
+    match saved.queries.get(name) {
+        Some(saved) if saved.domain == domain => Ok(saved.query.clone()),
+        Some(saved) => Err(AppError::InvalidQuery(format!(
+            "Saved query '@{}' is a {} query and can't be used for a {} search",
+            name, saved.domain, domain
+        ))),
+        None => Err(AppError::InvalidQuery(format!(
+            "Unknown saved query '@{}' (save it with `ddog query save {} <QUERY>`{})",
+            name,
+            name,
+            workspace
+                .map(|ws| format!(" or define it in {}", ws.path.display()))
+                .unwrap_or_default()
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn saved(query: &str, domain: SearchDomain) -> SavedQuery {
+        SavedQuery {
+            query: query.to_string(),
+            domain,
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let path = std::env::temp_dir()
+            .join(format!("ddog-saved-{}", std::process::id()))
+            .join(SAVED_QUERIES_FILE);
+
+        let mut queries = SavedQueries::load(&path).unwrap();
+        assert!(queries.queries.is_empty());
+        queries
+            .insert(
+                "errors-prod",
+                saved("service:api status:error", SearchDomain::Logs),
+                false,
+            )
+            .unwrap();
+        queries.store(&path).unwrap();
+
+        let loaded = SavedQueries::load(&path).unwrap();
+        assert_eq!(
+            loaded.queries.get("errors-prod"),
+            Some(&saved("service:api status:error", SearchDomain::Logs))
+        );
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut queries = SavedQueries::default();
+        let query = saved("status:error", SearchDomain::Logs);
+        queries.insert("errors", query.clone(), false).unwrap();
+
+        assert!(matches!(
+            queries.insert("errors", query.clone(), false),
+            Err(AppError::Config(_))
+        ));
+        assert!(queries.insert("errors", query.clone(), true).is_ok());
+        assert!(matches!(
+            queries.insert("bad name", query, false),
+            Err(AppError::InvalidQuery(_))
+        ));
+    }
+
+    #[test]
+    fn test_expand_query() {
+        let mut queries = SavedQueries::default();
+        queries
+            .insert("errors", saved("status:error", SearchDomain::Logs), false)
+            .unwrap();
+
+        assert_eq!(
+            expand_query("@errors", SearchDomain::Logs, None, &queries).unwrap(),
+            "status:error"
+        );
+        assert_eq!(
+            expand_query("@http.status_code:500", SearchDomain::Logs, None, &queries).unwrap(),
+            "@http.status_code:500"
+        );
+
+        let err = expand_query("@errors", SearchDomain::Spans, None, &queries).unwrap_err();
+        assert!(err.to_string().contains("is a logs query"));
+        assert!(matches!(
+            expand_query("@missing", SearchDomain::Logs, None, &queries),
+            Err(AppError::InvalidQuery(_))
+        ));
+    }
+
+    #[test]
+    fn test_expand_query_prefers_workspace() {
+        let ws: Workspace = toml::from_str("[queries]\nerrors = \"env:dev status:error\"").unwrap();
+        let mut queries = SavedQueries::default();
+        queries
+            .insert("errors", saved("status:error", SearchDomain::Logs), false)
+            .unwrap();
+
+        assert_eq!(
+            expand_query("@errors", SearchDomain::Logs, Some(&ws), &queries).unwrap(),
+            "env:dev status:error"
+        );
+    }
+}
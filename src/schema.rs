@@ -0,0 +1,216 @@
+//! Inferring a JSON Schema from sample records (`ddog logs schema`).
+//!
+//! A [`SchemaInference`] folds records into one schema node per field path,
+//! noting every JSON type a field held and how many of its parent objects
+//! carried it. [`SchemaInference::finish`] writes the result as a JSON Schema
+//! (draft 2020-12): a field seen with several types lists them all, a field
+//! some records lack is left out of its parent's `required`, and strings that
+//! were always RFC 3339 timestamps get `"format": "date-time"`.
+
+use chrono::DateTime;
+use serde_json::{Map, Value, json};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The `$schema` of inferred schemas.
+pub const SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// What is known about the values at one field path.
+#[derive(Debug, Clone, Default)]
+struct Node {
+    /// JSON Schema type names of the values seen
+    types: BTreeSet<&'static str>,
+    /// Values that were objects, for telling which properties are required
+    objects: u64,
+    /// Values that were strings, and of those, RFC 3339 timestamps
+    strings: u64,
+    dates: u64,
+    /// Properties of object values, with the number of objects holding each
+    properties: BTreeMap<String, (u64, Node)>,
+    /// Elements of array values
+    items: Option<Box<Node>>,
+}
+
+impl Node {
+    fn add(&mut self, value: &Value) {
+        match value {
+            Value::Null => {
+                self.types.insert("null");
+            }
+            Value::Bool(_) => {
+                self.types.insert("boolean");
+            }
+            Value::Number(number) if number.is_f64() => {
+                self.types.insert("number");
+            }
+            Value::Number(_) => {
+                self.types.insert("integer");
+            }
+            Value::String(text) => {
+                self.types.insert("string");
+                self.strings += 1;
+                if DateTime::parse_from_rfc3339(text).is_ok() {
+                    self.dates += 1;
+                }
+            }
+            Value::Array(items) => {
+                self.types.insert("array");
+                let node = self.items.get_or_insert_default();
+                items.iter().for_each(|item| node.add(item));
+            }
+            Value::Object(map) => {
+                self.types.insert("object");
+                self.objects += 1;
+                for (key, child) in map {
+                    let (seen, node) = self.properties.entry(key.clone()).or_default();
+                    *seen += 1;
+                    node.add(child);
+                }
+            }
+        }
+    }
+
+    fn schema(&self) -> Map<String, Value> {
+        let mut types = self.types.clone();
+        // Integers are numbers too
+        if types.contains("number") {
+            types.remove("integer");
+        }
+        let mut schema = Map::new();
+        match types.len() {
+            0 => {}
+            1 => {
+                schema.insert("type".to_string(), json!(types.first()));
+            }
+            _ => {
+                schema.insert("type".to_string(), json!(types));
+            }
+        }
+        if self.strings > 0 && self.dates == self.strings {
+            schema.insert("format".to_string(), json!("date-time"));
+        }
+        if self.objects > 0 {
+            let properties: Map<String, Value> = self
+                .properties
+                .iter()
+                .map(|(key, (_, node))| (key.clone(), Value::Object(node.schema())))
+                .collect();
+            let required: Vec<&String> = self
+                .properties
+                .iter()
+                .filter(|(_, (seen, _))| *seen == self.objects)
+                .map(|(key, _)| key)
+                .collect();
+            schema.insert("properties".to_string(), Value::Object(properties));
+            if !required.is_empty() {
+                schema.insert("required".to_string(), json!(required));
+            }
+        }
+        if let Some(items) = &self.items {
+            schema.insert("items".to_string(), Value::Object(items.schema()));
+        }
+        schema
+    }
+}
+
+/// Infers a JSON Schema from sample records.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::schema::SchemaInference;
+/// use serde_json::json;
+///
+/// let mut inference = SchemaInference::new();
+/// inference.add(&json!({"id": "A", "attributes": {"status": 200, "host": "web-1"}}));
+/// inference.add(&json!({"id": "B", "attributes": {"status": 502.5}}));
+/// let schema = inference.finish();
+/// let attributes = &schema["properties"]["attributes"];
+/// assert_eq!(attributes["properties"]["status"]["type"], "number");
+/// assert_eq!(attributes["required"], json!(["status"]));
+/// assert_eq!(schema["required"], json!(["attributes", "id"]));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SchemaInference {
+    root: Node,
+    samples: u64,
+}
+
+impl SchemaInference {
+    /// Creates an inference with no samples.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a sample record.
+    pub fn add(&mut self, record: &Value) {
+        self.root.add(record);
+        self.samples += 1;
+    }
+
+    /// Number of records added.
+    pub fn samples(&self) -> u64 {
+        self.samples
+    }
+
+    /// The schema of the records added, with its `$schema` dialect.
+    pub fn finish(&self) -> Value {
+        let mut schema = Map::new();
+        schema.insert("$schema".to_string(), json!(SCHEMA_DIALECT));
+        schema.extend(self.root.schema());
+        Value::Object(schema)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_optional_and_mixed_types() {
+        let mut inference = SchemaInference::new();
+        inference.add(&json!({
+            "timestamp": "2024-01-15T10:00:00Z",
+            "user": {"id": 7, "email": "a@example.com"},
+            "tags": ["env:prod"],
+            "retry": null,
+        }));
+        inference.add(&json!({
+            "timestamp": "2024-01-15T10:00:01.5Z",
+            "user": "anonymous",
+            "tags": [],
+            "retry": true,
+        }));
+        let schema = inference.finish();
+        assert_eq!(inference.samples(), 2);
+        assert_eq!(
+            schema,
+            json!({
+                "$schema": SCHEMA_DIALECT,
+                "type": "object",
+                "properties": {
+                    "retry": {"type": ["boolean", "null"]},
+                    "tags": {"type": "array", "items": {"type": "string"}},
+                    "timestamp": {"type": "string", "format": "date-time"},
+                    "user": {
+                        "type": ["object", "string"],
+                        "properties": {
+                            "email": {"type": "string"},
+                            "id": {"type": "integer"},
+                        },
+                        "required": ["email", "id"],
+                    },
+                },
+                "required": ["retry", "tags", "timestamp", "user"],
+            })
+        );
+    }
+
+    #[test]
+    fn test_array_of_objects() {
+        let mut inference = SchemaInference::new();
+        inference.add(&json!({"spans": [{"name": "a", "ms": 1}, {"name": "b"}]}));
+        let items = &inference.finish()["properties"]["spans"]["items"];
+        assert_eq!(items["required"], json!(["name"]));
+        assert_eq!(items["properties"]["ms"]["type"], "integer");
+    }
+}
@@ -0,0 +1,342 @@
+//! Posting records to an HTTP endpoint (`--sink`).
+//!
+//! An [`HttpSink`] collects records into batches and POSTs each batch, as a
+//! JSON array or a Grafana Loki push payload, from a thread of their own so
+//! searching carries on while a batch is sent. Failed requests are retried like API requests: connection
+//! errors, 429s, and 5xx responses, with exponential backoff. Writes block
+//! when sending falls behind.
+
+use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::Arc;
+use std::sync::mpsc::{Receiver, SyncSender, sync_channel};
+use std::thread::JoinHandle;
+
+use crate::client::{DatadogTransport, TransportMiddleware};
+use crate::error::AppError;
+use crate::loki::{self, LokiEntry};
+
+/// Records per POST when not set.
+pub const DEFAULT_BATCH_SIZE: u64 = 100;
+
+/// Retries of a failed POST before giving up.
+pub const SINK_RETRIES: u32 = 3;
+
+/// Batches held in memory waiting to be sent before writes block.
+const QUEUED_BATCHES: usize = 4;
+
+/// How a batch of records is sent as a request body.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum BatchEncoding {
+    /// The records as a JSON array
+    #[default]
+    JsonArray,
+
+    /// A Loki push API payload of log records, labelled with these fields
+    /// (see [`LokiEntry::from_log`])
+    Loki(Vec<String>),
+}
+
+impl BatchEncoding {
+    fn encode(&self, batch: &[Value]) -> Result<Vec<u8>, AppError> {
+        Ok(match self {
+            Self::JsonArray => serde_json::to_vec(batch)?,
+            Self::Loki(labels) => {
+                let entries: Vec<LokiEntry> = batch
+                    .iter()
+                    .map(|log| LokiEntry::from_log(log, labels))
+                    .collect();
+                serde_json::to_vec(&loki::push_payload(&entries))?
+            }
+        })
+    }
+}
+
+/// What a sink delivered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SinkSummary {
+    /// Records posted
+    pub records: u64,
+
+    /// Requests made, one per batch
+    pub batches: u64,
+}
+
+/// POSTs records to an HTTP endpoint in batches.
+///
+/// Call [`finish`](Self::finish) once everything is written, to send the
+/// last batch and learn whether every batch was delivered.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ddog::sink::HttpSink;
+/// use serde_json::json;
+///
+/// # fn main() -> Result<(), ddog::AppError> {
+/// let mut sink = HttpSink::new("http://internal-svc/ingest", 100);
+/// sink.push(&json!({"message": "hello"}))?;
+/// let summary = sink.finish()?;
+/// assert_eq!(summary.records, 1);
+/// # Ok(())
+/// # }
+/// ```
+pub struct HttpSink {
+    url: String,
+    batch_size: usize,
+    encoding: BatchEncoding,
+    transport: Option<Arc<dyn DatadogTransport>>,
+    batch: Vec<Value>,
+    batches: Option<SyncSender<Vec<Value>>>,
+    poster: Option<JoinHandle<Result<SinkSummary, AppError>>>,
+}
+
+impl HttpSink {
+    /// Creates a sink posting batches of `batch_size` records to `url` as
+    /// JSON arrays. Nothing is sent until the first batch is full.
+    pub fn new(url: &str, batch_size: usize) -> Self {
+        Self {
+            url: url.to_string(),
+            batch_size: batch_size.max(1),
+            encoding: BatchEncoding::default(),
+            transport: None,
+            batch: Vec::new(),
+            batches: None,
+            poster: None,
+        }
+    }
+
+    /// Sends batches encoded as `encoding`.
+    pub fn with_encoding(mut self, encoding: BatchEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Sends requests through `transport`, such as a
+    /// [`MockTransport`](crate::client::MockTransport) in tests.
+    pub fn with_transport(mut self, transport: impl DatadogTransport + 'static) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Starts the thread posting batches.
+    fn start(&mut self) {
+        let http = retrying_client(self.transport.take());
+
+        let (batches, queue) = sync_channel(QUEUED_BATCHES);
+        let url = self.url.clone();
+        let encoding = self.encoding.clone();
+        self.batches = Some(batches);
+        self.poster = Some(std::thread::spawn(move || {
+            run_poster(http, url, encoding, queue)
+        }));
+    }
+
+    /// Adds a record, sending the batch once it is full.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the record can't be serialized, or if an earlier
+    /// batch has failed; [`finish`](Self::finish) reports why it failed.
+    pub fn push<T: Serialize>(&mut self, record: &T) -> Result<(), AppError> {
+        self.batch.push(serde_json::to_value(record)?);
+        if self.batch.len() >= self.batch_size {
+            self.send_batch()?;
+        }
+        Ok(())
+    }
+
+    fn send_batch(&mut self) -> Result<(), AppError> {
+        if self.poster.is_none() {
+            self.start();
+        }
+        let batch = std::mem::take(&mut self.batch);
+        let sent = self
+            .batches
+            .as_ref()
+            .is_some_and(|batches| batches.send(batch).is_ok());
+        if sent {
+            Ok(())
+        } else {
+            Err(AppError::Api(format!("posting to {} failed", self.url)))
+        }
+    }
+
+    /// Sends the last batch and waits for every batch to be delivered.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the first batch that failed after its retries:
+    /// `AppError::Auth` or `AppError::Forbidden` for 401 and 403,
+    /// `AppError::RateLimited` for 429, and `AppError::Api` otherwise.
+    pub fn finish(&mut self) -> Result<SinkSummary, AppError> {
+        if !self.batch.is_empty() {
+            // A failed poster reports its own error below
+            let _ = self.send_batch();
+        }
+        self.batches = None;
+        match self.poster.take() {
+            Some(poster) => poster
+                .join()
+                .unwrap_or_else(|_| Err(AppError::Api(format!("{}: poster panicked", self.url)))),
+            None => Ok(SinkSummary::default()),
+        }
+    }
+}
+
+impl Drop for HttpSink {
+    fn drop(&mut self) {
+        self.batches = None;
+        if let Some(poster) = self.poster.take() {
+            let _ = poster.join();
+        }
+    }
+}
+
+/// An HTTP client retrying transient failures [`SINK_RETRIES`] times,
+/// sending requests through `transport` when one is given.
+pub(crate) fn retrying_client(
+    transport: Option<Arc<dyn DatadogTransport>>,
+) -> ClientWithMiddleware {
+    let client = reqwest::Client::new();
+    let mut builder = ClientBuilder::new(client.clone()).with(
+        reqwest_retry::RetryTransientMiddleware::new_with_policy(
+            reqwest_retry::policies::ExponentialBackoff::builder()
+                .build_with_max_retries(SINK_RETRIES),
+        ),
+    );
+    if let Some(transport) = transport {
+        builder = builder.with(TransportMiddleware(transport, client));
+    }
+    builder.build()
+}
+
+/// Posts queued batches in order until the queue closes or one fails.
+fn run_poster(
+    http: ClientWithMiddleware,
+    url: String,
+    encoding: BatchEncoding,
+    batches: Receiver<Vec<Value>>,
+) -> Result<SinkSummary, AppError> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    runtime.block_on(async {
+        let mut summary = SinkSummary::default();
+        while let Ok(batch) = batches.recv() {
+            post(&http, &url, &url, encoding.encode(&batch)?).await?;
+            summary.records += batch.len() as u64;
+            summary.batches += 1;
+        }
+        Ok(summary)
+    })
+}
+
+/// POSTs a JSON body, mapping a rejection to the error of its status.
+///
+/// Errors name the endpoint `name` rather than `url`, which may hold a
+/// secret such as a webhook token.
+pub(crate) async fn post(
+    http: &ClientWithMiddleware,
+    url: &str,
+    name: &str,
+    body: Vec<u8>,
+) -> Result<(), AppError> {
+    let response = http
+        .post(url)
+        .header("content-type", "application/json")
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| {
+            let error = match e {
+                reqwest_middleware::Error::Reqwest(e) => e.without_url().to_string(),
+                e => e.to_string(),
+            };
+            AppError::Api(format!("{}: {}", name, error))
+        })?;
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    let body = response.text().await.unwrap_or_default();
+    let message = format!("{}: HTTP {}: {}", name, status, body.trim());
+    Err(match status.as_u16() {
+        401 => AppError::Auth(message),
+        403 => AppError::Forbidden(message),
+        429 => AppError::RateLimited(message),
+        _ => AppError::Api(message),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::MockTransport;
+    use serde_json::json;
+
+    const URL: &str = "http://internal-svc/ingest";
+
+    #[test]
+    fn test_posts_batches_in_order() {
+        let transport = MockTransport::new()
+            .respond("POST", "/ingest", 200, json!({}))
+            .respond("POST", "/ingest", 202, json!({}));
+        let mut sink = HttpSink::new(URL, 2).with_transport(transport.clone());
+        for id in ["a", "b", "c"] {
+            sink.push(&json!({"id": id})).unwrap();
+        }
+
+        let summary = sink.finish().unwrap();
+        assert_eq!(
+            summary,
+            SinkSummary {
+                records: 3,
+                batches: 2
+            }
+        );
+        let bodies: Vec<_> = transport
+            .requests()
+            .into_iter()
+            .map(|r| r.body.unwrap())
+            .collect();
+        assert_eq!(
+            bodies,
+            [json!([{"id": "a"}, {"id": "b"}]), json!([{"id": "c"}])]
+        );
+    }
+
+    #[test]
+    fn test_rejected_batch_fails_finish() {
+        let transport =
+            MockTransport::new().respond("POST", "/ingest", 403, json!({"error": "denied"}));
+        let mut sink = HttpSink::new(URL, 10).with_transport(transport);
+        sink.push(&json!({"id": "a"})).unwrap();
+        assert!(matches!(sink.finish(), Err(AppError::Forbidden(_))));
+    }
+
+    #[test]
+    fn test_loki_encoding() {
+        let transport = MockTransport::new().respond("POST", "/loki/api/v1/push", 204, json!({}));
+        let mut sink = HttpSink::new("http://loki:3100/loki/api/v1/push", 10)
+            .with_encoding(BatchEncoding::Loki(vec!["service".to_string()]))
+            .with_transport(transport.clone());
+        let log = json!({"attributes": {
+            "message": "hi",
+            "service": "api",
+            "timestamp": "2024-01-15T10:00:00Z",
+        }});
+        sink.push(&log).unwrap();
+        assert_eq!(sink.finish().unwrap().records, 1);
+
+        let body = transport.requests()[0].body.clone().unwrap();
+        assert_eq!(
+            body,
+            json!({"streams": [{
+                "stream": {"service": "api"},
+                "values": [["1705312800000000000", "hi"]],
+            }]})
+        );
+    }
+}
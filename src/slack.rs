@@ -0,0 +1,196 @@
+//! Slack notifications (`--notify-slack`).
+//!
+//! A [`SlackMessage`] is a short summary of a breached check: a headline,
+//! a few labelled fields, and a link to the result in Datadog. It is posted
+//! to a Slack incoming webhook as Block Kit blocks, with the headline as the
+//! plain-text fallback Slack shows in notifications.
+
+use reqwest_middleware::ClientWithMiddleware;
+use serde_json::{Value, json};
+use std::sync::Arc;
+
+use crate::client::DatadogTransport;
+use crate::error::AppError;
+use crate::sink;
+
+/// A notification message.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SlackMessage {
+    /// Headline, also the notification text
+    pub headline: String,
+
+    /// Labelled values, in order
+    pub fields: Vec<(String, String)>,
+
+    /// URL of the result in Datadog
+    pub link: Option<String>,
+}
+
+impl SlackMessage {
+    /// Creates a message with only a headline.
+    pub fn new(headline: impl Into<String>) -> Self {
+        Self {
+            headline: headline.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Adds a labelled value.
+    pub fn field(mut self, label: &str, value: impl Into<String>) -> Self {
+        self.fields.push((label.to_string(), value.into()));
+        self
+    }
+
+    /// Sets the link to the result in Datadog.
+    pub fn link(mut self, url: impl Into<String>) -> Self {
+        self.link = Some(url.into());
+        self
+    }
+
+    /// The incoming-webhook payload for the message.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ddog::slack::SlackMessage;
+    /// use serde_json::json;
+    ///
+    /// let message = SlackMessage::new("Log check failed")
+    ///     .field("Query", "`service:api status:error`")
+    ///     .field("Count", "12");
+    /// let payload = message.payload();
+    /// assert_eq!(payload["text"], "Log check failed");
+    /// assert_eq!(
+    ///     payload["blocks"][1]["fields"][1],
+    ///     json!({"type": "mrkdwn", "text": "*Count*\n12"})
+    /// );
+    /// ```
+    pub fn payload(&self) -> Value {
+        let mut blocks = vec![json!({
+            "type": "section",
+            "text": {"type": "mrkdwn", "text": format!(":rotating_light: *{}*", self.headline)},
+        })];
+        // Slack allows at most 10 fields per section
+        for fields in self.fields.chunks(10) {
+            let fields: Vec<Value> = fields
+                .iter()
+                .map(|(label, value)| {
+                    json!({"type": "mrkdwn", "text": format!("*{}*\n{}", label, value)})
+                })
+                .collect();
+            blocks.push(json!({"type": "section", "fields": fields}));
+        }
+        if let Some(link) = &self.link {
+            blocks.push(json!({
+                "type": "context",
+                "elements": [{"type": "mrkdwn", "text": format!("<{}|Open in Datadog>", link)}],
+            }));
+        }
+        json!({"text": self.headline, "blocks": blocks})
+    }
+}
+
+/// Posts messages to a Slack incoming webhook.
+///
+/// Requests are retried like `--sink` batches: connection errors, 429s, and
+/// 5xx responses, with exponential backoff.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ddog::slack::{SlackMessage, SlackWebhook};
+///
+/// # async fn example() -> Result<(), ddog::AppError> {
+/// let webhook = SlackWebhook::new("https://hooks.slack.com/services/T000/B000/XXXX");
+/// webhook.send(&SlackMessage::new("Monitor High CPU is Alert")).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct SlackWebhook {
+    url: String,
+    http: ClientWithMiddleware,
+}
+
+impl std::fmt::Debug for SlackWebhook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // The webhook URL is a secret
+        f.debug_struct("SlackWebhook").finish_non_exhaustive()
+    }
+}
+
+impl SlackWebhook {
+    /// Creates a webhook posting to `url`.
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            http: sink::retrying_client(None),
+        }
+    }
+
+    /// Sends requests through `transport`, such as a
+    /// [`MockTransport`](crate::client::MockTransport) in tests.
+    pub fn with_transport(mut self, transport: impl DatadogTransport + 'static) -> Self {
+        self.http = sink::retrying_client(Some(Arc::new(transport)));
+        self
+    }
+
+    /// Posts a message.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of a request that failed after its retries:
+    /// `AppError::Forbidden` for 403 (a revoked webhook), `AppError::RateLimited`
+    /// for 429, and `AppError::Api` otherwise.
+    pub async fn send(&self, message: &SlackMessage) -> Result<(), AppError> {
+        let body = serde_json::to_vec(&message.payload())?;
+        sink::post(&self.http, &self.url, "Slack webhook", body).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::MockTransport;
+
+    const URL: &str = "https://hooks.slack.com/services/T000/B000/XXXX";
+
+    #[test]
+    fn test_payload_without_fields() {
+        let payload = SlackMessage::new("Monitor High CPU is Alert")
+            .link("https://app.datadoghq.com/monitors/1")
+            .payload();
+        let blocks = payload["blocks"].as_array().unwrap();
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(
+            blocks[1]["elements"][0]["text"],
+            "<https://app.datadoghq.com/monitors/1|Open in Datadog>"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_posts_payload() {
+        let transport =
+            MockTransport::new().respond("POST", "/services/T000/B000/XXXX", 200, json!("ok"));
+        let webhook = SlackWebhook::new(URL).with_transport(transport.clone());
+        let message = SlackMessage::new("Log check failed").field("Count", "3");
+        webhook.send(&message).await.unwrap();
+
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].body, Some(message.payload()));
+    }
+
+    #[tokio::test]
+    async fn test_revoked_webhook() {
+        let transport = MockTransport::new().respond(
+            "POST",
+            "/services/T000/B000/XXXX",
+            403,
+            json!("invalid_token"),
+        );
+        let webhook = SlackWebhook::new(URL).with_transport(transport);
+        let result = webhook.send(&SlackMessage::new("Log check failed")).await;
+        assert!(matches!(result, Err(AppError::Forbidden(_))));
+    }
+}
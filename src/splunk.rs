@@ -0,0 +1,120 @@
+//! Splunk HTTP Event Collector envelopes (`--format splunk-hec`).
+//!
+//! Each record is wrapped in the envelope HEC's `/services/collector/event`
+//! endpoint accepts, with the record's time and host lifted out of it. HEC
+//! accepts envelopes concatenated in one request body, so the NDJSON output
+//! can be sent as is:
+//!
+//! ```text
+//! curl -H "Authorization: Splunk $HEC_TOKEN" \
+//!      https://splunk:8088/services/collector/event --data-binary @events.ndjson
+//! ```
+
+use chrono::DateTime;
+use serde_json::{Map, Value};
+
+/// Sourcetype of events when none is given.
+pub const DEFAULT_SOURCETYPE: &str = "datadog:log";
+
+/// Source of every event.
+pub const SOURCE: &str = "ddog";
+
+/// Envelope fields set for every event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HecOptions {
+    /// Splunk sourcetype
+    pub sourcetype: String,
+
+    /// Splunk index; HEC's default index when `None`
+    pub index: Option<String>,
+}
+
+impl Default for HecOptions {
+    fn default() -> Self {
+        Self {
+            sourcetype: DEFAULT_SOURCETYPE.to_string(),
+            index: None,
+        }
+    }
+}
+
+/// Wraps a record in a HEC event envelope.
+///
+/// The event time is the record's `timestamp` (a span's `start_timestamp`)
+/// in epoch seconds, and its host the record's `host`, looked up in its
+/// `attributes` first. Records without them are left for HEC to default.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::splunk::{HecOptions, envelope};
+/// use serde_json::json;
+///
+/// let log = json!({"id": "AAA", "attributes": {
+///     "timestamp": "2024-01-15T10:00:00.250Z",
+///     "host": "web-1",
+///     "message": "connection refused",
+/// }});
+/// let event = envelope(&log, &HecOptions::default());
+/// assert_eq!(event["time"], json!(1705312800.25));
+/// assert_eq!(event["host"], "web-1");
+/// assert_eq!(event["sourcetype"], "datadog:log");
+/// assert_eq!(event["event"], log);
+/// ```
+pub fn envelope(record: &Value, options: &HecOptions) -> Value {
+    let field = |name: &str| {
+        record
+            .pointer(&format!("/attributes/{}", name))
+            .or_else(|| record.get(name))
+            .and_then(Value::as_str)
+    };
+    let mut envelope = Map::new();
+    let time = field("timestamp")
+        .or_else(|| field("start_timestamp"))
+        .and_then(|t| DateTime::parse_from_rfc3339(t).ok());
+    if let Some(time) = time {
+        let seconds = time.timestamp_millis() as f64 / 1000.0;
+        envelope.insert("time".to_string(), Value::from(seconds));
+    }
+    if let Some(host) = field("host") {
+        envelope.insert("host".to_string(), Value::from(host));
+    }
+    envelope.insert("source".to_string(), Value::from(SOURCE));
+    envelope.insert(
+        "sourcetype".to_string(),
+        Value::from(options.sourcetype.as_str()),
+    );
+    if let Some(index) = &options.index {
+        envelope.insert("index".to_string(), Value::from(index.as_str()));
+    }
+    envelope.insert("event".to_string(), record.clone());
+    Value::Object(envelope)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_envelope_span_with_index() {
+        let span = json!({"attributes": {"start_timestamp": "2024-01-15T10:00:00Z"}});
+        let options = HecOptions {
+            sourcetype: "datadog:span".to_string(),
+            index: Some("security".to_string()),
+        };
+        let event = envelope(&span, &options);
+        assert_eq!(event["time"], json!(1705312800.0));
+        assert_eq!(event["index"], "security");
+        assert_eq!(event.get("host"), None);
+    }
+
+    #[test]
+    fn test_envelope_without_time() {
+        let record = json!({"metric": "system.cpu.user", "value": 1.5});
+        let event = envelope(&record, &HecOptions::default());
+        assert_eq!(event.get("time"), None);
+        assert_eq!(event.get("index"), None);
+        assert_eq!(event["source"], "ddog");
+    }
+}
@@ -0,0 +1,211 @@
+//! Summary statistics of a log field, computed client-side.
+//!
+//! The aggregate API computes percentiles of measures only and counts
+//! distinct values one facet at a time. [`FieldCollector`] reads one field
+//! from each log of a search instead, so any attribute or tag can be
+//! summarized: numeric values into min, max, average, and percentiles, and
+//! every value into a cardinality.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashSet;
+
+/// Summary of one field over a set of logs.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldStats {
+    /// Field as given (e.g., "@duration", "service", "env")
+    pub field: String,
+
+    /// Logs carrying the field
+    pub count: u64,
+
+    /// Logs without the field
+    pub missing: u64,
+
+    /// Number of distinct values
+    pub cardinality: u64,
+
+    /// Values that are numbers; the statistics below cover only these
+    pub numeric: u64,
+
+    /// Smallest numeric value
+    pub min: Option<f64>,
+
+    /// Largest numeric value
+    pub max: Option<f64>,
+
+    /// Mean of the numeric values
+    pub avg: Option<f64>,
+
+    /// Median of the numeric values
+    pub p50: Option<f64>,
+
+    /// 90th percentile of the numeric values
+    pub p90: Option<f64>,
+
+    /// 95th percentile of the numeric values
+    pub p95: Option<f64>,
+
+    /// 99th percentile of the numeric values
+    pub p99: Option<f64>,
+}
+
+/// Collects the values of one field from logs, one at a time.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::stats::FieldCollector;
+/// use serde_json::json;
+///
+/// let mut collector = FieldCollector::new("@duration");
+/// for duration in [120, 80, 300] {
+///     collector.add(&json!({"attributes": {"attributes": {"duration": duration}}}));
+/// }
+/// collector.add(&json!({"attributes": {"service": "api"}}));
+///
+/// let stats = collector.finish();
+/// assert_eq!((stats.count, stats.missing, stats.cardinality), (3, 1, 3));
+/// assert_eq!(stats.max, Some(300.0));
+/// assert_eq!(stats.p50, Some(120.0));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FieldCollector {
+    field: String,
+    count: u64,
+    missing: u64,
+    values: Vec<f64>,
+    distinct: HashSet<String>,
+}
+
+impl FieldCollector {
+    /// Creates a collector for `field`, named as in queries: `@` and a
+    /// dotted path for custom attributes, otherwise a reserved attribute or
+    /// a tag key.
+    pub fn new(field: &str) -> Self {
+        Self {
+            field: field.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Adds a log's value of the field, in the shape the logs search API
+    /// returns logs.
+    pub fn add(&mut self, log: &Value) {
+        let attributes = log.get("attributes").unwrap_or(log);
+        let Some(value) = field_value(attributes, &self.field) else {
+            self.missing += 1;
+            return;
+        };
+        self.count += 1;
+        if let Some(number) = value.as_f64() {
+            self.values.push(number);
+        }
+        let text = match value {
+            Value::String(s) => s,
+            value => value.to_string(),
+        };
+        self.distinct.insert(text);
+    }
+
+    /// Computes the statistics of the values added.
+    pub fn finish(mut self) -> FieldStats {
+        self.values.sort_by(f64::total_cmp);
+        let values = &self.values;
+        let at = |p: f64| (!values.is_empty()).then(|| percentile(values, p));
+        FieldStats {
+            field: self.field.clone(),
+            count: self.count,
+            missing: self.missing,
+            cardinality: self.distinct.len() as u64,
+            numeric: values.len() as u64,
+            min: values.first().copied(),
+            max: values.last().copied(),
+            avg: (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64),
+            p50: at(50.0),
+            p90: at(90.0),
+            p95: at(95.0),
+            p99: at(99.0),
+        }
+    }
+}
+
+/// A log's value of `field`, looked up among its custom attributes for an
+/// `@` field, and otherwise as a top-level attribute, then a `key:value` tag.
+///
+/// A dotted custom attribute follows nested objects, or matches a key that
+/// itself contains dots. Null values count as missing.
+fn field_value(attributes: &Value, field: &str) -> Option<Value> {
+    let value = match field.strip_prefix('@') {
+        Some(path) => {
+            let custom = attributes.get("attributes")?;
+            custom
+                .get(path)
+                .or_else(|| {
+                    path.split('.')
+                        .try_fold(custom, |value, key| value.get(key))
+                })
+                .cloned()
+        }
+        None => attributes.get(field).cloned().or_else(|| {
+            let prefix = format!("{}:", field);
+            attributes
+                .get("tags")?
+                .as_array()?
+                .iter()
+                .filter_map(Value::as_str)
+                .find_map(|tag| tag.strip_prefix(&prefix))
+                .map(Value::from)
+        }),
+    };
+    value.filter(|v| !v.is_null())
+}
+
+/// Nearest-rank percentile of sorted, non-empty values.
+pub(crate) fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_numeric_percentiles() {
+        let mut collector = FieldCollector::new("@http.latency");
+        for ms in 1..=100 {
+            collector.add(&json!({"attributes": {"attributes": {"http": {"latency": ms}}}}));
+        }
+        // Flattened key
+        collector.add(&json!({"attributes": {"attributes": {"http.latency": 1000}}}));
+        collector.add(&json!({"attributes": {"attributes": {"http": {"latency": null}}}}));
+
+        let stats = collector.finish();
+        assert_eq!(stats.count, 101);
+        assert_eq!(stats.missing, 1);
+        assert_eq!(stats.min, Some(1.0));
+        assert_eq!(stats.max, Some(1000.0));
+        assert_eq!(stats.p50, Some(51.0));
+        assert_eq!(stats.p99, Some(100.0));
+    }
+
+    #[test]
+    fn test_string_field_from_tags() {
+        let mut collector = FieldCollector::new("env");
+        for tags in [["env:prod"], ["env:prod"], ["env:staging"], ["team:web"]] {
+            collector.add(&json!({"attributes": {"tags": tags}}));
+        }
+
+        let stats = collector.finish();
+        assert_eq!((stats.count, stats.missing), (3, 1));
+        assert_eq!(stats.cardinality, 2);
+        assert_eq!(stats.numeric, 0);
+        assert_eq!(stats.avg, None);
+        assert_eq!(
+            serde_json::to_value(&stats).unwrap()["p95"],
+            serde_json::Value::Null
+        );
+    }
+}
@@ -0,0 +1,196 @@
+//! Local usage metrics (`telemetry.ndjson` in the state dir).
+//!
+//! When turned on in the user configuration file, every command ddog runs is
+//! appended as one line with its duration and, if it failed, the kind of
+//! error. Only the command's name is kept: no queries, arguments, hosts, or
+//! credentials. Nothing is sent anywhere; `ddog telemetry report` summarizes
+//! the file, and platform teams collect it however they see fit.
+//!
+//! ```toml
+//! [telemetry]
+//! enabled = true
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::config::{UserConfig, state_dir};
+use crate::error::AppError;
+use crate::history;
+use crate::stats::percentile;
+
+/// Name of the telemetry file in the state directory.
+pub const TELEMETRY_FILE: &str = "telemetry.ndjson";
+
+/// Most events kept; older events are dropped when the file grows past this.
+pub const MAX_EVENTS: usize = 10_000;
+
+/// One command run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageEvent {
+    /// When the command finished (RFC3339, UTC)
+    pub timestamp: String,
+
+    /// Command path, such as `logs search`
+    pub command: String,
+
+    /// Wall-clock duration in milliseconds
+    pub duration_ms: u64,
+
+    /// Kind of error the command failed with (see
+    /// [`AppError::kind`](crate::error::AppError::kind)), `None` on success
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Returns the path of the telemetry file (`telemetry.ndjson` in [`state_dir`]).
+pub fn telemetry_path() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join(TELEMETRY_FILE))
+}
+
+/// Whether usage is recorded: only when `[telemetry] enabled = true`.
+pub fn is_enabled(config: &UserConfig) -> bool {
+    config.telemetry.enabled
+}
+
+/// Loads usage events, oldest first, skipping lines that don't parse.
+///
+/// # Errors
+///
+/// Returns `AppError::Config` if the file exists but cannot be read.
+pub fn load(path: &Path) -> Result<Vec<UsageEvent>, AppError> {
+    history::load_lines(path)
+}
+
+/// Appends an event, keeping the most recent [`MAX_EVENTS`].
+///
+/// # Errors
+///
+/// Returns `AppError::Config` if the file cannot be written.
+pub fn append(path: &Path, event: &UsageEvent) -> Result<(), AppError> {
+    history::append_line(path, event, MAX_EVENTS)
+}
+
+/// Usage of one command.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CommandUsage {
+    /// Command path, such as `logs search`
+    pub command: String,
+
+    /// Times the command ran
+    pub runs: u64,
+
+    /// Runs that failed
+    pub errors: u64,
+
+    /// Failed runs per error kind
+    pub error_kinds: BTreeMap<String, u64>,
+
+    /// Median duration, in milliseconds
+    pub p50_ms: f64,
+
+    /// 95th percentile duration, in milliseconds
+    pub p95_ms: f64,
+
+    /// When the command last ran
+    pub last_run: String,
+}
+
+/// Summarizes events per command, most run first.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::telemetry::{UsageEvent, summarize};
+///
+/// let event = |command: &str, duration_ms: u64, error: Option<&str>| UsageEvent {
+///     timestamp: "2024-01-15T10:00:00Z".to_string(),
+///     command: command.to_string(),
+///     duration_ms,
+///     error: error.map(String::from),
+/// };
+/// let usage = summarize(&[
+///     event("logs search", 800, None),
+///     event("logs search", 1200, Some("rate_limited")),
+///     event("metrics query", 300, None),
+/// ]);
+/// assert_eq!(usage[0].command, "logs search");
+/// assert_eq!((usage[0].runs, usage[0].errors), (2, 1));
+/// assert_eq!(usage[0].error_kinds["rate_limited"], 1);
+/// ```
+pub fn summarize(events: &[UsageEvent]) -> Vec<CommandUsage> {
+    let mut commands: BTreeMap<&str, Vec<&UsageEvent>> = BTreeMap::new();
+    for event in events {
+        commands.entry(&event.command).or_default().push(event);
+    }
+
+    let mut usage: Vec<CommandUsage> = commands
+        .into_iter()
+        .map(|(command, events)| {
+            let mut durations: Vec<f64> = events.iter().map(|e| e.duration_ms as f64).collect();
+            durations.sort_by(f64::total_cmp);
+            let mut error_kinds = BTreeMap::new();
+            for kind in events.iter().filter_map(|e| e.error.as_ref()) {
+                *error_kinds.entry(kind.clone()).or_default() += 1;
+            }
+            CommandUsage {
+                command: command.to_string(),
+                runs: events.len() as u64,
+                errors: error_kinds.values().sum(),
+                error_kinds,
+                p50_ms: percentile(&durations, 50.0),
+                p95_ms: percentile(&durations, 95.0),
+                last_run: events
+                    .iter()
+                    .map(|e| e.timestamp.as_str())
+                    .max()
+                    .unwrap_or_default()
+                    .to_string(),
+            }
+        })
+        .collect();
+    usage.sort_by_key(|u| Reverse(u.runs));
+    usage
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(command: &str, timestamp: &str) -> UsageEvent {
+        UsageEvent {
+            timestamp: timestamp.to_string(),
+            command: command.to_string(),
+            duration_ms: 100,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_append_and_summarize() {
+        let dir = std::env::temp_dir().join(format!("ddog-telemetry-{}", std::process::id()));
+        let path = dir.join(TELEMETRY_FILE);
+
+        assert!(load(&path).unwrap().is_empty());
+        append(&path, &event("spans search", "2024-01-15T10:00:00Z")).unwrap();
+        append(&path, &event("logs search", "2024-01-15T11:00:00Z")).unwrap();
+        append(&path, &event("logs search", "2024-01-15T12:00:00Z")).unwrap();
+
+        let usage = summarize(&load(&path).unwrap());
+        let runs: Vec<(&str, u64)> = usage.iter().map(|u| (u.command.as_str(), u.runs)).collect();
+        assert_eq!(runs, [("logs search", 2), ("spans search", 1)]);
+        assert_eq!(usage[0].last_run, "2024-01-15T12:00:00Z");
+        assert_eq!(usage[0].errors, 0);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_off_unless_enabled() {
+        assert!(!is_enabled(&UserConfig::default()));
+        let config: UserConfig = toml::from_str("[telemetry]\nenabled = true\n").unwrap();
+        assert!(is_enabled(&config));
+    }
+}
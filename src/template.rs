@@ -0,0 +1,194 @@
+//! Request body templates for `ddog api post`.
+//!
+//! A template is JSON with `{{name}}` placeholders, filled from `--var
+//! name=value` pairs. A string that is exactly one placeholder is replaced
+//! by the value as JSON when it parses as JSON (so `"{{threshold}}"` with
+//! `threshold=90` becomes the number `90`), and by the plain string
+//! otherwise; placeholders inside longer strings are replaced as text.
+
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::error::AppError;
+
+/// Parses a `--var` argument of the form `name=value`.
+///
+/// # Errors
+///
+/// Returns an error message if there is no `=` or the name is empty.
+pub fn parse_var(var: &str) -> Result<(String, String), String> {
+    match var.split_once('=') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_string(), value.to_string()))
+        }
+        _ => Err(format!("invalid variable '{}' (expected NAME=VALUE)", var)),
+    }
+}
+
+/// Parses a template and fills in its placeholders.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::template::render;
+/// use serde_json::json;
+/// use std::collections::BTreeMap;
+///
+/// let vars = BTreeMap::from([
+///     ("service".to_string(), "checkout".to_string()),
+///     ("threshold".to_string(), "90".to_string()),
+/// ]);
+/// let body = render(r#"{"name": "{{service}} errors", "critical": "{{threshold}}"}"#, &vars).unwrap();
+/// assert_eq!(body, json!({"name": "checkout errors", "critical": 90}));
+/// ```
+///
+/// # Errors
+///
+/// Returns `AppError::Config` if the template isn't valid JSON, a placeholder
+/// has no variable, or a variable isn't used by any placeholder.
+pub fn render(template: &str, vars: &BTreeMap<String, String>) -> Result<Value, AppError> {
+    let mut body: Value = serde_json::from_str(template)
+        .map_err(|e| AppError::Config(format!("Request body is not valid JSON: {}", e)))?;
+
+    let mut used = BTreeSet::new();
+    let mut missing = BTreeSet::new();
+    fill(&mut body, vars, &mut used, &mut missing);
+
+    if !missing.is_empty() {
+        return Err(AppError::Config(format!(
+            "No value for placeholder(s) {} (pass --var NAME=VALUE)",
+            missing
+                .iter()
+                .map(|name| format!("{{{{{}}}}}", name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )));
+    }
+    let unused: Vec<&str> = vars
+        .keys()
+        .filter(|name| !used.contains(name.as_str()))
+        .map(String::as_str)
+        .collect();
+    if !unused.is_empty() {
+        return Err(AppError::Config(format!(
+            "Variable(s) not used by the body: {}",
+            unused.join(", ")
+        )));
+    }
+    Ok(body)
+}
+
+fn fill<'a>(
+    value: &mut Value,
+    vars: &'a BTreeMap<String, String>,
+    used: &mut BTreeSet<&'a str>,
+    missing: &mut BTreeSet<String>,
+) {
+    match value {
+        Value::Object(fields) => {
+            for field in fields.values_mut() {
+                fill(field, vars, used, missing);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                fill(item, vars, used, missing);
+            }
+        }
+        Value::String(text) => {
+            let mut lookup = |name: &str| match vars.get_key_value(name) {
+                Some((key, value)) => {
+                    used.insert(key.as_str());
+                    Some(value.as_str())
+                }
+                None => {
+                    missing.insert(name.to_string());
+                    None
+                }
+            };
+
+            if let Some(name) = whole_placeholder(text) {
+                if let Some(var) = lookup(name) {
+                    *value = serde_json::from_str(var)
+                        .unwrap_or_else(|_| Value::String(var.to_string()));
+                }
+                return;
+            }
+
+            let mut filled = String::new();
+            let mut rest = text.as_str();
+            while let Some(start) = rest.find("{{") {
+                let Some(end) = rest[start..].find("}}") else {
+                    break;
+                };
+                let name = rest[start + 2..start + end].trim();
+                filled.push_str(&rest[..start]);
+                filled.push_str(lookup(name).unwrap_or_default());
+                rest = &rest[start + end + 2..];
+            }
+            filled.push_str(rest);
+            *text = filled;
+        }
+        _ => {}
+    }
+}
+
+/// The name in a string that is exactly one placeholder, like `{{name}}`.
+fn whole_placeholder(text: &str) -> Option<&str> {
+    let name = text.strip_prefix("{{")?.strip_suffix("}}")?.trim();
+    (!name.is_empty() && !name.contains(['{', '}'])).then_some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn vars(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_render() {
+        let body = render(
+            r#"{"tags": ["team:{{team}}", "{{ env }}"], "options": {"notify": "{{notify}}", "id": "{{id}}"}}"#,
+            &vars(&[("team", "pay"), ("env", "env:prod"), ("notify", "true"), ("id", "abc")]),
+        )
+        .unwrap();
+        assert_eq!(
+            body,
+            json!({"tags": ["team:pay", "env:prod"], "options": {"notify": true, "id": "abc"}})
+        );
+    }
+
+    #[test]
+    fn test_render_errors() {
+        assert!(matches!(
+            render("{not json", &vars(&[])),
+            Err(AppError::Config(_))
+        ));
+        let Err(AppError::Config(missing)) =
+            render(r#"{"a": "{{x}} {{y}}"}"#, &vars(&[("x", "1")]))
+        else {
+            panic!("expected a missing variable error");
+        };
+        assert!(missing.contains("{{y}}"));
+        assert!(matches!(
+            render(r#"{"a": 1}"#, &vars(&[("x", "1")])),
+            Err(AppError::Config(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_var() {
+        assert_eq!(
+            parse_var("query=a=b").unwrap(),
+            ("query".to_string(), "a=b".to_string())
+        );
+        assert!(parse_var("novalue").is_err());
+        assert!(parse_var("=x").is_err());
+    }
+}
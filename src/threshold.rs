@@ -0,0 +1,179 @@
+//! Thresholds for `ddog logs check` and `ddog metrics scalar`.
+//!
+//! A logs check passes when the number of matching logs is within
+//! `[min, max]`. `--max 0` gates on "no such logs", and `--min 1` on "at
+//! least one", such as a heartbeat that must keep logging.
+//!
+//! A metrics check grades a value against Nagios-style warning and critical
+//! [`Levels`].
+
+use serde::Serialize;
+
+/// Bounds a count must be within.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Threshold {
+    /// Smallest passing count
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min: Option<u64>,
+
+    /// Largest passing count
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max: Option<u64>,
+}
+
+impl Threshold {
+    /// Creates a threshold; without either bound, the maximum is 0.
+    pub fn new(min: Option<u64>, max: Option<u64>) -> Self {
+        match (min, max) {
+            (None, None) => Self {
+                min: None,
+                max: Some(0),
+            },
+            (min, max) => Self { min, max },
+        }
+    }
+
+    /// Checks a count, describing the violated bound if it fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ddog::threshold::Threshold;
+    ///
+    /// let threshold = Threshold::new(None, None);
+    /// assert!(threshold.check(0).is_ok());
+    /// assert_eq!(threshold.check(3).unwrap_err(), "3 match, more than --max 0");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a description of the failure when `count` is out of bounds.
+    pub fn check(&self, count: u64) -> Result<(), String> {
+        if let Some(max) = self.max
+            && count > max
+        {
+            return Err(format!("{} match, more than --max {}", count, max));
+        }
+        if let Some(min) = self.min
+            && count < min
+        {
+            return Err(format!("{} match, fewer than --min {}", count, min));
+        }
+        Ok(())
+    }
+}
+
+/// Grade of a value checked against [`Levels`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    /// Within both levels
+    Ok,
+
+    /// Past the warning level
+    Warning,
+
+    /// Past the critical level
+    Critical,
+
+    /// No value to check
+    Unknown,
+}
+
+/// Warning and critical levels for a value.
+///
+/// Values above a level are past it, unless the critical level is below the
+/// warning level: then lower is worse, and values below a level are past it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Levels {
+    /// Warning level
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warn: Option<f64>,
+
+    /// Critical level
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crit: Option<f64>,
+}
+
+impl Levels {
+    /// Whether either level is set.
+    pub fn is_set(&self) -> bool {
+        self.warn.is_some() || self.crit.is_some()
+    }
+
+    /// Grades a value; a missing value is [`CheckStatus::Unknown`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ddog::threshold::{CheckStatus, Levels};
+    ///
+    /// let cpu = Levels { warn: Some(80.0), crit: Some(95.0) };
+    /// assert_eq!(cpu.status(Some(50.0)), CheckStatus::Ok);
+    /// assert_eq!(cpu.status(Some(90.0)), CheckStatus::Warning);
+    ///
+    /// let free_disk = Levels { warn: Some(20.0), crit: Some(5.0) };
+    /// assert_eq!(free_disk.status(Some(3.0)), CheckStatus::Critical);
+    /// ```
+    pub fn status(&self, value: Option<f64>) -> CheckStatus {
+        let Some(value) = value else {
+            return CheckStatus::Unknown;
+        };
+        let lower_is_worse =
+            matches!((self.warn, self.crit), (Some(warn), Some(crit)) if crit < warn);
+        let past = |level: Option<f64>| {
+            level.is_some_and(|level| match lower_is_worse {
+                true => value < level,
+                false => value > level,
+            })
+        };
+        if past(self.crit) {
+            CheckStatus::Critical
+        } else if past(self.warn) {
+            CheckStatus::Warning
+        } else {
+            CheckStatus::Ok
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_threshold_bounds() {
+        let threshold = Threshold::new(Some(1), Some(10));
+        assert!(threshold.check(1).is_ok());
+        assert!(threshold.check(10).is_ok());
+        assert_eq!(
+            threshold.check(0).unwrap_err(),
+            "0 match, fewer than --min 1"
+        );
+        assert!(threshold.check(11).is_err());
+
+        let heartbeat = Threshold::new(Some(1), None);
+        assert_eq!(heartbeat.max, None);
+        assert!(heartbeat.check(1_000_000).is_ok());
+    }
+
+    #[test]
+    fn test_levels_status() {
+        let levels = Levels {
+            warn: Some(80.0),
+            crit: Some(95.0),
+        };
+        assert_eq!(levels.status(Some(80.0)), CheckStatus::Ok);
+        assert_eq!(levels.status(Some(95.0)), CheckStatus::Warning);
+        assert_eq!(levels.status(Some(95.5)), CheckStatus::Critical);
+        assert_eq!(levels.status(None), CheckStatus::Unknown);
+
+        let crit_only = Levels {
+            warn: None,
+            crit: Some(0.0),
+        };
+        assert_eq!(crit_only.status(Some(-1.0)), CheckStatus::Ok);
+        assert_eq!(crit_only.status(Some(1.0)), CheckStatus::Critical);
+        assert!(!Levels::default().is_set());
+    }
+}
@@ -24,88 +24,1101 @@
 //!
 //! For the Metrics API (V1), which requires Unix timestamps in seconds, use the
 //! `parse_to_unix_seconds` function to convert time strings to i64.
+//!
+//! On top of these, the CLI accepts natural-language phrases such as `today`,
+//! `yesterday 9am`, `2 hours ago`, and `last monday 14:30`, and ISO8601 times
+//! without a UTC offset (`2024-01-15T10:00:00`). Both are resolved client-side
+//! with [`resolve_local`] in the chosen [`Timezone`].
+//!
+//! [`parse_time`] parses these (all but offset-less times) into a
+//! [`TimeSpec`], with errors that point at the offending token and suggest a
+//! fix (`now-1mth` → `now-1mo`, `last mondy` → `last monday`).
+//!
+//! Relative times can also be snapped to calendar boundaries, Datadog-style:
+//! `now/d` is the start of today, `now-1d/d` the start of yesterday, and
+//! `now/w` the start of the week (Monday). See [`parse_date_math`].
+
+use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, Local, Months, NaiveDate, NaiveDateTime, NaiveTime,
+    TimeZone, Timelike, Utc, Weekday,
+};
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
 
 use crate::error::AppError;
 
-/// Validates that a time string is in a format Datadog accepts.
+/// Timezone used to interpret calendar-based times like `today` or `yesterday 9am`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Timezone {
+    /// The system's local timezone
+    #[default]
+    Local,
+
+    /// An IANA timezone such as `Europe/London`
+    Named(chrono_tz::Tz),
+}
+
+impl FromStr for Timezone {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("local") {
+            return Ok(Timezone::Local);
+        }
+        if s.eq_ignore_ascii_case("utc") {
+            return Ok(Timezone::Named(chrono_tz::UTC));
+        }
+        s.parse::<chrono_tz::Tz>()
+            .map(Timezone::Named)
+            .map_err(|_| {
+                format!(
+                    "unknown timezone '{}' (use an IANA name like Europe/London, or 'local')",
+                    s
+                )
+            })
+    }
+}
+
+impl fmt::Display for Timezone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Timezone::Local => write!(f, "local"),
+            Timezone::Named(tz) => write!(f, "{}", tz.name()),
+        }
+    }
+}
+
+impl Timezone {
+    /// Interprets a wall-clock time in this timezone.
+    ///
+    /// Returns `None` for times skipped by a DST transition; ambiguous times
+    /// resolve to the earlier instant.
+    pub fn from_local(&self, naive: NaiveDateTime) -> Option<DateTime<FixedOffset>> {
+        match self {
+            Timezone::Local => Local
+                .from_local_datetime(&naive)
+                .earliest()
+                .map(|t| t.fixed_offset()),
+            Timezone::Named(tz) => tz
+                .from_local_datetime(&naive)
+                .earliest()
+                .map(|t| t.fixed_offset()),
+        }
+    }
+
+    /// Converts an instant into this timezone.
+    pub fn convert<Z: TimeZone>(&self, time: &DateTime<Z>) -> DateTime<FixedOffset> {
+        match self {
+            Timezone::Local => time.with_timezone(&Local).fixed_offset(),
+            Timezone::Named(tz) => time.with_timezone(tz).fixed_offset(),
+        }
+    }
+}
+
+/// Resolves times whose meaning depends on the timezone: natural-language
+/// phrases, snapped date math (`now-1d/d`), and ISO8601 times without a UTC
+/// offset.
 ///
-/// Datadog accepts three formats:
-/// 1. **Relative times**: "now", "now-NUMBERUNIT" where unit is s, m, h, d, w, mo, y
-/// 2. **ISO8601 timestamps**: "2024-01-15T10:00:00Z" or with timezone offsets
-/// 3. **Unix timestamps**: milliseconds since Unix epoch (numeric string)
+/// Returns `None` for everything else (`now-1h`, ISO8601 with an offset,
+/// Unix ms), which callers pass to Datadog unchanged.
+pub fn resolve_local(input: &str, timezone: Timezone) -> Option<DateTime<FixedOffset>> {
+    resolve_natural(input, timezone)
+        .or_else(|| resolve_snapped(input, timezone))
+        .or_else(|| resolve_local_iso(input, timezone))
+}
+
+/// Resolves any supported time format to an absolute time.
 ///
-/// This function performs basic validation. The actual parsing is done by Datadog's API.
+/// # Errors
 ///
-/// # Arguments
+/// Returns `AppError::InvalidQuery` if `input` is not a recognized time.
+pub fn resolve_absolute(
+    input: &str,
+    timezone: Timezone,
+) -> Result<DateTime<FixedOffset>, AppError> {
+    if let Some(time) = resolve_local(input, timezone) {
+        return Ok(time);
+    }
+    if let Some(time) = parse_date_math(input, &Utc::now()) {
+        return Ok(time.fixed_offset());
+    }
+    if let Ok(time) = DateTime::parse_from_rfc3339(input) {
+        return Ok(time);
+    }
+    input
+        .parse::<i64>()
+        .ok()
+        .and_then(|ms| Utc.timestamp_millis_opt(ms).single())
+        .map(|time| time.fixed_offset())
+        .ok_or_else(|| match parse_time(input) {
+            Err(e) => e.into(),
+            Ok(_) => AppError::InvalidQuery(format!("Time out of range: {}", input)),
+        })
+}
+
+/// Formats accepted for ISO8601 times without a UTC offset.
+const LOCAL_ISO_FORMATS: [&str; 4] = [
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M",
+];
+
+/// Resolves an ISO8601 time without a UTC offset, such as `2024-01-15T10:00:00`
+/// or a bare date (`2024-01-15`, meaning midnight), in `timezone`.
 ///
-/// * `time_str` - Time string to validate
+/// # Examples
 ///
-/// # Returns
+/// ```
+/// use ddog::time::{resolve_local_iso, Timezone};
 ///
-/// `true` if the format appears valid, `false` otherwise
-pub fn is_valid_time_format(time_str: &str) -> bool {
-    if time_str.is_empty() {
-        return false;
+/// let london: Timezone = "Europe/London".parse().unwrap();
+/// let time = resolve_local_iso("2024-07-01T09:00:00", london).unwrap();
+/// assert_eq!(time.to_rfc3339(), "2024-07-01T09:00:00+01:00");
+///
+/// // Times with an explicit offset are left to Datadog
+/// assert!(resolve_local_iso("2024-07-01T09:00:00Z", london).is_none());
+/// ```
+pub fn resolve_local_iso(input: &str, timezone: Timezone) -> Option<DateTime<FixedOffset>> {
+    let naive = LOCAL_ISO_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(input, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(input, "%Y-%m-%d")
+                .ok()
+                .map(|date| date.and_time(NaiveTime::MIN))
+        })?;
+    timezone.from_local(naive)
+}
+
+/// Resolves a natural-language time phrase to an absolute time in `timezone`.
+///
+/// Returns `None` when `input` is not a natural-language phrase, so callers
+/// can pass other formats (`now-1h`, ISO8601, Unix ms) through unchanged.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::time::{resolve_natural, Timezone};
+///
+/// assert!(resolve_natural("yesterday 9am", Timezone::Local).is_some());
+/// assert!(resolve_natural("now-1h", Timezone::Local).is_none());
+/// ```
+pub fn resolve_natural(input: &str, timezone: Timezone) -> Option<DateTime<FixedOffset>> {
+    match timezone {
+        Timezone::Local => parse_natural(input, &Local::now()).map(|t| t.fixed_offset()),
+        Timezone::Named(tz) => {
+            parse_natural(input, &Utc::now().with_timezone(&tz)).map(|t| t.fixed_offset())
+        }
     }
+}
 
-    // Check for relative time format: "now" or "now-<number><unit>"
-    if time_str == "now" {
-        return true;
+/// Parses a natural-language time phrase relative to `now`.
+///
+/// Supported phrases (case-insensitive):
+/// - `today`, `yesterday` - start of the day, or a time of day on it (`yesterday 9am`)
+/// - `last <weekday>` - the most recent such day before today (`last monday 14:30`)
+/// - `<N> <unit> ago` - seconds, minutes, hours, days, weeks, months, or years
+///
+/// Times of day can be written as `9am`, `9:30 pm`, `14:30`, `noon`, or `midnight`.
+pub fn parse_natural<Z: TimeZone>(input: &str, now: &DateTime<Z>) -> Option<DateTime<Z>> {
+    parse_natural_phrase(input)?.ok()?.resolve(now)
+}
+
+/// A natural-language time phrase, as parsed by [`parse_time`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NaturalTime {
+    /// `<N> <unit> ago`
+    Ago(u32, SnapUnit),
+    /// A time of day on a recent day: `today`, `yesterday 9am`, `last monday 14:30`
+    On(NaturalDay, NaiveTime),
+}
+
+/// The day of a [`NaturalTime::On`] phrase.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NaturalDay {
+    Today,
+    Yesterday,
+    /// The most recent such day before today
+    Last(Weekday),
+}
+
+impl NaturalTime {
+    /// Resolves the phrase relative to `now`, in `now`'s timezone.
+    ///
+    /// Returns `None` for a time chrono can't represent, or a time of day
+    /// skipped by a DST change.
+    pub fn resolve<Z: TimeZone>(self, now: &DateTime<Z>) -> Option<DateTime<Z>> {
+        let today = now.date_naive();
+        let (day, time) = match self {
+            NaturalTime::Ago(count, unit) => return subtract_units(now, count, unit),
+            NaturalTime::On(day, time) => (day, time),
+        };
+        let date = match day {
+            NaturalDay::Today => today,
+            NaturalDay::Yesterday => today.pred_opt()?,
+            NaturalDay::Last(weekday) => {
+                let mut date = today.pred_opt()?;
+                while date.weekday() != weekday {
+                    date = date.pred_opt()?;
+                }
+                date
+            }
+        };
+        // `earliest` picks the first instant when a DST change makes the time ambiguous
+        now.timezone()
+            .from_local_datetime(&date.and_time(time))
+            .earliest()
     }
+}
 
-    if let Some(rest) = time_str.strip_prefix("now-") {
-        if rest.is_empty() {
-            return false;
-        }
+/// Parses a natural-language time phrase, or returns `None` if `input`
+/// doesn't start like one.
+fn parse_natural_phrase(input: &str) -> Option<Result<NaturalTime, TimeParseError>> {
+    let lower = input.to_ascii_lowercase();
+    // Words with their byte ranges, which lowercasing ASCII leaves in place
+    let words: Vec<(&str, Range<usize>)> = lower
+        .split_whitespace()
+        .map(|word| {
+            let start = word.as_ptr() as usize - lower.as_ptr() as usize;
+            (word, start..start + word.len())
+        })
+        .collect();
+    let end = input.trim_end().len();
 
-        // Parse number and unit
-        let mut chars = rest.chars();
-        let mut num_str = String::new();
+    let (day, rest) = match words.as_slice() {
+        [("today", _), rest @ ..] => (NaturalDay::Today, rest),
+        [("yesterday", _), rest @ ..] => (NaturalDay::Yesterday, rest),
+        [("last", _), rest @ ..] => {
+            let Some(((day, span), rest)) = rest.split_first() else {
+                return Some(Err(TimeParseError::new(
+                    input,
+                    end..end,
+                    "expected a weekday after 'last'",
+                )));
+            };
+            let Some(weekday) = parse_weekday(day) else {
+                let suggestion = closest_weekday(day)
+                    .map(|name| format!("{}{}{}", &input[..span.start], name, &input[span.end..]));
+                return Some(Err(TimeParseError::new(
+                    input,
+                    span.clone(),
+                    format!("unknown weekday '{}'", day),
+                )
+                .suggest(suggestion)));
+            };
+            (NaturalDay::Last(weekday), rest)
+        }
+        [(count, count_span), rest @ ..]
+            if !rest.is_empty() && count.chars().all(|c| c.is_ascii_digit()) =>
+        {
+            return Some(parse_ago(input, count, count_span.clone(), rest));
+        }
+        _ => return None,
+    };
 
-        // Collect digits
-        while let Some(c) = chars.next() {
-            if c.is_ascii_digit() {
-                num_str.push(c);
-            } else {
-                // Check if it's a valid unit
-                let unit = format!("{}{}", c, chars.as_str());
-                return matches!(unit.as_str(), "s" | "m" | "h" | "d" | "w" | "mo" | "y")
-                    && !num_str.is_empty();
+    let time = match (rest.first(), rest.last()) {
+        (Some((_, first)), Some((_, last))) => {
+            let words: String = rest.iter().map(|(word, _)| *word).collect();
+            match parse_time_of_day(&words) {
+                Ok(time) => time,
+                Err(message) => {
+                    let span = first.start..last.end;
+                    return Some(Err(TimeParseError::new(input, span, message)));
+                }
             }
         }
+        _ => NaiveTime::MIN,
+    };
+    Some(Ok(NaturalTime::On(day, time)))
+}
 
-        // If we got here, there was no unit
-        return false;
+/// Parses the unit and `ago` following the count of `<N> <unit> ago`.
+fn parse_ago(
+    input: &str,
+    count: &str,
+    count_span: Range<usize>,
+    rest: &[(&str, Range<usize>)],
+) -> Result<NaturalTime, TimeParseError> {
+    let count: u32 = count
+        .parse()
+        .map_err(|_| TimeParseError::new(input, count_span, "number is too large"))?;
+    let (unit, unit_span) = &rest[0];
+    let unit = natural_unit(unit).ok_or_else(|| {
+        TimeParseError::new(
+            input,
+            unit_span.clone(),
+            format!(
+                "unknown unit '{}' (expected seconds, minutes, hours, days, weeks, months, or years)",
+                unit
+            ),
+        )
+    })?;
+    match &rest[1..] {
+        [("ago", _)] => Ok(NaturalTime::Ago(count, unit)),
+        [] => Err(TimeParseError::new(
+            input,
+            unit_span.end..unit_span.end,
+            "expected 'ago' after the unit",
+        )
+        .suggest(Some(format!("{} ago", input.trim_end())))),
+        [(word, span), ..] => Err(TimeParseError::new(
+            input,
+            span.clone(),
+            format!("unexpected '{}' (expected 'ago')", word),
+        )),
+    }
+}
+
+/// Parses the units of `<N> <unit> ago`, singular, plural, or short.
+fn natural_unit(unit: &str) -> Option<SnapUnit> {
+    let unit = unit.strip_suffix('s').unwrap_or(unit);
+    match unit {
+        "sec" => Some(SnapUnit::Second),
+        "min" => Some(SnapUnit::Minute),
+        "hr" => Some(SnapUnit::Hour),
+        "second" | "minute" | "hour" | "day" | "week" | "month" | "year" => unit.parse().ok(),
+        _ => None,
     }
+}
+
+/// Parses `9am`, `9:30pm`, `14:30`, `noon`, or `midnight`, or describes what
+/// is wrong with it.
+fn parse_time_of_day(s: &str) -> Result<NaiveTime, String> {
+    let unrecognized = || {
+        format!(
+            "unrecognized time of day '{}' (expected e.g. 9am, 9:30pm, 14:30, noon, or midnight)",
+            s
+        )
+    };
+    match s {
+        "noon" => return NaiveTime::from_hms_opt(12, 0, 0).ok_or_else(unrecognized),
+        "midnight" => return Ok(NaiveTime::MIN),
+        _ => {}
+    }
+
+    let (body, is_pm) = if let Some(body) = s.strip_suffix("am") {
+        (body, Some(false))
+    } else if let Some(body) = s.strip_suffix("pm") {
+        (body, Some(true))
+    } else {
+        (s, None)
+    };
+
+    let (hour, minute) = match body.split_once(':') {
+        Some((h, m)) => (h.parse::<u32>(), m.parse::<u32>()),
+        None => (body.parse::<u32>(), Ok(0)),
+    };
+    let (Ok(hour), Ok(minute)) = (hour, minute) else {
+        return Err(unrecognized());
+    };
 
-    // Check for ISO8601 format (basic check)
-    // Format: YYYY-MM-DDTHH:MM:SS[.SSS][Z|+HH:MM|-HH:MM]
-    if time_str.len() >= 19 {
-        // Check for date part: YYYY-MM-DD
-        let date_part = &time_str[0..10];
-        if date_part.chars().filter(|c| c == &'-').count() == 2 {
-            // Check for T separator
-            if time_str.len() > 10 && time_str.chars().nth(10) == Some('T') {
-                // Basic ISO8601 format detected
-                return true;
+    let hour = match is_pm {
+        Some(_) if hour == 0 || hour > 12 => {
+            return Err(format!(
+                "invalid hour {} (expected 1 to 12 with am or pm)",
+                hour
+            ));
+        }
+        Some(pm) => hour % 12 + if pm { 12 } else { 0 },
+        None if hour > 23 => {
+            return Err(format!("invalid hour {} (expected 0 to 23)", hour));
+        }
+        None => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, 0)
+        .ok_or_else(|| format!("invalid minute {} (expected 0 to 59)", minute))
+}
+
+/// Parses a weekday name or its three-letter abbreviation.
+fn parse_weekday(s: &str) -> Option<Weekday> {
+    let weekday = match s.get(..3)? {
+        "mon" => Weekday::Mon,
+        "tue" => Weekday::Tue,
+        "wed" => Weekday::Wed,
+        "thu" => Weekday::Thu,
+        "fri" => Weekday::Fri,
+        "sat" => Weekday::Sat,
+        "sun" => Weekday::Sun,
+        _ => return None,
+    };
+    // Reject near-misses like "monkey" while allowing "mon" and "monday"
+    (s.len() == 3 || weekday_name(weekday) == s).then_some(weekday)
+}
+
+/// Returns the weekday name within two edits of `input`, if any.
+fn closest_weekday(input: &str) -> Option<&'static str> {
+    [
+        Weekday::Mon,
+        Weekday::Tue,
+        Weekday::Wed,
+        Weekday::Thu,
+        Weekday::Fri,
+        Weekday::Sat,
+        Weekday::Sun,
+    ]
+    .into_iter()
+    .map(weekday_name)
+    .map(|name| (edit_distance(input, name), name))
+    .filter(|(distance, _)| *distance <= 2)
+    .min_by_key(|(distance, _)| *distance)
+    .map(|(_, name)| name)
+}
+
+fn weekday_name(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "monday",
+        Weekday::Tue => "tuesday",
+        Weekday::Wed => "wednesday",
+        Weekday::Thu => "thursday",
+        Weekday::Fri => "friday",
+        Weekday::Sat => "saturday",
+        Weekday::Sun => "sunday",
+    }
+}
+
+/// Subtracts `count` units from `now`.
+///
+/// Returns `None` for a time before the earliest one chrono can represent.
+fn subtract_units<Z: TimeZone>(
+    now: &DateTime<Z>,
+    count: u32,
+    unit: SnapUnit,
+) -> Option<DateTime<Z>> {
+    let count_i64 = i64::from(count);
+    let now = now.clone();
+
+    let delta = match unit {
+        SnapUnit::Second => Duration::try_seconds(count_i64),
+        SnapUnit::Minute => Duration::try_minutes(count_i64),
+        SnapUnit::Hour => Duration::try_hours(count_i64),
+        SnapUnit::Day => Duration::try_days(count_i64),
+        SnapUnit::Week => Duration::try_weeks(count_i64),
+        SnapUnit::Month => return now.checked_sub_months(Months::new(count)),
+        SnapUnit::Year => return now.checked_sub_months(Months::new(count.checked_mul(12)?)),
+    };
+    now.checked_sub_signed(delta?)
+}
+
+/// Calendar unit for snapping (`now/d`) and aligning time bounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapUnit {
+    Second,
+    Minute,
+    Hour,
+    Day,
+    /// Weeks start on Monday
+    Week,
+    Month,
+    Year,
+}
+
+impl FromStr for SnapUnit {
+    type Err = String;
+
+    /// Parses a date-math unit (`h`, `d`, `mo`) or its name (`hour`, `day`, `month`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "s" | "second" => Ok(SnapUnit::Second),
+            "m" | "minute" => Ok(SnapUnit::Minute),
+            "h" | "hour" => Ok(SnapUnit::Hour),
+            "d" | "day" => Ok(SnapUnit::Day),
+            "w" | "week" => Ok(SnapUnit::Week),
+            "mo" | "month" => Ok(SnapUnit::Month),
+            "y" | "year" => Ok(SnapUnit::Year),
+            _ => Err(format!(
+                "invalid unit '{}' (expected second, minute, hour, day, week, month, or year)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for SnapUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl SnapUnit {
+    /// Rounds `time` down to the start of the unit in its timezone.
+    ///
+    /// Returns `None` if the boundary falls in a DST gap.
+    pub fn floor<Z: TimeZone>(self, time: &DateTime<Z>) -> Option<DateTime<Z>> {
+        let naive = time.naive_local();
+        let date = naive.date();
+        let floored = match self {
+            SnapUnit::Second => date.and_hms_opt(naive.hour(), naive.minute(), naive.second())?,
+            SnapUnit::Minute => date.and_hms_opt(naive.hour(), naive.minute(), 0)?,
+            SnapUnit::Hour => date.and_hms_opt(naive.hour(), 0, 0)?,
+            SnapUnit::Day => date.and_time(NaiveTime::MIN),
+            SnapUnit::Week => {
+                let monday =
+                    date - Duration::days(i64::from(date.weekday().num_days_from_monday()));
+                monday.and_time(NaiveTime::MIN)
             }
+            SnapUnit::Month => date.with_day(1)?.and_time(NaiveTime::MIN),
+            SnapUnit::Year => NaiveDate::from_ymd_opt(date.year(), 1, 1)?.and_time(NaiveTime::MIN),
+        };
+        time.timezone().from_local_datetime(&floored).earliest()
+    }
+
+    /// Rounds `time` up to the next unit boundary; boundaries are unchanged.
+    pub fn ceil<Z: TimeZone>(self, time: &DateTime<Z>) -> Option<DateTime<Z>> {
+        let floor = self.floor(time)?;
+        if floor == *time {
+            return Some(floor);
+        }
+        let next = match self {
+            SnapUnit::Second | SnapUnit::Minute | SnapUnit::Hour => {
+                return Some(floor + self.fixed_duration()?);
+            }
+            SnapUnit::Day => floor.naive_local() + Duration::days(1),
+            SnapUnit::Week => floor.naive_local() + Duration::weeks(1),
+            SnapUnit::Month => floor.naive_local().checked_add_months(Months::new(1))?,
+            SnapUnit::Year => floor.naive_local().checked_add_months(Months::new(12))?,
+        };
+        time.timezone().from_local_datetime(&next).earliest()
+    }
+
+    /// Length of units that never vary with DST or the calendar.
+    fn fixed_duration(self) -> Option<Duration> {
+        match self {
+            SnapUnit::Second => Some(Duration::seconds(1)),
+            SnapUnit::Minute => Some(Duration::minutes(1)),
+            SnapUnit::Hour => Some(Duration::hours(1)),
+            _ => None,
+        }
+    }
+
+    /// Unit name understood by [`parse_natural`]'s `<N> <unit> ago` phrases.
+    fn name(self) -> &'static str {
+        match self {
+            SnapUnit::Second => "second",
+            SnapUnit::Minute => "minute",
+            SnapUnit::Hour => "hour",
+            SnapUnit::Day => "day",
+            SnapUnit::Week => "week",
+            SnapUnit::Month => "month",
+            SnapUnit::Year => "year",
+        }
+    }
+}
+
+/// Parses Datadog date math relative to `now`, with optional snapping.
+///
+/// Accepts `now`, `now-<N><unit>`, and either followed by `/<unit>` to round
+/// down to the start of that unit, e.g. `now-1d/d` for the start of yesterday
+/// or `now/w` for the start of this week. Units are `s`, `m`, `h`, `d`, `w`,
+/// `mo`, and `y`.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use ddog::time::parse_date_math;
+///
+/// let now = Utc.with_ymd_and_hms(2024, 1, 17, 15, 45, 0).unwrap();
+/// let start = parse_date_math("now-1d/d", &now).unwrap();
+/// assert_eq!(start.to_rfc3339(), "2024-01-16T00:00:00+00:00");
+/// ```
+pub fn parse_date_math<Z: TimeZone>(input: &str, now: &DateTime<Z>) -> Option<DateTime<Z>> {
+    let (expr, snap) = match input.split_once('/') {
+        Some((expr, unit)) => (expr, Some(parse_date_math_unit(unit)?)),
+        None => (input, None),
+    };
+
+    let rest = expr.strip_prefix("now")?;
+    let time = if rest.is_empty() {
+        now.clone()
+    } else {
+        let offset = rest.strip_prefix('-')?;
+        let unit_start = offset.find(|c: char| !c.is_ascii_digit())?;
+        let (count, unit) = offset.split_at(unit_start);
+        let count: u32 = count.parse().ok()?;
+        subtract_units(now, count, parse_date_math_unit(unit)?)?
+    };
+
+    match snap {
+        Some(unit) => unit.floor(&time),
+        None => Some(time),
+    }
+}
+
+/// Parses the short units used in date math (`h`, `d`, `mo`, ...).
+fn parse_date_math_unit(unit: &str) -> Option<SnapUnit> {
+    match unit {
+        "s" | "m" | "h" | "d" | "w" | "mo" | "y" => unit.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Resolves snapped date math such as `now-1d/d` in `timezone`.
+///
+/// Returns `None` for unsnapped expressions like `now-1h`, which Datadog
+/// resolves itself.
+pub fn resolve_snapped(input: &str, timezone: Timezone) -> Option<DateTime<FixedOffset>> {
+    if !input.contains('/') {
+        return None;
+    }
+    match timezone {
+        Timezone::Local => parse_date_math(input, &Local::now()).map(|t| t.fixed_offset()),
+        Timezone::Named(tz) => {
+            parse_date_math(input, &Utc::now().with_timezone(&tz)).map(|t| t.fixed_offset())
+        }
+    }
+}
+
+/// Expands a `--last` duration such as `15m` or `2d` into the start time `now-15m`.
+///
+/// # Errors
+///
+/// Returns a message for the CLI when `duration` is not a number followed by
+/// one of the relative time units (`s`, `m`, `h`, `d`, `w`, `mo`, `y`).
+///
+/// # Examples
+///
+/// ```
+/// use ddog::time::parse_last;
+///
+/// assert_eq!(parse_last("15m").unwrap(), "now-15m");
+/// assert!(parse_last("soon").is_err());
+/// ```
+pub fn parse_last(duration: &str) -> Result<String, String> {
+    let relative = format!("now-{}", duration);
+    match parse_time(&relative) {
+        Ok(TimeSpec::Relative { snap: None, .. }) => Ok(relative),
+        result => {
+            let hint = result
+                .err()
+                .and_then(|e| e.suggestion)
+                .and_then(|s| {
+                    s.strip_prefix("now-")
+                        .map(|d| format!("; did you mean {}?", d))
+                })
+                .unwrap_or_default();
+            Err(format!(
+                "invalid duration '{}' (expected a number and unit, e.g. 15m, 1h, 2d, 1w, 3mo, 1y){}",
+                duration, hint
+            ))
+        }
+    }
+}
+
+/// Validates a duration such as "90s" or "5m" and returns its length in seconds.
+///
+/// Accepts the same durations as [`parse_last`]; months and years are measured
+/// back from now.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::time::duration_seconds;
+///
+/// assert_eq!(duration_seconds("5m").unwrap(), 300);
+/// assert!(duration_seconds("5").is_err());
+/// ```
+pub fn duration_seconds(duration: &str) -> Result<i64, String> {
+    let relative = parse_last(duration)?;
+    let now = Utc::now();
+    parse_date_math(&relative, &now)
+        .map(|start| (now - start).num_seconds())
+        .ok_or_else(|| format!("invalid duration '{}'", duration))
+}
+
+/// A time in one of the formats Datadog accepts, as parsed by [`parse_time`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimeSpec {
+    /// Date math: `now`, `now-15m`, optionally snapped with `/d`
+    Relative {
+        /// How far before now, e.g. `(15, SnapUnit::Minute)`; `None` for `now`
+        ago: Option<(u32, SnapUnit)>,
+        /// Unit to round down to, e.g. `SnapUnit::Day` for `now-1d/d`
+        snap: Option<SnapUnit>,
+    },
+    /// Natural-language phrase such as `yesterday 9am`, which Datadog doesn't
+    /// accept: it must be resolved with [`resolve_local`] first
+    Natural(NaturalTime),
+    /// ISO8601 timestamp with a UTC offset
+    Iso(DateTime<FixedOffset>),
+    /// Milliseconds since the Unix epoch
+    UnixMillis(u64),
+}
+
+/// Error from [`parse_time`], pointing at the offending part of the input.
+///
+/// Its `Display` output spans several lines: the message, the input with the
+/// offending token underlined, and a suggestion when one is known.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeParseError {
+    /// The time string that failed to parse
+    pub input: String,
+    /// Byte range of the offending token in `input`
+    pub span: Range<usize>,
+    /// What is wrong with the token
+    pub message: String,
+    /// A corrected time, if one is likely
+    pub suggestion: Option<String>,
+}
+
+impl TimeParseError {
+    fn new(input: &str, span: Range<usize>, message: impl Into<String>) -> Self {
+        Self {
+            input: input.to_string(),
+            span,
+            message: message.into(),
+            suggestion: None,
         }
     }
 
-    // Check for Unix timestamp (milliseconds since epoch)
-    // Should be all digits and represent a reasonable timestamp
-    // (between 1970 and 2100, roughly 0 to 4102444800000)
-    if time_str.chars().all(|c| c.is_ascii_digit())
-        && !time_str.is_empty()
-        && let Ok(timestamp) = time_str.parse::<u64>()
+    fn suggest(mut self, suggestion: Option<String>) -> Self {
+        self.suggestion = suggestion;
+        self
+    }
+}
+
+impl fmt::Display for TimeParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let column = self.input[..self.span.start].chars().count();
+        let width = self.input[self.span.clone()].chars().count().max(1);
+        write!(f, "invalid time '{}': {}", self.input, self.message)?;
+        write!(
+            f,
+            "\n  {}\n  {}{}",
+            self.input,
+            " ".repeat(column),
+            "^".repeat(width)
+        )?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, "\n  did you mean {}?", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for TimeParseError {}
+
+impl From<TimeParseError> for AppError {
+    fn from(e: TimeParseError) -> Self {
+        AppError::InvalidQuery(e.to_string())
+    }
+}
+
+/// Latest Unix millisecond timestamp accepted (the start of 2100).
+const MAX_UNIX_MILLIS: u64 = 4_102_444_800_000;
+
+/// Words a mistyped time is compared against for suggestions.
+const TIME_KEYWORDS: [&str; 3] = ["now", "today", "yesterday"];
+
+/// Parses a time string in one of the formats Datadog accepts.
+///
+/// Accepts date math (`now`, `now-15m`, `now-1d/d`), natural-language
+/// phrases (`yesterday 9am`, `2 hours ago`), ISO8601 timestamps with a UTC
+/// offset, and Unix milliseconds. Natural-language, snapped, and offset-less
+/// times must be resolved with [`resolve_local`] before they're sent.
+///
+/// Natural-language phrases are recognized by their first word, so errors in
+/// the rest point at the bad token: `yesterday 13pm` is an invalid hour.
+///
+/// # Errors
+///
+/// Returns a [`TimeParseError`] locating the offending token, with a
+/// suggested correction for common mistakes such as `now-1mth`, `now+1h`,
+/// `1h`, or a missing UTC offset.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::time::{SnapUnit, TimeSpec, parse_time};
+///
+/// assert_eq!(
+///     parse_time("now-15m").unwrap(),
+///     TimeSpec::Relative { ago: Some((15, SnapUnit::Minute)), snap: None }
+/// );
+///
+/// let err = parse_time("now-1mth").unwrap_err();
+/// assert_eq!(err.suggestion.as_deref(), Some("now-1mo"));
+/// ```
+pub fn parse_time(input: &str) -> Result<TimeSpec, TimeParseError> {
+    if input.trim().is_empty() {
+        return Err(TimeParseError::new(input, 0..input.len(), "empty time")
+            .suggest(Some("now".to_string())));
+    }
+
+    if looks_like_iso_date(input) {
+        return parse_iso(input);
+    }
+
+    if let Some(natural) = parse_natural_phrase(input) {
+        return natural.map(TimeSpec::Natural);
+    }
+
+    if let Some(space) = input.find(char::is_whitespace) {
+        let compact: String = input.split_whitespace().collect();
+        let suggestion = parse_time(&compact).is_ok().then_some(compact);
+        let token_end = space + input[space..].len() - input[space..].trim_start().len();
+        return Err(TimeParseError::new(
+            input,
+            space..token_end.max(space + 1),
+            "unexpected whitespace",
+        )
+        .suggest(suggestion));
+    }
+
+    if input.starts_with("now") {
+        return parse_relative(input);
+    }
+
+    if input.chars().all(|c| c.is_ascii_digit()) {
+        return parse_unix_millis(input);
+    }
+
+    // Durations without `now`: `1h`, `-1h`
+    let duration = input.strip_prefix('-').unwrap_or(input);
+    if let Some(unit_start) = duration.find(|c: char| !c.is_ascii_digit())
+        && unit_start > 0
+        && parse_date_math_unit(&duration[unit_start..]).is_some()
     {
-        // Valid Unix timestamp range: 0 to ~4102444800000 (year 2100)
-        if timestamp <= 4_102_444_800_000 {
-            return true;
+        return Err(TimeParseError::new(
+            input,
+            0..input.len(),
+            "relative times are written as now-<N><unit>",
+        )
+        .suggest(Some(format!("now-{}", duration))));
+    }
+    if input.starts_with('-') && input[1..].chars().all(|c| c.is_ascii_digit()) {
+        return Err(TimeParseError::new(
+            input,
+            0..1,
+            "negative timestamps are not supported",
+        ));
+    }
+
+    let suggestion = closest_keyword(input).map(String::from);
+    Err(TimeParseError::new(
+        input,
+        0..input.len(),
+        "unrecognized time (expected now-<N><unit>, ISO8601 such as 2024-01-15T10:00:00Z, or Unix ms)",
+    )
+    .suggest(suggestion))
+}
+
+/// Parses `now`, `now-<N><unit>`, and either snapped with `/<unit>`.
+fn parse_relative(input: &str) -> Result<TimeSpec, TimeParseError> {
+    let (expr, snap) = match input.split_once('/') {
+        Some((expr, unit)) => {
+            let unit_start = expr.len() + 1;
+            let snap = parse_date_math_unit(unit).ok_or_else(|| {
+                unit_error(input, unit_start..input.len(), unit)
+                    .suggest(unit_suggestion(unit).map(|u| format!("{}/{}", expr, u)))
+            })?;
+            (expr, Some(snap))
+        }
+        None => (input, None),
+    };
+
+    let rest = &expr["now".len()..];
+    let snap_suffix = &input[expr.len()..];
+    if rest.is_empty() {
+        return Ok(TimeSpec::Relative { ago: None, snap });
+    }
+
+    let offset_start = "now".len() + 1;
+    let offset = match rest.strip_prefix('-') {
+        Some(offset) => offset,
+        None if rest.starts_with('+') => {
+            return Err(
+                TimeParseError::new(input, 3..4, "future times are not supported")
+                    .suggest(Some(format!("now-{}{}", &rest[1..], snap_suffix))),
+            );
         }
+        None => {
+            return Err(
+                TimeParseError::new(input, 3..expr.len(), "expected '-' after 'now'")
+                    .suggest(closest_keyword(expr).map(String::from)),
+            );
+        }
+    };
+
+    let unit_start = offset
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(offset.len());
+    let (amount, unit) = offset.split_at(unit_start);
+    let unit_span = offset_start + unit_start..expr.len();
+
+    if amount.is_empty() {
+        let suggestion = parse_date_math_unit(unit)
+            .or_else(|| unit_suggestion(unit).and_then(parse_date_math_unit))
+            .map(|_| {
+                format!(
+                    "now-1{}{}",
+                    unit_suggestion(unit).unwrap_or(unit),
+                    snap_suffix
+                )
+            });
+        return Err(TimeParseError::new(
+            input,
+            offset_start..offset_start,
+            "expected a number after 'now-'",
+        )
+        .suggest(suggestion));
+    }
+    let amount_span = offset_start..offset_start + amount.len();
+    let amount: u32 = amount
+        .parse()
+        .map_err(|_| TimeParseError::new(input, amount_span, "number is too large"))?;
+
+    if unit.is_empty() {
+        return Err(TimeParseError::new(
+            input,
+            unit_span,
+            format!(
+                "missing unit after '{}' (expected s, m, h, d, w, mo, or y)",
+                amount
+            ),
+        ));
+    }
+    let unit = parse_date_math_unit(unit).ok_or_else(|| {
+        unit_error(input, unit_span, unit)
+            .suggest(unit_suggestion(unit).map(|u| format!("now-{}{}{}", amount, u, snap_suffix)))
+    })?;
+
+    Ok(TimeSpec::Relative {
+        ago: Some((amount, unit)),
+        snap,
+    })
+}
+
+fn unit_error(input: &str, span: Range<usize>, unit: &str) -> TimeParseError {
+    TimeParseError::new(
+        input,
+        span,
+        format!("unknown unit '{}' (expected s, m, h, d, w, mo, or y)", unit),
+    )
+}
+
+/// Maps common spellings of time units to the date-math unit.
+fn unit_suggestion(unit: &str) -> Option<&'static str> {
+    let unit = unit.to_ascii_lowercase();
+    let unit = unit.as_str();
+    let singular = unit
+        .strip_suffix('s')
+        .filter(|u| u.len() > 1)
+        .unwrap_or(unit);
+    match singular {
+        "sec" | "second" => Some("s"),
+        "min" | "minute" => Some("m"),
+        "hr" | "hour" => Some("h"),
+        "day" => Some("d"),
+        "wk" | "week" => Some("w"),
+        "mon" | "mth" | "month" => Some("mo"),
+        "yr" | "year" => Some("y"),
+        _ => None,
+    }
+}
+
+/// Parses Unix milliseconds, recognizing seconds-scale mistakes by length.
+fn parse_unix_millis(input: &str) -> Result<TimeSpec, TimeParseError> {
+    let too_large =
+        || TimeParseError::new(input, 0..input.len(), "timestamp is beyond the year 2100");
+    let millis: u64 = input.parse().map_err(|_| too_large())?;
+    if millis <= MAX_UNIX_MILLIS {
+        return Ok(TimeSpec::UnixMillis(millis));
     }
 
-    false
+    let (unit, divisor) = match input.len() {
+        16 => ("microseconds", 1_000),
+        19 => ("nanoseconds", 1_000_000),
+        _ => return Err(too_large()),
+    };
+    Err(TimeParseError {
+        message: format!(
+            "timestamp is beyond the year 2100 (it looks like {}; Datadog expects milliseconds)",
+            unit
+        ),
+        ..too_large()
+    }
+    .suggest(Some((millis / divisor).to_string())))
+}
+
+/// Checks for a leading `YYYY-MM-DD`.
+fn looks_like_iso_date(input: &str) -> bool {
+    let bytes = input.as_bytes();
+    bytes.len() >= 10
+        && bytes[..4].iter().all(u8::is_ascii_digit)
+        && bytes[4] == b'-'
+        && bytes[7] == b'-'
+}
+
+/// Parses an ISO8601 timestamp, which must carry a UTC offset.
+fn parse_iso(input: &str) -> Result<TimeSpec, TimeParseError> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(input) {
+        return Ok(TimeSpec::Iso(time));
+    }
+
+    let is_local = LOCAL_ISO_FORMATS
+        .iter()
+        .any(|format| NaiveDateTime::parse_from_str(input, format).is_ok())
+        || NaiveDate::parse_from_str(input, "%Y-%m-%d").is_ok();
+    if is_local {
+        let iso = input.replacen(' ', "T", 1);
+        let suggestion = match iso.len() {
+            10 => format!("{}T00:00:00Z", iso),
+            16 => format!("{}:00Z", iso),
+            _ => format!("{}Z", iso),
+        };
+        return Err(TimeParseError::new(
+            input,
+            input.len()..input.len(),
+            "missing UTC offset (add Z for UTC or an offset like +01:00)",
+        )
+        .suggest(Some(suggestion)));
+    }
+
+    Err(TimeParseError::new(
+        input,
+        0..input.len(),
+        "not a valid ISO8601 timestamp (expected e.g. 2024-01-15T10:00:00Z)",
+    ))
+}
+
+/// Returns the keyword within two edits of `input`, if any.
+fn closest_keyword(input: &str) -> Option<&'static str> {
+    let input = input.to_ascii_lowercase();
+    TIME_KEYWORDS
+        .into_iter()
+        .map(|keyword| (edit_distance(&input, keyword), keyword))
+        .filter(|(distance, _)| *distance <= 2)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, keyword)| keyword)
+}
+
+/// Levenshtein distance between two strings.
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                previous
+            } else {
+                1 + previous.min(row[j]).min(current)
+            };
+            previous = current;
+        }
+    }
+    row[b.len()]
+}
+
+/// Validates that a time string is in a format Datadog accepts.
+///
+/// Shorthand for [`parse_time`] when the details of a failure don't matter.
+pub fn is_valid_time_format(time_str: &str) -> bool {
+    parse_time(time_str).is_ok()
 }
 
 /// Validates that a time range is logically correct.
@@ -347,6 +1360,128 @@ mod tests {
         assert!(!is_valid_time_format("now+1h"));
     }
 
+    #[test]
+    fn test_parse_time() {
+        assert_eq!(
+            parse_time("now").unwrap(),
+            TimeSpec::Relative {
+                ago: None,
+                snap: None
+            }
+        );
+        assert_eq!(
+            parse_time("now-1d/d").unwrap(),
+            TimeSpec::Relative {
+                ago: Some((1, SnapUnit::Day)),
+                snap: Some(SnapUnit::Day)
+            }
+        );
+        assert_eq!(
+            parse_time("1705315200000").unwrap(),
+            TimeSpec::UnixMillis(1_705_315_200_000)
+        );
+        assert!(matches!(
+            parse_time("2024-01-15T10:00:00+01:00").unwrap(),
+            TimeSpec::Iso(_)
+        ));
+    }
+
+    #[test]
+    fn test_parse_time_natural() {
+        assert_eq!(
+            parse_time("yesterday").unwrap(),
+            TimeSpec::Natural(NaturalTime::On(NaturalDay::Yesterday, NaiveTime::MIN))
+        );
+        assert_eq!(
+            parse_time("2 hours ago").unwrap(),
+            TimeSpec::Natural(NaturalTime::Ago(2, SnapUnit::Hour))
+        );
+        assert_eq!(
+            parse_time("Last Monday 9am").unwrap(),
+            TimeSpec::Natural(NaturalTime::On(
+                NaturalDay::Last(Weekday::Mon),
+                NaiveTime::from_hms_opt(9, 0, 0).unwrap()
+            ))
+        );
+        assert!(is_valid_time_format("today 9:30 pm"));
+    }
+
+    #[test]
+    fn test_parse_time_natural_errors() {
+        let err = parse_time("yesterday 13pm").unwrap_err();
+        assert_eq!(err.span, 10..14);
+        assert_eq!(
+            err.message,
+            "invalid hour 13 (expected 1 to 12 with am or pm)"
+        );
+        assert_eq!(
+            parse_time("today 25:00").unwrap_err().message,
+            "invalid hour 25 (expected 0 to 23)"
+        );
+        assert_eq!(parse_time("today 9:30 pm").map(|_| ()), Ok(()));
+
+        let err = parse_time("last mondy 9am").unwrap_err();
+        assert_eq!(err.span, 5..10);
+        assert_eq!(err.suggestion.as_deref(), Some("last monday 9am"));
+
+        let err = parse_time("2 fortnights ago").unwrap_err();
+        assert_eq!(err.span, 2..12);
+        assert_eq!(
+            parse_time("2 hours").unwrap_err().suggestion.as_deref(),
+            Some("2 hours ago")
+        );
+        assert_eq!(parse_time("2 hours later").unwrap_err().span, 8..13);
+    }
+
+    #[test]
+    fn test_parse_time_suggestions() {
+        let suggest = |input: &str| parse_time(input).unwrap_err().suggestion;
+        assert_eq!(suggest("now-1mth").as_deref(), Some("now-1mo"));
+        assert_eq!(suggest("now-2hours").as_deref(), Some("now-2h"));
+        assert_eq!(suggest("now-1d/day").as_deref(), Some("now-1d/d"));
+        assert_eq!(suggest("now+1h").as_deref(), Some("now-1h"));
+        assert_eq!(suggest("now - 1h").as_deref(), Some("now-1h"));
+        assert_eq!(suggest("-15m").as_deref(), Some("now-15m"));
+        assert_eq!(suggest("1h").as_deref(), Some("now-1h"));
+        assert_eq!(suggest("now-h").as_deref(), Some("now-1h"));
+        assert_eq!(suggest("yesterdya").as_deref(), Some("yesterday"));
+        assert_eq!(
+            suggest("2024-01-15T10:00").as_deref(),
+            Some("2024-01-15T10:00:00Z")
+        );
+        assert_eq!(
+            suggest("2024-01-15").as_deref(),
+            Some("2024-01-15T00:00:00Z")
+        );
+        assert_eq!(
+            suggest("1705315200000000").as_deref(),
+            Some("1705315200000")
+        );
+        assert_eq!(suggest("now-1"), None);
+        assert_eq!(suggest("banana"), None);
+    }
+
+    #[test]
+    fn test_time_parse_error_display() {
+        let err = parse_time("now-1mth").unwrap_err();
+        assert_eq!(err.span, 5..8);
+        assert_eq!(
+            err.to_string(),
+            "invalid time 'now-1mth': unknown unit 'mth' (expected s, m, h, d, w, mo, or y)\n  now-1mth\n       ^^^\n  did you mean now-1mo?"
+        );
+        assert!(matches!(AppError::from(err), AppError::InvalidQuery(_)));
+    }
+
+    #[test]
+    fn test_parse_last_suggestion() {
+        assert!(
+            parse_last("1mth")
+                .unwrap_err()
+                .contains("did you mean 1mo?")
+        );
+        assert!(parse_last("1d/d").is_err());
+    }
+
     #[test]
     fn test_valid_time_ranges() {
         // Relative times
@@ -373,6 +1508,229 @@ mod tests {
         assert!(!is_valid_time_range("now", "invalid"));
     }
 
+    /// Wednesday 2024-01-17 15:45:00 UTC
+    fn fixed_now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 17, 15, 45, 0).unwrap()
+    }
+
+    fn natural(input: &str) -> Option<String> {
+        parse_natural(input, &fixed_now()).map(|t| t.to_rfc3339())
+    }
+
+    #[test]
+    fn test_parse_natural_days() {
+        assert_eq!(natural("today").unwrap(), "2024-01-17T00:00:00+00:00");
+        assert_eq!(natural("Yesterday").unwrap(), "2024-01-16T00:00:00+00:00");
+        assert_eq!(
+            natural("yesterday 9am").unwrap(),
+            "2024-01-16T09:00:00+00:00"
+        );
+        assert_eq!(
+            natural("today 9:30 pm").unwrap(),
+            "2024-01-17T21:30:00+00:00"
+        );
+        assert_eq!(natural("today 14:30").unwrap(), "2024-01-17T14:30:00+00:00");
+        assert_eq!(natural("today noon").unwrap(), "2024-01-17T12:00:00+00:00");
+        assert_eq!(natural("today 12am").unwrap(), "2024-01-17T00:00:00+00:00");
+    }
+
+    #[test]
+    fn test_parse_natural_last_weekday() {
+        // 2024-01-17 is a Wednesday
+        assert_eq!(
+            natural("last monday 9am").unwrap(),
+            "2024-01-15T09:00:00+00:00"
+        );
+        assert_eq!(natural("last wed").unwrap(), "2024-01-10T00:00:00+00:00");
+        assert_eq!(
+            natural("last tuesday").unwrap(),
+            "2024-01-16T00:00:00+00:00"
+        );
+        assert!(natural("last monkey").is_none());
+    }
+
+    #[test]
+    fn test_parse_natural_ago() {
+        assert_eq!(natural("2 hours ago").unwrap(), "2024-01-17T13:45:00+00:00");
+        assert_eq!(natural("1 hour ago").unwrap(), "2024-01-17T14:45:00+00:00");
+        assert_eq!(natural("30 mins ago").unwrap(), "2024-01-17T15:15:00+00:00");
+        assert_eq!(natural("3 days ago").unwrap(), "2024-01-14T15:45:00+00:00");
+        assert_eq!(natural("1 month ago").unwrap(), "2023-12-17T15:45:00+00:00");
+        assert!(natural("2 fortnights ago").is_none());
+    }
+
+    #[test]
+    fn test_out_of_range_offsets_are_invalid() {
+        assert!(natural("4000000000 days ago").is_none());
+        assert!(date_math("now-4000000000d/d").is_none());
+        for input in ["4000000000 days ago", "now-4000000000d/d"] {
+            assert!(matches!(
+                resolve_absolute(input, Timezone::Local),
+                Err(AppError::InvalidQuery(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_parse_natural_rejects_other_formats() {
+        assert!(natural("now").is_none());
+        assert!(natural("now-1h").is_none());
+        assert!(natural("2024-01-15T10:00:00Z").is_none());
+        assert!(natural("1705315200000").is_none());
+        assert!(natural("today 25:00").is_none());
+        assert!(natural("today 13pm").is_none());
+    }
+
+    #[test]
+    fn test_parse_natural_in_named_timezone() {
+        let tz: chrono_tz::Tz = "America/New_York".parse().unwrap();
+        let now = fixed_now().with_timezone(&tz);
+        let start = parse_natural("today", &now).unwrap();
+        assert_eq!(start.to_rfc3339(), "2024-01-17T00:00:00-05:00");
+    }
+
+    #[test]
+    fn test_resolve_local_iso() {
+        let tokyo: Timezone = "Asia/Tokyo".parse().unwrap();
+        let resolve = |input: &str| resolve_local_iso(input, tokyo).map(|t| t.to_rfc3339());
+
+        assert_eq!(
+            resolve("2024-01-15T10:00:00").unwrap(),
+            "2024-01-15T10:00:00+09:00"
+        );
+        assert_eq!(
+            resolve("2024-01-15 10:00:00.250").unwrap(),
+            "2024-01-15T10:00:00.250+09:00"
+        );
+        assert_eq!(
+            resolve("2024-01-15T10:00").unwrap(),
+            "2024-01-15T10:00:00+09:00"
+        );
+        assert_eq!(resolve("2024-01-15").unwrap(), "2024-01-15T00:00:00+09:00");
+        assert!(resolve("2024-01-15T10:00:00Z").is_none());
+        assert!(resolve("2024-01-15T10:00:00+02:00").is_none());
+        assert!(resolve("now-1h").is_none());
+    }
+
+    #[test]
+    fn test_resolve_local_iso_dst_gap() {
+        // 01:30 does not exist in London when clocks go forward
+        let london: Timezone = "Europe/London".parse().unwrap();
+        assert!(resolve_local_iso("2024-03-31T01:30:00", london).is_none());
+    }
+
+    #[test]
+    fn test_timezone_convert() {
+        let tz: Timezone = "America/New_York".parse().unwrap();
+        let utc = fixed_now();
+        assert_eq!(tz.convert(&utc).to_rfc3339(), "2024-01-17T10:45:00-05:00");
+    }
+
+    #[test]
+    fn test_timezone_from_str() {
+        assert_eq!("local".parse::<Timezone>().unwrap(), Timezone::Local);
+        assert_eq!(
+            "UTC".parse::<Timezone>().unwrap(),
+            Timezone::Named(chrono_tz::UTC)
+        );
+        assert_eq!(
+            "Europe/London".parse::<Timezone>().unwrap().to_string(),
+            "Europe/London"
+        );
+        assert!("Mars/Olympus".parse::<Timezone>().is_err());
+    }
+
+    fn date_math(input: &str) -> Option<String> {
+        parse_date_math(input, &fixed_now()).map(|t| t.to_rfc3339())
+    }
+
+    #[test]
+    fn test_parse_date_math_snapping() {
+        // 2024-01-17 15:45 is a Wednesday
+        assert_eq!(date_math("now/d").unwrap(), "2024-01-17T00:00:00+00:00");
+        assert_eq!(date_math("now-1d/d").unwrap(), "2024-01-16T00:00:00+00:00");
+        assert_eq!(date_math("now/w").unwrap(), "2024-01-15T00:00:00+00:00");
+        assert_eq!(date_math("now-1w/w").unwrap(), "2024-01-08T00:00:00+00:00");
+        assert_eq!(date_math("now/h").unwrap(), "2024-01-17T15:00:00+00:00");
+        assert_eq!(
+            date_math("now-1mo/mo").unwrap(),
+            "2023-12-01T00:00:00+00:00"
+        );
+        assert_eq!(date_math("now/y").unwrap(), "2024-01-01T00:00:00+00:00");
+        assert_eq!(date_math("now-2h").unwrap(), "2024-01-17T13:45:00+00:00");
+        assert_eq!(date_math("now").unwrap(), "2024-01-17T15:45:00+00:00");
+    }
+
+    #[test]
+    fn test_parse_date_math_invalid() {
+        assert!(date_math("now/x").is_none());
+        assert!(date_math("now/day").is_none());
+        assert!(date_math("now-1x/d").is_none());
+        assert!(date_math("now+1d").is_none());
+        assert!(date_math("yesterday/d").is_none());
+    }
+
+    #[test]
+    fn test_snapping_uses_timezone() {
+        let tz: chrono_tz::Tz = "Asia/Tokyo".parse().unwrap();
+        let start = parse_date_math("now/d", &fixed_now().with_timezone(&tz)).unwrap();
+        // 15:45 UTC is 00:45 on the 18th in Tokyo
+        assert_eq!(start.to_rfc3339(), "2024-01-18T00:00:00+09:00");
+    }
+
+    #[test]
+    fn test_snap_unit_ceil() {
+        let now = fixed_now();
+        assert_eq!(
+            SnapUnit::Hour.ceil(&now).unwrap().to_rfc3339(),
+            "2024-01-17T16:00:00+00:00"
+        );
+        assert_eq!(
+            SnapUnit::Day.ceil(&now).unwrap().to_rfc3339(),
+            "2024-01-18T00:00:00+00:00"
+        );
+        let boundary = Utc.with_ymd_and_hms(2024, 1, 17, 0, 0, 0).unwrap();
+        assert_eq!(SnapUnit::Day.ceil(&boundary).unwrap(), boundary);
+    }
+
+    #[test]
+    fn test_snap_unit_from_str() {
+        assert_eq!("hour".parse::<SnapUnit>().unwrap(), SnapUnit::Hour);
+        assert_eq!("mo".parse::<SnapUnit>().unwrap(), SnapUnit::Month);
+        assert!("fortnight".parse::<SnapUnit>().is_err());
+    }
+
+    #[test]
+    fn test_resolve_absolute() {
+        let utc = Timezone::Named(chrono_tz::UTC);
+        assert_eq!(
+            resolve_absolute("2024-01-15T10:00:00+02:00", utc)
+                .unwrap()
+                .to_rfc3339(),
+            "2024-01-15T10:00:00+02:00"
+        );
+        assert_eq!(
+            resolve_absolute("1705315200000", utc).unwrap().timestamp(),
+            1705315200
+        );
+        assert!(resolve_absolute("now-1h", utc).is_ok());
+        assert!(matches!(
+            resolve_absolute("later", utc),
+            Err(AppError::InvalidQuery(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_last() {
+        assert_eq!(parse_last("15m").unwrap(), "now-15m");
+        assert_eq!(parse_last("3mo").unwrap(), "now-3mo");
+        assert!(parse_last("").is_err());
+        assert!(parse_last("15").is_err());
+        assert!(parse_last("m").is_err());
+        assert!(parse_last("now").is_err());
+        assert!(parse_last("1h-5m").is_err());
+    }
+
     #[test]
     fn test_parse_to_unix_seconds_now() {
         let result = parse_to_unix_seconds("now");
@@ -0,0 +1,381 @@
+//! Shaping metric timeseries into rows.
+//!
+//! A metrics query with several comma-separated expressions returns one
+//! series per expression (or per group), each with its own timestamps and
+//! possibly its own rollup interval. Points can be written as:
+//!
+//! - a [`PointRow`] each, the long format time-series databases and pandas
+//!   load directly
+//! - a [`SeriesRow`] per series with all of its points ([`series_rows`])
+//! - one [`JoinedRow`] per timestamp ([`join_points`]), which buckets every
+//!   series onto the coarsest interval among them with a column per series,
+//!   so the result loads straight into a spreadsheet
+
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use std::collections::BTreeMap;
+
+use crate::client::MetricPoint;
+
+/// One datapoint, serialized as `{"metric", "scope", "tags", "ts", "value"}`.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::client::MetricPoint;
+/// use ddog::timeseries::PointRow;
+///
+/// let point = MetricPoint {
+///     metric: "system.cpu.user".to_string(),
+///     display_name: None,
+///     query_index: Some(0),
+///     aggr: Some("avg".to_string()),
+///     scope: "host:a".to_string(),
+///     tag_set: vec!["host:a".to_string()],
+///     timestamp: 1705315200,
+///     value: 0.5,
+/// };
+/// assert_eq!(
+///     serde_json::to_string(&PointRow::from(&point)).unwrap(),
+///     r#"{"metric":"system.cpu.user","scope":"host:a","tags":["host:a"],"ts":1705315200,"value":0.5}"#
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct PointRow<'a> {
+    /// Metric name
+    pub metric: &'a str,
+
+    /// Scope of the series (e.g., "host:a" or "*")
+    pub scope: &'a str,
+
+    /// Tags of the series
+    pub tags: &'a [String],
+
+    /// Timestamp in Unix seconds
+    pub ts: i64,
+
+    /// Value at `ts`
+    pub value: f64,
+}
+
+impl<'a> From<&'a MetricPoint> for PointRow<'a> {
+    fn from(point: &'a MetricPoint) -> Self {
+        Self {
+            metric: &point.metric,
+            scope: &point.scope,
+            tags: &point.tag_set,
+            ts: point.timestamp,
+            value: point.value,
+        }
+    }
+}
+
+/// A series with all of its points, as `[ts, value]` pairs in arrival order.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct SeriesRow {
+    /// Metric name
+    pub metric: String,
+
+    /// Display name for the metric
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+
+    /// Index of the query expression that returned the series
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query_index: Option<i64>,
+
+    /// Aggregation method
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aggr: Option<String>,
+
+    /// Scope of the series
+    pub scope: String,
+
+    /// Tags of the series
+    pub tags: Vec<String>,
+
+    /// `(ts, value)` pairs, serialized as two-element arrays
+    pub points: Vec<(i64, f64)>,
+}
+
+/// Groups points into one row per series, in the order series first appear.
+///
+/// Series are distinguished like in [`join_points`]: by query index,
+/// aggregation, metric, and scope.
+pub fn series_rows(points: &[MetricPoint]) -> Vec<SeriesRow> {
+    let mut rows: Vec<SeriesRow> = Vec::new();
+    for point in points {
+        let existing = rows.iter_mut().find(|row| {
+            row.query_index == point.query_index
+                && row.aggr == point.aggr
+                && row.metric == point.metric
+                && row.scope == point.scope
+        });
+        let row = match existing {
+            Some(row) => row,
+            None => {
+                rows.push(SeriesRow {
+                    metric: point.metric.clone(),
+                    display_name: point.display_name.clone(),
+                    query_index: point.query_index,
+                    aggr: point.aggr.clone(),
+                    scope: point.scope.clone(),
+                    tags: point.tag_set.clone(),
+                    points: Vec::new(),
+                });
+                rows.last_mut().expect("row was just pushed")
+            }
+        };
+        row.points.push((point.timestamp, point.value));
+    }
+    rows
+}
+
+/// One timestamp of joined timeseries, with a value per series.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JoinedRow {
+    /// Start of the grid bucket in Unix seconds
+    pub timestamp: i64,
+
+    /// `(column, value)` pairs in column order; `None` where a series has no point
+    pub values: Vec<(String, Option<f64>)>,
+}
+
+impl Serialize for JoinedRow {
+    /// Serializes as a flat object: `{"timestamp": ..., "<column>": <value>, ...}`.
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.values.len() + 1))?;
+        map.serialize_entry("timestamp", &self.timestamp)?;
+        for (column, value) in &self.values {
+            map.serialize_entry(column, value)?;
+        }
+        map.end()
+    }
+}
+
+/// A series identified by its query and scope, with its points in arrival order.
+struct Series {
+    column: String,
+    points: Vec<(i64, f64)>,
+}
+
+/// Joins metric points from one or more queries into rows on a common grid.
+///
+/// Series are distinguished by query index, aggregation, metric, and scope,
+/// and named `<aggr>:<metric>{<scope>}` (with `#<query index>` appended if
+/// two series would otherwise share a name). The grid interval is the
+/// largest point spacing found in any series; points falling into the same
+/// bucket are averaged.
+pub fn join_points(points: &[MetricPoint]) -> Vec<JoinedRow> {
+    let series = group_series(points);
+    let Some(interval) = grid_interval(&series) else {
+        return Vec::new();
+    };
+
+    let mut buckets: BTreeMap<i64, Vec<(f64, u32)>> = BTreeMap::new();
+    for (index, s) in series.iter().enumerate() {
+        for &(timestamp, value) in &s.points {
+            let bucket = timestamp.div_euclid(interval) * interval;
+            let sums = buckets
+                .entry(bucket)
+                .or_insert_with(|| vec![(0.0, 0); series.len()]);
+            sums[index].0 += value;
+            sums[index].1 += 1;
+        }
+    }
+
+    buckets
+        .into_iter()
+        .map(|(timestamp, sums)| JoinedRow {
+            timestamp,
+            values: series
+                .iter()
+                .zip(sums)
+                .map(|(s, (sum, count))| {
+                    (
+                        s.column.clone(),
+                        (count > 0).then(|| sum / f64::from(count)),
+                    )
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+/// Groups points into series, preserving the order series first appear in.
+fn group_series(points: &[MetricPoint]) -> Vec<Series> {
+    let mut keys: Vec<(Option<i64>, &str, &str, &str)> = Vec::new();
+    let mut series: Vec<Series> = Vec::new();
+
+    for point in points {
+        let key = (
+            point.query_index,
+            point.aggr.as_deref().unwrap_or(""),
+            point.metric.as_str(),
+            point.scope.as_str(),
+        );
+        let index = match keys.iter().position(|k| *k == key) {
+            Some(index) => index,
+            None => {
+                keys.push(key);
+                series.push(Series {
+                    column: column_name(point),
+                    points: Vec::new(),
+                });
+                keys.len() - 1
+            }
+        };
+        series[index].points.push((point.timestamp, point.value));
+    }
+
+    // Disambiguate series that render to the same name
+    let duplicated: Vec<bool> = series
+        .iter()
+        .map(|s| {
+            series
+                .iter()
+                .filter(|other| other.column == s.column)
+                .count()
+                > 1
+        })
+        .collect();
+    for ((s, key), duplicated) in series.iter_mut().zip(&keys).zip(duplicated) {
+        if duplicated && let Some(query_index) = key.0 {
+            s.column = format!("{}#{}", s.column, query_index);
+        }
+    }
+    series
+}
+
+/// Names a series column as `<aggr>:<metric>{<scope>}`.
+fn column_name(point: &MetricPoint) -> String {
+    match &point.aggr {
+        Some(aggr) => format!("{}:{}{{{}}}", aggr, point.metric, point.scope),
+        None => format!("{}{{{}}}", point.metric, point.scope),
+    }
+}
+
+/// Returns the coarsest point spacing across series, in seconds.
+///
+/// Series with a single point don't constrain the grid; if no series has two
+/// points, every timestamp is its own bucket.
+fn grid_interval(series: &[Series]) -> Option<i64> {
+    if series.iter().all(|s| s.points.is_empty()) {
+        return None;
+    }
+    let interval = series
+        .iter()
+        .filter_map(|s| {
+            let mut timestamps: Vec<i64> = s.points.iter().map(|(t, _)| *t).collect();
+            timestamps.sort_unstable();
+            timestamps.dedup();
+            timestamps.windows(2).map(|w| w[1] - w[0]).min()
+        })
+        .max()
+        .unwrap_or(1);
+    Some(interval.max(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(
+        query_index: i64,
+        metric: &str,
+        scope: &str,
+        timestamp: i64,
+        value: f64,
+    ) -> MetricPoint {
+        MetricPoint {
+            metric: metric.to_string(),
+            display_name: None,
+            query_index: Some(query_index),
+            aggr: Some("avg".to_string()),
+            scope: scope.to_string(),
+            tag_set: Vec::new(),
+            timestamp,
+            value,
+        }
+    }
+
+    #[test]
+    fn test_series_rows() {
+        let rows = series_rows(&[
+            point(0, "cpu.user", "host:a", 0, 1.0),
+            point(0, "cpu.user", "host:b", 0, 2.0),
+            point(0, "cpu.user", "host:a", 60, 3.0),
+        ]);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].points, [(0, 1.0), (60, 3.0)]);
+        assert_eq!(
+            serde_json::to_value(&rows[1]).unwrap(),
+            serde_json::json!({
+                "metric": "cpu.user", "query_index": 0, "aggr": "avg",
+                "scope": "host:b", "tags": [], "points": [[0, 2.0]]
+            })
+        );
+    }
+
+    #[test]
+    fn test_join_same_interval() {
+        let rows = join_points(&[
+            point(0, "cpu.user", "*", 0, 1.0),
+            point(0, "cpu.user", "*", 60, 2.0),
+            point(1, "cpu.system", "*", 0, 3.0),
+            point(1, "cpu.system", "*", 60, 4.0),
+        ]);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(
+            serde_json::to_string(&rows[1]).unwrap(),
+            r#"{"timestamp":60,"avg:cpu.user{*}":2.0,"avg:cpu.system{*}":4.0}"#
+        );
+    }
+
+    #[test]
+    fn test_join_aligns_finer_series_onto_coarser_grid() {
+        let rows = join_points(&[
+            point(0, "fine", "*", 0, 1.0),
+            point(0, "fine", "*", 30, 3.0),
+            point(0, "fine", "*", 60, 5.0),
+            point(1, "coarse", "*", 0, 10.0),
+            point(1, "coarse", "*", 60, 20.0),
+        ]);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].timestamp, 0);
+        assert_eq!(rows[0].values[0], ("avg:fine{*}".to_string(), Some(2.0)));
+        assert_eq!(rows[0].values[1], ("avg:coarse{*}".to_string(), Some(10.0)));
+    }
+
+    #[test]
+    fn test_join_missing_values_are_null() {
+        let rows = join_points(&[
+            point(0, "a", "*", 0, 1.0),
+            point(0, "a", "*", 60, 2.0),
+            point(1, "b", "*", 60, 3.0),
+        ]);
+        assert_eq!(rows[0].values[1], ("avg:b{*}".to_string(), None));
+        assert_eq!(
+            serde_json::to_string(&rows[0]).unwrap(),
+            r#"{"timestamp":0,"avg:a{*}":1.0,"avg:b{*}":null}"#
+        );
+    }
+
+    #[test]
+    fn test_join_groups_by_scope_and_disambiguates() {
+        let rows = join_points(&[
+            point(0, "cpu", "host:a", 0, 1.0),
+            point(0, "cpu", "host:b", 0, 2.0),
+            point(1, "cpu", "host:a", 0, 3.0),
+        ]);
+        let columns: Vec<&str> = rows[0].values.iter().map(|(c, _)| c.as_str()).collect();
+        assert_eq!(
+            columns,
+            vec!["avg:cpu{host:a}#0", "avg:cpu{host:b}", "avg:cpu{host:a}#1"]
+        );
+    }
+
+    #[test]
+    fn test_join_empty() {
+        assert!(join_points(&[]).is_empty());
+    }
+}
@@ -0,0 +1,540 @@
+//! Trace trees, sequence diagrams, and waterfalls.
+//!
+//! Rebuilds a trace's call tree from its [`TraceSpan`]s and renders the calls
+//! between services as a Mermaid or PlantUML sequence diagram, with each
+//! call's start offset and duration, for design docs and postmortems. For
+//! debugging in a terminal, every span can be drawn as a Gantt-style
+//! waterfall instead, and for profiling tools written as folded stacks.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::client::TraceSpan;
+
+/// Resources longer than this are truncated in diagram labels.
+const MAX_LABEL_CHARS: usize = 60;
+
+/// Labels longer than this, indentation included, are truncated in waterfalls.
+const MAX_WATERFALL_LABEL_CHARS: usize = 48;
+
+/// Number of columns of a waterfall's bars.
+pub const WATERFALL_WIDTH: usize = 60;
+
+/// Sequence diagram dialects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dialect {
+    /// Mermaid `sequenceDiagram`
+    Mermaid,
+    /// PlantUML `@startuml` sequence diagram
+    PlantUml,
+}
+
+/// How `ddog spans trace` draws a trace instead of writing its spans.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceDiagram {
+    /// The calls between services, as a sequence diagram
+    Sequence(Dialect),
+    /// Every span as a bar on the trace's timeline
+    Waterfall,
+    /// Folded stacks for flamegraph tools
+    Folded,
+}
+
+/// A span placed in its trace's call tree.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct TreeSpan {
+    /// The span itself
+    #[serde(flatten)]
+    pub span: TraceSpan,
+
+    /// Depth in the call tree; roots are 0
+    pub depth: usize,
+
+    /// Milliseconds from the start of the trace to the start of the span
+    pub offset_ms: f64,
+}
+
+/// Orders spans depth-first, children by start time, and records each depth.
+///
+/// Spans whose parent isn't in the trace (including the root, whose parent
+/// ID is "0") are treated as roots.
+pub fn build_tree(mut spans: Vec<TraceSpan>) -> Vec<TreeSpan> {
+    spans.sort_by_key(|s| s.start_ns);
+    let trace_start = spans.first().map(|s| s.start_ns).unwrap_or_default();
+    let ids: HashSet<&str> = spans.iter().map(|s| s.span_id.as_str()).collect();
+
+    let mut children: HashMap<Option<&str>, Vec<usize>> = HashMap::new();
+    for (index, span) in spans.iter().enumerate() {
+        let parent = span.parent_id.as_deref().filter(|p| ids.contains(p));
+        children.entry(parent).or_default().push(index);
+    }
+
+    let mut order = Vec::with_capacity(spans.len());
+    let mut stack: Vec<(usize, usize)> = children
+        .get(&None)
+        .into_iter()
+        .flatten()
+        .rev()
+        .map(|&index| (index, 0))
+        .collect();
+    let mut visited = HashSet::new();
+    while let Some((index, depth)) = stack.pop() {
+        if !visited.insert(index) {
+            continue;
+        }
+        order.push((index, depth));
+        let id = spans[index].span_id.as_str();
+        for &child in children.get(&Some(id)).into_iter().flatten().rev() {
+            stack.push((child, depth + 1));
+        }
+    }
+
+    order
+        .into_iter()
+        .map(|(index, depth)| TreeSpan {
+            offset_ms: (spans[index].start_ns - trace_start) as f64 / 1_000_000.0,
+            span: spans[index].clone(),
+            depth,
+        })
+        .collect()
+}
+
+/// A call or return between two services.
+#[derive(Debug, Clone, PartialEq)]
+struct Message<'a> {
+    from: &'a str,
+    to: &'a str,
+    label: String,
+    is_return: bool,
+}
+
+/// Calls between services, in time order, each followed by its return.
+///
+/// Only spans whose parent belongs to a different service become messages;
+/// work within a service stays off the diagram.
+fn messages(tree: &[TreeSpan]) -> Vec<Message<'_>> {
+    let by_id: HashMap<&str, &TreeSpan> =
+        tree.iter().map(|s| (s.span.span_id.as_str(), s)).collect();
+
+    // (time, returns before calls, nesting order, message)
+    let mut events: Vec<(i64, bool, i64, Message)> = Vec::new();
+    for span in tree {
+        let Some(parent) = span.span.parent_id.as_deref().and_then(|id| by_id.get(id)) else {
+            continue;
+        };
+        if parent.span.service == span.span.service {
+            continue;
+        }
+
+        let end_ns = span.span.start_ns + (span.span.duration_ms * 1_000_000.0) as i64;
+        let depth = span.depth as i64;
+        events.push((
+            span.span.start_ns,
+            true,
+            depth,
+            Message {
+                from: &parent.span.service,
+                to: &span.span.service,
+                label: format!(
+                    "{} (+{:.1} ms)",
+                    truncate(&span.span.resource, MAX_LABEL_CHARS),
+                    span.offset_ms
+                ),
+                is_return: false,
+            },
+        ));
+        events.push((
+            end_ns,
+            false,
+            -depth,
+            Message {
+                from: &span.span.service,
+                to: &parent.span.service,
+                label: format!("{:.1} ms", span.span.duration_ms),
+                is_return: true,
+            },
+        ));
+    }
+
+    events.sort_by_key(|(time, is_call, nesting, _)| (*time, *is_call, *nesting));
+    events
+        .into_iter()
+        .map(|(_, _, _, message)| message)
+        .collect()
+}
+
+/// Renders the calls between services in a trace as a sequence diagram.
+pub fn render_sequence(tree: &[TreeSpan], dialect: Dialect) -> String {
+    let mut participants: Vec<&str> = Vec::new();
+    for span in tree {
+        if !participants.contains(&span.span.service.as_str()) {
+            participants.push(&span.span.service);
+        }
+    }
+    let alias = |service: &str| {
+        let index = participants.iter().position(|p| *p == service);
+        format!("p{}", index.unwrap_or_default())
+    };
+
+    let mut out = String::new();
+    match dialect {
+        Dialect::Mermaid => {
+            out.push_str("sequenceDiagram\n");
+            for (index, service) in participants.iter().enumerate() {
+                out.push_str(&format!(
+                    "    participant p{} as {}\n",
+                    index,
+                    escape_mermaid(service)
+                ));
+            }
+            for message in messages(tree) {
+                out.push_str(&format!(
+                    "    {}{}{}: {}\n",
+                    alias(message.from),
+                    if message.is_return { "-->>" } else { "->>" },
+                    alias(message.to),
+                    escape_mermaid(&message.label)
+                ));
+            }
+        }
+        Dialect::PlantUml => {
+            out.push_str("@startuml\n");
+            for (index, service) in participants.iter().enumerate() {
+                out.push_str(&format!(
+                    "participant \"{}\" as p{}\n",
+                    service.replace('"', "'"),
+                    index
+                ));
+            }
+            for message in messages(tree) {
+                out.push_str(&format!(
+                    "{} {} {} : {}\n",
+                    alias(message.from),
+                    if message.is_return { "-->" } else { "->" },
+                    alias(message.to),
+                    message.label
+                ));
+            }
+            out.push_str("@enduml\n");
+        }
+    }
+    out
+}
+
+/// Renders a trace as a waterfall, one row per span in call-tree order.
+///
+/// Each row is labelled with the span's service and resource, indented by
+/// its depth, and draws the span as a bar scaled to the whole trace, followed
+/// by its duration. Error spans are marked with `✗`, and with `color` (for a
+/// terminal) drawn in red.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::client::TraceSpan;
+/// use ddog::trace::{build_tree, render_waterfall};
+///
+/// let span = |id: &str, parent: &str, service: &str, start_ms: i64, duration_ms: f64| TraceSpan {
+///     span_id: id.to_string(),
+///     parent_id: Some(parent.to_string()),
+///     service: service.to_string(),
+///     resource: "GET /users".to_string(),
+///     operation: "http.request".to_string(),
+///     start: String::new(),
+///     start_ns: start_ms * 1_000_000,
+///     duration_ms,
+///     error: service == "api",
+/// };
+/// let tree = build_tree(vec![span("1", "0", "web", 0, 40.0), span("2", "1", "api", 20, 20.0)]);
+/// let waterfall = render_waterfall(&tree, false);
+/// let rows: Vec<&str> = waterfall.lines().collect();
+/// assert!(rows[1].starts_with("web GET /users    │████"));
+/// assert!(rows[2].starts_with("  api GET /users  │                              ███"));
+/// assert!(rows[2].ends_with("20.0 ms ✗"));
+/// ```
+pub fn render_waterfall(tree: &[TreeSpan], color: bool) -> String {
+    let total_ms = tree
+        .iter()
+        .map(|s| s.offset_ms + s.span.duration_ms)
+        .fold(0.0, f64::max);
+    let labels: Vec<String> = tree
+        .iter()
+        .map(|s| {
+            let label = format!(
+                "{}{} {}",
+                "  ".repeat(s.depth),
+                s.span.service,
+                s.span.resource
+            );
+            truncate(&label, MAX_WATERFALL_LABEL_CHARS)
+        })
+        .collect();
+    let label_width = labels.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+    let durations: Vec<String> = tree
+        .iter()
+        .map(|s| format!("{:.1} ms", s.span.duration_ms))
+        .collect();
+    let duration_width = durations.iter().map(String::len).max().unwrap_or(0);
+
+    let end = format!("{:.1} ms", total_ms);
+    let mut out = format!(
+        "{:label_width$}  {}{:>pad$}\n",
+        "",
+        "0 ms",
+        end,
+        pad = WATERFALL_WIDTH + 2 - "0 ms".len()
+    );
+    for ((span, label), duration) in tree.iter().zip(&labels).zip(&durations) {
+        let (start, end) = bar_columns(span.offset_ms, span.span.duration_ms, total_ms);
+        let bar: String = (0..WATERFALL_WIDTH)
+            .map(|column| {
+                if (start..end).contains(&column) {
+                    '█'
+                } else {
+                    ' '
+                }
+            })
+            .collect();
+        let row = format!(
+            "{:label_width$}  │{}│ {:>duration_width$}{}",
+            label,
+            bar,
+            duration,
+            if span.span.error { " ✗" } else { "" }
+        );
+        if color && span.span.error {
+            out.push_str(&format!("\x1b[31m{}\x1b[0m\n", row));
+        } else {
+            out.push_str(&row);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Renders a trace as folded stacks, the input of `flamegraph.pl` and inferno.
+///
+/// Each line is the path from a root span to a span, one frame per span
+/// (`service operation resource`, separated by `;`), followed by the span's
+/// self time in microseconds: its duration less its children's, so a
+/// flamegraph's widths add up. Spans without self time are left out, and
+/// identical stacks are merged.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::client::TraceSpan;
+/// use ddog::trace::{build_tree, render_folded};
+///
+/// let span = |id: &str, parent: &str, service: &str, operation: &str, duration_ms: f64| TraceSpan {
+///     span_id: id.to_string(),
+///     parent_id: Some(parent.to_string()),
+///     service: service.to_string(),
+///     resource: "GET /users".to_string(),
+///     operation: operation.to_string(),
+///     start: String::new(),
+///     start_ns: 0,
+///     duration_ms,
+///     error: false,
+/// };
+/// let tree = build_tree(vec![
+///     span("1", "0", "web", "http.request", 40.0),
+///     span("2", "1", "api", "grpc.server", 25.0),
+/// ]);
+/// assert_eq!(
+///     render_folded(&tree),
+///     "web http.request GET /users 15000\n\
+///      web http.request GET /users;api grpc.server GET /users 25000\n"
+/// );
+/// ```
+pub fn render_folded(tree: &[TreeSpan]) -> String {
+    let ids: HashSet<&str> = tree.iter().map(|s| s.span.span_id.as_str()).collect();
+    let mut child_ms: HashMap<&str, f64> = HashMap::new();
+    for span in tree {
+        if let Some(parent) = span.span.parent_id.as_deref().filter(|p| ids.contains(p)) {
+            *child_ms.entry(parent).or_default() += span.span.duration_ms;
+        }
+    }
+
+    let mut path: Vec<String> = Vec::new();
+    let mut stacks: BTreeMap<String, u64> = BTreeMap::new();
+    for span in tree {
+        path.truncate(span.depth);
+        path.push(folded_frame(&span.span));
+        let children = child_ms.get(span.span.span_id.as_str()).copied();
+        let self_us = ((span.span.duration_ms - children.unwrap_or_default()).max(0.0) * 1000.0)
+            .round() as u64;
+        if self_us > 0 {
+            *stacks.entry(path.join(";")).or_default() += self_us;
+        }
+    }
+    stacks
+        .into_iter()
+        .map(|(stack, micros)| format!("{} {}\n", stack, micros))
+        .collect()
+}
+
+/// A span's frame in a folded stack, without the `;` that separates frames.
+fn folded_frame(span: &TraceSpan) -> String {
+    [&span.service, &span.operation, &span.resource]
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .map(|part| part.replace(';', ","))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The columns `[start, end)` of a span's bar in a trace lasting `total_ms`;
+/// every span gets at least one column.
+fn bar_columns(offset_ms: f64, duration_ms: f64, total_ms: f64) -> (usize, usize) {
+    if total_ms <= 0.0 {
+        return (0, 1);
+    }
+    let scale = WATERFALL_WIDTH as f64 / total_ms;
+    let start = ((offset_ms * scale).floor() as usize).min(WATERFALL_WIDTH - 1);
+    let end =
+        (((offset_ms + duration_ms) * scale).ceil() as usize).clamp(start + 1, WATERFALL_WIDTH);
+    (start, end)
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+/// Escapes characters Mermaid treats as syntax in names and message text.
+fn escape_mermaid(text: &str) -> String {
+    text.replace('#', "#35;").replace(';', "#59;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(id: &str, parent: &str, service: &str, start_ms: i64, duration_ms: f64) -> TraceSpan {
+        TraceSpan {
+            span_id: id.to_string(),
+            parent_id: Some(parent.to_string()),
+            service: service.to_string(),
+            resource: format!("{} op", service),
+            operation: String::new(),
+            start: String::new(),
+            start_ns: start_ms * 1_000_000,
+            duration_ms,
+            error: false,
+        }
+    }
+
+    fn trace() -> Vec<TraceSpan> {
+        vec![
+            span("3", "2", "db", 15, 5.0),
+            span("1", "0", "web", 0, 50.0),
+            span("2", "1", "api", 10, 30.0),
+            span("4", "1", "web", 45, 2.0),
+        ]
+    }
+
+    #[test]
+    fn test_build_tree() {
+        let tree = build_tree(trace());
+        let order: Vec<(&str, usize)> = tree
+            .iter()
+            .map(|s| (s.span.span_id.as_str(), s.depth))
+            .collect();
+        assert_eq!(order, vec![("1", 0), ("2", 1), ("3", 2), ("4", 1)]);
+        assert_eq!(tree[2].offset_ms, 15.0);
+    }
+
+    #[test]
+    fn test_render_mermaid() {
+        let diagram = render_sequence(&build_tree(trace()), Dialect::Mermaid);
+        assert_eq!(
+            diagram,
+            "sequenceDiagram
+    participant p0 as web
+    participant p1 as api
+    participant p2 as db
+    p0->>p1: api op (+10.0 ms)
+    p1->>p2: db op (+15.0 ms)
+    p2-->>p1: 5.0 ms
+    p1-->>p0: 30.0 ms
+"
+        );
+    }
+
+    #[test]
+    fn test_render_plantuml() {
+        let diagram = render_sequence(&build_tree(trace()), Dialect::PlantUml);
+        assert!(diagram.starts_with("@startuml\nparticipant \"web\" as p0\n"));
+        assert!(diagram.contains("p0 -> p1 : api op (+10.0 ms)\n"));
+        assert!(diagram.ends_with("p1 --> p0 : 30.0 ms\n@enduml\n"));
+    }
+
+    #[test]
+    fn test_nested_returns_at_same_time() {
+        // The db call ends exactly when the api call does; its return comes first
+        let spans = vec![
+            span("1", "0", "web", 0, 50.0),
+            span("2", "1", "api", 10, 30.0),
+            span("3", "2", "db", 20, 20.0),
+        ];
+        let diagram = render_sequence(&build_tree(spans), Dialect::Mermaid);
+        let db_return = diagram.find("p2-->>p1").unwrap();
+        let api_return = diagram.find("p1-->>p0").unwrap();
+        assert!(db_return < api_return);
+    }
+
+    #[test]
+    fn test_render_waterfall() {
+        let mut spans = trace();
+        spans[0].error = true;
+        let waterfall = render_waterfall(&build_tree(spans), true);
+        let rows: Vec<&str> = waterfall.lines().collect();
+        assert_eq!(rows.len(), 5);
+        assert!(rows[0].trim_start().starts_with("0 ms"));
+        assert!(rows[0].ends_with("50.0 ms"));
+        // The root spans the whole trace
+        assert!(rows[1].contains(&format!("│{}│", "█".repeat(WATERFALL_WIDTH))));
+        // The failed db span is red and marked
+        assert!(rows[3].starts_with("\x1b[31m    db db op"));
+        assert!(rows[3].ends_with(" 5.0 ms ✗\x1b[0m"));
+        assert!(!rows[4].contains('✗'));
+    }
+
+    #[test]
+    fn test_render_folded() {
+        let mut spans = trace();
+        spans[2].operation = "grpc.server".to_string();
+        // A child overlapping past its parent leaves the parent no self time
+        spans.push(span("5", "4", "web", 45, 3.0));
+        let folded = render_folded(&build_tree(spans));
+        assert_eq!(
+            folded,
+            "web web op 18000
+web web op;api grpc.server api op 25000
+web web op;api grpc.server api op;db db op 5000
+web web op;web web op;web web op 3000
+"
+        );
+    }
+
+    #[test]
+    fn test_folded_frame_without_semicolons() {
+        let mut span = trace().remove(0);
+        span.resource = "SELECT 1; SELECT 2".to_string();
+        assert_eq!(folded_frame(&span), "db SELECT 1, SELECT 2");
+    }
+
+    #[test]
+    fn test_bar_columns() {
+        assert_eq!(bar_columns(0.0, 50.0, 50.0), (0, WATERFALL_WIDTH));
+        // A span too short to see still gets a column
+        assert_eq!(bar_columns(25.0, 0.001, 50.0), (30, 31));
+        assert_eq!(
+            bar_columns(50.0, 0.0, 50.0),
+            (WATERFALL_WIDTH - 1, WATERFALL_WIDTH)
+        );
+        assert_eq!(bar_columns(0.0, 0.0, 0.0), (0, 1));
+    }
+}
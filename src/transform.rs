@@ -0,0 +1,469 @@
+//! Reshaping records before they are written (`--drop-field`,
+//! `--rename-field`, `--add-field`, `--flatten`, `--timestamp-format`).
+//!
+//! A [`Pipeline`] runs a record through a list of [`Stage`]s in order, each
+//! editing it in place: redacting values, rewriting the timestamp, dropping
+//! fields, moving a field to another path, setting a field to a constant, or
+//! flattening nested objects into dotted keys. It sits between a
+//! command's results and its writer, so the records every output format and
+//! destination receives are already in their final shape.
+
+use chrono::{DateTime, SecondsFormat, TimeZone, Utc};
+use serde_json::{Map, Value};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::redact::{Redactor, glob_match};
+
+/// One edit of a record. Paths are dotted (e.g., "attributes.host").
+#[derive(Debug, Clone)]
+pub enum Stage {
+    /// Mask sensitive values
+    Redact(Redactor),
+
+    /// Rewrite the record's timestamp in one format, moved to `field` when
+    /// given (see [`TIMESTAMP_FIELDS`])
+    Timestamp {
+        /// Format to write
+        format: TimestampFormat,
+        /// Path to move the timestamp to
+        field: Option<String>,
+    },
+
+    /// Remove the fields at a path, where a segment may contain `*` wildcards
+    Drop(String),
+
+    /// Move the value at a path to another path
+    Rename {
+        /// Path of the value
+        from: String,
+        /// Path to move it to, created as needed
+        to: String,
+    },
+
+    /// Set the value at a path, created as needed
+    Add {
+        /// Path of the field
+        path: String,
+        /// Value to set
+        value: Value,
+    },
+
+    /// Replace nested objects with dotted keys, such as
+    /// `attributes.http.status_code`; arrays are kept as values
+    Flatten,
+}
+
+impl Stage {
+    fn apply(&self, record: &mut Value) {
+        match self {
+            Stage::Redact(redactor) => redactor.apply(record),
+            Stage::Timestamp { format, field } => normalize_timestamp(record, *format, field),
+            Stage::Drop(path) => drop_fields(record, &segments(path)),
+            Stage::Rename { from, to } => {
+                if let Some(value) = take(record, &segments(from)) {
+                    set(record, &segments(to), value);
+                }
+            }
+            Stage::Add { path, value } => set(record, &segments(path), value.clone()),
+            Stage::Flatten => {
+                if let Value::Object(map) = record {
+                    let mut flat = Map::new();
+                    flatten_into(&mut flat, "", std::mem::take(map));
+                    *map = flat;
+                }
+            }
+        }
+    }
+}
+
+/// Stages applied to every record, in order.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::transform::{Pipeline, Stage};
+/// use serde_json::json;
+///
+/// let pipeline = Pipeline::default()
+///     .then(Stage::Drop("attributes.tags".to_string()))
+///     .then(Stage::Rename {
+///         from: "attributes.message".to_string(),
+///         to: "msg".to_string(),
+///     })
+///     .then(Stage::Add {
+///         path: "env".to_string(),
+///         value: json!("prod"),
+///     });
+/// let mut log = json!({"attributes": {"message": "hi", "tags": ["a:b"]}});
+/// pipeline.apply(&mut log);
+/// assert_eq!(log, json!({"attributes": {}, "msg": "hi", "env": "prod"}));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Pipeline {
+    stages: Vec<Stage>,
+}
+
+impl Pipeline {
+    /// Appends a stage.
+    pub fn then(mut self, stage: Stage) -> Self {
+        self.stages.push(stage);
+        self
+    }
+
+    /// Whether the pipeline leaves records unchanged.
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+
+    /// Runs a record through every stage, in order.
+    pub fn apply(&self, record: &mut Value) {
+        for stage in &self.stages {
+            stage.apply(record);
+        }
+    }
+}
+
+/// Where records keep their timestamp, in the order looked up: logs, spans,
+/// then metric points, events, and time series.
+pub const TIMESTAMP_FIELDS: [&str; 4] = [
+    "attributes.timestamp",
+    "attributes.start_timestamp",
+    "timestamp",
+    "ts",
+];
+
+/// Numeric timestamps below this are in seconds, others in milliseconds.
+/// 10^11 seconds is in the year 5138, and 10^11 milliseconds in 1973.
+const MILLIS_THRESHOLD: i64 = 100_000_000_000;
+
+/// How `--timestamp-format` writes timestamps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimestampFormat {
+    /// Unix milliseconds, as a number
+    UnixMs,
+
+    /// RFC 3339 in UTC with milliseconds (2024-01-15T10:00:00.000Z)
+    Rfc3339,
+
+    /// Unix seconds, as a number
+    EpochS,
+}
+
+impl TimestampFormat {
+    /// Writes a time in this format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::{TimeZone, Utc};
+    /// use ddog::transform::TimestampFormat;
+    /// use serde_json::json;
+    ///
+    /// let time = Utc.timestamp_millis_opt(1705312800250).unwrap();
+    /// assert_eq!(TimestampFormat::UnixMs.format(&time), json!(1705312800250_i64));
+    /// assert_eq!(TimestampFormat::EpochS.format(&time), json!(1705312800));
+    /// assert_eq!(
+    ///     TimestampFormat::Rfc3339.format(&time),
+    ///     json!("2024-01-15T10:00:00.250Z")
+    /// );
+    /// ```
+    pub fn format(self, time: &DateTime<Utc>) -> Value {
+        match self {
+            Self::UnixMs => Value::from(time.timestamp_millis()),
+            Self::Rfc3339 => Value::from(time.to_rfc3339_opts(SecondsFormat::Millis, true)),
+            Self::EpochS => Value::from(time.timestamp()),
+        }
+    }
+}
+
+impl FromStr for TimestampFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "unix-ms" => Ok(Self::UnixMs),
+            "rfc3339" => Ok(Self::Rfc3339),
+            "epoch-s" => Ok(Self::EpochS),
+            _ => Err(format!(
+                "invalid timestamp format '{}': expected unix-ms, rfc3339, or epoch-s",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for TimestampFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::UnixMs => "unix-ms",
+            Self::Rfc3339 => "rfc3339",
+            Self::EpochS => "epoch-s",
+        })
+    }
+}
+
+/// Reads a timestamp: an RFC 3339 string, or a number of Unix seconds or
+/// milliseconds (told apart by size).
+fn parse_timestamp(value: &Value) -> Option<DateTime<Utc>> {
+    match value {
+        Value::String(text) => DateTime::parse_from_rfc3339(text)
+            .ok()
+            .map(|t| t.with_timezone(&Utc)),
+        Value::Number(number) => {
+            let number = number.as_i64()?;
+            if number.abs() < MILLIS_THRESHOLD {
+                Utc.timestamp_opt(number, 0).single()
+            } else {
+                Utc.timestamp_millis_opt(number).single()
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Rewrites the first of [`TIMESTAMP_FIELDS`] holding a timestamp, moving it
+/// to `field` when given. Records without one are left as they are.
+fn normalize_timestamp(record: &mut Value, format: TimestampFormat, field: &Option<String>) {
+    for path in TIMESTAMP_FIELDS {
+        let path = segments(path);
+        let time = path
+            .iter()
+            .try_fold(&*record, |value, key| value.get(*key))
+            .and_then(parse_timestamp);
+        let Some(time) = time else {
+            continue;
+        };
+        match field {
+            Some(field) => {
+                take(record, &path);
+                set(record, &segments(field), format.format(&time));
+            }
+            None => set(record, &path, format.format(&time)),
+        }
+        return;
+    }
+}
+
+/// Parses `--rename-field OLD=NEW` into its two paths.
+///
+/// # Errors
+///
+/// Returns an error message unless both paths are given.
+pub fn parse_rename(arg: &str) -> Result<(String, String), String> {
+    match arg.split_once('=') {
+        Some((from, to)) if !from.is_empty() && !to.is_empty() => {
+            Ok((from.to_string(), to.to_string()))
+        }
+        _ => Err(format!(
+            "invalid rename '{}': expected OLD=NEW (e.g. attributes.message=msg)",
+            arg
+        )),
+    }
+}
+
+/// Parses `--add-field PATH=VALUE`. A VALUE that is a JSON number, boolean,
+/// or null is set as such; anything else is set as a string.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::transform::parse_field;
+/// use serde_json::json;
+///
+/// assert_eq!(parse_field("env=prod"), Ok(("env".to_string(), json!("prod"))));
+/// assert_eq!(parse_field("batch=7"), Ok(("batch".to_string(), json!(7))));
+/// assert!(parse_field("=prod").is_err());
+/// ```
+///
+/// # Errors
+///
+/// Returns an error message unless a path and `=` are given.
+pub fn parse_field(arg: &str) -> Result<(String, Value), String> {
+    match arg.split_once('=') {
+        Some((path, value)) if !path.is_empty() => {
+            let value = match serde_json::from_str::<Value>(value) {
+                Ok(scalar @ (Value::Number(_) | Value::Bool(_) | Value::Null)) => scalar,
+                _ => Value::from(value),
+            };
+            Ok((path.to_string(), value))
+        }
+        _ => Err(format!(
+            "invalid field '{}': expected PATH=VALUE (e.g. env=prod)",
+            arg
+        )),
+    }
+}
+
+fn segments(path: &str) -> Vec<&str> {
+    path.split('.').collect()
+}
+
+/// Removes the fields matching a path; arrays along it are searched element
+/// by element.
+fn drop_fields(value: &mut Value, path: &[&str]) {
+    let Some((segment, rest)) = path.split_first() else {
+        return;
+    };
+    match value {
+        Value::Object(map) if rest.is_empty() => map.retain(|key, _| !glob_match(segment, key)),
+        Value::Object(map) => {
+            for (key, child) in map.iter_mut() {
+                if glob_match(segment, key) {
+                    drop_fields(child, rest);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| drop_fields(item, path)),
+        _ => {}
+    }
+}
+
+/// Removes and returns the value at a path.
+fn take(value: &mut Value, path: &[&str]) -> Option<Value> {
+    let (last, parents) = path.split_last()?;
+    let parent = parents
+        .iter()
+        .try_fold(value, |value, key| value.get_mut(*key))?;
+    parent.as_object_mut()?.remove(*last)
+}
+
+/// Sets the value at a path, replacing anything in the way with objects.
+fn set(value: &mut Value, path: &[&str], new: Value) {
+    let Some((first, rest)) = path.split_first() else {
+        *value = new;
+        return;
+    };
+    if !value.is_object() {
+        *value = Value::Object(Map::new());
+    }
+    if let Value::Object(map) = value {
+        let child = map.entry(first.to_string()).or_insert(Value::Null);
+        set(child, rest, new);
+    }
+}
+
+/// Moves the fields of `map` into `flat`, nested objects' fields under
+/// their dotted path. Empty objects are kept as values.
+fn flatten_into(flat: &mut Map<String, Value>, prefix: &str, map: Map<String, Value>) {
+    for (key, value) in map {
+        let path = match prefix {
+            "" => key,
+            _ => format!("{}.{}", prefix, key),
+        };
+        match value {
+            Value::Object(child) if !child.is_empty() => flatten_into(flat, &path, child),
+            value => {
+                flat.insert(path, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_drop_with_wildcards() {
+        let mut record = json!({"attributes": {
+            "attributes": {"debug_id": 1, "debug_ctx": {}, "user": "a"},
+            "spans": [{"debug_id": 2, "name": "x"}],
+        }});
+        drop_fields(&mut record, &segments("attributes.attributes.debug_*"));
+        drop_fields(&mut record, &segments("attributes.spans.debug_id"));
+        assert_eq!(
+            record,
+            json!({"attributes": {"attributes": {"user": "a"}, "spans": [{"name": "x"}]}})
+        );
+    }
+
+    #[test]
+    fn test_rename_missing_and_nested() {
+        let pipeline = Pipeline::default()
+            .then(Stage::Rename {
+                from: "attributes.service".to_string(),
+                to: "labels.service".to_string(),
+            })
+            .then(Stage::Rename {
+                from: "attributes.missing".to_string(),
+                to: "found".to_string(),
+            });
+        let mut record = json!({"attributes": {"service": "api"}, "labels": "old"});
+        pipeline.apply(&mut record);
+        assert_eq!(
+            record,
+            json!({"attributes": {}, "labels": {"service": "api"}})
+        );
+    }
+
+    #[test]
+    fn test_flatten() {
+        let pipeline = Pipeline::default().then(Stage::Flatten);
+        let mut record = json!({
+            "id": "AAA",
+            "attributes": {
+                "http": {"status_code": 502, "url": "/pay"},
+                "tags": ["env:prod"],
+                "spans": [{"nested": {"kept": true}}],
+                "empty": {},
+            },
+        });
+        pipeline.apply(&mut record);
+        assert_eq!(
+            record,
+            json!({
+                "id": "AAA",
+                "attributes.http.status_code": 502,
+                "attributes.http.url": "/pay",
+                "attributes.tags": ["env:prod"],
+                "attributes.spans": [{"nested": {"kept": true}}],
+                "attributes.empty": {},
+            })
+        );
+    }
+
+    #[test]
+    fn test_timestamps_across_domains() {
+        let stage = Stage::Timestamp {
+            format: TimestampFormat::UnixMs,
+            field: Some("ts".to_string()),
+        };
+        let mut log = json!({"attributes": {"timestamp": "2024-01-15T11:00:00+01:00"}});
+        let mut span = json!({"attributes": {"start_timestamp": "2024-01-15T10:00:00Z"}});
+        let mut point = json!({"metric": "cpu", "timestamp": 1705312800});
+        let mut event = json!({"title": "x", "timestamp": 1705312800000_i64});
+        for record in [&mut log, &mut span, &mut point, &mut event] {
+            stage.apply(record);
+            assert_eq!(record["ts"], json!(1705312800000_i64), "{}", record);
+        }
+        assert_eq!(log, json!({"attributes": {}, "ts": 1705312800000_i64}));
+        assert_eq!(point.get("timestamp"), None);
+    }
+
+    #[test]
+    fn test_timestamp_in_place() {
+        let stage = Stage::Timestamp {
+            format: TimestampFormat::Rfc3339,
+            field: None,
+        };
+        let mut point = json!({"metric": "cpu", "timestamp": 1705312800});
+        stage.apply(&mut point);
+        assert_eq!(point["timestamp"], "2024-01-15T10:00:00.000Z");
+
+        let mut unparsed = json!({"timestamp": "yesterday"});
+        stage.apply(&mut unparsed);
+        assert_eq!(unparsed, json!({"timestamp": "yesterday"}));
+    }
+
+    #[test]
+    fn test_parse_rename() {
+        assert_eq!(
+            parse_rename("a.b=c"),
+            Ok(("a.b".to_string(), "c".to_string()))
+        );
+        assert!(parse_rename("a.b").is_err());
+        assert!(parse_rename("a=").is_err());
+    }
+}
@@ -0,0 +1,371 @@
+//! Updating the installed binary from GitHub releases (`ddog self-update`).
+//!
+//! Each release carries a `ddog-<os>-<arch>.tar.gz` archive per platform and
+//! a `.sha256` file with its checksum. An [`Updater`] looks up the latest
+//! release, downloads the archive for this platform, and checks it against
+//! its checksum before [`extract_binary`] takes the binary out of it and
+//! [`replace_executable`] swaps it in for the running one.
+
+use flate2::read::GzDecoder;
+use reqwest_middleware::ClientWithMiddleware;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::client::DatadogTransport;
+use crate::error::AppError;
+use crate::sink;
+
+/// GitHub repository releases are published to.
+pub const REPOSITORY: &str = "tmcinerney/ddog";
+
+/// Version of the running binary.
+pub const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Name of the binary inside release archives.
+const BINARY: &str = "ddog";
+
+/// Name of this platform's release archive, or `None` if releases aren't
+/// built for it.
+pub fn asset_name() -> Option<&'static str> {
+    match (std::env::consts::OS, std::env::consts::ARCH) {
+        ("linux", "x86_64") => Some("ddog-linux-x86_64.tar.gz"),
+        ("macos", "x86_64") => Some("ddog-darwin-x86_64.tar.gz"),
+        ("macos", "aarch64") => Some("ddog-darwin-aarch64.tar.gz"),
+        _ => None,
+    }
+}
+
+/// A published release.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Release {
+    /// Git tag, such as `v1.2.0`
+    #[serde(rename = "tag_name")]
+    pub tag: String,
+
+    /// Downloadable files
+    pub assets: Vec<Asset>,
+}
+
+/// A file attached to a release.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Asset {
+    /// File name
+    pub name: String,
+
+    /// Download URL
+    #[serde(rename = "browser_download_url")]
+    pub url: String,
+}
+
+impl Release {
+    /// The version of the release, its tag without a leading `v`.
+    pub fn version(&self) -> &str {
+        self.tag.trim_start_matches('v')
+    }
+
+    /// The asset named `name`.
+    pub fn asset(&self, name: &str) -> Option<&Asset> {
+        self.assets.iter().find(|asset| asset.name == name)
+    }
+}
+
+/// Whether version `latest` is newer than `current`, comparing dotted
+/// numbers; anything after a `-` or `+` is ignored.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::update::is_newer;
+///
+/// assert!(is_newer("1.1.0", "1.2.0"));
+/// assert!(is_newer("1.9.0", "1.10.0"));
+/// assert!(!is_newer("1.2.0", "1.2.0"));
+/// assert!(!is_newer("2.0.0", "1.9.9"));
+/// ```
+pub fn is_newer(current: &str, latest: &str) -> bool {
+    let parse = |version: &str| -> Vec<u64> {
+        version
+            .trim_start_matches('v')
+            .split(['-', '+'])
+            .next()
+            .unwrap_or_default()
+            .split('.')
+            .map(|part| part.parse().unwrap_or(0))
+            .collect()
+    };
+    parse(latest) > parse(current)
+}
+
+/// Checks `data` against a `.sha256` file: a hex SHA-256 digest, optionally
+/// followed by a file name as `sha256sum` writes it.
+///
+/// # Errors
+///
+/// Returns `AppError::Api` if the file holds no digest or the digests differ.
+pub fn verify_checksum(data: &[u8], checksum_file: &str) -> Result<(), AppError> {
+    let expected = checksum_file
+        .split_whitespace()
+        .next()
+        .filter(|digest| digest.len() == 64)
+        .ok_or_else(|| AppError::Api("the release checksum file holds no SHA-256".to_string()))?;
+    let actual = hex::encode(Sha256::digest(data));
+    if actual.eq_ignore_ascii_case(expected) {
+        Ok(())
+    } else {
+        Err(AppError::Api(format!(
+            "checksum mismatch: expected {}, downloaded {}",
+            expected, actual
+        )))
+    }
+}
+
+/// Takes the `ddog` binary out of a gzipped tar archive.
+///
+/// # Errors
+///
+/// Returns `AppError::Io` if the archive can't be decompressed, and
+/// `AppError::Api` if it is malformed or holds no `ddog` file.
+pub fn extract_binary(archive: &[u8]) -> Result<Vec<u8>, AppError> {
+    let mut tar = Vec::new();
+    GzDecoder::new(archive).read_to_end(&mut tar)?;
+    let malformed = || AppError::Api("the release archive is malformed".to_string());
+
+    let mut offset = 0;
+    while offset + 512 <= tar.len() {
+        let header = &tar[offset..offset + 512];
+        // Two zero blocks end the archive
+        if header.iter().all(|&b| b == 0) {
+            break;
+        }
+        let name = field(&header[..100]);
+        let size =
+            usize::from_str_radix(field(&header[124..136]).trim(), 8).map_err(|_| malformed())?;
+        let kind = header[156];
+        let start = offset + 512;
+        let data = tar.get(start..start + size).ok_or_else(malformed)?;
+        if matches!(kind, b'0' | 0) && name.trim_start_matches("./") == BINARY {
+            return Ok(data.to_vec());
+        }
+        offset = start + size.div_ceil(512) * 512;
+    }
+    Err(AppError::Api(format!(
+        "the release archive holds no {} binary",
+        BINARY
+    )))
+}
+
+/// A NUL-terminated tar header field.
+fn field(bytes: &[u8]) -> &str {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    std::str::from_utf8(&bytes[..end]).unwrap_or_default()
+}
+
+/// Replaces the executable at `path` with `binary`.
+///
+/// The new binary is written next to it and renamed over it, so the swap is
+/// atomic and a failed write leaves the old binary in place.
+///
+/// # Errors
+///
+/// Returns `AppError::Io` if the directory isn't writable, such as
+/// `/usr/local/bin` without `sudo`.
+pub fn replace_executable(path: &Path, binary: &[u8]) -> Result<(), AppError> {
+    let staged = staged_path(path);
+    std::fs::write(&staged, binary)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o755))?;
+    }
+    std::fs::rename(&staged, path).inspect_err(|_| {
+        let _ = std::fs::remove_file(&staged);
+    })?;
+    Ok(())
+}
+
+fn staged_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".update");
+    path.with_file_name(name)
+}
+
+/// Looks up and downloads releases.
+///
+/// Requests are retried like `--sink` batches: connection errors, 429s, and
+/// 5xx responses, with exponential backoff.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ddog::update::{CURRENT_VERSION, Updater, is_newer};
+///
+/// # async fn example() -> Result<(), ddog::AppError> {
+/// let release = Updater::new().latest().await?;
+/// if is_newer(CURRENT_VERSION, release.version()) {
+///     println!("ddog {} is available", release.version());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Updater {
+    api: String,
+    http: ClientWithMiddleware,
+}
+
+impl Default for Updater {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Updater {
+    /// Creates an updater for [`REPOSITORY`].
+    pub fn new() -> Self {
+        Self {
+            api: format!("https://api.github.com/repos/{}", REPOSITORY),
+            http: sink::retrying_client(None),
+        }
+    }
+
+    /// Sends requests through `transport`, such as a
+    /// [`MockTransport`](crate::client::MockTransport) in tests.
+    pub fn with_transport(mut self, transport: impl DatadogTransport + 'static) -> Self {
+        self.http = sink::retrying_client(Some(Arc::new(transport)));
+        self
+    }
+
+    /// The latest published release.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::RateLimited` when GitHub's unauthenticated rate
+    /// limit is used up, and `AppError::Api` for other failures.
+    pub async fn latest(&self) -> Result<Release, AppError> {
+        let body = self
+            .get(&format!("{}/releases/latest", self.api), "GitHub releases")
+            .await?;
+        Ok(serde_json::from_slice(&body)?)
+    }
+
+    /// Downloads a release asset.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Api` if the download fails.
+    pub async fn download(&self, asset: &Asset) -> Result<Vec<u8>, AppError> {
+        self.get(&asset.url, &asset.name).await
+    }
+
+    async fn get(&self, url: &str, name: &str) -> Result<Vec<u8>, AppError> {
+        let response = self
+            .http
+            .get(url)
+            .header("user-agent", format!("ddog/{}", CURRENT_VERSION))
+            .header("accept", "application/vnd.github+json")
+            .send()
+            .await
+            .map_err(|e| AppError::Api(format!("{}: {}", name, e)))?;
+        let status = response.status();
+        let body = response
+            .bytes()
+            .await
+            .map_err(|e| AppError::Api(format!("{}: {}", name, e)))?;
+        if status.is_success() {
+            return Ok(body.to_vec());
+        }
+        let message = format!("{}: HTTP {}", name, status);
+        Err(match status.as_u16() {
+            403 | 429 => AppError::RateLimited(message),
+            _ => AppError::Api(message),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::MockTransport;
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use serde_json::json;
+    use std::io::Write;
+
+    /// A gzipped tar archive of `files`.
+    fn archive(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut tar = Vec::new();
+        for (name, data) in files {
+            let mut header = [0u8; 512];
+            header[..name.len()].copy_from_slice(name.as_bytes());
+            header[124..135].copy_from_slice(format!("{:011o}", data.len()).as_bytes());
+            header[156] = b'0';
+            tar.extend_from_slice(&header);
+            tar.extend_from_slice(data);
+            tar.resize(tar.len().div_ceil(512) * 512, 0);
+        }
+        tar.resize(tar.len() + 1024, 0);
+        let mut gz = GzEncoder::new(Vec::new(), Compression::default());
+        gz.write_all(&tar).unwrap();
+        gz.finish().unwrap()
+    }
+
+    #[test]
+    fn test_extract_binary() {
+        let data = archive(&[("README.md", b"docs"), ("./ddog", b"\x7fELF binary")]);
+        assert_eq!(extract_binary(&data).unwrap(), b"\x7fELF binary");
+
+        let missing = archive(&[("other", b"x")]);
+        assert!(matches!(extract_binary(&missing), Err(AppError::Api(_))));
+    }
+
+    #[test]
+    fn test_verify_checksum() {
+        let digest = hex::encode(Sha256::digest(b"archive"));
+        verify_checksum(
+            b"archive",
+            &format!("{}  ddog-linux-x86_64.tar.gz\n", digest),
+        )
+        .unwrap();
+        assert!(verify_checksum(b"tampered", &digest).is_err());
+        assert!(verify_checksum(b"archive", "").is_err());
+    }
+
+    #[test]
+    fn test_replace_executable() {
+        let dir = std::env::temp_dir().join(format!("ddog-update-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("ddog");
+        std::fs::write(&path, b"old").unwrap();
+        replace_executable(&path, b"new").unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"new");
+        assert!(!staged_path(&path).exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_latest_release() {
+        let transport = MockTransport::new().respond(
+            "GET",
+            "/repos/tmcinerney/ddog/releases/latest",
+            200,
+            json!({
+                "tag_name": "v1.2.0",
+                "assets": [{
+                    "name": "ddog-linux-x86_64.tar.gz",
+                    "browser_download_url": "https://github.com/tmcinerney/ddog/releases/download/v1.2.0/ddog-linux-x86_64.tar.gz",
+                }],
+            }),
+        );
+        let release = Updater::new()
+            .with_transport(transport)
+            .latest()
+            .await
+            .unwrap();
+        assert_eq!(release.version(), "1.2.0");
+        assert!(release.asset("ddog-linux-x86_64.tar.gz").is_some());
+        assert!(release.asset("ddog-windows.zip").is_none());
+    }
+}
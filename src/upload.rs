@@ -0,0 +1,648 @@
+//! Streaming exports to object storage (`--output s3://...`, `gs://...`).
+//!
+//! An [`UploadWriter`] gzips whatever is written to it and uploads the
+//! compressed stream as the parts of an S3 multipart upload, so an export of
+//! any size is held in memory a part at a time and never written to disk.
+//! Parts are uploaded from a thread of their own while the search carries
+//! on; writes block when uploads fall behind.
+//!
+//! S3 is reached with AWS credentials from the environment. GCS is reached
+//! through its S3-compatible XML API, signed with HMAC keys, since both speak
+//! the same multipart protocol.
+
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+use std::sync::mpsc::{Receiver, SyncSender, sync_channel};
+use std::thread::JoinHandle;
+
+use crate::error::AppError;
+
+/// Compressed bytes uploaded per part; S3 requires at least 5 MiB for all
+/// parts but the last.
+pub const PART_SIZE: usize = 8 * 1024 * 1024;
+
+/// Parts held in memory waiting to be uploaded before writes block.
+const QUEUED_PARTS: usize = 2;
+
+/// Object storage service of an upload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageProvider {
+    /// Amazon S3, or an S3-compatible store with `AWS_ENDPOINT_URL`
+    S3,
+
+    /// Google Cloud Storage, through its XML API
+    Gcs,
+}
+
+impl StorageProvider {
+    fn scheme(self) -> &'static str {
+        match self {
+            Self::S3 => "s3",
+            Self::Gcs => "gs",
+        }
+    }
+}
+
+/// Where an upload goes: a bucket and object key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectUrl {
+    /// Storage service
+    pub provider: StorageProvider,
+
+    /// Bucket name
+    pub bucket: String,
+
+    /// Object key, or a prefix ending in `/` (possibly empty) to name the
+    /// object under
+    pub key: String,
+}
+
+impl ObjectUrl {
+    /// Whether the URL names a prefix rather than an object.
+    pub fn is_prefix(&self) -> bool {
+        self.key.is_empty() || self.key.ends_with('/')
+    }
+
+    /// The URL of the object `name` under this prefix; an object URL is
+    /// returned as is.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ddog::upload::ObjectUrl;
+    ///
+    /// let url: ObjectUrl = "s3://exports/incident-1234/".parse().unwrap();
+    /// let object = url.object("logs.ndjson.gz");
+    /// assert_eq!(object.to_string(), "s3://exports/incident-1234/logs.ndjson.gz");
+    /// assert_eq!(object.object("other").key, "incident-1234/logs.ndjson.gz");
+    /// ```
+    pub fn object(&self, name: &str) -> Self {
+        let mut url = self.clone();
+        if url.is_prefix() {
+            url.key.push_str(name);
+        }
+        url
+    }
+}
+
+impl FromStr for ObjectUrl {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (provider, rest) = if let Some(rest) = s.strip_prefix("s3://") {
+            (StorageProvider::S3, rest)
+        } else if let Some(rest) = s.strip_prefix("gs://") {
+            (StorageProvider::Gcs, rest)
+        } else {
+            return Err(format!(
+                "'{}' is not an object storage URL (s3://bucket/prefix/ or gs://bucket/prefix/)",
+                s
+            ));
+        };
+        let (bucket, key) = rest.split_once('/').unwrap_or((rest, ""));
+        if bucket.is_empty() {
+            return Err(format!("'{}' has no bucket name", s));
+        }
+        Ok(Self {
+            provider,
+            bucket: bucket.to_string(),
+            key: key.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for ObjectUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}://{}/{}",
+            self.provider.scheme(),
+            self.bucket,
+            self.key
+        )
+    }
+}
+
+/// Keys signing requests to a storage service.
+#[derive(Clone)]
+pub struct Credentials {
+    access_key: String,
+    secret_key: String,
+    session_token: Option<String>,
+    region: String,
+    endpoint: Option<String>,
+}
+
+impl Credentials {
+    /// Reads the credentials for `provider` from the environment.
+    ///
+    /// S3 uses `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY`, and optionally
+    /// `AWS_SESSION_TOKEN`, `AWS_REGION` (or `AWS_DEFAULT_REGION`, default
+    /// us-east-1), and `AWS_ENDPOINT_URL` for S3-compatible stores. GCS uses
+    /// the HMAC key in `GCS_HMAC_ACCESS_ID` and `GCS_HMAC_SECRET`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Config` if a required variable is unset.
+    pub fn from_env(provider: StorageProvider) -> Result<Self, AppError> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let require = |name: &str| {
+            var(name).ok_or_else(|| {
+                AppError::Config(format!(
+                    "{} is required to upload to {}://",
+                    name,
+                    provider.scheme()
+                ))
+            })
+        };
+        Ok(match provider {
+            StorageProvider::S3 => Self {
+                access_key: require("AWS_ACCESS_KEY_ID")?,
+                secret_key: require("AWS_SECRET_ACCESS_KEY")?,
+                session_token: var("AWS_SESSION_TOKEN"),
+                region: var("AWS_REGION")
+                    .or_else(|| var("AWS_DEFAULT_REGION"))
+                    .unwrap_or_else(|| "us-east-1".to_string()),
+                endpoint: var("AWS_ENDPOINT_URL").map(|e| e.trim_end_matches('/').to_string()),
+            },
+            StorageProvider::Gcs => Self {
+                access_key: require("GCS_HMAC_ACCESS_ID")?,
+                secret_key: require("GCS_HMAC_SECRET")?,
+                session_token: None,
+                region: "auto".to_string(),
+                endpoint: Some("https://storage.googleapis.com".to_string()),
+            },
+        })
+    }
+
+    /// Host and path of an object: virtual-hosted on AWS, path-style on
+    /// custom endpoints and GCS.
+    fn locate(&self, url: &ObjectUrl) -> (String, String, String) {
+        let key = encode_path(&url.key);
+        match &self.endpoint {
+            Some(endpoint) => {
+                let (scheme, host) = endpoint.split_once("://").unwrap_or(("https", endpoint));
+                (
+                    scheme.to_string(),
+                    host.to_string(),
+                    format!("/{}/{}", encode_path(&url.bucket), key),
+                )
+            }
+            None => (
+                "https".to_string(),
+                format!("{}.s3.{}.amazonaws.com", url.bucket, self.region),
+                format!("/{}", key),
+            ),
+        }
+    }
+
+    /// Signs a request with AWS Signature Version 4, returning the headers
+    /// to send with it. `query` must already be in canonical form (sorted
+    /// and encoded).
+    fn sign(
+        &self,
+        method: &str,
+        host: &str,
+        path: &str,
+        query: &str,
+        body: &[u8],
+        amz_date: &str,
+    ) -> Vec<(String, String)> {
+        let payload_hash = hex::encode(Sha256::digest(body));
+        let mut headers = vec![
+            ("host".to_string(), host.to_string()),
+            ("x-amz-content-sha256".to_string(), payload_hash.clone()),
+            ("x-amz-date".to_string(), amz_date.to_string()),
+        ];
+        if let Some(token) = &self.session_token {
+            headers.push(("x-amz-security-token".to_string(), token.clone()));
+        }
+        let signed_headers: Vec<&str> = headers.iter().map(|(name, _)| name.as_str()).collect();
+        let signed_headers = signed_headers.join(";");
+        let canonical_headers: String = headers
+            .iter()
+            .map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+            .collect();
+        let canonical_request = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            method, path, query, canonical_headers, signed_headers, payload_hash
+        );
+
+        let date = &amz_date[..8];
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+        let key = signing_key(&self.secret_key, date, &self.region, "s3");
+        let signature = hex::encode(hmac(&key, string_to_sign.as_bytes()));
+
+        headers.push((
+            "authorization".to_string(),
+            format!(
+                "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+                self.access_key, scope, signed_headers, signature
+            ),
+        ));
+        headers
+    }
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Derives the SigV4 signing key for a day, region, and service.
+fn signing_key(secret: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let key = hmac(format!("AWS4{}", secret).as_bytes(), date.as_bytes());
+    let key = hmac(&key, region.as_bytes());
+    let key = hmac(&key, service.as_bytes());
+    hmac(&key, b"aws4_request")
+}
+
+/// Percent-encodes an object path, keeping `/` separators.
+fn encode_path(path: &str) -> String {
+    path.split('/')
+        .map(|segment| urlencoding::encode(segment).into_owned())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Extracts the text of the first `<tag>` element of an XML response.
+fn xml_element<'a>(xml: &'a str, tag: &str) -> Option<&'a str> {
+    let start = xml.find(&format!("<{}>", tag))? + tag.len() + 2;
+    let end = start + xml[start..].find(&format!("</{}>", tag))?;
+    Some(&xml[start..end])
+}
+
+/// Body completing a multipart upload of parts with `etags`, in order.
+fn complete_body(etags: &[String]) -> String {
+    let parts: String = etags
+        .iter()
+        .enumerate()
+        .map(|(i, etag)| {
+            format!(
+                "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
+                i + 1,
+                etag
+            )
+        })
+        .collect();
+    format!(
+        "<CompleteMultipartUpload>{}</CompleteMultipartUpload>",
+        parts
+    )
+}
+
+/// Maps a storage service's error response to an `AppError`.
+fn storage_error(status: reqwest::StatusCode, body: &str, url: &ObjectUrl) -> AppError {
+    let message = match (xml_element(body, "Code"), xml_element(body, "Message")) {
+        (Some(code), Some(message)) => format!("{}: {}: {}", url, code, message),
+        _ => format!("{}: HTTP {}", url, status),
+    };
+    match status.as_u16() {
+        401 => AppError::Auth(message),
+        403 => AppError::Forbidden(message),
+        404 => AppError::Config(message),
+        429 | 503 => AppError::RateLimited(message),
+        _ => AppError::Api(message),
+    }
+}
+
+/// An S3 multipart upload in progress.
+struct MultipartUpload {
+    http: reqwest::Client,
+    credentials: Credentials,
+    url: ObjectUrl,
+    upload_id: String,
+    etags: Vec<String>,
+}
+
+impl MultipartUpload {
+    async fn start(credentials: Credentials, url: ObjectUrl) -> Result<Self, AppError> {
+        let mut upload = Self {
+            http: reqwest::Client::new(),
+            credentials,
+            url,
+            upload_id: String::new(),
+            etags: Vec::new(),
+        };
+        let response = upload.send("POST", "uploads=", Vec::new()).await?;
+        upload.upload_id = xml_element(&response.1, "UploadId")
+            .ok_or_else(|| AppError::Api(format!("{}: no UploadId in response", upload.url)))?
+            .to_string();
+        Ok(upload)
+    }
+
+    async fn upload_part(&mut self, part: Vec<u8>) -> Result<(), AppError> {
+        let query = format!(
+            "partNumber={}&uploadId={}",
+            self.etags.len() + 1,
+            urlencoding::encode(&self.upload_id)
+        );
+        let (etag, _) = self.send("PUT", &query, part).await?;
+        let etag =
+            etag.ok_or_else(|| AppError::Api(format!("{}: no ETag for uploaded part", self.url)))?;
+        self.etags.push(etag);
+        Ok(())
+    }
+
+    async fn complete(self) -> Result<(), AppError> {
+        let query = format!("uploadId={}", urlencoding::encode(&self.upload_id));
+        let body = complete_body(&self.etags).into_bytes();
+        let (_, response) = self.send("POST", &query, body).await?;
+        // Completing can fail after the response has started, with a 200 status
+        if response.contains("<Error>") {
+            return Err(storage_error(
+                reqwest::StatusCode::INTERNAL_SERVER_ERROR,
+                &response,
+                &self.url,
+            ));
+        }
+        Ok(())
+    }
+
+    async fn abort(self) {
+        let query = format!("uploadId={}", urlencoding::encode(&self.upload_id));
+        let _ = self.send("DELETE", &query, Vec::new()).await;
+    }
+
+    /// Sends a signed request, returning the response's ETag and body.
+    async fn send(
+        &self,
+        method: &str,
+        query: &str,
+        body: Vec<u8>,
+    ) -> Result<(Option<String>, String), AppError> {
+        let (scheme, host, path) = self.credentials.locate(&self.url);
+        let amz_date = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        let headers = self
+            .credentials
+            .sign(method, &host, &path, query, &body, &amz_date);
+
+        let url = format!("{}://{}{}?{}", scheme, host, path, query);
+        let method = reqwest::Method::from_bytes(method.as_bytes()).expect("valid HTTP method");
+        let mut request = self.http.request(method, url).body(body);
+        for (name, value) in headers.iter().filter(|(name, _)| name != "host") {
+            request = request.header(name, value);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| AppError::Api(format!("{}: {}", self.url, e)))?;
+
+        let status = response.status();
+        let etag = response
+            .headers()
+            .get("etag")
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let text = response
+            .text()
+            .await
+            .map_err(|e| AppError::Api(format!("{}: {}", self.url, e)))?;
+        if !status.is_success() {
+            return Err(storage_error(status, &text, &self.url));
+        }
+        Ok((etag, text))
+    }
+}
+
+/// Uploads queued parts, completing the upload once the queue closes after
+/// a final empty part, and aborting it on the first failure.
+fn run_upload(
+    credentials: Credentials,
+    url: ObjectUrl,
+    parts: Receiver<Vec<u8>>,
+) -> Result<ObjectUrl, AppError> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    runtime.block_on(async {
+        let mut upload = MultipartUpload::start(credentials, url.clone()).await?;
+        let mut finished = false;
+        while let Ok(part) = parts.recv() {
+            if part.is_empty() {
+                finished = true;
+                break;
+            }
+            if let Err(e) = upload.upload_part(part).await {
+                upload.abort().await;
+                return Err(e);
+            }
+        }
+        if !finished {
+            upload.abort().await;
+            return Err(AppError::Api(format!("{}: upload abandoned", url)));
+        }
+        upload.complete().await?;
+        Ok(url)
+    })
+}
+
+/// Writes a gzipped stream to an object, uploading it a part at a time.
+///
+/// Call [`finish`](Self::finish) once everything is written; an upload
+/// dropped unfinished is aborted.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ddog::upload::{Credentials, ObjectUrl, UploadWriter};
+/// use std::io::Write;
+///
+/// # fn main() -> Result<(), ddog::AppError> {
+/// let url: ObjectUrl = "s3://exports/incident-1234/logs.ndjson.gz".parse().unwrap();
+/// let credentials = Credentials::from_env(url.provider)?;
+/// let mut writer = UploadWriter::new(url, credentials);
+/// writeln!(writer, "{{\"message\":\"hello\"}}")?;
+/// let url = writer.finish()?;
+/// println!("Uploaded {}", url);
+/// # Ok(())
+/// # }
+/// ```
+pub struct UploadWriter {
+    url: ObjectUrl,
+    encoder: Option<GzEncoder<Vec<u8>>>,
+    parts: Option<SyncSender<Vec<u8>>>,
+    uploader: Option<JoinHandle<Result<ObjectUrl, AppError>>>,
+}
+
+impl UploadWriter {
+    /// Starts uploading to `url`, which must name an object.
+    pub fn new(url: ObjectUrl, credentials: Credentials) -> Self {
+        let (parts, queue) = sync_channel(QUEUED_PARTS);
+        let target = url.clone();
+        let uploader = std::thread::spawn(move || run_upload(credentials, target, queue));
+        Self {
+            url,
+            encoder: Some(GzEncoder::new(Vec::new(), Compression::default())),
+            parts: Some(parts),
+            uploader: Some(uploader),
+        }
+    }
+
+    /// Queues a part for upload, failing if the upload has already failed.
+    fn queue(&mut self, part: Vec<u8>) -> io::Result<()> {
+        let sent = self
+            .parts
+            .as_ref()
+            .is_some_and(|parts| parts.send(part).is_ok());
+        if sent {
+            Ok(())
+        } else {
+            Err(io::Error::other(format!("upload to {} failed", self.url)))
+        }
+    }
+
+    /// Uploads the rest of the stream and completes the upload, returning
+    /// the uploaded object's URL.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error that failed the upload, mapped like API errors
+    /// (`AppError::Forbidden` for denied access, and so on).
+    pub fn finish(&mut self) -> Result<ObjectUrl, AppError> {
+        if let Some(encoder) = self.encoder.take() {
+            let rest = encoder.finish()?;
+            // An upload that failed reports its own error below
+            let _ = self.queue(rest).and_then(|_| self.queue(Vec::new()));
+        }
+        self.parts = None;
+        match self.uploader.take() {
+            Some(uploader) => uploader
+                .join()
+                .unwrap_or_else(|_| Err(AppError::Api(format!("{}: uploader panicked", self.url)))),
+            None => Ok(self.url.clone()),
+        }
+    }
+}
+
+impl Write for UploadWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let encoder = self
+            .encoder
+            .as_mut()
+            .ok_or_else(|| io::Error::other("upload already finished"))?;
+        encoder.write_all(buf)?;
+        if encoder.get_ref().len() >= PART_SIZE {
+            let part = std::mem::take(encoder.get_mut());
+            self.queue(part)?;
+        }
+        Ok(buf.len())
+    }
+
+    /// Does nothing: flushing the compressor would cost compression on every
+    /// record, and parts are uploaded as they fill.
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for UploadWriter {
+    fn drop(&mut self) {
+        // Closing the queue without a final part aborts the upload
+        self.encoder = None;
+        self.parts = None;
+        if let Some(uploader) = self.uploader.take() {
+            let _ = uploader.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn credentials(endpoint: Option<&str>) -> Credentials {
+        Credentials {
+            access_key: "AKIDEXAMPLE".to_string(),
+            secret_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+            session_token: None,
+            region: "us-east-1".to_string(),
+            endpoint: endpoint.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_parse_object_url() {
+        let url: ObjectUrl = "gs://exports/a b/".parse().unwrap();
+        assert_eq!(url.provider, StorageProvider::Gcs);
+        assert_eq!(url.bucket, "exports");
+        assert!(url.is_prefix());
+        assert!("s3://bucket".parse::<ObjectUrl>().unwrap().is_prefix());
+        assert!("s3:///key".parse::<ObjectUrl>().is_err());
+        assert!("out/logs.ndjson".parse::<ObjectUrl>().is_err());
+    }
+
+    #[test]
+    fn test_signing_key() {
+        // Example from the AWS Signature Version 4 documentation
+        let key = signing_key(
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "20120215",
+            "us-east-1",
+            "iam",
+        );
+        assert_eq!(
+            hex::encode(key),
+            "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d"
+        );
+    }
+
+    #[test]
+    fn test_locate_and_sign() {
+        let url: ObjectUrl = "s3://exports/run 1/logs.ndjson.gz".parse().unwrap();
+        let (_, host, path) = credentials(None).locate(&url);
+        assert_eq!(host, "exports.s3.us-east-1.amazonaws.com");
+        assert_eq!(path, "/run%201/logs.ndjson.gz");
+
+        let (scheme, host, path) = credentials(Some("http://localhost:9000")).locate(&url);
+        assert_eq!((scheme.as_str(), host.as_str()), ("http", "localhost:9000"));
+        assert_eq!(path, "/exports/run%201/logs.ndjson.gz");
+
+        let headers =
+            credentials(None).sign("POST", &host, &path, "uploads=", b"", "20240115T100000Z");
+        let authorization = &headers.last().unwrap().1;
+        assert!(authorization.starts_with(
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20240115/us-east-1/s3/aws4_request, \
+             SignedHeaders=host;x-amz-content-sha256;x-amz-date, Signature="
+        ));
+        // Signing is deterministic for the same request and time
+        let again =
+            credentials(None).sign("POST", &host, &path, "uploads=", b"", "20240115T100000Z");
+        assert_eq!(headers, again);
+    }
+
+    #[test]
+    fn test_multipart_xml() {
+        let response = "<InitiateMultipartUploadResult><Bucket>b</Bucket>\
+                        <UploadId>VXBsb2FkIElE</UploadId></InitiateMultipartUploadResult>";
+        assert_eq!(xml_element(response, "UploadId"), Some("VXBsb2FkIElE"));
+        assert_eq!(xml_element(response, "Key"), None);
+
+        assert_eq!(
+            complete_body(&["\"a\"".to_string(), "\"b\"".to_string()]),
+            "<CompleteMultipartUpload>\
+             <Part><PartNumber>1</PartNumber><ETag>\"a\"</ETag></Part>\
+             <Part><PartNumber>2</PartNumber><ETag>\"b\"</ETag></Part>\
+             </CompleteMultipartUpload>"
+        );
+
+        let url: ObjectUrl = "s3://b/k".parse().unwrap();
+        let denied = "<Error><Code>AccessDenied</Code><Message>Access Denied</Message></Error>";
+        let error = storage_error(reqwest::StatusCode::FORBIDDEN, denied, &url);
+        assert!(
+            matches!(error, AppError::Forbidden(ref m) if m == "s3://b/k: AccessDenied: Access Denied")
+        );
+    }
+}
@@ -0,0 +1,293 @@
+//! Terminal charts of timeseries for eyeballing spikes.
+//!
+//! A [`Plot`] holds one or more series on a shared time axis, built from
+//! aggregation [`VolumePoint`]s or metric points, and renders them as
+//! Unicode block charts: a multi-row histogram per series, or one sparkline
+//! row per series. Series longer than the chart width are downsampled by
+//! keeping each column's largest value, so short spikes stay visible.
+
+use chrono::{DateTime, TimeZone, Utc};
+
+use crate::client::{MetricPoint, VolumePoint};
+use crate::heatmap::Heatmap;
+use crate::timeseries::join_points;
+
+/// Maximum number of columns in a chart.
+pub const WIDTH: usize = 60;
+
+/// Number of rows in a histogram.
+pub const HEIGHT: usize = 8;
+
+/// Blocks for increasing height in eighths; the first marks an empty cell.
+const BLOCKS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// One named series of values, aligned with [`Plot::times`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Series {
+    /// Series name (e.g., a facet value or `avg:system.load.1{*}`)
+    pub label: String,
+
+    /// Value per time, `None` where the series has no point
+    pub values: Vec<Option<f64>>,
+}
+
+/// Series sharing a time axis.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Plot {
+    /// Time labels (`MM-DD HH:MM`), oldest first
+    pub times: Vec<String>,
+
+    /// Series to draw, in display order
+    pub series: Vec<Series>,
+}
+
+impl Plot {
+    /// Builds a plot from volume points, one series per facet value.
+    ///
+    /// Series are ordered as in a heatmap: earliest activity first.
+    pub fn from_volume(points: &[VolumePoint]) -> Self {
+        let heatmap = Heatmap::from_points("", points);
+        Self {
+            times: heatmap
+                .times
+                .iter()
+                .map(|t| {
+                    DateTime::parse_from_rfc3339(t)
+                        .map(|t| short_time(&t))
+                        .unwrap_or_else(|_| t.clone())
+                })
+                .collect(),
+            series: heatmap
+                .rows
+                .into_iter()
+                .map(|row| Series {
+                    label: row.group,
+                    values: row.counts.into_iter().map(Some).collect(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Builds a plot from metric points, one series per query and scope.
+    ///
+    /// Series are joined onto a common grid as by [`join_points`].
+    pub fn from_metrics(points: &[MetricPoint]) -> Self {
+        let rows = join_points(points);
+        let labels: Vec<String> = rows
+            .first()
+            .map(|row| {
+                row.values
+                    .iter()
+                    .map(|(column, _)| column.clone())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            times: rows
+                .iter()
+                .map(|row| {
+                    Utc.timestamp_opt(row.timestamp, 0)
+                        .single()
+                        .map(|t| short_time(&t))
+                        .unwrap_or_else(|| row.timestamp.to_string())
+                })
+                .collect(),
+            series: labels
+                .into_iter()
+                .enumerate()
+                .map(|(index, label)| Series {
+                    label,
+                    values: rows.iter().map(|row| row.values[index].1).collect(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Renders each series as a block histogram, [`HEIGHT`] rows tall.
+    ///
+    /// Bars are scaled from zero to the series' largest value; each chart is
+    /// headed by its label and maximum and followed by the time axis.
+    pub fn render_histogram(&self) -> String {
+        let mut out = String::new();
+        for series in &self.series {
+            let columns = downsample(&series.values, WIDTH);
+            let max = columns.iter().flatten().copied().fold(0.0, f64::max);
+            out.push_str(&format!("{}  max {}\n", series.label, format_value(max)));
+
+            let levels: Vec<usize> = columns
+                .iter()
+                .map(|value| match value {
+                    Some(v) if *v > 0.0 && max > 0.0 => {
+                        ((v / max * (HEIGHT * 8) as f64).round() as usize).max(1)
+                    }
+                    _ => 0,
+                })
+                .collect();
+            for row in (0..HEIGHT).rev() {
+                let line: String = levels
+                    .iter()
+                    .map(|level| BLOCKS[level.saturating_sub(row * 8).min(8)])
+                    .collect();
+                out.push_str(line.trim_end());
+                out.push('\n');
+            }
+            out.push_str(&self.axis(columns.len()));
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Renders one sparkline row per series, with its range.
+    ///
+    /// Each sparkline is scaled between its own smallest value (or zero, if
+    /// lower) and largest value; gaps are left blank.
+    pub fn render_sparklines(&self) -> String {
+        let label_width = self
+            .series
+            .iter()
+            .map(|s| s.label.chars().count())
+            .max()
+            .unwrap_or_default();
+        let columns = self.times.len().min(WIDTH);
+
+        let mut out = format!("{:label_width$}  {}\n", "", self.axis(columns));
+        for series in &self.series {
+            let values = downsample(&series.values, WIDTH);
+            let (min, max) = values
+                .iter()
+                .flatten()
+                .fold((0.0_f64, f64::MIN), |(lo, hi), v| (lo.min(*v), hi.max(*v)));
+            let line: String = values
+                .iter()
+                .map(|value| match value {
+                    Some(v) if max > min => {
+                        BLOCKS[1 + ((v - min) / (max - min) * 7.0).round() as usize]
+                    }
+                    Some(_) => BLOCKS[1],
+                    None => BLOCKS[0],
+                })
+                .collect();
+            out.push_str(&format!(
+                "{:label_width$}  {:columns$}  {}..{}\n",
+                series.label,
+                line,
+                format_value(min),
+                format_value(max.max(min))
+            ));
+        }
+        out
+    }
+
+    /// First and last time labels spread across `columns` characters.
+    fn axis(&self, columns: usize) -> String {
+        let start = self.times.first().map(String::as_str).unwrap_or_default();
+        let end = self.times.last().map(String::as_str).unwrap_or_default();
+        if self.times.len() < 2 {
+            return start.to_string();
+        }
+        let width = columns.max(start.len() + end.len() + 1);
+        format!("{}{:>pad$}", start, end, pad = width - start.len())
+    }
+}
+
+/// Shrinks values to at most `width` columns, keeping each column's largest value.
+fn downsample(values: &[Option<f64>], width: usize) -> Vec<Option<f64>> {
+    if values.len() <= width {
+        return values.to_vec();
+    }
+    (0..width)
+        .map(|column| {
+            let start = column * values.len() / width;
+            let end = (column + 1) * values.len() / width;
+            values[start..end]
+                .iter()
+                .flatten()
+                .copied()
+                .reduce(f64::max)
+        })
+        .collect()
+}
+
+fn short_time<Z: TimeZone>(time: &DateTime<Z>) -> String
+where
+    Z::Offset: std::fmt::Display,
+{
+    time.format("%m-%d %H:%M").to_string()
+}
+
+/// Formats a value without trailing zeros for whole numbers.
+fn format_value(value: f64) -> String {
+    if value.fract() == 0.0 {
+        format!("{}", value)
+    } else {
+        format!("{:.2}", value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plot(values: Vec<Option<f64>>) -> Plot {
+        Plot {
+            times: (0..values.len())
+                .map(|i| format!("01-15 10:{:02}", i))
+                .collect(),
+            series: vec![Series {
+                label: "api".to_string(),
+                values,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_render_sparklines() {
+        let plot = plot(vec![Some(0.0), Some(4.0), None, Some(8.0)]);
+        assert_eq!(
+            plot.render_sparklines(),
+            "     01-15 10:00 01-15 10:03\napi  ▁▅ █  0..8\n"
+        );
+    }
+
+    #[test]
+    fn test_render_histogram() {
+        let rendered = plot(vec![Some(1.0), Some(8.0), Some(4.0)]).render_histogram();
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines.len(), HEIGHT + 2);
+        assert_eq!(lines[0], "api  max 8");
+        assert_eq!(lines[1], " █");
+        assert_eq!(lines[5], " ██");
+        assert_eq!(lines[HEIGHT], "███");
+        assert_eq!(lines[HEIGHT + 1], "01-15 10:00 01-15 10:02");
+    }
+
+    #[test]
+    fn test_downsample_keeps_spikes() {
+        let values: Vec<Option<f64>> = (0..10)
+            .map(|i| Some(if i == 7 { 100.0 } else { 1.0 }))
+            .collect();
+        assert_eq!(
+            downsample(&values, 5),
+            vec![Some(1.0), Some(1.0), Some(1.0), Some(100.0), Some(1.0)]
+        );
+        assert_eq!(downsample(&[None, None], 1), vec![None]);
+    }
+
+    #[test]
+    fn test_from_volume() {
+        let point = |timestamp: &str, group: &str, count: f64| VolumePoint {
+            timestamp: timestamp.to_string(),
+            group: group.to_string(),
+            count,
+        };
+        let plot = Plot::from_volume(&[
+            point("2024-01-15T10:00:00Z", "api", 3.0),
+            point("2024-01-15T10:10:00Z", "api", 5.0),
+            point("2024-01-15T10:10:00Z", "web", 1.0),
+        ]);
+        assert_eq!(plot.times, vec!["01-15 10:00", "01-15 10:10"]);
+        assert_eq!(plot.series[0].label, "api");
+        assert_eq!(plot.series[1].values, vec![Some(0.0), Some(1.0)]);
+    }
+}
@@ -0,0 +1,279 @@
+//! Change detection for `--watch`.
+//!
+//! A watched search re-runs on an interval and emits only events it hasn't
+//! emitted before, keyed by event ID. After the first run, each run starts
+//! [`LATE_ARRIVAL`] before the newest event seen (or at the start of the time
+//! range, if later), so late-indexed events are still caught without paging
+//! through the whole range again. A watched aggregation emits the buckets
+//! whose count is new or has changed since the previous run.
+//!
+//! Each run that emits something after the first is a trigger, summarized on
+//! stderr in one line, optionally with a terminal bell.
+//!
+//! A watched monitor status emits a transition whenever the polled state
+//! differs from the previous poll ([`StateTracker`]).
+
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::client::VolumePoint;
+use crate::patterns::PatternCounter;
+use crate::records::Status;
+use crate::time::duration_seconds;
+
+/// How far before the newest event seen a watched search starts again.
+pub const LATE_ARRIVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Shortest accepted `--watch` interval, to stay clear of API rate limits.
+pub const MIN_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Parses a `--watch` interval such as `30s` or `5m`.
+///
+/// # Examples
+///
+/// ```
+/// use ddog::watch::parse_watch_interval;
+/// use std::time::Duration;
+///
+/// assert_eq!(parse_watch_interval("30s").unwrap(), Duration::from_secs(30));
+/// assert!(parse_watch_interval("1s").is_err());
+/// ```
+///
+/// # Errors
+///
+/// Returns an error message for an invalid duration or one shorter than
+/// [`MIN_INTERVAL`].
+pub fn parse_watch_interval(interval: &str) -> Result<Duration, String> {
+    let seconds = duration_seconds(interval)?;
+    let interval = Duration::from_secs(seconds.max(0) as u64);
+    if interval < MIN_INTERVAL {
+        return Err(format!(
+            "watch interval must be at least {}s",
+            MIN_INTERVAL.as_secs()
+        ));
+    }
+    Ok(interval)
+}
+
+/// Whether a log status is severe enough to ring the bell harder.
+pub fn is_severe(status: &str) -> bool {
+    Status::parse(status).is_severe()
+}
+
+/// Event IDs already emitted by a watched search, with their timestamps.
+#[derive(Debug, Default)]
+pub struct SeenEvents {
+    seen: HashMap<String, i64>,
+    latest_ms: Option<i64>,
+}
+
+impl SeenEvents {
+    /// Records an event, returning whether it is new.
+    ///
+    /// Events without an ID can't be told apart, so they are always new.
+    pub fn insert(&mut self, id: Option<&str>, timestamp_ms: Option<i64>) -> bool {
+        if let Some(timestamp) = timestamp_ms {
+            self.latest_ms = Some(self.latest_ms.map_or(timestamp, |l| l.max(timestamp)));
+        }
+        match id {
+            Some(id) => self
+                .seen
+                .insert(id.to_string(), timestamp_ms.unwrap_or(i64::MAX))
+                .is_none(),
+            None => true,
+        }
+    }
+
+    /// Start of the next run: [`LATE_ARRIVAL`] before the newest event seen,
+    /// but not before `range_from_ms`.
+    ///
+    /// Forgets events older than that start, since the next run can't return them.
+    pub fn next_from(&mut self, range_from_ms: i64) -> i64 {
+        let from = match self.latest_ms {
+            Some(latest) => range_from_ms.max(latest - LATE_ARRIVAL.as_millis() as i64),
+            None => range_from_ms,
+        };
+        self.seen.retain(|_, timestamp| *timestamp >= from);
+        from
+    }
+
+    /// Number of event IDs remembered.
+    pub fn len(&self) -> usize {
+        self.seen.len()
+    }
+
+    /// Whether no event IDs are remembered.
+    pub fn is_empty(&self) -> bool {
+        self.seen.is_empty()
+    }
+}
+
+/// Bucket counts of the previous run of a watched aggregation.
+#[derive(Debug, Default)]
+pub struct BucketCounts {
+    counts: HashMap<(String, String), f64>,
+}
+
+impl BucketCounts {
+    /// Replaces the counts with `points`, returning the new or changed ones.
+    pub fn update(&mut self, points: Vec<VolumePoint>) -> Vec<VolumePoint> {
+        let previous = std::mem::take(&mut self.counts);
+        let mut changed = Vec::new();
+        for point in points {
+            let key = (point.timestamp.clone(), point.group.clone());
+            self.counts.insert(key.clone(), point.count);
+            if previous.get(&key) != Some(&point.count) {
+                changed.push(point);
+            }
+        }
+        changed
+    }
+}
+
+/// A change of a polled state, such as a monitor going from OK to Alert.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StateChange {
+    /// Previous state; `None` for the first poll
+    pub from: Option<String>,
+
+    /// New state
+    pub to: String,
+}
+
+/// The last state seen by a watched status poll.
+#[derive(Debug, Default)]
+pub struct StateTracker {
+    state: Option<String>,
+}
+
+impl StateTracker {
+    /// Records a polled state, returning the change if it differs from the
+    /// previous poll. The first poll is always a change, from `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ddog::watch::StateTracker;
+    ///
+    /// let mut tracker = StateTracker::default();
+    /// assert_eq!(tracker.update("OK").unwrap().from, None);
+    /// assert!(tracker.update("OK").is_none());
+    /// assert_eq!(tracker.update("Alert").unwrap().from.as_deref(), Some("OK"));
+    /// ```
+    pub fn update(&mut self, state: &str) -> Option<StateChange> {
+        if self.state.as_deref() == Some(state) {
+            return None;
+        }
+        Some(StateChange {
+            from: self.state.replace(state.to_string()),
+            to: state.to_string(),
+        })
+    }
+}
+
+/// What one run of a watched command emitted, for its stderr summary.
+#[derive(Debug, Default)]
+pub struct Trigger {
+    count: usize,
+    patterns: PatternCounter,
+    severe: bool,
+}
+
+impl Trigger {
+    /// Counts an emitted event with its message (or resource) and severity.
+    pub fn add(&mut self, message: Option<&str>, severe: bool) {
+        self.count += 1;
+        if let Some(message) = message {
+            self.patterns.add(message);
+        }
+        self.severe |= severe;
+    }
+
+    /// Number of events emitted.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Whether any event emitted was severe.
+    pub fn is_severe(&self) -> bool {
+        self.severe
+    }
+
+    /// One-line summary, such as
+    /// `[10:00:30] 5 new logs, top pattern (3): Timeout after <*> calling <*>`
+    /// for the noun `new logs`.
+    pub fn summary(&self, noun: &str, at: DateTime<Local>) -> String {
+        let mut line = format!("[{}] {} {}", at.format("%H:%M:%S"), self.count, noun);
+        if let Some(top) = self.patterns.top() {
+            line.push_str(&format!(", top pattern ({}): {}", top.count, top.pattern));
+        }
+        line
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_seen_events() {
+        let mut seen = SeenEvents::default();
+        assert!(seen.insert(Some("a"), Some(1_000_000)));
+        assert!(seen.insert(Some("b"), Some(2_000_000)));
+        assert!(!seen.insert(Some("a"), Some(1_000_000)));
+        assert!(seen.insert(None, None));
+        assert!(seen.insert(None, None));
+
+        // 2_000_000 - 300_000 = 1_700_000 forgets "a"
+        assert_eq!(seen.next_from(0), 1_700_000);
+        assert_eq!(seen.len(), 1);
+        assert!(seen.insert(Some("a"), Some(1_000_000)));
+
+        // The time range start wins when later
+        assert_eq!(seen.next_from(1_900_000), 1_900_000);
+    }
+
+    #[test]
+    fn test_bucket_counts() {
+        let point = |group: &str, count: f64| VolumePoint {
+            timestamp: "2024-01-15T10:00:00Z".to_string(),
+            group: group.to_string(),
+            count,
+        };
+        let mut counts = BucketCounts::default();
+        assert_eq!(
+            counts
+                .update(vec![point("api", 1.0), point("web", 2.0)])
+                .len(),
+            2
+        );
+        assert_eq!(
+            counts.update(vec![point("api", 1.0), point("web", 3.0)]),
+            vec![point("web", 3.0)]
+        );
+        assert!(
+            counts
+                .update(vec![point("api", 1.0), point("web", 3.0)])
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_trigger_summary() {
+        let mut trigger = Trigger::default();
+        trigger.add(Some("Timeout after 30ms calling 10.0.0.1"), false);
+        trigger.add(Some("Timeout after 45ms calling 10.0.0.2"), true);
+        trigger.add(Some("connection reset"), false);
+        let at = Local.with_ymd_and_hms(2024, 1, 15, 10, 0, 30).unwrap();
+        assert_eq!(
+            trigger.summary("new logs", at),
+            "[10:00:30] 3 new logs, top pattern (2): Timeout after <*> calling <*>"
+        );
+        assert!(trigger.is_severe());
+        assert!(is_severe("CRITICAL"));
+        assert!(!is_severe("warn"));
+    }
+}
@@ -0,0 +1,243 @@
+//! Project-local workspace configuration (`.ddog.toml`).
+//!
+//! A workspace file lives in a repository checkout and scopes every search run
+//! from inside it: it can select a profile, add default `service`/`env` filters,
+//! and define saved queries referenced as `@name`.
+//!
+//! ```toml
+//! profile = "payments-prod"
+//!
+//! [defaults]
+//! service = "checkout"
+//! env = "prod"
+//!
+//! [queries]
+//! errors = "status:error"
+//! slow = "@duration:>1s"
+//! ```
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::error::AppError;
+use crate::query;
+
+/// Name of the workspace file searched for in the current directory and its parents.
+pub const WORKSPACE_FILE: &str = ".ddog.toml";
+
+/// Default filters applied to logs and spans queries run inside the workspace.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkspaceDefaults {
+    /// Service name added as `service:<name>` unless the query already filters on service
+    pub service: Option<String>,
+
+    /// Environment added as `env:<name>` unless the query already filters on env
+    pub env: Option<String>,
+}
+
+/// A parsed `.ddog.toml` workspace file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Workspace {
+    /// Profile name to use from the user configuration
+    pub profile: Option<String>,
+
+    /// Default query filters
+    #[serde(default)]
+    pub defaults: WorkspaceDefaults,
+
+    /// Saved queries, referenced on the command line as `@name`
+    #[serde(default)]
+    pub queries: BTreeMap<String, String>,
+
+    /// Path of the file this workspace was loaded from
+    #[serde(skip)]
+    pub path: PathBuf,
+}
+
+impl Workspace {
+    /// Walks up from `start` looking for a `.ddog.toml` file.
+    ///
+    /// Returns `Ok(None)` when no workspace file exists in `start` or any of its parents.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Config` if a workspace file is found but cannot be parsed.
+    pub fn discover(start: &Path) -> Result<Option<Self>, AppError> {
+        for dir in start.ancestors() {
+            let candidate = dir.join(WORKSPACE_FILE);
+            if candidate.is_file() {
+                return Self::load(&candidate).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Loads a workspace from an explicit file path.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Config` if the file cannot be read or parsed.
+    pub fn load(path: &Path) -> Result<Self, AppError> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| AppError::Config(format!("Failed to read {}: {}", path.display(), e)))?;
+        let mut workspace: Workspace = toml::from_str(&contents)
+            .map_err(|e| AppError::Config(format!("Invalid {}: {}", path.display(), e)))?;
+        workspace.path = path.to_path_buf();
+        Ok(workspace)
+    }
+
+    /// Expands a `@name` reference to the saved query it names.
+    ///
+    /// Queries that don't start with `@` are returned unchanged. Note that
+    /// attribute searches like `@http.status_code:500` are left alone because
+    /// they don't match a saved query name.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::InvalidQuery` if the query looks like a bare `@name`
+    /// reference but no saved query with that name exists.
+    pub fn expand_query(&self, query: &str) -> Result<String, AppError> {
+        let Some(name) = saved_query_name(query) else {
+            return Ok(query.to_string());
+        };
+
+        match self.queries.get(name) {
+            Some(saved) => Ok(saved.clone()),
+            None => Err(AppError::InvalidQuery(format!(
+                "Unknown saved query '@{}' (not defined in {})",
+                name,
+                self.path.display()
+            ))),
+        }
+    }
+
+    /// Adds the workspace's default filters to a logs or spans query.
+    ///
+    /// A default is skipped when the query already mentions the same facet,
+    /// so an explicit `service:other` always wins over the workspace default.
+    pub fn scope_query(&self, query: &str) -> String {
+        let mut filters = Vec::new();
+        if let Some(service) = &self.defaults.service {
+            filters.push(("service", service.as_str()));
+        }
+        if let Some(env) = &self.defaults.env {
+            filters.push(("env", env.as_str()));
+        }
+        query::scope(query, &filters)
+    }
+}
+
+/// Returns the saved query name if `query` is a bare `@name` reference.
+///
+/// Names may contain alphanumerics, `-` and `_`; anything else (such as the
+/// `.` and `:` in `@http.status_code:500`) means the query is a regular search.
+pub fn saved_query_name(query: &str) -> Option<&str> {
+    let name = query.trim().strip_prefix('@')?;
+    let is_name = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    is_name.then_some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace(contents: &str) -> Workspace {
+        toml::from_str(contents).unwrap()
+    }
+
+    #[test]
+    fn test_parse_full_workspace() {
+        let ws = workspace(
+            r#"
+            profile = "prod"
+
+            [defaults]
+            service = "checkout"
+            env = "prod"
+
+            [queries]
+            errors = "status:error"
+            "#,
+        );
+        assert_eq!(ws.profile.as_deref(), Some("prod"));
+        assert_eq!(ws.defaults.service.as_deref(), Some("checkout"));
+        assert_eq!(ws.defaults.env.as_deref(), Some("prod"));
+        assert_eq!(ws.queries.get("errors").unwrap(), "status:error");
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_keys() {
+        let result: Result<Workspace, _> = toml::from_str("servce = \"typo\"");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_scope_query_adds_defaults() {
+        let ws = workspace("[defaults]\nservice = \"checkout\"\nenv = \"prod\"");
+        assert_eq!(
+            ws.scope_query("status:error"),
+            "service:checkout env:prod (status:error)"
+        );
+        assert_eq!(ws.scope_query("*"), "service:checkout env:prod");
+    }
+
+    #[test]
+    fn test_scope_query_respects_explicit_facets() {
+        let ws = workspace("[defaults]\nservice = \"checkout\"\nenv = \"prod\"");
+        assert_eq!(
+            ws.scope_query("service:billing status:error"),
+            "env:prod (service:billing status:error)"
+        );
+        assert_eq!(
+            ws.scope_query("(service:a OR service:b) env:staging"),
+            "(service:a OR service:b) env:staging"
+        );
+        assert_eq!(
+            ws.scope_query("-service:checkout"),
+            "env:prod (-service:checkout)"
+        );
+    }
+
+    #[test]
+    fn test_expand_query() {
+        let ws = workspace("[queries]\nerrors = \"status:error\"");
+        assert_eq!(ws.expand_query("@errors").unwrap(), "status:error");
+        assert_eq!(
+            ws.expand_query("@http.status_code:500").unwrap(),
+            "@http.status_code:500"
+        );
+        assert!(matches!(
+            ws.expand_query("@missing"),
+            Err(AppError::InvalidQuery(_))
+        ));
+    }
+
+    #[test]
+    fn test_saved_query_name() {
+        assert_eq!(saved_query_name("@errors-prod"), Some("errors-prod"));
+        assert_eq!(saved_query_name(" @slow_v2 "), Some("slow_v2"));
+        assert_eq!(saved_query_name("@duration:>1s"), None);
+        assert_eq!(saved_query_name("service:api"), None);
+        assert_eq!(saved_query_name("@"), None);
+    }
+
+    #[test]
+    fn test_discover_walks_up() {
+        let root = std::env::temp_dir().join(format!("ddog-ws-{}", std::process::id()));
+        let nested = root.join("a").join("b");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(root.join(WORKSPACE_FILE), "profile = \"eu\"").unwrap();
+
+        let ws = Workspace::discover(&nested).unwrap().unwrap();
+        assert_eq!(ws.profile.as_deref(), Some("eu"));
+        assert_eq!(ws.path, root.join(WORKSPACE_FILE));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}